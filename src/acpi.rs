@@ -0,0 +1,657 @@
+//! Walks the ACPI tables that the firmware publishes in the configuration table.
+//!
+//! The RSDP points to the XSDT, or the RSDT on ACPI 1.0 systems, which lists the addresses of all
+//! other tables except the FACS and DSDT, which are referenced by the FADT. Every table starts
+//! with an `SdtHeader` and the bytes of a table, including the header, sum up to zero.
+//!
+//! The MADT describes the interrupt controllers and processors, which is needed to start the
+//! application processors. The MCFG describes the memory mapped PCI Express configuration space
+//! and is only decoded with the `pci` feature.
+
+use core::{mem::size_of, slice};
+
+use crate::{
+    guid::{ACPI_20_TABLE_GUID, ACPI_TABLE_GUID},
+    status::Error,
+    system::SystemTable,
+};
+
+/// The signature of the RSDP.
+pub const RSDP_SIGNATURE: &[u8; 8] = b"RSD PTR ";
+/// The signature of the XSDT.
+pub const XSDT_SIGNATURE: &[u8; 4] = b"XSDT";
+/// The signature of the RSDT.
+pub const RSDT_SIGNATURE: &[u8; 4] = b"RSDT";
+/// The signature of the MADT.
+pub const MADT_SIGNATURE: &[u8; 4] = b"APIC";
+/// The signature of the MCFG.
+pub const MCFG_SIGNATURE: &[u8; 4] = b"MCFG";
+/// The signature of the FADT.
+pub const FADT_SIGNATURE: &[u8; 4] = b"FACP";
+
+/// The size of the RSDP in ACPI 1.0, which is covered by its checksum.
+const RSDP_V1_SIZE: usize = 20;
+
+/// The root system description pointer.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Rsdp {
+    /// `RSDP_SIGNATURE`.
+    pub Signature: [u8; 8],
+    /// Makes the bytes of the ACPI 1.0 part sum up to zero.
+    pub Checksum: u8,
+    /// An OEM supplied string that identifies the OEM.
+    pub OemId: [u8; 6],
+    /// 0 for ACPI 1.0 and 2 for ACPI 2.0 and later.
+    pub Revision: u8,
+    /// The physical address of the RSDT.
+    pub RsdtAddress: u32,
+    /// The length of the whole structure, since ACPI 2.0.
+    pub Length: u32,
+    /// The physical address of the XSDT, since ACPI 2.0.
+    pub XsdtAddress: u64,
+    /// Makes the bytes of the whole structure sum up to zero, since ACPI 2.0.
+    pub ExtendedChecksum: u8,
+    /// Reserved.
+    pub Reserved: [u8; 3],
+}
+
+/// The header of every system description table.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SdtHeader {
+    /// The signature that identifies the table.
+    pub Signature: [u8; 4],
+    /// The length of the table including the header.
+    pub Length: u32,
+    /// The revision of the structure corresponding to the signature field for this table.
+    pub Revision: u8,
+    /// Makes the bytes of the table sum up to zero.
+    pub Checksum: u8,
+    /// An OEM supplied string that identifies the OEM.
+    pub OemId: [u8; 6],
+    /// An OEM supplied string that the OEM uses to identify the particular data table.
+    pub OemTableId: [u8; 8],
+    /// An OEM supplied revision number, where larger numbers are assumed to be newer revisions.
+    pub OemRevision: u32,
+    /// The vendor ID of the utility that created the table.
+    pub CreatorId: u32,
+    /// The revision of the utility that created the table.
+    pub CreatorRevision: u32,
+}
+
+/// Returns whether the bytes sum up to zero.
+fn checksum_valid(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) == 0
+}
+
+/// Returns the little endian value of `size` bytes at `offset`, if `bytes` is long enough.
+fn read_le(bytes: &[u8], offset: usize, size: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset + size)?;
+
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |value, &byte| value << 8 | u64::from(byte)),
+    )
+}
+
+/// A system description table.
+///
+/// A table can only be created with `from_address`, which validates it, so its bytes can be
+/// accessed safely afterwards.
+#[derive(Clone, Copy, Debug)]
+pub struct AcpiTable {
+    /// The signature that identifies the table.
+    pub signature: [u8; 4],
+    /// The revision of the table.
+    pub revision: u8,
+    /// The physical address of the table.
+    address: usize,
+    /// The length of the table including the header.
+    length: usize,
+}
+
+impl AcpiTable {
+    /// Validates the table at `address`.
+    ///
+    /// Returns `Error::CrcError` if the checksum is wrong and `Error::BadBufferSize` if the
+    /// length is smaller than the header.
+    ///
+    /// # Safety
+    /// `address` must point to a system description table that stays mapped and unchanged for
+    /// the rest of the program, since the table is accessed through `'static` references.
+    pub unsafe fn from_address(address: usize) -> Result<AcpiTable, Error> {
+        let header = &*(address as *const SdtHeader);
+        let length = header.Length as usize;
+
+        if length < size_of::<SdtHeader>() {
+            return Err(Error::BadBufferSize);
+        }
+        if !checksum_valid(slice::from_raw_parts(address as *const u8, length)) {
+            return Err(Error::CrcError);
+        }
+
+        Ok(AcpiTable {
+            signature: header.Signature,
+            revision: header.Revision,
+            address,
+            length,
+        })
+    }
+
+    /// Returns the physical address of the table.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the length of the table including the header.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Returns the header of the table.
+    pub fn header(&self) -> &'static SdtHeader {
+        // This is safe, because the table was validated by `from_address`.
+        unsafe { &*(self.address as *const SdtHeader) }
+    }
+
+    /// Returns the bytes of the table, including the header.
+    pub fn bytes(&self) -> &'static [u8] {
+        // This is safe, because the table was validated by `from_address`.
+        unsafe { slice::from_raw_parts(self.address as *const u8, self.length) }
+    }
+
+    /// Returns the bytes of the table after the header.
+    pub fn data(&self) -> &'static [u8] {
+        &self.bytes()[size_of::<SdtHeader>()..]
+    }
+
+    /// Returns whether the table has the given signature.
+    pub fn is(&self, signature: &[u8; 4]) -> bool {
+        self.signature == *signature
+    }
+
+    /// Decodes the table as a MADT.
+    ///
+    /// Returns `Error::Unsupported` if the table is not a MADT.
+    pub fn madt(&self) -> Result<Madt, Error> {
+        if !self.is(MADT_SIGNATURE) {
+            return Err(Error::Unsupported);
+        }
+
+        let data = self.data();
+        Ok(Madt {
+            local_apic_address: read_le(data, 0, 4).ok_or(Error::BadBufferSize)? as u32,
+            flags: read_le(data, 4, 4).ok_or(Error::BadBufferSize)? as u32,
+            entries: &data[8..],
+        })
+    }
+
+    /// Decodes the table as a MCFG.
+    ///
+    /// Returns `Error::Unsupported` if the table is not a MCFG.
+    #[cfg(feature = "pci")]
+    pub fn mcfg(&self) -> Result<Mcfg, Error> {
+        if !self.is(MCFG_SIGNATURE) {
+            return Err(Error::Unsupported);
+        }
+
+        // The entries follow eight reserved bytes.
+        let entries = self.data().get(8..).ok_or(Error::BadBufferSize)?;
+
+        Ok(Mcfg { entries })
+    }
+
+    /// Decodes the table as a FADT.
+    ///
+    /// Returns `Error::Unsupported` if the table is not a FADT.
+    pub fn fadt(&self) -> Result<Fadt, Error> {
+        if !self.is(FADT_SIGNATURE) {
+            return Err(Error::Unsupported);
+        }
+
+        let bytes = self.bytes();
+        let read = |offset: usize, size: usize| read_le(bytes, offset, size);
+        // The 64 bit addresses are used if they are present and set.
+        let address = |offset: usize, extended_offset: usize| match read(extended_offset, 8) {
+            Some(address) if address != 0 => Some(address),
+            _ => read(offset, 4).filter(|&address| address != 0),
+        };
+
+        Ok(Fadt {
+            firmware_ctrl: address(36, 132),
+            dsdt: address(40, 140),
+            preferred_pm_profile: read(45, 1).unwrap_or(0) as u8,
+            sci_interrupt: read(46, 2).unwrap_or(0) as u16,
+            smi_command: read(48, 4).unwrap_or(0) as u32,
+            iapc_boot_arch: read(109, 2).unwrap_or(0) as u16,
+            flags: read(112, 4).unwrap_or(0) as u32,
+            reset_register: bytes.get(116..128).map(GenericAddress::parse),
+            reset_value: read(128, 1).map(|value| value as u8),
+            arm_boot_arch: read(129, 2).unwrap_or(0) as u16,
+            minor_version: read(131, 1).unwrap_or(0) as u8,
+        })
+    }
+}
+
+/// The ACPI tables of the system.
+#[derive(Clone, Copy, Debug)]
+pub struct Acpi {
+    /// The RSDP.
+    rsdp: &'static Rsdp,
+    /// The XSDT, or the RSDT if there is no XSDT.
+    root: AcpiTable,
+}
+
+impl Acpi {
+    /// Validates the RSDP at `address` and the XSDT or RSDT it points to.
+    ///
+    /// Returns `Error::Unsupported` if the signature is wrong and `Error::CrcError` if a
+    /// checksum is wrong.
+    ///
+    /// # Safety
+    /// `address` must point to an RSDP that points to valid tables.
+    pub unsafe fn from_rsdp(address: usize) -> Result<Acpi, Error> {
+        let rsdp = &*(address as *const Rsdp);
+
+        if rsdp.Signature != *RSDP_SIGNATURE {
+            return Err(Error::Unsupported);
+        }
+        if !checksum_valid(slice::from_raw_parts(address as *const u8, RSDP_V1_SIZE)) {
+            return Err(Error::CrcError);
+        }
+
+        let root = if rsdp.Revision >= 2 && rsdp.XsdtAddress != 0 {
+            let length = rsdp.Length as usize;
+            if length < size_of::<Rsdp>() {
+                return Err(Error::BadBufferSize);
+            }
+            if !checksum_valid(slice::from_raw_parts(address as *const u8, length)) {
+                return Err(Error::CrcError);
+            }
+
+            AcpiTable::from_address(rsdp.XsdtAddress as usize)?
+        } else {
+            AcpiTable::from_address(rsdp.RsdtAddress as usize)?
+        };
+
+        if !root.is(XSDT_SIGNATURE) && !root.is(RSDT_SIGNATURE) {
+            return Err(Error::Unsupported);
+        }
+
+        Ok(Acpi { rsdp, root })
+    }
+
+    /// Finds the RSDP in the configuration table of the system table.
+    ///
+    /// The ACPI 2.0 table is preferred over the ACPI 1.0 table. Returns `Error::NotFound` if
+    /// there is neither.
+    pub fn from_system_table(system_table: &SystemTable) -> Result<Acpi, Error> {
+        let table = system_table
+            .find_config_table(&ACPI_20_TABLE_GUID)
+            .or_else(|| system_table.find_config_table(&ACPI_TABLE_GUID))
+            .ok_or(Error::NotFound)?;
+
+        // This is safe under the assumption that the firmware supplied valid tables.
+        unsafe { Acpi::from_rsdp(table.VendorTable) }
+    }
+
+    /// Returns the RSDP.
+    pub fn rsdp(&self) -> &'static Rsdp {
+        self.rsdp
+    }
+
+    /// Returns the address of the RSDP, which is passed to operating systems.
+    pub fn rsdp_address(&self) -> usize {
+        self.rsdp as *const Rsdp as usize
+    }
+
+    /// Returns the XSDT, or the RSDT if there is no XSDT.
+    pub fn root(&self) -> AcpiTable {
+        self.root
+    }
+
+    /// Returns an iterator over the tables listed in the XSDT or RSDT.
+    ///
+    /// Each table is validated before it is returned.
+    pub fn tables(&self) -> AcpiTables {
+        AcpiTables {
+            entries: self.root.data(),
+            entry_size: if self.root.is(XSDT_SIGNATURE) { 8 } else { 4 },
+        }
+    }
+
+    /// Returns the first valid table with the given signature.
+    ///
+    /// Returns `Error::NotFound` if there is no such table.
+    pub fn find(&self, signature: &[u8; 4]) -> Result<AcpiTable, Error> {
+        self.tables()
+            .filter_map(Result::ok)
+            .find(|table| table.is(signature))
+            .ok_or(Error::NotFound)
+    }
+
+    /// Returns the MADT.
+    pub fn madt(&self) -> Result<Madt, Error> {
+        self.find(MADT_SIGNATURE)?.madt()
+    }
+
+    /// Returns the MCFG.
+    #[cfg(feature = "pci")]
+    pub fn mcfg(&self) -> Result<Mcfg, Error> {
+        self.find(MCFG_SIGNATURE)?.mcfg()
+    }
+
+    /// Returns the FADT.
+    pub fn fadt(&self) -> Result<Fadt, Error> {
+        self.find(FADT_SIGNATURE)?.fadt()
+    }
+}
+
+/// The iterator returned by `Acpi::tables`.
+pub struct AcpiTables {
+    /// The remaining entries of the XSDT or RSDT.
+    entries: &'static [u8],
+    /// The size of an entry, 8 for the XSDT and 4 for the RSDT.
+    entry_size: usize,
+}
+
+impl Iterator for AcpiTables {
+    type Item = Result<AcpiTable, Error>;
+
+    fn next(&mut self) -> Option<Result<AcpiTable, Error>> {
+        let address = read_le(self.entries, 0, self.entry_size)?;
+        self.entries = &self.entries[self.entry_size..];
+
+        if address == 0 {
+            return Some(Err(Error::NotFound));
+        }
+
+        // This is safe under the assumption that the firmware supplied valid tables.
+        Some(unsafe { AcpiTable::from_address(address as usize) })
+    }
+}
+
+/// A generic address structure, which describes a register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GenericAddress {
+    /// The address space, such as 0 for system memory and 1 for system I/O.
+    pub address_space: u8,
+    /// The size in bits of the given register.
+    pub bit_width: u8,
+    /// The bit offset of the given register at the given address.
+    pub bit_offset: u8,
+    /// The access size, 1 for byte, 2 for word, 3 for dword and 4 for qword access, or 0 if it
+    /// is undefined.
+    pub access_size: u8,
+    /// The 64 bit address of the data structure or register in the given address space.
+    pub address: u64,
+}
+
+impl GenericAddress {
+    /// Parses the 12 bytes of a generic address structure.
+    fn parse(bytes: &[u8]) -> GenericAddress {
+        GenericAddress {
+            address_space: bytes[0],
+            bit_width: bytes[1],
+            bit_offset: bytes[2],
+            access_size: bytes[3],
+            address: read_le(bytes, 4, 8).unwrap_or(0),
+        }
+    }
+}
+
+/// The fixed ACPI description table, decoded.
+///
+/// Fields that are not present in older revisions of the table are zero or `None`.
+#[derive(Clone, Copy, Debug)]
+pub struct Fadt {
+    /// The physical address of the FACS, if there is one.
+    pub firmware_ctrl: Option<u64>,
+    /// The physical address of the DSDT, if there is one.
+    pub dsdt: Option<u64>,
+    /// The preferred power management profile, such as 1 for desktops and 2 for mobile systems.
+    pub preferred_pm_profile: u8,
+    /// The system interrupt that the SCI is connected to.
+    pub sci_interrupt: u16,
+    /// The I/O port of the SMI command register, or zero if there is none.
+    pub smi_command: u32,
+    /// The IA-PC boot architecture flags.
+    pub iapc_boot_arch: u16,
+    /// The fixed feature flags.
+    pub flags: u32,
+    /// The register used to reset the system.
+    pub reset_register: Option<GenericAddress>,
+    /// The value written to the reset register.
+    pub reset_value: Option<u8>,
+    /// The ARM boot architecture flags.
+    pub arm_boot_arch: u16,
+    /// The minor version of the table.
+    pub minor_version: u8,
+}
+
+/// The multiple APIC description table.
+#[derive(Clone, Copy, Debug)]
+pub struct Madt {
+    /// The physical address of the local APIC of each processor.
+    pub local_apic_address: u32,
+    /// The MADT flags, bit 0 indicates that dual 8259 PICs are installed.
+    pub flags: u32,
+    /// The interrupt controller structures.
+    entries: &'static [u8],
+}
+
+impl Madt {
+    /// Returns an iterator over the interrupt controller structures.
+    ///
+    /// The iteration stops at a truncated structure.
+    pub fn entries(&self) -> MadtEntries {
+        MadtEntries {
+            entries: self.entries,
+        }
+    }
+}
+
+/// The local APIC is enabled.
+pub const MADT_LAPIC_ENABLED: u32 = 1 << 0;
+/// The local APIC is disabled, but can be enabled by the operating system.
+pub const MADT_LAPIC_ONLINE_CAPABLE: u32 = 1 << 1;
+
+/// An interrupt controller structure of the MADT.
+#[derive(Clone, Copy, Debug)]
+pub enum MadtEntry {
+    /// A processor with a local APIC.
+    LocalApic {
+        /// The processor UID, which matches the `_UID` of a processor object in the namespace.
+        processor_uid: u8,
+        /// The local APIC ID of the processor.
+        apic_id: u8,
+        /// `MADT_LAPIC_ENABLED` and `MADT_LAPIC_ONLINE_CAPABLE`.
+        flags: u32,
+    },
+    /// An I/O APIC.
+    IoApic {
+        /// The I/O APIC ID.
+        id: u8,
+        /// The 32 bit physical address to access the I/O APIC.
+        address: u32,
+        /// The first system interrupt handled by the I/O APIC.
+        global_system_interrupt_base: u32,
+    },
+    /// An ISA interrupt that is connected to a different system interrupt.
+    InterruptSourceOverride {
+        /// 0, which means ISA.
+        bus: u8,
+        /// The bus relative interrupt source, the IRQ.
+        source: u8,
+        /// The system interrupt that the bus relative interrupt source triggers.
+        global_system_interrupt: u32,
+        /// The polarity and trigger mode.
+        flags: u16,
+    },
+    /// The local APIC interrupt input connected to NMI.
+    LocalApicNmi {
+        /// The processor, or 0xff for all processors.
+        processor_uid: u8,
+        /// The polarity and trigger mode.
+        flags: u16,
+        /// The local APIC interrupt input `LINTn` to which NMI is connected.
+        lint: u8,
+    },
+    /// The 64 bit address of the local APICs, which replaces `Madt::local_apic_address`.
+    LocalApicAddressOverride {
+        /// The physical address of the local APIC.
+        address: u64,
+    },
+    /// A processor with a local x2APIC.
+    LocalX2Apic {
+        /// The x2APIC ID of the processor.
+        x2apic_id: u32,
+        /// `MADT_LAPIC_ENABLED` and `MADT_LAPIC_ONLINE_CAPABLE`.
+        flags: u32,
+        /// The processor UID, which matches the `_UID` of a processor object in the namespace.
+        processor_uid: u32,
+    },
+    /// A processor with an ARM generic interrupt controller CPU interface.
+    Gicc {
+        /// The GIC CPU interface number of the processor.
+        cpu_interface_number: u32,
+        /// The processor UID, which matches the `_UID` of a processor object in the namespace.
+        processor_uid: u32,
+        /// Whether the processor is enabled, and the trigger modes of its interrupts.
+        flags: u32,
+        /// The affinity value of the MPIDR register of the processor.
+        mpidr: u64,
+    },
+    /// A structure of another type, with its type and contents after the type and length.
+    Other {
+        /// The type of the structure.
+        entry_type: u8,
+        /// The contents of the structure after the type and length.
+        data: &'static [u8],
+    },
+}
+
+/// The iterator returned by `Madt::entries`.
+pub struct MadtEntries {
+    /// The remaining structures.
+    entries: &'static [u8],
+}
+
+impl Iterator for MadtEntries {
+    type Item = MadtEntry;
+
+    fn next(&mut self) -> Option<MadtEntry> {
+        let entry_type = *self.entries.get(0)?;
+        let length = usize::from(*self.entries.get(1)?);
+
+        if length < 2 || length > self.entries.len() {
+            self.entries = &[];
+            return None;
+        }
+
+        let entry = &self.entries[..length];
+        self.entries = &self.entries[length..];
+        let read = |offset: usize, size: usize| read_le(entry, offset, size);
+
+        let decoded = match entry_type {
+            0 => MadtEntry::LocalApic {
+                processor_uid: read(2, 1)? as u8,
+                apic_id: read(3, 1)? as u8,
+                flags: read(4, 4)? as u32,
+            },
+            1 => MadtEntry::IoApic {
+                id: read(2, 1)? as u8,
+                address: read(4, 4)? as u32,
+                global_system_interrupt_base: read(8, 4)? as u32,
+            },
+            2 => MadtEntry::InterruptSourceOverride {
+                bus: read(2, 1)? as u8,
+                source: read(3, 1)? as u8,
+                global_system_interrupt: read(4, 4)? as u32,
+                flags: read(8, 2)? as u16,
+            },
+            4 => MadtEntry::LocalApicNmi {
+                processor_uid: read(2, 1)? as u8,
+                flags: read(3, 2)? as u16,
+                lint: read(5, 1)? as u8,
+            },
+            5 => MadtEntry::LocalApicAddressOverride {
+                address: read(4, 8)?,
+            },
+            9 => MadtEntry::LocalX2Apic {
+                x2apic_id: read(4, 4)? as u32,
+                flags: read(8, 4)? as u32,
+                processor_uid: read(12, 4)? as u32,
+            },
+            0xb => MadtEntry::Gicc {
+                cpu_interface_number: read(4, 4)? as u32,
+                processor_uid: read(8, 4)? as u32,
+                flags: read(12, 4)? as u32,
+                mpidr: read(68, 8)?,
+            },
+            _ => MadtEntry::Other {
+                entry_type,
+                data: &entry[2..],
+            },
+        };
+
+        Some(decoded)
+    }
+}
+
+/// The PCI Express memory mapped configuration space base address description table.
+#[cfg(feature = "pci")]
+#[derive(Clone, Copy, Debug)]
+pub struct Mcfg {
+    /// The configuration space allocations.
+    entries: &'static [u8],
+}
+
+/// A configuration space allocation of the MCFG.
+#[cfg(feature = "pci")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct McfgEntry {
+    /// The physical address of the configuration space of bus 0 of the segment, even if
+    /// `start_bus` is not 0.
+    pub base_address: u64,
+    /// The PCI segment group.
+    pub segment: u16,
+    /// The start PCI bus number decoded by this host bridge.
+    pub start_bus: u8,
+    /// The end PCI bus number decoded by this host bridge.
+    pub end_bus: u8,
+}
+
+#[cfg(feature = "pci")]
+impl McfgEntry {
+    /// Returns the physical address of the configuration space of a function.
+    ///
+    /// Returns `None` if the bus is not in the allocation, the device or function is invalid or
+    /// the address does not fit into 64 bits.
+    pub fn config_address(&self, bus: u8, device: u8, function: u8) -> Option<u64> {
+        if bus < self.start_bus || bus > self.end_bus || device >= 32 || function >= 8 {
+            return None;
+        }
+
+        self.base_address
+            .checked_add(u64::from(bus) << 20 | u64::from(device) << 15 | u64::from(function) << 12)
+    }
+}
+
+#[cfg(feature = "pci")]
+impl Mcfg {
+    /// Returns the configuration space allocations.
+    pub fn entries(&self) -> impl Iterator<Item = McfgEntry> {
+        self.entries.chunks(16).filter_map(|entry| {
+            Some(McfgEntry {
+                base_address: read_le(entry, 0, 8)?,
+                segment: read_le(entry, 8, 2)? as u16,
+                start_bus: read_le(entry, 10, 1)? as u8,
+                end_bus: read_le(entry, 11, 1)? as u8,
+            })
+        })
+    }
+}
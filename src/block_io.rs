@@ -1,5 +1,17 @@
 //! This protocol provides control over block devices.
-use crate::status::Status;
+use crate::{
+    guid::{Guid, BLOCK_IO_GUID},
+    protocol::Protocol,
+    status::{Error, Status},
+};
+
+/// The revision of block IO protocols that provide `BlockIoMediaExtension::LowestAlignedLba` and
+/// `BlockIoMediaExtension::LogicalBlocksPerPhysicalBlock`.
+pub const BLOCK_IO_PROTOCOL_REVISION2: u64 = 0x0002_0001;
+
+/// The revision of block IO protocols that provide
+/// `BlockIoMediaExtension::OptimalTransferLengthGranularity`.
+pub const BLOCK_IO_PROTOCOL_REVISION3: u64 = (2 << 16) | 31;
 
 /// Represents block IO media information.
 #[repr(C)]
@@ -46,6 +58,25 @@ pub struct BlockIoMedia {
     pub LastBlock: u64,
 }
 
+/// The fields that follow `BlockIoMedia` in newer revisions of the block IO protocol.
+///
+/// Use the accessors of `BlockIo` to read them, as they check the revision first.
+#[repr(C)]
+pub struct BlockIoMediaExtension {
+    /// The first LBA that is aligned to a physical block boundary.
+    ///
+    /// Present in revision 2 and later.
+    pub LowestAlignedLba: u64,
+    /// The number of logical blocks per physical block.
+    ///
+    /// Present in revision 2 and later.
+    pub LogicalBlocksPerPhysicalBlock: u32,
+    /// The optimal transfer length granularity as a number of logical blocks.
+    ///
+    /// Present in revision 3 and later.
+    pub OptimalTransferLengthGranularity: u32,
+}
+
 /// This protocol provides control over block devices.
 #[repr(C)]
 pub struct BlockIo {
@@ -56,24 +87,102 @@ pub struct BlockIo {
     /// A pointer to the EFI_BLOCK_IO_MEDIA data for this device.
     pub Media: &'static BlockIoMedia,
     /// Resets the block device hardware.
-    pub Reset: extern "win64" fn(&BlockIo, ExtendedVerification: bool) -> Status,
+    pub Reset: eficall! { fn(&BlockIo, ExtendedVerification: bool) -> Status },
     /// Reads the requested number of blocks from the device.
-    pub ReadBlocks: extern "win64" fn(
+    pub ReadBlocks: eficall! { fn(
         &BlockIo,
         MediaId: u32,
         LBA: u64,
         BufferSize: usize,
         Buffer: *mut u8,
-    ) -> Status,
+    ) -> Status },
     /// Writes the requested number of blocks to the device.
-    pub WriteBlocks: extern "win64" fn(
+    pub WriteBlocks: eficall! { fn(
         &BlockIo,
         MediaId: u32,
         LBA: u64,
         BufferSize: usize,
         Buffer: *const u8,
-    ) -> Status,
+    ) -> Status },
     /// Flushes any cache blocks. This function is optional and only
     /// needs to be supported on block devices that cache writes.
-    pub FlushBlocks: extern "win64" fn(&BlockIo) -> Status,
+    pub FlushBlocks: eficall! { fn(&BlockIo) -> Status },
+}
+
+unsafe impl Protocol for BlockIo {
+    const GUID: Guid = BLOCK_IO_GUID;
+}
+
+impl BlockIo {
+    /// Reads blocks starting at `lba` into `buffer`.
+    ///
+    /// The length of `buffer` must be a multiple of the block size and its address must be
+    /// aligned to `BlockIoMedia::IoAlign`.
+    pub fn read_blocks(&self, lba: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        (self.ReadBlocks)(
+            self,
+            self.Media.MediaId,
+            lba,
+            buffer.len(),
+            buffer.as_mut_ptr(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes `data` to the blocks starting at `lba`.
+    ///
+    /// The same restrictions as for `read_blocks` apply to `data`.
+    pub fn write_blocks(&self, lba: u64, data: &[u8]) -> Result<(), Error> {
+        (self.WriteBlocks)(self, self.Media.MediaId, lba, data.len(), data.as_ptr())?;
+
+        Ok(())
+    }
+
+    /// Flushes cached writes to the device.
+    pub fn flush_blocks(&self) -> Result<(), Error> {
+        (self.FlushBlocks)(self)?;
+
+        Ok(())
+    }
+
+    /// Returns the first LBA that is aligned to a physical block boundary.
+    ///
+    /// Returns `Error::Unsupported` if the protocol predates revision 2.
+    pub fn lowest_aligned_lba(&self) -> Result<u64, Error> {
+        Ok(self
+            .media_extension(BLOCK_IO_PROTOCOL_REVISION2)?
+            .LowestAlignedLba)
+    }
+
+    /// Returns the number of logical blocks per physical block.
+    ///
+    /// Returns `Error::Unsupported` if the protocol predates revision 2.
+    pub fn logical_blocks_per_physical_block(&self) -> Result<u32, Error> {
+        Ok(self
+            .media_extension(BLOCK_IO_PROTOCOL_REVISION2)?
+            .LogicalBlocksPerPhysicalBlock)
+    }
+
+    /// Returns the optimal transfer length granularity as a number of logical blocks.
+    ///
+    /// Returns `Error::Unsupported` if the protocol predates revision 3.
+    pub fn optimal_transfer_length_granularity(&self) -> Result<u32, Error> {
+        Ok(self
+            .media_extension(BLOCK_IO_PROTOCOL_REVISION3)?
+            .OptimalTransferLengthGranularity)
+    }
+
+    /// Returns the fields that follow the media information, if the protocol has at least the
+    /// given revision.
+    fn media_extension(&self, revision: u64) -> Result<&BlockIoMediaExtension, Error> {
+        if self.Revision < revision {
+            return Err(Error::Unsupported);
+        }
+
+        let media = self.Media as *const BlockIoMedia;
+
+        // This is safe, because media information of this revision is followed by the extension.
+        Ok(unsafe { &*(media.add(1) as *const BlockIoMediaExtension) })
+    }
 }
@@ -0,0 +1,392 @@
+//! Provides the protocols used to configure Bluetooth devices and to talk to the Bluetooth host
+//! controller directly.
+
+use crate::{
+    guid::{Guid, BLUETOOTH_CONFIG_PROTOCOL_GUID, BLUETOOTH_HC_PROTOCOL_GUID},
+    protocol::Protocol,
+};
+
+use crate::status::{Error, Status};
+
+/// The maximum size of the name of a Bluetooth device.
+pub const BLUETOOTH_HCI_COMMAND_LOCAL_READABLE_NAME_MAX_SIZE: usize = 248;
+
+/// The remote device is connected.
+pub const BLUETOOTH_REMOTE_DEVICE_STATE_CONNECTED: u8 = 0x01;
+/// The remote device is paired.
+pub const BLUETOOTH_REMOTE_DEVICE_STATE_PAIRED: u8 = 0x02;
+
+/// The opcode of the HCI_Reset command.
+pub const HCI_RESET_OPCODE: u16 = 0x0c03;
+/// The event code of the HCI_Command_Complete event.
+pub const HCI_COMMAND_COMPLETE_EVENT: u8 = 0x0e;
+
+/// The address of a Bluetooth device.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct BluetoothAddress {
+    /// The address in little endian byte order.
+    pub Address: [u8; 6],
+}
+
+/// The class of a Bluetooth device.
+///
+/// The bits are laid out as: 2 bits format type, 6 bits minor device class, 5 bits major device
+/// class, 11 bits major service class.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct BluetoothClassOfDevice(pub [u8; 3]);
+
+impl BluetoothClassOfDevice {
+    /// Returns the minor device class.
+    pub fn minor_device_class(&self) -> u8 {
+        self.0[0] >> 2
+    }
+
+    /// Returns the major device class.
+    pub fn major_device_class(&self) -> u8 {
+        self.0[1] & 0x1f
+    }
+
+    /// Returns the major service class.
+    pub fn major_service_class(&self) -> u16 {
+        (u16::from(self.0[2]) << 3) | (u16::from(self.0[1]) >> 5)
+    }
+}
+
+/// The information about a device that was found during a scan.
+#[repr(C)]
+pub struct BluetoothScanCallbackInformation {
+    /// The address of the device.
+    pub BDAddr: BluetoothAddress,
+    /// The state of the device, a combination of the `BLUETOOTH_REMOTE_DEVICE_STATE_*` flags.
+    pub RemoteDeviceState: u8,
+    /// The class of the device.
+    pub ClassOfDevice: BluetoothClassOfDevice,
+    /// The null terminated UTF-8 name of the device.
+    pub RemoteDeviceName: [u8; BLUETOOTH_HCI_COMMAND_LOCAL_READABLE_NAME_MAX_SIZE],
+}
+
+impl BluetoothScanCallbackInformation {
+    /// Returns the name of the device, without the null terminator.
+    pub fn name(&self) -> &[u8] {
+        let length = self
+            .RemoteDeviceName
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(self.RemoteDeviceName.len());
+
+        &self.RemoteDeviceName[..length]
+    }
+}
+
+/// The type of data that is set or retrieved with the Bluetooth configuration protocol.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum BluetoothConfigDataType {
+    /// The name of the local device.
+    DeviceName,
+    /// The class of the local device.
+    ClassOfDevice,
+    /// The state of a remote device.
+    RemoteDeviceState,
+    /// The SDP information of a remote device.
+    SdpInfo,
+    /// The address of the local device.
+    BdAddr,
+    /// Whether the local device is discoverable.
+    Discoverable,
+    /// The paired devices stored in the controller.
+    ControllerStoredPairedDeviceList,
+    /// The available devices.
+    AvailableDeviceList,
+    /// The random address of the local device.
+    RandomAddress,
+    /// The signal strength of a remote device.
+    Rssi,
+    /// The advertisement data of a remote device.
+    AdvertisementData,
+    /// The IO capability of the local device.
+    IoCapability,
+    /// Whether OOB data is present.
+    OobDataFlag,
+    /// The type of the link key.
+    KeyType,
+    /// The size of the encryption key.
+    EncKeySize,
+}
+
+/// Called for every device that is found during a scan.
+pub type BluetoothConfigScanCallback = eficall! { fn(
+    This: &BluetoothConfig,
+    Context: *mut u8,
+    CallbackInfo: &BluetoothScanCallbackInformation,
+) -> Status };
+
+/// Abstracts user interface configuration for a Bluetooth device.
+#[repr(C)]
+pub struct BluetoothConfig {
+    /// Initializes the Bluetooth host controller and the Bluetooth bus driver.
+    pub Init: eficall! { fn(&BluetoothConfig) -> Status },
+    /// Scans for remote Bluetooth devices.
+    pub Scan: eficall! { fn(
+        &BluetoothConfig,
+        ReScan: bool,
+        ScanType: u8,
+        Callback: BluetoothConfigScanCallback,
+        Context: *mut u8,
+    ) -> Status },
+    /// Connects to a remote Bluetooth device.
+    pub Connect: eficall! { fn(&BluetoothConfig, BDAddr: &BluetoothAddress) -> Status },
+    /// Disconnects from a remote Bluetooth device.
+    pub Disconnect: eficall! { fn(
+        &BluetoothConfig,
+        BDAddr: &BluetoothAddress,
+        Reason: u8,
+    ) -> Status },
+    /// Gets data of the local Bluetooth device.
+    pub GetData: eficall! { fn(
+        &BluetoothConfig,
+        DataType: BluetoothConfigDataType,
+        DataSize: &mut usize,
+        Data: *mut u8,
+    ) -> Status },
+    /// Sets data of the local Bluetooth device.
+    pub SetData: eficall! { fn(
+        &BluetoothConfig,
+        DataType: BluetoothConfigDataType,
+        DataSize: usize,
+        Data: *const u8,
+    ) -> Status },
+    /// Gets data of a remote Bluetooth device.
+    pub GetRemoteData: eficall! { fn(
+        &BluetoothConfig,
+        DataType: BluetoothConfigDataType,
+        BDAddr: &BluetoothAddress,
+        DataSize: &mut usize,
+        Data: *mut u8,
+    ) -> Status },
+    /// Registers a callback to get the PIN code for pairing.
+    pub RegisterPinCallback: eficall! { fn() },
+    /// Registers a callback to get the link key.
+    pub RegisterGetLinkKeyCallback: eficall! { fn() },
+    /// Registers a callback to store the link key.
+    pub RegisterSetLinkKeyCallback: eficall! { fn() },
+    /// Registers a callback that is called when a link was established.
+    pub RegisterLinkConnectCompleteCallback: eficall! { fn() },
+}
+
+unsafe impl Protocol for BluetoothConfig {
+    const GUID: Guid = BLUETOOTH_CONFIG_PROTOCOL_GUID;
+}
+
+impl BluetoothConfig {
+    /// Initializes the Bluetooth host controller and the Bluetooth bus driver.
+    pub fn init(&self) -> Result<(), Error> {
+        (self.Init)(self)?;
+
+        Ok(())
+    }
+
+    /// Scans for remote Bluetooth devices.
+    ///
+    /// `callback` is called with `context` for every device that is found. If `rescan` is
+    /// false, the cached results of a previous scan may be reported.
+    pub fn scan(
+        &self,
+        rescan: bool,
+        scan_type: u8,
+        callback: BluetoothConfigScanCallback,
+        context: *mut u8,
+    ) -> Result<(), Error> {
+        (self.Scan)(self, rescan, scan_type, callback, context)?;
+
+        Ok(())
+    }
+
+    /// Connects to a remote Bluetooth device.
+    pub fn connect(&self, address: &BluetoothAddress) -> Result<(), Error> {
+        (self.Connect)(self, address)?;
+
+        Ok(())
+    }
+
+    /// Disconnects from a remote Bluetooth device with the given HCI reason code.
+    pub fn disconnect(&self, address: &BluetoothAddress, reason: u8) -> Result<(), Error> {
+        (self.Disconnect)(self, address, reason)?;
+
+        Ok(())
+    }
+
+    /// Gets data of the local Bluetooth device.
+    ///
+    /// Returns the number of bytes written into the buffer.
+    pub fn get_data(
+        &self,
+        data_type: BluetoothConfigDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        let mut size = buffer.len();
+
+        (self.GetData)(self, data_type, &mut size, buffer.as_mut_ptr())?;
+
+        Ok(size)
+    }
+
+    /// Sets data of the local Bluetooth device.
+    pub fn set_data(&self, data_type: BluetoothConfigDataType, data: &[u8]) -> Result<(), Error> {
+        (self.SetData)(self, data_type, data.len(), data.as_ptr())?;
+
+        Ok(())
+    }
+
+    /// Gets data of a remote Bluetooth device.
+    ///
+    /// Returns the number of bytes written into the buffer.
+    pub fn get_remote_data(
+        &self,
+        data_type: BluetoothConfigDataType,
+        address: &BluetoothAddress,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        let mut size = buffer.len();
+
+        (self.GetRemoteData)(self, data_type, address, &mut size, buffer.as_mut_ptr())?;
+
+        Ok(size)
+    }
+}
+
+/// Called when data was received asynchronously.
+pub type BluetoothHcAsyncCallback =
+    eficall! { fn(Data: *const u8, DataLength: usize, Context: *mut u8) -> Status };
+
+/// Abstracts the Bluetooth host controller layer message transmit and receive.
+///
+/// All timeouts are given in milliseconds.
+#[repr(C)]
+pub struct BluetoothHc {
+    /// Sends an HCI command packet.
+    pub SendCommand: eficall! { fn(
+        &BluetoothHc,
+        BufferSize: &mut usize,
+        Buffer: *const u8,
+        Timeout: usize,
+    ) -> Status },
+    /// Receives an HCI event packet.
+    pub ReceiveEvent: eficall! { fn(
+        &BluetoothHc,
+        BufferSize: &mut usize,
+        Buffer: *mut u8,
+        Timeout: usize,
+    ) -> Status },
+    /// Receives HCI event packets asynchronously.
+    pub AsyncReceiveEvent: eficall! { fn(
+        &BluetoothHc,
+        IsNewTransfer: bool,
+        PollingInterval: usize,
+        DataLength: usize,
+        Callback: BluetoothHcAsyncCallback,
+        Context: *mut u8,
+    ) -> Status },
+    /// Sends an HCI ACL data packet.
+    pub SendACLData: eficall! { fn(
+        &BluetoothHc,
+        BufferSize: &mut usize,
+        Buffer: *const u8,
+        Timeout: usize,
+    ) -> Status },
+    /// Receives an HCI ACL data packet.
+    pub ReceiveACLData: eficall! { fn(
+        &BluetoothHc,
+        BufferSize: &mut usize,
+        Buffer: *mut u8,
+        Timeout: usize,
+    ) -> Status },
+    /// Receives HCI ACL data packets asynchronously.
+    pub AsyncReceiveACLData: eficall! { fn(
+        &BluetoothHc,
+        IsNewTransfer: bool,
+        PollingInterval: usize,
+        DataLength: usize,
+        Callback: BluetoothHcAsyncCallback,
+        Context: *mut u8,
+    ) -> Status },
+    /// Sends an HCI SCO data packet.
+    pub SendSCOData: eficall! { fn(
+        &BluetoothHc,
+        BufferSize: &mut usize,
+        Buffer: *const u8,
+        Timeout: usize,
+    ) -> Status },
+    /// Receives an HCI SCO data packet.
+    pub ReceiveSCOData: eficall! { fn(
+        &BluetoothHc,
+        BufferSize: &mut usize,
+        Buffer: *mut u8,
+        Timeout: usize,
+    ) -> Status },
+    /// Receives HCI SCO data packets asynchronously.
+    pub AsyncReceiveSCOData: eficall! { fn(
+        &BluetoothHc,
+        IsNewTransfer: bool,
+        PollingInterval: usize,
+        DataLength: usize,
+        Callback: BluetoothHcAsyncCallback,
+        Context: *mut u8,
+    ) -> Status },
+}
+
+unsafe impl Protocol for BluetoothHc {
+    const GUID: Guid = BLUETOOTH_HC_PROTOCOL_GUID;
+}
+
+impl BluetoothHc {
+    /// Sends an HCI command packet.
+    ///
+    /// Returns the number of bytes that were sent.
+    pub fn send_command(&self, packet: &[u8], timeout: usize) -> Result<usize, Error> {
+        let mut size = packet.len();
+
+        (self.SendCommand)(self, &mut size, packet.as_ptr(), timeout)?;
+
+        Ok(size)
+    }
+
+    /// Receives an HCI event packet.
+    ///
+    /// Returns the number of bytes that were received.
+    pub fn receive_event(&self, buffer: &mut [u8], timeout: usize) -> Result<usize, Error> {
+        let mut size = buffer.len();
+
+        (self.ReceiveEvent)(self, &mut size, buffer.as_mut_ptr(), timeout)?;
+
+        Ok(size)
+    }
+
+    /// Resets the Bluetooth controller with an HCI_Reset command and waits for its completion.
+    ///
+    /// This is useful to test whether the controller hardware is responsive. Returns
+    /// `Error::DeviceError` if the controller reports a failure.
+    pub fn reset_controller(&self, timeout: usize) -> Result<(), Error> {
+        let opcode = HCI_RESET_OPCODE.to_le_bytes();
+
+        self.send_command(&[opcode[0], opcode[1], 0], timeout)?;
+
+        // The event consists of the event code, the parameter length, the number of allowed
+        // command packets, the opcode and the status of the command.
+        let mut event = [0u8; 255];
+
+        loop {
+            let size = self.receive_event(&mut event, timeout)?;
+
+            if size >= 6 && event[0] == HCI_COMMAND_COMPLETE_EVENT && event[3..5] == opcode {
+                break if event[5] == 0 {
+                    Ok(())
+                } else {
+                    Err(Error::DeviceError)
+                };
+            }
+        }
+    }
+}
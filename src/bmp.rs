@@ -0,0 +1,424 @@
+//! Decodes BMP images into pixels that can be passed to `GraphicsOutput::Blt`.
+//!
+//! Uncompressed images with 1, 4, 8, 16, 24 and 32 bits per pixel are supported, including the
+//! 32 bit BGRA images with bit field masks that firmware logos usually are. Rows are stored
+//! bottom-up unless the height is negative and every row is padded to a multiple of four bytes,
+//! which `Bmp` hides, so pixels are always addressed from the top left corner.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "fs")]
+use crate::fs::File;
+use crate::{
+    graphics::{GraphicsBltPixel, GraphicsOutput},
+    status::Error,
+};
+
+/// The signature at the start of every BMP file.
+const SIGNATURE: [u8; 2] = *b"BM";
+/// The size of the file header that precedes the DIB header.
+const FILE_HEADER_SIZE: usize = 14;
+/// The size of the `BITMAPCOREHEADER`, which uses 16 bit dimensions and 3 byte palette entries.
+const CORE_HEADER_SIZE: usize = 12;
+/// The size of the `BITMAPINFOHEADER`.
+const INFO_HEADER_SIZE: usize = 40;
+/// The size of the `BITMAPV2INFOHEADER`, the first header that contains the color masks.
+const V2_INFO_HEADER_SIZE: usize = 52;
+
+/// The pixels are not compressed.
+const BI_RGB: u32 = 0;
+/// The pixels are not compressed and the color masks are given.
+const BI_BITFIELDS: u32 = 3;
+/// The pixels are not compressed and the color and alpha masks are given.
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// The number of pixels `draw_bmp` and `write_bmp` convert at once.
+const CHUNK_PIXELS: usize = 128;
+/// The resolution written by `write_bmp`, which is 72 DPI.
+const PIXELS_PER_METER: u32 = 2835;
+
+/// Reads a little endian `u16` at `offset`.
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    let bytes = data.get(offset..offset + 2).ok_or(Error::BadBufferSize)?;
+
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Reads a little endian `u32` at `offset`.
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    let bytes = data.get(offset..offset + 4).ok_or(Error::BadBufferSize)?;
+
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Extracts the bits of `mask` from `value` and scales them to 8 bits.
+fn extract_component(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+
+    let shift = mask.trailing_zeros();
+    let max = u64::from(mask >> shift);
+
+    (u64::from((value & mask) >> shift) * 255 / max) as u8
+}
+
+/// The way the pixel values are converted to colors.
+#[derive(Clone, Copy, Debug)]
+enum PixelFormat<'a> {
+    /// The values are indices into a palette with entries of the given size.
+    Palette {
+        /// The palette entries, in blue, green, red order.
+        palette: &'a [u8],
+        /// The size of a palette entry, which is 3 for core headers and 4 otherwise.
+        entry_size: usize,
+    },
+    /// The values contain the red, green and blue components in the bits of these masks.
+    Masks([u32; 3]),
+}
+
+/// A parsed BMP image.
+#[derive(Clone, Copy, Debug)]
+pub struct Bmp<'a> {
+    /// The pixel data.
+    pixels: &'a [u8],
+    /// The width of the image in pixels.
+    width: usize,
+    /// The height of the image in pixels.
+    height: usize,
+    /// Whether the first row in the data is the top row.
+    top_down: bool,
+    /// The number of bits per pixel.
+    bits_per_pixel: usize,
+    /// The size of a row in bytes, including the padding.
+    row_size: usize,
+    /// How pixel values are converted to colors.
+    format: PixelFormat<'a>,
+}
+
+impl<'a> Bmp<'a> {
+    /// Parses the headers of a BMP image.
+    ///
+    /// Returns `Error::Unsupported` if `data` is not a BMP image or uses compression or an
+    /// unsupported number of bits per pixel, and `Error::BadBufferSize` if the image is
+    /// truncated or its headers are inconsistent.
+    pub fn parse(data: &'a [u8]) -> Result<Bmp<'a>, Error> {
+        if !data.starts_with(&SIGNATURE) {
+            return Err(Error::Unsupported);
+        }
+
+        let pixels_offset = read_u32(data, 10)? as usize;
+        let header_size = read_u32(data, FILE_HEADER_SIZE)? as usize;
+        let header = FILE_HEADER_SIZE;
+
+        let (width, height, bits_per_pixel, compression) = if header_size == CORE_HEADER_SIZE {
+            (
+                i64::from(read_u16(data, header + 4)?),
+                i64::from(read_u16(data, header + 6)?),
+                read_u16(data, header + 10)?,
+                BI_RGB,
+            )
+        } else if header_size >= INFO_HEADER_SIZE {
+            (
+                i64::from(read_u32(data, header + 4)? as i32),
+                i64::from(read_u32(data, header + 8)? as i32),
+                read_u16(data, header + 14)?,
+                read_u32(data, header + 16)?,
+            )
+        } else {
+            return Err(Error::Unsupported);
+        };
+
+        if width <= 0 || height == 0 {
+            return Err(Error::BadBufferSize);
+        }
+
+        let bits_per_pixel = usize::from(bits_per_pixel);
+        let (width, height, top_down) = (width as usize, height.abs() as usize, height < 0);
+
+        let format = match (bits_per_pixel, compression) {
+            (1, BI_RGB) | (4, BI_RGB) | (8, BI_RGB) => {
+                let entry_size = if header_size == CORE_HEADER_SIZE {
+                    3
+                } else {
+                    4
+                };
+                let used_colors = if header_size == CORE_HEADER_SIZE {
+                    0
+                } else {
+                    read_u32(data, header + 32)? as usize
+                };
+                let colors = if used_colors == 0 || used_colors > 1 << bits_per_pixel {
+                    1 << bits_per_pixel
+                } else {
+                    used_colors
+                };
+
+                let palette_start = header + header_size;
+                let palette_end = (palette_start + colors * entry_size).min(pixels_offset);
+
+                PixelFormat::Palette {
+                    palette: data
+                        .get(palette_start..palette_end)
+                        .ok_or(Error::BadBufferSize)?,
+                    entry_size,
+                }
+            }
+            (16, BI_RGB) => PixelFormat::Masks([0x7c00, 0x03e0, 0x001f]),
+            (24, BI_RGB) | (32, BI_RGB) => {
+                PixelFormat::Masks([0x00ff_0000, 0x0000_ff00, 0x0000_00ff])
+            }
+            (16, BI_BITFIELDS)
+            | (32, BI_BITFIELDS)
+            | (16, BI_ALPHABITFIELDS)
+            | (32, BI_ALPHABITFIELDS) => {
+                // Headers before the second version are followed by the masks instead.
+                let masks = if header_size >= V2_INFO_HEADER_SIZE {
+                    header + INFO_HEADER_SIZE
+                } else {
+                    header + header_size
+                };
+
+                PixelFormat::Masks([
+                    read_u32(data, masks)?,
+                    read_u32(data, masks + 4)?,
+                    read_u32(data, masks + 8)?,
+                ])
+            }
+            _ => return Err(Error::Unsupported),
+        };
+
+        let row_size = width
+            .checked_mul(bits_per_pixel)
+            .map(|bits| (bits + 31) / 32 * 4)
+            .ok_or(Error::BadBufferSize)?;
+        let pixels_end = row_size
+            .checked_mul(height)
+            .and_then(|size| size.checked_add(pixels_offset))
+            .ok_or(Error::BadBufferSize)?;
+
+        Ok(Bmp {
+            pixels: data
+                .get(pixels_offset..pixels_end)
+                .ok_or(Error::BadBufferSize)?,
+            width,
+            height,
+            top_down,
+            bits_per_pixel,
+            row_size,
+            format,
+        })
+    }
+
+    /// Returns the width of the image in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixel at the given position, counted from the top left corner.
+    ///
+    /// Positions outside of the image and palette indices outside of the palette are black.
+    pub fn pixel(&self, x: usize, y: usize) -> GraphicsBltPixel {
+        let black = GraphicsBltPixel {
+            Blue: 0,
+            Green: 0,
+            Red: 0,
+            Reserved: 0,
+        };
+
+        if x >= self.width || y >= self.height {
+            return black;
+        }
+
+        let row = if self.top_down {
+            y
+        } else {
+            self.height - 1 - y
+        };
+        let row = &self.pixels[row * self.row_size..(row + 1) * self.row_size];
+
+        let value = match self.bits_per_pixel {
+            1 | 4 | 8 => {
+                let bit = x * self.bits_per_pixel;
+                let shift = 8 - self.bits_per_pixel - bit % 8;
+
+                u32::from(row[bit / 8] >> shift) & ((1 << self.bits_per_pixel) - 1)
+            }
+            16 => u32::from(u16::from_le_bytes([row[x * 2], row[x * 2 + 1]])),
+            24 => u32::from_le_bytes([row[x * 3], row[x * 3 + 1], row[x * 3 + 2], 0]),
+            _ => u32::from_le_bytes([row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]]),
+        };
+
+        match self.format {
+            PixelFormat::Palette {
+                palette,
+                entry_size,
+            } => {
+                let entry = value as usize * entry_size;
+
+                match palette.get(entry..entry + 3) {
+                    Some(color) => GraphicsBltPixel {
+                        Blue: color[0],
+                        Green: color[1],
+                        Red: color[2],
+                        Reserved: 0,
+                    },
+                    None => black,
+                }
+            }
+            PixelFormat::Masks([red, green, blue]) => GraphicsBltPixel {
+                Blue: extract_component(value, blue),
+                Green: extract_component(value, green),
+                Red: extract_component(value, red),
+                Reserved: 0,
+            },
+        }
+    }
+
+    /// Converts the pixels of row `y`, starting at column `x`, into `buffer`.
+    ///
+    /// As many pixels as fit into `buffer` are converted, up to the end of the row. Returns the
+    /// number of converted pixels.
+    pub fn read_row(&self, x: usize, y: usize, buffer: &mut [GraphicsBltPixel]) -> usize {
+        let count = self.width.saturating_sub(x).min(buffer.len());
+
+        for (offset, pixel) in buffer[..count].iter_mut().enumerate() {
+            *pixel = self.pixel(x + offset, y);
+        }
+
+        count
+    }
+
+    /// Converts the whole image into `buffer`, row by row from the top without any padding.
+    ///
+    /// The buffer can be passed to `GraphicsOutput::buffer_to_video`. Returns
+    /// `Error::BufferTooSmall` if it holds fewer than `width * height` pixels.
+    pub fn decode_into(&self, buffer: &mut [GraphicsBltPixel]) -> Result<(), Error> {
+        if buffer.len() < self.width * self.height {
+            return Err(Error::BufferTooSmall);
+        }
+
+        for (y, row) in buffer.chunks_mut(self.width).take(self.height).enumerate() {
+            self.read_row(0, y, row);
+        }
+
+        Ok(())
+    }
+
+    /// Converts the whole image into pixels, row by row from the top without any padding.
+    #[cfg(feature = "alloc")]
+    pub fn decode(&self) -> Vec<GraphicsBltPixel> {
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+
+        for y in 0..self.height {
+            pixels.extend((0..self.width).map(|x| self.pixel(x, y)));
+        }
+
+        pixels
+    }
+}
+
+/// Draws the BMP image in `data` with its top left corner at (`x`, `y`).
+///
+/// The image is converted in small chunks, so no memory needs to be allocated. Errors of
+/// `Bmp::parse` and of the `Blt` function are returned.
+pub fn draw_bmp(
+    graphics_output: &mut GraphicsOutput,
+    x: usize,
+    y: usize,
+    data: &[u8],
+) -> Result<(), Error> {
+    let image = Bmp::parse(data)?;
+    let mut buffer = [GraphicsBltPixel {
+        Blue: 0,
+        Green: 0,
+        Red: 0,
+        Reserved: 0,
+    }; CHUNK_PIXELS];
+
+    for row in 0..image.height() {
+        let mut column = 0;
+
+        while column < image.width() {
+            let count = image.read_row(column, row, &mut buffer);
+
+            graphics_output.buffer_to_video(&buffer[..count], x + column, y + row, count, 1)?;
+            column += count;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes `pixels` as an uncompressed 24 bit BMP image at the current position of `file`.
+///
+/// `pixels` holds the image row by row from the top without any padding, like the buffers of
+/// `GraphicsOutput::video_to_buffer`. Returns `Error::InvalidParameter` if the image is empty,
+/// `Error::BufferTooSmall` if `pixels` holds fewer than `width * height` pixels and
+/// `Error::BadBufferSize` if the image is too large for the format.
+#[cfg(feature = "fs")]
+pub fn write_bmp(
+    file: &mut File,
+    width: usize,
+    height: usize,
+    pixels: &[GraphicsBltPixel],
+) -> Result<(), Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidParameter);
+    }
+    if pixels.len() < width.saturating_mul(height) {
+        return Err(Error::BufferTooSmall);
+    }
+
+    let row_size = width
+        .checked_mul(3)
+        .map(|size| (size + 3) / 4 * 4)
+        .ok_or(Error::BadBufferSize)?;
+    let image_size = row_size.checked_mul(height).ok_or(Error::BadBufferSize)?;
+    let pixels_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+    let file_size = image_size
+        .checked_add(pixels_offset)
+        .filter(|&size| size <= u32::max_value() as usize && width <= i32::max_value() as usize)
+        .ok_or(Error::BadBufferSize)?;
+
+    let mut header = [0; FILE_HEADER_SIZE + INFO_HEADER_SIZE];
+    header[0..2].copy_from_slice(&SIGNATURE);
+    header[2..6].copy_from_slice(&(file_size as u32).to_le_bytes());
+    header[10..14].copy_from_slice(&(pixels_offset as u32).to_le_bytes());
+
+    let info = &mut header[FILE_HEADER_SIZE..];
+    info[0..4].copy_from_slice(&(INFO_HEADER_SIZE as u32).to_le_bytes());
+    info[4..8].copy_from_slice(&(width as u32).to_le_bytes());
+    info[8..12].copy_from_slice(&(height as u32).to_le_bytes());
+    info[12..14].copy_from_slice(&1u16.to_le_bytes());
+    info[14..16].copy_from_slice(&24u16.to_le_bytes());
+    info[16..20].copy_from_slice(&BI_RGB.to_le_bytes());
+    info[20..24].copy_from_slice(&(image_size as u32).to_le_bytes());
+    info[24..28].copy_from_slice(&PIXELS_PER_METER.to_le_bytes());
+    info[28..32].copy_from_slice(&PIXELS_PER_METER.to_le_bytes());
+
+    file.write_all(&header)?;
+
+    let mut buffer = [0; CHUNK_PIXELS * 3];
+
+    // The rows are stored bottom-up, so the top row is written last.
+    for row in pixels.chunks(width).take(height).rev() {
+        for chunk in row.chunks(CHUNK_PIXELS) {
+            for (bytes, pixel) in buffer.chunks_mut(3).zip(chunk) {
+                bytes.copy_from_slice(&[pixel.Blue, pixel.Green, pixel.Red]);
+            }
+
+            file.write_all(&buffer[..chunk.len() * 3])?;
+        }
+
+        file.write_all(&[0; 3][..row_size - width * 3])?;
+    }
+
+    Ok(())
+}
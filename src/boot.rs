@@ -4,18 +4,143 @@
 //! as defined in Section 7. The function pointers in this table are not valid after the operating system
 //! has taken control of the platform with a call to EFI_BOOT_SERVICES.ExitBootServices().
 
-use core::mem::size_of;
+use bitflags::bitflags;
+use core::{
+    mem::{size_of, ManuallyDrop},
+    ops::Deref,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
 
 use crate::{
+    boxed::PoolBuffer,
+    config::ConfigurationTable,
+    device::DevicePath,
     guid::{Guid, LOADED_IMAGE_PROTOCOL_GUID},
     loaded_image::LoadedImage,
     memory::{
-        MemoryDescriptor, MemoryMap, MemoryType, NamedMemoryType, PhysicalAddress, PAGE_SIZE,
+        MemoryDescriptor, MemoryMap, MemoryType, PhysicalAddress, MEMORY_DESCRIPTOR_VERSION,
+        PAGE_SIZE,
     },
-    status::{Error, Status, SUCCESS},
-    Event, Handle, TableHeader,
+    protocol::Protocol,
+    quirk::{self, Quirk},
+    runtime::RuntimeServices,
+    status::{Error, ErrorExt, NuefilError, Status, StatusResultExt, SUCCESS},
+    system::{SystemTable, SYSTEM_TABLE_REVISION_2_00},
+    ucs2::Lossy,
+    Event, Handle, OptionalHandle, TableHeader,
 };
 
+/// The number of additional descriptors `get_memory_map` allocates room for.
+pub const DEFAULT_MEMORY_MAP_SLACK: usize = 8;
+
+/// The number of additional pages allocated for the memory map when exiting boot services.
+///
+/// After the first attempt to exit boot services, no memory can be allocated anymore, so the
+/// buffer must be large enough for any later memory map up front.
+pub const DEFAULT_EXIT_SLACK_PAGES: usize = 1;
+
+/// The number of times exiting boot services is attempted before giving up.
+pub const DEFAULT_EXIT_ATTEMPTS: usize = 8;
+
+/// The number of times the memory map is requested before giving up.
+///
+/// The first request only queries the required size of the buffer.
+pub const MEMORY_MAP_ATTEMPTS: usize = 8;
+
+/// The boot services used by types that release their memory when they are dropped.
+static GLOBAL_BOOT_SERVICES: AtomicPtr<BootServices> = AtomicPtr::new(ptr::null_mut());
+
+/// Registers the boot services that are used to free memory when owning types are dropped.
+///
+/// The registration is removed again by `exit_boot_services`, after which dropped memory is leaked.
+pub fn register_global(boot_services: &'static BootServices) {
+    GLOBAL_BOOT_SERVICES.store(boot_services as *const _ as *mut _, Ordering::SeqCst);
+}
+
+/// Removes the registered boot services.
+pub fn unregister_global() {
+    GLOBAL_BOOT_SERVICES.store(ptr::null_mut(), Ordering::SeqCst);
+}
+
+/// Returns the registered boot services, if any.
+pub fn global() -> Option<&'static BootServices> {
+    // This is safe, because only `'static` references can be registered.
+    unsafe { GLOBAL_BOOT_SERVICES.load(Ordering::SeqCst).as_ref() }
+}
+
+/// Checks that the descriptors of a memory map returned by `GetMemoryMap` can be accessed.
+///
+/// Returns `Error::IncompatibleVersion` if there is no known layout for the descriptors.
+fn check_descriptors(memory_map: &MemoryMap) -> Result<(), Error> {
+    if memory_map.descriptor_size < size_of::<MemoryDescriptor>() {
+        return Err(quirk::report(Quirk::UndersizedDescriptor {
+            size: memory_map.descriptor_size,
+        }));
+    }
+
+    if memory_map.version < MEMORY_DESCRIPTOR_VERSION {
+        return Err(Error::IncompatibleVersion);
+    }
+
+    Ok(())
+}
+
+/// The task priority level of normal applications.
+pub const TPL_APPLICATION: usize = 4;
+/// The task priority level of most notification functions.
+pub const TPL_CALLBACK: usize = 8;
+/// The task priority level of notifications that must run quickly.
+pub const TPL_NOTIFY: usize = 16;
+/// The highest task priority level, at which interrupts are disabled.
+pub const TPL_HIGH_LEVEL: usize = 31;
+
+/// Opens a protocol like `HandleProtocol` does.
+pub const OPEN_PROTOCOL_BY_HANDLE_PROTOCOL: u32 = 0x01;
+/// Opens a protocol without tracking the agent, used by drivers to get a protocol interface.
+pub const OPEN_PROTOCOL_GET_PROTOCOL: u32 = 0x02;
+/// Tests whether a protocol is installed on a handle without returning the interface.
+pub const OPEN_PROTOCOL_TEST_PROTOCOL: u32 = 0x04;
+/// Opens a protocol on behalf of a child controller.
+pub const OPEN_PROTOCOL_BY_CHILD_CONTROLLER: u32 = 0x08;
+/// Opens a protocol for a driver that manages the controller.
+pub const OPEN_PROTOCOL_BY_DRIVER: u32 = 0x10;
+/// Opens a protocol exclusively, disconnecting other agents if necessary.
+pub const OPEN_PROTOCOL_EXCLUSIVE: u32 = 0x20;
+
+bitflags! {
+    /// The type of an event.
+    pub struct EventType: u32 {
+        /// The event is a timer event that can be passed to `SetTimer`.
+        const TIMER = 0x8000_0000;
+        /// The event is allocated from runtime memory.
+        const RUNTIME = 0x4000_0000;
+        /// The notification function is queued whenever the event is waited on or checked.
+        const NOTIFY_WAIT = 0x0000_0100;
+        /// The notification function is queued whenever the event is signaled.
+        const NOTIFY_SIGNAL = 0x0000_0200;
+        /// The event is signaled when `ExitBootServices` is called.
+        const SIGNAL_EXIT_BOOT_SERVICES = 0x0000_0201;
+        /// The event is signaled when `SetVirtualAddressMap` is called.
+        const SIGNAL_VIRTUAL_ADDRESS_CHANGE = 0x6000_0202;
+    }
+}
+
+/// A function that is called when an event is signaled or checked.
+pub type EventNotify = eficall! { fn(Event: Event, Context: usize) };
+
+/// The type of time that is specified in `SetTimer`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum TimerDelay {
+    /// The timer is cancelled.
+    Cancel,
+    /// The event is signaled periodically.
+    Periodic,
+    /// The event is signaled once.
+    Relative,
+}
+
 /// Indicates whether Interface is supplied in native form.
 #[repr(C)]
 pub enum InterfaceType {
@@ -24,6 +149,7 @@ pub enum InterfaceType {
 }
 
 /// Specifies which handle(s) are to be returned.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(C)]
 pub enum LocateSearchType {
     /// Retrieve all the handles in the handle database.
@@ -40,6 +166,17 @@ pub enum LocateSearchType {
     ByProtocol,
 }
 
+/// Specifies where `AllocatePages` places the allocated pages.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AllocateType {
+    /// Any available range of pages.
+    AnyPages,
+    /// Any available range of pages whose last byte is at or below the address.
+    MaxAddress(PhysicalAddress),
+    /// The range of pages starting at the address.
+    Address(PhysicalAddress),
+}
+
 /// Contains a table header and pointers to all of the boot services.
 #[repr(C)]
 pub struct BootServices {
@@ -50,177 +187,219 @@ pub struct BootServices {
     /// that the contents of the EFI Boot Services Table are valid.
     pub Hdr: TableHeader,
     /// Raises the task priority level.
-    RaiseTpl: extern "win64" fn(NewTpl: usize) -> usize,
+    pub(crate) RaiseTpl: eficall! { fn(NewTpl: usize) -> usize },
     /// Restores/lowers the task priority level.
-    RestoreTpl: extern "win64" fn(OldTpl: usize),
+    pub(crate) RestoreTpl: eficall! { fn(OldTpl: usize) },
     /// Allocates pages of a particular type.
-    AllocatePages: extern "win64" fn(
+    pub(crate) AllocatePages: eficall! { fn(
         AllocType: usize,
         MemoryType: MemoryType,
         Pages: usize,
         Memory: &mut PhysicalAddress,
-    ) -> Status,
+    ) -> Status },
     /// Frees allocated pages.
-    FreePages: extern "win64" fn(Memory: PhysicalAddress, Pages: usize) -> Status,
+    pub(crate) FreePages: eficall! { fn(Memory: PhysicalAddress, Pages: usize) -> Status },
     /// Returns the current boot services memory map and memory map key.
-    GetMemoryMap: extern "win64" fn(
+    pub(crate) GetMemoryMap: eficall! { fn(
         MemoryMapSize: &mut usize,
         MemoryMap: *mut MemoryDescriptor,
         MapKey: &mut usize,
         DescriptorSize: &mut usize,
         DescriptorVersion: &mut u32,
-    ) -> Status,
+    ) -> Status },
     /// Allocates a pool of a particular type.
-    AllocatePool:
-        extern "win64" fn(PoolType: MemoryType, Size: usize, Buffer: &mut usize) -> Status,
+    pub(crate) AllocatePool: eficall! { fn(
+        PoolType: MemoryType,
+        Size: usize,
+        Buffer: &mut usize,
+    ) -> Status },
     /// Frees allocated pool.
-    FreePool: extern "win64" fn(Buffer: usize) -> Status,
+    pub(crate) FreePool: eficall! { fn(Buffer: usize) -> Status },
     /// Creates a general-purpose event structure.
-    CreateEvent: extern "win64" fn(),
+    pub(crate) CreateEvent: eficall! { fn(
+        Type: EventType,
+        NotifyTpl: usize,
+        NotifyFunction: Option<EventNotify>,
+        NotifyContext: usize,
+        Event: &mut Event,
+    ) -> Status },
     /// Sets an event to be signaled at a particular time.
-    SetTimer: extern "win64" fn(),
+    pub(crate) SetTimer: eficall! { fn(
+        Event: Event,
+        Type: TimerDelay,
+        TriggerTime: u64,
+    ) -> Status },
     /// Stops execution until an event is signaled.
-    WaitForEvent:
-        extern "win64" fn(NumberOfEvents: usize, Event: *const Event, Index: &mut usize) -> Status,
+    pub(crate) WaitForEvent: eficall! { fn(
+        NumberOfEvents: usize,
+        Event: *const Event,
+        Index: &mut usize,
+    ) -> Status },
     /// Signals an event.
-    SignalEvent: extern "win64" fn(),
+    pub(crate) SignalEvent: eficall! { fn(Event: Event) -> Status },
     /// Closes and frees an event structure.
-    CloseEvent: extern "win64" fn(),
+    pub(crate) CloseEvent: eficall! { fn(Event: Event) -> Status },
     /// Checks whether an event is in the signaled state.
-    CheckEvent: extern "win64" fn(),
+    pub(crate) CheckEvent: eficall! { fn(Event: Event) -> Status },
     /// Installs a protocol interface on a device handle.
-    InstallProtocolInterface: extern "win64" fn(
-        Handle: &mut Handle,
+    pub(crate) InstallProtocolInterface: eficall! { fn(
+        Handle: &mut OptionalHandle,
         Protocol: &Guid,
         InterfaceType: InterfaceType,
         Interface: usize,
-    ) -> Status,
+    ) -> Status },
     /// Reinstalls a protocol interface on a device handle.
-    ReinstallProtocolInterface: extern "win64" fn(),
+    pub(crate) ReinstallProtocolInterface: eficall! { fn() },
     /// Removes a protocol interface from a device handle.
-    UninstallProtocolInterface:
-        extern "win64" fn(Handle: Handle, Protocol: &Guid, Interface: usize) -> Status,
+    pub(crate) UninstallProtocolInterface: eficall! { fn(
+        Handle: Handle,
+        Protocol: &Guid,
+        Interface: usize,
+    ) -> Status },
     /// Queries a handle to determine if it supports a specified protocol.
-    HandleProtocol:
-        extern "win64" fn(Handle: Handle, Protocol: &Guid, Interface: &mut usize) -> Status,
+    pub(crate) HandleProtocol: eficall! { fn(
+        Handle: Handle,
+        Protocol: &Guid,
+        Interface: &mut usize,
+    ) -> Status },
     /// Reserved. Must be NULL.
-    _rsvd: usize,
+    pub(crate) _rsvd: usize,
     /// Registers an event that is to be signaled whenever an interface is
     /// installed for a specified protocol.
-    RegisterProtocolNotify: extern "win64" fn(),
+    pub(crate) RegisterProtocolNotify: eficall! { fn() },
     /// Returns an array of handles that support a specified protocol.
-    LocateHandle: extern "win64" fn(
+    pub(crate) LocateHandle: eficall! { fn(
         SearchType: LocateSearchType,
         Protocol: &Guid,
         SearchKey: usize,
         BufferSize: &mut usize,
         Buffer: *mut Handle,
-    ) -> Status,
+    ) -> Status },
     /// Locates all devices on a device path that support a specified
     /// protocol and returns the handle to the device that is closest to
     /// the path.
-    LocateDevicePath: extern "win64" fn(),
+    pub(crate) LocateDevicePath: eficall! { fn() },
     /// Adds, updates, or removes a configuration table from the EFI
     /// System Table.
-    InstallConfigurationTable: extern "win64" fn(),
+    pub(crate) InstallConfigurationTable: eficall! { fn() },
     /// Loads an EFI image into memory.
-    LoadImage: extern "win64" fn(
+    pub(crate) LoadImage: eficall! { fn(
         BootPolicy: bool,
         ParentImageHandle: Handle,
-        DevicePath: usize, /*TODO*/
+        DevicePath: *const DevicePath,
         SourceBuffer: *const u8,
         SourceSize: usize,
-        ImageHandle: &mut Handle,
-    ) -> Status,
+        ImageHandle: &mut OptionalHandle,
+    ) -> Status },
     /// Transfers control to a loaded image’s entry point.
-    StartImage: extern "win64" fn(
+    pub(crate) StartImage: eficall! { fn(
         ImageHandle: Handle,
         ExitDataSize: &mut usize,
         ExitData: &mut *mut u16,
-    ) -> Status,
+    ) -> Status },
     /// Exits the image’s entry point.
-    Exit: extern "win64" fn(
+    pub(crate) Exit: eficall! { fn(
         ImageHandle: Handle,
         ExitStatus: isize,
         ExitDataSize: usize,
         ExitData: *const u16,
-    ) -> Status,
+    ) -> Status },
     /// Unloads an image.
-    UnloadImage: extern "win64" fn(),
+    pub(crate) UnloadImage: eficall! { fn(ImageHandle: Handle) -> Status },
     /// Terminates boot services.
-    ExitBootServices: extern "win64" fn(ImageHandle: Handle, MapKey: usize) -> Status,
+    pub(crate) ExitBootServices: eficall! { fn(ImageHandle: Handle, MapKey: usize) -> Status },
     /// Returns a monotonically increasing count for the platform.
-    GetNextMonotonicCount: extern "win64" fn(),
+    pub(crate) GetNextMonotonicCount: eficall! { fn() },
     /// Stalls the processor.
-    Stall: extern "win64" fn(Microseconds: usize) -> Status,
+    pub(crate) Stall: eficall! { fn(Microseconds: usize) -> Status },
     /// Resets and sets a watchdog timer used during boot services time.
-    SetWatchdogTimer: extern "win64" fn(
+    pub(crate) SetWatchdogTimer: eficall! { fn(
         Timeout: usize,
         WatchdogCode: u64,
         DataSize: usize,
         WatchdogData: *const u16,
-    ) -> Status,
+    ) -> Status },
     /// Uses a set of precedence rules to find the best set of drivers to
     /// manage a controller.
-    ConnectController: extern "win64" fn(),
+    pub(crate) ConnectController: eficall! { fn() },
     /// Informs a set of drivers to stop managing a controller.
-    DisconnectController: extern "win64" fn(),
+    pub(crate) DisconnectController: eficall! { fn() },
     /// Adds elements to the list of agents consuming a protocol interface.
-    OpenProtocol: extern "win64" fn(
+    pub(crate) OpenProtocol: eficall! { fn(
         Handle: Handle,
         Protocol: &Guid,
         Interface: &mut usize,
         AgentHandle: Handle,
-        ControllerHandle: Handle,
+        ControllerHandle: OptionalHandle,
         Attributes: u32,
-    ) -> Status,
+    ) -> Status },
     /// Removes elements from the list of agents consuming a protocol
     /// interface.
-    CloseProtocol: extern "win64" fn(),
+    pub(crate) CloseProtocol: eficall! { fn() },
     /// Retrieve the list of agents that are currently consuming a
     /// protocol interface.
-    OpenProtocolInformation: extern "win64" fn(),
+    pub(crate) OpenProtocolInformation: eficall! { fn() },
     /// Retrieves the list of protocols installed on a handle. The return
     /// buffer is automatically allocated.
-    ProtocolsPerHandle: extern "win64" fn(
+    pub(crate) ProtocolsPerHandle: eficall! { fn(
         Handle: Handle,
-        ProtocolBuffer: *mut Guid,
-        ProtocolBufferCount: usize,
-    ) -> Status,
+        ProtocolBuffer: &mut *mut *const Guid,
+        ProtocolBufferCount: &mut usize,
+    ) -> Status },
     /// Retrieves the list of handles from the handle database that meet
     /// the search criteria. The return buffer is automatically allocated.
-    LocateHandleBuffer: extern "win64" fn(
+    pub(crate) LocateHandleBuffer: eficall! { fn(
         SearchType: LocateSearchType,
-        Protocol: &Guid,
+        Protocol: *const Guid,
         SearchKey: usize,
         NoHandles: &mut usize,
         Buffer: &mut *mut Handle,
-    ),
+    ) -> Status },
     /// Finds the first handle in the handle database the supports the requested protocol.
-    LocateProtocol:
-        extern "win64" fn(Protocol: &Guid, Registration: usize, Interface: &mut usize) -> Status,
+    pub(crate) LocateProtocol: eficall! { fn(
+        Protocol: &Guid,
+        Registration: usize,
+        Interface: &mut usize,
+    ) -> Status },
     /// Installs one or more protocol interfaces onto a handle.
-    InstallMultipleProtocolInterfaces: extern "win64" fn(),
+    pub(crate) InstallMultipleProtocolInterfaces: eficall! { fn() },
     /// Uninstalls one or more protocol interfaces from a handle.
-    UninstallMultipleProtocolInterfaces: extern "win64" fn(),
+    pub(crate) UninstallMultipleProtocolInterfaces: eficall! { fn() },
     /// Computes and returns a 32-bit CRC for a data buffer.
-    CalculateCrc32: extern "win64" fn(),
+    pub(crate) CalculateCrc32: eficall! { fn() },
     /// Copies the contents of one buffer to another buffer.
-    CopyMem: extern "win64" fn(),
+    pub(crate) CopyMem: eficall! { fn() },
     /// Fills a buffer with a specified value.
-    SetMem: extern "win64" fn(),
+    pub(crate) SetMem: eficall! { fn() },
     /// Creates an event structure as part of an event group.
-    CreateEventEx: extern "win64" fn(),
+    ///
+    /// This function was added in UEFI 2.0.
+    pub(crate) CreateEventEx: eficall! { fn(
+        Type: EventType,
+        NotifyTpl: usize,
+        NotifyFunction: Option<EventNotify>,
+        NotifyContext: usize,
+        EventGroup: *const Guid,
+        Event: &mut Event,
+    ) -> Status },
 }
 
 impl BootServices {
     /// Stops execution until an event is signaled.
+    ///
+    /// If the firmware returns an index that does not refer to one of the events, the quirk is
+    /// reported and `Error::WrongEventIndex` is returned in the permissive quirk mode.
     pub fn wait_for_events<'a>(&self, events: &'a [Event]) -> Result<&'a Event, Error> {
         let mut index = 0;
 
         (self.WaitForEvent)(events.len(), events.as_ptr(), &mut index)?;
 
-        Ok(events.get(index).expect("UEFI returned the wrong index."))
+        events.get(index).ok_or_else(|| {
+            quirk::report(Quirk::WrongEventIndex {
+                index,
+                count: events.len(),
+            })
+        })
     }
 
     /// Stops execution until an event is signaled.
@@ -229,7 +408,196 @@ impl BootServices {
 
         (self.WaitForEvent)(1, event, &mut index)?;
 
-        assert!(index == 0, "UEFI returned the wrong index.");
+        // The only event was signaled, regardless of the index.
+        if index != 0 {
+            quirk::report(Quirk::WrongEventIndex { index, count: 1 });
+        }
+
+        Ok(())
+    }
+
+    /// Creates an event.
+    ///
+    /// If a notification function is given, it is called with `notify_context` at the task
+    /// priority level `notify_tpl`.
+    ///
+    /// # Safety
+    /// The notification function must be able to handle the context and both must stay valid
+    /// until the event is closed.
+    pub unsafe fn create_event(
+        &self,
+        event_type: EventType,
+        notify_tpl: usize,
+        notify_function: Option<EventNotify>,
+        notify_context: usize,
+    ) -> Result<Event, Error> {
+        let mut event = Event(0);
+
+        (self.CreateEvent)(
+            event_type,
+            notify_tpl,
+            notify_function,
+            notify_context,
+            &mut event,
+        )?;
+
+        Ok(event)
+    }
+
+    /// Creates an event in an event group.
+    ///
+    /// All events in the group are signaled when one of them is signaled. If `event_group` is
+    /// `None`, this behaves like `create_event`.
+    ///
+    /// Returns `Error::Unsupported` if the firmware predates UEFI 2.0, which introduced this
+    /// function.
+    ///
+    /// # Safety
+    /// See `create_event`.
+    pub unsafe fn create_event_ex(
+        &self,
+        event_type: EventType,
+        notify_tpl: usize,
+        notify_function: Option<EventNotify>,
+        notify_context: usize,
+        event_group: Option<&Guid>,
+    ) -> Result<Event, Error> {
+        if !self
+            .Hdr
+            .provides(SYSTEM_TABLE_REVISION_2_00, self, &self.CreateEventEx)
+        {
+            return Err(Error::Unsupported);
+        }
+
+        let mut event = Event(0);
+
+        let event_group = match event_group {
+            Some(event_group) => event_group as *const Guid,
+            None => ptr::null(),
+        };
+
+        (self.CreateEventEx)(
+            event_type,
+            notify_tpl,
+            notify_function,
+            notify_context,
+            event_group,
+            &mut event,
+        )?;
+
+        Ok(event)
+    }
+
+    /// Creates a timer event without a notification function.
+    pub fn create_timer_event(&self) -> Result<Event, Error> {
+        // This is safe, because there is no notification function.
+        unsafe { self.create_event(EventType::TIMER, TPL_APPLICATION, None, 0) }
+    }
+
+    /// Sets a timer event to be signaled after `trigger_time` in units of 100 nanoseconds.
+    pub fn set_timer(
+        &self,
+        event: &Event,
+        timer_type: TimerDelay,
+        trigger_time: u64,
+    ) -> Result<(), Error> {
+        (self.SetTimer)(*event, timer_type, trigger_time)?;
+
+        Ok(())
+    }
+
+    /// Signals an event.
+    pub fn signal_event(&self, event: &Event) -> Result<(), Error> {
+        (self.SignalEvent)(*event)?;
+
+        Ok(())
+    }
+
+    /// Closes and frees an event.
+    pub fn close_event(&self, event: Event) -> Result<(), Error> {
+        (self.CloseEvent)(event)?;
+
+        Ok(())
+    }
+
+    /// Checks whether an event is in the signaled state.
+    ///
+    /// Returns true if the event was signaled. The signaled state is cleared.
+    pub fn check_event(&self, event: &Event) -> Result<bool, Error> {
+        let status = (self.CheckEvent)(*event);
+
+        if status == Error::NotReady.into() {
+            return Ok(false);
+        }
+
+        status?;
+
+        Ok(true)
+    }
+
+    /// Stalls the processor for at least the given number of microseconds.
+    pub fn stall(&self, microseconds: usize) -> Result<(), Error> {
+        (self.Stall)(microseconds)?;
+
+        Ok(())
+    }
+
+    /// Sets the watchdog timer to reset the platform after `timeout` seconds.
+    ///
+    /// A timeout of 0 disables the watchdog. The watchdog codes up to 0xffff are reserved for
+    /// the firmware. `data` is an optional null terminated string that may be followed by binary
+    /// data and is logged when the watchdog fires.
+    pub fn set_watchdog_timer(
+        &self,
+        timeout: usize,
+        watchdog_code: u64,
+        data: Option<&[u16]>,
+    ) -> Result<(), Error> {
+        let (data_size, data) = match data {
+            Some(data) => (data.len() * size_of::<u16>(), data.as_ptr()),
+            None => (0, ptr::null()),
+        };
+
+        (self.SetWatchdogTimer)(timeout, watchdog_code, data_size, data)?;
+
+        Ok(())
+    }
+
+    /// Installs a protocol interface on a device handle.
+    ///
+    /// If `handle` is `None`, a new handle is created.
+    /// Returns the handle the interface was installed on.
+    ///
+    /// # Safety
+    /// `interface` must point to a valid instance of the protocol identified by `protocol`
+    /// and must stay valid until the interface is uninstalled.
+    pub unsafe fn install_protocol_interface(
+        &self,
+        handle: Option<Handle>,
+        protocol: &Guid,
+        interface: *const u8,
+    ) -> Result<Handle, Error> {
+        let mut handle = handle;
+
+        (self.InstallProtocolInterface)(
+            &mut handle,
+            protocol,
+            InterfaceType::Native,
+            interface as usize,
+        )?;
+
+        // The firmware always returns a handle on success.
+        handle.ok_or(Error::Unsupported)
+    }
+
+    /// Removes a protocol interface from a device handle.
+    pub fn uninstall_protocol_interface(
+        &self,
+        handle: Handle,
+        protocol: &Guid,
+        interface: *const u8,
+    ) -> Result<(), Error> {
+        (self.UninstallProtocolInterface)(handle, protocol, interface as usize)?;
 
         Ok(())
     }
@@ -247,6 +615,26 @@ impl BootServices {
         Ok(address.0 as *const u8)
     }
 
+    /// Allocates pages of a particular type at the location given by `allocate_type`.
+    ///
+    /// Returns `Error::NotFound` if the requested pages are not available.
+    pub fn allocate_pages_with(
+        &self,
+        allocate_type: AllocateType,
+        memory_type: MemoryType,
+        pages: usize,
+    ) -> Result<PhysicalAddress, Error> {
+        let (allocate_type, mut address) = match allocate_type {
+            AllocateType::AnyPages => (0, PhysicalAddress::default()),
+            AllocateType::MaxAddress(address) => (1, address),
+            AllocateType::Address(address) => (2, address),
+        };
+
+        (self.AllocatePages)(allocate_type, memory_type, pages, &mut address)?;
+
+        Ok(address)
+    }
+
     /// Frees allocated pages.
     pub fn free_pages(&self, memory: *const u8, pages: usize) -> Result<(), Error> {
         (self.FreePages)(PhysicalAddress(memory as u64), pages)?;
@@ -255,41 +643,87 @@ impl BootServices {
     }
 
     /// Returns the current boot services memory map and memory map key.
+    ///
+    /// The buffer is allocated with room for `DEFAULT_MEMORY_MAP_SLACK` additional descriptors.
     pub fn get_memory_map(&self, memory_type: MemoryType) -> Result<MemoryMap, Error> {
-        // The buffer will be allocated on whole pages, that makes it easier to reuse the memory later on.
-        // Try one page as a buffer size first.
+        self.get_memory_map_with_slack(memory_type, DEFAULT_MEMORY_MAP_SLACK)
+    }
+
+    /// Returns the current boot services memory map and memory map key.
+    ///
+    /// The required size is queried first and the buffer is allocated with room for `slack`
+    /// additional descriptors, since allocating the buffer can itself add descriptors to the map.
+    /// The buffer is only reallocated if that room is not sufficient. If the memory map still
+    /// does not fit after `MEMORY_MAP_ATTEMPTS` requests, `Error::BufferTooSmall` is returned.
+    pub fn get_memory_map_with_slack(
+        &self,
+        memory_type: MemoryType,
+        slack: usize,
+    ) -> Result<MemoryMap, Error> {
+        self.get_memory_map_with_slack_pages(memory_type, slack, 0)
+    }
+
+    /// Returns the current boot services memory map, allocating `slack_pages` additional pages.
+    fn get_memory_map_with_slack_pages(
+        &self,
+        memory_type: MemoryType,
+        slack: usize,
+        slack_pages: usize,
+    ) -> Result<MemoryMap, Error> {
         let mut memory_map = MemoryMap {
-            buffer: self.allocate_pages(memory_type, 1)? as *const MemoryDescriptor,
-            alloc_size: 1,
-            size: PAGE_SIZE,
+            buffer: ptr::null(),
+            alloc_size: 0,
+            size: 0,
             key: 0,
             descriptor_size: 0,
             version: 0,
+            sorted: false,
         };
+        let mut attempt = 1;
 
         loop {
-            if (self.GetMemoryMap)(
+            let status = (self.GetMemoryMap)(
                 &mut memory_map.size,
                 memory_map.buffer as *mut MemoryDescriptor,
                 &mut memory_map.key,
                 &mut memory_map.descriptor_size,
                 &mut memory_map.version,
-            ) == SUCCESS
-            {
+            );
+
+            if status == SUCCESS && !memory_map.buffer.is_null() {
                 break;
             }
 
-            memory_map.alloc_size = (memory_map.size / PAGE_SIZE) + 1;
+            if !memory_map.buffer.is_null() {
+                self.free_pages(memory_map.buffer as *const u8, memory_map.alloc_size)?;
+            }
 
-            self.free_pages(memory_map.buffer as *const u8, memory_map.alloc_size)?;
+            if status != Error::BufferTooSmall.into() && status != SUCCESS {
+                status?;
+            }
+
+            // The map keeps growing faster than the slack, so allocating more will not help.
+            if attempt == MEMORY_MAP_ATTEMPTS {
+                return Err(Error::BufferTooSmall);
+            }
+            attempt += 1;
+
+            // The size now holds the required size of the buffer.
+            let required_size = memory_map.size + slack * memory_map.descriptor_size;
+
+            memory_map.alloc_size =
+                ((required_size + PAGE_SIZE - 1) / PAGE_SIZE).max(1) + slack_pages;
             memory_map.buffer =
                 self.allocate_pages(memory_type, memory_map.alloc_size)? as *const MemoryDescriptor;
+            memory_map.size = memory_map.alloc_size * PAGE_SIZE;
         }
 
-        assert!(
-            memory_map.descriptor_size >= size_of::<MemoryDescriptor>(),
-            "The size of the memory descriptor is smaller than the standard says."
-        );
+        if let Err(error) = check_descriptors(&memory_map) {
+            // The descriptors cannot be accessed, so the memory map cannot be used.
+            self.free_pages(memory_map.buffer as *const u8, memory_map.alloc_size)?;
+
+            return Err(error);
+        }
 
         Ok(memory_map)
     }
@@ -310,6 +744,86 @@ impl BootServices {
         Ok(())
     }
 
+    /// Loads an EFI image into memory.
+    ///
+    /// If `source` is `None`, the image is loaded from `device_path`.
+    /// `boot_policy` indicates whether the request originates from the boot manager.
+    ///
+    /// Returns the handle of the loaded image.
+    pub fn load_image(
+        &self,
+        boot_policy: bool,
+        parent_image_handle: Handle,
+        device_path: &DevicePath,
+        source: Option<&[u8]>,
+    ) -> Result<Handle, Error> {
+        let mut image_handle = None;
+
+        let (source_buffer, source_size) = match source {
+            Some(source) => (source.as_ptr(), source.len()),
+            None => (ptr::null(), 0),
+        };
+
+        (self.LoadImage)(
+            boot_policy,
+            parent_image_handle,
+            device_path,
+            source_buffer,
+            source_size,
+            &mut image_handle,
+        )?;
+
+        // The firmware always returns a handle on success.
+        image_handle.ok_or(Error::LoadError)
+    }
+
+    /// Loads an EFI image from a buffer that does not correspond to a device path.
+    ///
+    /// Returns the handle of the loaded image.
+    pub fn load_image_from_buffer(
+        &self,
+        parent_image_handle: Handle,
+        source: &[u8],
+    ) -> Result<Handle, Error> {
+        let mut image_handle = None;
+
+        (self.LoadImage)(
+            false,
+            parent_image_handle,
+            ptr::null(),
+            source.as_ptr(),
+            source.len(),
+            &mut image_handle,
+        )?;
+
+        // The firmware always returns a handle on success.
+        image_handle.ok_or(Error::LoadError)
+    }
+
+    /// Transfers control to the entry point of a loaded image.
+    ///
+    /// Returns the exit status of the image, together with the exit data it passed to `Exit`.
+    /// If the image could not be started, the status is the error of `StartImage` instead.
+    pub fn start_image(&self, image_handle: Handle) -> ImageExit {
+        let mut data_size = 0;
+        let mut data = ptr::null_mut();
+
+        let status = (self.StartImage)(image_handle, &mut data_size, &mut data);
+
+        ImageExit {
+            status,
+            // This is safe, because the firmware allocates the exit data from pool memory.
+            data: unsafe { PoolBuffer::from_raw(data, data_size / size_of::<u16>()) },
+        }
+    }
+
+    /// Unloads an image that was loaded, but not started, or that supports unloading.
+    pub fn unload_image(&self, image_handle: Handle) -> Result<(), Error> {
+        (self.UnloadImage)(image_handle)?;
+
+        Ok(())
+    }
+
     /// Terminates boot services if a memory map and its key is already available.
     pub fn exit_boot_services_with_map(
         &self,
@@ -322,71 +836,243 @@ impl BootServices {
     }
 
     /// Terminates boot services returning the memory map.
-    pub fn exit_boot_services(&self, image_handle: Handle) -> Result<MemoryMap, Error> {
-        let image_data = self.get_loaded_image_data(image_handle);
+    ///
+    /// The memory map buffer is allocated with `DEFAULT_EXIT_SLACK_PAGES` additional pages and
+    /// exiting is attempted at most `DEFAULT_EXIT_ATTEMPTS` times.
+    /// The returned error records whether `GetMemoryMap` or `ExitBootServices` failed.
+    /// Use `ExitBootServices` to change these limits or to run code right before boot services
+    /// are exited.
+    pub fn exit_boot_services(&self, image_handle: Handle) -> Result<MemoryMap, NuefilError> {
+        self.exit_boot_services_inner(image_handle, ExitConfig::DEFAULT, &mut [])
+    }
 
-        let memory_type = if let Ok(image_data) = image_data {
-            image_data.ImageDataType
-        } else {
-            // The data memory type for applications that would call exit_boot_services is assumed to always be `LoaderData`, if it cannot be determined.
-            NamedMemoryType::LoaderData.into()
-        };
+    /// Terminates boot services, calling `callbacks` between getting the final memory map and
+    /// calling `ExitBootServices`.
+    fn exit_boot_services_inner(
+        &self,
+        image_handle: Handle,
+        config: ExitConfig,
+        callbacks: &mut [Option<&mut dyn FnMut(&BootServices, &MemoryMap)>],
+    ) -> Result<MemoryMap, NuefilError> {
+        let memory_type = config.memory_type.unwrap_or_else(|| {
+            match self.get_loaded_image_data(image_handle) {
+                Ok(image_data) => image_data.ImageDataType,
+                // The data memory type for applications that would call exit_boot_services is assumed to always be `LoaderData`, if it cannot be determined.
+                Err(_) => MemoryType::LoaderData,
+            }
+        });
+
+        let mut memory_map = self
+            .get_memory_map_with_slack_pages(memory_type, config.slack, config.slack_pages)
+            .context("GetMemoryMap")?;
+
+        for callback in callbacks.iter_mut().filter_map(Option::as_mut) {
+            callback(self, &memory_map);
+        }
+
+        let mut attempt = 1;
 
-        let mut memory_map = self.get_memory_map(memory_type)?;
-
-        match self.exit_boot_services_with_map(image_handle, memory_map.key) {
-            Ok(_) => Ok(()),
-            Err(_) => loop {
-                // If the call to ExitBootServices failed, the memory map was invalid.
-                // We need to try get a new memory map, but we cannot allocate anymore
-                // after trying to call ExitBootServices once.
-                if (self.GetMemoryMap)(
-                    &mut memory_map.size,
-                    memory_map.buffer as *mut MemoryDescriptor,
-                    &mut memory_map.key,
-                    &mut memory_map.descriptor_size,
-                    &mut memory_map.version,
-                ) != SUCCESS
-                {
-                    // If the call to GetMemoryMap failed, there is no way to get another buffer.
-                    // Therefore we have to abort with an error.
-                    break Err(Error::Aborted);
-                } else if self
-                    .exit_boot_services_with_map(image_handle, memory_map.key)
-                    .is_ok()
-                {
-                    // If the call succeeded, we're done.
-                    break Ok(());
+        loop {
+            match self.exit_boot_services_with_map(image_handle, memory_map.key) {
+                // If the call succeeded, we're done.
+                Ok(()) => break,
+                // The memory map changed since it was retrieved, so another attempt is needed.
+                Err(Error::InvalidParameter) if attempt < config.max_attempts => (),
+                Err(Error::InvalidParameter) => {
+                    return Err(NuefilError::new("ExitBootServices", Error::Aborted)
+                        .with_parameter("Attempts", attempt));
                 }
-            },
-        }?;
+                Err(error) => {
+                    return Err(NuefilError::new("ExitBootServices", error)
+                        .with_parameter("MapKey", memory_map.key));
+                }
+            }
+
+            attempt += 1;
+
+            // No memory can be allocated after the first call to ExitBootServices, so the new
+            // memory map has to fit into the existing buffer.
+            memory_map.size = memory_map.alloc_size * PAGE_SIZE;
+
+            let status = (self.GetMemoryMap)(
+                &mut memory_map.size,
+                memory_map.buffer as *mut MemoryDescriptor,
+                &mut memory_map.key,
+                &mut memory_map.descriptor_size,
+                &mut memory_map.version,
+            );
+
+            if status == Error::BufferTooSmall.into() {
+                // The size now holds the required size of the buffer.
+                return Err(NuefilError::new("GetMemoryMap", Error::BufferTooSmall)
+                    .with_parameter("MemoryMapSize", memory_map.size));
+            }
+
+            status
+                .with_value(())
+                .ignore_warning()
+                .context("GetMemoryMap")?;
+
+            // The firmware reports the descriptor size and version again, so they are checked
+            // again before the descriptors are accessed below.
+            check_descriptors(&memory_map).context("GetMemoryMap")?;
+        }
+
+        // The boot services cannot be used to free memory anymore.
+        unregister_global();
 
         // Boot services memory can be treated as conventional memory after calling `ExitBootServices`.
         for entry in memory_map.iter_mut() {
-            if entry.Type == NamedMemoryType::BootServicesCode.into()
-                || entry.Type == NamedMemoryType::BootServicesData.into()
+            if entry.Type == MemoryType::BootServicesCode
+                || entry.Type == MemoryType::BootServicesData
             {
-                entry.Type = NamedMemoryType::ConventionalMemory.into();
+                entry.Type = MemoryType::ConventionalMemory;
             }
         }
 
         Ok(memory_map)
     }
 
+    /// Returns the handles that match the search criteria.
+    ///
+    /// `protocol` is required for `LocateSearchType::ByProtocol` and `search_key` is the
+    /// registration for `LocateSearchType::ByRegisterNotify`.
+    /// Returns `Error::NotFound` if no handle matches.
+    pub fn locate_handle_buffer(
+        &self,
+        search_type: LocateSearchType,
+        protocol: Option<&Guid>,
+        search_key: usize,
+    ) -> Result<PoolBuffer<Handle>, Error> {
+        let protocol = match protocol {
+            Some(protocol) => protocol as *const Guid,
+            None => ptr::null(),
+        };
+
+        let mut count = 0;
+        let mut buffer = ptr::null_mut();
+
+        (self.LocateHandleBuffer)(search_type, protocol, search_key, &mut count, &mut buffer)?;
+
+        // This is safe, because the firmware allocated the buffer with `count` handles.
+        unsafe { PoolBuffer::from_raw(buffer, count) }.ok_or(Error::NotFound)
+    }
+
+    /// Returns all handles in the handle database.
+    pub fn all_handles(&self) -> Result<PoolBuffer<Handle>, Error> {
+        self.locate_handle_buffer(LocateSearchType::AllHandles, None, 0)
+    }
+
+    /// Returns the GUIDs of the protocols installed on a handle.
+    pub fn protocols_per_handle(&self, handle: Handle) -> Result<PoolBuffer<&Guid>, Error> {
+        let mut count = 0;
+        let mut buffer = ptr::null_mut();
+
+        (self.ProtocolsPerHandle)(handle, &mut buffer, &mut count)?;
+
+        // This is safe, because the firmware allocated the buffer with `count` GUID pointers,
+        // which stay valid while boot services are available.
+        unsafe { PoolBuffer::from_raw(buffer as *mut &Guid, count) }.ok_or(Error::NotFound)
+    }
+
+    /// Returns true if the protocol is installed on the handle.
+    ///
+    /// This opens the protocol with `OPEN_PROTOCOL_TEST_PROTOCOL`, so no interface is returned
+    /// and nothing has to be closed afterwards.
+    pub fn supports_protocol(&self, handle: Handle, protocol: &Guid) -> bool {
+        let mut interface = 0;
+
+        (self.OpenProtocol)(
+            handle,
+            protocol,
+            &mut interface,
+            handle,
+            None,
+            OPEN_PROTOCOL_TEST_PROTOCOL,
+        ) == SUCCESS
+    }
+
+    /// Returns the first handle in the handle database the protocol is installed on.
+    ///
+    /// This is useful to detect whether the firmware provides a protocol at all.
+    pub fn first_handle_supporting(&self, protocol: &Guid) -> Option<Handle> {
+        let handles = self
+            .locate_handle_buffer(LocateSearchType::ByProtocol, Some(protocol), 0)
+            .ok()?;
+        let first = handles.first().cloned();
+
+        // There is no way to report the error here, since the handle was already found.
+        let _ = handles.free(self);
+
+        first
+    }
+
+    /// Queries a handle to determine if it supports the protocol `P`.
+    ///
+    /// Returns the protocol interface, if it is supported.
+    pub fn handle_protocol<P: Protocol>(&self, handle: Handle) -> Result<&P, Error> {
+        // This is safe, because `Protocol` guarantees that the GUID identifies `P`.
+        unsafe { self.handle_protocol_raw(handle, &P::GUID) }
+    }
+
+    /// Finds the first protocol interface of type `P` in the handle database.
+    pub fn locate_protocol<P: Protocol>(&self) -> Result<&P, Error> {
+        // This is safe, because `Protocol` guarantees that the GUID identifies `P`.
+        unsafe { self.locate_protocol_raw(&P::GUID) }
+    }
+
+    /// Finds the first protocol interface in the handle database that supports the protocol.
+    ///
+    /// # Safety
+    /// `T` must be the interface type of the protocol identified by `protocol`.
+    pub unsafe fn locate_protocol_raw<T>(&self, protocol: &Guid) -> Result<&T, Error> {
+        let mut interface = 0;
+
+        (self.LocateProtocol)(protocol, 0, &mut interface)?;
+
+        if interface == 0 {
+            Err(Error::NotFound)
+        } else {
+            Ok(&*(interface as *const T))
+        }
+    }
+
+    /// Queries a handle to determine if it supports a specified protocol.
+    ///
+    /// Returns the protocol interface, if it is supported.
+    ///
+    /// # Safety
+    /// `T` must be the interface type of the protocol identified by `protocol`.
+    pub unsafe fn handle_protocol_raw<T>(
+        &self,
+        handle: Handle,
+        protocol: &Guid,
+    ) -> Result<&T, Error> {
+        let mut interface = 0;
+
+        (self.HandleProtocol)(handle, protocol, &mut interface)?;
+
+        if interface == 0 {
+            Err(Error::Unsupported)
+        } else {
+            Ok(&*(interface as *const T))
+        }
+    }
+
     /// Queries the UEFI firmware for information about a loaded image.
     pub fn get_loaded_image_data(&self, image_handle: Handle) -> Result<&LoadedImage, Error> {
         static PROTOCOL: Guid = LOADED_IMAGE_PROTOCOL_GUID;
 
         let mut result = 0;
         // Get exlusive access
-        let attributes = 0x20;
+        let attributes = OPEN_PROTOCOL_EXCLUSIVE;
 
         (self.OpenProtocol)(
             image_handle,
             &PROTOCOL,
             &mut result,
             image_handle,
-            Handle(0),
+            None,
             attributes,
         )?;
 
@@ -402,3 +1088,267 @@ impl BootServices {
         }
     }
 }
+
+/// The result of running an image with `BootServices::start_image`.
+#[derive(Debug)]
+pub struct ImageExit {
+    /// The exit status of the image.
+    status: Status,
+    /// The exit data of the image, if it passed any.
+    data: Option<PoolBuffer<u16>>,
+}
+
+impl ImageExit {
+    /// Returns the exit status of the image.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns the exit data of the image, if it passed any.
+    ///
+    /// The data starts with a null terminated description, which may be followed by binary data.
+    pub fn data(&self) -> Option<&[u16]> {
+        self.data.as_ref().map(|data| &data[..])
+    }
+
+    /// Returns the description at the start of the exit data, if the image passed any.
+    pub fn description(&self) -> Option<Lossy> {
+        self.data().map(Lossy::new)
+    }
+
+    /// Frees the exit data.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        match self.data {
+            Some(data) => data.free(boot_services),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Determines how boot services are exited.
+#[derive(Clone, Copy, Debug)]
+struct ExitConfig {
+    /// The memory type of the memory map buffer, if it was overridden.
+    memory_type: Option<MemoryType>,
+    /// The number of additional descriptors the memory map buffer has room for.
+    slack: usize,
+    /// The number of additional pages allocated for the memory map buffer.
+    slack_pages: usize,
+    /// The number of times exiting boot services is attempted.
+    max_attempts: usize,
+}
+
+impl ExitConfig {
+    /// The configuration used by `BootServices::exit_boot_services`.
+    const DEFAULT: ExitConfig = ExitConfig {
+        memory_type: None,
+        slack: DEFAULT_MEMORY_MAP_SLACK,
+        slack_pages: DEFAULT_EXIT_SLACK_PAGES,
+        max_attempts: DEFAULT_EXIT_ATTEMPTS,
+    };
+}
+
+/// The maximum number of callbacks that can be registered with `ExitBootServices::before_exit`.
+pub const MAX_EXIT_CALLBACKS: usize = 8;
+
+/// What remains available after boot services were exited.
+pub struct RuntimeView {
+    /// The runtime services, which stay available.
+    pub runtime_services: &'static RuntimeServices,
+    /// The configuration tables, such as the ACPI and SMBIOS tables.
+    pub config_tables: &'static [ConfigurationTable],
+    /// The memory map at the time boot services were exited.
+    ///
+    /// Boot services memory is reported as conventional memory.
+    pub memory_map: MemoryMap,
+}
+
+/// Exits boot services, running callbacks at the last moment boot services are available.
+///
+/// The callbacks run after the final memory map was retrieved and right before `ExitBootServices`
+/// is called. They can query information that is only available through boot services, such as
+/// the current graphics mode. They must not allocate or free memory, since that would change the
+/// memory map and require another attempt, for which the callbacks are not run again.
+///
+/// If the memory map changes before boot services are exited, the memory map is retrieved again
+/// into the same buffer, so the buffer is allocated with slack up front.
+pub struct ExitBootServices<'a> {
+    /// The system table of the image.
+    system_table: &'static SystemTable,
+    /// The handle of the image that exits boot services.
+    image_handle: Handle,
+    /// How boot services are exited.
+    config: ExitConfig,
+    /// The callbacks to run before exiting.
+    callbacks: [Option<&'a mut dyn FnMut(&BootServices, &MemoryMap)>; MAX_EXIT_CALLBACKS],
+}
+
+impl<'a> ExitBootServices<'a> {
+    /// Prepares exiting boot services for the given image.
+    pub fn new(system_table: &'static SystemTable, image_handle: Handle) -> ExitBootServices<'a> {
+        ExitBootServices {
+            system_table,
+            image_handle,
+            config: ExitConfig::DEFAULT,
+            callbacks: [None, None, None, None, None, None, None, None],
+        }
+    }
+
+    /// Sets the memory type of the memory map buffer.
+    ///
+    /// By default, the data memory type of the image is used.
+    pub fn memory_type(mut self, memory_type: MemoryType) -> ExitBootServices<'a> {
+        self.config.memory_type = Some(memory_type);
+        self
+    }
+
+    /// Sets the number of additional descriptors the memory map buffer has room for.
+    ///
+    /// The default is `DEFAULT_MEMORY_MAP_SLACK`.
+    pub fn slack(mut self, slack: usize) -> ExitBootServices<'a> {
+        self.config.slack = slack;
+        self
+    }
+
+    /// Sets the number of additional pages allocated for the memory map buffer.
+    ///
+    /// The default is `DEFAULT_EXIT_SLACK_PAGES`.
+    pub fn slack_pages(mut self, slack_pages: usize) -> ExitBootServices<'a> {
+        self.config.slack_pages = slack_pages;
+        self
+    }
+
+    /// Sets the number of times exiting boot services is attempted before giving up.
+    ///
+    /// The default is `DEFAULT_EXIT_ATTEMPTS`.
+    ///
+    /// # Panics
+    /// Panics if `max_attempts` is zero.
+    pub fn max_attempts(mut self, max_attempts: usize) -> ExitBootServices<'a> {
+        assert!(
+            max_attempts > 0,
+            "At least one attempt is needed to exit boot services."
+        );
+
+        self.config.max_attempts = max_attempts;
+        self
+    }
+
+    /// Registers a callback that runs right before `ExitBootServices` is called.
+    ///
+    /// Callbacks run in the order they were registered.
+    ///
+    /// # Panics
+    /// Panics if `MAX_EXIT_CALLBACKS` callbacks are already registered.
+    pub fn before_exit(
+        mut self,
+        callback: &'a mut dyn FnMut(&BootServices, &MemoryMap),
+    ) -> ExitBootServices<'a> {
+        let slot = self
+            .callbacks
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .expect("Too many callbacks were registered to run before exiting boot services.");
+
+        *slot = Some(callback);
+        self
+    }
+
+    /// Exits boot services.
+    ///
+    /// The returned error records whether `GetMemoryMap` or `ExitBootServices` failed.
+    pub fn exit(mut self) -> Result<RuntimeView, NuefilError> {
+        let memory_map = self.system_table.BootServices.exit_boot_services_inner(
+            self.image_handle,
+            self.config,
+            &mut self.callbacks,
+        )?;
+
+        Ok(RuntimeView {
+            runtime_services: &*self.system_table.RuntimeServices,
+            config_tables: self.system_table.config_tables(),
+            memory_map,
+        })
+    }
+}
+
+/// An event created by the caller, which is closed when it is no longer needed.
+///
+/// Unlike events owned by the firmware, such as `TextInput::WaitForKey`, an owned event has to be
+/// closed to avoid leaking it. This happens either explicitly with `close` or when the event is
+/// dropped, if boot services were registered with `register_global`.
+#[derive(Debug)]
+pub struct OwnedEvent {
+    /// The event that is owned.
+    event: Event,
+}
+
+impl OwnedEvent {
+    /// Creates an event.
+    ///
+    /// # Safety
+    /// See `BootServices::create_event`.
+    pub unsafe fn new(
+        boot_services: &BootServices,
+        event_type: EventType,
+        notify_tpl: usize,
+        notify_function: Option<EventNotify>,
+        notify_context: usize,
+    ) -> Result<OwnedEvent, Error> {
+        let event =
+            boot_services.create_event(event_type, notify_tpl, notify_function, notify_context)?;
+
+        Ok(OwnedEvent { event })
+    }
+
+    /// Creates a timer event without a notification function.
+    pub fn timer(boot_services: &BootServices) -> Result<OwnedEvent, Error> {
+        let event = boot_services.create_timer_event()?;
+
+        Ok(OwnedEvent { event })
+    }
+
+    /// Takes ownership of an event.
+    ///
+    /// # Safety
+    /// The event must have been created with `CreateEvent` and must not be closed by anyone else.
+    pub unsafe fn from_raw(event: Event) -> OwnedEvent {
+        OwnedEvent { event }
+    }
+
+    /// Returns the event, for example to wait for it together with other events.
+    pub fn event(&self) -> Event {
+        self.event
+    }
+
+    /// Closes the event.
+    pub fn close(self, boot_services: &BootServices) -> Result<(), Error> {
+        let event = self.into_raw();
+
+        boot_services.close_event(event)
+    }
+
+    /// Gives up ownership of the event without closing it.
+    pub fn into_raw(self) -> Event {
+        let this = ManuallyDrop::new(self);
+
+        this.event
+    }
+}
+
+impl Deref for OwnedEvent {
+    type Target = Event;
+
+    fn deref(&self) -> &Event {
+        &self.event
+    }
+}
+
+impl Drop for OwnedEvent {
+    fn drop(&mut self) {
+        if let Some(boot_services) = global() {
+            // There is no way to report the error here.
+            let _ = boot_services.close_event(self.event);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Creates boot options and registers them with the boot manager.
+//!
+//! A boot option is stored in a `Boot####` variable as an `EFI_LOAD_OPTION`, which consists of
+//! attributes, a description, the device path of the image and optional data that is passed to
+//! the image as its load options. The boot manager tries the options in the order of the
+//! `BootOrder` variable, unless `BootNext` selects an option for the next boot only.
+
+use core::{mem::size_of, slice, str};
+
+use crate::{
+    boot::BootServices, boxed::PoolBuffer, device::DevicePath, memory::MemoryType,
+    runtime::RuntimeServices, status::Error, system, var_store::VarStore,
+};
+
+/// The boot manager tries to boot the option.
+pub const LOAD_OPTION_ACTIVE: u32 = 0x0000_0001;
+/// All drivers are reconnected after the option was loaded, which is used for driver options.
+pub const LOAD_OPTION_FORCE_RECONNECT: u32 = 0x0000_0002;
+/// The option is not shown in the boot manager menu.
+pub const LOAD_OPTION_HIDDEN: u32 = 0x0000_0008;
+/// The option is an application that is only started from the boot manager menu.
+pub const LOAD_OPTION_CATEGORY_APP: u32 = 0x0000_0100;
+
+/// The maximum number of entries in `BootOrder` that can be updated.
+pub const MAX_BOOT_ORDER_LEN: usize = 256;
+
+/// Where a new option is inserted into `BootOrder`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BootOrderPosition {
+    /// The option is tried before all other options.
+    First,
+    /// The option is tried after all other options.
+    Last,
+    /// The option is not added to `BootOrder`.
+    None,
+}
+
+/// Returns the name of the `Boot####` variable of the given option.
+fn option_name(number: u16, buffer: &mut [u8; 8]) -> &str {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    buffer[..4].copy_from_slice(b"Boot");
+    for (index, byte) in buffer[4..].iter_mut().enumerate() {
+        *byte = HEX_DIGITS[usize::from(number >> (12 - index * 4)) & 0xf];
+    }
+
+    // This is safe, because only ASCII characters were written.
+    unsafe { str::from_utf8_unchecked(buffer) }
+}
+
+/// Returns the size of the `EFI_LOAD_OPTION` with the given contents.
+fn load_option_size(
+    description: &str,
+    device_path: &DevicePath,
+    optional_data: &[u8],
+) -> Result<usize, Error> {
+    Ok(size_of::<u32>()
+        + size_of::<u16>()
+        + (description.encode_utf16().count() + 1) * size_of::<u16>()
+        + device_path.size()?
+        + optional_data.len())
+}
+
+/// Serializes an `EFI_LOAD_OPTION` into `buffer`, which must have the size returned by
+/// `load_option_size`.
+fn write_load_option(
+    buffer: &mut [u8],
+    attributes: u32,
+    description: &str,
+    device_path: &DevicePath,
+    optional_data: &[u8],
+) -> Result<(), Error> {
+    let device_path_size = device_path.size()?;
+
+    if description.contains('\0') || device_path_size > usize::from(u16::max_value()) {
+        return Err(Error::InvalidParameter);
+    }
+
+    buffer[..4].copy_from_slice(&attributes.to_le_bytes());
+    buffer[4..6].copy_from_slice(&(device_path_size as u16).to_le_bytes());
+
+    let mut offset = 6;
+    for code_unit in description.encode_utf16().chain(Some(0)) {
+        buffer[offset..offset + 2].copy_from_slice(&code_unit.to_le_bytes());
+        offset += 2;
+    }
+
+    // This is safe, because `size` walked the nodes up to the end of the entire device path.
+    let device_path_bytes = unsafe {
+        slice::from_raw_parts(
+            device_path as *const DevicePath as *const u8,
+            device_path_size,
+        )
+    };
+    buffer[offset..offset + device_path_size].copy_from_slice(device_path_bytes);
+    offset += device_path_size;
+
+    buffer[offset..].copy_from_slice(optional_data);
+
+    Ok(())
+}
+
+/// A boot option that is registered with the boot manager.
+pub struct BootEntry<'a> {
+    /// The description shown in the boot manager menu.
+    description: &'a str,
+    /// The device path of the image.
+    device_path: &'a DevicePath,
+    /// The data passed to the image as its load options.
+    optional_data: &'a [u8],
+    /// The attributes of the option.
+    attributes: u32,
+    /// Where the option is inserted into `BootOrder`.
+    position: BootOrderPosition,
+    /// Whether the option is booted on the next boot.
+    boot_next: bool,
+}
+
+impl<'a> BootEntry<'a> {
+    /// Creates an active boot option without optional data, which is tried first.
+    pub fn new(description: &'a str, device_path: &'a DevicePath) -> BootEntry<'a> {
+        BootEntry {
+            description,
+            device_path,
+            optional_data: &[],
+            attributes: LOAD_OPTION_ACTIVE,
+            position: BootOrderPosition::First,
+            boot_next: false,
+        }
+    }
+
+    /// Sets the data passed to the image as its load options.
+    ///
+    /// For most applications this is a UCS-2 command line, but it is passed on unchanged.
+    pub fn optional_data(mut self, optional_data: &'a [u8]) -> BootEntry<'a> {
+        self.optional_data = optional_data;
+        self
+    }
+
+    /// Sets the `LOAD_OPTION_*` attributes of the option.
+    pub fn attributes(mut self, attributes: u32) -> BootEntry<'a> {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Selects where the option is inserted into `BootOrder`.
+    pub fn position(mut self, position: BootOrderPosition) -> BootEntry<'a> {
+        self.position = position;
+        self
+    }
+
+    /// Selects whether `BootNext` is set to the option, so it is booted on the next boot only.
+    pub fn boot_next(mut self, boot_next: bool) -> BootEntry<'a> {
+        self.boot_next = boot_next;
+        self
+    }
+
+    /// Writes the option to the first free `Boot####` variable and registers it.
+    ///
+    /// The option, `BootOrder` and `BootNext` are written in a transaction, so either all of them
+    /// are changed or none of them. Returns the number of the option.
+    ///
+    /// Returns `Error::OutOfResources` if all option numbers are used or `BootOrder` has
+    /// `MAX_BOOT_ORDER_LEN` entries, and `Error::InvalidParameter` if the description contains a
+    /// null character or the device path is too long or malformed.
+    pub fn register(
+        &self,
+        boot_services: &BootServices,
+        runtime_services: &RuntimeServices,
+    ) -> Result<u16, Error> {
+        let store = VarStore::global(runtime_services);
+
+        let size = load_option_size(self.description, self.device_path, self.optional_data)?;
+        let mut option = PoolBuffer::new(boot_services, MemoryType::LoaderData, size)?;
+
+        let registered = write_load_option(
+            &mut option,
+            self.attributes,
+            self.description,
+            self.device_path,
+            self.optional_data,
+        )
+        .and_then(|_| self.write_variables(&store, &option));
+
+        option.free(boot_services)?;
+
+        registered
+    }
+
+    /// Writes the serialized option, `BootOrder` and `BootNext`.
+    fn write_variables(&self, store: &VarStore, option: &[u8]) -> Result<u16, Error> {
+        let mut name_buffer = [0; 8];
+        let number = free_option_number(store)?;
+        let name = option_name(number, &mut name_buffer);
+
+        let mut order = [0u16; MAX_BOOT_ORDER_LEN];
+        let mut len = read_boot_order(store, &mut order[..MAX_BOOT_ORDER_LEN - 1])?;
+
+        // `BootOrder` may still refer to a deleted option with the same number.
+        let mut kept = 0;
+        for index in 0..len {
+            if order[index] != number {
+                order[kept] = order[index];
+                kept += 1;
+            }
+        }
+        len = kept;
+
+        match self.position {
+            BootOrderPosition::First => {
+                for index in (0..len).rev() {
+                    order[index + 1] = order[index];
+                }
+                order[0] = number;
+                len += 1;
+            }
+            BootOrderPosition::Last => {
+                order[len] = number;
+                len += 1;
+            }
+            BootOrderPosition::None => (),
+        }
+
+        let mut order_bytes = [0u8; MAX_BOOT_ORDER_LEN * 2];
+        for (bytes, entry) in order_bytes.chunks_mut(2).zip(&order[..len]) {
+            bytes.copy_from_slice(&entry.to_le_bytes());
+        }
+
+        let mut transaction = store.transaction();
+        transaction.set_raw(name, option)?;
+        if self.position != BootOrderPosition::None {
+            transaction.set_raw("BootOrder", &order_bytes[..len * 2])?;
+        }
+        if self.boot_next {
+            transaction.set("BootNext", &number)?;
+        }
+
+        // The old `BootOrder` and `BootNext` are saved, while the option variable is new.
+        let mut scratch = [0u8; MAX_BOOT_ORDER_LEN * 2 + 2];
+        transaction.commit(&mut scratch)?;
+
+        Ok(number)
+    }
+}
+
+/// Returns the lowest option number without a `Boot####` variable.
+fn free_option_number(store: &VarStore) -> Result<u16, Error> {
+    let mut name_buffer = [0; 8];
+
+    for number in 0..=u16::max_value() {
+        match store.get_raw(option_name(number, &mut name_buffer), &mut []) {
+            Err(Error::NotFound) => return Ok(number),
+            Ok(_) | Err(Error::BufferTooSmall) => (),
+            Err(error) => return Err(error),
+        }
+    }
+
+    Err(Error::OutOfResources)
+}
+
+/// Reads `BootOrder` into `order` and returns the number of entries.
+///
+/// A missing `BootOrder` is treated as an empty one.
+fn read_boot_order(store: &VarStore, order: &mut [u16]) -> Result<usize, Error> {
+    // This is safe, because every byte pattern is a valid `u16`.
+    let bytes =
+        unsafe { slice::from_raw_parts_mut(order.as_mut_ptr() as *mut u8, order.len() * 2) };
+
+    let size = match store.get_raw("BootOrder", bytes) {
+        Ok((size, _)) => size,
+        Err(Error::NotFound) => return Ok(0),
+        Err(Error::BufferTooSmall) => return Err(Error::OutOfResources),
+        Err(error) => return Err(error),
+    };
+
+    if size % 2 != 0 {
+        return Err(Error::BadBufferSize);
+    }
+
+    // The variable is little endian, which only matters on big endian targets.
+    for entry in &mut order[..size / 2] {
+        *entry = u16::from_le(*entry);
+    }
+
+    Ok(size / 2)
+}
+
+/// Creates an active boot option and inserts it at the front of `BootOrder`.
+///
+/// The boot and runtime services registered with `system::init` are used. Returns
+/// `Error::NotStarted` if `system::init` was not called. Use `BootEntry` to select other
+/// attributes, positions or `BootNext`.
+pub fn create_entry(
+    description: &str,
+    device_path: &DevicePath,
+    optional_data: &[u8],
+) -> Result<u16, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+    let system_table = system::system_table().ok_or(Error::NotStarted)?;
+
+    BootEntry::new(description, device_path)
+        .optional_data(optional_data)
+        .register(boot_services, system_table.RuntimeServices)
+}
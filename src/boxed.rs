@@ -0,0 +1,423 @@
+//! Owning wrappers around page and pool memory that is allocated with the boot services.
+//!
+//! The memory is released with an explicit call to `free`. If boot services were registered with
+//! `boot::register_global`, dropping the wrappers releases the memory as well, otherwise it is leaked.
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem::{align_of, size_of, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+};
+
+use crate::{
+    boot::{self, BootServices},
+    memory::{MemoryType, PhysicalAddress, PAGE_SIZE},
+    status::Error,
+};
+
+/// Returns the number of pages needed to hold `size` bytes, but at least one.
+fn pages_for(size: usize) -> usize {
+    if size == 0 {
+        1
+    } else {
+        (size + PAGE_SIZE - 1) / PAGE_SIZE
+    }
+}
+
+/// A value stored in pages allocated with `AllocatePages`.
+pub struct PageBox<T> {
+    /// The value at the start of the allocated pages.
+    value: NonNull<T>,
+    /// The number of allocated pages.
+    pages: usize,
+    /// Marks that the value is owned.
+    _marker: PhantomData<T>,
+}
+
+impl<T> PageBox<T> {
+    /// Allocates enough pages of the given memory type to hold `value` and moves it there.
+    ///
+    /// # Panics
+    /// Panics if `T` requires an alignment larger than a page.
+    pub fn new(
+        boot_services: &BootServices,
+        memory_type: MemoryType,
+        value: T,
+    ) -> Result<PageBox<T>, Error> {
+        assert!(
+            align_of::<T>() <= PAGE_SIZE,
+            "The type requires an alignment larger than a page."
+        );
+
+        let pages = pages_for(size_of::<T>());
+        let memory = boot_services.allocate_pages(memory_type, pages)? as *mut T;
+        let memory = NonNull::new(memory).ok_or(Error::OutOfResources)?;
+
+        // This is safe, because the memory was just allocated, is large enough and page aligned.
+        unsafe { ptr::write(memory.as_ptr(), value) };
+
+        Ok(PageBox {
+            value: memory,
+            pages,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the number of allocated pages.
+    pub fn pages(&self) -> usize {
+        self.pages
+    }
+
+    /// Returns the physical address of the allocated pages.
+    pub fn address(&self) -> PhysicalAddress {
+        PhysicalAddress(self.value.as_ptr() as u64)
+    }
+
+    /// Drops the value and frees the pages.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        let this = ManuallyDrop::new(self);
+
+        // This is safe, because the value is valid and not used after this.
+        unsafe { ptr::drop_in_place(this.value.as_ptr()) };
+
+        boot_services.free_pages(this.value.as_ptr() as *const u8, this.pages)
+    }
+
+    /// Gives up ownership of the pages without freeing them.
+    ///
+    /// This is useful for memory that must outlive the boot services, like data passed to a kernel.
+    pub fn leak(self) -> &'static mut T {
+        let this = ManuallyDrop::new(self);
+
+        // This is safe, because the pages are never freed.
+        unsafe { &mut *this.value.as_ptr() }
+    }
+}
+
+impl<T> Deref for PageBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // This is safe, because the value is valid for as long as the box exists.
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T> DerefMut for PageBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // This is safe, because the value is valid for as long as the box exists.
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T> Drop for PageBox<T> {
+    fn drop(&mut self) {
+        // This is safe, because the value is valid and not used after this.
+        unsafe { ptr::drop_in_place(self.value.as_ptr()) };
+
+        if let Some(boot_services) = boot::global() {
+            // There is no way to report the error here.
+            let _ = boot_services.free_pages(self.value.as_ptr() as *const u8, self.pages);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PageBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A byte buffer made up of pages allocated with `AllocatePages`.
+pub struct PageSlice {
+    /// The start of the allocated pages.
+    buffer: NonNull<u8>,
+    /// The number of allocated pages.
+    pages: usize,
+}
+
+impl PageSlice {
+    /// Allocates the given number of pages of the given memory type and fills them with zeros.
+    pub fn new(
+        boot_services: &BootServices,
+        memory_type: MemoryType,
+        pages: usize,
+    ) -> Result<PageSlice, Error> {
+        let buffer = boot_services.allocate_pages(memory_type, pages)? as *mut u8;
+        let buffer = NonNull::new(buffer).ok_or(Error::OutOfResources)?;
+
+        // This is safe, because the memory was just allocated with the given size.
+        unsafe { ptr::write_bytes(buffer.as_ptr(), 0, pages * PAGE_SIZE) };
+
+        Ok(PageSlice { buffer, pages })
+    }
+
+    /// Allocates enough pages of the given memory type to hold `size` bytes.
+    pub fn with_size(
+        boot_services: &BootServices,
+        memory_type: MemoryType,
+        size: usize,
+    ) -> Result<PageSlice, Error> {
+        PageSlice::new(boot_services, memory_type, pages_for(size))
+    }
+
+    /// Returns the number of allocated pages.
+    pub fn pages(&self) -> usize {
+        self.pages
+    }
+
+    /// Returns the physical address of the allocated pages.
+    pub fn address(&self) -> PhysicalAddress {
+        PhysicalAddress(self.buffer.as_ptr() as u64)
+    }
+
+    /// Frees the pages.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        let this = ManuallyDrop::new(self);
+
+        boot_services.free_pages(this.buffer.as_ptr(), this.pages)
+    }
+
+    /// Gives up ownership of the pages without freeing them.
+    pub fn leak(self) -> &'static mut [u8] {
+        let this = ManuallyDrop::new(self);
+
+        // This is safe, because the pages are never freed.
+        unsafe { slice::from_raw_parts_mut(this.buffer.as_ptr(), this.pages * PAGE_SIZE) }
+    }
+}
+
+impl Deref for PageSlice {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // This is safe, because the buffer is valid for as long as the slice exists.
+        unsafe { slice::from_raw_parts(self.buffer.as_ptr(), self.pages * PAGE_SIZE) }
+    }
+}
+
+impl DerefMut for PageSlice {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // This is safe, because the buffer is valid for as long as the slice exists.
+        unsafe { slice::from_raw_parts_mut(self.buffer.as_ptr(), self.pages * PAGE_SIZE) }
+    }
+}
+
+impl Drop for PageSlice {
+    fn drop(&mut self) {
+        if let Some(boot_services) = boot::global() {
+            // There is no way to report the error here.
+            let _ = boot_services.free_pages(self.buffer.as_ptr(), self.pages);
+        }
+    }
+}
+
+impl fmt::Debug for PageSlice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PageSlice")
+            .field("address", &self.address())
+            .field("pages", &self.pages)
+            .finish()
+    }
+}
+
+/// The alignment of memory allocated with `AllocatePool`.
+const POOL_ALIGNMENT: usize = 8;
+
+/// A value stored in memory allocated with `AllocatePool`.
+pub struct PoolBox<T> {
+    /// The value at the start of the pool allocation.
+    value: NonNull<T>,
+    /// Marks that the value is owned.
+    _marker: PhantomData<T>,
+}
+
+impl<T> PoolBox<T> {
+    /// Allocates pool memory of the given memory type to hold `value` and moves it there.
+    ///
+    /// # Panics
+    /// Panics if `T` requires an alignment larger than the 8 bytes guaranteed for pool memory.
+    pub fn new(
+        boot_services: &BootServices,
+        memory_type: MemoryType,
+        value: T,
+    ) -> Result<PoolBox<T>, Error> {
+        assert!(
+            align_of::<T>() <= POOL_ALIGNMENT,
+            "The type requires an alignment larger than pool memory provides."
+        );
+
+        let memory = boot_services.allocate_pool(memory_type, size_of::<T>())? as *mut T;
+        let memory = NonNull::new(memory).ok_or(Error::OutOfResources)?;
+
+        // This is safe, because the memory was just allocated, is large enough and aligned.
+        unsafe { ptr::write(memory.as_ptr(), value) };
+
+        Ok(PoolBox {
+            value: memory,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Takes ownership of a value that the firmware allocated from pool memory.
+    ///
+    /// # Safety
+    /// `value` must point to a valid `T` at the start of an allocation made with `AllocatePool`,
+    /// which must not be freed elsewhere.
+    pub unsafe fn from_raw(value: *mut T) -> Option<PoolBox<T>> {
+        NonNull::new(value).map(|value| PoolBox {
+            value,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Drops the value and frees the pool memory.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        let this = ManuallyDrop::new(self);
+
+        // This is safe, because the value is valid and not used after this.
+        unsafe { ptr::drop_in_place(this.value.as_ptr()) };
+
+        boot_services.free_pool(this.value.as_ptr() as *const u8)
+    }
+
+    /// Gives up ownership of the pool memory without freeing it.
+    pub fn into_raw(self) -> *mut T {
+        ManuallyDrop::new(self).value.as_ptr()
+    }
+}
+
+impl<T> Deref for PoolBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // This is safe, because the value is valid for as long as the box exists.
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T> DerefMut for PoolBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // This is safe, because the value is valid for as long as the box exists.
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T> Drop for PoolBox<T> {
+    fn drop(&mut self) {
+        // This is safe, because the value is valid and not used after this.
+        unsafe { ptr::drop_in_place(self.value.as_ptr()) };
+
+        if let Some(boot_services) = boot::global() {
+            // There is no way to report the error here.
+            let _ = boot_services.free_pool(self.value.as_ptr() as *const u8);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PoolBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A buffer of plain values stored in memory allocated with `AllocatePool`.
+///
+/// Many boot services, like `LocateHandleBuffer`, return such buffers and leave it to the
+/// caller to free them.
+pub struct PoolBuffer<T: Copy = u8> {
+    /// The start of the pool allocation.
+    buffer: NonNull<T>,
+    /// The number of elements in the buffer.
+    len: usize,
+}
+
+impl<T: Copy + Default> PoolBuffer<T> {
+    /// Allocates pool memory of the given memory type for `len` elements and fills it with
+    /// default values.
+    ///
+    /// # Panics
+    /// Panics if `T` requires an alignment larger than the 8 bytes guaranteed for pool memory.
+    pub fn new(
+        boot_services: &BootServices,
+        memory_type: MemoryType,
+        len: usize,
+    ) -> Result<PoolBuffer<T>, Error> {
+        assert!(
+            align_of::<T>() <= POOL_ALIGNMENT,
+            "The type requires an alignment larger than pool memory provides."
+        );
+
+        let size = size_of::<T>()
+            .checked_mul(len)
+            .ok_or(Error::InvalidParameter)?;
+        let buffer = boot_services.allocate_pool(memory_type, size)? as *mut T;
+        let buffer = NonNull::new(buffer).ok_or(Error::OutOfResources)?;
+
+        for i in 0..len {
+            // This is safe, because the memory was just allocated with room for `len` elements.
+            unsafe { ptr::write(buffer.as_ptr().add(i), T::default()) };
+        }
+
+        Ok(PoolBuffer { buffer, len })
+    }
+}
+
+impl<T: Copy> PoolBuffer<T> {
+    /// Takes ownership of a buffer that the firmware allocated from pool memory.
+    ///
+    /// # Safety
+    /// `buffer` must point to `len` valid elements at the start of an allocation made with
+    /// `AllocatePool`, which must not be freed elsewhere.
+    pub unsafe fn from_raw(buffer: *mut T, len: usize) -> Option<PoolBuffer<T>> {
+        NonNull::new(buffer).map(|buffer| PoolBuffer { buffer, len })
+    }
+
+    /// Frees the pool memory.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        let this = ManuallyDrop::new(self);
+
+        boot_services.free_pool(this.buffer.as_ptr() as *const u8)
+    }
+
+    /// Gives up ownership of the pool memory without freeing it.
+    pub fn into_raw(self) -> (*mut T, usize) {
+        let this = ManuallyDrop::new(self);
+
+        (this.buffer.as_ptr(), this.len)
+    }
+}
+
+impl<T: Copy> Deref for PoolBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // This is safe, because the buffer is valid for as long as it is owned.
+        unsafe { slice::from_raw_parts(self.buffer.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Copy> DerefMut for PoolBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // This is safe, because the buffer is valid for as long as it is owned.
+        unsafe { slice::from_raw_parts_mut(self.buffer.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Copy> Drop for PoolBuffer<T> {
+    fn drop(&mut self) {
+        if let Some(boot_services) = boot::global() {
+            // There is no way to report the error here.
+            let _ = boot_services.free_pool(self.buffer.as_ptr() as *const u8);
+        }
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for PoolBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -1,9 +1,49 @@
-use crate::{guid::Guid, memory::PhysicalAddress};
+//! Capsules, which deliver firmware updates to the firmware.
+//!
+//! Besides the structures passed to `UpdateCapsule`, this implements the capsule on disk flow:
+//! `stage_on_disk_with` places a capsule file in `\EFI\UpdateCapsule\` and asks the firmware to
+//! process it during the next boot through `OsIndications`. After the reset, the firmware
+//! reports the outcome in the `CapsuleXXXX` variables, which `capsule_result` reads.
+
+use core::{mem::size_of, str};
+
+#[cfg(feature = "fs")]
+use crate::{
+    boot::BootServices,
+    fs::{
+        SimpleFileSystem, Volume, FILE_DIRECTORY, FILE_MODE_CREATE, FILE_MODE_READ, FILE_MODE_WRITE,
+    },
+    reset::ResetType,
+    status::{StatusResultExt, SUCCESS},
+    system, Handle,
+};
+use crate::{
+    guid,
+    guid::Guid,
+    guids,
+    memory::PhysicalAddress,
+    runtime::RuntimeServices,
+    status::{Error, Status},
+    time::Time,
+    var_store::VarStore,
+};
 
 pub const CAPSULE_FLAGS_PERSIST_ACROSS_RESET: u32 = 0x00010000;
 pub const CAPSULE_FLAGS_POPULATE_SYSTEM_TABLE: u32 = 0x00020000;
 pub const CAPSULE_FLAGS_INITIATE_RESET: u32 = 0x00040000;
 
+/// The bit of `OsIndications` and `OsIndicationsSupported` requesting capsule on disk processing.
+pub const OS_INDICATIONS_FILE_CAPSULE_DELIVERY_SUPPORTED: u64 = 0x0000_0000_0000_0004;
+
+/// The GUID of capsules processed by the firmware management protocol.
+pub const FMP_CAPSULE_GUID: Guid = guid!("6dcbd5ed-e82d-4c44-bda1-7194199ad92a");
+
+/// The directory on the ESP that capsules on disk are placed in.
+pub const CAPSULE_ON_DISK_DIRECTORY: &str = "\\EFI\\UpdateCapsule";
+
+/// The size of the buffer a `CapsuleXXXX` variable is read into.
+const RESULT_VARIABLE_SIZE: usize = 1024;
+
 #[repr(C)]
 pub struct CapsuleHeader {
     CapsuleGuid: Guid,
@@ -17,3 +57,278 @@ pub struct CapsuleBlockDescriptor {
     pub Length: u64,
     pub DataBlock: PhysicalAddress,
 }
+
+/// Checks that `capsule` starts with a consistent capsule header.
+///
+/// Returns `Error::BadBufferSize` if the header or the sizes it contains do not fit.
+pub fn validate(capsule: &[u8]) -> Result<(), Error> {
+    if capsule.len() < size_of::<CapsuleHeader>() {
+        return Err(Error::BadBufferSize);
+    }
+
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes([
+            capsule[offset],
+            capsule[offset + 1],
+            capsule[offset + 2],
+            capsule[offset + 3],
+        ]) as usize
+    };
+    let (header_size, image_size) = (read_u32(16), read_u32(24));
+
+    if header_size < size_of::<CapsuleHeader>()
+        || image_size < header_size
+        || image_size > capsule.len()
+    {
+        return Err(Error::BadBufferSize);
+    }
+
+    Ok(())
+}
+
+/// Returns whether the firmware supports capsules on disk.
+pub fn on_disk_supported(runtime_services: &RuntimeServices) -> Result<bool, Error> {
+    match VarStore::global(runtime_services).get::<u64>("OsIndicationsSupported") {
+        Ok(supported) => Ok(supported & OS_INDICATIONS_FILE_CAPSULE_DELIVERY_SUPPORTED != 0),
+        Err(Error::NotFound) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Writes `capsule` to `\EFI\UpdateCapsule\<file_name>` on `volume` and requests that the
+/// firmware processes it during the next boot.
+///
+/// The volume must be the ESP, which is usually `Volume::Image`. An existing file with the same
+/// name is replaced. The firmware processes the capsule after the next reset, which is not
+/// triggered here.
+///
+/// Returns `Error::Unsupported` if the firmware does not support capsules on disk,
+/// `Error::InvalidParameter` if `file_name` contains a path separator and
+/// `Error::BadBufferSize` if `capsule` has no consistent capsule header. Warnings of the file
+/// system, such as `Warning::DeleteFailure` if an existing file cannot be deleted, are returned
+/// as they are.
+#[cfg(feature = "fs")]
+pub fn stage_on_disk_with(
+    boot_services: &BootServices,
+    runtime_services: &RuntimeServices,
+    image_handle: Handle,
+    volume: Volume,
+    file_name: &str,
+    capsule: &[u8],
+) -> Result<(), Status> {
+    if file_name.is_empty() || file_name.contains(|character| character == '\\' || character == '/')
+    {
+        return Err(Error::InvalidParameter.into());
+    }
+    validate(capsule)?;
+    if !on_disk_supported(runtime_services)? {
+        return Err(Error::Unsupported.into());
+    }
+
+    let handle = volume.handle(boot_services, image_handle)?;
+    let mut dir = boot_services
+        .handle_protocol::<SimpleFileSystem>(handle)?
+        .open_volume()?;
+
+    let dir_mode = FILE_MODE_READ | FILE_MODE_WRITE | FILE_MODE_CREATE;
+    for component in CAPSULE_ON_DISK_DIRECTORY
+        .split('\\')
+        .filter(|c| !c.is_empty())
+    {
+        dir = dir.open(component, dir_mode, FILE_DIRECTORY)?;
+    }
+
+    match dir.open(file_name, FILE_MODE_READ | FILE_MODE_WRITE, 0) {
+        Ok(existing) => existing.delete().warn_as_err()?,
+        Err(Error::NotFound) => (),
+        Err(error) => return Err(error.into()),
+    }
+
+    let mut file = dir.open(file_name, dir_mode, 0)?;
+    file.write_all(capsule).warn_as_err()?;
+    file.close().warn_as_err()?;
+
+    let store = VarStore::global(runtime_services);
+    let indications = match store.get::<u64>("OsIndications") {
+        Ok(indications) => indications,
+        Err(Error::NotFound) => 0,
+        Err(error) => return Err(error.into()),
+    };
+
+    store.set(
+        "OsIndications",
+        &(indications | OS_INDICATIONS_FILE_CAPSULE_DELIVERY_SUPPORTED),
+    )?;
+
+    Ok(())
+}
+
+/// Stages `capsule` on the volume the image was loaded from and resets the system to process
+/// it.
+///
+/// The boot services, runtime services and image handle registered with `system::init` are
+/// used, see `stage_on_disk_with`. This only returns the status of staging the capsule if it
+/// fails, which is `Error::NotStarted` if `system::init` was not called.
+#[cfg(feature = "fs")]
+pub fn update_on_disk(file_name: &str, capsule: &[u8]) -> Status {
+    let staged = (|| {
+        let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+        let image_handle = system::image_handle().ok_or(Error::NotStarted)?;
+        let runtime_services = &*system::system_table()
+            .ok_or(Error::NotStarted)?
+            .RuntimeServices;
+
+        stage_on_disk_with(
+            boot_services,
+            runtime_services,
+            image_handle,
+            Volume::Image,
+            file_name,
+            capsule,
+        )?;
+
+        Ok(runtime_services)
+    })();
+
+    match staged {
+        Ok(runtime_services) => runtime_services.reset_system(ResetType::Cold, SUCCESS),
+        Err(status) => status,
+    }
+}
+
+/// The details that the firmware management protocol adds to a capsule result.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CapsuleResultFmp {
+    /// The version of the structure.
+    pub version: u16,
+    /// The index of the payload within the capsule.
+    pub payload_index: u8,
+    /// The image index passed to `SetImage`.
+    pub update_image_index: u8,
+    /// The type of the updated image.
+    pub update_image_type_id: Guid,
+}
+
+/// The outcome of processing a capsule, read from a `CapsuleXXXX` variable.
+#[derive(Clone, Copy, Debug)]
+pub struct CapsuleResult {
+    /// The GUID of the processed capsule.
+    pub capsule_guid: Guid,
+    /// The time the capsule was processed.
+    pub processed: Time,
+    /// The status of processing the capsule.
+    pub status: Status,
+    /// The details added by the firmware management protocol, for FMP capsules.
+    pub fmp: Option<CapsuleResultFmp>,
+}
+
+impl CapsuleResult {
+    /// Parses the contents of a `CapsuleXXXX` variable.
+    ///
+    /// Returns `Error::BadBufferSize` if the contents are truncated.
+    pub fn parse(data: &[u8]) -> Result<CapsuleResult, Error> {
+        let time_offset = 24;
+        let status_offset = time_offset + size_of::<Time>();
+        let header_size = status_offset + size_of::<usize>();
+
+        if data.len() < header_size {
+            return Err(Error::BadBufferSize);
+        }
+
+        let total_size = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if total_size < header_size || total_size > data.len() {
+            return Err(Error::BadBufferSize);
+        }
+
+        let guid_at = |offset: usize| {
+            let mut bytes = [0; 16];
+            bytes.copy_from_slice(&data[offset..offset + 16]);
+
+            Guid::from_bytes(bytes)
+        };
+
+        let capsule_guid = guid_at(8);
+        // This is safe, because the data holds a `Time` at this offset and every byte pattern is
+        // a valid `Time`.
+        let processed = unsafe { (data[time_offset..].as_ptr() as *const Time).read_unaligned() };
+        let mut status = [0; size_of::<usize>()];
+        status.copy_from_slice(&data[status_offset..header_size]);
+
+        let fmp = if capsule_guid == FMP_CAPSULE_GUID && total_size >= header_size + 20 {
+            let fmp = &data[header_size..];
+
+            Some(CapsuleResultFmp {
+                version: u16::from_le_bytes([fmp[0], fmp[1]]),
+                payload_index: fmp[2],
+                update_image_index: fmp[3],
+                update_image_type_id: guid_at(header_size + 4),
+            })
+        } else {
+            None
+        };
+
+        Ok(CapsuleResult {
+            capsule_guid,
+            processed,
+            status: Status(usize::from_le_bytes(status)),
+            fmp,
+        })
+    }
+}
+
+/// Reads the capsule result in the variable `CapsuleXXXX` with the given index.
+pub fn capsule_result(
+    runtime_services: &RuntimeServices,
+    index: u16,
+) -> Result<CapsuleResult, Error> {
+    let mut name = *b"Capsule0000";
+    for (position, digit) in name[7..].iter_mut().enumerate() {
+        let nibble = (index >> (12 - 4 * position)) & 0xf;
+        *digit = b"0123456789ABCDEF"[nibble as usize];
+    }
+    // This cannot fail, because the name only consists of ASCII characters.
+    let name = str::from_utf8(&name).map_err(|_| Error::InvalidParameter)?;
+
+    let mut data = [0; RESULT_VARIABLE_SIZE];
+    let (size, _) =
+        VarStore::new(runtime_services, guids::vendor::CAPSULE_REPORT).get_raw(name, &mut data)?;
+
+    CapsuleResult::parse(&data[..size])
+}
+
+/// Returns the index of the `CapsuleXXXX` variable that was written last.
+///
+/// Returns `Error::NotFound` if no capsule results were reported yet.
+pub fn last_capsule_index(runtime_services: &RuntimeServices) -> Result<u16, Error> {
+    let mut buffer = [0; 16];
+    let name = VarStore::new(runtime_services, guids::vendor::CAPSULE_REPORT)
+        .get_string("CapsuleLast", &mut buffer)?;
+
+    let mut characters = name.chars();
+    if !characters.by_ref().take(7).eq("Capsule".chars()) {
+        return Err(Error::BadBufferSize);
+    }
+
+    let mut index = 0u16;
+    let mut digits = 0;
+    for character in characters {
+        let digit = character.to_digit(16).ok_or(Error::BadBufferSize)?;
+
+        index = (index << 4) | digit as u16;
+        digits += 1;
+    }
+
+    if digits != 4 {
+        return Err(Error::BadBufferSize);
+    }
+
+    Ok(index)
+}
+
+/// Reads the capsule result that was reported last, such as for a capsule on disk processed
+/// during this boot.
+pub fn last_capsule_result(runtime_services: &RuntimeServices) -> Result<CapsuleResult, Error> {
+    let index = last_capsule_index(runtime_services)?;
+
+    capsule_result(runtime_services, index)
+}
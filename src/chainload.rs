@@ -0,0 +1,217 @@
+//! Loads and starts an EFI application from a file, which is the core operation of a boot manager.
+//!
+//! The image is either read into memory and loaded from there, or loaded by the firmware from the
+//! device path of the file with the boot policy set. The load options of the image are set before
+//! it is started and the exit status and exit data of the image are returned.
+
+#[cfg(feature = "tpm")]
+use crate::measure::{self, MeasureKind};
+use crate::{
+    boot::{BootServices, ImageExit},
+    boxed::PoolBuffer,
+    device::{self, DevicePath, DevicePathBuffer},
+    fs::{self, Volume, FILE_MODE_READ},
+    loaded_image::LoadedImage,
+    memory::MemoryType,
+    status::Error,
+    system, ucs2, Handle,
+};
+
+/// Loads an image from a file and starts it.
+///
+/// `path` is interpreted like by `fs::open_with`.
+pub struct Chainload<'a> {
+    /// The boot services used to load and start the image.
+    boot_services: &'a BootServices,
+    /// The handle of the image that loads the new image.
+    parent_image_handle: Handle,
+    /// The load options passed to the image.
+    load_options: Option<&'a str>,
+    /// Whether the image is loaded by the firmware from its device path.
+    boot_policy: bool,
+}
+
+impl<'a> Chainload<'a> {
+    /// Creates a chainload request without load options that reads the image into memory.
+    pub fn new(boot_services: &'a BootServices, parent_image_handle: Handle) -> Chainload<'a> {
+        Chainload {
+            boot_services,
+            parent_image_handle,
+            load_options: None,
+            boot_policy: false,
+        }
+    }
+
+    /// Sets the load options passed to the image, usually its command line.
+    pub fn load_options(mut self, load_options: &'a str) -> Chainload<'a> {
+        self.load_options = Some(load_options);
+        self
+    }
+
+    /// Selects whether the firmware loads the image from the device path of the file, with the
+    /// boot policy set, instead of loading it from memory.
+    pub fn boot_policy(mut self, boot_policy: bool) -> Chainload<'a> {
+        self.boot_policy = boot_policy;
+        self
+    }
+
+    /// Loads the image at `path`, sets its load options and starts it.
+    ///
+    /// Returns the exit status and exit data of the image once it returns. The load options are
+    /// freed at that point, where a failure to free them only leaks them.
+    pub fn start(self, path: &str) -> Result<ImageExit, Error> {
+        let image_handle = if self.boot_policy {
+            self.load_by_device_path(path)?
+        } else {
+            self.load_from_file(path)?
+        };
+
+        let load_options = match self.load_options {
+            Some(load_options) => match self.set_load_options(image_handle, load_options) {
+                Ok(buffer) => Some(buffer),
+                Err(error) => {
+                    // The image was never started, so it has to be unloaded explicitly.
+                    let _ = self.boot_services.unload_image(image_handle);
+
+                    return Err(error);
+                }
+            },
+            None => None,
+        };
+
+        let exit = self.boot_services.start_image(image_handle);
+
+        if let Some(load_options) = load_options {
+            // The image already ran, so its exit is more important than the leaked load options.
+            let _ = load_options.free(self.boot_services);
+        }
+
+        Ok(exit)
+    }
+
+    /// Reads the image into memory and loads it from there.
+    ///
+    /// The device path of the file is passed along with the image, so the image can find the
+    /// volume it was loaded from.
+    fn load_from_file(&self, path: &str) -> Result<Handle, Error> {
+        let mut file = fs::open_with(
+            self.boot_services,
+            self.parent_image_handle,
+            path,
+            FILE_MODE_READ,
+        )?;
+
+        let size = file.info()?.FileSize as usize;
+        let mut buffer = PoolBuffer::new(self.boot_services, MemoryType::LoaderData, size)?;
+
+        let read = file.read_exact(&mut buffer);
+        #[cfg(feature = "tpm")]
+        let read = read.and_then(|_| {
+            measure::measure_loaded(self.boot_services, MeasureKind::Kernel, path, &buffer)
+        });
+        let image_handle = read
+            .and_then(|_| self.file_path(path))
+            .and_then(|file_path| {
+                let image_handle = self.boot_services.load_image(
+                    false,
+                    self.parent_image_handle,
+                    &file_path,
+                    Some(&buffer),
+                );
+
+                // A failure to free the device path only leaks it, which must not leak the
+                // loaded image as well.
+                let _ = file_path.free(self.boot_services);
+
+                image_handle
+            });
+
+        // The firmware copies the image, so the buffer is not needed anymore.
+        let _ = buffer.free(self.boot_services);
+
+        image_handle
+    }
+
+    /// Lets the firmware load the image from the device path of the file.
+    fn load_by_device_path(&self, path: &str) -> Result<Handle, Error> {
+        let file_path = self.file_path(path)?;
+
+        let image_handle =
+            self.boot_services
+                .load_image(true, self.parent_image_handle, &file_path, None);
+
+        // The image is more important than the leaked device path.
+        let _ = file_path.free(self.boot_services);
+
+        image_handle
+    }
+
+    /// Returns the device path of the file at `path`, which is the device path of its volume
+    /// followed by a file path node.
+    fn file_path(&self, path: &str) -> Result<DevicePathBuffer, Error> {
+        let (volume, path) = Volume::parse(path)?;
+        let volume_handle = volume.handle(self.boot_services, self.parent_image_handle)?;
+        let volume_path = self
+            .boot_services
+            .handle_protocol::<DevicePath>(volume_handle)?;
+
+        device::file_path(self.boot_services, volume_path, path)
+    }
+
+    /// Sets the load options of the loaded image.
+    ///
+    /// Returns the buffer holding the load options, which must stay valid while the image runs.
+    fn set_load_options(
+        &self,
+        image_handle: Handle,
+        load_options: &str,
+    ) -> Result<PoolBuffer<u16>, Error> {
+        let len = ucs2::encoded_len(load_options) + 1;
+        let size = len * 2;
+
+        if size > u32::max_value() as usize {
+            return Err(Error::InvalidParameter);
+        }
+
+        let mut buffer = PoolBuffer::new(self.boot_services, MemoryType::LoaderData, len)?;
+
+        let loaded_image = ucs2::encode_into(load_options, &mut buffer).and_then(|_| {
+            self.boot_services
+                .handle_protocol::<LoadedImage>(image_handle)
+        });
+
+        let loaded_image = match loaded_image {
+            Ok(loaded_image) => loaded_image as *const LoadedImage as *mut LoadedImage,
+            Err(error) => {
+                buffer.free(self.boot_services)?;
+
+                return Err(error);
+            }
+        };
+
+        // This is safe, because the image was not started yet, so nothing else accesses the
+        // protocol, and the firmware expects the loader to set the load options.
+        unsafe {
+            (*loaded_image).LoadOptions = buffer.as_ptr();
+            (*loaded_image).LoadOptionsSize = size as u32;
+        }
+
+        Ok(buffer)
+    }
+}
+
+/// Loads the image at `path`, passes it `load_options` and starts it.
+///
+/// The boot services and image handle registered with `system::init` are used. Returns
+/// `Error::NotStarted` if `system::init` was not called.
+pub fn chainload(path: &str, load_options: Option<&str>) -> Result<ImageExit, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+    let image_handle = system::image_handle().ok_or(Error::NotStarted)?;
+
+    let request = Chainload::new(boot_services, image_handle);
+
+    match load_options {
+        Some(load_options) => request.load_options(load_options).start(path),
+        None => request.start(path),
+    }
+}
@@ -0,0 +1,131 @@
+//! This protocol returns information about images whose load was denied because of user
+//! authentication policy. These images can be loaded after the user has been identified,
+//! using `BootServices::load_image`.
+
+use core::{ptr, slice};
+
+use crate::{
+    boot::BootServices,
+    device::DevicePath,
+    guid::{Guid, DEFERRED_IMAGE_LOAD_PROTOCOL_GUID},
+    protocol::Protocol,
+    status::{Error, Status},
+    Handle,
+};
+
+/// Returns information about images whose load was denied because of the user authentication
+/// policy.
+#[repr(C)]
+pub struct DeferredImageLoad {
+    /// Returns information about a deferred image.
+    pub GetImageInfo: eficall! { fn(
+        &DeferredImageLoad,
+        ImageIndex: usize,
+        ImageDevicePath: &mut *const DevicePath,
+        Image: &mut *const u8,
+        ImageSize: &mut usize,
+        BootOption: &mut bool,
+    ) -> Status },
+}
+
+unsafe impl Protocol for DeferredImageLoad {
+    const GUID: Guid = DEFERRED_IMAGE_LOAD_PROTOCOL_GUID;
+}
+
+/// Information about an image whose load was deferred.
+#[derive(Clone, Copy)]
+pub struct DeferredImageInfo<'a> {
+    /// The device path of the image.
+    pub device_path: &'a DevicePath,
+    /// The contents of the image, if it is still available.
+    pub image: Option<&'a [u8]>,
+    /// Whether the image was deferred as part of a boot option.
+    pub boot_option: bool,
+}
+
+impl DeferredImageLoad {
+    /// Returns information about the deferred image with the given index.
+    ///
+    /// Returns `Error::NotFound` if there is no image with the given index.
+    pub fn get_image_info(&self, index: usize) -> Result<DeferredImageInfo, Error> {
+        let mut device_path = ptr::null();
+        let mut image = ptr::null();
+        let mut image_size = 0;
+        let mut boot_option = false;
+
+        (self.GetImageInfo)(
+            self,
+            index,
+            &mut device_path,
+            &mut image,
+            &mut image_size,
+            &mut boot_option,
+        )?;
+
+        if device_path.is_null() {
+            return Err(Error::NotFound);
+        }
+
+        // This is safe under the assumption, that the firmware returned valid pointers.
+        let (device_path, image) = unsafe {
+            let image = if image.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(image, image_size))
+            };
+
+            (&*device_path, image)
+        };
+
+        Ok(DeferredImageInfo {
+            device_path,
+            image,
+            boot_option,
+        })
+    }
+
+    /// Returns an iterator over all deferred images.
+    pub fn images(&self) -> DeferredImages {
+        DeferredImages {
+            protocol: self,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> DeferredImageInfo<'a> {
+    /// Loads the deferred image again, after the user has been authenticated.
+    ///
+    /// Returns the handle of the loaded image, which can be started with `StartImage`.
+    pub fn redispatch(
+        &self,
+        boot_services: &BootServices,
+        parent_image_handle: Handle,
+    ) -> Result<Handle, Error> {
+        boot_services.load_image(
+            self.boot_option,
+            parent_image_handle,
+            self.device_path,
+            self.image,
+        )
+    }
+}
+
+/// An iterator over the deferred images.
+pub struct DeferredImages<'a> {
+    /// The protocol used to query the images.
+    protocol: &'a DeferredImageLoad,
+    /// The index of the next image.
+    index: usize,
+}
+
+impl<'a> Iterator for DeferredImages<'a> {
+    type Item = DeferredImageInfo<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let info = self.protocol.get_image_info(self.index).ok()?;
+        self.index += 1;
+
+        Some(info)
+    }
+}
@@ -1,4 +1,19 @@
-use crate::guid::Guid;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+use core::{mem::size_of, ops::Deref, slice};
+
+#[cfg(feature = "alloc")]
+use crate::string::CStr16;
+use crate::{
+    boot::BootServices,
+    boxed::PoolBuffer,
+    guid::{Guid, DEVICE_PATH_GUID},
+    guids,
+    memory::MemoryType,
+    protocol::Protocol,
+    status::Error,
+    ucs2,
+};
 
 #[repr(C)]
 pub enum DevicePathType {
@@ -74,8 +89,302 @@ pub struct DevicePath {
     pub Length: u16,
 }
 
+unsafe impl Protocol for DevicePath {
+    const GUID: Guid = DEVICE_PATH_GUID;
+}
+
+impl DevicePath {
+    /// Returns the length of the node in bytes, including the header.
+    pub fn length(&self) -> usize {
+        usize::from(self.Length)
+    }
+
+    /// Returns true if the node ends a device path instance or the entire device path.
+    pub fn is_end(&self) -> bool {
+        self.Type == DevicePathType::End as u8
+    }
+
+    /// Returns true if the node has the given type and sub-type.
+    pub fn is(&self, node_type: DevicePathType, sub_type: u8) -> bool {
+        self.Type == node_type as u8 && self.SubType == sub_type
+    }
+
+    /// Returns the size of the entire device path in bytes, including the end node.
+    ///
+    /// All instances of a multi-instance device path are included. Returns
+    /// `Error::InvalidParameter` if a node is shorter than its header.
+    pub fn size(&self) -> Result<usize, Error> {
+        let mut size = 0;
+        let mut node = self;
+
+        loop {
+            let length = node.length();
+
+            if length < size_of::<DevicePath>() {
+                return Err(Error::InvalidParameter);
+            }
+
+            size += length;
+
+            if node.is(DevicePathType::End, DevicePathEndType::Entire as u8) {
+                return Ok(size);
+            }
+
+            // This is safe under the assumption that the firmware terminated the device path.
+            node = unsafe {
+                &*((node as *const DevicePath as *const u8).add(length) as *const DevicePath)
+            };
+        }
+    }
+
+    /// Returns an iterator over the nodes of the device path, excluding the end node.
+    pub fn nodes(&self) -> DevicePathNodes {
+        DevicePathNodes { next: Some(self) }
+    }
+
+    /// Returns the node as a hard drive media node, if it is one.
+    pub fn as_hard_drive(&self) -> Option<&HardDriveDevicePath> {
+        if self.is(DevicePathType::Media, DevicePathMediaType::Harddrive as u8)
+            && self.length() >= size_of::<HardDriveDevicePath>()
+        {
+            // This is safe, because the type and length of the node were checked.
+            Some(unsafe { &*(self as *const DevicePath as *const HardDriveDevicePath) })
+        } else {
+            None
+        }
+    }
+}
+
+/// An iterator over the nodes of a device path.
+pub struct DevicePathNodes<'a> {
+    /// The next node, if the end was not reached yet.
+    next: Option<&'a DevicePath>,
+}
+
+impl<'a> Iterator for DevicePathNodes<'a> {
+    type Item = &'a DevicePath;
+
+    fn next(&mut self) -> Option<&'a DevicePath> {
+        let node = self.next.take()?;
+
+        // A node shorter than its header would never advance, so it ends the iteration as well.
+        if node.is_end() || node.length() < size_of::<DevicePath>() {
+            return None;
+        }
+
+        // This is safe under the assumption that the firmware terminated the device path.
+        self.next = Some(unsafe {
+            &*((node as *const DevicePath as *const u8).add(node.length()) as *const DevicePath)
+        });
+
+        Some(node)
+    }
+}
+
+/// A device path in pool memory, such as one built by `file_path`.
+#[derive(Debug)]
+pub struct DevicePathBuffer(PoolBuffer<u8>);
+
+impl DevicePathBuffer {
+    /// Frees the device path.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        self.0.free(boot_services)
+    }
+}
+
+impl Deref for DevicePathBuffer {
+    type Target = DevicePath;
+
+    fn deref(&self) -> &DevicePath {
+        // This is safe, because the buffer holds a terminated device path and pool memory is
+        // sufficiently aligned.
+        unsafe { &*(self.0.as_ptr() as *const DevicePath) }
+    }
+}
+
+/// Builds the device path of a file on the device with the given device path.
+///
+/// The file path node for `path` is appended to the nodes of `device_path`. Slashes in `path`
+/// are replaced by backslashes and a leading backslash is added if it is missing.
+///
+/// Returns `Error::InvalidParameter` if the path contains a null character or is too long for a
+/// device path node.
+pub fn file_path(
+    boot_services: &BootServices,
+    device_path: &DevicePath,
+    path: &str,
+) -> Result<DevicePathBuffer, Error> {
+    if path.contains('\0') {
+        return Err(Error::InvalidParameter);
+    }
+
+    let leading_separator = !path.starts_with(|character| character == '\\' || character == '/');
+    let path_units = ucs2::encoded_len(path) + leading_separator as usize + 1;
+    let file_node_len = size_of::<DevicePath>() + path_units * size_of::<u16>();
+
+    if file_node_len > usize::from(u16::max_value()) {
+        return Err(Error::InvalidParameter);
+    }
+
+    let device_len: usize = device_path.nodes().map(DevicePath::length).sum();
+    let mut buffer = PoolBuffer::new(
+        boot_services,
+        MemoryType::LoaderData,
+        device_len + file_node_len + size_of::<DevicePath>(),
+    )?;
+
+    // This is safe, because the nodes were measured above and lie within the device path.
+    buffer[..device_len].copy_from_slice(unsafe {
+        slice::from_raw_parts(device_path as *const DevicePath as *const u8, device_len)
+    });
+
+    let (file_node, end_node) = buffer[device_len..].split_at_mut(file_node_len);
+
+    file_node[0] = DevicePathType::Media as u8;
+    file_node[1] = DevicePathMediaType::Filepath as u8;
+    file_node[2..4].copy_from_slice(&(file_node_len as u16).to_le_bytes());
+
+    let separator = if leading_separator { Some('\\') } else { None };
+    let characters = separator
+        .into_iter()
+        .chain(path.chars())
+        .map(|character| match character {
+            '/' => '\\',
+            character => character,
+        });
+
+    let mut units = [0; 2];
+    let mut offset = size_of::<DevicePath>();
+
+    for character in characters {
+        for unit in character.encode_utf16(&mut units) {
+            file_node[offset..offset + 2].copy_from_slice(&unit.to_le_bytes());
+            offset += 2;
+        }
+    }
+
+    // The null terminator is already there, since the buffer was filled with zeros.
+    end_node.copy_from_slice(&[
+        DevicePathType::End as u8,
+        DevicePathEndType::Entire as u8,
+        size_of::<DevicePath>() as u8,
+        0,
+    ]);
+
+    Ok(DevicePathBuffer(buffer))
+}
+
+/// The signature type of a hard drive node with an MBR signature.
+pub const SIGNATURE_TYPE_MBR: u8 = 0x01;
+/// The signature type of a hard drive node with a GPT partition GUID.
+pub const SIGNATURE_TYPE_GUID: u8 = 0x02;
+
+/// A media node describing a partition of a hard drive.
+#[repr(C, packed)]
+pub struct HardDriveDevicePath {
+    pub Header: DevicePath,
+    /// The number of the partition, starting at 1, or 0 for the whole disk.
+    pub PartitionNumber: u32,
+    /// The first logical block of the partition.
+    pub PartitionStart: u64,
+    /// The size of the partition in logical blocks.
+    pub PartitionSize: u64,
+    /// The signature of the partition, whose format is given by `SignatureType`.
+    pub PartitionSignature: [u8; 16],
+    /// The partitioning format of the disk, 0x01 for MBR and 0x02 for GPT.
+    pub PartitionFormat: u8,
+    /// The type of `PartitionSignature`.
+    pub SignatureType: u8,
+}
+
+impl HardDriveDevicePath {
+    /// Returns the unique partition GUID, if the partition is identified by one.
+    pub fn partition_guid(&self) -> Option<Guid> {
+        if self.SignatureType == SIGNATURE_TYPE_GUID {
+            Some(Guid::from_bytes(self.PartitionSignature))
+        } else {
+            None
+        }
+    }
+}
+
 #[repr(C)]
 pub struct VendorDevicePath {
     pub Header: DevicePath,
     pub Guid: Guid,
 }
+
+/// Converts device paths and device nodes to their text representation.
+#[repr(C)]
+pub struct DevicePathToText {
+    /// Converts a single device node to text.
+    pub ConvertDeviceNodeToText: eficall! { fn(
+        DeviceNode: &DevicePath,
+        DisplayOnly: bool,
+        AllowShortcuts: bool,
+    ) -> *mut u16 },
+    /// Converts a complete device path to text.
+    pub ConvertDevicePathToText: eficall! { fn(
+        DevicePath: &DevicePath,
+        DisplayOnly: bool,
+        AllowShortcuts: bool,
+    ) -> *mut u16 },
+}
+
+unsafe impl Protocol for DevicePathToText {
+    const GUID: Guid = guids::protocol::DEVICE_PATH_TO_TEXT;
+}
+
+#[cfg(feature = "alloc")]
+impl DevicePathToText {
+    /// Returns the text representation of a single device node.
+    ///
+    /// `display_only` selects the shorter display format, `allow_shortcuts` allows the use of
+    /// shortcut forms of the text representation.
+    pub fn device_node_to_string(
+        &self,
+        boot_services: &BootServices,
+        device_node: &DevicePath,
+        display_only: bool,
+        allow_shortcuts: bool,
+    ) -> Result<String, Error> {
+        let text = (self.ConvertDeviceNodeToText)(device_node, display_only, allow_shortcuts);
+
+        // This is safe, because the firmware returns a pool allocated string or null.
+        unsafe { pool_string(boot_services, text) }
+    }
+
+    /// Returns the text representation of a complete device path.
+    ///
+    /// `display_only` selects the shorter display format, `allow_shortcuts` allows the use of
+    /// shortcut forms of the text representation.
+    pub fn device_path_to_string(
+        &self,
+        boot_services: &BootServices,
+        device_path: &DevicePath,
+        display_only: bool,
+        allow_shortcuts: bool,
+    ) -> Result<String, Error> {
+        let text = (self.ConvertDevicePathToText)(device_path, display_only, allow_shortcuts);
+
+        // This is safe, because the firmware returns a pool allocated string or null.
+        unsafe { pool_string(boot_services, text) }
+    }
+}
+
+/// Copies a string allocated by the firmware into a `String` and frees it.
+///
+/// # Safety
+/// `text` must be null or a null terminated string allocated with `AllocatePool`.
+#[cfg(feature = "alloc")]
+unsafe fn pool_string(boot_services: &BootServices, text: *mut u16) -> Result<String, Error> {
+    if text.is_null() {
+        return Err(Error::OutOfResources);
+    }
+
+    let string = CStr16::from_ptr(text).to_string();
+
+    boot_services.free_pool(text as *const u8)?;
+
+    Ok(string)
+}
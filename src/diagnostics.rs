@@ -0,0 +1,368 @@
+//! Register, stack and handle database dumps to debug crashes and firmware differences.
+//!
+//! A panic message alone rarely explains a crash that only happens on some firmware. The
+//! registers and the raw stack contents at the time of the panic often do. `Registers::capture`
+//! records the general purpose registers of the current CPU with inline assembly, which is
+//! implemented for `x86_64` and `aarch64`. `write_dump` writes the registers and a hexdump of the
+//! stack around the stack pointer to any writer, such as the console or a `SerialLogger`.
+//!
+//! With the `panic-handler` feature enabled, the panic handler prints a dump after the panic
+//! message.
+//!
+//! `write_handle_dump` lists the handle database, similar to the `dh` command of the shell, which
+//! helps to find out why a protocol cannot be found. It is available on every architecture.
+
+use core::fmt::{self, Write};
+
+use crate::{
+    boot::BootServices,
+    device::{DevicePath, DevicePathToText},
+    format,
+    guid::Guid,
+    guids,
+    status::Error,
+    ucs2::{self, Lossy},
+    Handle,
+};
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+use crate::{
+    format::HEXDUMP_BYTES_PER_LINE,
+    system::SystemTable,
+    text::{self, BackgroundColor, BufferedWriter, Color, ForegroundColor},
+};
+
+/// The number of bytes below the stack pointer included in a stack dump.
+pub const STACK_DUMP_BELOW: usize = 64;
+
+/// The number of bytes at and above the stack pointer included in a stack dump.
+pub const STACK_DUMP_ABOVE: usize = 512;
+
+/// The general purpose registers of an `x86_64` CPU.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct Registers {
+    pub rax: u64,
+    pub rbx: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub rbp: u64,
+    pub rsp: u64,
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    pub rip: u64,
+    pub rflags: u64,
+}
+
+/// The general purpose registers of an `aarch64` CPU.
+#[cfg(target_arch = "aarch64")]
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct Registers {
+    /// The registers `x0` to `x30`, where `x29` is the frame pointer and `x30` the link register.
+    pub x: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Registers {
+    /// Captures the registers at the point of the call.
+    ///
+    /// The register used to address the result is overwritten before it is stored, and `rip`
+    /// points into this function.
+    #[inline(always)]
+    pub fn capture() -> Registers {
+        let mut registers = Registers::default();
+
+        // This is safe, because only the fields of `registers` are written.
+        unsafe {
+            asm!(
+                "mov [{0} + 0x00], rax",
+                "mov [{0} + 0x08], rbx",
+                "mov [{0} + 0x10], rcx",
+                "mov [{0} + 0x18], rdx",
+                "mov [{0} + 0x20], rsi",
+                "mov [{0} + 0x28], rdi",
+                "mov [{0} + 0x30], rbp",
+                "mov [{0} + 0x38], rsp",
+                "mov [{0} + 0x40], r8",
+                "mov [{0} + 0x48], r9",
+                "mov [{0} + 0x50], r10",
+                "mov [{0} + 0x58], r11",
+                "mov [{0} + 0x60], r12",
+                "mov [{0} + 0x68], r13",
+                "mov [{0} + 0x70], r14",
+                "mov [{0} + 0x78], r15",
+                "lea {1}, [rip]",
+                "mov [{0} + 0x80], {1}",
+                "pushfq",
+                "pop {1}",
+                "mov [{0} + 0x88], {1}",
+                in(reg) &mut registers,
+                out(reg) _,
+            );
+        }
+
+        registers
+    }
+
+    /// Returns the stack pointer.
+    pub fn stack_pointer(&self) -> u64 {
+        self.rsp
+    }
+
+    /// Returns the instruction pointer.
+    pub fn instruction_pointer(&self) -> u64 {
+        self.rip
+    }
+
+    /// Returns the names and values of the registers in the order they are printed.
+    fn named(&self) -> [(&'static str, u64); 18] {
+        [
+            ("rax", self.rax),
+            ("rbx", self.rbx),
+            ("rcx", self.rcx),
+            ("rdx", self.rdx),
+            ("rsi", self.rsi),
+            ("rdi", self.rdi),
+            ("rbp", self.rbp),
+            ("rsp", self.rsp),
+            ("r8", self.r8),
+            ("r9", self.r9),
+            ("r10", self.r10),
+            ("r11", self.r11),
+            ("r12", self.r12),
+            ("r13", self.r13),
+            ("r14", self.r14),
+            ("r15", self.r15),
+            ("rip", self.rip),
+            ("rflags", self.rflags),
+        ]
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl Registers {
+    /// Captures the registers at the point of the call.
+    ///
+    /// The registers used to address the result are overwritten before they are stored, and `pc`
+    /// points into this function.
+    #[inline(always)]
+    pub fn capture() -> Registers {
+        let mut registers = Registers::default();
+
+        // This is safe, because only the fields of `registers` are written.
+        unsafe {
+            asm!(
+                "stp x0, x1, [{0}, #0x00]",
+                "stp x2, x3, [{0}, #0x10]",
+                "stp x4, x5, [{0}, #0x20]",
+                "stp x6, x7, [{0}, #0x30]",
+                "stp x8, x9, [{0}, #0x40]",
+                "stp x10, x11, [{0}, #0x50]",
+                "stp x12, x13, [{0}, #0x60]",
+                "stp x14, x15, [{0}, #0x70]",
+                "stp x16, x17, [{0}, #0x80]",
+                "stp x18, x19, [{0}, #0x90]",
+                "stp x20, x21, [{0}, #0xa0]",
+                "stp x22, x23, [{0}, #0xb0]",
+                "stp x24, x25, [{0}, #0xc0]",
+                "stp x26, x27, [{0}, #0xd0]",
+                "stp x28, x29, [{0}, #0xe0]",
+                "str x30, [{0}, #0xf0]",
+                "mov {1}, sp",
+                "str {1}, [{0}, #0xf8]",
+                "adr {1}, .",
+                "str {1}, [{0}, #0x100]",
+                in(reg) &mut registers,
+                out(reg) _,
+                options(nostack),
+            );
+        }
+
+        registers
+    }
+
+    /// Returns the stack pointer.
+    pub fn stack_pointer(&self) -> u64 {
+        self.sp
+    }
+
+    /// Returns the instruction pointer.
+    pub fn instruction_pointer(&self) -> u64 {
+        self.pc
+    }
+
+    /// Returns the names and values of the registers in the order they are printed.
+    fn named(&self) -> [(&'static str, u64); 33] {
+        const NAMES: [&str; 31] = [
+            "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13",
+            "x14", "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25",
+            "x26", "x27", "x28", "fp", "lr",
+        ];
+
+        let mut named = [("", 0); 33];
+
+        for (index, value) in self.x.iter().enumerate() {
+            named[index] = (NAMES[index], *value);
+        }
+
+        named[31] = ("sp", self.sp);
+        named[32] = ("pc", self.pc);
+
+        named
+    }
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, (name, value)) in self.named().iter().enumerate() {
+            if index != 0 {
+                f.write_str(if index % 3 == 0 { "\n" } else { "  " })?;
+            }
+
+            write!(f, "{:>6} {:016x}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a hexdump of the memory from `start` to `start + len`.
+///
+/// # Safety
+/// The whole range must be readable.
+pub unsafe fn write_hexdump<W: Write>(writer: &mut W, start: usize, len: usize) -> fmt::Result {
+    let data = core::slice::from_raw_parts(start as *const u8, len);
+
+    format::write_hexdump(writer, data, start as u64)
+}
+
+/// Writes the registers and the stack around the stack pointer.
+///
+/// The stack from `STACK_DUMP_BELOW` bytes below to `STACK_DUMP_ABOVE` bytes above the stack
+/// pointer is dumped. On a nearly empty stack this may read past its end.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub fn write_dump<W: Write>(writer: &mut W, registers: &Registers) -> fmt::Result {
+    let stack_pointer = registers.stack_pointer() as usize;
+    let start = stack_pointer.saturating_sub(STACK_DUMP_BELOW) & !(HEXDUMP_BYTES_PER_LINE - 1);
+
+    writeln!(writer, "Registers:")?;
+    writeln!(writer, "{}", registers)?;
+    writeln!(writer, "Stack:")?;
+
+    // This is safe under the assumption that the stack extends past the dumped range.
+    unsafe { write_hexdump(writer, start, stack_pointer + STACK_DUMP_ABOVE - start) }
+}
+
+/// Captures the registers and prints them and the stack to the standard error console.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub fn print_dump(system_table: &'static SystemTable) -> Result<(), Error> {
+    let registers = Registers::capture();
+    let output = &*system_table.ConsoleError;
+    let color = Color::new(ForegroundColor::LightRed, BackgroundColor::Black);
+
+    output.with_color(color, |output| {
+        let mut writer = LineFeeds(BufferedWriter::new(output));
+
+        write_dump(&mut writer, &registers).map_err(|_| Error::DeviceError)?;
+
+        writer.0.flush()
+    })?
+}
+
+/// Translates line feeds to the carriage return line feed pairs the console expects.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+struct LineFeeds<W>(W);
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+impl<W: Write> Write for LineFeeds<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        text::write_crlf(s, |text| self.0.write_str(text))
+    }
+}
+
+/// Writes every handle of the handle database with the protocols installed on it.
+///
+/// Known protocols are shown by the name of their constant in `guids::protocol`, others by their
+/// GUID. If `device_paths` is true and the firmware provides the Device Path to Text protocol,
+/// device paths are shown as text.
+pub fn write_handle_dump<W: Write>(
+    writer: &mut W,
+    boot_services: &BootServices,
+    device_paths: bool,
+) -> Result<(), Error> {
+    let to_text = if device_paths {
+        boot_services.locate_protocol::<DevicePathToText>().ok()
+    } else {
+        None
+    };
+
+    let handles = boot_services.all_handles()?;
+
+    for &handle in handles.iter() {
+        writeln!(writer, "{:?}", handle).map_err(|_| Error::DeviceError)?;
+
+        let protocols = match boot_services.protocols_per_handle(handle) {
+            Ok(protocols) => protocols,
+            Err(error) => {
+                writeln!(writer, "  {}", error).map_err(|_| Error::DeviceError)?;
+                continue;
+            }
+        };
+
+        for &protocol in protocols.iter() {
+            write_protocol(writer, boot_services, handle, protocol, to_text)
+                .map_err(|_| Error::DeviceError)?;
+        }
+
+        protocols.free(boot_services)?;
+    }
+
+    handles.free(boot_services)
+}
+
+/// Writes a line for a protocol installed on a handle.
+fn write_protocol<W: Write>(
+    writer: &mut W,
+    boot_services: &BootServices,
+    handle: Handle,
+    protocol: &Guid,
+    to_text: Option<&DevicePathToText>,
+) -> fmt::Result {
+    match guids::protocol::name(protocol) {
+        Some(name) => write!(writer, "  {}", name)?,
+        None => write!(writer, "  {}", protocol)?,
+    }
+
+    let to_text = to_text.filter(|_| *protocol == guids::protocol::DEVICE_PATH);
+
+    if let Some(to_text) = to_text {
+        if let Ok(device_path) = boot_services.handle_protocol::<DevicePath>(handle) {
+            let text = (to_text.ConvertDevicePathToText)(device_path, false, true);
+
+            if !text.is_null() {
+                // This is safe, because the firmware returned a null terminated string.
+                let units = unsafe { ucs2::from_ptr(text) };
+
+                write!(writer, ": {}", Lossy::new(units))?;
+
+                // There is no way to report the error here.
+                let _ = boot_services.free_pool(text as *const u8);
+            }
+        }
+    }
+
+    writer.write_char('\n')
+}
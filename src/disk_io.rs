@@ -0,0 +1,89 @@
+//! This protocol provides byte granular access to block devices.
+
+use crate::{
+    block_io::BlockIo,
+    boot::BootServices,
+    guid::Guid,
+    guids,
+    protocol::Protocol,
+    status::{Error, Status},
+    Handle,
+};
+
+/// The revision of the disk IO protocol defined by the specification.
+pub const DISK_IO_PROTOCOL_REVISION: u64 = 0x0001_0000;
+
+/// This protocol provides byte granular access to block devices.
+#[repr(C)]
+pub struct DiskIo {
+    /// The revision to which the disk IO interface adheres.
+    pub Revision: u64,
+    /// Reads bytes at an offset from the device.
+    pub ReadDisk: eficall! { fn(
+        &DiskIo,
+        MediaId: u32,
+        Offset: u64,
+        BufferSize: usize,
+        Buffer: *mut u8,
+    ) -> Status },
+    /// Writes bytes at an offset to the device.
+    pub WriteDisk: eficall! { fn(
+        &DiskIo,
+        MediaId: u32,
+        Offset: u64,
+        BufferSize: usize,
+        Buffer: *const u8,
+    ) -> Status },
+}
+
+unsafe impl Protocol for DiskIo {
+    const GUID: Guid = guids::protocol::DISK_IO;
+}
+
+impl DiskIo {
+    /// Reads `buffer.len()` bytes at the byte offset `offset` of the media with `media_id`.
+    pub fn read_disk(&self, media_id: u32, offset: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        (self.ReadDisk)(self, media_id, offset, buffer.len(), buffer.as_mut_ptr())?;
+
+        Ok(())
+    }
+
+    /// Writes `data` at the byte offset `offset` of the media with `media_id`.
+    pub fn write_disk(&self, media_id: u32, offset: u64, data: &[u8]) -> Result<(), Error> {
+        (self.WriteDisk)(self, media_id, offset, data.len(), data.as_ptr())?;
+
+        Ok(())
+    }
+}
+
+/// A disk IO protocol together with the media it accesses.
+#[derive(Clone, Copy)]
+pub struct Disk<'a> {
+    /// The disk IO protocol.
+    pub disk_io: &'a DiskIo,
+    /// The ID of the media, which makes accesses fail if the media changed.
+    pub media_id: u32,
+}
+
+impl<'a> Disk<'a> {
+    /// Opens the disk IO protocol of the given handle for its current media.
+    pub fn from_handle(boot_services: &'a BootServices, handle: Handle) -> Result<Disk<'a>, Error> {
+        let disk_io = boot_services.handle_protocol::<DiskIo>(handle)?;
+        let media_id = boot_services
+            .handle_protocol::<BlockIo>(handle)?
+            .Media
+            .MediaId;
+
+        Ok(Disk { disk_io, media_id })
+    }
+
+    /// Reads `buffer.len()` bytes at the byte offset `offset`.
+    pub fn read(&self, offset: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        self.disk_io.read_disk(self.media_id, offset, buffer)
+    }
+
+    /// Writes `data` at the byte offset `offset`.
+    pub fn write(&self, offset: u64, data: &[u8]) -> Result<(), Error> {
+        self.disk_io.write_disk(self.media_id, offset, data)
+    }
+}
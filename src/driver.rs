@@ -0,0 +1,78 @@
+//! Provides the protocols that describe UEFI drivers.
+
+use core::mem::size_of;
+
+use crate::{
+    boot::BootServices,
+    guid::{Guid, DRIVER_SUPPORTED_EFI_VERSION_PROTOCOL_GUID},
+    protocol::Protocol,
+    status::Error,
+    system::SYSTEM_TABLE_REVISION_2_70,
+    Handle,
+};
+
+/// Provides information about the version of the UEFI specification a driver is compliant with.
+///
+/// This protocol is installed on the image handle of the driver.
+#[derive(Debug)]
+#[repr(C)]
+pub struct DriverSupportedEfiVersion {
+    /// The size, in bytes, of the entire structure. Future versions of this specification may
+    /// grow the size of the structure.
+    pub Length: u32,
+    /// The latest version of the UEFI specification that this driver conforms to.
+    pub FirmwareVersion: u32,
+}
+
+unsafe impl Protocol for DriverSupportedEfiVersion {
+    const GUID: Guid = DRIVER_SUPPORTED_EFI_VERSION_PROTOCOL_GUID;
+}
+
+impl DriverSupportedEfiVersion {
+    /// Creates the protocol for a driver that conforms to the given revision of the UEFI
+    /// specification.
+    pub const fn new(firmware_version: u32) -> DriverSupportedEfiVersion {
+        DriverSupportedEfiVersion {
+            Length: size_of::<DriverSupportedEfiVersion>() as u32,
+            FirmwareVersion: firmware_version,
+        }
+    }
+
+    /// Installs the protocol on the image handle of the driver.
+    pub fn install(
+        &'static self,
+        boot_services: &BootServices,
+        image_handle: Handle,
+    ) -> Result<(), Error> {
+        // This is safe, because the protocol is valid and lives forever.
+        unsafe {
+            boot_services.install_protocol_interface(
+                Some(image_handle),
+                &DRIVER_SUPPORTED_EFI_VERSION_PROTOCOL_GUID,
+                self as *const DriverSupportedEfiVersion as *const u8,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Uninstalls the protocol from the image handle of the driver.
+    pub fn uninstall(
+        &'static self,
+        boot_services: &BootServices,
+        image_handle: Handle,
+    ) -> Result<(), Error> {
+        boot_services.uninstall_protocol_interface(
+            image_handle,
+            &DRIVER_SUPPORTED_EFI_VERSION_PROTOCOL_GUID,
+            self as *const DriverSupportedEfiVersion as *const u8,
+        )
+    }
+}
+
+impl Default for DriverSupportedEfiVersion {
+    /// Creates the protocol for the revision of the UEFI specification this library is based on.
+    fn default() -> Self {
+        DriverSupportedEfiVersion::new(SYSTEM_TABLE_REVISION_2_70)
+    }
+}
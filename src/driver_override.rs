@@ -0,0 +1,356 @@
+//! Provides the protocols that override the default precedence rules `ConnectController()` uses
+//! to select the drivers that manage a controller.
+//!
+//! The platform driver override protocol has the highest precedence and is produced by the
+//! platform. `PlatformDriverOverrideInstance` implements it for a static list of overrides.
+//!
+//! The bus specific driver override protocol is produced by bus drivers on the handles of their
+//! child controllers, for example to supply the drivers from the option ROM of a PCI device.
+//! `BusSpecificDriverOverrideInstance` implements it for a static list of drivers.
+
+use crate::{
+    boot::BootServices,
+    device::DevicePath,
+    guid::{
+        Guid, BUS_SPECIFIC_DRIVER_OVERRIDE_PROTOCOL_GUID, PLATFORM_DRIVER_OVERRIDE_PROTOCOL_GUID,
+    },
+    protocol::Protocol,
+    status::{Error, Status, SUCCESS},
+    Handle, OptionalHandle,
+};
+
+/// Provides a platform specific override mechanism for the selection of the best driver for a
+/// given controller.
+#[repr(C)]
+pub struct PlatformDriverOverride {
+    /// Retrieves the image handle of the platform override driver for a controller in the
+    /// system.
+    pub GetDriver: eficall! { fn(
+        &PlatformDriverOverride,
+        ControllerHandle: Handle,
+        DriverImageHandle: &mut OptionalHandle,
+    ) -> Status },
+    /// Retrieves the device path of the platform override driver for a controller in the
+    /// system.
+    pub GetDriverPath: eficall! { fn(
+        &PlatformDriverOverride,
+        ControllerHandle: Handle,
+        DriverImagePath: &mut *const DevicePath,
+    ) -> Status },
+    /// Used to associate a driver image handle with a device path that was returned on a prior
+    /// call to the `GetDriverPath()` service.
+    pub DriverLoaded: eficall! { fn(
+        &PlatformDriverOverride,
+        ControllerHandle: Handle,
+        DriverImagePath: *const DevicePath,
+        DriverImageHandle: Handle,
+    ) -> Status },
+}
+
+unsafe impl Protocol for PlatformDriverOverride {
+    const GUID: Guid = PLATFORM_DRIVER_OVERRIDE_PROTOCOL_GUID;
+}
+
+impl PlatformDriverOverride {
+    /// Retrieves the image handle of the next platform override driver for the controller.
+    ///
+    /// `driver_image_handle` is updated to the next driver. To start the search, pass `None`.
+    /// Returns `Error::NotFound` if there are no more override drivers.
+    pub fn get_driver(
+        &self,
+        controller_handle: Handle,
+        driver_image_handle: &mut OptionalHandle,
+    ) -> Result<(), Error> {
+        (self.GetDriver)(self, controller_handle, driver_image_handle)?;
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the override drivers of the controller, in the order of their
+    /// precedence.
+    pub fn drivers(&self, controller_handle: Handle) -> OverrideDrivers {
+        OverrideDrivers {
+            get_driver: GetDriver::Platform(self, controller_handle),
+            driver_image_handle: None,
+        }
+    }
+}
+
+/// Provides a bus specific override mechanism for the selection of the best driver for a given
+/// controller.
+#[repr(C)]
+pub struct BusSpecificDriverOverride {
+    /// Uses a bus specific algorithm to retrieve a driver image handle for a controller.
+    pub GetDriver: eficall! { fn(
+        &BusSpecificDriverOverride,
+        DriverImageHandle: &mut OptionalHandle,
+    ) -> Status },
+}
+
+unsafe impl Protocol for BusSpecificDriverOverride {
+    const GUID: Guid = BUS_SPECIFIC_DRIVER_OVERRIDE_PROTOCOL_GUID;
+}
+
+impl BusSpecificDriverOverride {
+    /// Retrieves the image handle of the next bus specific override driver for the controller
+    /// this protocol is installed on.
+    ///
+    /// `driver_image_handle` is updated to the next driver. To start the search, pass `None`.
+    /// Returns `Error::NotFound` if there are no more override drivers.
+    pub fn get_driver(&self, driver_image_handle: &mut OptionalHandle) -> Result<(), Error> {
+        (self.GetDriver)(self, driver_image_handle)?;
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the override drivers of the controller, in the order of their
+    /// precedence.
+    pub fn drivers(&self) -> OverrideDrivers {
+        OverrideDrivers {
+            get_driver: GetDriver::BusSpecific(self),
+            driver_image_handle: None,
+        }
+    }
+}
+
+/// The protocol used to iterate over the override drivers.
+pub(crate) enum GetDriver<'a> {
+    /// The platform driver override protocol and the controller whose drivers are iterated.
+    Platform(&'a PlatformDriverOverride, Handle),
+    /// The bus specific driver override protocol.
+    BusSpecific(&'a BusSpecificDriverOverride),
+}
+
+/// An iterator over the override drivers of a controller.
+pub struct OverrideDrivers<'a> {
+    /// The protocol used to get the next driver.
+    get_driver: GetDriver<'a>,
+    /// The last returned driver.
+    driver_image_handle: OptionalHandle,
+}
+
+impl<'a> Iterator for OverrideDrivers<'a> {
+    type Item = Handle;
+
+    fn next(&mut self) -> Option<Handle> {
+        match self.get_driver {
+            GetDriver::Platform(protocol, controller_handle) => protocol
+                .get_driver(controller_handle, &mut self.driver_image_handle)
+                .ok()?,
+            GetDriver::BusSpecific(protocol) => {
+                protocol.get_driver(&mut self.driver_image_handle).ok()?
+            }
+        }
+
+        self.driver_image_handle
+    }
+}
+
+/// Forces a driver to manage a controller.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DriverOverride {
+    /// The controller that is managed by the driver.
+    pub controller: Handle,
+    /// The image handle of the driver.
+    pub driver: Handle,
+}
+
+/// An implementation of the platform driver override protocol for a static list of overrides.
+///
+/// For every controller, the drivers are returned in the order in which they appear in the list.
+#[repr(C)]
+pub struct PlatformDriverOverrideInstance {
+    /// The protocol interface that is installed. This must be the first field, so the instance
+    /// can be found from the interface.
+    protocol: PlatformDriverOverride,
+    /// The overrides that are reported.
+    overrides: &'static [DriverOverride],
+}
+
+impl PlatformDriverOverrideInstance {
+    /// Creates a new instance reporting the given overrides.
+    pub const fn new(overrides: &'static [DriverOverride]) -> PlatformDriverOverrideInstance {
+        PlatformDriverOverrideInstance {
+            protocol: PlatformDriverOverride {
+                GetDriver: platform_get_driver,
+                GetDriverPath: platform_get_driver_path,
+                DriverLoaded: platform_driver_loaded,
+            },
+            overrides,
+        }
+    }
+
+    /// Returns the protocol interface of this instance.
+    pub fn protocol(&self) -> &PlatformDriverOverride {
+        &self.protocol
+    }
+
+    /// Installs the protocol on the given handle, or a new handle if `handle` is `None`.
+    ///
+    /// Returns the handle the protocol was installed on.
+    pub fn install(
+        &'static self,
+        boot_services: &BootServices,
+        handle: Option<Handle>,
+    ) -> Result<Handle, Error> {
+        // This is safe, because the instance is a valid protocol interface that lives forever.
+        unsafe {
+            boot_services.install_protocol_interface(
+                handle,
+                &PLATFORM_DRIVER_OVERRIDE_PROTOCOL_GUID,
+                &self.protocol as *const PlatformDriverOverride as *const u8,
+            )
+        }
+    }
+
+    /// Uninstalls the protocol from the given handle.
+    pub fn uninstall(
+        &'static self,
+        boot_services: &BootServices,
+        handle: Handle,
+    ) -> Result<(), Error> {
+        boot_services.uninstall_protocol_interface(
+            handle,
+            &PLATFORM_DRIVER_OVERRIDE_PROTOCOL_GUID,
+            &self.protocol as *const PlatformDriverOverride as *const u8,
+        )
+    }
+}
+
+/// An implementation of the bus specific driver override protocol for a static list of drivers.
+///
+/// The drivers are returned in the order in which they appear in the list.
+#[repr(C)]
+pub struct BusSpecificDriverOverrideInstance {
+    /// The protocol interface that is installed. This must be the first field, so the instance
+    /// can be found from the interface.
+    protocol: BusSpecificDriverOverride,
+    /// The image handles of the drivers that are reported.
+    drivers: &'static [Handle],
+}
+
+impl BusSpecificDriverOverrideInstance {
+    /// Creates a new instance reporting the given drivers.
+    pub const fn new(drivers: &'static [Handle]) -> BusSpecificDriverOverrideInstance {
+        BusSpecificDriverOverrideInstance {
+            protocol: BusSpecificDriverOverride {
+                GetDriver: bus_specific_get_driver,
+            },
+            drivers,
+        }
+    }
+
+    /// Returns the protocol interface of this instance.
+    pub fn protocol(&self) -> &BusSpecificDriverOverride {
+        &self.protocol
+    }
+
+    /// Installs the protocol on the handle of a child controller.
+    pub fn install(
+        &'static self,
+        boot_services: &BootServices,
+        controller_handle: Handle,
+    ) -> Result<(), Error> {
+        // This is safe, because the instance is a valid protocol interface that lives forever.
+        unsafe {
+            boot_services.install_protocol_interface(
+                Some(controller_handle),
+                &BUS_SPECIFIC_DRIVER_OVERRIDE_PROTOCOL_GUID,
+                &self.protocol as *const BusSpecificDriverOverride as *const u8,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Uninstalls the protocol from the handle of a child controller.
+    pub fn uninstall(
+        &'static self,
+        boot_services: &BootServices,
+        controller_handle: Handle,
+    ) -> Result<(), Error> {
+        boot_services.uninstall_protocol_interface(
+            controller_handle,
+            &BUS_SPECIFIC_DRIVER_OVERRIDE_PROTOCOL_GUID,
+            &self.protocol as *const BusSpecificDriverOverride as *const u8,
+        )
+    }
+}
+
+/// Returns the next driver in `drivers` after `driver_image_handle` and stores it there.
+fn next_driver<I>(mut drivers: I, driver_image_handle: &mut OptionalHandle) -> Status
+where
+    I: Iterator<Item = Handle>,
+{
+    if let Some(current) = *driver_image_handle {
+        if drivers.by_ref().find(|&driver| driver == current).is_none() {
+            return Error::InvalidParameter.into();
+        }
+    }
+
+    match drivers.next() {
+        Some(driver) => {
+            *driver_image_handle = Some(driver);
+            SUCCESS
+        }
+        None => Error::NotFound.into(),
+    }
+}
+
+/// Returns the instance that contains the protocol interface.
+fn platform_instance(this: &PlatformDriverOverride) -> &PlatformDriverOverrideInstance {
+    // This is safe, because the interface is only ever installed as part of an instance.
+    unsafe { &*(this as *const PlatformDriverOverride as *const PlatformDriverOverrideInstance) }
+}
+
+eficall! {
+    fn platform_get_driver(
+        this: &PlatformDriverOverride,
+        controller_handle: Handle,
+        driver_image_handle: &mut OptionalHandle,
+    ) -> Status {
+        let drivers = platform_instance(this)
+            .overrides
+            .iter()
+            .filter(|entry| entry.controller == controller_handle)
+            .map(|entry| entry.driver);
+
+        next_driver(drivers, driver_image_handle)
+    }
+}
+
+eficall! {
+    fn platform_get_driver_path(
+        _this: &PlatformDriverOverride,
+        _controller_handle: Handle,
+        _driver_image_path: &mut *const DevicePath,
+    ) -> Status {
+        // The overrides are specified by image handles, so there are never any drivers to load.
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn platform_driver_loaded(
+        _this: &PlatformDriverOverride,
+        _controller_handle: Handle,
+        _driver_image_path: *const DevicePath,
+        _driver_image_handle: Handle,
+    ) -> Status {
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn bus_specific_get_driver(
+        this: &BusSpecificDriverOverride,
+        driver_image_handle: &mut OptionalHandle,
+    ) -> Status {
+        // This is safe, because the interface is only ever installed as part of an instance.
+        let instance = unsafe {
+            &*(this as *const BusSpecificDriverOverride as *const BusSpecificDriverOverrideInstance)
+        };
+
+        next_driver(instance.drivers.iter().cloned(), driver_image_handle)
+    }
+}
@@ -0,0 +1,490 @@
+//! Loads ELF64 kernels into memory.
+//!
+//! The loadable segments are placed at their physical addresses (`p_paddr`) in pages allocated
+//! with `AllocateType::Address`. Position independent executables are relocated to any free
+//! pages if their addresses are not available, which requires that they only use relative
+//! relocations. The returned `LoadedElf` describes where the segments ended up, so they can be
+//! mapped by the kernel or passed on in the boot information.
+
+use core::{convert::TryFrom, ptr};
+
+use crate::{
+    boot::{AllocateType, BootServices},
+    memory::{MemoryType, PhysicalAddress, PAGE_SIZE},
+    status::Error,
+    system,
+};
+
+/// The maximum number of loadable segments in a `LoadedElf`.
+pub const MAX_LOADED_SEGMENTS: usize = 16;
+
+/// The file uses 64 bit objects.
+pub const ELFCLASS64: u8 = 2;
+/// The file uses little endian encoding.
+pub const ELFDATA2LSB: u8 = 1;
+/// An executable file.
+pub const ET_EXEC: u16 = 2;
+/// A shared object file, which is also used for position independent executables.
+pub const ET_DYN: u16 = 3;
+/// The machine type of `x86_64`.
+pub const EM_X86_64: u16 = 62;
+/// The machine type of `aarch64`.
+pub const EM_AARCH64: u16 = 183;
+/// A loadable segment.
+pub const PT_LOAD: u32 = 1;
+/// The segment holding the dynamic linking information.
+pub const PT_DYNAMIC: u32 = 2;
+/// The segment is executable.
+pub const PF_X: u32 = 0x1;
+/// The segment is writable.
+pub const PF_W: u32 = 0x2;
+/// The segment is readable.
+pub const PF_R: u32 = 0x4;
+
+/// The machine type of the processor this crate is compiled for.
+#[cfg(target_arch = "x86_64")]
+pub const EM_NATIVE: u16 = EM_X86_64;
+/// The machine type of the processor this crate is compiled for.
+#[cfg(target_arch = "aarch64")]
+pub const EM_NATIVE: u16 = EM_AARCH64;
+/// The machine type of the processor this crate is compiled for.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub const EM_NATIVE: u16 = 0;
+
+/// The relocation type that does nothing.
+const R_NONE: u32 = 0;
+/// The relocation type that adds the load offset to the addend.
+#[cfg(not(target_arch = "aarch64"))]
+const R_RELATIVE: u32 = 8;
+/// The relocation type that adds the load offset to the addend.
+#[cfg(target_arch = "aarch64")]
+const R_RELATIVE: u32 = 1027;
+
+/// The end of the dynamic section.
+const DT_NULL: u64 = 0;
+/// The address of the relocations with addends.
+const DT_RELA: u64 = 7;
+/// The size of the relocations with addends in bytes.
+const DT_RELASZ: u64 = 8;
+/// The size of a relocation with addend.
+const DT_RELAENT: u64 = 9;
+
+/// The size of the ELF64 header.
+const HEADER_SIZE: usize = 64;
+/// The size of an ELF64 program header.
+const PROGRAM_HEADER_SIZE: usize = 56;
+
+/// Reads a little endian `u16` at `offset`.
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Reads a little endian `u32` at `offset`.
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Reads a little endian `u64` at `offset`.
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from(read_u32(bytes, offset)) | u64::from(read_u32(bytes, offset + 4)) << 32
+}
+
+/// A program header of an ELF64 file.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ProgramHeader {
+    /// The kind of segment, such as `PT_LOAD`.
+    pub kind: u32,
+    /// A combination of `PF_R`, `PF_W` and `PF_X`.
+    pub flags: u32,
+    /// The offset of the contents in the file.
+    pub offset: u64,
+    /// The virtual address of the segment.
+    pub virtual_address: u64,
+    /// The physical address of the segment.
+    pub physical_address: u64,
+    /// The size of the contents in the file.
+    pub file_size: u64,
+    /// The size of the segment in memory, which is zero filled past `file_size`.
+    pub memory_size: u64,
+    /// The required alignment.
+    pub align: u64,
+}
+
+/// A parsed ELF64 file.
+#[derive(Clone, Copy, Debug)]
+pub struct ElfFile<'a> {
+    /// The contents of the file.
+    data: &'a [u8],
+    /// The type of the file, `ET_EXEC` or `ET_DYN`.
+    kind: u16,
+    /// The virtual address of the entry point.
+    entry: u64,
+    /// The offset of the program headers.
+    program_header_offset: usize,
+    /// The size of a program header.
+    program_header_size: usize,
+    /// The number of program headers.
+    program_header_count: usize,
+}
+
+impl<'a> ElfFile<'a> {
+    /// Parses the header of an ELF64 file.
+    ///
+    /// Returns `Error::Unsupported` if the file is not a little endian ELF64 executable for the
+    /// processor this crate is compiled for, `Error::BadBufferSize` if the program headers do not
+    /// fit in the file, and `Error::LoadError` if their offset does not fit in a `usize`.
+    pub fn parse(data: &'a [u8]) -> Result<ElfFile<'a>, Error> {
+        if data.len() < HEADER_SIZE || &data[..4] != b"\x7fELF" {
+            return Err(Error::Unsupported);
+        }
+        if data[4] != ELFCLASS64 || data[5] != ELFDATA2LSB || data[6] != 1 {
+            return Err(Error::Unsupported);
+        }
+
+        let kind = read_u16(data, 16);
+        if (kind != ET_EXEC && kind != ET_DYN) || read_u16(data, 18) != EM_NATIVE {
+            return Err(Error::Unsupported);
+        }
+
+        let program_header_offset =
+            usize::try_from(read_u64(data, 32)).map_err(|_| Error::LoadError)?;
+        let program_header_size = usize::from(read_u16(data, 54));
+        let program_header_count = usize::from(read_u16(data, 56));
+
+        let end = program_header_size
+            .checked_mul(program_header_count)
+            .and_then(|size| size.checked_add(program_header_offset));
+        if program_header_size < PROGRAM_HEADER_SIZE || end.map_or(true, |end| end > data.len()) {
+            return Err(Error::BadBufferSize);
+        }
+
+        Ok(ElfFile {
+            data,
+            kind,
+            entry: read_u64(data, 24),
+            program_header_offset,
+            program_header_size,
+            program_header_count,
+        })
+    }
+
+    /// Returns whether the file is position independent and can be relocated.
+    pub fn is_relocatable(&self) -> bool {
+        self.kind == ET_DYN
+    }
+
+    /// Returns the virtual address of the entry point.
+    pub fn entry(&self) -> u64 {
+        self.entry
+    }
+
+    /// Returns the program headers.
+    pub fn program_headers(&self) -> impl Iterator<Item = ProgramHeader> + 'a {
+        let data = self.data;
+        let (offset, size) = (self.program_header_offset, self.program_header_size);
+
+        (0..self.program_header_count).map(move |index| {
+            let header = &data[offset + index * size..];
+
+            ProgramHeader {
+                kind: read_u32(header, 0),
+                flags: read_u32(header, 4),
+                offset: read_u64(header, 8),
+                virtual_address: read_u64(header, 16),
+                physical_address: read_u64(header, 24),
+                file_size: read_u64(header, 32),
+                memory_size: read_u64(header, 40),
+                align: read_u64(header, 48),
+            }
+        })
+    }
+
+    /// Returns the loadable segments.
+    pub fn load_segments(&self) -> impl Iterator<Item = ProgramHeader> + 'a {
+        self.program_headers()
+            .filter(|header| header.kind == PT_LOAD && header.memory_size != 0)
+    }
+}
+
+/// A segment placed in memory by `load_with`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LoadedSegment {
+    /// The physical address the segment was loaded to.
+    pub physical_address: PhysicalAddress,
+    /// The virtual address the segment expects to be mapped at, including the relocation
+    /// offset.
+    pub virtual_address: u64,
+    /// The size of the segment in memory.
+    pub memory_size: u64,
+    /// A combination of `PF_R`, `PF_W` and `PF_X`.
+    pub flags: u32,
+}
+
+/// An ELF64 file loaded into memory.
+#[derive(Debug)]
+pub struct LoadedElf {
+    /// The virtual address of the entry point, including the relocation offset.
+    pub entry: u64,
+    /// The physical address of the entry point.
+    pub physical_entry: PhysicalAddress,
+    /// The difference between the actual and the requested addresses.
+    ///
+    /// This is only non-zero for relocated position independent executables.
+    pub offset: u64,
+    /// The first allocated page.
+    pub base: PhysicalAddress,
+    /// The number of allocated pages.
+    pub pages: usize,
+    /// The loaded segments.
+    segments: [LoadedSegment; MAX_LOADED_SEGMENTS],
+    /// The number of used entries in `segments`.
+    segment_count: usize,
+}
+
+impl LoadedElf {
+    /// Returns the loaded segments.
+    pub fn segments(&self) -> &[LoadedSegment] {
+        &self.segments[..self.segment_count]
+    }
+
+    /// Frees the pages of the loaded file.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        boot_services.free_pages(self.base.0 as usize as *const u8, self.pages)
+    }
+}
+
+/// Loads the ELF64 file in `data` into pages of `memory_type`.
+///
+/// The segments are placed at their physical addresses. If these are not available, position
+/// independent executables are loaded to other pages and relocated, while other executables
+/// fail with `Error::NotFound`. The memory between and after the contents of the segments is
+/// zeroed, which clears the BSS.
+///
+/// Returns `Error::LoadError` if there are no loadable segments, more than
+/// `MAX_LOADED_SEGMENTS` of them, the entry point is outside of them or an offset or size does
+/// not fit in a `usize`, and `Error::Unsupported` if the file needs other relocations than
+/// relative ones.
+pub fn load_with(
+    boot_services: &BootServices,
+    data: &[u8],
+    memory_type: MemoryType,
+) -> Result<LoadedElf, Error> {
+    let file = ElfFile::parse(data)?;
+
+    let mut segments = [LoadedSegment::default(); MAX_LOADED_SEGMENTS];
+    let mut contents: [&[u8]; MAX_LOADED_SEGMENTS] = [&[]; MAX_LOADED_SEGMENTS];
+    let mut segment_count = 0;
+    let mut start = u64::max_value();
+    let mut end = 0;
+
+    for header in file.load_segments() {
+        let file_offset = usize::try_from(header.offset).map_err(|_| Error::LoadError)?;
+        let file_size = usize::try_from(header.file_size).map_err(|_| Error::LoadError)?;
+        let file_contents = file_offset
+            .checked_add(file_size)
+            .and_then(|end| data.get(file_offset..end));
+        let file_contents = match file_contents {
+            Some(file_contents) if header.file_size <= header.memory_size => file_contents,
+            _ => return Err(Error::BadBufferSize),
+        };
+        if segment_count == MAX_LOADED_SEGMENTS {
+            return Err(Error::LoadError);
+        }
+
+        contents[segment_count] = file_contents;
+        segments[segment_count] = LoadedSegment {
+            physical_address: PhysicalAddress(header.physical_address),
+            virtual_address: header.virtual_address,
+            memory_size: header.memory_size,
+            flags: header.flags,
+        };
+        segment_count += 1;
+
+        start = start.min(header.physical_address);
+        end = end.max(
+            header
+                .physical_address
+                .checked_add(header.memory_size)
+                .ok_or(Error::BadBufferSize)?,
+        );
+    }
+
+    if segment_count == 0 {
+        return Err(Error::LoadError);
+    }
+
+    let start = PhysicalAddress(start).align_down(PAGE_SIZE as u64);
+    let end = PhysicalAddress(end)
+        .checked_align_up(PAGE_SIZE as u64)
+        .ok_or(Error::BadBufferSize)?;
+    let pages =
+        usize::try_from((end.0 - start.0) / PAGE_SIZE as u64).map_err(|_| Error::LoadError)?;
+
+    let base =
+        match boot_services.allocate_pages_with(AllocateType::Address(start), memory_type, pages) {
+            Err(Error::NotFound) if file.is_relocatable() => {
+                boot_services.allocate_pages_with(AllocateType::AnyPages, memory_type, pages)?
+            }
+            result => result?,
+        };
+    let offset = base.0.wrapping_sub(start.0);
+
+    // This is safe, because the pages were just allocated and the segments lie within them.
+    unsafe {
+        ptr::write_bytes(base.0 as usize as *mut u8, 0, pages * PAGE_SIZE);
+
+        for (segment, contents) in segments[..segment_count].iter().zip(&contents) {
+            ptr::copy_nonoverlapping(
+                contents.as_ptr(),
+                segment.physical_address.0.wrapping_add(offset) as usize as *mut u8,
+                contents.len(),
+            );
+        }
+    }
+
+    let mut loaded = LoadedElf {
+        entry: file.entry().wrapping_add(offset),
+        physical_entry: PhysicalAddress(0),
+        offset,
+        base,
+        pages,
+        segments,
+        segment_count,
+    };
+
+    for segment in &mut loaded.segments[..segment_count] {
+        segment.physical_address = PhysicalAddress(segment.physical_address.0.wrapping_add(offset));
+        segment.virtual_address = segment.virtual_address.wrapping_add(offset);
+    }
+
+    let physical_entry = loaded
+        .segments()
+        .iter()
+        .find(|segment| {
+            loaded.entry >= segment.virtual_address
+                && loaded.entry - segment.virtual_address < segment.memory_size
+        })
+        .map(|segment| {
+            PhysicalAddress(segment.physical_address.0 + (loaded.entry - segment.virtual_address))
+        });
+
+    match physical_entry {
+        Some(physical_entry) => loaded.physical_entry = physical_entry,
+        None => {
+            loaded.free(boot_services)?;
+
+            return Err(Error::LoadError);
+        }
+    }
+
+    if offset != 0 {
+        if let Err(error) = relocate(&file, &loaded) {
+            loaded.free(boot_services)?;
+
+            return Err(error);
+        }
+    }
+
+    Ok(loaded)
+}
+
+/// Applies the relative relocations of a relocated position independent executable.
+fn relocate(file: &ElfFile, loaded: &LoadedElf) -> Result<(), Error> {
+    let dynamic = match file
+        .program_headers()
+        .find(|header| header.kind == PT_DYNAMIC)
+    {
+        Some(dynamic) => dynamic,
+        None => return Ok(()),
+    };
+
+    // Translates a virtual address of the file into a pointer to the loaded copy.
+    let translate = |address: u64, size: u64| -> Result<*mut u8, Error> {
+        loaded
+            .segments()
+            .iter()
+            .map(|segment| (segment.virtual_address.wrapping_sub(loaded.offset), segment))
+            .find(|(start, segment)| {
+                address
+                    .checked_sub(*start)
+                    .and_then(|offset| offset.checked_add(size))
+                    .map_or(false, |end| end <= segment.memory_size)
+            })
+            .map(|(start, segment)| {
+                (segment.physical_address.0 + (address - start)) as usize as *mut u8
+            })
+            .ok_or(Error::LoadError)
+    };
+
+    let (mut rela, mut rela_size, mut rela_entry) = (0, 0, 24);
+    let entries = translate(dynamic.virtual_address, dynamic.memory_size)? as *const u64;
+    let entry_count = usize::try_from(dynamic.memory_size / 16).map_err(|_| Error::LoadError)?;
+    for index in 0..entry_count {
+        // This is safe, because the dynamic section lies within the loaded segments.
+        let (tag, value) = unsafe {
+            (
+                ptr::read_unaligned(entries.add(index * 2)),
+                ptr::read_unaligned(entries.add(index * 2 + 1)),
+            )
+        };
+
+        match tag {
+            DT_NULL => break,
+            DT_RELA => rela = value,
+            DT_RELASZ => rela_size = value,
+            DT_RELAENT => rela_entry = value,
+            _ => (),
+        }
+    }
+
+    if rela_size == 0 {
+        return Ok(());
+    }
+    if rela_entry < 24 {
+        return Err(Error::LoadError);
+    }
+
+    let relocations = translate(rela, rela_size)?;
+    for index in 0..rela_size / rela_entry {
+        let relocation_offset =
+            usize::try_from(index * rela_entry).map_err(|_| Error::LoadError)?;
+
+        // This is safe, because the relocations lie within the loaded segments.
+        let (target, info, addend) = unsafe {
+            let relocation = relocations.add(relocation_offset) as *const u64;
+
+            (
+                ptr::read_unaligned(relocation),
+                ptr::read_unaligned(relocation.add(1)),
+                ptr::read_unaligned(relocation.add(2)),
+            )
+        };
+
+        match info as u32 {
+            R_NONE => continue,
+            R_RELATIVE => (),
+            _ => return Err(Error::Unsupported),
+        }
+
+        let target = translate(target, 8)? as *mut u64;
+        // This is safe, because the target lies within the loaded segments.
+        unsafe { ptr::write_unaligned(target, addend.wrapping_add(loaded.offset)) };
+    }
+
+    Ok(())
+}
+
+/// Loads the ELF64 file in `data` into `LoaderData` pages.
+///
+/// The boot services registered with `system::init` are used, see `load_with`. Returns
+/// `Error::NotStarted` if `system::init` was not called.
+pub fn load(data: &[u8]) -> Result<LoadedElf, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+
+    load_with(boot_services, data, MemoryType::LoaderData)
+}
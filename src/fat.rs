@@ -0,0 +1,928 @@
+//! A read-only FAT12, FAT16 and FAT32 file system driver that works directly on a device.
+//!
+//! The simple file system protocol is missing for controllers that were never connected and is
+//! unreliable in some firmware. `FatVolume` reads such volumes through `DiskIo` or `BlockIo`
+//! instead. Long file names are supported and names are compared ignoring ASCII case, like
+//! `fs::open_with` does on most firmware.
+
+use core::{cell::RefCell, cmp::min, fmt, str};
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use crate::{block_io::BlockIo, disk_io::Disk, status::Error, ucs2::Lossy};
+
+/// The maximum length of a long file name in UTF-16 code units.
+pub const MAX_LONG_NAME_LEN: usize = 255;
+
+/// The largest block size supported when reading through `BlockIo`.
+const MAX_BLOCK_SIZE: usize = 4096;
+
+/// The size of a directory entry.
+const DIR_ENTRY_SIZE: usize = 32;
+
+/// The size of the chunks that directories and the FAT are read in.
+const CHUNK_SIZE: usize = 512;
+
+/// The number of UTF-16 code units in a long file name entry.
+const LONG_NAME_CHARS: usize = 13;
+
+/// The entry is read only.
+pub const ATTR_READ_ONLY: u8 = 0x01;
+/// The entry is hidden.
+pub const ATTR_HIDDEN: u8 = 0x02;
+/// The entry belongs to the operating system.
+pub const ATTR_SYSTEM: u8 = 0x04;
+/// The entry is the volume label.
+pub const ATTR_VOLUME_ID: u8 = 0x08;
+/// The entry is a directory.
+pub const ATTR_DIRECTORY: u8 = 0x10;
+/// The entry was changed since the last backup.
+pub const ATTR_ARCHIVE: u8 = 0x20;
+/// The attributes marking a long file name entry.
+const ATTR_LONG_NAME: u8 = 0x0f;
+
+/// The base of the short name is displayed in lower case.
+const NT_LOWER_BASE: u8 = 0x08;
+/// The extension of the short name is displayed in lower case.
+const NT_LOWER_EXTENSION: u8 = 0x10;
+
+/// A device that a FAT volume can be read from.
+pub trait FatDevice {
+    /// Reads `buffer.len()` bytes at the byte offset `offset`.
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<(), Error>;
+}
+
+impl<'a> FatDevice for Disk<'a> {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        self.read(offset, buffer)
+    }
+}
+
+/// A block read through a buffer, aligned for all devices with `IoAlign` up to its size.
+#[repr(C, align(4096))]
+struct BlockBuffer([u8; MAX_BLOCK_SIZE]);
+
+impl FatDevice for BlockIo {
+    /// Reads whole blocks directly into `buffer` where possible and other blocks through a
+    /// buffer on the stack.
+    ///
+    /// Returns `Error::Unsupported` if the block size or the alignment requirement is larger
+    /// than 4096 bytes.
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        let block_size = self.Media.BlockSize as usize;
+        let io_align = (self.Media.IoAlign as usize).max(1);
+
+        if block_size == 0 || block_size > MAX_BLOCK_SIZE || io_align > MAX_BLOCK_SIZE {
+            return Err(Error::Unsupported);
+        }
+
+        let mut bounce = BlockBuffer([0; MAX_BLOCK_SIZE]);
+        let mut done = 0;
+
+        while done < buffer.len() {
+            let position = offset + done as u64;
+            let lba = position / block_size as u64;
+            let within = (position % block_size as u64) as usize;
+            let remaining = buffer.len() - done;
+            let rest = &mut buffer[done..];
+
+            if within == 0 && remaining >= block_size && rest.as_ptr() as usize % io_align == 0 {
+                let len = remaining - remaining % block_size;
+
+                self.read_blocks(lba, &mut rest[..len])?;
+                done += len;
+            } else {
+                let len = min(block_size - within, remaining);
+
+                self.read_blocks(lba, &mut bounce.0[..block_size])?;
+                rest[..len].copy_from_slice(&bounce.0[within..within + len]);
+                done += len;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The kind of FAT, which depends on the number of clusters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FatType {
+    /// 12 bit cluster numbers, used for fewer than 4085 clusters.
+    Fat12,
+    /// 16 bit cluster numbers, used for fewer than 65525 clusters.
+    Fat16,
+    /// 28 bit cluster numbers.
+    Fat32,
+}
+
+/// Reads a little endian `u16` at `offset`.
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Reads a little endian `u32` at `offset`.
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// The part of the FAT that was read last.
+struct FatCache {
+    /// The byte offset of `data` on the device, or `u64::MAX` if nothing was read yet.
+    offset: u64,
+    /// The cached bytes.
+    data: [u8; CHUNK_SIZE],
+}
+
+/// A mounted FAT volume.
+pub struct FatVolume<D> {
+    /// The device holding the volume.
+    device: D,
+    /// The kind of FAT.
+    fat_type: FatType,
+    /// The size of a cluster in bytes.
+    cluster_size: u32,
+    /// The number of data clusters.
+    cluster_count: u32,
+    /// The byte offset of the first FAT.
+    fat_offset: u64,
+    /// The byte offset of the fixed root directory of FAT12 and FAT16.
+    root_dir_offset: u64,
+    /// The number of entries of the fixed root directory of FAT12 and FAT16.
+    root_dir_entries: u32,
+    /// The first cluster of the root directory of FAT32.
+    root_cluster: u32,
+    /// The byte offset of cluster 2.
+    data_offset: u64,
+    /// The part of the FAT that was read last.
+    cache: RefCell<FatCache>,
+}
+
+impl<D: FatDevice> FatVolume<D> {
+    /// Mounts the FAT volume on `device`.
+    ///
+    /// Returns `Error::Unsupported` if the device does not hold a FAT volume and
+    /// `Error::VolumeCorrupted` if the boot sector is inconsistent.
+    pub fn mount(device: D) -> Result<FatVolume<D>, Error> {
+        let mut boot_sector = [0; 512];
+        device.read_at(0, &mut boot_sector)?;
+
+        if boot_sector[510] != 0x55 || boot_sector[511] != 0xaa {
+            return Err(Error::Unsupported);
+        }
+
+        let bytes_per_sector = u64::from(read_u16(&boot_sector, 11));
+        let sectors_per_cluster = u64::from(boot_sector[13]);
+        let reserved_sectors = u64::from(read_u16(&boot_sector, 14));
+        let fats = u64::from(boot_sector[16]);
+        let root_dir_entries = u32::from(read_u16(&boot_sector, 17));
+        let total_sectors = match read_u16(&boot_sector, 19) {
+            0 => u64::from(read_u32(&boot_sector, 32)),
+            sectors => u64::from(sectors),
+        };
+        let fat_sectors = match read_u16(&boot_sector, 22) {
+            0 => u64::from(read_u32(&boot_sector, 36)),
+            sectors => u64::from(sectors),
+        };
+
+        if !bytes_per_sector.is_power_of_two()
+            || bytes_per_sector < 512
+            || bytes_per_sector > 4096
+            || !sectors_per_cluster.is_power_of_two()
+        {
+            return Err(Error::Unsupported);
+        }
+        if reserved_sectors == 0 || fats == 0 || fat_sectors == 0 {
+            return Err(Error::VolumeCorrupted);
+        }
+
+        let root_dir_sectors =
+            (u64::from(root_dir_entries) * DIR_ENTRY_SIZE as u64 + bytes_per_sector - 1)
+                / bytes_per_sector;
+        let first_data_sector = reserved_sectors + fats * fat_sectors + root_dir_sectors;
+        if total_sectors <= first_data_sector {
+            return Err(Error::VolumeCorrupted);
+        }
+
+        let cluster_count = (total_sectors - first_data_sector) / sectors_per_cluster;
+        let fat_type = if cluster_count < 4085 {
+            FatType::Fat12
+        } else if cluster_count < 65525 {
+            FatType::Fat16
+        } else {
+            FatType::Fat32
+        };
+
+        let root_cluster = if fat_type == FatType::Fat32 {
+            read_u32(&boot_sector, 44) & 0x0fff_ffff
+        } else {
+            0
+        };
+
+        let volume = FatVolume {
+            device,
+            fat_type,
+            cluster_size: (sectors_per_cluster * bytes_per_sector) as u32,
+            cluster_count: min(cluster_count, 0x0fff_fff5) as u32,
+            fat_offset: reserved_sectors * bytes_per_sector,
+            root_dir_offset: (reserved_sectors + fats * fat_sectors) * bytes_per_sector,
+            root_dir_entries,
+            root_cluster,
+            data_offset: first_data_sector * bytes_per_sector,
+            cache: RefCell::new(FatCache {
+                offset: u64::max_value(),
+                data: [0; CHUNK_SIZE],
+            }),
+        };
+
+        if volume.fat_type == FatType::Fat32 && !volume.is_data_cluster(volume.root_cluster) {
+            return Err(Error::VolumeCorrupted);
+        }
+
+        Ok(volume)
+    }
+
+    /// Returns the kind of FAT.
+    pub fn fat_type(&self) -> FatType {
+        self.fat_type
+    }
+
+    /// Returns the size of a cluster in bytes.
+    pub fn cluster_size(&self) -> u32 {
+        self.cluster_size
+    }
+
+    /// Returns the device holding the volume.
+    pub fn device(&self) -> &D {
+        &self.device
+    }
+
+    /// Returns the root directory.
+    ///
+    /// Returns `Error::VolumeCorrupted` if the root directory of FAT32 does not start at a data
+    /// cluster.
+    pub fn root_dir(&self) -> Result<FatDir<D>, Error> {
+        if self.fat_type == FatType::Fat32 && !self.is_data_cluster(self.root_cluster) {
+            return Err(Error::VolumeCorrupted);
+        }
+
+        Ok(FatDir::new(self, self.root_cluster))
+    }
+
+    /// Returns the contents of the directory `entry`.
+    ///
+    /// Returns `Error::InvalidParameter` if the entry is not a directory and
+    /// `Error::VolumeCorrupted` if it does not start at a data cluster.
+    pub fn read_dir(&self, entry: &FatEntry) -> Result<FatDir<D>, Error> {
+        if !entry.is_directory() {
+            return Err(Error::InvalidParameter);
+        }
+
+        // The `..` entry of a directory below the root refers to the root as cluster zero.
+        if entry.first_cluster == 0 {
+            return self.root_dir();
+        }
+        if !self.is_data_cluster(entry.first_cluster) {
+            return Err(Error::VolumeCorrupted);
+        }
+
+        Ok(FatDir::new(self, entry.first_cluster))
+    }
+
+    /// Returns the contents of the file `entry`.
+    ///
+    /// Returns `Error::InvalidParameter` if the entry is a directory.
+    pub fn file(&self, entry: &FatEntry) -> Result<FatFile<D>, Error> {
+        if entry.is_directory() {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(FatFile {
+            volume: self,
+            first_cluster: entry.first_cluster,
+            size: entry.size,
+            position: 0,
+            cluster: entry.first_cluster,
+            cluster_index: 0,
+        })
+    }
+
+    /// Finds the entry with the given path.
+    ///
+    /// Both slashes and backslashes separate directories. Returns `Error::NotFound` if there is
+    /// no such entry and `Error::InvalidParameter` for the root directory, which has no entry.
+    pub fn find(&self, path: &str) -> Result<FatEntry, Error> {
+        let mut components = path
+            .split(|character| character == '\\' || character == '/')
+            .filter(|component| !component.is_empty() && *component != ".")
+            .peekable();
+
+        let mut dir = self.root_dir()?;
+        while let Some(component) = components.next() {
+            let entry = dir.find(component)?;
+
+            if components.peek().is_none() {
+                return Ok(entry);
+            }
+            if !entry.is_directory() {
+                return Err(Error::NotFound);
+            }
+
+            dir = self.read_dir(&entry)?;
+        }
+
+        Err(Error::InvalidParameter)
+    }
+
+    /// Opens the file with the given path for reading.
+    pub fn open(&self, path: &str) -> Result<FatFile<D>, Error> {
+        let entry = self.find(path)?;
+
+        self.file(&entry)
+    }
+
+    /// Returns the contents of the directory with the given path.
+    ///
+    /// An empty path refers to the root directory.
+    pub fn open_dir(&self, path: &str) -> Result<FatDir<D>, Error> {
+        match self.find(path) {
+            Ok(entry) => self.read_dir(&entry),
+            Err(Error::InvalidParameter) => self.root_dir(),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Reads the whole file with the given path.
+    #[cfg(feature = "alloc")]
+    pub fn read_to_vec(&self, path: &str) -> Result<Vec<u8>, Error> {
+        self.open(path)?.read_to_vec()
+    }
+
+    /// Returns the byte offset of `cluster`, which must be a data cluster.
+    fn cluster_offset(&self, cluster: u32) -> u64 {
+        self.data_offset + u64::from(cluster - 2) * u64::from(self.cluster_size)
+    }
+
+    /// Returns whether `cluster` is a valid data cluster.
+    fn is_data_cluster(&self, cluster: u32) -> bool {
+        cluster >= 2 && cluster - 2 < self.cluster_count
+    }
+
+    /// Reads a byte of the first FAT.
+    fn fat_byte(&self, offset: u64) -> Result<u8, Error> {
+        let mut cache = self.cache.borrow_mut();
+        let chunk = offset - offset % CHUNK_SIZE as u64;
+
+        if cache.offset != chunk {
+            cache.offset = u64::max_value();
+            self.device.read_at(chunk, &mut cache.data)?;
+            cache.offset = chunk;
+        }
+
+        Ok(cache.data[(offset - chunk) as usize])
+    }
+
+    /// Returns the cluster following `cluster`, or `None` at the end of the chain.
+    ///
+    /// Returns `Error::VolumeCorrupted` for free, bad and out of range clusters.
+    fn next_cluster(&self, cluster: u32) -> Result<Option<u32>, Error> {
+        if !self.is_data_cluster(cluster) {
+            return Err(Error::VolumeCorrupted);
+        }
+
+        let (next, end) = match self.fat_type {
+            FatType::Fat12 => {
+                let offset = self.fat_offset + u64::from(cluster + cluster / 2);
+                let value =
+                    u16::from_le_bytes([self.fat_byte(offset)?, self.fat_byte(offset + 1)?]);
+                let value = if cluster % 2 == 1 {
+                    value >> 4
+                } else {
+                    value & 0x0fff
+                };
+
+                (u32::from(value), 0x0ff8)
+            }
+            FatType::Fat16 => {
+                let offset = self.fat_offset + u64::from(cluster) * 2;
+                let value =
+                    u16::from_le_bytes([self.fat_byte(offset)?, self.fat_byte(offset + 1)?]);
+
+                (u32::from(value), 0xfff8)
+            }
+            FatType::Fat32 => {
+                let offset = self.fat_offset + u64::from(cluster) * 4;
+                let value = u32::from_le_bytes([
+                    self.fat_byte(offset)?,
+                    self.fat_byte(offset + 1)?,
+                    self.fat_byte(offset + 2)?,
+                    self.fat_byte(offset + 3)?,
+                ]);
+
+                (value & 0x0fff_ffff, 0x0fff_fff8)
+            }
+        };
+
+        if next >= end {
+            Ok(None)
+        } else if self.is_data_cluster(next) {
+            Ok(Some(next))
+        } else {
+            Err(Error::VolumeCorrupted)
+        }
+    }
+}
+
+/// An entry of a directory.
+#[derive(Clone)]
+pub struct FatEntry {
+    /// The short name as stored on disk, with the base and extension padded with spaces.
+    short_name: [u8; 11],
+    /// The flags selecting lower case for parts of the short name.
+    case_flags: u8,
+    /// The long file name.
+    long_name: [u16; MAX_LONG_NAME_LEN],
+    /// The length of the long file name, which is zero if there is none.
+    long_name_len: usize,
+    /// The attributes.
+    attributes: u8,
+    /// The first cluster of the contents, which is zero for empty files.
+    first_cluster: u32,
+    /// The size of the file in bytes.
+    size: u32,
+}
+
+impl FatEntry {
+    /// Returns the name of the entry, which is the long file name if there is one.
+    pub fn name(&self) -> FatName {
+        FatName(self)
+    }
+
+    /// Returns whether the entry has a long file name.
+    pub fn has_long_name(&self) -> bool {
+        self.long_name_len != 0
+    }
+
+    /// Returns the long file name, if there is one.
+    pub fn long_name(&self) -> Option<&[u16]> {
+        if self.has_long_name() {
+            Some(&self.long_name[..self.long_name_len])
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether `name` is the long or the short name of the entry, ignoring ASCII case.
+    pub fn matches(&self, name: &str) -> bool {
+        if let Some(long_name) = self.long_name() {
+            let mut characters = Lossy::new(long_name).chars();
+            let mut expected = name.chars();
+
+            let matches = loop {
+                match (characters.next(), expected.next()) {
+                    (Some(a), Some(b)) if a.eq_ignore_ascii_case(&b) => (),
+                    (None, None) => break true,
+                    _ => break false,
+                }
+            };
+
+            if matches {
+                return true;
+            }
+        }
+
+        let mut buffer = [0; 12];
+        let short_name = self.short_name(&mut buffer);
+
+        short_name.eq_ignore_ascii_case(name)
+    }
+
+    /// Formats the short name as `BASE.EXT` into `buffer`.
+    ///
+    /// Characters outside of ASCII are replaced with `_`.
+    pub fn short_name<'a>(&self, buffer: &'a mut [u8; 12]) -> &'a str {
+        let (base, extension) = self.short_name.split_at(8);
+        let trim = |part: &[u8]| {
+            part.iter()
+                .rposition(|&byte| byte != b' ')
+                .map_or(0, |i| i + 1)
+        };
+        let mut len = 0;
+
+        let mut push = |byte: u8, lower: bool| {
+            let byte = if byte.is_ascii() { byte } else { b'_' };
+
+            buffer[len] = if lower {
+                byte.to_ascii_lowercase()
+            } else {
+                byte
+            };
+            len += 1;
+        };
+
+        for &byte in &base[..trim(base)] {
+            push(byte, self.case_flags & NT_LOWER_BASE != 0);
+        }
+        if trim(extension) != 0 {
+            push(b'.', false);
+
+            for &byte in &extension[..trim(extension)] {
+                push(byte, self.case_flags & NT_LOWER_EXTENSION != 0);
+            }
+        }
+
+        // This cannot fail, because only ASCII characters are written.
+        str::from_utf8(&buffer[..len]).unwrap_or("")
+    }
+
+    /// Returns the attributes of the entry.
+    pub fn attributes(&self) -> u8 {
+        self.attributes
+    }
+
+    /// Returns whether the entry is a directory.
+    pub fn is_directory(&self) -> bool {
+        self.attributes & ATTR_DIRECTORY != 0
+    }
+
+    /// Returns the size of the file in bytes.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+impl fmt::Debug for FatEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FatEntry")
+            .field("name", &format_args!("{}", self.name()))
+            .field("attributes", &self.attributes)
+            .field("first_cluster", &self.first_cluster)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// Displays the name of a `FatEntry`.
+pub struct FatName<'a>(&'a FatEntry);
+
+impl<'a> fmt::Display for FatName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.long_name() {
+            Some(long_name) => write!(f, "{}", Lossy::new(long_name)),
+            None => f.write_str(self.0.short_name(&mut [0; 12])),
+        }
+    }
+}
+
+/// Computes the checksum of a short name that long file name entries refer to.
+fn short_name_checksum(short_name: &[u8]) -> u8 {
+    short_name
+        .iter()
+        .fold(0u8, |sum, &byte| sum.rotate_right(1).wrapping_add(byte))
+}
+
+/// The long file name entries read so far.
+struct LongName {
+    /// The characters of the name.
+    characters: [u16; MAX_LONG_NAME_LEN],
+    /// The number of long file name entries, or zero if no name is being read.
+    entries: u8,
+    /// The sequence number of the entry that was read last.
+    sequence: u8,
+    /// The checksum of the short name that the entries belong to.
+    checksum: u8,
+}
+
+impl LongName {
+    /// Adds a long file name entry.
+    fn add(&mut self, entry: &[u8]) {
+        let sequence = entry[0] & 0x1f;
+
+        if entry[0] & 0x40 != 0 {
+            self.entries = sequence;
+            self.checksum = entry[13];
+            self.characters = [0xffff; MAX_LONG_NAME_LEN];
+        } else if self.entries == 0 || sequence + 1 != self.sequence || entry[13] != self.checksum {
+            self.entries = 0;
+            return;
+        }
+
+        if sequence == 0 || usize::from(sequence) * LONG_NAME_CHARS > MAX_LONG_NAME_LEN + 5 {
+            self.entries = 0;
+            return;
+        }
+        self.sequence = sequence;
+
+        let start = (usize::from(sequence) - 1) * LONG_NAME_CHARS;
+        let offsets = (1..11)
+            .step_by(2)
+            .chain((14..26).step_by(2))
+            .chain((28..32).step_by(2));
+
+        for (index, offset) in offsets.enumerate() {
+            if let Some(character) = self.characters.get_mut(start + index) {
+                *character = read_u16(entry, offset);
+            }
+        }
+    }
+
+    /// Returns the length of the name if it belongs to the given short name.
+    fn finish(&mut self, short_name: &[u8]) -> usize {
+        let complete = self.entries != 0
+            && self.sequence == 1
+            && self.checksum == short_name_checksum(short_name);
+        self.entries = 0;
+
+        if !complete {
+            return 0;
+        }
+
+        self.characters
+            .iter()
+            .position(|&character| character == 0 || character == 0xffff)
+            .unwrap_or(MAX_LONG_NAME_LEN)
+    }
+}
+
+/// Iterates over the entries of a directory.
+///
+/// Deleted entries and the volume label are skipped, while the `.` and `..` entries are
+/// returned.
+pub struct FatDir<'a, D> {
+    /// The volume of the directory.
+    volume: &'a FatVolume<D>,
+    /// The current cluster, or zero for the fixed root directory.
+    cluster: u32,
+    /// The index of the next entry within the current cluster or the fixed root directory.
+    index: u32,
+    /// The number of clusters visited, to detect cycles.
+    visited: u32,
+    /// The chunk of the directory that was read last.
+    chunk: [u8; CHUNK_SIZE],
+    /// The index of the first entry in `chunk`, or `u32::MAX` if it is not valid.
+    chunk_index: u32,
+    /// The long file name entries read so far.
+    long_name: LongName,
+    /// Whether the end of the directory was reached.
+    done: bool,
+}
+
+impl<'a, D: FatDevice> FatDir<'a, D> {
+    /// Starts reading the directory at `cluster`, or the fixed root directory if it is zero.
+    fn new(volume: &'a FatVolume<D>, cluster: u32) -> FatDir<'a, D> {
+        FatDir {
+            volume,
+            cluster,
+            index: 0,
+            visited: 0,
+            chunk: [0; CHUNK_SIZE],
+            chunk_index: u32::max_value(),
+            long_name: LongName {
+                characters: [0; MAX_LONG_NAME_LEN],
+                entries: 0,
+                sequence: 0,
+                checksum: 0,
+            },
+            done: false,
+        }
+    }
+
+    /// Finds the entry with the given name, ignoring ASCII case.
+    pub fn find(self, name: &str) -> Result<FatEntry, Error> {
+        for entry in self {
+            let entry = entry?;
+
+            if entry.matches(name) {
+                return Ok(entry);
+            }
+        }
+
+        Err(Error::NotFound)
+    }
+
+    /// Returns the raw directory entry at the current position, or `None` at the end.
+    fn next_raw(&mut self) -> Result<Option<[u8; DIR_ENTRY_SIZE]>, Error> {
+        let entries_per_chunk = (CHUNK_SIZE / DIR_ENTRY_SIZE) as u32;
+
+        let base = if self.cluster == 0 {
+            if self.index >= self.volume.root_dir_entries {
+                return Ok(None);
+            }
+
+            self.volume.root_dir_offset
+        } else {
+            if self.index * DIR_ENTRY_SIZE as u32 >= self.volume.cluster_size {
+                self.visited += 1;
+                if self.visited > self.volume.cluster_count {
+                    return Err(Error::VolumeCorrupted);
+                }
+
+                match self.volume.next_cluster(self.cluster)? {
+                    Some(next) => self.cluster = next,
+                    None => return Ok(None),
+                }
+                self.index = 0;
+                self.chunk_index = u32::max_value();
+            }
+
+            if !self.volume.is_data_cluster(self.cluster) {
+                return Err(Error::VolumeCorrupted);
+            }
+
+            self.volume.cluster_offset(self.cluster)
+        };
+
+        let chunk_index = self.index - self.index % entries_per_chunk;
+        if self.chunk_index != chunk_index {
+            self.chunk_index = u32::max_value();
+            self.volume.device.read_at(
+                base + u64::from(chunk_index) * DIR_ENTRY_SIZE as u64,
+                &mut self.chunk,
+            )?;
+            self.chunk_index = chunk_index;
+        }
+
+        let offset = (self.index - chunk_index) as usize * DIR_ENTRY_SIZE;
+        let mut entry = [0; DIR_ENTRY_SIZE];
+        entry.copy_from_slice(&self.chunk[offset..offset + DIR_ENTRY_SIZE]);
+        self.index += 1;
+
+        Ok(Some(entry))
+    }
+}
+
+impl<'a, D: FatDevice> Iterator for FatDir<'a, D> {
+    type Item = Result<FatEntry, Error>;
+
+    fn next(&mut self) -> Option<Result<FatEntry, Error>> {
+        while !self.done {
+            let entry = match self.next_raw() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            };
+
+            match entry[0] {
+                0x00 => break,
+                0xe5 => {
+                    self.long_name.entries = 0;
+                    continue;
+                }
+                _ => (),
+            }
+
+            let attributes = entry[11];
+            if attributes & 0x3f == ATTR_LONG_NAME {
+                self.long_name.add(&entry);
+                continue;
+            }
+
+            let mut short_name = [0; 11];
+            short_name.copy_from_slice(&entry[..11]);
+            let long_name_len = self.long_name.finish(&short_name);
+
+            if attributes & ATTR_VOLUME_ID != 0 {
+                continue;
+            }
+
+            // A leading 0xe5 of a name is stored as 0x05, since 0xe5 marks deleted entries.
+            if short_name[0] == 0x05 {
+                short_name[0] = 0xe5;
+            }
+
+            let first_cluster = u32::from(read_u16(&entry, 26))
+                | if self.volume.fat_type == FatType::Fat32 {
+                    u32::from(read_u16(&entry, 20)) << 16
+                } else {
+                    0
+                };
+
+            return Some(Ok(FatEntry {
+                short_name,
+                case_flags: entry[12],
+                long_name: self.long_name.characters,
+                long_name_len,
+                attributes,
+                first_cluster,
+                size: read_u32(&entry, 28),
+            }));
+        }
+
+        self.done = true;
+
+        None
+    }
+}
+
+/// A file opened for reading.
+pub struct FatFile<'a, D> {
+    /// The volume of the file.
+    volume: &'a FatVolume<D>,
+    /// The first cluster of the file.
+    first_cluster: u32,
+    /// The size of the file in bytes.
+    size: u32,
+    /// The current position in bytes.
+    position: u32,
+    /// The cluster that `cluster_index` refers to.
+    cluster: u32,
+    /// The index of `cluster` in the cluster chain.
+    cluster_index: u32,
+}
+
+impl<'a, D: FatDevice> FatFile<'a, D> {
+    /// Returns the size of the file in bytes.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the current position in bytes.
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Sets the current position, which may be past the end of the file.
+    pub fn seek(&mut self, position: u32) {
+        self.position = position;
+    }
+
+    /// Reads from the current position into `buffer`.
+    ///
+    /// Returns the number of bytes read, which is zero at the end of the file.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+        if self.position >= self.size {
+            return Ok(0);
+        }
+
+        let len = min(buffer.len(), (self.size - self.position) as usize);
+        let cluster_size = self.volume.cluster_size;
+        let mut done = 0;
+
+        while done < len {
+            self.seek_cluster(self.position / cluster_size)?;
+
+            let within = self.position % cluster_size;
+            let chunk = min((cluster_size - within) as usize, len - done);
+
+            self.volume.device.read_at(
+                self.volume.cluster_offset(self.cluster) + u64::from(within),
+                &mut buffer[done..done + chunk],
+            )?;
+
+            done += chunk;
+            self.position += chunk as u32;
+        }
+
+        Ok(done)
+    }
+
+    /// Fills `buffer` from the current position.
+    ///
+    /// Returns `Error::EndOfFile` if the file ends before the buffer is filled.
+    pub fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+        if self.read(buffer)? != buffer.len() {
+            return Err(Error::EndOfFile);
+        }
+
+        Ok(())
+    }
+
+    /// Reads the file from the current position to the end.
+    #[cfg(feature = "alloc")]
+    pub fn read_to_vec(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = vec![0; self.size.saturating_sub(self.position) as usize];
+        self.read_exact(&mut data)?;
+
+        Ok(data)
+    }
+
+    /// Moves to the cluster with the given index in the cluster chain.
+    fn seek_cluster(&mut self, index: u32) -> Result<(), Error> {
+        if index < self.cluster_index {
+            self.cluster = self.first_cluster;
+            self.cluster_index = 0;
+        }
+
+        while self.cluster_index < index {
+            self.cluster = self
+                .volume
+                .next_cluster(self.cluster)?
+                .ok_or(Error::VolumeCorrupted)?;
+            self.cluster_index += 1;
+        }
+
+        if !self.volume.is_data_cluster(self.cluster) {
+            return Err(Error::VolumeCorrupted);
+        }
+
+        Ok(())
+    }
+}
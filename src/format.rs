@@ -0,0 +1,119 @@
+//! Helpers to format addresses, sizes and memory contents.
+//!
+//! These are used by the `Display` implementations of this crate, so diagnostic output looks the
+//! same everywhere.
+
+use core::{
+    fmt::{self, Write},
+    mem::size_of,
+};
+
+/// The number of bytes shown per line of a hexdump.
+pub const HEXDUMP_BYTES_PER_LINE: usize = 16;
+
+/// Formats a value in hexadecimal with a `0x` prefix, padded to the full width of its type.
+///
+/// `Hex(PhysicalAddress(0x1000))` is formatted as `0x0000000000001000`.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Hex<T>(pub T);
+
+impl<T: fmt::LowerHex> fmt::Display for Hex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#01$x}", self.0, 2 + 2 * size_of::<T>())
+    }
+}
+
+impl<T: fmt::LowerHex> fmt::Debug for Hex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Formats a number of bytes with a binary unit, such as `3.5 MiB`.
+///
+/// Sizes of at least 1 KiB are shown with one decimal, which is rounded down and omitted if it
+/// is zero.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ByteSize(pub u64);
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut unit = 0;
+
+        while unit + 1 < UNITS.len() && self.0 >> (10 * (unit + 2)) != 0 {
+            unit += 1;
+        }
+
+        let tenths = (u128::from(self.0) * 10) >> (10 * (unit + 1));
+
+        if tenths % 10 == 0 {
+            write!(f, "{} {}", tenths / 10, UNITS[unit])
+        } else {
+            write!(f, "{}.{} {}", tenths / 10, tenths % 10, UNITS[unit])
+        }
+    }
+}
+
+/// Writes a hexdump of `data`, labeling the lines with addresses starting at `base`.
+///
+/// Every line shows the address, up to `HEXDUMP_BYTES_PER_LINE` bytes and their printable ASCII
+/// characters.
+pub fn write_hexdump<W: Write>(writer: &mut W, data: &[u8], base: u64) -> fmt::Result {
+    for (line, bytes) in data.chunks(HEXDUMP_BYTES_PER_LINE).enumerate() {
+        let address = base.wrapping_add((line * HEXDUMP_BYTES_PER_LINE) as u64);
+
+        write!(writer, "{:016x}:", address)?;
+
+        for byte in bytes {
+            write!(writer, " {:02x}", byte)?;
+        }
+
+        for _ in bytes.len()..HEXDUMP_BYTES_PER_LINE {
+            writer.write_str("   ")?;
+        }
+
+        writer.write_str("  ")?;
+
+        for &byte in bytes {
+            let character = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+
+            writer.write_char(character)?;
+        }
+
+        writer.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// Formats a hexdump of the data, as written by `write_hexdump`.
+#[derive(Clone, Copy, Debug)]
+pub struct HexDump<'a> {
+    /// The data that is dumped.
+    data: &'a [u8],
+    /// The address of the first byte.
+    base: u64,
+}
+
+impl<'a> HexDump<'a> {
+    /// Creates a hexdump of `data`, labeling the lines with addresses starting at `base`.
+    pub fn new(data: &'a [u8], base: u64) -> HexDump<'a> {
+        HexDump { data, base }
+    }
+}
+
+impl<'a> fmt::Display for HexDump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hexdump(f, self.data, self.base)
+    }
+}
@@ -0,0 +1,137 @@
+//! A simple physical frame allocator for the time after `ExitBootServices`.
+//!
+//! The allocator hands out the frames of the `ConventionalMemory` regions of the final memory map
+//! and never frees them. The regions are used in the order of the memory map, which is not
+//! necessarily sorted, and the frames of a region in ascending order. It is meant to bootstrap a
+//! kernel until its own memory management is set up.
+
+use core::ops::Range;
+
+use crate::{
+    memory::{MemoryMap, MemoryMapIterator, MemoryType, PhysicalAddress, PAGE_SIZE},
+    status::Error,
+};
+
+/// The maximum number of ranges that can be reserved in a `FrameAllocator`.
+pub const MAX_RESERVED_RANGES: usize = 8;
+
+/// Allocates physical frames from the `ConventionalMemory` regions of a memory map.
+pub struct FrameAllocator<'a> {
+    /// The remaining descriptors of the memory map.
+    regions: MemoryMapIterator<'a>,
+    /// The frame numbers of the current region that were not handed out yet.
+    current: Range<u64>,
+    /// The frame numbers that must not be handed out.
+    reserved: [Range<u64>; MAX_RESERVED_RANGES],
+    /// The number of used entries in `reserved`.
+    reserved_count: usize,
+    /// The number of frames handed out so far.
+    allocated: u64,
+}
+
+impl<'a> FrameAllocator<'a> {
+    /// Creates a frame allocator for the given memory map.
+    ///
+    /// The frames holding the memory map itself are reserved, so the memory map stays valid.
+    /// The frame at address zero is never handed out.
+    pub fn new(memory_map: &'a MemoryMap) -> FrameAllocator<'a> {
+        let buffer = memory_map.buffer();
+        let first_frame = buffer.as_ptr() as u64 / PAGE_SIZE as u64;
+        let frame_count = (buffer.len() / PAGE_SIZE) as u64;
+
+        let mut allocator = FrameAllocator {
+            regions: memory_map.iter(),
+            current: 0..0,
+            reserved: Default::default(),
+            reserved_count: 0,
+            allocated: 0,
+        };
+
+        allocator.reserved[0] = 0..1;
+        allocator.reserved[1] = first_frame..first_frame + frame_count;
+        allocator.reserved_count = 2;
+
+        allocator
+    }
+
+    /// Excludes the frames of the given physical memory range from allocation.
+    ///
+    /// Returns `Error::OutOfResources` if `MAX_RESERVED_RANGES` ranges are already reserved and
+    /// `Error::InvalidParameter` if the end of the range cannot be aligned to a page.
+    pub fn reserve(&mut self, range: Range<PhysicalAddress>) -> Result<(), Error> {
+        if self.reserved_count == MAX_RESERVED_RANGES {
+            return Err(Error::OutOfResources);
+        }
+
+        let first_frame = range.start.0 / PAGE_SIZE as u64;
+        let end_frame = range
+            .end
+            .checked_align_up(PAGE_SIZE as u64)
+            .ok_or(Error::InvalidParameter)?
+            .0
+            / PAGE_SIZE as u64;
+
+        self.reserved[self.reserved_count] = first_frame..end_frame;
+        self.reserved_count += 1;
+
+        Ok(())
+    }
+
+    /// Returns the number of frames handed out so far.
+    pub fn allocated_frames(&self) -> u64 {
+        self.allocated
+    }
+
+    /// Allocates a single frame.
+    pub fn allocate_frame(&mut self) -> Option<PhysicalAddress> {
+        self.allocate_contiguous(1)
+    }
+
+    /// Allocates `count` physically contiguous frames and returns the address of the first one.
+    ///
+    /// Frames that are skipped because the current region is too small are not handed out later.
+    /// Returns `None` if no region has room for the frames.
+    pub fn allocate_contiguous(&mut self, count: u64) -> Option<PhysicalAddress> {
+        if count == 0 {
+            return None;
+        }
+
+        loop {
+            let candidate = self.current.start..self.current.start.checked_add(count)?;
+
+            if candidate.end > self.current.end {
+                self.next_region()?;
+                continue;
+            }
+
+            if let Some(reserved) = self.reserved_overlap(&candidate) {
+                self.current.start = reserved.end;
+                continue;
+            }
+
+            self.current.start = candidate.end;
+            self.allocated += count;
+
+            return Some(PhysicalAddress(candidate.start * PAGE_SIZE as u64));
+        }
+    }
+
+    /// Advances to the next `ConventionalMemory` region.
+    fn next_region(&mut self) -> Option<()> {
+        let region = self
+            .regions
+            .find(|descriptor| descriptor.Type == MemoryType::ConventionalMemory)?;
+
+        self.current = region.page_range();
+
+        Some(())
+    }
+
+    /// Returns the reserved range that overlaps the given frames, if any.
+    fn reserved_overlap(&self, frames: &Range<u64>) -> Option<Range<u64>> {
+        self.reserved[..self.reserved_count]
+            .iter()
+            .find(|reserved| reserved.start < frames.end && frames.start < reserved.end)
+            .cloned()
+    }
+}
@@ -1,4 +1,36 @@
-use crate::{guid::Guid, status::Status, time::Time};
+//! Provides access to the files on volumes with a supported file system.
+//!
+//! `open` opens a file by its path, which can refer to the volume the current image was loaded
+//! from, to a volume by its index or to a partition by its unique GUID.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::{
+    mem::size_of,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+    str::FromStr,
+};
+
+#[cfg(all(feature = "alloc", feature = "tpm"))]
+use crate::measure::{self, MeasureKind};
+use crate::{
+    boot::{BootServices, LocateSearchType},
+    device::DevicePath,
+    guid::{Guid, FILE_INFO_ID, FILE_SYSTEM_GUID},
+    loaded_image::LoadedImage,
+    protocol::Protocol,
+    quirk,
+    status::{Completion, Error, Status, StatusResult, Warning},
+    system,
+    time::Time,
+    ucs2::{self, Lossy},
+    Handle,
+};
+
+/// The number of bytes `File::read_to_vec` reads at once.
+#[cfg(feature = "alloc")]
+const READ_CHUNK_SIZE: usize = 4096;
 
 // Open modes
 pub const FILE_MODE_READ: u64 = 0x0000000000000001;
@@ -13,10 +45,29 @@ pub const FILE_RESERVED: u64 = 0x08;
 pub const FILE_DIRECTORY: u64 = 0x10;
 pub const FILE_ARCHIVE: u64 = 0x20;
 
+/// The maximum number of UTF-16 code units in a single component of a path passed to `open`,
+/// excluding the null terminator.
+pub const MAX_FILE_NAME_LEN: usize = 255;
+
 #[repr(C)]
 pub struct SimpleFileSystem {
     pub Revision: u64,
-    pub OpenVolume: extern "win64" fn(&mut SimpleFileSystem, Root: &mut *mut File) -> Status,
+    pub OpenVolume: eficall! { fn(&SimpleFileSystem, Root: &mut *mut File) -> Status },
+}
+
+unsafe impl Protocol for SimpleFileSystem {
+    const GUID: Guid = FILE_SYSTEM_GUID;
+}
+
+impl SimpleFileSystem {
+    /// Opens the root directory of the volume.
+    pub fn open_volume(&self) -> Result<OwnedFile, Error> {
+        let mut root = core::ptr::null_mut();
+
+        (self.OpenVolume)(self, &mut root)?;
+
+        NonNull::new(root).map(OwnedFile).ok_or(Error::NotFound)
+    }
 }
 
 #[repr(C)]
@@ -46,33 +97,357 @@ impl Default for FileInfo {
     }
 }
 
+impl FileInfo {
+    /// Returns the name of the file decoded up to the null terminator.
+    pub fn file_name(&self) -> Lossy {
+        Lossy::new(&self.FileName)
+    }
+}
+
 #[repr(C)]
 pub struct File {
     pub Revision: u64,
-    pub Open: extern "win64" fn(
+    pub Open: eficall! { fn(
         &mut File,
         NewHandle: &mut *mut File,
         FileName: *const u16,
         OpenMode: u64,
         Attributes: u64,
-    ) -> Status,
-    pub Close: extern "win64" fn(&mut File) -> Status,
-    pub Delete: extern "win64" fn(&mut File) -> Status,
-    pub Read: extern "win64" fn(&mut File, BufferSize: &mut usize, Buffer: *mut u8) -> Status,
-    pub Write: extern "win64" fn(&mut File, BufferSize: &mut usize, Buffer: *const u8) -> Status,
-    pub SetPosition: extern "win64" fn(&mut File, Position: u64) -> Status,
-    pub GetPosition: extern "win64" fn(&mut File, Position: &mut u64) -> Status,
-    pub GetInfo: extern "win64" fn(
+    ) -> Status },
+    pub Close: eficall! { fn(&mut File) -> Status },
+    pub Delete: eficall! { fn(&mut File) -> Status },
+    pub Read: eficall! { fn(&mut File, BufferSize: &mut usize, Buffer: *mut u8) -> Status },
+    pub Write: eficall! { fn(&mut File, BufferSize: &mut usize, Buffer: *const u8) -> Status },
+    pub SetPosition: eficall! { fn(&mut File, Position: u64) -> Status },
+    pub GetPosition: eficall! { fn(&mut File, Position: &mut u64) -> Status },
+    pub GetInfo: eficall! { fn(
         &mut File,
         InformationType: &Guid,
         BufferSize: &mut usize,
         Buffer: *mut u8,
-    ) -> Status,
-    pub SetInfo: extern "win64" fn(
+    ) -> Status },
+    pub SetInfo: eficall! { fn(
         &mut File,
         InformationType: &Guid,
         BufferSize: &mut usize,
         Buffer: *const u8,
-    ) -> Status,
-    pub Flush: extern "win64" fn(&mut File) -> Status,
+    ) -> Status },
+    pub Flush: eficall! { fn(&mut File) -> Status },
+}
+
+impl File {
+    /// Opens the file with the given name relative to this directory.
+    ///
+    /// `name` may contain multiple components separated by backslashes. Returns
+    /// `Error::BufferTooSmall` if it is longer than `MAX_FILE_NAME_LEN`.
+    pub fn open(&mut self, name: &str, mode: u64, attributes: u64) -> Result<OwnedFile, Error> {
+        let mut buffer = [0u16; MAX_FILE_NAME_LEN + 1];
+        ucs2::encode_into(name, &mut buffer)?;
+
+        let mut file = core::ptr::null_mut();
+
+        (self.Open)(self, &mut file, buffer.as_ptr(), mode, attributes)?;
+
+        NonNull::new(file).map(OwnedFile).ok_or(Error::NotFound)
+    }
+
+    /// Reads data from the current position into `buffer`.
+    ///
+    /// Returns the number of bytes read, which is zero at the end of the file.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+        let mut size = buffer.len();
+
+        (self.Read)(self, &mut size, buffer.as_mut_ptr())?;
+
+        quirk::check_transfer(size, buffer.len())
+    }
+
+    /// Fills `buffer` with data from the current position.
+    ///
+    /// Returns `Error::EndOfFile` if the file ends before the buffer is full.
+    pub fn read_exact(&mut self, mut buffer: &mut [u8]) -> Result<(), Error> {
+        while !buffer.is_empty() {
+            match self.read(buffer)? {
+                0 => return Err(Error::EndOfFile),
+                size => {
+                    let rest = buffer;
+                    buffer = &mut rest[size..];
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` at the current position.
+    ///
+    /// Returns the number of bytes written, which the firmware may report as less than the size of
+    /// `data` if the device is full. The completion carries any warning the firmware returned,
+    /// such as `Warning::WriteFailure`.
+    pub fn write(&mut self, data: &[u8]) -> StatusResult<usize> {
+        let mut size = data.len();
+
+        let warning = (self.Write)(self, &mut size, data.as_ptr())?;
+
+        Ok(Completion::new(
+            quirk::check_transfer(size, data.len())?,
+            warning,
+        ))
+    }
+
+    /// Writes all of `data` at the current position.
+    ///
+    /// Returns `Error::VolumeFull` if the firmware stops writing before all data was written. The
+    /// completion carries the first warning the firmware returned while writing.
+    pub fn write_all(&mut self, mut data: &[u8]) -> StatusResult<()> {
+        let mut warning = Warning::Success;
+
+        while !data.is_empty() {
+            let (size, write_warning) = self.write(data)?.split();
+
+            if warning == Warning::Success {
+                warning = write_warning;
+            }
+
+            match size {
+                0 => return Err(Error::VolumeFull),
+                size => data = &data[size..],
+            }
+        }
+
+        Ok(Completion::new((), warning))
+    }
+
+    /// Returns information about the file, such as its size.
+    ///
+    /// Returns `Error::BufferTooSmall` if the name of the file does not fit into
+    /// `FileInfo::FileName`.
+    pub fn info(&mut self) -> Result<FileInfo, Error> {
+        let mut info = FileInfo::default();
+        let mut size = size_of::<FileInfo>();
+
+        (self.GetInfo)(
+            self,
+            &FILE_INFO_ID,
+            &mut size,
+            &mut info as *mut FileInfo as *mut u8,
+        )?;
+
+        Ok(info)
+    }
+
+    /// Reads all data from the current position to the end of the file.
+    #[cfg(feature = "alloc")]
+    pub fn read_to_vec(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        let mut chunk = [0; READ_CHUNK_SIZE];
+
+        loop {
+            match self.read(&mut chunk)? {
+                0 => break Ok(data),
+                size => data.extend_from_slice(&chunk[..size]),
+            }
+        }
+    }
+}
+
+/// An open file or directory that is closed when it is dropped.
+#[derive(Debug)]
+pub struct OwnedFile(NonNull<File>);
+
+impl OwnedFile {
+    /// Closes the file.
+    ///
+    /// The completion carries `Warning::WriteFailure` if the file was closed, but its data was
+    /// not flushed properly.
+    pub fn close(self) -> StatusResult<()> {
+        let file = self.0;
+        core::mem::forget(self);
+
+        // This is safe, because the file is open until it is closed here.
+        unsafe { ((*file.as_ptr()).Close)(&mut *file.as_ptr()) }.with_value(())
+    }
+
+    /// Deletes the file, which also closes it.
+    ///
+    /// Returns `Warning::DeleteFailure` if the firmware closed the file without deleting it.
+    pub fn delete(self) -> StatusResult<()> {
+        let file = self.0;
+        core::mem::forget(self);
+
+        // This is safe, because the file is open until it is deleted here.
+        unsafe { ((*file.as_ptr()).Delete)(&mut *file.as_ptr()) }.with_value(())
+    }
+}
+
+impl Deref for OwnedFile {
+    type Target = File;
+
+    fn deref(&self) -> &File {
+        // This is safe, because the file is open until it is closed.
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl DerefMut for OwnedFile {
+    fn deref_mut(&mut self) -> &mut File {
+        // This is safe, because the file is open until it is closed.
+        unsafe { self.0.as_mut() }
+    }
+}
+
+impl Drop for OwnedFile {
+    fn drop(&mut self) {
+        // There is no way to report the error here.
+        let _ = (self.Close)(self);
+    }
+}
+
+/// The volume a path passed to `open` refers to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Volume {
+    /// The volume the current image was loaded from.
+    Image,
+    /// The volume with the given index among all volumes in the handle database, as in `fs0:`.
+    Index(usize),
+    /// The partition with the given unique GUID.
+    Partition(Guid),
+}
+
+impl Volume {
+    /// Splits a path into the volume it refers to and the path on the volume.
+    ///
+    /// Paths without a prefix refer to `Volume::Image`, `fsN:` refers to `Volume::Index` and a
+    /// GUID followed by a colon refers to `Volume::Partition`.
+    pub fn parse(path: &str) -> Result<(Volume, &str), Error> {
+        let colon = match path.find(':') {
+            Some(colon) => colon,
+            None => return Ok((Volume::Image, path)),
+        };
+
+        let (prefix, rest) = (&path[..colon], &path[colon + 1..]);
+
+        let volume = if prefix.len() > 2 && prefix[..2].eq_ignore_ascii_case("fs") {
+            Volume::Index(prefix[2..].parse().map_err(|_| Error::InvalidParameter)?)
+        } else {
+            Volume::Partition(Guid::from_str(prefix).map_err(|_| Error::InvalidParameter)?)
+        };
+
+        Ok((volume, rest))
+    }
+
+    /// Returns the handle of the volume.
+    ///
+    /// Returns `Error::NotFound` if there is no such volume.
+    pub fn handle(
+        self,
+        boot_services: &BootServices,
+        image_handle: Handle,
+    ) -> Result<Handle, Error> {
+        match self {
+            Volume::Image => boot_services
+                .handle_protocol::<LoadedImage>(image_handle)?
+                .DeviceHandle
+                .ok_or(Error::NotFound),
+            Volume::Index(index) => {
+                let handles = boot_services.locate_handle_buffer(
+                    LocateSearchType::ByProtocol,
+                    Some(&FILE_SYSTEM_GUID),
+                    0,
+                )?;
+                let handle = handles.get(index).cloned();
+                handles.free(boot_services)?;
+
+                handle.ok_or(Error::NotFound)
+            }
+            Volume::Partition(guid) => {
+                let handles = boot_services.locate_handle_buffer(
+                    LocateSearchType::ByProtocol,
+                    Some(&FILE_SYSTEM_GUID),
+                    0,
+                )?;
+                let handle = handles.iter().cloned().find(|&handle| {
+                    boot_services
+                        .handle_protocol::<DevicePath>(handle)
+                        .map(|path| {
+                            path.nodes()
+                                .filter_map(DevicePath::as_hard_drive)
+                                .any(|node| node.partition_guid() == Some(guid))
+                        })
+                        .unwrap_or(false)
+                });
+                handles.free(boot_services)?;
+
+                handle.ok_or(Error::NotFound)
+            }
+        }
+    }
+}
+
+/// Opens a file for reading by its path.
+///
+/// The boot services and image handle registered with `system::init` are used to find the
+/// volume, see `open_with` for the accepted paths. Returns `Error::NotStarted` if `system::init`
+/// was not called.
+pub fn open(path: &str) -> Result<OwnedFile, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+    let image_handle = system::image_handle().ok_or(Error::NotStarted)?;
+
+    open_with(boot_services, image_handle, path, FILE_MODE_READ)
+}
+
+/// Reads the configuration file at `path`.
+///
+/// With the `tpm` feature, the file is measured as `MeasureKind::Config` according to the global
+/// `measure::MeasurePolicy`. The boot services and image handle registered with `system::init`
+/// are used, see `open_with` for the accepted paths. Returns `Error::NotStarted` if
+/// `system::init` was not called.
+#[cfg(feature = "alloc")]
+pub fn read_config(path: &str) -> Result<Vec<u8>, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+    let image_handle = system::image_handle().ok_or(Error::NotStarted)?;
+
+    let data = open_with(boot_services, image_handle, path, FILE_MODE_READ)?.read_to_vec()?;
+    #[cfg(feature = "tpm")]
+    measure::measure_loaded(boot_services, MeasureKind::Config, path, &data)?;
+
+    Ok(data)
+}
+
+/// Opens a file by its path with the given mode.
+///
+/// Paths like `\EFI\BOOT\kernel.elf` refer to the volume the image was loaded from, paths like
+/// `fs0:\EFI\BOOT\kernel.elf` to a volume by its index and paths with a partition GUID as the
+/// prefix to the partition with that GUID. Both slashes and backslashes separate directories.
+/// An empty path opens the root directory of the volume.
+pub fn open_with(
+    boot_services: &BootServices,
+    image_handle: Handle,
+    path: &str,
+    mode: u64,
+) -> Result<OwnedFile, Error> {
+    let (volume, path) = Volume::parse(path)?;
+    let handle = volume.handle(boot_services, image_handle)?;
+
+    let mut file = boot_services
+        .handle_protocol::<SimpleFileSystem>(handle)?
+        .open_volume()?;
+
+    let mut components = path
+        .split(|character| character == '\\' || character == '/')
+        .filter(|component| !component.is_empty() && *component != ".")
+        .peekable();
+
+    while let Some(component) = components.next() {
+        // Directories on the way are only read, the mode only applies to the file itself.
+        let component_mode = if components.peek().is_some() {
+            FILE_MODE_READ
+        } else {
+            mode
+        };
+
+        file = file.open(component, component_mode, 0)?;
+    }
+
+    Ok(file)
 }
@@ -0,0 +1,439 @@
+//! A text console that renders glyphs directly onto the frame buffer of the graphics output
+//! protocol.
+//!
+//! The text output protocol is no longer available after `ExitBootServices` and some firmware
+//! implements it poorly, but the linear frame buffer stays accessible. `GfxConsole` offers the
+//! same interface as `TextOutput`, including `fmt::Write` and colors, and draws every character
+//! with a bitmap font, scrolling the screen when the last row is full.
+//!
+//! As on the text output protocol, a line feed only moves the cursor down and a carriage return
+//! moves it to the start of the line.
+
+pub mod font;
+
+use core::{fmt, ptr};
+
+use self::font::{Font, Glyph};
+use crate::{
+    graphics::{GraphicsBltPixel, GraphicsOutput, GraphicsOutputModeInfo, GraphicsPixelFormat},
+    status::Error,
+    text::{BackgroundColor, Color, ForegroundColor},
+};
+
+/// Creates a pixel with the given color components.
+const fn rgb(red: u8, green: u8, blue: u8) -> GraphicsBltPixel {
+    GraphicsBltPixel {
+        Blue: blue,
+        Green: green,
+        Red: red,
+        Reserved: 0,
+    }
+}
+
+/// The pixel colors of the sixteen text colors, indexed by the value of `ForegroundColor`.
+///
+/// These match the colors of the graphics console of the EDK II firmware.
+pub const PALETTE: [GraphicsBltPixel; 16] = [
+    rgb(0x00, 0x00, 0x00),
+    rgb(0x00, 0x00, 0x98),
+    rgb(0x00, 0x98, 0x00),
+    rgb(0x00, 0x98, 0x98),
+    rgb(0x98, 0x00, 0x00),
+    rgb(0x98, 0x00, 0x98),
+    rgb(0x98, 0x98, 0x00),
+    rgb(0x98, 0x98, 0x98),
+    rgb(0x30, 0x30, 0x30),
+    rgb(0x00, 0x00, 0xff),
+    rgb(0x00, 0xff, 0x00),
+    rgb(0x00, 0xff, 0xff),
+    rgb(0xff, 0x00, 0x00),
+    rgb(0xff, 0x00, 0xff),
+    rgb(0xff, 0xff, 0x00),
+    rgb(0xff, 0xff, 0xff),
+];
+
+/// Scales an 8 bit color component to the bits of `mask`.
+fn scale_component(value: u8, mask: u32) -> u32 {
+    if mask == 0 {
+        return 0;
+    }
+
+    let shift = mask.trailing_zeros();
+    let max = u64::from(mask >> shift);
+
+    (((u64::from(value) * max + 127) / 255) as u32) << shift
+}
+
+/// Checks that the masks of a `PixelBitMask` mode describe 32 bit pixels with contiguous color
+/// components, which is the layout `Framebuffer` writes.
+///
+/// The size of a pixel is given by the highest bit set in any of the masks, so a mode whose
+/// masks all lie in the low 24 bits has pixels smaller than 32 bits.
+fn check_masks(colors: [u32; 3], reserved: u32) -> Result<(), Error> {
+    let mut used = reserved;
+
+    for &mask in colors.iter() {
+        let component = mask.checked_shr(mask.trailing_zeros()).unwrap_or(0);
+
+        // Every color needs a contiguous mask that does not overlap with the other masks.
+        if mask == 0 || component & component.wrapping_add(1) != 0 || used & mask != 0 {
+            return Err(Error::Unsupported);
+        }
+
+        used |= mask;
+    }
+
+    if used.leading_zeros() >= 8 {
+        return Err(Error::Unsupported);
+    }
+
+    Ok(())
+}
+
+/// A linear frame buffer with 32 bit pixels.
+pub struct Framebuffer {
+    /// The address of the top left pixel.
+    base: *mut u32,
+    /// The number of visible pixels in a row.
+    width: usize,
+    /// The number of rows.
+    height: usize,
+    /// The number of pixels between the starts of two rows.
+    stride: usize,
+    /// The bits of a pixel holding the red, green and blue components.
+    masks: [u32; 3],
+}
+
+impl Framebuffer {
+    /// Returns the frame buffer of the current mode of `graphics_output`.
+    ///
+    /// Returns `Error::Unsupported` if the mode has no linear frame buffer with 32 bit pixels.
+    ///
+    /// # Safety
+    /// The frame buffer must not be accessed by anything else while the `Framebuffer` exists,
+    /// including `Blt` calls and mode changes through `graphics_output`.
+    pub unsafe fn from_gop(graphics_output: &GraphicsOutput) -> Result<Framebuffer, Error> {
+        Framebuffer::new(
+            graphics_output.Mode.FrameBufferBase,
+            graphics_output.Mode.Info,
+        )
+    }
+
+    /// Creates a frame buffer at `base` with the layout described by `info`.
+    ///
+    /// The mode information can be copied before `ExitBootServices` to use the frame buffer
+    /// afterwards. Returns `Error::Unsupported` if the pixel format has no linear frame buffer or
+    /// the pixel masks do not describe 32 bit pixels.
+    ///
+    /// # Safety
+    /// `base` must be the address of a frame buffer with the layout described by `info`, which
+    /// must not be accessed by anything else while the `Framebuffer` exists.
+    pub unsafe fn new(base: usize, info: &GraphicsOutputModeInfo) -> Result<Framebuffer, Error> {
+        let masks = match info.PixelFormat {
+            GraphicsPixelFormat::PixelRedGreenBlueReserved8BitPerColor => {
+                [0x0000_00ff, 0x0000_ff00, 0x00ff_0000]
+            }
+            GraphicsPixelFormat::PixelBlueGreenRedReserved8BitPerColor => {
+                [0x00ff_0000, 0x0000_ff00, 0x0000_00ff]
+            }
+            GraphicsPixelFormat::PixelBitMask => {
+                let masks = info.PixelInformation;
+                check_masks(
+                    [masks.RedMask, masks.GreenMask, masks.BlueMask],
+                    masks.ReservedMask,
+                )?;
+
+                [masks.RedMask, masks.GreenMask, masks.BlueMask]
+            }
+            _ => return Err(Error::Unsupported),
+        };
+
+        Ok(Framebuffer {
+            base: base as *mut u32,
+            width: info.HorizontalResolution as usize,
+            height: info.VerticalResolution as usize,
+            stride: info.PixelsPerScanLine as usize,
+            masks,
+        })
+    }
+
+    /// Returns the width of the frame buffer in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the frame buffer in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Converts a pixel to the format of the frame buffer.
+    pub fn encode(&self, pixel: GraphicsBltPixel) -> u32 {
+        scale_component(pixel.Red, self.masks[0])
+            | scale_component(pixel.Green, self.masks[1])
+            | scale_component(pixel.Blue, self.masks[2])
+    }
+
+    /// Sets the pixel at the given position to an encoded value.
+    ///
+    /// Positions outside of the frame buffer are ignored.
+    pub fn write_encoded(&mut self, x: usize, y: usize, value: u32) {
+        if x < self.width && y < self.height {
+            // This is safe, because the position is inside of the frame buffer.
+            unsafe { ptr::write_volatile(self.base.add(y * self.stride + x), value) };
+        }
+    }
+
+    /// Sets the pixel at the given position.
+    ///
+    /// Positions outside of the frame buffer are ignored.
+    pub fn write_pixel(&mut self, x: usize, y: usize, pixel: GraphicsBltPixel) {
+        let value = self.encode(pixel);
+
+        self.write_encoded(x, y, value);
+    }
+
+    /// Fills a rectangle with a single color.
+    ///
+    /// The rectangle is clipped to the frame buffer.
+    pub fn fill(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixel: GraphicsBltPixel,
+    ) {
+        let value = self.encode(pixel);
+        let x_end = x.saturating_add(width).min(self.width);
+        let y_end = y.saturating_add(height).min(self.height);
+
+        for row in y..y_end {
+            for column in x..x_end {
+                self.write_encoded(column, row, value);
+            }
+        }
+    }
+
+    /// Moves the first `height` rows of pixels up by `lines` rows and fills the rows that became
+    /// free with a single color.
+    pub fn scroll_up(&mut self, lines: usize, height: usize, pixel: GraphicsBltPixel) {
+        let height = height.min(self.height);
+        let lines = lines.min(height);
+
+        // This is safe, because both regions lie inside of the first `height` rows and
+        // `ptr::copy` handles overlapping regions.
+        unsafe {
+            ptr::copy(
+                self.base.add(lines * self.stride),
+                self.base,
+                (height - lines) * self.stride,
+            );
+        }
+
+        self.fill(0, height - lines, self.width, lines, pixel);
+    }
+}
+
+/// Renders text onto a frame buffer.
+pub struct GfxConsole<'a> {
+    /// The frame buffer the text is drawn onto.
+    framebuffer: Framebuffer,
+    /// The font of the text.
+    font: Font<'a>,
+    /// The number of characters in a row.
+    columns: usize,
+    /// The number of rows.
+    rows: usize,
+    /// The column of the cursor.
+    column: usize,
+    /// The row of the cursor.
+    row: usize,
+    /// The colors of the text that is output.
+    attribute: Color,
+}
+
+impl<'a> GfxConsole<'a> {
+    /// Creates a console that draws text with `font` and starts at the top left corner.
+    ///
+    /// The text is light gray on black initially and the screen is not cleared. Returns
+    /// `Error::Unsupported` if the frame buffer cannot hold a single glyph of the font.
+    pub fn new(framebuffer: Framebuffer, font: Font<'a>) -> Result<GfxConsole<'a>, Error> {
+        let columns = framebuffer.width() / font.width();
+        let rows = framebuffer.height() / font.height();
+
+        if columns == 0 || rows == 0 {
+            return Err(Error::Unsupported);
+        }
+
+        Ok(GfxConsole {
+            framebuffer,
+            font,
+            columns,
+            rows,
+            column: 0,
+            row: 0,
+            attribute: Color::new(ForegroundColor::LightGray, BackgroundColor::Black),
+        })
+    }
+
+    /// Returns the number of characters in a row.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the frame buffer the text is drawn onto.
+    pub fn framebuffer(&mut self) -> &mut Framebuffer {
+        &mut self.framebuffer
+    }
+
+    /// Displays the string on the screen.
+    ///
+    /// Characters without a glyph in the font are drawn as a question mark, or left blank if the
+    /// font has no question mark either.
+    pub fn output_string(&mut self, string: &str) -> Result<(), Error> {
+        for character in string.chars() {
+            self.output_char(character);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the foreground and background color of the text that is output.
+    pub fn set_attribute(&mut self, attribute: Color) -> Result<(), Error> {
+        self.attribute = attribute;
+
+        Ok(())
+    }
+
+    /// Returns the foreground and background color of the text that is output.
+    pub fn attribute(&self) -> Color {
+        self.attribute
+    }
+
+    /// Runs `function` with the given color set and restores the previous color afterwards.
+    pub fn with_color<F, R>(&mut self, color: Color, function: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut GfxConsole<'a>) -> R,
+    {
+        let previous = self.attribute();
+
+        self.set_attribute(color)?;
+
+        let result = function(self);
+
+        self.set_attribute(previous)?;
+
+        Ok(result)
+    }
+
+    /// Displays the string in the given color and restores the previous color afterwards.
+    pub fn write_colored(&mut self, color: Color, string: &str) -> Result<(), Error> {
+        self.with_color(color, |console| console.output_string(string))?
+    }
+
+    /// Clears the screen with the current background color and moves the cursor to the top left
+    /// corner.
+    pub fn clear_screen(&mut self) -> Result<(), Error> {
+        let background = self.background();
+        let (width, height) = (self.framebuffer.width(), self.framebuffer.height());
+
+        self.framebuffer.fill(0, 0, width, height, background);
+        self.column = 0;
+        self.row = 0;
+
+        Ok(())
+    }
+
+    /// Sets the current cursor position.
+    ///
+    /// Returns `Error::Unsupported` if the position is outside of the screen, as the text output
+    /// protocol does.
+    pub fn set_cursor_position(&mut self, column: usize, row: usize) -> Result<(), Error> {
+        if column >= self.columns || row >= self.rows {
+            return Err(Error::Unsupported);
+        }
+
+        self.column = column;
+        self.row = row;
+
+        Ok(())
+    }
+
+    /// Returns the current cursor position as (column, row).
+    pub fn cursor_position(&self) -> (usize, usize) {
+        (self.column, self.row)
+    }
+
+    /// Returns the pixel color of the foreground.
+    fn foreground(&self) -> GraphicsBltPixel {
+        PALETTE[self.attribute.foreground_index()]
+    }
+
+    /// Returns the pixel color of the background.
+    fn background(&self) -> GraphicsBltPixel {
+        PALETTE[self.attribute.background_index()]
+    }
+
+    /// Handles a single character, moving the cursor accordingly.
+    fn output_char(&mut self, character: char) {
+        match character {
+            '\r' => self.column = 0,
+            '\n' => self.line_feed(),
+            '\u{8}' => self.column = self.column.saturating_sub(1),
+            _ if character.is_control() => (),
+            _ => {
+                if self.column >= self.columns {
+                    self.column = 0;
+                    self.line_feed();
+                }
+
+                let glyph = self.font.glyph(character).or_else(|| self.font.glyph('?'));
+                self.draw_glyph(glyph);
+
+                self.column += 1;
+            }
+        }
+    }
+
+    /// Moves the cursor down by one row, scrolling if it is in the last row.
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.rows {
+            self.row += 1;
+        } else {
+            let background = self.background();
+            let height = self.font.height();
+
+            self.framebuffer
+                .scroll_up(height, self.rows * height, background);
+        }
+    }
+
+    /// Draws a glyph at the cursor position, or an empty cell if there is no glyph.
+    fn draw_glyph(&mut self, glyph: Option<Glyph>) {
+        let foreground = self.framebuffer.encode(self.foreground());
+        let background = self.framebuffer.encode(self.background());
+        let (width, height) = (self.font.width(), self.font.height());
+        let (left, top) = (self.column * width, self.row * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let set = glyph.map_or(false, |glyph| glyph.pixel(x, y));
+                let value = if set { foreground } else { background };
+
+                self.framebuffer.write_encoded(left + x, top + y, value);
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Write for GfxConsole<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.output_string(s).map_err(|_| fmt::Error)
+    }
+}
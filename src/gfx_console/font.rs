@@ -0,0 +1,865 @@
+//! Bitmap fonts in the PC Screen Font format and the built-in font.
+//!
+//! Both versions of the PC Screen Font format are supported, with and without a unicode table.
+//! Fonts without a unicode table map characters to glyphs by their code point.
+
+use core::{char, str};
+
+use crate::status::Error;
+
+/// The magic number of a PSF1 font.
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+/// The PSF1 font has 512 glyphs instead of 256.
+const PSF1_MODE_512: u8 = 0x01;
+/// The PSF1 font has a unicode table.
+const PSF1_MODE_HAS_TABLE: u8 = 0x02;
+/// The PSF1 font has a unicode table that includes sequences.
+const PSF1_MODE_HAS_SEQUENCES: u8 = 0x04;
+/// Ends the entry of a glyph in the unicode table of a PSF1 font.
+const PSF1_SEPARATOR: u16 = 0xffff;
+/// Starts the sequences of a glyph in the unicode table of a PSF1 font.
+const PSF1_START_SEQUENCE: u16 = 0xfffe;
+
+/// The magic number of a PSF2 font.
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+/// The size of the PSF2 header.
+const PSF2_HEADER_SIZE: usize = 32;
+/// The PSF2 font has a unicode table.
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+/// Ends the entry of a glyph in the unicode table of a PSF2 font.
+const PSF2_SEPARATOR: u8 = 0xff;
+/// Starts the sequences of a glyph in the unicode table of a PSF2 font.
+const PSF2_START_SEQUENCE: u8 = 0xfe;
+
+/// The first character of the built-in font.
+const BUILTIN_FIRST: u32 = 0x20;
+/// The width of the glyphs of the built-in font.
+const BUILTIN_WIDTH: usize = 8;
+/// The height of the glyphs of the built-in font.
+const BUILTIN_HEIGHT: usize = 16;
+
+/// Marks a character without a glyph in the lookup table.
+const NO_GLYPH: u16 = u16::max_value();
+
+/// The format of the unicode table of a font.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum UnicodeTable<'a> {
+    /// The font has no table, so glyphs are indexed by the code point minus the first character.
+    None {
+        /// The character of the first glyph.
+        first: u32,
+    },
+    /// A PSF1 table of little endian UCS-2 code units.
+    Psf1(&'a [u8]),
+    /// A PSF2 table of UTF-8 encoded characters.
+    Psf2(&'a [u8]),
+}
+
+/// A bitmap font with glyphs of a fixed size.
+#[derive(Clone)]
+pub struct Font<'a> {
+    /// The bitmaps of all glyphs.
+    glyphs: &'a [u8],
+    /// The number of glyphs.
+    glyph_count: usize,
+    /// The size of a single glyph in bytes.
+    bytes_per_glyph: usize,
+    /// The width of a glyph in pixels.
+    width: usize,
+    /// The height of a glyph in pixels.
+    height: usize,
+    /// Maps characters to glyphs.
+    table: UnicodeTable<'a>,
+    /// The glyph indices of the first 256 characters, so they do not need to be looked up in the
+    /// unicode table.
+    latin1: [u16; 256],
+}
+
+impl Font<'static> {
+    /// Returns the built-in 8x16 font, which covers the printable ASCII characters.
+    pub fn builtin() -> Font<'static> {
+        // This is safe, because the glyphs are stored without any padding.
+        let glyphs = unsafe {
+            core::slice::from_raw_parts(
+                BUILTIN_GLYPHS.as_ptr() as *const u8,
+                BUILTIN_GLYPHS.len() * BUILTIN_HEIGHT,
+            )
+        };
+
+        Font::with_table(
+            glyphs,
+            BUILTIN_GLYPHS.len(),
+            BUILTIN_WIDTH,
+            BUILTIN_HEIGHT,
+            UnicodeTable::None {
+                first: BUILTIN_FIRST,
+            },
+        )
+    }
+}
+
+impl<'a> Font<'a> {
+    /// Parses a font in the PSF1 or PSF2 format.
+    ///
+    /// Returns `Error::Unsupported` if `data` is not a PC Screen Font and `Error::BadBufferSize`
+    /// if it is truncated or its header is inconsistent.
+    pub fn parse(data: &'a [u8]) -> Result<Font<'a>, Error> {
+        if data.starts_with(&PSF1_MAGIC) {
+            Font::parse_psf1(data)
+        } else if data.starts_with(&PSF2_MAGIC) {
+            Font::parse_psf2(data)
+        } else {
+            Err(Error::Unsupported)
+        }
+    }
+
+    /// Parses a font in the PSF1 format.
+    fn parse_psf1(data: &'a [u8]) -> Result<Font<'a>, Error> {
+        let mode = *data.get(2).ok_or(Error::BadBufferSize)?;
+        let height = usize::from(*data.get(3).ok_or(Error::BadBufferSize)?);
+        let glyph_count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+
+        let glyphs_end = 4 + glyph_count * height;
+        let glyphs = data.get(4..glyphs_end).ok_or(Error::BadBufferSize)?;
+
+        if height == 0 {
+            return Err(Error::BadBufferSize);
+        }
+
+        let table = if mode & (PSF1_MODE_HAS_TABLE | PSF1_MODE_HAS_SEQUENCES) != 0 {
+            UnicodeTable::Psf1(&data[glyphs_end..])
+        } else {
+            UnicodeTable::None { first: 0 }
+        };
+
+        Ok(Font::with_table(glyphs, glyph_count, 8, height, table))
+    }
+
+    /// Parses a font in the PSF2 format.
+    fn parse_psf2(data: &'a [u8]) -> Result<Font<'a>, Error> {
+        let field = |index: usize| -> Result<usize, Error> {
+            let bytes = data
+                .get(index * 4..index * 4 + 4)
+                .ok_or(Error::BadBufferSize)?;
+
+            Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+        };
+
+        let header_size = field(2)?;
+        let flags = field(3)? as u32;
+        let glyph_count = field(4)?;
+        let bytes_per_glyph = field(5)?;
+        let height = field(6)?;
+        let width = field(7)?;
+
+        if field(1)? != 0 {
+            return Err(Error::Unsupported);
+        }
+
+        if header_size < PSF2_HEADER_SIZE
+            || width == 0
+            || height == 0
+            || glyph_count > usize::from(NO_GLYPH)
+            || bytes_per_glyph != (width + 7) / 8 * height
+        {
+            return Err(Error::BadBufferSize);
+        }
+
+        let glyphs_end = glyph_count
+            .checked_mul(bytes_per_glyph)
+            .and_then(|size| size.checked_add(header_size))
+            .ok_or(Error::BadBufferSize)?;
+        let glyphs = data
+            .get(header_size..glyphs_end)
+            .ok_or(Error::BadBufferSize)?;
+
+        let table = if flags & PSF2_HAS_UNICODE_TABLE != 0 {
+            UnicodeTable::Psf2(&data[glyphs_end..])
+        } else {
+            UnicodeTable::None { first: 0 }
+        };
+
+        Ok(Font::with_table(glyphs, glyph_count, width, height, table))
+    }
+
+    /// Creates a font and fills the lookup table of the first 256 characters.
+    fn with_table(
+        glyphs: &'a [u8],
+        glyph_count: usize,
+        width: usize,
+        height: usize,
+        table: UnicodeTable<'a>,
+    ) -> Font<'a> {
+        let mut font = Font {
+            glyphs,
+            glyph_count,
+            bytes_per_glyph: (width + 7) / 8 * height,
+            width,
+            height,
+            table,
+            latin1: [NO_GLYPH; 256],
+        };
+
+        match table {
+            UnicodeTable::None { first } => {
+                for (code_point, entry) in font.latin1.iter_mut().enumerate() {
+                    let code_point = code_point as u32;
+
+                    if code_point >= first && ((code_point - first) as usize) < glyph_count {
+                        *entry = (code_point - first) as u16;
+                    }
+                }
+            }
+            _ => {
+                // Later entries do not replace earlier ones, matching `lookup`.
+                let latin1 = &mut font.latin1;
+                table.for_each(|character, index| {
+                    let code_point = character as usize;
+
+                    if code_point < latin1.len() && latin1[code_point] == NO_GLYPH {
+                        latin1[code_point] = index as u16;
+                    }
+
+                    true
+                });
+            }
+        }
+
+        font
+    }
+
+    /// Returns the width of a glyph in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of a glyph in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of glyphs in the font.
+    pub fn glyph_count(&self) -> usize {
+        self.glyph_count
+    }
+
+    /// Returns the glyph of `character`, if the font has one.
+    pub fn glyph(&self, character: char) -> Option<Glyph<'a>> {
+        let code_point = character as usize;
+
+        let index = if code_point < self.latin1.len() {
+            match self.latin1[code_point] {
+                NO_GLYPH => None,
+                index => Some(usize::from(index)),
+            }
+        } else {
+            self.lookup(character)
+        }?;
+
+        self.glyph_at(index)
+    }
+
+    /// Returns the glyph with the given index.
+    pub fn glyph_at(&self, index: usize) -> Option<Glyph<'a>> {
+        if index >= self.glyph_count {
+            return None;
+        }
+
+        let start = index * self.bytes_per_glyph;
+
+        Some(Glyph {
+            data: &self.glyphs[start..start + self.bytes_per_glyph],
+            width: self.width,
+            height: self.height,
+        })
+    }
+
+    /// Finds the glyph index of a character outside of the first 256 in the unicode table.
+    fn lookup(&self, character: char) -> Option<usize> {
+        let code_point = character as u32;
+
+        match self.table {
+            UnicodeTable::None { first } if code_point >= first => {
+                Some((code_point - first) as usize)
+            }
+            UnicodeTable::None { .. } => None,
+            table => {
+                let mut found = None;
+
+                table.for_each(|entry, index| {
+                    if entry == character {
+                        found = Some(index);
+                    }
+
+                    found.is_none()
+                });
+
+                found
+            }
+        }
+    }
+}
+
+impl<'a> UnicodeTable<'a> {
+    /// Calls `function` with every single character in the table and the index of its glyph,
+    /// until `function` returns false.
+    ///
+    /// Sequences of multiple characters are skipped, since they cannot be rendered as one glyph.
+    fn for_each<F: FnMut(char, usize) -> bool>(self, mut function: F) {
+        match self {
+            UnicodeTable::None { .. } => (),
+            UnicodeTable::Psf1(table) => {
+                let mut index = 0;
+                let mut in_sequences = false;
+
+                for bytes in table.chunks_exact(2) {
+                    match u16::from_le_bytes([bytes[0], bytes[1]]) {
+                        PSF1_SEPARATOR => {
+                            index += 1;
+                            in_sequences = false;
+                        }
+                        PSF1_START_SEQUENCE => in_sequences = true,
+                        _ if in_sequences => (),
+                        code_unit => {
+                            if let Some(character) = char::from_u32(u32::from(code_unit)) {
+                                if !function(character, index) {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            UnicodeTable::Psf2(table) => {
+                for (index, entry) in table.split(|&byte| byte == PSF2_SEPARATOR).enumerate() {
+                    let singles = entry
+                        .split(|&byte| byte == PSF2_START_SEQUENCE)
+                        .next()
+                        .unwrap_or(&[]);
+
+                    // Invalid UTF-8 only affects the entry it occurs in.
+                    for character in str::from_utf8(singles).unwrap_or("").chars() {
+                        if !function(character, index) {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The bitmap of a single character.
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph<'a> {
+    /// The rows of the bitmap, each padded to a whole number of bytes.
+    data: &'a [u8],
+    /// The width of the glyph in pixels.
+    width: usize,
+    /// The height of the glyph in pixels.
+    height: usize,
+}
+
+impl<'a> Glyph<'a> {
+    /// Returns the width of the glyph in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the glyph in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns true if the pixel at the given position is set.
+    ///
+    /// Positions outside of the glyph are never set.
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let bytes_per_row = (self.width + 7) / 8;
+
+        self.data[y * bytes_per_row + x / 8] & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// The glyphs of the built-in font, for the characters from U+0020 to U+007E.
+static BUILTIN_GLYPHS: [[u8; BUILTIN_HEIGHT]; 95] = [
+    // ' '
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '!'
+    [
+        0x00, 0x00, 0x18, 0x3c, 0x3c, 0x3c, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '"'
+    [
+        0x00, 0x00, 0x66, 0x66, 0x66, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '#'
+    [
+        0x00, 0x00, 0x00, 0x6c, 0x6c, 0xfe, 0x6c, 0x6c, 0x6c, 0xfe, 0x6c, 0x6c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '$'
+    [
+        0x00, 0x18, 0x18, 0x7c, 0xc6, 0xc0, 0x78, 0x0c, 0x06, 0xc6, 0x7c, 0x18, 0x18, 0x00, 0x00,
+        0x00,
+    ],
+    // '%'
+    [
+        0x00, 0x00, 0x00, 0x00, 0xc2, 0xc6, 0x0c, 0x18, 0x30, 0x60, 0xc6, 0x86, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '&'
+    [
+        0x00, 0x00, 0x38, 0x6c, 0x6c, 0x38, 0x76, 0xdc, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '\''
+    [
+        0x00, 0x00, 0x18, 0x18, 0x18, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '('
+    [
+        0x00, 0x00, 0x0c, 0x18, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x18, 0x0c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // ')'
+    [
+        0x00, 0x00, 0x30, 0x18, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x18, 0x30, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '*'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x66, 0x3c, 0xff, 0x3c, 0x66, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '+'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x7e, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // ','
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x30, 0x00, 0x00,
+        0x00,
+    ],
+    // '-'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '.'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '/'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x02, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xc0, 0x80, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '0'
+    [
+        0x00, 0x00, 0x38, 0x6c, 0xc6, 0xce, 0xde, 0xf6, 0xe6, 0xc6, 0x6c, 0x38, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '1'
+    [
+        0x00, 0x00, 0x18, 0x38, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '2'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xc0, 0xc6, 0xfe, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '3'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0x06, 0x06, 0x3c, 0x06, 0x06, 0x06, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '4'
+    [
+        0x00, 0x00, 0x0c, 0x1c, 0x3c, 0x6c, 0xcc, 0xfe, 0x0c, 0x0c, 0x0c, 0x1e, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '5'
+    [
+        0x00, 0x00, 0xfe, 0xc0, 0xc0, 0xc0, 0xfc, 0x06, 0x06, 0x06, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '6'
+    [
+        0x00, 0x00, 0x38, 0x60, 0xc0, 0xc0, 0xfc, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '7'
+    [
+        0x00, 0x00, 0xfe, 0xc6, 0x06, 0x0c, 0x18, 0x30, 0x30, 0x30, 0x30, 0x30, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '8'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '9'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0x06, 0x06, 0x0c, 0x78, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // ':'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // ';'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x18, 0x18, 0x30, 0x00, 0x00,
+        0x00,
+    ],
+    // '<'
+    [
+        0x00, 0x00, 0x00, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '='
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '>'
+    [
+        0x00, 0x00, 0x00, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '?'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0xc6, 0x0c, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '@'
+    [
+        0x00, 0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xde, 0xde, 0xde, 0xdc, 0xc0, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'A'
+    [
+        0x00, 0x00, 0x10, 0x38, 0x6c, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'B'
+    [
+        0x00, 0x00, 0xfc, 0x66, 0x66, 0x66, 0x7c, 0x66, 0x66, 0x66, 0x66, 0xfc, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'C'
+    [
+        0x00, 0x00, 0x3c, 0x66, 0xc2, 0xc0, 0xc0, 0xc0, 0xc0, 0xc2, 0x66, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'D'
+    [
+        0x00, 0x00, 0xf8, 0x6c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x6c, 0xf8, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'E'
+    [
+        0x00, 0x00, 0xfe, 0x66, 0x62, 0x68, 0x78, 0x68, 0x60, 0x62, 0x66, 0xfe, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'F'
+    [
+        0x00, 0x00, 0xfe, 0x66, 0x62, 0x68, 0x78, 0x68, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'G'
+    [
+        0x00, 0x00, 0x3c, 0x66, 0xc2, 0xc0, 0xc0, 0xde, 0xc6, 0xc6, 0x66, 0x3a, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'H'
+    [
+        0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'I'
+    [
+        0x00, 0x00, 0x3c, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'J'
+    [
+        0x00, 0x00, 0x1e, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0xcc, 0xcc, 0xcc, 0x78, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'K'
+    [
+        0x00, 0x00, 0xe6, 0x66, 0x6c, 0x6c, 0x78, 0x78, 0x6c, 0x66, 0x66, 0xe6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'L'
+    [
+        0x00, 0x00, 0xf0, 0x60, 0x60, 0x60, 0x60, 0x60, 0x60, 0x62, 0x66, 0xfe, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'M'
+    [
+        0x00, 0x00, 0xc6, 0xee, 0xfe, 0xfe, 0xd6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'N'
+    [
+        0x00, 0x00, 0xc6, 0xe6, 0xf6, 0xfe, 0xde, 0xce, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'O'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'P'
+    [
+        0x00, 0x00, 0xfc, 0x66, 0x66, 0x66, 0x7c, 0x60, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'Q'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xd6, 0xde, 0x7c, 0x0c, 0x0e, 0x00,
+        0x00,
+    ],
+    // 'R'
+    [
+        0x00, 0x00, 0xfc, 0x66, 0x66, 0x66, 0x7c, 0x6c, 0x66, 0x66, 0x66, 0xe6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'S'
+    [
+        0x00, 0x00, 0x7c, 0xc6, 0xc6, 0x60, 0x38, 0x0c, 0x06, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'T'
+    [
+        0x00, 0x00, 0x7e, 0x7e, 0x5a, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'U'
+    [
+        0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'V'
+    [
+        0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x6c, 0x38, 0x10, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'W'
+    [
+        0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xd6, 0xd6, 0xd6, 0xfe, 0x6c, 0x6c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'X'
+    [
+        0x00, 0x00, 0xc6, 0xc6, 0x6c, 0x7c, 0x38, 0x38, 0x7c, 0x6c, 0xc6, 0xc6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'Y'
+    [
+        0x00, 0x00, 0x66, 0x66, 0x66, 0x66, 0x3c, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'Z'
+    [
+        0x00, 0x00, 0xfe, 0xc6, 0x86, 0x0c, 0x18, 0x30, 0x60, 0xc2, 0xc6, 0xfe, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '['
+    [
+        0x00, 0x00, 0x3c, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '\\'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x80, 0xc0, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x02, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // ']'
+    [
+        0x00, 0x00, 0x3c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '^'
+    [
+        0x00, 0x10, 0x38, 0x6c, 0xc6, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '_'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x00,
+        0x00,
+    ],
+    // '`'
+    [
+        0x00, 0x30, 0x30, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'a'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x78, 0x0c, 0x7c, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'b'
+    [
+        0x00, 0x00, 0xe0, 0x60, 0x60, 0x78, 0x6c, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'c'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0xc0, 0xc0, 0xc0, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'd'
+    [
+        0x00, 0x00, 0x1c, 0x0c, 0x0c, 0x3c, 0x6c, 0xcc, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'e'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0xfe, 0xc0, 0xc0, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'f'
+    [
+        0x00, 0x00, 0x38, 0x6c, 0x64, 0x60, 0xf0, 0x60, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'g'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x76, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0x7c, 0x0c, 0xcc, 0x78,
+        0x00,
+    ],
+    // 'h'
+    [
+        0x00, 0x00, 0xe0, 0x60, 0x60, 0x6c, 0x76, 0x66, 0x66, 0x66, 0x66, 0xe6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'i'
+    [
+        0x00, 0x00, 0x18, 0x18, 0x00, 0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'j'
+    [
+        0x00, 0x00, 0x06, 0x06, 0x00, 0x0e, 0x06, 0x06, 0x06, 0x06, 0x06, 0x06, 0x66, 0x66, 0x3c,
+        0x00,
+    ],
+    // 'k'
+    [
+        0x00, 0x00, 0xe0, 0x60, 0x60, 0x66, 0x6c, 0x78, 0x78, 0x6c, 0x66, 0xe6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'l'
+    [
+        0x00, 0x00, 0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'm'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xec, 0xfe, 0xd6, 0xd6, 0xd6, 0xd6, 0xc6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'n'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xdc, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'o'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'p'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xdc, 0x66, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x60, 0x60, 0xf0,
+        0x00,
+    ],
+    // 'q'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x76, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0x7c, 0x0c, 0x0c, 0x1e,
+        0x00,
+    ],
+    // 'r'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xdc, 0x76, 0x66, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 's'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0x60, 0x38, 0x0c, 0xc6, 0x7c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 't'
+    [
+        0x00, 0x00, 0x10, 0x30, 0x30, 0xfc, 0x30, 0x30, 0x30, 0x30, 0x36, 0x1c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'u'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'v'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0x6c, 0x38, 0x10, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'w'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0xc6, 0xd6, 0xd6, 0xd6, 0xfe, 0x6c, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'x'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0x6c, 0x38, 0x38, 0x38, 0x6c, 0xc6, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // 'y'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0x0c, 0xf8,
+        0x00,
+    ],
+    // 'z'
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xfe, 0xcc, 0x18, 0x30, 0x60, 0xc6, 0xfe, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '{'
+    [
+        0x00, 0x00, 0x0e, 0x18, 0x18, 0x18, 0x70, 0x18, 0x18, 0x18, 0x18, 0x0e, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '|'
+    [
+        0x00, 0x00, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x00,
+        0x00,
+    ],
+    // '}'
+    [
+        0x00, 0x00, 0x70, 0x18, 0x18, 0x18, 0x0e, 0x18, 0x18, 0x18, 0x18, 0x70, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+    // '~'
+    [
+        0x00, 0x00, 0x76, 0xdc, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ],
+];
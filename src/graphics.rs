@@ -2,12 +2,21 @@
 //! controller’s frame buffer. The linear address of the hardware frame buffer is also exposed so
 //! software can write directly to the video hardware.
 
-use crate::status::Status;
+#[cfg(feature = "fs")]
+use crate::{bmp, fs::File};
+use crate::{
+    boot::BootServices,
+    boxed::PoolBuffer,
+    guid::{Guid, GRAPHICS_OUTPUT_PROTOCOL_GUID},
+    memory::MemoryType,
+    protocol::Protocol,
+    status::{Error, Status},
+};
 
 /// Represents a pixel when doing a Blt.
 ///
 /// Blt stands for BLock Transfer.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 #[repr(C)]
 pub struct GraphicsBltPixel {
     /// The blue part of the pixel.
@@ -150,16 +159,16 @@ pub struct GraphicsOutputMode {
 pub struct GraphicsOutput {
     /// Returns information for an available graphics mode that the graphics
     /// device and the set of active video output devices supports.
-    pub QueryMode: extern "win64" fn(
+    pub QueryMode: eficall! { fn(
         &mut GraphicsOutput,
         u32,
         &mut usize,
         &mut *mut GraphicsOutputModeInfo,
-    ) -> Status,
+    ) -> Status },
     /// Set the video device into the specified mode and clears the visible portions of the output display to black.
-    pub SetMode: extern "win64" fn(&mut GraphicsOutput, u32) -> Status,
+    pub SetMode: eficall! { fn(&mut GraphicsOutput, u32) -> Status },
     /// Software abstraction to draw on the video device’s frame buffer.
-    pub Blt: extern "win64" fn(
+    pub Blt: eficall! { fn(
         &mut GraphicsOutput,
         *mut GraphicsBltPixel,
         GraphicsBltOp,
@@ -170,7 +179,146 @@ pub struct GraphicsOutput {
         usize,
         usize,
         usize,
-    ) -> Status,
+    ) -> Status },
     /// Reference to EFI_GRAPHICS_OUTPUT_PROTOCOL_MODE data.
     pub Mode: &'static mut GraphicsOutputMode,
 }
+
+unsafe impl Protocol for GraphicsOutput {
+    const GUID: Guid = GRAPHICS_OUTPUT_PROTOCOL_GUID;
+}
+
+impl GraphicsOutput {
+    /// Returns the horizontal and vertical resolution of the current mode.
+    pub fn resolution(&self) -> (usize, usize) {
+        (
+            self.Mode.Info.HorizontalResolution as usize,
+            self.Mode.Info.VerticalResolution as usize,
+        )
+    }
+
+    /// Copies a `width` by `height` rectangle of `buffer` to the screen at (`x`, `y`).
+    ///
+    /// `buffer` holds the rectangle row by row without any padding. Returns
+    /// `Error::BufferTooSmall` if it holds fewer than `width * height` pixels.
+    pub fn buffer_to_video(
+        &mut self,
+        buffer: &[GraphicsBltPixel],
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<(), Error> {
+        if buffer.len() < width.saturating_mul(height) {
+            return Err(Error::BufferTooSmall);
+        }
+
+        // The firmware only reads from the buffer for this operation.
+        (self.Blt)(
+            self,
+            buffer.as_ptr() as *mut GraphicsBltPixel,
+            GraphicsBltOp::BufferToVideo,
+            0,
+            0,
+            x,
+            y,
+            width,
+            height,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Copies a `width` by `height` rectangle of the screen at (`x`, `y`) into `buffer`.
+    ///
+    /// `buffer` receives the rectangle row by row without any padding. Returns
+    /// `Error::BufferTooSmall` if it holds fewer than `width * height` pixels.
+    pub fn video_to_buffer(
+        &mut self,
+        buffer: &mut [GraphicsBltPixel],
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<(), Error> {
+        if buffer.len() < width.saturating_mul(height) {
+            return Err(Error::BufferTooSmall);
+        }
+
+        (self.Blt)(
+            self,
+            buffer.as_mut_ptr(),
+            GraphicsBltOp::VideoToBuffer,
+            x,
+            y,
+            0,
+            0,
+            width,
+            height,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Captures the visible screen into pool memory.
+    pub fn screenshot(&mut self, boot_services: &BootServices) -> Result<Screenshot, Error> {
+        let (width, height) = self.resolution();
+        let len = width.checked_mul(height).ok_or(Error::BadBufferSize)?;
+        let mut pixels = PoolBuffer::new(boot_services, MemoryType::LoaderData, len)?;
+
+        if let Err(error) = self.video_to_buffer(&mut pixels, 0, 0, width, height) {
+            pixels.free(boot_services)?;
+
+            return Err(error);
+        }
+
+        Ok(Screenshot {
+            pixels,
+            width,
+            height,
+        })
+    }
+}
+
+/// The pixels of the screen, as captured by `GraphicsOutput::screenshot`.
+///
+/// The pixels are freed on drop only if `boot::register_global` was called. Prefer calling `free`
+/// explicitly.
+pub struct Screenshot {
+    /// The pixels row by row, starting at the top left corner.
+    pixels: PoolBuffer<GraphicsBltPixel>,
+    /// The width of the screenshot in pixels.
+    width: usize,
+    /// The height of the screenshot in pixels.
+    height: usize,
+}
+
+impl Screenshot {
+    /// Returns the width of the screenshot in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the screenshot in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixels row by row, starting at the top left corner.
+    pub fn pixels(&self) -> &[GraphicsBltPixel] {
+        &self.pixels
+    }
+
+    /// Writes the screenshot as a BMP image at the current position of `file`.
+    #[cfg(feature = "fs")]
+    pub fn write_bmp(&self, file: &mut File) -> Result<(), Error> {
+        bmp::write_bmp(file, self.width, self.height, &self.pixels)
+    }
+
+    /// Frees the pixels.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        self.pixels.free(boot_services)
+    }
+}
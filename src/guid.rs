@@ -1,244 +1,224 @@
-use core::fmt;
-
-pub const NULL_GUID: Guid = Guid(
-    0x00000000,
-    0x0000,
-    0x0000,
-    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
-);
-pub const MPS_TABLE_GUID: Guid = Guid(
-    0xeb9d2d2f,
-    0x2d88,
-    0x11d3,
-    [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
-);
-pub const ACPI_TABLE_GUID: Guid = Guid(
-    0xeb9d2d30,
-    0x2d88,
-    0x11d3,
-    [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
-);
-pub const ACPI_20_TABLE_GUID: Guid = Guid(
-    0x8868e871,
-    0xe4f1,
-    0x11d3,
-    [0xbc, 0x22, 0x00, 0x80, 0xc7, 0x3c, 0x88, 0x81],
-);
-pub const SMBIOS_TABLE_GUID: Guid = Guid(
-    0xeb9d2d31,
-    0x2d88,
-    0x11d3,
-    [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
-);
-pub const SMBIOS3_TABLE_GUID: Guid = Guid(
-    0xf2fd1544,
-    0x9794,
-    0x4a2c,
-    [0x99, 0x2e, 0xe5, 0xbb, 0xcf, 0x20, 0xe3, 0x94],
-);
-pub const SAL_SYSTEM_TABLE_GUID: Guid = Guid(
-    0xeb9d2d32,
-    0x2d88,
-    0x11d3,
-    [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
-);
-pub const HCDP_TABLE_GUID: Guid = Guid(
-    0xf951938d,
-    0x620b,
-    0x42ef,
-    [0x82, 0x79, 0xa8, 0x4b, 0x79, 0x61, 0x78, 0x98],
-);
-pub const UGA_IO_PROTOCOL_GUID: Guid = Guid(
-    0x61a4d49e,
-    0x6f68,
-    0x4f1b,
-    [0xb9, 0x22, 0xa8, 0x6e, 0xed, 0x0b, 0x07, 0xa2],
-);
-pub const SIMPLE_TEXT_OUTPUT_GUID: Guid = Guid(
-    0x387477c2,
-    0x69c7,
-    0x11d2,
-    [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
-);
-pub const GLOBAL_VARIABLE_GUID: Guid = Guid(
-    0x8be4df61,
-    0x93ca,
-    0x11d2,
-    [0xaa, 0x0d, 0x00, 0xe0, 0x98, 0x03, 0x2b, 0x8c],
-);
-pub const UV_SYSTEM_TABLE_GUID: Guid = Guid(
-    0x3b13a7d4,
-    0x633e,
-    0x11dd,
-    [0x93, 0xec, 0xda, 0x25, 0x56, 0xd8, 0x95, 0x93],
-);
-pub const LINUX_EFI_CRASH_GUID: Guid = Guid(
-    0xcfc8fc79,
-    0xbe2e,
-    0x4ddc,
-    [0x97, 0xf0, 0x9f, 0x98, 0xbf, 0xe2, 0x98, 0xa0],
-);
+use core::{fmt, str::FromStr};
+
+/// Parses a GUID in the canonical `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` form at compile time.
+///
+/// Compilation fails if the string is not a valid GUID.
+///
+/// ```ignore
+/// const BLOCK_IO_GUID: Guid = guid!("964e5b21-6459-11d2-8e39-00a0c969723b");
+/// ```
+#[macro_export]
+macro_rules! guid {
+    ($string:expr) => {{
+        const STRING: &str = $string;
+        const _: () = [()][!$crate::guid::is_canonical_guid(STRING) as usize];
+        const GUID: $crate::guid::Guid = $crate::guid::parse_guid_unchecked(STRING);
+
+        GUID
+    }};
+}
+
+/// Returns true if the string is a GUID in the canonical form.
+///
+/// This is used by the `guid!` macro.
+#[doc(hidden)]
+pub const fn is_canonical_guid(string: &str) -> bool {
+    let bytes = string.as_bytes();
+
+    if bytes.len() != 36 {
+        return false;
+    }
+
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let valid = if is_separator_position(index) {
+            bytes[index] == b'-'
+        } else {
+            const_hex_digit(bytes[index]) < 16
+        };
+
+        if !valid {
+            return false;
+        }
+
+        index += 1;
+    }
+
+    true
+}
+
+/// Returns true if a hyphen separates the fields at this position of the canonical form.
+const fn is_separator_position(index: usize) -> bool {
+    index == 8 || index == 13 || index == 18 || index == 23
+}
+
+/// Parses a GUID in the canonical form without validating it.
+///
+/// This is used by the `guid!` macro.
+#[doc(hidden)]
+pub const fn parse_guid_unchecked(string: &str) -> Guid {
+    let bytes = string.as_bytes();
+
+    Guid(
+        parse_hex(bytes, 0, 8) as u32,
+        parse_hex(bytes, 9, 4) as u16,
+        parse_hex(bytes, 14, 4) as u16,
+        [
+            parse_hex(bytes, 19, 2) as u8,
+            parse_hex(bytes, 21, 2) as u8,
+            parse_hex(bytes, 24, 2) as u8,
+            parse_hex(bytes, 26, 2) as u8,
+            parse_hex(bytes, 28, 2) as u8,
+            parse_hex(bytes, 30, 2) as u8,
+            parse_hex(bytes, 32, 2) as u8,
+            parse_hex(bytes, 34, 2) as u8,
+        ],
+    )
+}
+
+/// Parses `len` hexadecimal digits starting at `start`.
+const fn parse_hex(bytes: &[u8], start: usize, len: usize) -> u64 {
+    let mut value = 0;
+    let mut index = start;
+
+    while index < start + len {
+        value = value << 4 | const_hex_digit(bytes[index]) as u64;
+        index += 1;
+    }
+
+    value
+}
+
+/// Returns the value of an ASCII hexadecimal digit or 16 if it is not a hexadecimal digit.
+const fn const_hex_digit(digit: u8) -> u8 {
+    match digit {
+        b'0'...b'9' => digit - b'0',
+        b'a'...b'f' => digit - b'a' + 10,
+        b'A'...b'F' => digit - b'A' + 10,
+        _ => 16,
+    }
+}
+
+pub const NULL_GUID: Guid = guid!("00000000-0000-0000-0000-000000000000");
+pub const MPS_TABLE_GUID: Guid = guid!("eb9d2d2f-2d88-11d3-9a16-0090273fc14d");
+pub const ACPI_TABLE_GUID: Guid = guid!("eb9d2d30-2d88-11d3-9a16-0090273fc14d");
+pub const ACPI_20_TABLE_GUID: Guid = guid!("8868e871-e4f1-11d3-bc22-0080c73c8881");
+pub const SMBIOS_TABLE_GUID: Guid = guid!("eb9d2d31-2d88-11d3-9a16-0090273fc14d");
+pub const SMBIOS3_TABLE_GUID: Guid = guid!("f2fd1544-9794-4a2c-992e-e5bbcf20e394");
+pub const SAL_SYSTEM_TABLE_GUID: Guid = guid!("eb9d2d32-2d88-11d3-9a16-0090273fc14d");
+pub const HCDP_TABLE_GUID: Guid = guid!("f951938d-620b-42ef-8279-a84b79617898");
+pub const UGA_IO_PROTOCOL_GUID: Guid = guid!("61a4d49e-6f68-4f1b-b922-a86eed0b07a2");
+pub const SIMPLE_TEXT_OUTPUT_GUID: Guid = guid!("387477c2-69c7-11d2-8e39-00a0c969723b");
+pub const GLOBAL_VARIABLE_GUID: Guid = guid!("8be4df61-93ca-11d2-aa0d-00e098032b8c");
+pub const UV_SYSTEM_TABLE_GUID: Guid = guid!("3b13a7d4-633e-11dd-93ec-da2556d89593");
+pub const LINUX_EFI_CRASH_GUID: Guid = guid!("cfc8fc79-be2e-4ddc-97f0-9f98bfe298a0");
 /// Can be used on any image handle to obtain information about the loaded image.
-pub const LOADED_IMAGE_PROTOCOL_GUID: Guid = Guid(
-    0x5b1b31a1,
-    0x9562,
-    0x11d2,
-    [0x8e, 0x3f, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
-);
+pub const LOADED_IMAGE_PROTOCOL_GUID: Guid = guid!("5b1b31a1-9562-11d2-8e3f-00a0c969723b");
 /// Provides a basic abstraction to set video modes and copy pixels to and from the graphics
 /// controller’s frame buffer. The linear address of the hardware frame buffer is also exposed so
 /// software can write directly to the video hardware.
-pub const GRAPHICS_OUTPUT_PROTOCOL_GUID: Guid = Guid(
-    0x9042a9de,
-    0x23dc,
-    0x4a38,
-    [0x96, 0xfb, 0x7a, 0xde, 0xd0, 0x80, 0x51, 0x6a],
-);
-pub const UGA_PROTOCOL_GUID: Guid = Guid(
-    0x982c298b,
-    0xf4fa,
-    0x41cb,
-    [0xb8, 0x38, 0x77, 0xaa, 0x68, 0x8f, 0xb8, 0x39],
-);
-pub const PCI_IO_PROTOCOL_GUID: Guid = Guid(
-    0x4cf5b200,
-    0x68b8,
-    0x4ca5,
-    [0x9e, 0xec, 0xb2, 0x3e, 0x3f, 0x50, 0x02, 0x9a],
-);
-pub const FILE_INFO_ID: Guid = Guid(
-    0x09576e92,
-    0x6d3f,
-    0x11d2,
-    [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
-);
-pub const SYSTEM_RESOURCE_TABLE_GUID: Guid = Guid(
-    0xb122a263,
-    0x3661,
-    0x4f68,
-    [0x99, 0x29, 0x78, 0xf8, 0xb0, 0xd6, 0x21, 0x80],
-);
+pub const GRAPHICS_OUTPUT_PROTOCOL_GUID: Guid = guid!("9042a9de-23dc-4a38-96fb-7aded080516a");
+pub const UGA_PROTOCOL_GUID: Guid = guid!("982c298b-f4fa-41cb-b838-77aa688fb839");
+pub const PCI_IO_PROTOCOL_GUID: Guid = guid!("4cf5b200-68b8-4ca5-9eec-b23e3f50029a");
+pub const FILE_INFO_ID: Guid = guid!("09576e92-6d3f-11d2-8e39-00a0c969723b");
+pub const SYSTEM_RESOURCE_TABLE_GUID: Guid = guid!("b122a263-3661-4f68-9929-78f8b0d62180");
 /// This protocol provides control over block devices.
-pub const BLOCK_IO_GUID: Guid = Guid(
-    0x964e5b21,
-    0x6459,
-    0x11d2,
-    [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
-);
-pub const FILE_SYSTEM_GUID: Guid = Guid(
-    0x964e5b22,
-    0x6459,
-    0x11d2,
-    [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
-);
-pub const LOAD_FILE_GUID: Guid = Guid(
-    0x56ec3091,
-    0x954c,
-    0x11d2,
-    [0x8e, 0x3f, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
-);
-pub const DEVICE_PATH_GUID: Guid = Guid(
-    0x09576e91,
-    0x6d3f,
-    0x11d2,
-    [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
-);
-pub const DEVICE_TREE_GUID: Guid = Guid(
-    0xb1b621d5,
-    0xf19c,
-    0x41a5,
-    [0x83, 0x0b, 0xd9, 0x15, 0x2c, 0x69, 0xaa, 0xe0],
-);
-pub const PROPERTIES_TABLE_GUID: Guid = Guid(
-    0x880aaca3,
-    0x4adc,
-    0x4a04,
-    [0x90, 0x79, 0xb7, 0x47, 0x34, 0x08, 0x25, 0xe5],
-);
-pub const RNG_PROTOCOL_GUID: Guid = Guid(
-    0x3152bca5,
-    0xeade,
-    0x433d,
-    [0x86, 0x2e, 0xc0, 0x1c, 0xdc, 0x29, 0x1f, 0x44],
-);
-pub const RNG_ALGORITHM_RAW: Guid = Guid(
-    0xe43176d7,
-    0xb6e8,
-    0x4827,
-    [0xb7, 0x84, 0x7f, 0xfd, 0xc4, 0xb6, 0x85, 0x61],
-);
-pub const MEMORY_ATTRIBUTES_TABLE_GUID: Guid = Guid(
-    0xdcfa911d,
-    0x26eb,
-    0x469f,
-    [0xa2, 0x20, 0x38, 0xb7, 0xdc, 0x46, 0x12, 0x20],
-);
-pub const CONSOLE_OUT_DEVICE_GUID: Guid = Guid(
-    0xd3b36f2c,
-    0xd551,
-    0x11d4,
-    [0x9a, 0x46, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
-);
-pub const SECTION_TIANO_COMPRESS_GUID: Guid = Guid(
-    0xa31280ad,
-    0x481e,
-    0x41b6,
-    [0x95, 0xe8, 0x12, 0x7f, 0x4c, 0x98, 0x47, 0x79],
-);
-pub const SECTION_LZMA_COMPRESS_GUID: Guid = Guid(
-    0xee4e5898,
-    0x3914,
-    0x4259,
-    [0x9d, 0x6e, 0xdc, 0x7b, 0xd7, 0x94, 0x03, 0xcf],
-);
-pub const DXE_SERVICES_TABLE_GUID: Guid = Guid(
-    0x05ad34ba,
-    0x6f02,
-    0x4214,
-    [0x95, 0x2e, 0x4d, 0xa0, 0x39, 0x8e, 0x2b, 0xb9],
-);
-pub const HOB_LIST_GUID: Guid = Guid(
-    0x7739f24c,
-    0x93d7,
-    0x11d4,
-    [0x9a, 0x3a, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
-);
-pub const MEMORY_TYPE_INFORMATION_GUID: Guid = Guid(
-    0x4c19049f,
-    0x4137,
-    0x4dd3,
-    [0x9c, 0x10, 0x8b, 0x97, 0xa8, 0x3f, 0xfd, 0xfa],
-);
-pub const DEBUG_IMAGE_INFO_TABLE_GUID: Guid = Guid(
-    0x49152e77,
-    0x1ada,
-    0x4764,
-    [0xb7, 0xa2, 0x7a, 0xfe, 0xfe, 0xd9, 0x5e, 0x8b],
-);
-pub const SHELL_GUID: Guid = Guid(
-    0x6302d008,
-    0x7f9b,
-    0x4f30,
-    [0x87, 0xac, 0x60, 0xc9, 0xfe, 0xf5, 0xda, 0x4e],
-);
-pub const SHELL_PARAMETERS_GUID: Guid = Guid(
-    0x752f3136,
-    0x4e16,
-    0x4fdc,
-    [0xa2, 0x2a, 0xe5, 0xf4, 0x68, 0x12, 0xf4, 0xca],
-);
+pub const BLOCK_IO_GUID: Guid = guid!("964e5b21-6459-11d2-8e39-00a0c969723b");
+pub const FILE_SYSTEM_GUID: Guid = guid!("964e5b22-6459-11d2-8e39-00a0c969723b");
+pub const LOAD_FILE_GUID: Guid = guid!("56ec3091-954c-11d2-8e3f-00a0c969723b");
+pub const DEVICE_PATH_GUID: Guid = guid!("09576e91-6d3f-11d2-8e39-00a0c969723b");
+pub const DEVICE_TREE_GUID: Guid = guid!("b1b621d5-f19c-41a5-830b-d9152c69aae0");
+pub const PROPERTIES_TABLE_GUID: Guid = guid!("880aaca3-4adc-4a04-9079-b747340825e5");
+pub const RNG_PROTOCOL_GUID: Guid = guid!("3152bca5-eade-433d-862e-c01cdc291f44");
+pub const RNG_ALGORITHM_RAW: Guid = guid!("e43176d7-b6e8-4827-b784-7ffdc4b68561");
+pub const MEMORY_ATTRIBUTES_TABLE_GUID: Guid = guid!("dcfa911d-26eb-469f-a220-38b7dc461220");
+pub const CONSOLE_OUT_DEVICE_GUID: Guid = guid!("d3b36f2c-d551-11d4-9a46-0090273fc14d");
+pub const SECTION_TIANO_COMPRESS_GUID: Guid = guid!("a31280ad-481e-41b6-95e8-127f4c984779");
+pub const SECTION_LZMA_COMPRESS_GUID: Guid = guid!("ee4e5898-3914-4259-9d6e-dc7bd79403cf");
+pub const DXE_SERVICES_TABLE_GUID: Guid = guid!("05ad34ba-6f02-4214-952e-4da0398e2bb9");
+pub const HOB_LIST_GUID: Guid = guid!("7739f24c-93d7-11d4-9a3a-0090273fc14d");
+pub const MEMORY_TYPE_INFORMATION_GUID: Guid = guid!("4c19049f-4137-4dd3-9c10-8b97a83ffdfa");
+pub const DEBUG_IMAGE_INFO_TABLE_GUID: Guid = guid!("49152e77-1ada-4764-b7a2-7afefed95e8b");
+pub const SHELL_GUID: Guid = guid!("6302d008-7f9b-4f30-87ac-60c9fef5da4e");
+pub const SHELL_PARAMETERS_GUID: Guid = guid!("752f3136-4e16-4fdc-a22a-e5f46812f4ca");
 /// Provides services that allow information about a pointer device to be retrieved.
-pub const SIMPLE_POINTER_GUID: Guid = Guid(
-    0x31878c87,
-    0x0b75,
-    0x11d5,
-    [0x9a, 0x4f, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
-);
+pub const SIMPLE_POINTER_GUID: Guid = guid!("31878c87-0b75-11d5-9a4f-0090273fc14d");
+/// Allows consumers to log SMBIOS data records, and enables the producer to create the SMBIOS tables
+/// for a platform.
+pub const SMBIOS_PROTOCOL_GUID: Guid = guid!("03583ff6-cb36-4940-947e-b9b39f4afaf7");
+/// Provides services to retrieve and update the memory attributes of memory regions.
+pub const MEMORY_ATTRIBUTE_PROTOCOL_GUID: Guid = guid!("f4560cf6-40ec-4b4a-a192-bf1d57d0b189");
+/// Provides services that allow management of labels contained in a Label Storage Area.
+pub const NVDIMM_LABEL_PROTOCOL_GUID: Guid = guid!("d40b6b80-97d5-4282-bb1d-223a16918058");
+/// Returns information about images whose load was denied because of the user authentication policy.
+pub const DEFERRED_IMAGE_LOAD_PROTOCOL_GUID: Guid = guid!("15853d7c-3ddf-43e0-a1cb-ebf85b8f872c");
+/// Manages user profiles.
+pub const USER_MANAGER_PROTOCOL_GUID: Guid = guid!("6fd5b00c-d426-4283-9887-6cf5cf1cb1fe");
+/// Provides services to communicate with a REST service.
+pub const REST_EX_PROTOCOL_GUID: Guid = guid!("55648b91-0e7d-40a3-a9b3-a815d7eadf97");
+/// Used to create and destroy instances of the REST EX protocol.
+pub const REST_EX_SERVICE_BINDING_PROTOCOL_GUID: Guid =
+    guid!("456bbe01-99d0-45ea-bb5f-16d84bedc559");
+/// Provides management service interfaces for 802.11 connections.
+pub const WIRELESS_MAC_CONNECTION_II_PROTOCOL_GUID: Guid =
+    guid!("1b0fb9bf-699d-4fdd-a7c3-2546681bf63b");
+/// Provides services to process authentication and data encryption/decryption for security management.
+pub const SUPPLICANT_PROTOCOL_GUID: Guid = guid!("54fcc43e-aa89-4333-9a85-cdea24051e9e");
+/// Used to create and destroy instances of the supplicant protocol.
+pub const SUPPLICANT_SERVICE_BINDING_PROTOCOL_GUID: Guid =
+    guid!("45bcd98e-59ad-4174-9546-344a07485898");
+/// Abstracts the Bluetooth host controller layer message transmit and receive.
+pub const BLUETOOTH_HC_PROTOCOL_GUID: Guid = guid!("b3930571-beba-4fc5-9203-9427242e6a43");
+/// Abstracts user interface configuration for a Bluetooth device.
+pub const BLUETOOTH_CONFIG_PROTOCOL_GUID: Guid = guid!("62960cf3-40ff-4263-a77c-dfdebd191b4b");
+/// Provides a platform specific override mechanism for the selection of the best driver for a given
+/// controller.
+pub const PLATFORM_DRIVER_OVERRIDE_PROTOCOL_GUID: Guid =
+    guid!("6b30c738-a391-11d4-9a3b-0090273fc14d");
+/// Provides a bus specific override mechanism for the selection of the best driver for a given
+/// controller.
+pub const BUS_SPECIFIC_DRIVER_OVERRIDE_PROTOCOL_GUID: Guid =
+    guid!("3bc1b285-8a15-4a82-aabf-4d7d13fb3265");
+/// Provides information about the version of the UEFI specification a driver is compliant with.
+pub const DRIVER_SUPPORTED_EFI_VERSION_PROTOCOL_GUID: Guid =
+    guid!("5c198761-16a8-4e69-972c-89d67954f81d");
+/// The GUID of the extended simple text input protocol.
+pub const SIMPLE_TEXT_INPUT_EX_GUID: Guid = guid!("dd9e7534-7762-4698-8c14-f58517a625aa");
+/// The GUID of the simple text input protocol.
+pub const SIMPLE_TEXT_INPUT_GUID: Guid = guid!("387477c1-69c7-11d2-8e39-00a0c969723b");
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 #[repr(C)]
 pub struct Guid(pub u32, pub u16, pub u16, pub [u8; 8]);
 
+/// Describes why a string could not be parsed as a GUID.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParseGuidError {
+    /// The string is not 36 characters long.
+    InvalidLength,
+    /// A hyphen is missing or misplaced.
+    InvalidSeparator,
+    /// A character that is not a hexadecimal digit was found at the given position.
+    InvalidDigit(usize),
+}
+
+impl fmt::Display for ParseGuidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseGuidError::InvalidLength => write!(f, "a GUID must be 36 characters long"),
+            ParseGuidError::InvalidSeparator => write!(f, "misplaced or missing hyphen in GUID"),
+            ParseGuidError::InvalidDigit(position) => {
+                write!(
+                    f,
+                    "invalid hexadecimal digit at position {} in GUID",
+                    position
+                )
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum GuidKind {
     Null,
@@ -285,10 +265,78 @@ pub enum GuidKind {
     ShellParameters,
     /// Provides services that allow information about a pointer device to be retrieved.
     SimplePointer,
+    /// Allows consumers to log SMBIOS data records, and enables the producer to create the SMBIOS tables
+    /// for a platform.
+    SmbiosProtocol,
+    /// Provides services to retrieve and update the memory attributes of memory regions.
+    MemoryAttribute,
+    /// Provides services that allow management of labels contained in a Label Storage Area.
+    NvdimmLabel,
+    /// Returns information about images whose load was denied because of the user authentication policy.
+    DeferredImageLoad,
+    /// Manages user profiles.
+    UserManager,
+    /// Provides services to communicate with a REST service.
+    RestEx,
+    /// Used to create and destroy instances of the REST EX protocol.
+    RestExServiceBinding,
+    /// Provides management service interfaces for 802.11 connections.
+    WirelessMacConnectionII,
+    /// Provides services to process authentication and data encryption/decryption for security management.
+    Supplicant,
+    /// Used to create and destroy instances of the supplicant protocol.
+    SupplicantServiceBinding,
+    /// Abstracts the Bluetooth host controller layer message transmit and receive.
+    BluetoothHc,
+    /// Abstracts user interface configuration for a Bluetooth device.
+    BluetoothConfig,
+    /// Provides a platform specific override mechanism for the selection of the best driver for a given
+    /// controller.
+    PlatformDriverOverride,
+    /// Provides a bus specific override mechanism for the selection of the best driver for a given
+    /// controller.
+    BusSpecificDriverOverride,
+    /// Provides information about the version of the UEFI specification a driver is compliant with.
+    DriverSupportedEfiVersion,
+    /// The GUID of the extended simple text input protocol.
+    SimpleTextInputEx,
+    /// The GUID of the simple text input protocol.
+    SimpleTextInput,
     Unknown,
 }
 
 impl Guid {
+    /// Creates a GUID from its binary representation in memory.
+    ///
+    /// The first three fields are stored in little endian byte order, as specified in
+    /// Appendix A of the UEFI specification.
+    pub fn from_bytes(bytes: [u8; 16]) -> Guid {
+        let mut data4 = [0; 8];
+        data4.copy_from_slice(&bytes[8..]);
+
+        Guid(
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u16::from_le_bytes([bytes[4], bytes[5]]),
+            u16::from_le_bytes([bytes[6], bytes[7]]),
+            data4,
+        )
+    }
+
+    /// Returns the binary representation of the GUID in memory.
+    ///
+    /// The first three fields are stored in little endian byte order, as specified in
+    /// Appendix A of the UEFI specification.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+
+        bytes[0..4].copy_from_slice(&self.0.to_le_bytes());
+        bytes[4..6].copy_from_slice(&self.1.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.2.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.3);
+
+        bytes
+    }
+
     pub fn kind(&self) -> GuidKind {
         match *self {
             NULL_GUID => GuidKind::Null,
@@ -329,21 +377,108 @@ impl Guid {
             SHELL_GUID => GuidKind::Shell,
             SHELL_PARAMETERS_GUID => GuidKind::ShellParameters,
             SIMPLE_POINTER_GUID => GuidKind::SimplePointer,
+            SMBIOS_PROTOCOL_GUID => GuidKind::SmbiosProtocol,
+            MEMORY_ATTRIBUTE_PROTOCOL_GUID => GuidKind::MemoryAttribute,
+            NVDIMM_LABEL_PROTOCOL_GUID => GuidKind::NvdimmLabel,
+            DEFERRED_IMAGE_LOAD_PROTOCOL_GUID => GuidKind::DeferredImageLoad,
+            USER_MANAGER_PROTOCOL_GUID => GuidKind::UserManager,
+            REST_EX_PROTOCOL_GUID => GuidKind::RestEx,
+            REST_EX_SERVICE_BINDING_PROTOCOL_GUID => GuidKind::RestExServiceBinding,
+            WIRELESS_MAC_CONNECTION_II_PROTOCOL_GUID => GuidKind::WirelessMacConnectionII,
+            SUPPLICANT_PROTOCOL_GUID => GuidKind::Supplicant,
+            SUPPLICANT_SERVICE_BINDING_PROTOCOL_GUID => GuidKind::SupplicantServiceBinding,
+            BLUETOOTH_HC_PROTOCOL_GUID => GuidKind::BluetoothHc,
+            BLUETOOTH_CONFIG_PROTOCOL_GUID => GuidKind::BluetoothConfig,
+            PLATFORM_DRIVER_OVERRIDE_PROTOCOL_GUID => GuidKind::PlatformDriverOverride,
+            BUS_SPECIFIC_DRIVER_OVERRIDE_PROTOCOL_GUID => GuidKind::BusSpecificDriverOverride,
+            DRIVER_SUPPORTED_EFI_VERSION_PROTOCOL_GUID => GuidKind::DriverSupportedEfiVersion,
+            SIMPLE_TEXT_INPUT_EX_GUID => GuidKind::SimpleTextInputEx,
+            SIMPLE_TEXT_INPUT_GUID => GuidKind::SimpleTextInput,
             _ => GuidKind::Unknown,
         }
     }
 }
 
 impl fmt::Display for Guid {
+    /// Formats the GUID in the canonical `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` form.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({:>08x}, {:>04x}, {:>04x}, [", self.0, self.1, self.2)?;
-        for (i, b) in self.3.iter().enumerate() {
-            if i > 0 {
-                write!(f, ",")?;
-            }
-            write!(f, "{:>02x}", b)?;
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-",
+            self.0, self.1, self.2, self.3[0], self.3[1]
+        )?;
+        for b in &self.3[2..] {
+            write!(f, "{:02x}", b)?;
         }
-        write!(f, "])")?;
         Ok(())
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Guid {
+    /// Formats the GUID in the canonical `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` form.
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{=u32:08x}-{=u16:04x}-{=u16:04x}-{=u8:02x}{=u8:02x}-{=u8:02x}{=u8:02x}{=u8:02x}{=u8:02x}{=u8:02x}{=u8:02x}",
+            self.0,
+            self.1,
+            self.2,
+            self.3[0],
+            self.3[1],
+            self.3[2],
+            self.3[3],
+            self.3[4],
+            self.3[5],
+            self.3[6],
+            self.3[7]
+        );
+    }
+}
+
+impl fmt::Debug for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Guid({})", self)
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<r_efi::efi::Guid> for Guid {
+    fn from(guid: r_efi::efi::Guid) -> Guid {
+        Guid::from_bytes(*guid.as_bytes())
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<Guid> for r_efi::efi::Guid {
+    fn from(guid: Guid) -> r_efi::efi::Guid {
+        r_efi::efi::Guid::from_bytes(&guid.to_bytes())
+    }
+}
+
+impl FromStr for Guid {
+    type Err = ParseGuidError;
+
+    /// Parses a GUID in the canonical `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` form.
+    ///
+    /// Both upper and lower case hexadecimal digits are accepted.
+    fn from_str(s: &str) -> Result<Guid, ParseGuidError> {
+        let bytes = s.as_bytes();
+
+        if bytes.len() != 36 {
+            return Err(ParseGuidError::InvalidLength);
+        }
+
+        for (position, &byte) in bytes.iter().enumerate() {
+            if is_separator_position(position) {
+                if byte != b'-' {
+                    return Err(ParseGuidError::InvalidSeparator);
+                }
+            } else if const_hex_digit(byte) >= 16 {
+                return Err(ParseGuidError::InvalidDigit(position));
+            }
+        }
+
+        Ok(parse_guid_unchecked(s))
+    }
+}
@@ -0,0 +1,475 @@
+//! A catalog of the well-known GUIDs defined by the UEFI specification.
+//!
+//! The GUIDs are grouped by what they identify. GUIDs that are already defined in the `guid`
+//! module are re-exported here, so both paths refer to the same constant.
+
+pub mod protocol {
+    //! GUIDs identifying protocols.
+
+    use crate::{guid, guid::Guid};
+
+    /// Loaded image protocol, installed on every image handle.
+    pub use crate::guid::LOADED_IMAGE_PROTOCOL_GUID as LOADED_IMAGE;
+    /// Loaded image device path protocol, the device path the image was loaded from.
+    pub const LOADED_IMAGE_DEVICE_PATH: Guid = guid!("bc62157e-3e33-4fec-9920-2d3b36d750df");
+    /// Device path protocol.
+    pub use crate::guid::DEVICE_PATH_GUID as DEVICE_PATH;
+    /// Device path utilities protocol.
+    pub const DEVICE_PATH_UTILITIES: Guid = guid!("0379be4e-d706-437d-b037-edb82fb772a4");
+    /// Device path to text protocol.
+    pub const DEVICE_PATH_TO_TEXT: Guid = guid!("8b843e20-8132-4852-90cc-551a4e4a7f1c");
+    /// Device path from text protocol.
+    pub const DEVICE_PATH_FROM_TEXT: Guid = guid!("05c99a21-c70f-4ad2-8a5f-35df3343f51e");
+    /// Driver binding protocol.
+    pub const DRIVER_BINDING: Guid = guid!("18a031ab-b443-4d1a-a5c0-0c09261e9f71");
+    /// Bus specific driver override protocol.
+    pub use crate::guid::BUS_SPECIFIC_DRIVER_OVERRIDE_PROTOCOL_GUID as BUS_SPECIFIC_DRIVER_OVERRIDE;
+    /// Platform driver override protocol.
+    pub use crate::guid::PLATFORM_DRIVER_OVERRIDE_PROTOCOL_GUID as PLATFORM_DRIVER_OVERRIDE;
+    /// Driver diagnostics 2 protocol.
+    pub const DRIVER_DIAGNOSTICS2: Guid = guid!("4d330321-025f-4aac-90d8-5ed900173b63");
+    /// Component name 2 protocol.
+    pub const COMPONENT_NAME2: Guid = guid!("6a7a5cff-e8d9-4f70-bada-75ab3025ce14");
+    /// Driver health protocol.
+    pub const DRIVER_HEALTH: Guid = guid!("2a534210-9280-41d8-ae79-cada01a2b127");
+    /// Driver family override protocol.
+    pub const DRIVER_FAMILY_OVERRIDE: Guid = guid!("b1ee129e-da36-4181-91f8-04a4923766a7");
+    /// Driver supported EFI version protocol.
+    pub use crate::guid::DRIVER_SUPPORTED_EFI_VERSION_PROTOCOL_GUID as DRIVER_SUPPORTED_EFI_VERSION;
+    /// Adapter information protocol.
+    pub const ADAPTER_INFORMATION: Guid = guid!("e5dd1403-d622-c24e-8488-c71b17f5e802");
+    /// Simple pointer protocol.
+    pub use crate::guid::SIMPLE_POINTER_GUID as SIMPLE_POINTER;
+    /// Extended simple text input protocol.
+    pub use crate::guid::SIMPLE_TEXT_INPUT_EX_GUID as SIMPLE_TEXT_INPUT_EX;
+    /// Simple text input protocol.
+    pub use crate::guid::SIMPLE_TEXT_INPUT_GUID as SIMPLE_TEXT_INPUT;
+    /// Simple text output protocol.
+    pub use crate::guid::SIMPLE_TEXT_OUTPUT_GUID as SIMPLE_TEXT_OUTPUT;
+    /// Absolute pointer protocol.
+    pub const ABSOLUTE_POINTER: Guid = guid!("8d59d32b-c655-4ae9-9b15-f25904992a43");
+    /// Serial I/O protocol.
+    pub const SERIAL_IO: Guid = guid!("bb25cf6f-f1d4-11d2-9a0c-0090273fc1fd");
+    /// Graphics output protocol.
+    pub use crate::guid::GRAPHICS_OUTPUT_PROTOCOL_GUID as GRAPHICS_OUTPUT;
+    /// EDID discovered protocol.
+    pub const EDID_DISCOVERED: Guid = guid!("1c0c34f6-d380-41fa-a049-8ad06c1a66aa");
+    /// EDID active protocol.
+    pub const EDID_ACTIVE: Guid = guid!("bd8c1056-9f36-44ec-92a8-a6337f817986");
+    /// Load file protocol.
+    pub use crate::guid::LOAD_FILE_GUID as LOAD_FILE;
+    /// Universal graphics adapter I/O protocol.
+    pub use crate::guid::UGA_IO_PROTOCOL_GUID as UGA_IO;
+    /// Universal graphics adapter draw protocol, superseded by the graphics output protocol.
+    pub use crate::guid::UGA_PROTOCOL_GUID as UGA_DRAW;
+    /// Load file 2 protocol, which is not used for booting.
+    pub const LOAD_FILE2: Guid = guid!("4006c0c1-fcb3-403e-996d-4a6c8724e06d");
+    /// Simple file system protocol.
+    pub use crate::guid::FILE_SYSTEM_GUID as SIMPLE_FILE_SYSTEM;
+    /// Disk I/O protocol.
+    pub const DISK_IO: Guid = guid!("ce345171-ba0b-11d2-8e4f-00a0c969723b");
+    /// Disk I/O 2 protocol.
+    pub const DISK_IO2: Guid = guid!("151c8eae-7f2c-472c-9e54-9828194f6a88");
+    /// Block I/O protocol.
+    pub use crate::guid::BLOCK_IO_GUID as BLOCK_IO;
+    /// Block I/O 2 protocol.
+    pub const BLOCK_IO2: Guid = guid!("a77b2472-e282-4e9f-a245-c2c0e27bbcc1");
+    /// Erase block protocol.
+    pub const ERASE_BLOCK: Guid = guid!("95a9a93e-a86e-4926-aaef-9918e772d987");
+    /// Partition information protocol.
+    pub const PARTITION_INFO: Guid = guid!("8cf2f62c-bc9b-4821-808d-ec9ec421a1a0");
+    /// RAM disk protocol.
+    pub const RAM_DISK: Guid = guid!("ab38a0df-6873-44a9-87e6-d4eb56148449");
+    /// Storage security command protocol.
+    pub const STORAGE_SECURITY_COMMAND: Guid = guid!("c88b0b6d-0dfc-49a7-9cb4-49074b4c3a78");
+    /// ATA pass thru protocol.
+    pub const ATA_PASS_THRU: Guid = guid!("1d3de7f0-0807-424f-aa69-11a54e19a46f");
+    /// Extended SCSI pass thru protocol.
+    pub const EXT_SCSI_PASS_THRU: Guid = guid!("143b7632-b81b-4cb7-abd3-b625a5b9bffe");
+    /// SCSI I/O protocol.
+    pub const SCSI_IO: Guid = guid!("932f47e6-2362-4002-803e-3cd54b138f85");
+    /// NVM Express pass thru protocol.
+    pub const NVM_EXPRESS_PASS_THRU: Guid = guid!("52c78312-8edc-4233-98f2-1a1aa5e388a5");
+    /// SD/MMC pass thru protocol.
+    pub const SD_MMC_PASS_THRU: Guid = guid!("716ef0d9-ff83-4f69-81e9-518bd39a8e70");
+    /// PCI root bridge I/O protocol.
+    pub const PCI_ROOT_BRIDGE_IO: Guid = guid!("2f707ebb-4a1a-11d4-9a38-0090273fc14d");
+    /// PCI I/O protocol.
+    pub use crate::guid::PCI_IO_PROTOCOL_GUID as PCI_IO;
+    /// USB I/O protocol.
+    pub const USB_IO: Guid = guid!("2b2f68d6-0cd2-44cf-8e8b-bba20b1b5b75");
+    /// USB 2 host controller protocol.
+    pub const USB2_HC: Guid = guid!("3e745226-9818-45b6-a2ac-d7cd0e8ba2bc");
+    /// Simple network protocol.
+    pub const SIMPLE_NETWORK: Guid = guid!("a19832b9-ac25-11d3-9a2d-0090273fc14d");
+    /// PXE base code protocol.
+    pub const PXE_BASE_CODE: Guid = guid!("03c4e603-ac28-11d3-9a2d-0090273fc14d");
+    /// PXE base code callback protocol.
+    pub const PXE_BASE_CODE_CALLBACK: Guid = guid!("245dca21-fb7b-11d3-8f01-00a0c969723b");
+    /// Managed network protocol.
+    pub const MANAGED_NETWORK: Guid = guid!("7ab33a91-ace5-4326-b572-e7ee33d39f16");
+    /// ARP protocol.
+    pub const ARP: Guid = guid!("f4b427bb-ba21-4f16-bc4e-43e416ab619c");
+    /// ARP service binding protocol.
+    pub const ARP_SERVICE_BINDING: Guid = guid!("f44c00ee-1f2c-4a00-aa09-1c9f3e0800a3");
+    /// IPv4 protocol.
+    pub const IP4: Guid = guid!("41d94cd2-35b6-455a-8258-d4e51334aadd");
+    /// IPv4 service binding protocol.
+    pub const IP4_SERVICE_BINDING: Guid = guid!("c51711e7-b4bf-404a-bfb8-0a048ef1ffe4");
+    /// IPv4 configuration 2 protocol.
+    pub const IP4_CONFIG2: Guid = guid!("5b446ed1-e30b-4faa-871a-3654eca36080");
+    /// IPv6 protocol.
+    pub const IP6: Guid = guid!("2c8759d5-5c2d-66ef-925f-b66c101957e2");
+    /// IPv6 service binding protocol.
+    pub const IP6_SERVICE_BINDING: Guid = guid!("ec835dd3-fe0f-617b-a621-b350c3e13388");
+    /// TCPv4 protocol.
+    pub const TCP4: Guid = guid!("65530bc7-a359-410f-b010-5aadc7ec2b62");
+    /// TCPv4 service binding protocol.
+    pub const TCP4_SERVICE_BINDING: Guid = guid!("00720665-67eb-4a99-baf7-d3c33a1c7cc9");
+    /// TCPv6 protocol.
+    pub const TCP6: Guid = guid!("46e44855-bd60-4ab7-ab0d-a679b9447d77");
+    /// TCPv6 service binding protocol.
+    pub const TCP6_SERVICE_BINDING: Guid = guid!("ec20eb79-6c1a-4664-9a0d-d2e4cc16d664");
+    /// UDPv4 protocol.
+    pub const UDP4: Guid = guid!("3ad9df29-4501-478d-b1f8-7f7fe70e50f3");
+    /// UDPv4 service binding protocol.
+    pub const UDP4_SERVICE_BINDING: Guid = guid!("83f01464-99bd-45e5-b383-af6305d8e9e6");
+    /// UDPv6 protocol.
+    pub const UDP6: Guid = guid!("4f948815-b4b9-43cb-8a33-90e060b34955");
+    /// UDPv6 service binding protocol.
+    pub const UDP6_SERVICE_BINDING: Guid = guid!("66ed4721-3c98-4d3e-81e3-d03dd39a7254");
+    /// DHCPv4 protocol.
+    pub const DHCP4: Guid = guid!("8a219718-4ef5-4761-91c8-c0f04bda9e56");
+    /// DHCPv4 service binding protocol.
+    pub const DHCP4_SERVICE_BINDING: Guid = guid!("9d9a39d8-bd42-4a73-a4d5-8ee94be11380");
+    /// DHCPv6 protocol.
+    pub const DHCP6: Guid = guid!("87c8bad7-0595-4053-8297-dede395f5d5b");
+    /// DHCPv6 service binding protocol.
+    pub const DHCP6_SERVICE_BINDING: Guid = guid!("9fb9a8a1-2f4a-43a6-889c-d0f7b6c47ad5");
+    /// MTFTPv4 protocol.
+    pub const MTFTP4: Guid = guid!("78247c57-63db-4708-99c2-a8b4a9a61f6b");
+    /// MTFTPv4 service binding protocol.
+    pub const MTFTP4_SERVICE_BINDING: Guid = guid!("2fe800be-8f01-4aa6-946b-d71388e1833f");
+    /// HTTP protocol.
+    pub const HTTP: Guid = guid!("7a59b29b-910b-4171-8242-a85a0df25b5b");
+    /// HTTP service binding protocol.
+    pub const HTTP_SERVICE_BINDING: Guid = guid!("bdc8e6af-d9bc-4379-a72a-e0c4e75dae1c");
+    /// Bluetooth configuration protocol.
+    pub use crate::guid::BLUETOOTH_CONFIG_PROTOCOL_GUID as BLUETOOTH_CONFIG;
+    /// Bluetooth host controller protocol.
+    pub use crate::guid::BLUETOOTH_HC_PROTOCOL_GUID as BLUETOOTH_HC;
+    /// REST EX protocol.
+    pub use crate::guid::REST_EX_PROTOCOL_GUID as REST_EX;
+    /// REST EX service binding protocol.
+    pub use crate::guid::REST_EX_SERVICE_BINDING_PROTOCOL_GUID as REST_EX_SERVICE_BINDING;
+    /// Random number generator protocol.
+    pub use crate::guid::RNG_PROTOCOL_GUID as RNG;
+    /// Supplicant protocol.
+    pub use crate::guid::SUPPLICANT_PROTOCOL_GUID as SUPPLICANT;
+    /// Supplicant service binding protocol.
+    pub use crate::guid::SUPPLICANT_SERVICE_BINDING_PROTOCOL_GUID as SUPPLICANT_SERVICE_BINDING;
+    /// Wireless MAC connection II protocol.
+    pub use crate::guid::WIRELESS_MAC_CONNECTION_II_PROTOCOL_GUID as WIRELESS_MAC_CONNECTION_II;
+    /// Hash protocol.
+    pub const HASH: Guid = guid!("c5184932-dba5-46db-a5ba-cc0bda9c1435");
+    /// Hash 2 protocol.
+    pub const HASH2: Guid = guid!("55b1d734-c5e1-49db-9647-b16afb0e305b");
+    /// PKCS7 verify protocol.
+    pub const PKCS7_VERIFY: Guid = guid!("47889fb2-d671-4fab-a0ca-df0e44df70d6");
+    /// TCG2 protocol for TPM 2.0 measurements.
+    pub const TCG2: Guid = guid!("607f766c-7455-42be-930b-e4d76db2720f");
+    /// Timestamp protocol.
+    pub const TIMESTAMP: Guid = guid!("afbfde41-2e6e-4262-ba65-62b9236e5495");
+    /// Multiprocessor services protocol, defined in the Platform Initialization specification.
+    pub const MP_SERVICES: Guid = guid!("3fdda605-a76e-4f46-ad29-12f4531b3d08");
+    /// Deferred image load protocol.
+    pub use crate::guid::DEFERRED_IMAGE_LOAD_PROTOCOL_GUID as DEFERRED_IMAGE_LOAD;
+    /// Memory attribute protocol.
+    pub use crate::guid::MEMORY_ATTRIBUTE_PROTOCOL_GUID as MEMORY_ATTRIBUTE;
+    /// NVDIMM label protocol.
+    pub use crate::guid::NVDIMM_LABEL_PROTOCOL_GUID as NVDIMM_LABEL;
+    /// SMBIOS protocol, defined in the Platform Initialization specification.
+    pub use crate::guid::SMBIOS_PROTOCOL_GUID as SMBIOS;
+    /// User manager protocol.
+    pub use crate::guid::USER_MANAGER_PROTOCOL_GUID as USER_MANAGER;
+    /// Firmware management protocol.
+    pub const FIRMWARE_MANAGEMENT: Guid = guid!("86c77a67-0b97-4633-a187-49104d0685c7");
+    /// Boot manager policy protocol.
+    pub const BOOT_MANAGER_POLICY: Guid = guid!("fedf8e0c-e147-11e3-9903-b8e8562cbafa");
+    /// Reset notification protocol.
+    pub const RESET_NOTIFICATION: Guid = guid!("9da34ae0-eaf9-4bbf-8ec3-fd60226c44be");
+    /// Unicode collation 2 protocol.
+    pub const UNICODE_COLLATION2: Guid = guid!("a4c751fc-23ae-4c3e-92e9-4964cf63f349");
+    /// Decompress protocol.
+    pub const DECOMPRESS: Guid = guid!("d8117cfe-94a6-11d4-9a3a-0090273fc14d");
+    /// EFI byte code protocol.
+    pub const EBC: Guid = guid!("13ac6dd1-73d0-11d4-b06b-00aa00bd6de7");
+    /// HII database protocol.
+    pub const HII_DATABASE: Guid = guid!("ef9fc172-a1b2-4693-b327-6d32fc416042");
+    /// HII string protocol.
+    pub const HII_STRING: Guid = guid!("0fd96974-23aa-4cdc-b9cb-98d17750322a");
+    /// HII font protocol.
+    pub const HII_FONT: Guid = guid!("e9ca4775-8657-47fc-97e7-7ed65a084324");
+    /// HII configuration routing protocol.
+    pub const HII_CONFIG_ROUTING: Guid = guid!("587e72d7-cc50-4f79-8209-ca291fc1a10f");
+    /// Form browser 2 protocol.
+    pub const FORM_BROWSER2: Guid = guid!("b9d4c360-bcfb-4f9b-9298-53c136982258");
+    /// Shell protocol.
+    pub use crate::guid::SHELL_GUID as SHELL;
+    /// Shell parameters protocol, installed on images started by the shell.
+    pub use crate::guid::SHELL_PARAMETERS_GUID as SHELL_PARAMETERS;
+
+    /// Returns the name of the constant of a known protocol GUID, such as `"LOADED_IMAGE"`.
+    pub fn name(guid: &Guid) -> Option<&'static str> {
+        NAMES
+            .iter()
+            .find(|(known, _)| *known == guid)
+            .map(|&(_, name)| name)
+    }
+
+    /// The known protocol GUIDs and the names of their constants.
+    const NAMES: &[(&Guid, &str)] = &[
+        (&LOADED_IMAGE, "LOADED_IMAGE"),
+        (&LOADED_IMAGE_DEVICE_PATH, "LOADED_IMAGE_DEVICE_PATH"),
+        (&DEVICE_PATH, "DEVICE_PATH"),
+        (&DEVICE_PATH_UTILITIES, "DEVICE_PATH_UTILITIES"),
+        (&DEVICE_PATH_TO_TEXT, "DEVICE_PATH_TO_TEXT"),
+        (&DEVICE_PATH_FROM_TEXT, "DEVICE_PATH_FROM_TEXT"),
+        (&DRIVER_BINDING, "DRIVER_BINDING"),
+        (
+            &BUS_SPECIFIC_DRIVER_OVERRIDE,
+            "BUS_SPECIFIC_DRIVER_OVERRIDE",
+        ),
+        (&PLATFORM_DRIVER_OVERRIDE, "PLATFORM_DRIVER_OVERRIDE"),
+        (&DRIVER_DIAGNOSTICS2, "DRIVER_DIAGNOSTICS2"),
+        (&COMPONENT_NAME2, "COMPONENT_NAME2"),
+        (&DRIVER_HEALTH, "DRIVER_HEALTH"),
+        (&DRIVER_FAMILY_OVERRIDE, "DRIVER_FAMILY_OVERRIDE"),
+        (
+            &DRIVER_SUPPORTED_EFI_VERSION,
+            "DRIVER_SUPPORTED_EFI_VERSION",
+        ),
+        (&ADAPTER_INFORMATION, "ADAPTER_INFORMATION"),
+        (&SIMPLE_POINTER, "SIMPLE_POINTER"),
+        (&SIMPLE_TEXT_INPUT_EX, "SIMPLE_TEXT_INPUT_EX"),
+        (&SIMPLE_TEXT_INPUT, "SIMPLE_TEXT_INPUT"),
+        (&SIMPLE_TEXT_OUTPUT, "SIMPLE_TEXT_OUTPUT"),
+        (&ABSOLUTE_POINTER, "ABSOLUTE_POINTER"),
+        (&SERIAL_IO, "SERIAL_IO"),
+        (&GRAPHICS_OUTPUT, "GRAPHICS_OUTPUT"),
+        (&EDID_DISCOVERED, "EDID_DISCOVERED"),
+        (&EDID_ACTIVE, "EDID_ACTIVE"),
+        (&LOAD_FILE, "LOAD_FILE"),
+        (&UGA_IO, "UGA_IO"),
+        (&UGA_DRAW, "UGA_DRAW"),
+        (&LOAD_FILE2, "LOAD_FILE2"),
+        (&SIMPLE_FILE_SYSTEM, "SIMPLE_FILE_SYSTEM"),
+        (&DISK_IO, "DISK_IO"),
+        (&DISK_IO2, "DISK_IO2"),
+        (&BLOCK_IO, "BLOCK_IO"),
+        (&BLOCK_IO2, "BLOCK_IO2"),
+        (&ERASE_BLOCK, "ERASE_BLOCK"),
+        (&PARTITION_INFO, "PARTITION_INFO"),
+        (&RAM_DISK, "RAM_DISK"),
+        (&STORAGE_SECURITY_COMMAND, "STORAGE_SECURITY_COMMAND"),
+        (&ATA_PASS_THRU, "ATA_PASS_THRU"),
+        (&EXT_SCSI_PASS_THRU, "EXT_SCSI_PASS_THRU"),
+        (&SCSI_IO, "SCSI_IO"),
+        (&NVM_EXPRESS_PASS_THRU, "NVM_EXPRESS_PASS_THRU"),
+        (&SD_MMC_PASS_THRU, "SD_MMC_PASS_THRU"),
+        (&PCI_ROOT_BRIDGE_IO, "PCI_ROOT_BRIDGE_IO"),
+        (&PCI_IO, "PCI_IO"),
+        (&USB_IO, "USB_IO"),
+        (&USB2_HC, "USB2_HC"),
+        (&SIMPLE_NETWORK, "SIMPLE_NETWORK"),
+        (&PXE_BASE_CODE, "PXE_BASE_CODE"),
+        (&PXE_BASE_CODE_CALLBACK, "PXE_BASE_CODE_CALLBACK"),
+        (&MANAGED_NETWORK, "MANAGED_NETWORK"),
+        (&ARP, "ARP"),
+        (&ARP_SERVICE_BINDING, "ARP_SERVICE_BINDING"),
+        (&IP4, "IP4"),
+        (&IP4_SERVICE_BINDING, "IP4_SERVICE_BINDING"),
+        (&IP4_CONFIG2, "IP4_CONFIG2"),
+        (&IP6, "IP6"),
+        (&IP6_SERVICE_BINDING, "IP6_SERVICE_BINDING"),
+        (&TCP4, "TCP4"),
+        (&TCP4_SERVICE_BINDING, "TCP4_SERVICE_BINDING"),
+        (&TCP6, "TCP6"),
+        (&TCP6_SERVICE_BINDING, "TCP6_SERVICE_BINDING"),
+        (&UDP4, "UDP4"),
+        (&UDP4_SERVICE_BINDING, "UDP4_SERVICE_BINDING"),
+        (&UDP6, "UDP6"),
+        (&UDP6_SERVICE_BINDING, "UDP6_SERVICE_BINDING"),
+        (&DHCP4, "DHCP4"),
+        (&DHCP4_SERVICE_BINDING, "DHCP4_SERVICE_BINDING"),
+        (&DHCP6, "DHCP6"),
+        (&DHCP6_SERVICE_BINDING, "DHCP6_SERVICE_BINDING"),
+        (&MTFTP4, "MTFTP4"),
+        (&MTFTP4_SERVICE_BINDING, "MTFTP4_SERVICE_BINDING"),
+        (&HTTP, "HTTP"),
+        (&HTTP_SERVICE_BINDING, "HTTP_SERVICE_BINDING"),
+        (&BLUETOOTH_CONFIG, "BLUETOOTH_CONFIG"),
+        (&BLUETOOTH_HC, "BLUETOOTH_HC"),
+        (&REST_EX, "REST_EX"),
+        (&REST_EX_SERVICE_BINDING, "REST_EX_SERVICE_BINDING"),
+        (&RNG, "RNG"),
+        (&SUPPLICANT, "SUPPLICANT"),
+        (&SUPPLICANT_SERVICE_BINDING, "SUPPLICANT_SERVICE_BINDING"),
+        (&WIRELESS_MAC_CONNECTION_II, "WIRELESS_MAC_CONNECTION_II"),
+        (&HASH, "HASH"),
+        (&HASH2, "HASH2"),
+        (&PKCS7_VERIFY, "PKCS7_VERIFY"),
+        (&TCG2, "TCG2"),
+        (&TIMESTAMP, "TIMESTAMP"),
+        (&MP_SERVICES, "MP_SERVICES"),
+        (&DEFERRED_IMAGE_LOAD, "DEFERRED_IMAGE_LOAD"),
+        (&MEMORY_ATTRIBUTE, "MEMORY_ATTRIBUTE"),
+        (&NVDIMM_LABEL, "NVDIMM_LABEL"),
+        (&SMBIOS, "SMBIOS"),
+        (&USER_MANAGER, "USER_MANAGER"),
+        (&FIRMWARE_MANAGEMENT, "FIRMWARE_MANAGEMENT"),
+        (&BOOT_MANAGER_POLICY, "BOOT_MANAGER_POLICY"),
+        (&RESET_NOTIFICATION, "RESET_NOTIFICATION"),
+        (&UNICODE_COLLATION2, "UNICODE_COLLATION2"),
+        (&DECOMPRESS, "DECOMPRESS"),
+        (&EBC, "EBC"),
+        (&HII_DATABASE, "HII_DATABASE"),
+        (&HII_STRING, "HII_STRING"),
+        (&HII_FONT, "HII_FONT"),
+        (&HII_CONFIG_ROUTING, "HII_CONFIG_ROUTING"),
+        (&FORM_BROWSER2, "FORM_BROWSER2"),
+        (&SHELL, "SHELL"),
+        (&SHELL_PARAMETERS, "SHELL_PARAMETERS"),
+    ];
+}
+
+pub mod table {
+    //! GUIDs identifying configuration tables in the system table.
+
+    use crate::{guid, guid::Guid};
+
+    /// ACPI 2.0 or later RSDP.
+    pub use crate::guid::ACPI_20_TABLE_GUID as ACPI_20;
+    /// ACPI 1.0 RSDP.
+    pub use crate::guid::ACPI_TABLE_GUID as ACPI;
+    /// Flattened device tree blob.
+    pub use crate::guid::DEVICE_TREE_GUID as DEVICE_TREE;
+    /// Memory attributes table describing runtime memory protections.
+    pub use crate::guid::MEMORY_ATTRIBUTES_TABLE_GUID as MEMORY_ATTRIBUTES;
+    /// MPS table.
+    pub use crate::guid::MPS_TABLE_GUID as MPS;
+    /// EFI properties table, deprecated in favor of the memory attributes table.
+    pub use crate::guid::PROPERTIES_TABLE_GUID as PROPERTIES;
+    /// SAL system table.
+    pub use crate::guid::SAL_SYSTEM_TABLE_GUID as SAL_SYSTEM;
+    /// SMBIOS 3.x entry point structure.
+    pub use crate::guid::SMBIOS3_TABLE_GUID as SMBIOS3;
+    /// SMBIOS 2.x entry point structure.
+    pub use crate::guid::SMBIOS_TABLE_GUID as SMBIOS;
+    /// Runtime properties table listing the runtime services supported after `ExitBootServices`.
+    pub const RT_PROPERTIES: Guid = guid!("eb66918a-7eef-402a-842e-931d21c38ae9");
+    /// Debug image information table.
+    pub use crate::guid::DEBUG_IMAGE_INFO_TABLE_GUID as DEBUG_IMAGE_INFO;
+    /// EFI system resource table (ESRT).
+    pub use crate::guid::SYSTEM_RESOURCE_TABLE_GUID as SYSTEM_RESOURCE;
+    /// Image execution information table, also the vendor GUID of the secure boot databases.
+    pub const IMAGE_SECURITY_DATABASE: Guid = guid!("d719b2cb-3d3a-4596-a3bc-dad00e67656f");
+    /// JSON configuration data table.
+    pub const JSON_CONFIG_DATA: Guid = guid!("87367f87-1119-41ce-aaec-8be01101f558");
+    /// JSON capsule data table.
+    pub const JSON_CAPSULE_DATA: Guid = guid!("35e7a725-8dd2-4cac-8011-33cda8109056");
+    /// JSON capsule result table.
+    pub const JSON_CAPSULE_RESULT: Guid = guid!("dbc461c3-b3de-422a-b9b4-9886fd49a1e5");
+    /// Conformance profiles table.
+    pub const CONFORMANCE_PROFILES: Guid = guid!("36122546-f7e7-4c8f-bd9b-eb8525b50c0b");
+    /// TCG2 final events table, containing the events logged after `GetEventLog`.
+    pub const TCG2_FINAL_EVENTS: Guid = guid!("1e2ed096-30e2-4254-bd89-863bbef82325");
+    /// DXE services table, defined in the Platform Initialization specification.
+    pub use crate::guid::DXE_SERVICES_TABLE_GUID as DXE_SERVICES;
+    /// Headless console and debug port table.
+    pub use crate::guid::HCDP_TABLE_GUID as HCDP;
+    /// HOB list, defined in the Platform Initialization specification.
+    pub use crate::guid::HOB_LIST_GUID as HOB_LIST;
+    /// Linux crash information table.
+    pub use crate::guid::LINUX_EFI_CRASH_GUID as LINUX_EFI_CRASH;
+    /// Memory type information, defined by EDK II.
+    pub use crate::guid::MEMORY_TYPE_INFORMATION_GUID as MEMORY_TYPE_INFORMATION;
+    /// SGI UV system table.
+    pub use crate::guid::UV_SYSTEM_TABLE_GUID as UV_SYSTEM;
+}
+
+pub mod event_group {
+    //! GUIDs identifying event groups for `CreateEventEx`.
+
+    use crate::{guid, guid::Guid};
+
+    /// Signaled when `ExitBootServices` is called.
+    pub const EXIT_BOOT_SERVICES: Guid = guid!("27abf055-b1b8-4c26-8048-748f37baa2df");
+    /// Signaled at the start of `ExitBootServices`, before the exit boot services event group.
+    pub const BEFORE_EXIT_BOOT_SERVICES: Guid = guid!("8be0e274-3970-4b44-80c5-1ab9502f3bfc");
+    /// Signaled when `SetVirtualAddressMap` is called.
+    pub const VIRTUAL_ADDRESS_CHANGE: Guid = guid!("13fa7698-c831-49c7-87ea-8f43fcc25196");
+    /// Signaled when the memory map changes.
+    pub const MEMORY_MAP_CHANGE: Guid = guid!("78bee926-692f-48fd-9edb-01422ef0d7ab");
+    /// Signaled by the boot manager right before it attempts to boot a boot option.
+    pub const READY_TO_BOOT: Guid = guid!("7ce88fb3-4bd7-4679-87a8-a8d8dee50d2b");
+    /// Signaled by the boot manager right after the ready to boot event group.
+    pub const AFTER_READY_TO_BOOT: Guid = guid!("3a2a00ad-98b9-4cdf-a478-702777f1c10b");
+    /// Signaled when `ResetSystem` is called, before the system is reset.
+    pub const RESET_SYSTEM: Guid = guid!("62da6a56-13fb-485a-a8da-a3dd7912cb6b");
+}
+
+pub mod vendor {
+    //! Vendor GUIDs of variables.
+
+    use crate::{guid, guid::Guid};
+
+    /// Architecturally defined variables such as `BootOrder` and `SecureBoot`.
+    pub use crate::guid::GLOBAL_VARIABLE_GUID as GLOBAL_VARIABLE;
+    /// The secure boot signature databases `db`, `dbx`, `dbt` and `dbr`.
+    pub const IMAGE_SECURITY_DATABASE: Guid = guid!("d719b2cb-3d3a-4596-a3bc-dad00e67656f");
+    /// Hardware error records.
+    pub const HARDWARE_ERROR: Guid = guid!("414e6bdd-e47b-47cc-b244-bb61020cf516");
+    /// Variables used by shim, such as `MokList`.
+    pub const SHIM_LOCK: Guid = guid!("605dab50-e046-4300-abb6-3dd810dd8b23");
+    /// The boot loader interface variables used by systemd-boot.
+    pub const LOADER: Guid = guid!("4a67b082-0a4c-41cf-b6c7-440b29bb8c4f");
+    /// The `CapsuleXXXX` variables reporting the results of capsule updates.
+    pub const CAPSULE_REPORT: Guid = guid!("39b68c46-f7fb-441b-b6ec-16b0f69821f3");
+}
+
+pub mod media {
+    //! GUIDs of vendor defined media device path nodes.
+
+    use crate::{guid, guid::Guid};
+
+    /// The device path of the initial ramdisk that Linux loads with the load file 2 protocol.
+    pub const LINUX_INITRD: Guid = guid!("5568e427-68fc-4f3d-ac74-ca555231cc68");
+}
+
+pub mod signature {
+    //! GUIDs identifying the type of the signatures in an `EFI_SIGNATURE_LIST`.
+
+    use crate::{guid, guid::Guid};
+
+    /// A SHA-1 hash.
+    pub const CERT_SHA1: Guid = guid!("826ca512-cf10-4ac9-b187-be01496631bd");
+    /// A SHA-224 hash.
+    pub const CERT_SHA224: Guid = guid!("0b6e5233-a65c-44c9-9407-d9ab83bfc8bd");
+    /// A SHA-256 hash.
+    pub const CERT_SHA256: Guid = guid!("c1c41626-504c-4092-aca9-41f936934328");
+    /// A SHA-384 hash.
+    pub const CERT_SHA384: Guid = guid!("ff3e5307-9fd0-48c9-85f1-8ad56c701e01");
+    /// A SHA-512 hash.
+    pub const CERT_SHA512: Guid = guid!("093e0fae-a6c4-4f50-9f1b-d41e2b89c19a");
+    /// An RSA-2048 public key.
+    pub const CERT_RSA2048: Guid = guid!("3c5766e8-269c-4e34-aa14-ed776e85b3b6");
+    /// A DER encoded X.509 certificate.
+    pub const CERT_X509: Guid = guid!("a5c059a1-94e4-4aa7-87b5-ab155c2bf072");
+    /// The SHA-256 hash of the to be signed part of an X.509 certificate and a revocation time.
+    pub const CERT_X509_SHA256: Guid = guid!("3bd2a492-96c0-4079-b420-fcf98ef103ed");
+    /// The SHA-384 hash of the to be signed part of an X.509 certificate and a revocation time.
+    pub const CERT_X509_SHA384: Guid = guid!("7076876e-80c2-4ee6-aad2-28b349a6865b");
+    /// The SHA-512 hash of the to be signed part of an X.509 certificate and a revocation time.
+    pub const CERT_X509_SHA512: Guid = guid!("446dbf63-2502-4cda-bcfa-2465d2b0fe9d");
+}
@@ -0,0 +1,454 @@
+//! Passes the boot information to a kernel in a single versioned structure.
+//!
+//! `Handoff` collects the information a kernel needs, exits boot services and places a `BootInfo`
+//! together with the command line into `LoaderData` pages. The final memory map stays in the
+//! `LoaderData` pages it was retrieved into while exiting boot services, so nothing can fail once
+//! boot services are gone. The kernel receives the physical address of the structure and reads it
+//! with `BootInfo::from_address`.
+//!
+//! All addresses in the structure are physical addresses. The pages holding the structure and the
+//! memory map are reported as `LoaderData` in the memory map, so the kernel has to keep them until
+//! it has consumed the information, like the pages of the kernel itself.
+
+use core::{mem::size_of, ops::Range, ptr, slice, str};
+
+#[cfg(feature = "graphics")]
+use crate::graphics::{GraphicsOutput, GraphicsPixelFormat};
+use crate::{
+    boot::{AllocateType, BootServices, ExitBootServices},
+    guid::{ACPI_20_TABLE_GUID, ACPI_TABLE_GUID},
+    memory::{MemoryMapIterator, MemoryType, PhysicalAddress, PAGE_SIZE},
+    memtest::BadMemory,
+    runtime::RuntimeServices,
+    status::{Error, ErrorExt, NuefilError},
+    system::SystemTable,
+    Handle,
+};
+
+/// The magic number at the start of a `BootInfo`, `NUEFILHO` in ASCII.
+pub const HANDOFF_MAGIC: u64 = 0x4f48_4c49_4645_554e;
+/// The version of `BootInfo` written by this crate.
+///
+/// Later versions only append fields, so a kernel can read structures with a newer version.
+pub const HANDOFF_VERSION: u32 = 1;
+/// The default number of additional descriptors the memory map has room for.
+pub const DEFAULT_HANDOFF_SLACK: usize = 32;
+
+/// The pixel format of a frame buffer with 32 bit pixels described by `HandoffFramebuffer`.
+pub const HANDOFF_PIXEL_BITMASK: u32 = 1;
+
+/// The frame buffer passed to the kernel.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct HandoffFramebuffer {
+    /// The physical address of the top left pixel, or zero if there is no frame buffer.
+    pub Base: u64,
+    /// The size of the frame buffer in bytes.
+    pub Size: u64,
+    /// The number of visible pixels in a row.
+    pub Width: u32,
+    /// The number of rows.
+    pub Height: u32,
+    /// The number of pixels between the starts of two rows.
+    pub Stride: u32,
+    /// `HANDOFF_PIXEL_BITMASK`, which is the only format so far.
+    pub PixelFormat: u32,
+    /// The bits of a pixel holding the red component.
+    pub RedMask: u32,
+    /// The bits of a pixel holding the green component.
+    pub GreenMask: u32,
+    /// The bits of a pixel holding the blue component.
+    pub BlueMask: u32,
+    /// The bits of a pixel that are not used.
+    pub ReservedMask: u32,
+}
+
+#[cfg(feature = "graphics")]
+impl HandoffFramebuffer {
+    /// Describes the frame buffer of the current mode of the graphics output protocol.
+    ///
+    /// Returns `Error::Unsupported` if the mode has no linear frame buffer.
+    pub fn from_gop(graphics_output: &GraphicsOutput) -> Result<HandoffFramebuffer, Error> {
+        let mode = &graphics_output.Mode;
+        let info = mode.Info;
+
+        let (red, green, blue, reserved) = match info.PixelFormat {
+            GraphicsPixelFormat::PixelRedGreenBlueReserved8BitPerColor => {
+                (0x0000_00ff, 0x0000_ff00, 0x00ff_0000, 0xff00_0000)
+            }
+            GraphicsPixelFormat::PixelBlueGreenRedReserved8BitPerColor => {
+                (0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000)
+            }
+            GraphicsPixelFormat::PixelBitMask => {
+                let masks = info.PixelInformation;
+
+                (
+                    masks.RedMask,
+                    masks.GreenMask,
+                    masks.BlueMask,
+                    masks.ReservedMask,
+                )
+            }
+            _ => return Err(Error::Unsupported),
+        };
+
+        Ok(HandoffFramebuffer {
+            Base: mode.FrameBufferBase as u64,
+            Size: mode.FrameBufferSize as u64,
+            Width: info.HorizontalResolution,
+            Height: info.VerticalResolution,
+            Stride: info.PixelsPerScanLine,
+            PixelFormat: HANDOFF_PIXEL_BITMASK,
+            RedMask: red,
+            GreenMask: green,
+            BlueMask: blue,
+            ReservedMask: reserved,
+        })
+    }
+}
+
+/// The boot information passed to the kernel.
+///
+/// Missing information is represented by zero addresses and sizes.
+#[derive(Debug)]
+#[repr(C)]
+pub struct BootInfo {
+    /// `HANDOFF_MAGIC`.
+    pub Magic: u64,
+    /// The version of the structure.
+    pub Version: u32,
+    /// The size of the structure including the command line after it.
+    pub Size: u32,
+    /// The physical address of the memory map.
+    pub MemoryMap: u64,
+    /// The size of the memory map in bytes.
+    pub MemoryMapSize: u64,
+    /// The size of a memory descriptor in bytes.
+    pub MemoryDescriptorSize: u32,
+    /// The version of the memory descriptors.
+    pub MemoryDescriptorVersion: u32,
+    /// The frame buffer.
+    pub Framebuffer: HandoffFramebuffer,
+    /// The physical address of the ACPI RSDP.
+    pub Rsdp: u64,
+    /// The physical address of the system table.
+    pub SystemTable: u64,
+    /// The physical address of the runtime services.
+    pub RuntimeServices: u64,
+    /// The physical address of the UTF-8 command line, which is followed by a null byte.
+    pub CommandLine: u64,
+    /// The size of the command line in bytes, without the null byte.
+    pub CommandLineSize: u64,
+    /// The physical address of the initial ramdisk.
+    pub InitrdBase: u64,
+    /// The size of the initial ramdisk in bytes.
+    pub InitrdSize: u64,
+}
+
+impl BootInfo {
+    /// Validates the boot information at `address`.
+    ///
+    /// Returns `Error::Unsupported` if the magic number is wrong and `Error::BadBufferSize` if
+    /// the structure is smaller than this version of it.
+    ///
+    /// # Safety
+    /// `address` must point to boot information written by `Handoff::exit`, or to memory
+    /// that is at least as large as `BootInfo`.
+    pub unsafe fn from_address(address: u64) -> Result<&'static BootInfo, Error> {
+        let info = &*(address as usize as *const BootInfo);
+
+        if info.Magic != HANDOFF_MAGIC || info.Version == 0 {
+            return Err(Error::Unsupported);
+        }
+        if (info.Size as usize) < size_of::<BootInfo>() {
+            return Err(Error::BadBufferSize);
+        }
+
+        Ok(info)
+    }
+
+    /// Returns the memory map at the time boot services were exited.
+    ///
+    /// Boot services memory is reported as conventional memory.
+    pub fn memory_map(&self) -> Result<MemoryMapIterator<'static>, Error> {
+        // This is safe, because the memory map is part of the boot information.
+        let buffer = unsafe {
+            slice::from_raw_parts(
+                self.MemoryMap as usize as *const u8,
+                self.MemoryMapSize as usize,
+            )
+        };
+
+        MemoryMapIterator::from_bytes(
+            buffer,
+            self.MemoryDescriptorSize as usize,
+            self.MemoryDescriptorVersion,
+        )
+    }
+
+    /// Returns the command line, if there is one.
+    ///
+    /// Returns `None` if the command line is not valid UTF-8.
+    pub fn command_line(&self) -> Option<&'static str> {
+        if self.CommandLine == 0 {
+            return None;
+        }
+
+        // This is safe, because the command line is part of the boot information.
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                self.CommandLine as usize as *const u8,
+                self.CommandLineSize as usize,
+            )
+        };
+
+        str::from_utf8(bytes).ok()
+    }
+
+    /// Returns the frame buffer, if there is one.
+    pub fn framebuffer(&self) -> Option<&HandoffFramebuffer> {
+        if self.Framebuffer.Base == 0 {
+            None
+        } else {
+            Some(&self.Framebuffer)
+        }
+    }
+
+    /// Returns the physical address of the ACPI RSDP, if there is one.
+    pub fn rsdp(&self) -> Option<u64> {
+        if self.Rsdp == 0 {
+            None
+        } else {
+            Some(self.Rsdp)
+        }
+    }
+
+    /// Returns the initial ramdisk, if there is one.
+    pub fn initrd(&self) -> Option<&'static [u8]> {
+        if self.InitrdBase == 0 {
+            return None;
+        }
+
+        // This is safe under the assumption that the loader passed a valid initial ramdisk.
+        Some(unsafe {
+            slice::from_raw_parts(
+                self.InitrdBase as usize as *const u8,
+                self.InitrdSize as usize,
+            )
+        })
+    }
+
+    /// Returns the runtime services, which are only usable while memory is identity mapped or
+    /// after `SetVirtualAddressMap` was called.
+    pub fn runtime_services(&self) -> Option<&'static RuntimeServices> {
+        if self.RuntimeServices == 0 {
+            return None;
+        }
+
+        // This is safe under the assumption that the firmware supplied a valid pointer.
+        Some(unsafe { &*(self.RuntimeServices as usize as *const RuntimeServices) })
+    }
+}
+
+/// Collects the boot information and places it in memory when boot services are exited.
+pub struct Handoff<'a> {
+    /// The command line of the kernel.
+    command_line: &'a str,
+    /// The frame buffer.
+    framebuffer: Option<HandoffFramebuffer>,
+    /// The physical address of the RSDP, if it was set explicitly.
+    rsdp: Option<u64>,
+    /// The physical address and size of the initial ramdisk.
+    initrd: Option<(u64, u64)>,
+    /// The pages that are reported as unusable.
+    unusable: Option<&'a BadMemory>,
+    /// The number of additional descriptors the memory map has room for.
+    slack: usize,
+}
+
+impl<'a> Default for Handoff<'a> {
+    fn default() -> Handoff<'a> {
+        Handoff::new()
+    }
+}
+
+impl<'a> Handoff<'a> {
+    /// Creates a handoff with an empty command line and without frame buffer or initial
+    /// ramdisk.
+    pub fn new() -> Handoff<'a> {
+        Handoff {
+            command_line: "",
+            framebuffer: None,
+            rsdp: None,
+            initrd: None,
+            unusable: None,
+            slack: DEFAULT_HANDOFF_SLACK,
+        }
+    }
+
+    /// Sets the command line of the kernel.
+    pub fn command_line(mut self, command_line: &'a str) -> Handoff<'a> {
+        self.command_line = command_line;
+        self
+    }
+
+    /// Sets the frame buffer.
+    pub fn framebuffer(mut self, framebuffer: HandoffFramebuffer) -> Handoff<'a> {
+        self.framebuffer = Some(framebuffer);
+        self
+    }
+
+    /// Sets the physical address of the RSDP.
+    ///
+    /// By default, the ACPI 2.0 table of the configuration table is used, or the ACPI 1.0
+    /// table if there is none.
+    pub fn rsdp(mut self, rsdp: u64) -> Handoff<'a> {
+        self.rsdp = Some(rsdp);
+        self
+    }
+
+    /// Sets the initial ramdisk.
+    ///
+    /// The memory holding it must not be freed before boot services are exited, so it is
+    /// usually allocated as `LoaderData` pages.
+    pub fn initrd(mut self, base: u64, size: u64) -> Handoff<'a> {
+        self.initrd = Some((base, size));
+        self
+    }
+
+    /// Reports the given pages as `UnusableMemory` in the memory map.
+    ///
+    /// The pages are allocated as `UnusableMemory` before boot services are exited, so they must
+    /// be free, which is the case for pages that were found by `memtest::run`.
+    pub fn unusable(mut self, bad_memory: &'a BadMemory) -> Handoff<'a> {
+        self.unusable = Some(bad_memory);
+        self
+    }
+
+    /// Sets the number of additional descriptors the memory map has room for.
+    ///
+    /// The buffer of the memory map is allocated before boot services are exited, while the
+    /// final memory map can contain more descriptors. The default is `DEFAULT_HANDOFF_SLACK`.
+    pub fn slack(mut self, slack: usize) -> Handoff<'a> {
+        self.slack = slack;
+        self
+    }
+
+    /// Allocates the boot information, exits boot services and fills it in.
+    ///
+    /// Returns the boot information, whose address is passed to the kernel. The returned error
+    /// records whether allocating, getting the memory map or `ExitBootServices` failed, in which
+    /// case the boot information and the unusable pages are freed again. Nothing fails after boot
+    /// services were exited.
+    pub fn exit(
+        self,
+        system_table: &'static SystemTable,
+        image_handle: Handle,
+    ) -> Result<&'static BootInfo, NuefilError> {
+        let boot_services = &*system_table.BootServices;
+
+        let command_line_offset = size_of::<BootInfo>();
+        let size = command_line_offset + self.command_line.len() + 1;
+
+        if size > u32::max_value() as usize {
+            return Err(NuefilError::new("AllocatePages", Error::OutOfResources));
+        }
+
+        let pages = (size + PAGE_SIZE - 1) / PAGE_SIZE;
+        let base = boot_services
+            .allocate_pages(MemoryType::LoaderData, pages)
+            .context_with("AllocatePages", "Pages", pages)? as *mut u8;
+
+        let rsdp = self.rsdp.or_else(|| {
+            system_table
+                .find_config_table(&ACPI_20_TABLE_GUID)
+                .or_else(|| system_table.find_config_table(&ACPI_TABLE_GUID))
+                .map(|table| table.VendorTable as u64)
+        });
+        let (initrd_base, initrd_size) = self.initrd.unwrap_or((0, 0));
+
+        // This is safe, because the pages were just allocated with room for the contents.
+        let command_line = unsafe {
+            ptr::write_bytes(base, 0, size);
+
+            let command_line = base.add(command_line_offset);
+            ptr::copy_nonoverlapping(
+                self.command_line.as_ptr(),
+                command_line,
+                self.command_line.len(),
+            );
+
+            command_line
+        };
+
+        // The bad pages are allocated last, so that they are only held while exiting and can be
+        // allocated again if exiting fails and is retried.
+        let ranges = self.unusable.map(BadMemory::ranges).unwrap_or(&[]);
+        for (index, range) in ranges.iter().enumerate() {
+            let address = PhysicalAddress(range.start * PAGE_SIZE as u64);
+
+            if let Err(error) = boot_services
+                .allocate_pages_with(
+                    AllocateType::Address(address),
+                    MemoryType::UnusableMemory,
+                    (range.end - range.start) as usize,
+                )
+                .context_with("AllocatePages", "Address", address.0 as usize)
+            {
+                free_unusable(boot_services, &ranges[..index]);
+                let _ = boot_services.free_pages(base, pages);
+                return Err(error);
+            }
+        }
+
+        // The memory map is left in its `LoaderData` pages, which the kernel receives.
+        let view = match ExitBootServices::new(system_table, image_handle)
+            .memory_type(MemoryType::LoaderData)
+            .slack(self.slack)
+            .exit()
+        {
+            Ok(view) => view,
+            Err(error) => {
+                free_unusable(boot_services, ranges);
+                let _ = boot_services.free_pages(base, pages);
+                return Err(error);
+            }
+        };
+        let memory_map = &view.memory_map;
+
+        let info = BootInfo {
+            Magic: HANDOFF_MAGIC,
+            Version: HANDOFF_VERSION,
+            Size: size as u32,
+            MemoryMap: memory_map.buffer as u64,
+            MemoryMapSize: memory_map.size as u64,
+            MemoryDescriptorSize: memory_map.descriptor_size() as u32,
+            MemoryDescriptorVersion: memory_map.descriptor_version(),
+            Framebuffer: self.framebuffer.unwrap_or_default(),
+            Rsdp: rsdp.unwrap_or(0),
+            SystemTable: system_table as *const SystemTable as u64,
+            RuntimeServices: view.runtime_services as *const RuntimeServices as u64,
+            CommandLine: command_line as u64,
+            CommandLineSize: self.command_line.len() as u64,
+            InitrdBase: initrd_base,
+            InitrdSize: initrd_size,
+        };
+
+        // This is safe, because the pages are aligned and large enough for the structure.
+        unsafe {
+            ptr::write(base as *mut BootInfo, info);
+
+            Ok(&*(base as *const BootInfo))
+        }
+    }
+}
+
+/// Frees the bad pages of `ranges`, which were allocated as `UnusableMemory`.
+fn free_unusable(boot_services: &BootServices, ranges: &[Range<u64>]) {
+    for range in ranges {
+        let _ = boot_services.free_pages(
+            (range.start * PAGE_SIZE as u64) as usize as *const u8,
+            (range.end - range.start) as usize,
+        );
+    }
+}
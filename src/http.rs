@@ -0,0 +1,215 @@
+//! Defines the HTTP message types that are shared by the HTTP based network protocols.
+
+use core::{ptr, slice};
+
+/// The HTTP version of a request or response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum HttpVersion {
+    /// HTTP/1.0
+    HttpVersion10,
+    /// HTTP/1.1
+    HttpVersion11,
+    /// The HTTP version is not supported.
+    HttpVersionUnsupported,
+}
+
+/// The HTTP method of a request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Patch,
+    Options,
+    Connect,
+    Head,
+    Put,
+    Delete,
+    Trace,
+    Max,
+}
+
+/// The status code of an HTTP response.
+///
+/// The value is an index into the list of status codes defined by UEFI and not the numeric HTTP
+/// status code itself. Use `code()` to get the numeric HTTP status code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct HttpStatusCode(pub u32);
+
+/// The numeric HTTP status codes in the order they are defined in EFI_HTTP_STATUS_CODE.
+const HTTP_STATUS_CODES: [u16; 42] = [
+    0, 100, 101, 200, 201, 202, 203, 204, 205, 206, 300, 301, 302, 303, 304, 305, 307, 400, 401,
+    402, 403, 404, 405, 406, 407, 408, 409, 410, 411, 412, 413, 414, 415, 416, 417, 500, 501, 502,
+    503, 504, 505, 308,
+];
+
+impl HttpStatusCode {
+    /// Returns the numeric HTTP status code, if it is known.
+    pub fn code(self) -> Option<u16> {
+        match HTTP_STATUS_CODES.get(self.0 as usize) {
+            Some(0) | None => None,
+            Some(&code) => Some(code),
+        }
+    }
+
+    /// Creates the status code from a numeric HTTP status code.
+    ///
+    /// Returns `None` if UEFI does not define the status code.
+    pub fn from_code(code: u16) -> Option<HttpStatusCode> {
+        if code == 0 {
+            return None;
+        }
+
+        HTTP_STATUS_CODES
+            .iter()
+            .position(|&known_code| known_code == code)
+            .map(|index| HttpStatusCode(index as u32))
+    }
+}
+
+/// The request line of an HTTP request.
+#[derive(Debug)]
+#[repr(C)]
+pub struct HttpRequestData {
+    /// The HTTP method of the request.
+    pub Method: HttpMethod,
+    /// The null terminated URI of the request, either absolute or relative to the configured
+    /// server.
+    pub Url: *const u16,
+}
+
+/// The status line of an HTTP response.
+#[derive(Debug)]
+#[repr(C)]
+pub struct HttpResponseData {
+    /// The status code of the response.
+    pub StatusCode: HttpStatusCode,
+}
+
+/// A single HTTP header.
+#[derive(Debug)]
+#[repr(C)]
+pub struct HttpHeader {
+    /// The null terminated ASCII name of the header field.
+    pub FieldName: *const u8,
+    /// The null terminated ASCII value of the header field.
+    pub FieldValue: *const u8,
+}
+
+/// Either the request data or the response data of an HTTP message.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union HttpMessageData {
+    /// The request line of a request.
+    pub Request: *const HttpRequestData,
+    /// The status line of a response.
+    pub Response: *mut HttpResponseData,
+}
+
+/// An HTTP request or response.
+#[repr(C)]
+pub struct HttpMessage {
+    /// The request or response data, depending on the direction of the message.
+    pub Data: HttpMessageData,
+    /// The number of headers in `Headers`.
+    pub HeaderCount: usize,
+    /// An array of `HeaderCount` headers.
+    pub Headers: *mut HttpHeader,
+    /// The length of `Body` in bytes.
+    pub BodyLength: usize,
+    /// The body of the message.
+    pub Body: *mut u8,
+}
+
+impl HttpMessage {
+    /// Creates a request message with the given request data, headers and body.
+    pub fn request(request: &HttpRequestData, headers: &mut [HttpHeader], body: &mut [u8]) -> Self {
+        HttpMessage {
+            Data: HttpMessageData { Request: request },
+            HeaderCount: headers.len(),
+            Headers: headers.as_mut_ptr(),
+            BodyLength: body.len(),
+            Body: body.as_mut_ptr(),
+        }
+    }
+
+    /// Creates an empty response message that receives the status line into `response`.
+    pub fn response(response: &mut HttpResponseData) -> Self {
+        HttpMessage {
+            Data: HttpMessageData { Response: response },
+            HeaderCount: 0,
+            Headers: ptr::null_mut(),
+            BodyLength: 0,
+            Body: ptr::null_mut(),
+        }
+    }
+
+    /// Returns the headers of the message.
+    pub fn headers(&self) -> &[HttpHeader] {
+        if self.Headers.is_null() {
+            &[]
+        } else {
+            // This is safe under the assumption, that the message contains valid values.
+            unsafe { slice::from_raw_parts(self.Headers, self.HeaderCount) }
+        }
+    }
+
+    /// Returns the body of the message.
+    pub fn body(&self) -> &[u8] {
+        if self.Body.is_null() {
+            &[]
+        } else {
+            // This is safe under the assumption, that the message contains valid values.
+            unsafe { slice::from_raw_parts(self.Body, self.BodyLength) }
+        }
+    }
+}
+
+/// The local IPv4 address configuration of an HTTP instance.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct HttpV4AccessPoint {
+    /// Whether to use the default address information of the underlying network interface.
+    pub UseDefaultAddress: bool,
+    /// The local IPv4 address, if the default address is not used.
+    pub LocalAddress: [u8; 4],
+    /// The local subnet mask, if the default address is not used.
+    pub LocalSubnet: [u8; 4],
+    /// The local port, or 0 to use a random port.
+    pub LocalPort: u16,
+}
+
+/// The local IPv6 address configuration of an HTTP instance.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct HttpV6AccessPoint {
+    /// The local IPv6 address.
+    pub LocalAddress: [u8; 16],
+    /// The local port, or 0 to use a random port.
+    pub LocalPort: u16,
+}
+
+/// The local access point of an HTTP instance.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union HttpAccessPoint {
+    /// Used if `LocalAddressIsIPv6` is false.
+    pub IPv4Node: *const HttpV4AccessPoint,
+    /// Used if `LocalAddressIsIPv6` is true.
+    pub IPv6Node: *const HttpV6AccessPoint,
+}
+
+/// The configuration data of an HTTP instance.
+#[repr(C)]
+pub struct HttpConfigData {
+    /// The HTTP version to use.
+    pub HttpVersion: HttpVersion,
+    /// The timeout of the connection in milliseconds.
+    pub TimeOutMillisec: u32,
+    /// Whether the local address is an IPv6 address.
+    pub LocalAddressIsIPv6: bool,
+    /// The local access point.
+    pub AccessPoint: HttpAccessPoint,
+}
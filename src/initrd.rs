@@ -0,0 +1,294 @@
+//! Passes an initial ramdisk to Linux through the load file 2 protocol.
+//!
+//! Since version 5.8, the EFI stub of Linux loads its initial ramdisk from the load file 2
+//! protocol on the handle with the `guids::media::LINUX_INITRD` vendor media device path, so
+//! the legacy x86 boot parameters are not needed. `Initrd` installs such a handle that serves a
+//! buffer and removes it again when it is uninstalled.
+
+use core::{
+    marker::PhantomData,
+    mem::{size_of, ManuallyDrop},
+    ptr::{self, NonNull},
+};
+
+#[cfg(feature = "fs")]
+use crate::fs::{self, FILE_MODE_READ};
+#[cfg(feature = "tpm")]
+use crate::measure::{self, MeasureKind};
+use crate::{
+    boot::{self, BootServices},
+    boxed::{PoolBox, PoolBuffer},
+    device::{
+        DevicePath, DevicePathEndType, DevicePathMediaType, DevicePathType, VendorDevicePath,
+    },
+    guids,
+    load_file::LoadFile2,
+    memory::MemoryType,
+    protocol::Protocol,
+    status::{Error, Status, SUCCESS},
+    Handle,
+};
+
+/// The device path of the initial ramdisk, a vendor media node followed by the end node.
+#[repr(C)]
+struct InitrdDevicePath {
+    /// The vendor media node identifying the initial ramdisk.
+    vendor: VendorDevicePath,
+    /// The end of the device path.
+    end: DevicePath,
+}
+
+/// The installed protocols of an `Initrd`.
+#[repr(C)]
+struct InitrdInstance {
+    /// The protocol interface that is installed. This must be the first field, so the instance
+    /// can be found from the interface.
+    protocol: LoadFile2,
+    /// The device path that is installed.
+    device_path: InitrdDevicePath,
+    /// The start of the served data.
+    data: *const u8,
+    /// The size of the served data.
+    len: usize,
+}
+
+/// A handle that serves an initial ramdisk to Linux.
+///
+/// If the `Initrd` is dropped, the handle is only removed if `boot::register_global` was called.
+/// Prefer calling `uninstall` explicitly.
+pub struct Initrd<'a> {
+    /// The installed protocols.
+    instance: NonNull<InitrdInstance>,
+    /// The handle the protocols are installed on.
+    handle: Handle,
+    /// The buffer holding the data, if it is owned.
+    owned: Option<PoolBuffer<u8>>,
+    /// Marks that the data is borrowed.
+    _data: PhantomData<&'a [u8]>,
+}
+
+impl<'a> Initrd<'a> {
+    /// Installs a new handle that serves `data` as the initial ramdisk.
+    ///
+    /// With the `tpm` feature, the data is measured according to the global
+    /// `measure::MeasurePolicy`.
+    pub fn install(boot_services: &BootServices, data: &'a [u8]) -> Result<Initrd<'a>, Error> {
+        #[cfg(feature = "tpm")]
+        measure::measure_loaded(boot_services, MeasureKind::Initrd, "initrd", data)?;
+
+        // This is safe, because the data is borrowed for as long as the `Initrd` exists.
+        unsafe { Initrd::install_raw(boot_services, data.as_ptr(), data.len()) }
+    }
+
+    /// Installs the protocols serving the data.
+    ///
+    /// # Safety
+    /// `data` must point to `len` bytes that stay valid until the protocols are uninstalled.
+    unsafe fn install_raw(
+        boot_services: &BootServices,
+        data: *const u8,
+        len: usize,
+    ) -> Result<Initrd<'a>, Error> {
+        let instance = PoolBox::new(
+            boot_services,
+            MemoryType::LoaderData,
+            InitrdInstance {
+                protocol: LoadFile2 {
+                    LoadFile: load_initrd,
+                },
+                device_path: InitrdDevicePath {
+                    vendor: VendorDevicePath {
+                        Header: DevicePath {
+                            Type: DevicePathType::Media as u8,
+                            SubType: DevicePathMediaType::Vendor as u8,
+                            Length: size_of::<VendorDevicePath>() as u16,
+                        },
+                        Guid: guids::media::LINUX_INITRD,
+                    },
+                    end: DevicePath {
+                        Type: DevicePathType::End as u8,
+                        SubType: DevicePathEndType::Entire as u8,
+                        Length: size_of::<DevicePath>() as u16,
+                    },
+                },
+                data,
+                len,
+            },
+        )?;
+        let device_path = &instance.device_path as *const InitrdDevicePath as *const u8;
+        let protocol = &instance.protocol as *const LoadFile2 as *const u8;
+
+        let handle =
+            match boot_services.install_protocol_interface(None, &DevicePath::GUID, device_path) {
+                Ok(handle) => handle,
+                Err(error) => {
+                    instance.free(boot_services)?;
+
+                    return Err(error);
+                }
+            };
+
+        let installed =
+            boot_services.install_protocol_interface(Some(handle), &LoadFile2::GUID, protocol);
+
+        if let Err(error) = installed {
+            boot_services.uninstall_protocol_interface(handle, &DevicePath::GUID, device_path)?;
+            instance.free(boot_services)?;
+
+            return Err(error);
+        }
+
+        Ok(Initrd {
+            instance: NonNull::new_unchecked(instance.into_raw()),
+            handle,
+            owned: None,
+            _data: PhantomData,
+        })
+    }
+
+    /// Returns the handle the protocols are installed on.
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Returns the size of the initial ramdisk in bytes.
+    pub fn len(&self) -> usize {
+        // This is safe, because the instance is valid until it is uninstalled.
+        unsafe { self.instance.as_ref() }.len
+    }
+
+    /// Returns true if the initial ramdisk is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the handle and frees the buffer, if it is owned.
+    pub fn uninstall(self, boot_services: &BootServices) -> Result<(), Error> {
+        let mut this = ManuallyDrop::new(self);
+
+        this.uninstall_protocols(boot_services)?;
+        this.free(boot_services)
+    }
+
+    /// Removes both protocols from the handle, which removes the handle.
+    fn uninstall_protocols(&self, boot_services: &BootServices) -> Result<(), Error> {
+        boot_services.uninstall_protocol_interface(
+            self.handle,
+            &LoadFile2::GUID,
+            self.instance.as_ptr() as *const u8,
+        )?;
+        boot_services.uninstall_protocol_interface(
+            self.handle,
+            &DevicePath::GUID,
+            self.device_path_ptr(),
+        )
+    }
+
+    /// Frees the instance and the owned buffer.
+    fn free(&mut self, boot_services: &BootServices) -> Result<(), Error> {
+        // This is safe, because the instance was allocated from pool memory in `install_raw` and
+        // the protocols are not installed anymore.
+        unsafe { PoolBox::from_raw(self.instance.as_ptr()) }
+            .ok_or(Error::InvalidParameter)?
+            .free(boot_services)?;
+
+        match self.owned.take() {
+            Some(owned) => owned.free(boot_services),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the pointer to the installed device path.
+    fn device_path_ptr(&self) -> *const u8 {
+        // This is safe, because the instance is valid until it is freed.
+        unsafe { &(*self.instance.as_ptr()).device_path as *const InitrdDevicePath as *const u8 }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Initrd<'static> {
+    /// Reads the file at `path` into memory and installs a new handle that serves it.
+    ///
+    /// `path` is interpreted like by `fs::open_with`.
+    pub fn from_file(
+        boot_services: &BootServices,
+        image_handle: Handle,
+        path: &str,
+    ) -> Result<Initrd<'static>, Error> {
+        let mut file = fs::open_with(boot_services, image_handle, path, FILE_MODE_READ)?;
+
+        let size = file.info()?.FileSize as usize;
+        let mut buffer = PoolBuffer::new(boot_services, MemoryType::LoaderData, size)?;
+
+        let read = file.read_exact(&mut buffer);
+        #[cfg(feature = "tpm")]
+        let read = read.and_then(|_| {
+            measure::measure_loaded(boot_services, MeasureKind::Initrd, path, &buffer)
+        });
+
+        if let Err(error) = read {
+            buffer.free(boot_services)?;
+
+            return Err(error);
+        }
+
+        // This is safe, because the buffer is owned by the `Initrd` and freed after uninstalling.
+        match unsafe { Initrd::install_raw(boot_services, buffer.as_ptr(), buffer.len()) } {
+            Ok(mut initrd) => {
+                initrd.owned = Some(buffer);
+
+                Ok(initrd)
+            }
+            Err(error) => {
+                buffer.free(boot_services)?;
+
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<'a> Drop for Initrd<'a> {
+    fn drop(&mut self) {
+        if let Some(boot_services) = boot::global() {
+            // There is no way to report the error here.
+            if self.uninstall_protocols(boot_services).is_ok() {
+                let _ = self.free(boot_services);
+            }
+        }
+    }
+}
+
+eficall! {
+    /// Copies the initial ramdisk into the buffer of the caller.
+    fn load_initrd(
+        this: &LoadFile2,
+        _file_path: *const DevicePath,
+        boot_policy: bool,
+        buffer_size: &mut usize,
+        buffer: *mut u8,
+    ) -> Status {
+        // The load file 2 protocol is never used to load boot options.
+        if boot_policy {
+            return Error::Unsupported.into();
+        }
+
+        // This is safe, because the protocol is the first field of the instance.
+        let instance = unsafe { &*(this as *const LoadFile2 as *const InitrdInstance) };
+
+        if buffer.is_null() || *buffer_size < instance.len {
+            *buffer_size = instance.len;
+
+            return Error::BufferTooSmall.into();
+        }
+
+        // This is safe, because the data stays valid while the protocol is installed and the caller
+        // provided a buffer of at least `buffer_size` bytes.
+        unsafe {
+            ptr::copy_nonoverlapping(instance.data, buffer, instance.len);
+        }
+        *buffer_size = instance.len;
+
+        SUCCESS
+    }
+}
@@ -0,0 +1,75 @@
+//! Compile-time checks of the layout of the firmware structures.
+//!
+//! The sizes are checked on the 64-bit targets, where the firmware structures are laid out the
+//! same way, so a mismatch on `aarch64-unknown-uefi` already shows up when building for
+//! `x86_64-unknown-uefi`. Only the sizes are checked, because the field offsets cannot be
+//! computed in constants with the supported toolchains. Most structures consist of pointer sized
+//! fields, so a missing, extra or misaligned field changes the size as well.
+
+use core::mem::size_of;
+
+use crate::{
+    boot::BootServices,
+    config::ConfigurationTable,
+    device::DevicePath,
+    guid::Guid,
+    loaded_image::LoadedImage,
+    memory::MemoryDescriptor,
+    runtime::RuntimeServices,
+    status::Status,
+    system::SystemTable,
+    text::{TextInput, TextInputKey, TextOutput, TextOutputMode},
+    time::Time,
+    Event, Handle, TableHeader,
+};
+
+/// Fails to compile if the size of the type differs from the expected size.
+macro_rules! assert_size {
+    ($type:ty, $size:expr) => {
+        const _: () = [()][(size_of::<$type>() != $size) as usize];
+    };
+}
+
+assert_size!(TableHeader, 24);
+assert_size!(SystemTable, 120);
+assert_size!(BootServices, 24 + 44 * 8);
+assert_size!(RuntimeServices, 24 + 14 * 8);
+assert_size!(ConfigurationTable, 24);
+assert_size!(MemoryDescriptor, 40);
+assert_size!(Guid, 16);
+assert_size!(Time, 16);
+assert_size!(Status, 8);
+assert_size!(Handle, 8);
+assert_size!(Event, 8);
+assert_size!(TextInputKey, 4);
+assert_size!(TextInput, 24);
+assert_size!(TextOutputMode, 24);
+assert_size!(TextOutput, 80);
+assert_size!(LoadedImage, 96);
+assert_size!(DevicePath, 4);
+
+#[cfg(feature = "fs")]
+mod fs {
+    use core::mem::size_of;
+
+    use crate::{
+        block_io::{BlockIo, BlockIoMedia},
+        fs::{File, SimpleFileSystem},
+    };
+
+    assert_size!(BlockIoMedia, 32);
+    assert_size!(BlockIo, 48);
+    assert_size!(SimpleFileSystem, 16);
+    assert_size!(File, 88);
+}
+
+#[cfg(feature = "graphics")]
+mod graphics {
+    use core::mem::size_of;
+
+    use crate::graphics::{GraphicsOutput, GraphicsOutputMode, GraphicsOutputModeInfo};
+
+    assert_size!(GraphicsOutputModeInfo, 36);
+    assert_size!(GraphicsOutputMode, 40);
+    assert_size!(GraphicsOutput, 32);
+}
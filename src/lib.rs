@@ -2,30 +2,161 @@
 //!
 //! The documentation is based on UEFI version 2.7A which can be found
 //! [here](http://www.uefi.org/sites/default/files/resources/UEFI%20Spec%202_7_A%20Sept%206.pdf).
+//!
+//! With the `efiapi` feature, all firmware functions use the `efiapi` calling convention, which
+//! maps to the correct calling convention of every UEFI target, such as `x86_64-unknown-uefi` and
+//! `aarch64-unknown-uefi`. Toolchains without `abi_efiapi` can disable the feature, in which case
+//! `win64` is used on x86_64 and `C` on all other targets.
 #![allow(dead_code)]
 #![allow(non_snake_case)]
 #![no_std]
 #![feature(try_trait)]
 #![feature(const_fn)]
+#![cfg_attr(feature = "efiapi", feature(abi_efiapi))]
+#![feature(asm)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// Declares a function or a function pointer type with the calling convention of the firmware.
+//
+// The calling convention is chosen by the `efiapi` feature, so it is only spelled out here:
+// `eficall! { fn(Argument: usize) -> Status }` is a function pointer type and
+// `eficall! { fn name(argument: usize) -> Status { ... } }` a function.
+#[cfg(feature = "efiapi")]
+macro_rules! eficall {
+    (unsafe fn ($($arguments:tt)*) $($rest:tt)*) => {
+        unsafe extern "efiapi" fn($($arguments)*) $($rest)*
+    };
+    (fn ($($arguments:tt)*) $($rest:tt)*) => {
+        extern "efiapi" fn($($arguments)*) $($rest)*
+    };
+    ($(#[$attribute:meta])* $visibility:vis unsafe fn $name:ident $($rest:tt)*) => {
+        $(#[$attribute])* $visibility unsafe extern "efiapi" fn $name $($rest)*
+    };
+    ($(#[$attribute:meta])* $visibility:vis fn $name:ident $($rest:tt)*) => {
+        $(#[$attribute])* $visibility extern "efiapi" fn $name $($rest)*
+    };
+}
+
+#[cfg(all(not(feature = "efiapi"), target_arch = "x86_64"))]
+macro_rules! eficall {
+    (unsafe fn ($($arguments:tt)*) $($rest:tt)*) => {
+        unsafe extern "win64" fn($($arguments)*) $($rest)*
+    };
+    (fn ($($arguments:tt)*) $($rest:tt)*) => {
+        extern "win64" fn($($arguments)*) $($rest)*
+    };
+    ($(#[$attribute:meta])* $visibility:vis unsafe fn $name:ident $($rest:tt)*) => {
+        $(#[$attribute])* $visibility unsafe extern "win64" fn $name $($rest)*
+    };
+    ($(#[$attribute:meta])* $visibility:vis fn $name:ident $($rest:tt)*) => {
+        $(#[$attribute])* $visibility extern "win64" fn $name $($rest)*
+    };
+}
+
+#[cfg(all(not(feature = "efiapi"), not(target_arch = "x86_64")))]
+macro_rules! eficall {
+    (unsafe fn ($($arguments:tt)*) $($rest:tt)*) => {
+        unsafe extern "C" fn($($arguments)*) $($rest)*
+    };
+    (fn ($($arguments:tt)*) $($rest:tt)*) => {
+        extern "C" fn($($arguments)*) $($rest)*
+    };
+    ($(#[$attribute:meta])* $visibility:vis unsafe fn $name:ident $($rest:tt)*) => {
+        $(#[$attribute])* $visibility unsafe extern "C" fn $name $($rest)*
+    };
+    ($(#[$attribute:meta])* $visibility:vis fn $name:ident $($rest:tt)*) => {
+        $(#[$attribute])* $visibility extern "C" fn $name $($rest)*
+    };
+}
 
+pub mod acpi;
+#[cfg(feature = "fs")]
 pub mod block_io;
+pub mod bluetooth;
+#[cfg(feature = "graphics")]
+pub mod bmp;
 pub mod boot;
+pub mod boot_options;
+pub mod boxed;
 pub mod capsule;
+#[cfg(feature = "fs")]
+pub mod chainload;
 pub mod config;
+pub mod deferred_image;
 pub mod device;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "fs")]
+pub mod disk_io;
+pub mod driver;
+pub mod driver_override;
+pub mod elf;
+#[cfg(feature = "fat")]
+pub mod fat;
+pub mod format;
+pub mod frame_allocator;
+#[cfg(feature = "fs")]
 pub mod fs;
+#[cfg(feature = "graphics")]
+pub mod gfx_console;
+#[cfg(feature = "graphics")]
 pub mod graphics;
 pub mod guid;
+pub mod guids;
+pub mod handoff;
+#[cfg(feature = "net")]
+pub mod http;
+pub mod initrd;
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+mod layout;
+pub mod load_file;
 pub mod loaded_image;
+#[cfg(feature = "tpm")]
+pub mod measure;
 pub mod memory;
+pub mod memory_attribute;
+pub mod memtest;
+pub mod menu;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod nvdimm;
+pub mod panic;
+pub mod perf;
 pub mod pointer;
+pub mod protocol;
+#[cfg(feature = "net")]
+pub mod pxe;
+pub mod quirk;
 pub mod reset;
+#[cfg(feature = "net")]
+pub mod rest;
 pub mod runtime;
+pub mod secure_boot;
+pub mod serial;
 pub mod shell;
+pub mod smbios;
+pub mod smp;
 pub mod status;
+pub mod string;
 pub mod system;
+#[cfg(feature = "tpm")]
+pub mod tcg2;
 pub mod text;
 pub mod time;
+pub mod timer;
+pub mod timestamp;
+pub mod ucs2;
+pub mod user_manager;
+pub mod var_store;
+pub mod watchdog;
+#[cfg(feature = "net")]
+pub mod wifi;
+
+use core::{ffi::c_void, fmt, mem, ptr::NonNull};
 
 /// Handle to an event structure.
 ///
@@ -36,10 +167,49 @@ pub struct Event(pub usize);
 
 /// A collection of related interfaces.
 ///
-/// Type VOID *.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Type VOID *. A handle is never null, positions where the specification allows a null handle
+/// use `OptionalHandle` instead.
+#[derive(Clone, Copy, Eq, PartialEq)]
 #[repr(transparent)]
-pub struct Handle(pub usize);
+pub struct Handle(NonNull<c_void>);
+
+// Handles are opaque identifiers that are never dereferenced, so they can be shared freely.
+unsafe impl Send for Handle {}
+unsafe impl Sync for Handle {}
+
+impl Handle {
+    /// Creates a handle from a raw pointer, returning `None` if the pointer is null.
+    ///
+    /// # Safety
+    /// The pointer must be a handle obtained from the firmware.
+    pub unsafe fn from_ptr(ptr: *mut c_void) -> Option<Handle> {
+        NonNull::new(ptr).map(Handle)
+    }
+
+    /// Returns the raw pointer of the handle.
+    pub fn as_ptr(self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handle({:p})", self.0)
+    }
+}
+
+// Handles from `r-efi` may be null, so they are converted with `Handle::from_ptr` instead.
+#[cfg(feature = "r-efi")]
+impl From<Handle> for r_efi::efi::Handle {
+    fn from(handle: Handle) -> r_efi::efi::Handle {
+        handle.as_ptr()
+    }
+}
+
+/// A handle in a position where the specification allows it to be null.
+///
+/// `Option<Handle>` has the same layout as a pointer, with `None` being the null pointer.
+pub type OptionalHandle = Option<Handle>;
 
 /// Data structure that precedes all of the standard EFI table types.
 #[repr(C)]
@@ -77,3 +247,42 @@ pub struct TableHeader {
     /// Reserved field that must be set to 0.
     Reserved: u32,
 }
+
+impl TableHeader {
+    /// Returns the 64-bit signature that identifies the type of the table.
+    pub fn signature(&self) -> u64 {
+        self.Signature
+    }
+
+    /// Returns the raw revision of the specification the table conforms to.
+    ///
+    /// This can be compared with the `SYSTEM_TABLE_REVISION_*` constants.
+    pub fn raw_revision(&self) -> u32 {
+        self.Revision
+    }
+
+    /// Returns the revision of the specification the table conforms to as (major, minor).
+    ///
+    /// The minor revision holds two decimal digits, so revision 2.7 is returned as `(2, 70)` and
+    /// revision 2.3.1 as `(2, 31)`.
+    pub fn revision(&self) -> (u16, u16) {
+        ((self.Revision >> 16) as u16, self.Revision as u16)
+    }
+
+    /// Returns the size of the entire table, including the header.
+    pub fn header_size(&self) -> u32 {
+        self.HeaderSize
+    }
+
+    /// Returns true if the table conforms to at least the given raw revision and is large enough
+    /// to contain `field`.
+    ///
+    /// `table` must be the table this header belongs to and `field` must be a field of `table`.
+    /// This is used to avoid calling functions that older firmware does not provide.
+    pub(crate) fn provides<T, F>(&self, revision: u32, table: &T, field: &F) -> bool {
+        let field_end =
+            field as *const F as usize - table as *const T as usize + mem::size_of::<F>();
+
+        self.Revision >= revision && field_end <= self.HeaderSize as usize
+    }
+}
@@ -0,0 +1,108 @@
+//! Provides the protocols used to load files from devices without a file system, such as network
+//! boot servers.
+//!
+//! The load file protocol is used by the boot manager to load boot options, the load file 2
+//! protocol is used for files that are not boot options, like the initial ramdisk of Linux.
+
+use core::ptr;
+
+use crate::{
+    device::DevicePath,
+    guid::Guid,
+    guids,
+    protocol::Protocol,
+    status::{Error, Status},
+};
+
+/// Loads a file from a device that does not provide a file system.
+#[repr(C)]
+pub struct LoadFile {
+    /// Loads the file at the given path into the buffer.
+    pub LoadFile: eficall! { fn(
+        &LoadFile,
+        FilePath: *const DevicePath,
+        BootPolicy: bool,
+        BufferSize: &mut usize,
+        Buffer: *mut u8,
+    ) -> Status },
+}
+
+unsafe impl Protocol for LoadFile {
+    const GUID: Guid = guids::protocol::LOAD_FILE;
+}
+
+impl LoadFile {
+    /// Loads the file at `file_path` into `buffer`.
+    ///
+    /// See `LoadFile2::load_file` for the handling of the buffer. `boot_policy` indicates whether
+    /// the request originates from the boot manager.
+    pub fn load_file(
+        &self,
+        file_path: &DevicePath,
+        boot_policy: bool,
+        buffer: Option<&mut [u8]>,
+    ) -> Result<usize, Error> {
+        load(buffer, |size, buffer| {
+            (self.LoadFile)(self, file_path, boot_policy, size, buffer)
+        })
+    }
+}
+
+/// Loads a file that is not a boot option from a device.
+#[repr(C)]
+pub struct LoadFile2 {
+    /// Loads the file at the given path into the buffer. `BootPolicy` must be false.
+    pub LoadFile: eficall! { fn(
+        &LoadFile2,
+        FilePath: *const DevicePath,
+        BootPolicy: bool,
+        BufferSize: &mut usize,
+        Buffer: *mut u8,
+    ) -> Status },
+}
+
+unsafe impl Protocol for LoadFile2 {
+    const GUID: Guid = guids::protocol::LOAD_FILE2;
+}
+
+impl LoadFile2 {
+    /// Loads the file at `file_path` into `buffer`.
+    ///
+    /// `file_path` is the remaining device path after the node that the protocol was found on.
+    /// If `buffer` is `None`, only the size of the file is returned.
+    ///
+    /// Returns the size of the file. If `buffer` is too small, `Error::BufferTooSmall` is returned
+    /// and the size can be queried by passing `None`.
+    pub fn load_file(
+        &self,
+        file_path: &DevicePath,
+        buffer: Option<&mut [u8]>,
+    ) -> Result<usize, Error> {
+        load(buffer, |size, buffer| {
+            (self.LoadFile)(self, file_path, false, size, buffer)
+        })
+    }
+}
+
+/// Calls a load file function with the given buffer and returns the size of the file.
+fn load<F>(buffer: Option<&mut [u8]>, function: F) -> Result<usize, Error>
+where
+    F: FnOnce(&mut usize, *mut u8) -> Status,
+{
+    let (mut size, pointer) = match buffer {
+        Some(buffer) => (buffer.len(), buffer.as_mut_ptr()),
+        None => (0, ptr::null_mut()),
+    };
+    let query = pointer.is_null();
+
+    let status = function(&mut size, pointer);
+
+    // Querying the size with a null buffer is reported as a buffer that is too small.
+    if query && status == Error::BufferTooSmall.into() {
+        return Ok(size);
+    }
+
+    status?;
+
+    Ok(size)
+}
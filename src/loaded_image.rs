@@ -1,7 +1,15 @@
 //! Can be used on any image handle to obtain information about the loaded image.
 
-use crate::{memory::MemoryType, status::Status, system::SystemTable, Handle};
-use core::fmt;
+use crate::{
+    guid::{Guid, LOADED_IMAGE_PROTOCOL_GUID},
+    memory::MemoryType,
+    protocol::Protocol,
+    status::Status,
+    system::SystemTable,
+    ucs2::Lossy,
+    Handle, OptionalHandle,
+};
+use core::{fmt, mem::size_of, slice};
 
 /// Each loaded image has an image handle that supports EFI_LOADED_IMAGE_PROTOCOL. When an
 /// image is started, it is passed the image handle for itself. The image can use the handle to obtain its
@@ -14,11 +22,11 @@ pub struct LoadedImage {
     pub Revision: u32,
     /// Parent image’s image handle. NULL if the image is loaded
     /// directly from the firmware’s boot manager.
-    pub ParentHandle: Handle,
+    pub ParentHandle: OptionalHandle,
     /// The image’s EFI system table pointer.
     pub SystemTable: &'static mut SystemTable,
     /// The device handle that the EFI Image was loaded from.
-    pub DeviceHandle: Handle,
+    pub DeviceHandle: OptionalHandle,
     /// A pointer to the file path portion specific to DeviceHandle
     /// that the EFI Image was loaded from.
     pub FilePath: usize,
@@ -37,7 +45,35 @@ pub struct LoadedImage {
     /// The memory type that the data sections were loaded as.
     pub ImageDataType: MemoryType,
     /// Function that unloads the image.
-    pub Unload: extern "win64" fn(ImageHandle: Handle) -> Status,
+    pub Unload: eficall! { fn(ImageHandle: Handle) -> Status },
+}
+
+unsafe impl Protocol for LoadedImage {
+    const GUID: Guid = LOADED_IMAGE_PROTOCOL_GUID;
+}
+
+impl LoadedImage {
+    /// Returns the load options as UTF-16 code units.
+    ///
+    /// Load options are only text by convention, the firmware may pass arbitrary binary data.
+    pub fn load_options(&self) -> &[u16] {
+        if self.LoadOptions.is_null() {
+            return &[];
+        }
+
+        // This is safe under the assumption that the firmware supplied a valid buffer.
+        unsafe {
+            slice::from_raw_parts(
+                self.LoadOptions,
+                self.LoadOptionsSize as usize / size_of::<u16>(),
+            )
+        }
+    }
+
+    /// Returns the load options decoded as text up to the first null terminator.
+    pub fn load_options_lossy(&self) -> Lossy {
+        Lossy::new(self.load_options())
+    }
 }
 
 impl fmt::Debug for LoadedImage {
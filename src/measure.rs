@@ -0,0 +1,179 @@
+//! Measures loaded files into the PCRs of the TPM.
+//!
+//! Measurements are logged as `EV_IPL` events whose event data is the null terminated
+//! description, like other boot loaders do. The loading helpers of this crate measure kernels,
+//! initial ramdisks and configuration files according to the global `MeasurePolicy`, which
+//! follows the PCR usage of GRUB by default. If there is no TPM, nothing is measured.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{
+    boot::BootServices,
+    boxed::PoolBuffer,
+    memory::MemoryType,
+    status::Error,
+    system,
+    tcg2::{Tcg2, EV_IPL},
+};
+
+/// The PCR that kernels and initial ramdisks are measured into by default.
+pub const DEFAULT_FILE_PCR: u8 = 9;
+/// The PCR that configuration files are measured into by default.
+pub const DEFAULT_CONFIG_PCR: u8 = 8;
+
+/// The encoding of a disabled measurement in `POLICY`.
+const DISABLED: u8 = 0xff;
+
+/// The global policy, with one byte per `MeasureKind` holding the PCR or `DISABLED`.
+static POLICY: AtomicU32 = AtomicU32::new(0xff08_0909);
+
+/// The kind of a file loaded by the helpers of this crate.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MeasureKind {
+    /// A kernel or other image that is started.
+    Kernel,
+    /// An initial ramdisk.
+    Initrd,
+    /// A configuration file.
+    Config,
+}
+
+/// Selects the PCR that each kind of file is measured into.
+///
+/// A kind whose PCR is `None` is not measured.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MeasurePolicy {
+    /// The PCR that kernels are measured into.
+    pub kernel: Option<u8>,
+    /// The PCR that initial ramdisks are measured into.
+    pub initrd: Option<u8>,
+    /// The PCR that configuration files are measured into.
+    pub config: Option<u8>,
+}
+
+impl MeasurePolicy {
+    /// Returns a policy that measures nothing.
+    pub fn disabled() -> MeasurePolicy {
+        MeasurePolicy {
+            kernel: None,
+            initrd: None,
+            config: None,
+        }
+    }
+
+    /// Returns the PCR that files of `kind` are measured into.
+    pub fn pcr(&self, kind: MeasureKind) -> Option<u8> {
+        match kind {
+            MeasureKind::Kernel => self.kernel,
+            MeasureKind::Initrd => self.initrd,
+            MeasureKind::Config => self.config,
+        }
+    }
+}
+
+impl Default for MeasurePolicy {
+    fn default() -> MeasurePolicy {
+        MeasurePolicy {
+            kernel: Some(DEFAULT_FILE_PCR),
+            initrd: Some(DEFAULT_FILE_PCR),
+            config: Some(DEFAULT_CONFIG_PCR),
+        }
+    }
+}
+
+/// Sets the policy used by the loading helpers.
+///
+/// Returns `Error::InvalidParameter` if a PCR is not below 24.
+pub fn set_policy(policy: MeasurePolicy) -> Result<(), Error> {
+    let mut bits = 0xff00_0000;
+
+    for (index, &pcr) in [policy.kernel, policy.initrd, policy.config]
+        .iter()
+        .enumerate()
+    {
+        let pcr = match pcr {
+            Some(pcr) if pcr >= 24 => return Err(Error::InvalidParameter),
+            Some(pcr) => pcr,
+            None => DISABLED,
+        };
+
+        bits |= u32::from(pcr) << (index * 8);
+    }
+
+    POLICY.store(bits, Ordering::SeqCst);
+
+    Ok(())
+}
+
+/// Returns the policy used by the loading helpers.
+pub fn policy() -> MeasurePolicy {
+    let bits = POLICY.load(Ordering::SeqCst);
+    let pcr = |index: u32| match (bits >> (index * 8)) as u8 {
+        DISABLED => None,
+        pcr => Some(pcr),
+    };
+
+    MeasurePolicy {
+        kernel: pcr(0),
+        initrd: pcr(1),
+        config: pcr(2),
+    }
+}
+
+/// Extends `pcr` with the hash of `data` and logs an event with `description`.
+///
+/// Returns `Error::NotFound` if there is no TPM.
+pub fn hash_and_log_with(
+    boot_services: &BootServices,
+    pcr: u32,
+    description: &str,
+    data: &[u8],
+) -> Result<(), Error> {
+    let tcg2 = boot_services.locate_protocol::<Tcg2>()?;
+
+    if !tcg2.capability()?.TPMPresentFlag {
+        return Err(Error::NotFound);
+    }
+
+    let mut event_data =
+        PoolBuffer::new(boot_services, MemoryType::LoaderData, description.len() + 1)?;
+    event_data[..description.len()].copy_from_slice(description.as_bytes());
+    event_data[description.len()] = 0;
+
+    let measured = tcg2.hash_log_extend_event(boot_services, 0, data, pcr, EV_IPL, &event_data);
+
+    event_data.free(boot_services)?;
+
+    measured
+}
+
+/// Extends `pcr` with the hash of `data` and logs an event with `description`.
+///
+/// The boot services registered with `system::init` are used. Returns `Error::NotStarted` if
+/// `system::init` was not called and `Error::NotFound` if there is no TPM.
+pub fn hash_and_log(pcr: u32, description: &str, data: &[u8]) -> Result<(), Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+
+    hash_and_log_with(boot_services, pcr, description, data)
+}
+
+/// Measures a loaded file of `kind` according to the global policy.
+///
+/// Nothing is measured if the policy disables the kind or if there is no TPM. This is called by
+/// the loading helpers of this crate and can be used for files that are loaded otherwise.
+pub fn measure_loaded(
+    boot_services: &BootServices,
+    kind: MeasureKind,
+    description: &str,
+    data: &[u8],
+) -> Result<(), Error> {
+    let pcr = match policy().pcr(kind) {
+        Some(pcr) => pcr,
+        None => return Ok(()),
+    };
+
+    match hash_and_log_with(boot_services, u32::from(pcr), description, data) {
+        Err(Error::NotFound) => Ok(()),
+        result => result,
+    }
+}
@@ -2,11 +2,26 @@
 
 use bitflags::bitflags;
 use core::{
-    mem::size_of,
+    convert::TryFrom,
+    fmt,
+    mem::{align_of, size_of},
+    ops::{Add, AddAssign, Range, Sub, SubAssign},
+    ptr,
     slice::{self, Chunks, ChunksMut},
 };
 
-use crate::{boot::BootServices, status::Error};
+use crate::{
+    boot::BootServices,
+    format::{ByteSize, Hex},
+    quirk::{self, Quirk},
+    status::Error,
+};
+#[cfg(feature = "x86_64")]
+use x86_64::{
+    addr::{PhysAddrNotValid, VirtAddrNotValid},
+    structures::paging::{frame::PhysFrameRange, PhysFrame},
+    PhysAddr, VirtAddr,
+};
 
 /// Represents a physical address.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -16,11 +31,181 @@ pub struct PhysicalAddress(pub u64);
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct VirtualAddress(pub u64);
 
+/// Implements arithmetic, alignment and formatting for an address type.
+macro_rules! impl_address {
+    ($address:ident) => {
+        impl $address {
+            /// Adds `offset` to the address, returning `None` on overflow.
+            pub fn checked_add(self, offset: u64) -> Option<$address> {
+                self.0.checked_add(offset).map($address)
+            }
+
+            /// Subtracts `offset` from the address, returning `None` on underflow.
+            pub fn checked_sub(self, offset: u64) -> Option<$address> {
+                self.0.checked_sub(offset).map($address)
+            }
+
+            /// Rounds the address up to the next multiple of `align`.
+            ///
+            /// # Panics
+            /// Panics if `align` is not a power of two or the result overflows.
+            pub fn align_up(self, align: u64) -> $address {
+                self.checked_align_up(align)
+                    .expect("Aligning the address up overflowed.")
+            }
+
+            /// Rounds the address up to the next multiple of `align`, returning `None` on
+            /// overflow.
+            ///
+            /// # Panics
+            /// Panics if `align` is not a power of two.
+            pub fn checked_align_up(self, align: u64) -> Option<$address> {
+                assert!(
+                    align.is_power_of_two(),
+                    "The alignment must be a power of two."
+                );
+
+                Some($address(self.0.checked_add(align - 1)? & !(align - 1)))
+            }
+
+            /// Rounds the address down to the previous multiple of `align`.
+            ///
+            /// # Panics
+            /// Panics if `align` is not a power of two.
+            pub fn align_down(self, align: u64) -> $address {
+                assert!(
+                    align.is_power_of_two(),
+                    "The alignment must be a power of two."
+                );
+
+                $address(self.0 & !(align - 1))
+            }
+
+            /// Returns true if the address is a multiple of `align`.
+            ///
+            /// # Panics
+            /// Panics if `align` is not a power of two.
+            pub fn is_aligned(self, align: u64) -> bool {
+                self.align_down(align) == self
+            }
+
+            /// Returns true if the address is aligned to the UEFI page size.
+            pub fn is_page_aligned(self) -> bool {
+                self.is_aligned(PAGE_SIZE as u64)
+            }
+        }
+
+        impl Add<u64> for $address {
+            type Output = $address;
+
+            fn add(self, offset: u64) -> $address {
+                $address(self.0 + offset)
+            }
+        }
+
+        impl AddAssign<u64> for $address {
+            fn add_assign(&mut self, offset: u64) {
+                self.0 += offset;
+            }
+        }
+
+        impl Sub<u64> for $address {
+            type Output = $address;
+
+            fn sub(self, offset: u64) -> $address {
+                $address(self.0 - offset)
+            }
+        }
+
+        impl SubAssign<u64> for $address {
+            fn sub_assign(&mut self, offset: u64) {
+                self.0 -= offset;
+            }
+        }
+
+        impl Sub<$address> for $address {
+            type Output = u64;
+
+            fn sub(self, other: $address) -> u64 {
+                self.0 - other.0
+            }
+        }
+
+        impl fmt::Display for $address {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", Hex(self.0))
+            }
+        }
+
+        impl fmt::LowerHex for $address {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::LowerHex::fmt(&self.0, f)
+            }
+        }
+
+        #[cfg(feature = "defmt")]
+        impl defmt::Format for $address {
+            fn format(&self, f: defmt::Formatter) {
+                defmt::write!(f, "{=u64:#018x}", self.0);
+            }
+        }
+
+        impl fmt::UpperHex for $address {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::UpperHex::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+impl_address!(PhysicalAddress);
+impl_address!(VirtualAddress);
+
+#[cfg(feature = "x86_64")]
+impl From<PhysAddr> for PhysicalAddress {
+    fn from(address: PhysAddr) -> PhysicalAddress {
+        PhysicalAddress(address.as_u64())
+    }
+}
+
+#[cfg(feature = "x86_64")]
+impl TryFrom<PhysicalAddress> for PhysAddr {
+    type Error = PhysAddrNotValid;
+
+    /// Fails if any of the bits 52 to 63 are set.
+    fn try_from(address: PhysicalAddress) -> Result<PhysAddr, PhysAddrNotValid> {
+        PhysAddr::try_new(address.0)
+    }
+}
+
+#[cfg(feature = "x86_64")]
+impl From<VirtAddr> for VirtualAddress {
+    fn from(address: VirtAddr) -> VirtualAddress {
+        VirtualAddress(address.as_u64())
+    }
+}
+
+#[cfg(feature = "x86_64")]
+impl TryFrom<VirtualAddress> for VirtAddr {
+    type Error = VirtAddrNotValid;
+
+    /// Fails if the address is not canonical.
+    fn try_from(address: VirtualAddress) -> Result<VirtAddr, VirtAddrNotValid> {
+        VirtAddr::try_new(address.0)
+    }
+}
+
 /// The page size according to the UEFI specification is 4KiB.
 pub const PAGE_SIZE: usize = 0x1000;
 
+/// The version of the memory descriptors described by this crate.
+///
+/// Newer versions are expected to only append fields to the descriptor, which is why the
+/// descriptor size is reported separately.
+pub const MEMORY_DESCRIPTOR_VERSION: u32 = 1;
+
 /// Describes the different areas of memory in the memory map.
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct MemoryDescriptor {
     /// Type of the memory region.
@@ -44,40 +229,216 @@ pub struct MemoryDescriptor {
     pub Attribute: MemoryAttributes,
 }
 
+impl MemoryDescriptor {
+    /// Returns the physical address of the first byte after the memory region.
+    ///
+    /// Saturates at the end of the address space for regions that would exceed it.
+    pub fn physical_end(&self) -> PhysicalAddress {
+        PhysicalAddress(self.PhysicalStart.0.saturating_add(self.byte_len()))
+    }
+
+    /// Returns the size of the memory region in bytes.
+    ///
+    /// Saturates at `u64::MAX` if the number of pages is too large to be expressed in bytes.
+    pub fn byte_len(&self) -> u64 {
+        self.NumberOfPages.saturating_mul(PAGE_SIZE as u64)
+    }
+
+    /// Returns true if the given physical address lies within the memory region.
+    pub fn contains(&self, address: PhysicalAddress) -> bool {
+        // Comparing the offset avoids overflowing for regions at the end of the address space.
+        address.0 >= self.PhysicalStart.0 && address.0 - self.PhysicalStart.0 < self.byte_len()
+    }
+
+    /// Returns the range of physical page frame numbers covered by the memory region.
+    ///
+    /// The end of the range is exclusive and saturates at `u64::MAX` for regions that would
+    /// exceed it.
+    pub fn page_range(&self) -> Range<u64> {
+        let first_page = self.PhysicalStart.0 / PAGE_SIZE as u64;
+
+        first_page..first_page.saturating_add(self.NumberOfPages)
+    }
+
+    /// Returns the range of frames covered by the memory region.
+    ///
+    /// # Panics
+    /// Panics if the memory region does not fit into the 52 bit physical address space.
+    #[cfg(feature = "x86_64")]
+    pub fn frame_range(&self) -> PhysFrameRange {
+        let start = PhysFrame::containing_address(PhysAddr::new(self.PhysicalStart.0));
+
+        PhysFrame::range(start, start + self.NumberOfPages)
+    }
+
+    /// Returns true if the physical memory regions of both descriptors share at least one page.
+    pub fn overlaps(&self, other: &MemoryDescriptor) -> bool {
+        let own_pages = self.page_range();
+        let other_pages = other.page_range();
+
+        own_pages.start < other_pages.end && other_pages.start < own_pages.end
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<r_efi::efi::MemoryDescriptor> for MemoryDescriptor {
+    /// Attribute bits unknown to `MemoryAttributes` are dropped.
+    fn from(descriptor: r_efi::efi::MemoryDescriptor) -> MemoryDescriptor {
+        MemoryDescriptor {
+            Type: MemoryType(descriptor.r#type),
+            PhysicalStart: PhysicalAddress(descriptor.physical_start),
+            VirtualStart: VirtualAddress(descriptor.virtual_start),
+            NumberOfPages: descriptor.number_of_pages,
+            Attribute: MemoryAttributes::from_bits_truncate(descriptor.attribute),
+        }
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<MemoryDescriptor> for r_efi::efi::MemoryDescriptor {
+    fn from(descriptor: MemoryDescriptor) -> r_efi::efi::MemoryDescriptor {
+        r_efi::efi::MemoryDescriptor {
+            r#type: descriptor.Type.0,
+            physical_start: descriptor.PhysicalStart.0,
+            virtual_start: descriptor.VirtualStart.0,
+            number_of_pages: descriptor.NumberOfPages,
+            attribute: descriptor.Attribute.bits(),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MemoryDescriptor {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{} {}-{} ({=u64} pages) {=u64:#x}",
+            self.Type,
+            self.PhysicalStart,
+            self.physical_end(),
+            self.NumberOfPages,
+            self.Attribute.bits()
+        );
+    }
+}
+
 /// Represents the different types memory can have.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct MemoryType(u32);
 
-impl From<NamedMemoryType> for MemoryType {
-    fn from(named_memory_type: NamedMemoryType) -> MemoryType {
-        let inner_num = match named_memory_type {
-            NamedMemoryType::ReservedMemoryType => 0,
-            NamedMemoryType::LoaderCode => 1,
-            NamedMemoryType::LoaderData => 2,
-            NamedMemoryType::BootServicesCode => 3,
-            NamedMemoryType::BootServicesData => 4,
-            NamedMemoryType::RuntimeServicesCode => 5,
-            NamedMemoryType::RuntimeServicesData => 6,
-            NamedMemoryType::ConventionalMemory => 7,
-            NamedMemoryType::UnusableMemory => 8,
-            NamedMemoryType::ACPIReclaimMemory => 9,
-            NamedMemoryType::ACPIMemoryNVS => 10,
-            NamedMemoryType::MemoryMappedIO => 11,
-            NamedMemoryType::MemoryMappedIOPortSpace => 12,
-            NamedMemoryType::PalCode => 13,
-            NamedMemoryType::PersistentMemory => 14,
-            NamedMemoryType::UnknownMemoryType(num) => num,
-            NamedMemoryType::OEMSpecific(num) => num + 0x70000000,
-            NamedMemoryType::OSLoaderSpecific(num) => num + 0x80000000,
-        };
+/// The first memory type reserved for OEM use.
+const OEM_SPECIFIC_START: u32 = 0x7000_0000;
+/// The first memory type reserved for OS loader use.
+const OS_LOADER_SPECIFIC_START: u32 = 0x8000_0000;
+
+#[allow(non_upper_case_globals)]
+impl MemoryType {
+    /// Not usable.
+    pub const ReservedMemoryType: MemoryType = MemoryType(0);
+    /// The code portions of a loaded application.
+    pub const LoaderCode: MemoryType = MemoryType(1);
+    /// The data portions of a loaded application.
+    pub const LoaderData: MemoryType = MemoryType(2);
+    /// The code portions of a loaded Boot Service Driver.
+    pub const BootServicesCode: MemoryType = MemoryType(3);
+    /// The data portions of a loaded Boot Service Driver.
+    pub const BootServicesData: MemoryType = MemoryType(4);
+    /// The code portions of a loaded Runtime Driver.
+    pub const RuntimeServicesCode: MemoryType = MemoryType(5);
+    /// The data portions of a loaded Runtime Driver.
+    pub const RuntimeServicesData: MemoryType = MemoryType(6);
+    /// Free (unallocated) memory.
+    pub const ConventionalMemory: MemoryType = MemoryType(7);
+    /// Memory in which errors have been detected.
+    pub const UnusableMemory: MemoryType = MemoryType(8);
+    /// Memory that holds the ACPI tables.
+    pub const ACPIReclaimMemory: MemoryType = MemoryType(9);
+    /// Address space reserved for use by the firmware.
+    pub const ACPIMemoryNVS: MemoryType = MemoryType(10);
+    /// Memory-mapped IO that the runtime services need a virtual mapping for.
+    pub const MemoryMappedIO: MemoryType = MemoryType(11);
+    /// Memory-mapped IO region that is translated to IO cycles by the processor.
+    pub const MemoryMappedIOPortSpace: MemoryType = MemoryType(12);
+    /// Address space reserved by the firmware for code that is part of the processor.
+    pub const PalCode: MemoryType = MemoryType(13);
+    /// Conventional memory that also supports byte-addressable non-volatility.
+    pub const PersistentMemory: MemoryType = MemoryType(14);
+
+    /// Returns the OEM specific memory type with the given number.
+    ///
+    /// Returns `None` if the number is outside of the range reserved for OEMs.
+    pub fn oem_specific(number: u32) -> Option<MemoryType> {
+        if number < OS_LOADER_SPECIFIC_START - OEM_SPECIFIC_START {
+            Some(MemoryType(OEM_SPECIFIC_START + number))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the OS loader specific memory type with the given number.
+    ///
+    /// Returns `None` if the number is outside of the range reserved for OS loaders.
+    pub fn os_loader_specific(number: u32) -> Option<MemoryType> {
+        number.checked_add(OS_LOADER_SPECIFIC_START).map(MemoryType)
+    }
+
+    /// Returns the BIOS E820 type that corresponds to this memory type.
+    ///
+    /// Memory that is used by the boot services or the loader is reported as usable, since it
+    /// can be reused after `ExitBootServices`.
+    pub fn e820_type(self) -> u32 {
+        match NamedMemoryType::from(self) {
+            NamedMemoryType::LoaderCode
+            | NamedMemoryType::LoaderData
+            | NamedMemoryType::BootServicesCode
+            | NamedMemoryType::BootServicesData
+            | NamedMemoryType::ConventionalMemory => E820_RAM,
+            NamedMemoryType::ACPIReclaimMemory => E820_ACPI,
+            NamedMemoryType::ACPIMemoryNVS => E820_NVS,
+            NamedMemoryType::UnusableMemory => E820_UNUSABLE,
+            NamedMemoryType::PersistentMemory => E820_PMEM,
+            _ => E820_RESERVED,
+        }
+    }
+}
+
+impl TryFrom<NamedMemoryType> for MemoryType {
+    type Error = Error;
 
-        MemoryType(inner_num)
+    /// Fails with `Error::InvalidParameter` if an OEM or OS loader specific number is out of
+    /// range.
+    fn try_from(named_memory_type: NamedMemoryType) -> Result<MemoryType, Error> {
+        Ok(match named_memory_type {
+            NamedMemoryType::ReservedMemoryType => MemoryType::ReservedMemoryType,
+            NamedMemoryType::LoaderCode => MemoryType::LoaderCode,
+            NamedMemoryType::LoaderData => MemoryType::LoaderData,
+            NamedMemoryType::BootServicesCode => MemoryType::BootServicesCode,
+            NamedMemoryType::BootServicesData => MemoryType::BootServicesData,
+            NamedMemoryType::RuntimeServicesCode => MemoryType::RuntimeServicesCode,
+            NamedMemoryType::RuntimeServicesData => MemoryType::RuntimeServicesData,
+            NamedMemoryType::ConventionalMemory => MemoryType::ConventionalMemory,
+            NamedMemoryType::UnusableMemory => MemoryType::UnusableMemory,
+            NamedMemoryType::ACPIReclaimMemory => MemoryType::ACPIReclaimMemory,
+            NamedMemoryType::ACPIMemoryNVS => MemoryType::ACPIMemoryNVS,
+            NamedMemoryType::MemoryMappedIO => MemoryType::MemoryMappedIO,
+            NamedMemoryType::MemoryMappedIOPortSpace => MemoryType::MemoryMappedIOPortSpace,
+            NamedMemoryType::PalCode => MemoryType::PalCode,
+            NamedMemoryType::PersistentMemory => MemoryType::PersistentMemory,
+            NamedMemoryType::UnknownMemoryType(num) => MemoryType(num),
+            NamedMemoryType::OEMSpecific(num) => {
+                MemoryType::oem_specific(num).ok_or(Error::InvalidParameter)?
+            }
+            NamedMemoryType::OSLoaderSpecific(num) => {
+                MemoryType::os_loader_specific(num).ok_or(Error::InvalidParameter)?
+            }
+        })
     }
 }
 
 /// Represents the different types memory can have.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u32)]
 pub enum NamedMemoryType {
     /// Not usable.
@@ -124,6 +485,13 @@ pub enum NamedMemoryType {
     OSLoaderSpecific(u32),
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for MemoryType {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}", NamedMemoryType::from(*self));
+    }
+}
+
 impl From<MemoryType> for NamedMemoryType {
     fn from(memory_type: MemoryType) -> NamedMemoryType {
         match memory_type.0 {
@@ -201,6 +569,121 @@ bitflags! {
     }
 }
 
+/// The version of the memory attributes table described by this crate.
+pub const MEMORY_ATTRIBUTES_TABLE_VERSION: u32 = 2;
+
+/// Set in the `Flags` of the memory attributes table if the runtime code is compiled with forward
+/// control flow guard instructions.
+pub const MEMORY_ATTRIBUTES_FLAGS_RT_FORWARD_CONTROL_FLOW_GUARD: u32 = 0x1;
+
+/// Describes the memory protections of the runtime services memory regions.
+///
+/// This table is installed as a configuration table. The descriptors directly follow the header and
+/// split the `RuntimeServicesCode` and `RuntimeServicesData` regions of the memory map into
+/// parts, whose `Attribute` field only contains the `RUNTIME`, `RO` and `XP` attributes.
+#[derive(Debug)]
+#[repr(C)]
+pub struct MemoryAttributesTable {
+    /// The version of this table.
+    pub Version: u32,
+    /// The number of descriptors following the header.
+    pub NumberOfEntries: u32,
+    /// The size of a single descriptor in bytes.
+    pub DescriptorSize: u32,
+    /// Additional information about the runtime memory regions.
+    ///
+    /// This field was reserved in version 1 of the table.
+    pub Flags: u32,
+}
+
+impl MemoryAttributesTable {
+    /// Returns an iterator over the descriptors in the table.
+    ///
+    /// If the descriptors are smaller than `MemoryDescriptor`, the quirk is reported and the
+    /// iterator is empty in the permissive quirk mode.
+    pub fn iter(&self) -> MemoryMapIterator {
+        self.try_iter().unwrap_or(MemoryMapIterator {
+            iter: [].chunks(size_of::<MemoryDescriptor>()),
+            descriptor_size: size_of::<MemoryDescriptor>(),
+            version: MEMORY_DESCRIPTOR_VERSION,
+        })
+    }
+
+    /// Returns an iterator over the descriptors in the table.
+    ///
+    /// If the descriptors are smaller than `MemoryDescriptor`, the quirk is reported and
+    /// `Error::UndersizedDescriptor` is returned in the permissive quirk mode.
+    pub fn try_iter(&self) -> Result<MemoryMapIterator, Error> {
+        let descriptor_size = self.DescriptorSize as usize;
+
+        if descriptor_size < size_of::<MemoryDescriptor>() {
+            return Err(quirk::report(Quirk::UndersizedDescriptor {
+                size: descriptor_size,
+            }));
+        }
+
+        // This is safe under the assumption that the firmware placed the descriptors after the header.
+        let buffer = unsafe {
+            slice::from_raw_parts(
+                (self as *const MemoryAttributesTable).add(1) as *const u8,
+                self.NumberOfEntries as usize * descriptor_size,
+            )
+        };
+
+        Ok(MemoryMapIterator {
+            iter: buffer.chunks(descriptor_size),
+            descriptor_size,
+            // The table does not report the descriptor version, so the known layout is assumed.
+            version: MEMORY_DESCRIPTOR_VERSION,
+        })
+    }
+
+    /// Returns the protection attributes of the runtime region containing the given address.
+    pub fn attributes_for(&self, address: PhysicalAddress) -> Option<MemoryAttributes> {
+        self.iter()
+            .find(|descriptor| descriptor.contains(address))
+            .map(|descriptor| descriptor.Attribute)
+    }
+
+    /// Returns true if the runtime code uses forward control flow guard instructions.
+    pub fn has_forward_control_flow_guard(&self) -> bool {
+        self.Version >= 2 && self.Flags & MEMORY_ATTRIBUTES_FLAGS_RT_FORWARD_CONTROL_FLOW_GUARD != 0
+    }
+}
+
+/// The E820 type of usable memory.
+pub const E820_RAM: u32 = 1;
+/// The E820 type of reserved memory.
+pub const E820_RESERVED: u32 = 2;
+/// The E820 type of memory that holds ACPI tables and can be reclaimed after reading them.
+pub const E820_ACPI: u32 = 3;
+/// The E820 type of memory that is reserved for the firmware and must be preserved across
+/// sleep states.
+pub const E820_NVS: u32 = 4;
+/// The E820 type of memory in which errors have been detected.
+pub const E820_UNUSABLE: u32 = 5;
+/// The E820 type of persistent memory.
+pub const E820_PMEM: u32 = 7;
+
+/// An entry in a BIOS E820 memory map.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C, packed)]
+pub struct E820Entry {
+    /// The physical address of the first byte of the region.
+    pub Address: u64,
+    /// The size of the region in bytes.
+    pub Size: u64,
+    /// The E820 type of the region.
+    pub Type: u32,
+}
+
+/// The type of the Multiboot2 memory map tag.
+pub const MULTIBOOT2_TAG_TYPE_MMAP: u32 = 6;
+/// The size of an entry in the Multiboot2 memory map tag.
+pub const MULTIBOOT2_MMAP_ENTRY_SIZE: u32 = 24;
+/// The version of the entries in the Multiboot2 memory map tag.
+pub const MULTIBOOT2_MMAP_ENTRY_VERSION: u32 = 0;
+
 /// Represents a memory map.
 #[derive(Debug)]
 pub struct MemoryMap {
@@ -218,6 +701,8 @@ pub struct MemoryMap {
     pub(crate) descriptor_size: usize,
     /// The version of the memory descriptors.
     pub(crate) version: u32,
+    /// Whether the descriptors are known to be sorted by their physical address.
+    pub(crate) sorted: bool,
 }
 
 impl MemoryMap {
@@ -231,6 +716,24 @@ impl MemoryMap {
         self.len() == 0
     }
 
+    /// Returns the version of the memory descriptors.
+    pub fn descriptor_version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the size of a single memory descriptor in bytes.
+    pub fn descriptor_size(&self) -> usize {
+        self.descriptor_size
+    }
+
+    /// Returns true if the descriptor version is exactly the one described by this crate.
+    ///
+    /// Descriptors of newer versions can still be accessed, but any fields added by those
+    /// versions are not available.
+    pub fn is_descriptor_version_known(&self) -> bool {
+        self.version == MEMORY_DESCRIPTOR_VERSION
+    }
+
     /// Returns a slice to the underlying buffer.
     ///
     /// This is mainly useful to get the address and size of the buffer
@@ -254,6 +757,9 @@ impl MemoryMap {
 
     /// Returns an iterator over the `MemoryDescriptor`s in the `MemoryMap`.
     pub fn iter_mut(&mut self) -> MemoryMapIteratorMut {
+        // The descriptors might be modified, so the order cannot be relied upon anymore.
+        self.sorted = false;
+
         // This is safe under the assumption that the buffer has the specified size and is valid.
         let buffer = unsafe { slice::from_raw_parts_mut(self.buffer as *mut u8, self.size) };
 
@@ -264,18 +770,797 @@ impl MemoryMap {
         }
     }
 
+    /// Returns an iterator over the regions of the given memory type.
+    pub fn regions_of(&self, memory_type: MemoryType) -> MemoryRegions {
+        MemoryRegions {
+            iter: self.iter(),
+            filter: RegionFilter::Type(memory_type),
+        }
+    }
+
+    /// Returns an iterator over the regions that are free to use.
+    ///
+    /// These are the `ConventionalMemory` regions and, if `include_boot_services` is true, the
+    /// `BootServicesCode` and `BootServicesData` regions, which are free after `ExitBootServices`.
+    pub fn usable_regions(&self, include_boot_services: bool) -> MemoryRegions {
+        MemoryRegions {
+            iter: self.iter(),
+            filter: RegionFilter::Usable {
+                include_boot_services,
+            },
+        }
+    }
+
+    /// Returns the descriptor with the most pages of the given memory type.
+    ///
+    /// If `memory_type` is `None`, `ConventionalMemory` is used.
+    /// If multiple descriptors have the same size, the first one is returned.
+    pub fn largest_region(&self, memory_type: Option<MemoryType>) -> Option<&MemoryDescriptor> {
+        let memory_type = memory_type.unwrap_or(MemoryType::ConventionalMemory);
+
+        let mut largest: Option<&MemoryDescriptor> = None;
+
+        for descriptor in self
+            .iter()
+            .filter(|descriptor| descriptor.Type == memory_type)
+        {
+            match largest {
+                Some(largest) if largest.NumberOfPages >= descriptor.NumberOfPages => (),
+                _ => largest = Some(descriptor),
+            }
+        }
+
+        largest
+    }
+
+    /// Copies the memory map into the given buffer.
+    ///
+    /// The copy does not depend on the pages allocated by the firmware, so these can be freed
+    /// afterwards. The descriptors are placed at the first suitably aligned offset of the buffer.
+    ///
+    /// Returns `Error::BufferTooSmall` if the buffer cannot hold all descriptors.
+    pub fn copy_into<'a>(&self, buffer: &'a mut [u8]) -> Result<MemoryMapOwned<'a>, Error> {
+        let buffer = descriptor_buffer(buffer, self.size)?;
+
+        // This is safe under the assumption that the memory map buffer has the specified size and is valid.
+        buffer
+            .copy_from_slice(unsafe { slice::from_raw_parts(self.buffer as *const u8, self.size) });
+
+        Ok(MemoryMapOwned {
+            buffer,
+            descriptor_size: self.descriptor_size,
+            version: self.version,
+        })
+    }
+
+    /// Compares this memory map to a later snapshot.
+    ///
+    /// Regions are matched by their physical start address. The result can be printed to see
+    /// which regions were added, removed or changed in `other`.
+    pub fn diff<'a>(&'a self, other: &'a MemoryMap) -> MemoryMapDiff<'a> {
+        MemoryMapDiff {
+            old: self,
+            new: other,
+        }
+    }
+
+    /// Assigns virtual addresses to the runtime regions and collects them for `SetVirtualAddressMap`.
+    ///
+    /// The `VirtualStart` of every region with the `RUNTIME` attribute is set according to
+    /// `policy`, both in this memory map and in the returned copy. The copy only contains the
+    /// runtime regions and is placed at the first suitably aligned offset of the buffer.
+    ///
+    /// Returns `Error::BufferTooSmall` if the buffer cannot hold all runtime descriptors and
+    /// `Error::InvalidParameter` if an offset mapping would exceed the address space. In the
+    /// latter case the regions before the failing one already have their new `VirtualStart`.
+    pub fn build_virtual_map<'a>(
+        &mut self,
+        mut policy: VirtualMappingPolicy,
+        buffer: &'a mut [u8],
+    ) -> Result<MemoryMapOwned<'a>, Error> {
+        let count = self
+            .iter()
+            .filter(|descriptor| descriptor.Attribute.contains(MemoryAttributes::RUNTIME))
+            .count();
+        let buffer = descriptor_buffer(buffer, count * self.descriptor_size)?;
+        let mut chunks = buffer.chunks_mut(self.descriptor_size);
+
+        for i in 0..self.len() {
+            if !self
+                .descriptor(i)
+                .Attribute
+                .contains(MemoryAttributes::RUNTIME)
+            {
+                continue;
+            }
+
+            let virtual_start = policy.virtual_start(self.descriptor(i))?;
+            self.descriptor_mut(i).VirtualStart = virtual_start;
+
+            let chunk = chunks
+                .next()
+                .expect("The runtime descriptors were counted before.");
+
+            // This is safe, because the index is in bounds and the chunk has the size of a descriptor.
+            chunk.copy_from_slice(unsafe {
+                slice::from_raw_parts(
+                    (self.buffer as *const u8).add(i * self.descriptor_size),
+                    self.descriptor_size,
+                )
+            });
+        }
+
+        Ok(MemoryMapOwned {
+            buffer,
+            descriptor_size: self.descriptor_size,
+            version: self.version,
+        })
+    }
+
+    /// Returns the descriptor of the region that contains the given physical address.
+    ///
+    /// If the memory map is sorted, a binary search is used, otherwise all descriptors are
+    /// searched.
+    pub fn descriptor_for(&self, address: PhysicalAddress) -> Option<&MemoryDescriptor> {
+        if !self.sorted {
+            return self.iter().find(|descriptor| descriptor.contains(address));
+        }
+
+        // Find the first descriptor that starts after the address.
+        let mut low = 0;
+        let mut high = self.len();
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+
+            if self.descriptor(middle).PhysicalStart.0 <= address.0 {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        // Only the descriptor before that one can contain the address.
+        if low == 0 {
+            return None;
+        }
+
+        Some(self.descriptor(low - 1)).filter(|descriptor| descriptor.contains(address))
+    }
+
+    /// Writes the memory map as BIOS E820 entries into the given buffer.
+    ///
+    /// Adjacent regions of the same E820 type are merged into one entry, so the memory map should
+    /// be sorted first to produce the smallest result.
+    ///
+    /// Returns the number of entries written or `Error::BufferTooSmall` if the buffer cannot
+    /// hold all entries.
+    pub fn write_e820(&self, entries: &mut [E820Entry]) -> Result<usize, Error> {
+        let mut count = 0;
+
+        for descriptor in self.iter() {
+            let address = descriptor.PhysicalStart.0;
+            let size = descriptor.byte_len();
+            let entry_type = descriptor.Type.e820_type();
+
+            if count > 0 {
+                let previous = &mut entries[count - 1];
+
+                // Entries whose merged size would overflow are written separately.
+                let adjacent = previous.Address.checked_add(previous.Size) == Some(address);
+                let merged_size = previous.Size.checked_add(size);
+
+                match merged_size {
+                    Some(merged_size) if previous.Type == entry_type && adjacent => {
+                        previous.Size = merged_size;
+                        continue;
+                    }
+                    _ => (),
+                }
+            }
+
+            let entry = entries.get_mut(count).ok_or(Error::BufferTooSmall)?;
+            *entry = E820Entry {
+                Address: address,
+                Size: size,
+                Type: entry_type,
+            };
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Writes the memory map as a Multiboot2 memory map tag into the given buffer.
+    ///
+    /// Every descriptor is written as one entry. The memory types are translated like for
+    /// `write_e820`, except that persistent memory is reported as reserved, since Multiboot2
+    /// has no type for it. The tag does not include any padding after the last entry.
+    ///
+    /// Returns the size of the tag in bytes or `Error::BufferTooSmall` if the buffer cannot
+    /// hold the tag.
+    pub fn write_multiboot2_tag(&self, buffer: &mut [u8]) -> Result<usize, Error> {
+        const HEADER_SIZE: usize = 16;
+
+        let size = HEADER_SIZE + self.len() * MULTIBOOT2_MMAP_ENTRY_SIZE as usize;
+
+        if buffer.len() < size {
+            return Err(Error::BufferTooSmall);
+        }
+
+        buffer[0..4].copy_from_slice(&MULTIBOOT2_TAG_TYPE_MMAP.to_le_bytes());
+        buffer[4..8].copy_from_slice(&(size as u32).to_le_bytes());
+        buffer[8..12].copy_from_slice(&MULTIBOOT2_MMAP_ENTRY_SIZE.to_le_bytes());
+        buffer[12..16].copy_from_slice(&MULTIBOOT2_MMAP_ENTRY_VERSION.to_le_bytes());
+
+        let entries = buffer[HEADER_SIZE..size].chunks_mut(MULTIBOOT2_MMAP_ENTRY_SIZE as usize);
+
+        for (entry, descriptor) in entries.zip(self.iter()) {
+            let entry_type = match descriptor.Type.e820_type() {
+                E820_PMEM => E820_RESERVED,
+                entry_type => entry_type,
+            };
+
+            entry[0..8].copy_from_slice(&descriptor.PhysicalStart.0.to_le_bytes());
+            entry[8..16].copy_from_slice(&descriptor.byte_len().to_le_bytes());
+            entry[16..20].copy_from_slice(&entry_type.to_le_bytes());
+            entry[20..24].copy_from_slice(&0u32.to_le_bytes());
+        }
+
+        Ok(size)
+    }
+
+    /// Returns true if the descriptors are known to be sorted by their physical address.
+    pub fn is_sorted(&self) -> bool {
+        self.sorted
+    }
+
+    /// Sorts the descriptors in the memory map by their physical address.
+    ///
+    /// The firmware is not required to return the memory map in any particular order.
+    /// The sort happens in place and does not allocate.
+    pub fn sort(&mut self) {
+        for i in 1..self.len() {
+            let mut j = i;
+
+            while j > 0
+                && self.descriptor(j - 1).PhysicalStart.0 > self.descriptor(j).PhysicalStart.0
+            {
+                self.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+
+        self.sorted = true;
+    }
+
+    /// Merges physically adjacent descriptors with the same type and attributes.
+    ///
+    /// The memory map is sorted first, if it isn't already. The remaining descriptors are moved
+    /// to the start of the buffer and the size of the memory map is updated accordingly.
+    ///
+    /// After `ExitBootServices` the memory map usually contains many small adjacent regions
+    /// that can be merged.
+    pub fn coalesce(&mut self) {
+        if !self.sorted {
+            self.sort();
+        }
+
+        if self.is_empty() {
+            return;
+        }
+
+        // The index of the last descriptor that is kept.
+        let mut last = 0;
+
+        for i in 1..self.len() {
+            let merged_pages = self
+                .descriptor(last)
+                .NumberOfPages
+                .checked_add(self.descriptor(i).NumberOfPages);
+
+            match merged_pages {
+                Some(pages) if can_coalesce(self.descriptor(last), self.descriptor(i)) => {
+                    self.descriptor_mut(last).NumberOfPages = pages;
+                }
+                _ => {
+                    last += 1;
+                    self.copy_descriptor(i, last);
+                }
+            }
+        }
+
+        self.size = (last + 1) * self.descriptor_size;
+    }
+
+    /// Returns the descriptor at the given index.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    fn descriptor(&self, index: usize) -> &MemoryDescriptor {
+        assert!(index < self.len(), "The descriptor index is out of bounds.");
+
+        // This is safe, because the index is in bounds.
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                (self.buffer as *const u8).add(index * self.descriptor_size),
+                self.descriptor_size,
+            )
+        };
+
+        descriptor_ref(bytes, self.version)
+    }
+
+    /// Returns the descriptor at the given index mutably.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    fn descriptor_mut(&mut self, index: usize) -> &mut MemoryDescriptor {
+        assert!(index < self.len(), "The descriptor index is out of bounds.");
+
+        // This is safe, because the index is in bounds.
+        let bytes = unsafe {
+            slice::from_raw_parts_mut(
+                (self.buffer as *mut u8).add(index * self.descriptor_size),
+                self.descriptor_size,
+            )
+        };
+
+        descriptor_mut(bytes, self.version)
+    }
+
+    /// Copies the descriptor at index `from` over the descriptor at index `to`.
+    ///
+    /// # Panics
+    /// Panics if the indices are out of bounds.
+    fn copy_descriptor(&mut self, from: usize, to: usize) {
+        assert!(
+            from < self.len() && to < self.len(),
+            "The descriptor index is out of bounds."
+        );
+
+        if from != to {
+            // This is safe, because the indices are in bounds and different, so the descriptors don't overlap.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    (self.buffer as *const u8).add(from * self.descriptor_size),
+                    (self.buffer as *mut u8).add(to * self.descriptor_size),
+                    self.descriptor_size,
+                );
+            }
+        }
+    }
+
+    /// Swaps the descriptors at the given indices.
+    ///
+    /// The whole descriptor is swapped, including any data beyond `MemoryDescriptor` in
+    /// newer descriptor versions.
+    ///
+    /// # Panics
+    /// Panics if the indices are out of bounds.
+    fn swap(&mut self, a: usize, b: usize) {
+        assert!(
+            a < self.len() && b < self.len(),
+            "The descriptor index is out of bounds."
+        );
+
+        if a != b {
+            // This is safe, because the indices are in bounds and different, so the descriptors don't overlap.
+            unsafe {
+                ptr::swap_nonoverlapping(
+                    (self.buffer as *mut u8).add(a * self.descriptor_size),
+                    (self.buffer as *mut u8).add(b * self.descriptor_size),
+                    self.descriptor_size,
+                );
+            }
+        }
+    }
+
     /// Drops this memory map, deallocating the underlying buffer.
     ///
     /// # Safety
     /// This function assumes that the boot services are still active:
     /// Make sure not to call it after calling `ExitBootServices`.
-    pub unsafe fn drop(self, boot_services: &'static BootServices) -> Result<(), Error> {
+    pub unsafe fn drop(self, boot_services: &BootServices) -> Result<(), Error> {
         boot_services.free_pages(self.buffer as *const u8, self.alloc_size)?;
 
         Ok(())
     }
 }
 
+/// A copy of a memory map in a buffer provided by the caller.
+///
+/// Unlike `MemoryMap`, this does not refer to memory allocated by the firmware and stays valid
+/// after `ExitBootServices` and after the original memory map was freed.
+#[derive(Debug)]
+pub struct MemoryMapOwned<'a> {
+    /// The descriptors of the memory map.
+    buffer: &'a mut [u8],
+    /// The size of a single memory descriptor within the buffer.
+    descriptor_size: usize,
+    /// The version of the memory descriptors.
+    version: u32,
+}
+
+impl<'a> MemoryMapOwned<'a> {
+    /// The amount of entries in the memory map.
+    pub fn len(&self) -> usize {
+        self.buffer.len() / self.descriptor_size
+    }
+
+    /// Returns true if the memory map does not have any entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the size of a single memory descriptor in bytes.
+    pub fn descriptor_size(&self) -> usize {
+        self.descriptor_size
+    }
+
+    /// Returns the version of the memory descriptors.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the bytes of the descriptors.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns an iterator over the `MemoryDescriptor`s in the memory map.
+    pub fn iter(&self) -> MemoryMapIterator {
+        MemoryMapIterator {
+            iter: self.buffer.chunks(self.descriptor_size),
+            descriptor_size: self.descriptor_size,
+            version: self.version,
+        }
+    }
+
+    /// Returns an iterator over the `MemoryDescriptor`s in the memory map.
+    pub fn iter_mut(&mut self) -> MemoryMapIteratorMut {
+        MemoryMapIteratorMut {
+            iter: self.buffer.chunks_mut(self.descriptor_size),
+            descriptor_size: self.descriptor_size,
+            version: self.version,
+        }
+    }
+}
+
+/// Returns the part of `buffer` that is aligned for memory descriptors and `size` bytes large.
+fn descriptor_buffer(buffer: &mut [u8], size: usize) -> Result<&mut [u8], Error> {
+    let offset = buffer.as_ptr().align_offset(align_of::<MemoryDescriptor>());
+
+    if offset > buffer.len() || buffer.len() - offset < size {
+        return Err(Error::BufferTooSmall);
+    }
+
+    Ok(&mut buffer[offset..offset + size])
+}
+
+/// Determines the virtual addresses of the runtime regions.
+pub enum VirtualMappingPolicy<'a> {
+    /// Every region is mapped at its physical address.
+    Identity,
+    /// Every region is mapped at its physical address plus the given offset.
+    ///
+    /// Regions whose address would exceed the address space cannot be mapped.
+    Offset(u64),
+    /// The virtual address of every region is determined by the given function.
+    Custom(&'a mut dyn FnMut(&MemoryDescriptor) -> VirtualAddress),
+}
+
+impl<'a> VirtualMappingPolicy<'a> {
+    /// Returns the virtual address for the given region.
+    ///
+    /// Returns `Error::InvalidParameter` if an offset mapping would exceed the address space.
+    fn virtual_start(&mut self, descriptor: &MemoryDescriptor) -> Result<VirtualAddress, Error> {
+        match self {
+            VirtualMappingPolicy::Identity => Ok(VirtualAddress(descriptor.PhysicalStart.0)),
+            VirtualMappingPolicy::Offset(offset) => descriptor
+                .PhysicalStart
+                .0
+                .checked_add(*offset)
+                .map(VirtualAddress)
+                .ok_or(Error::InvalidParameter),
+            VirtualMappingPolicy::Custom(function) => Ok(function(descriptor)),
+        }
+    }
+}
+
+/// Returns true if `second` directly follows `first` and both can be merged into one descriptor.
+///
+/// Descriptors whose merged size or end would overflow are not merged.
+fn can_coalesce(first: &MemoryDescriptor, second: &MemoryDescriptor) -> bool {
+    let length = match first.NumberOfPages.checked_mul(PAGE_SIZE as u64) {
+        Some(length) => length,
+        None => return false,
+    };
+    let merged_end = first
+        .NumberOfPages
+        .checked_add(second.NumberOfPages)
+        .and_then(|pages| pages.checked_mul(PAGE_SIZE as u64))
+        .and_then(|length| first.PhysicalStart.0.checked_add(length));
+
+    let physically_adjacent =
+        first.PhysicalStart.0.checked_add(length) == Some(second.PhysicalStart.0);
+    // Virtual addresses are usually not assigned yet, in which case they are both zero.
+    let virtually_adjacent = (first.VirtualStart.0 == 0 && second.VirtualStart.0 == 0)
+        || first.VirtualStart.0.checked_add(length) == Some(second.VirtualStart.0);
+
+    first.Type == second.Type
+        && first.Attribute == second.Attribute
+        && merged_end.is_some()
+        && physically_adjacent
+        && virtually_adjacent
+}
+
+/// The differences between two memory maps.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryMapDiff<'a> {
+    /// The earlier memory map.
+    old: &'a MemoryMap,
+    /// The later memory map.
+    new: &'a MemoryMap,
+}
+
+impl<'a> MemoryMapDiff<'a> {
+    /// Returns an iterator over the changed regions.
+    ///
+    /// Removed and changed regions are returned first, followed by the added regions.
+    pub fn iter(&self) -> MemoryMapChanges<'a> {
+        MemoryMapChanges {
+            old: self.old,
+            new: self.new,
+            old_iter: self.old.iter(),
+            new_iter: self.new.iter(),
+        }
+    }
+
+    /// Returns true if both memory maps describe the same regions.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl<'a> fmt::Display for MemoryMapDiff<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in self.iter() {
+            writeln!(f, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A difference between two memory maps.
+#[derive(Clone, Copy, Debug)]
+pub enum RegionChange<'a> {
+    /// The region only exists in the later memory map.
+    Added(&'a MemoryDescriptor),
+    /// The region only exists in the earlier memory map.
+    Removed(&'a MemoryDescriptor),
+    /// The region starts at the same address in both memory maps, but differs otherwise.
+    Changed {
+        /// The region in the earlier memory map.
+        old: &'a MemoryDescriptor,
+        /// The region in the later memory map.
+        new: &'a MemoryDescriptor,
+    },
+}
+
+impl<'a> fmt::Display for RegionChange<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegionChange::Added(descriptor) => write!(f, "+ {}", DisplayDescriptor(descriptor)),
+            RegionChange::Removed(descriptor) => {
+                write!(f, "- {}", DisplayDescriptor(descriptor))
+            }
+            RegionChange::Changed { old, new } => write!(
+                f,
+                "~ {} -> {}",
+                DisplayDescriptor(old),
+                DisplayDescriptor(new)
+            ),
+        }
+    }
+}
+
+/// Formats a descriptor on a single line.
+struct DisplayDescriptor<'a>(&'a MemoryDescriptor);
+
+impl<'a> fmt::Display for DisplayDescriptor<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}..{} {:?} ({} pages, {}, {:?})",
+            self.0.PhysicalStart,
+            self.0.physical_end(),
+            NamedMemoryType::from(self.0.Type),
+            self.0.NumberOfPages,
+            ByteSize(self.0.byte_len()),
+            self.0.Attribute
+        )
+    }
+}
+
+/// An iterator over the differences between two memory maps.
+pub struct MemoryMapChanges<'a> {
+    /// The earlier memory map.
+    old: &'a MemoryMap,
+    /// The later memory map.
+    new: &'a MemoryMap,
+    /// The remaining regions of the earlier memory map.
+    old_iter: MemoryMapIterator<'a>,
+    /// The remaining regions of the later memory map.
+    new_iter: MemoryMapIterator<'a>,
+}
+
+impl<'a> Iterator for MemoryMapChanges<'a> {
+    type Item = RegionChange<'a>;
+
+    fn next(&mut self) -> Option<RegionChange<'a>> {
+        for old in &mut self.old_iter {
+            match find_by_start(self.new, old.PhysicalStart) {
+                None => return Some(RegionChange::Removed(old)),
+                Some(new)
+                    if new.Type != old.Type
+                        || new.NumberOfPages != old.NumberOfPages
+                        || new.Attribute != old.Attribute =>
+                {
+                    return Some(RegionChange::Changed { old, new })
+                }
+                Some(_) => (),
+            }
+        }
+
+        for new in &mut self.new_iter {
+            if find_by_start(self.old, new.PhysicalStart).is_none() {
+                return Some(RegionChange::Added(new));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the descriptor of the memory map that starts at the given address.
+fn find_by_start(memory_map: &MemoryMap, start: PhysicalAddress) -> Option<&MemoryDescriptor> {
+    memory_map
+        .iter()
+        .find(|descriptor| descriptor.PhysicalStart == start)
+}
+
+/// Selects the regions returned by `MemoryRegions`.
+#[derive(Clone, Copy, Debug)]
+enum RegionFilter {
+    /// Only regions of the given type.
+    Type(MemoryType),
+    /// Only regions that are free to use.
+    Usable {
+        /// Whether boot services regions count as free.
+        include_boot_services: bool,
+    },
+}
+
+impl RegionFilter {
+    /// Returns true if regions of the given memory type are selected.
+    fn matches(self, memory_type: MemoryType) -> bool {
+        match self {
+            RegionFilter::Type(selected_type) => memory_type == selected_type,
+            RegionFilter::Usable {
+                include_boot_services,
+            } => {
+                memory_type == MemoryType::ConventionalMemory
+                    || (include_boot_services
+                        && (memory_type == MemoryType::BootServicesCode
+                            || memory_type == MemoryType::BootServicesData))
+            }
+        }
+    }
+}
+
+/// An iterator over selected regions of a memory map.
+///
+/// Each region is returned as its physical start address, its number of pages and its attributes.
+pub struct MemoryRegions<'a> {
+    /// The descriptors of the memory map.
+    iter: MemoryMapIterator<'a>,
+    /// Selects which regions are returned.
+    filter: RegionFilter,
+}
+
+impl<'a> Iterator for MemoryRegions<'a> {
+    type Item = (PhysicalAddress, u64, MemoryAttributes);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let filter = self.filter;
+
+        self.iter
+            .find(|descriptor| filter.matches(descriptor.Type))
+            .map(|descriptor| {
+                (
+                    descriptor.PhysicalStart,
+                    descriptor.NumberOfPages,
+                    descriptor.Attribute,
+                )
+            })
+    }
+}
+
+#[cfg(feature = "x86_64")]
+impl<'a> MemoryRegions<'a> {
+    /// Returns the regions as ranges of frames.
+    ///
+    /// # Panics
+    /// Panics if a region does not fit into the 52 bit physical address space.
+    pub fn frame_ranges(self) -> impl Iterator<Item = PhysFrameRange> + 'a {
+        self.map(|(start, pages, _)| {
+            let start = PhysFrame::containing_address(PhysAddr::new(start.0));
+
+            PhysFrame::range(start, start + pages)
+        })
+    }
+}
+
+/// The known layouts of memory descriptors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DescriptorLayout {
+    /// The layout of `MemoryDescriptor`, introduced with descriptor version 1.
+    V1,
+}
+
+impl DescriptorLayout {
+    /// Returns the layout used to access descriptors of the given version.
+    ///
+    /// Newer versions than `MEMORY_DESCRIPTOR_VERSION` are accessed with the newest known
+    /// layout, since they extend it.
+    fn for_version(version: u32) -> Option<DescriptorLayout> {
+        match version {
+            0 => None,
+            _ => Some(DescriptorLayout::V1),
+        }
+    }
+}
+
+/// Interprets the bytes of a descriptor with the given version as a `MemoryDescriptor`.
+///
+/// # Panics
+/// Panics if the version is unknown or the bytes are too few for the layout.
+fn descriptor_ref(bytes: &[u8], version: u32) -> &MemoryDescriptor {
+    match DescriptorLayout::for_version(version) {
+        Some(DescriptorLayout::V1) => {
+            assert!(
+                bytes.len() >= size_of::<MemoryDescriptor>(),
+                "The size of the memory descriptor is smaller than the standard says."
+            );
+
+            // This is safe, because of the assertion above.
+            unsafe { &*(bytes.as_ptr() as *const MemoryDescriptor) }
+        }
+        None => panic!("The memory descriptor version {} is unknown.", version),
+    }
+}
+
+/// Interprets the bytes of a descriptor with the given version as a mutable `MemoryDescriptor`.
+///
+/// # Panics
+/// Panics if the version is unknown or the bytes are too few for the layout.
+fn descriptor_mut(bytes: &mut [u8], version: u32) -> &mut MemoryDescriptor {
+    match DescriptorLayout::for_version(version) {
+        Some(DescriptorLayout::V1) => {
+            assert!(
+                bytes.len() >= size_of::<MemoryDescriptor>(),
+                "The size of the memory descriptor is smaller than the standard says."
+            );
+
+            // This is safe, because of the assertion above.
+            unsafe { &mut *(bytes.as_mut_ptr() as *mut MemoryDescriptor) }
+        }
+        None => panic!("The memory descriptor version {} is unknown.", version),
+    }
+}
+
 /// An iterator over the memory map entries.
 pub struct MemoryMapIterator<'a> {
     /// The buffer where the contents of the memory map are located.
@@ -286,19 +1571,41 @@ pub struct MemoryMapIterator<'a> {
     version: u32,
 }
 
+impl<'a> MemoryMapIterator<'a> {
+    /// Iterates over the descriptors in `buffer`, such as a memory map passed on to a kernel.
+    ///
+    /// Returns `Error::IncompatibleVersion` if the descriptor version is unknown and
+    /// `Error::BadBufferSize` if the descriptors are too small or not aligned.
+    pub fn from_bytes(
+        buffer: &'a [u8],
+        descriptor_size: usize,
+        version: u32,
+    ) -> Result<MemoryMapIterator<'a>, Error> {
+        if DescriptorLayout::for_version(version).is_none() {
+            return Err(Error::IncompatibleVersion);
+        }
+        if descriptor_size < size_of::<MemoryDescriptor>()
+            || descriptor_size % align_of::<MemoryDescriptor>() != 0
+            || buffer.as_ptr().align_offset(align_of::<MemoryDescriptor>()) != 0
+        {
+            return Err(Error::BadBufferSize);
+        }
+
+        Ok(MemoryMapIterator {
+            iter: buffer.chunks(descriptor_size),
+            descriptor_size,
+            version,
+        })
+    }
+}
+
 impl<'a> Iterator for MemoryMapIterator<'a> {
     type Item = &'a MemoryDescriptor;
 
     fn next(&mut self) -> Option<&'a MemoryDescriptor> {
         if let Some(chunk) = self.iter.next() {
             if chunk.len() == self.descriptor_size {
-                debug_assert!(
-                    self.descriptor_size >= size_of::<MemoryDescriptor>(),
-                    "The size of the memory descriptor is smaller than the standard says."
-                );
-
-                // This is safe, because of the assertion above. That condition should hold according to the UEFI specification.
-                unsafe { Some(&*(chunk.as_ptr() as *const MemoryDescriptor)) }
+                Some(descriptor_ref(chunk, self.version))
             } else {
                 None
             }
@@ -332,13 +1639,7 @@ impl<'a> Iterator for MemoryMapIteratorMut<'a> {
     fn next(&mut self) -> Option<&'a mut MemoryDescriptor> {
         if let Some(chunk) = self.iter.next() {
             if chunk.len() == self.descriptor_size {
-                debug_assert!(
-                    self.descriptor_size >= size_of::<MemoryDescriptor>(),
-                    "The size of the memory descriptor is smaller than the standard says."
-                );
-
-                // This is safe, because of the assertion above. That condition should hold according to the UEFI specification.
-                unsafe { Some(&mut *(chunk.as_ptr() as *mut MemoryDescriptor)) }
+                Some(descriptor_mut(chunk, self.version))
             } else {
                 None
             }
@@ -0,0 +1,86 @@
+//! Provides services to retrieve and update the memory attributes of memory regions.
+//!
+//! This allows an image to mark its own memory regions as read-only or non-executable.
+
+use crate::{
+    guid::{Guid, MEMORY_ATTRIBUTE_PROTOCOL_GUID},
+    memory::{MemoryAttributes, PhysicalAddress},
+    protocol::Protocol,
+    status::{Error, Status},
+};
+
+/// Provides services to retrieve and update the memory attributes of memory regions.
+#[repr(C)]
+pub struct MemoryAttribute {
+    /// Retrieves the attributes of the memory region.
+    pub GetMemoryAttributes: eficall! { fn(
+        &MemoryAttribute,
+        BaseAddress: PhysicalAddress,
+        Length: u64,
+        Attributes: &mut u64,
+    ) -> Status },
+    /// Sets the attributes of the memory region.
+    pub SetMemoryAttributes: eficall! { fn(
+        &MemoryAttribute,
+        BaseAddress: PhysicalAddress,
+        Length: u64,
+        Attributes: u64,
+    ) -> Status },
+    /// Clears the attributes of the memory region.
+    pub ClearMemoryAttributes: eficall! { fn(
+        &MemoryAttribute,
+        BaseAddress: PhysicalAddress,
+        Length: u64,
+        Attributes: u64,
+    ) -> Status },
+}
+
+unsafe impl Protocol for MemoryAttribute {
+    const GUID: Guid = MEMORY_ATTRIBUTE_PROTOCOL_GUID;
+}
+
+impl MemoryAttribute {
+    /// Retrieves the attributes of the memory region.
+    ///
+    /// Only the `RP`, `XP` and `RO` attributes are reported. If the region is made up of
+    /// parts with different attributes, `Error::NoMapping` is returned.
+    pub fn get_memory_attributes(
+        &self,
+        base_address: PhysicalAddress,
+        length: u64,
+    ) -> Result<MemoryAttributes, Error> {
+        let mut attributes = 0;
+
+        (self.GetMemoryAttributes)(self, base_address, length, &mut attributes)?;
+
+        Ok(MemoryAttributes::from_bits_truncate(attributes))
+    }
+
+    /// Sets the attributes of the memory region.
+    ///
+    /// Only the `RP`, `XP` and `RO` attributes may be set.
+    pub fn set_memory_attributes(
+        &self,
+        base_address: PhysicalAddress,
+        length: u64,
+        attributes: MemoryAttributes,
+    ) -> Result<(), Error> {
+        (self.SetMemoryAttributes)(self, base_address, length, attributes.bits())?;
+
+        Ok(())
+    }
+
+    /// Clears the attributes of the memory region.
+    ///
+    /// Only the `RP`, `XP` and `RO` attributes may be cleared.
+    pub fn clear_memory_attributes(
+        &self,
+        base_address: PhysicalAddress,
+        length: u64,
+        attributes: MemoryAttributes,
+    ) -> Result<(), Error> {
+        (self.ClearMemoryAttributes)(self, base_address, length, attributes.bits())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+//! Tests the free memory before a kernel is started.
+//!
+//! Each `ConventionalMemory` region of the memory map is allocated at its address before it is
+//! tested, so memory used by the loader or the firmware is never overwritten. Regions that are
+//! allocated in the meantime are skipped. Failing addresses are reported to a callback and can
+//! be collected in `BadMemory`, which `Handoff::unusable` reports as `UnusableMemory` to the
+//! kernel.
+//!
+//! Memory is accessed at its physical address, which relies on the identity mapping set up by
+//! the firmware.
+
+use core::{ops::Range, ptr};
+
+use crate::{
+    boot::{AllocateType, BootServices},
+    memory::{MemoryType, PhysicalAddress, PAGE_SIZE},
+    status::Error,
+    system,
+};
+
+/// The maximum number of ranges that can be recorded in `BadMemory`.
+pub const MAX_BAD_RANGES: usize = 32;
+
+/// The patterns that are run if no others are selected.
+pub const DEFAULT_PATTERNS: [MemtestPattern; 4] = [
+    MemtestPattern::WalkingOnes,
+    MemtestPattern::WalkingZeros,
+    MemtestPattern::AddressInAddress,
+    MemtestPattern::Random(0x2545_f491_4f6c_dd1d),
+];
+
+/// A pattern that is written to the tested memory and read back.
+///
+/// The whole region is written before it is read back, so addressing faults are detected as
+/// well.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemtestPattern {
+    /// Each 64 bit word has a single bit set, which moves by one for each word.
+    WalkingOnes,
+    /// Each 64 bit word has a single bit cleared, which moves by one for each word.
+    WalkingZeros,
+    /// Each 64 bit word holds its own address.
+    AddressInAddress,
+    /// Pseudo random values generated from the seed.
+    Random(u64),
+}
+
+/// A word of memory that did not hold the value written to it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemtestFailure {
+    /// The physical address of the word.
+    pub address: PhysicalAddress,
+    /// The value that was written.
+    pub expected: u64,
+    /// The value that was read back.
+    pub actual: u64,
+    /// The pattern that detected the failure.
+    pub pattern: MemtestPattern,
+}
+
+/// The result of a memory test.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemtestSummary {
+    /// The number of pages that were tested.
+    pub tested_pages: u64,
+    /// The number of pages that were skipped, because they were allocated during the test.
+    pub skipped_pages: u64,
+    /// The number of failing words over all patterns.
+    pub failures: u64,
+}
+
+/// Generates the values of a pattern.
+struct PatternValues {
+    /// The pattern.
+    pattern: MemtestPattern,
+    /// The state of the random number generator.
+    state: u64,
+}
+
+impl PatternValues {
+    /// Starts the values of `pattern`.
+    fn new(pattern: MemtestPattern) -> PatternValues {
+        let state = match pattern {
+            // Xorshift does not leave the zero state.
+            MemtestPattern::Random(0) => 1,
+            MemtestPattern::Random(seed) => seed,
+            _ => 0,
+        };
+
+        PatternValues { pattern, state }
+    }
+
+    /// Returns the value for the word with the given index and address.
+    fn next(&mut self, index: usize, address: u64) -> u64 {
+        match self.pattern {
+            MemtestPattern::WalkingOnes => 1 << (index % 64),
+            MemtestPattern::WalkingZeros => !(1 << (index % 64)),
+            MemtestPattern::AddressInAddress => address,
+            MemtestPattern::Random(_) => {
+                self.state ^= self.state << 13;
+                self.state ^= self.state >> 7;
+                self.state ^= self.state << 17;
+                self.state
+            }
+        }
+    }
+}
+
+/// Runs `pattern` on `pages` pages starting at `base` and reports failing words.
+///
+/// Returns the number of failing words.
+///
+/// # Safety
+/// The memory must be owned by the caller and accessible at its physical address. Its contents
+/// are destroyed.
+pub unsafe fn test_region(
+    base: PhysicalAddress,
+    pages: u64,
+    pattern: MemtestPattern,
+    report: &mut dyn FnMut(&MemtestFailure),
+) -> u64 {
+    let words = pages as usize * (PAGE_SIZE / 8);
+    let start = base.0 as usize as *mut u64;
+
+    let mut values = PatternValues::new(pattern);
+    for index in 0..words {
+        let word = start.add(index);
+
+        ptr::write_volatile(word, values.next(index, word as u64));
+    }
+
+    let mut failures = 0;
+    let mut values = PatternValues::new(pattern);
+    for index in 0..words {
+        let word = start.add(index);
+        let expected = values.next(index, word as u64);
+        let actual = ptr::read_volatile(word);
+
+        if actual != expected {
+            failures += 1;
+            report(&MemtestFailure {
+                address: PhysicalAddress(word as u64),
+                expected,
+                actual,
+                pattern,
+            });
+        }
+    }
+
+    failures
+}
+
+/// Tests the `ConventionalMemory` regions with the given patterns.
+///
+/// Each failing word is passed to `report`. The page at address zero is not tested.
+pub fn run_with(
+    boot_services: &BootServices,
+    patterns: &[MemtestPattern],
+    report: &mut dyn FnMut(&MemtestFailure),
+) -> Result<MemtestSummary, Error> {
+    let memory_map = boot_services.get_memory_map(MemoryType::LoaderData)?;
+    let mut summary = MemtestSummary::default();
+
+    let mut result = Ok(());
+    for (start, pages, _) in memory_map.regions_of(MemoryType::ConventionalMemory) {
+        let mut frames = start.0 / PAGE_SIZE as u64..start.0 / PAGE_SIZE as u64 + pages;
+        if frames.start == 0 {
+            frames.start = 1;
+        }
+        if frames.start >= frames.end {
+            continue;
+        }
+
+        let base = PhysicalAddress(frames.start * PAGE_SIZE as u64);
+        let pages = frames.end - frames.start;
+
+        match boot_services.allocate_pages_with(
+            AllocateType::Address(base),
+            MemoryType::LoaderData,
+            pages as usize,
+        ) {
+            Ok(_) => (),
+            Err(Error::NotFound) => {
+                summary.skipped_pages += pages;
+                continue;
+            }
+            Err(error) => {
+                result = Err(error);
+                break;
+            }
+        }
+
+        for &pattern in patterns {
+            // This is safe, because the pages were just allocated.
+            summary.failures += unsafe { test_region(base, pages, pattern, report) };
+        }
+        summary.tested_pages += pages;
+
+        if let Err(error) = boot_services.free_pages(base.0 as usize as *const u8, pages as usize) {
+            result = Err(error);
+            break;
+        }
+    }
+
+    // This is safe, because the memory map is no longer used.
+    unsafe { memory_map.drop(boot_services) }?;
+
+    result.map(|_| summary)
+}
+
+/// Tests the `ConventionalMemory` regions with the given patterns.
+///
+/// The boot services registered with `system::init` are used. Returns `Error::NotStarted` if
+/// `system::init` was not called.
+pub fn run(
+    patterns: &[MemtestPattern],
+    report: &mut dyn FnMut(&MemtestFailure),
+) -> Result<MemtestSummary, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+
+    run_with(boot_services, patterns, report)
+}
+
+/// A set of pages that failed a memory test.
+#[derive(Clone, Debug, Default)]
+pub struct BadMemory {
+    /// The frame numbers of the bad pages.
+    ranges: [Range<u64>; MAX_BAD_RANGES],
+    /// The number of used entries in `ranges`.
+    count: usize,
+}
+
+impl BadMemory {
+    /// Creates an empty set.
+    pub fn new() -> BadMemory {
+        BadMemory::default()
+    }
+
+    /// Marks the page containing `address` as bad.
+    ///
+    /// Adjacent pages are merged into a single range. Returns `Error::OutOfResources` if
+    /// `MAX_BAD_RANGES` ranges are already recorded.
+    pub fn mark(&mut self, address: PhysicalAddress) -> Result<(), Error> {
+        let frame = address.0 / PAGE_SIZE as u64;
+
+        for range in &mut self.ranges[..self.count] {
+            if range.contains(&frame) {
+                return Ok(());
+            }
+            if range.end == frame {
+                range.end += 1;
+                return Ok(());
+            }
+            if range.start == frame + 1 {
+                range.start -= 1;
+                return Ok(());
+            }
+        }
+
+        if self.count == MAX_BAD_RANGES {
+            return Err(Error::OutOfResources);
+        }
+
+        self.ranges[self.count] = frame..frame + 1;
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Returns the frame numbers of the bad pages.
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges[..self.count]
+    }
+
+    /// Returns the number of bad pages.
+    pub fn pages(&self) -> u64 {
+        self.ranges()
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+
+    /// Returns whether no page is marked as bad.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
@@ -0,0 +1,274 @@
+//! A text-mode menu to let the user choose between several entries.
+//!
+//! The menu is controlled with the arrow keys, Home and End. Enter selects the highlighted entry,
+//! Escape cancels the menu. An optional timeout selects the default entry if no key is pressed.
+//! If there are more entries than fit on the screen, only a window of them is shown, which
+//! scrolls to follow the highlighted entry.
+
+use core::fmt::Write;
+
+use crate::{
+    status::Error,
+    system::SystemTable,
+    text::{BackgroundColor, BufferedWriter, Color, ForegroundColor, Key, ScanCode, TextOutput},
+};
+
+/// The interval in which the keyboard is polled, in microseconds.
+const POLL_INTERVAL: usize = 10_000;
+
+/// The number of times the keyboard is polled per second.
+const POLLS_PER_SECOND: usize = 1_000_000 / POLL_INTERVAL;
+
+/// The row of the first entry on the screen.
+const FIRST_ENTRY_ROW: usize = 2;
+
+/// The number of rows below the entries, for a blank line and the timeout.
+const ROWS_BELOW_ENTRIES: usize = 2;
+
+/// An entry of a menu.
+#[derive(Clone, Copy, Debug)]
+pub struct MenuEntry<'a, T> {
+    /// The text shown for the entry.
+    pub label: &'a str,
+    /// The value returned when the entry is selected.
+    pub value: T,
+}
+
+/// A menu that lets the user choose one of several entries.
+#[derive(Clone, Copy, Debug)]
+pub struct Menu<'a, T> {
+    /// The entries to choose from.
+    entries: &'a [MenuEntry<'a, T>],
+    /// The text shown above the entries.
+    title: Option<&'a str>,
+    /// The index of the entry that is highlighted first and selected on timeout.
+    default: usize,
+    /// The number of seconds after which the default entry is selected.
+    timeout: Option<u32>,
+    /// The color of the entries that are not highlighted.
+    normal_color: Color,
+    /// The color of the highlighted entry.
+    highlight_color: Color,
+}
+
+impl<'a, T> Menu<'a, T> {
+    /// Creates a menu with the given entries.
+    pub fn new(entries: &'a [MenuEntry<'a, T>]) -> Menu<'a, T> {
+        Menu {
+            entries,
+            title: None,
+            default: 0,
+            timeout: None,
+            normal_color: Color::new(ForegroundColor::LightGray, BackgroundColor::Black),
+            highlight_color: Color::new(ForegroundColor::Black, BackgroundColor::LightGray),
+        }
+    }
+
+    /// Sets the text shown above the entries.
+    pub fn title(mut self, title: &'a str) -> Menu<'a, T> {
+        self.title = Some(title);
+        self
+    }
+
+    /// Sets the entry that is highlighted first and selected on timeout.
+    pub fn default(mut self, default: usize) -> Menu<'a, T> {
+        self.default = default;
+        self
+    }
+
+    /// Selects the default entry if no key is pressed within the given number of seconds.
+    pub fn timeout(mut self, seconds: u32) -> Menu<'a, T> {
+        self.timeout = Some(seconds);
+        self
+    }
+
+    /// Sets the colors of normal and highlighted entries.
+    pub fn colors(mut self, normal_color: Color, highlight_color: Color) -> Menu<'a, T> {
+        self.normal_color = normal_color;
+        self.highlight_color = highlight_color;
+        self
+    }
+
+    /// Shows the menu on the console and waits for the user to choose an entry.
+    ///
+    /// Returns the value of the chosen entry or `None` if the menu was cancelled with Escape.
+    pub fn run(&self, system_table: &'static SystemTable) -> Result<Option<&'a T>, Error> {
+        if self.entries.is_empty() {
+            return Err(Error::InvalidParameter);
+        }
+
+        let output = &*system_table.ConsoleOut;
+        let previous_color = output.attribute();
+
+        let result = self.run_with(system_table, output);
+
+        output.set_attribute(previous_color)?;
+
+        result
+    }
+
+    /// Implements `run` without restoring the color.
+    fn run_with(
+        &self,
+        system_table: &'static SystemTable,
+        output: &TextOutput,
+    ) -> Result<Option<&'a T>, Error> {
+        let input = &*system_table.ConsoleIn;
+        let boot_services = &*system_table.BootServices;
+
+        let (_, rows) = output.current_mode_size()?;
+        let mut window = Window {
+            first: 0,
+            len: rows
+                .saturating_sub(FIRST_ENTRY_ROW + ROWS_BELOW_ENTRIES)
+                .max(1)
+                .min(self.entries.len()),
+        };
+
+        let mut selected = self.default.min(self.entries.len() - 1);
+        window.follow(selected);
+        // The remaining time until the default entry is selected, in polling intervals.
+        let mut remaining = self
+            .timeout
+            .map(|seconds| seconds as usize * POLLS_PER_SECOND);
+
+        output.set_attribute(self.normal_color)?;
+        output.clear_screen()?;
+
+        if let Some(title) = self.title {
+            output.output_string(title)?;
+        }
+
+        self.draw_entries(output, window, selected)?;
+        self.draw_timeout(output, window, remaining)?;
+
+        loop {
+            let key = match input.try_read_key_stroke() {
+                Ok(key) => key.key(),
+                Err(Error::NotReady) => {
+                    if let Some(ticks) = remaining {
+                        if ticks == 0 {
+                            return Ok(Some(&self.entries[selected].value));
+                        }
+
+                        remaining = Some(ticks - 1);
+
+                        if ticks % POLLS_PER_SECOND == 0 {
+                            self.draw_timeout(output, window, remaining)?;
+                        }
+                    }
+
+                    boot_services.stall(POLL_INTERVAL)?;
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            // Any key stops the countdown.
+            if remaining.is_some() {
+                remaining = None;
+                self.draw_timeout(output, window, remaining)?;
+            }
+
+            let last = self.entries.len() - 1;
+
+            selected = match key {
+                Key::Char('\r') | Key::Char('\n') => {
+                    return Ok(Some(&self.entries[selected].value))
+                }
+                Key::Special(ScanCode::Escape) => return Ok(None),
+                Key::Special(ScanCode::Up) => selected.saturating_sub(1),
+                Key::Special(ScanCode::Down) => (selected + 1).min(last),
+                Key::Special(ScanCode::Home) | Key::Special(ScanCode::PageUp) => 0,
+                Key::Special(ScanCode::End) | Key::Special(ScanCode::PageDown) => last,
+                _ => continue,
+            };
+
+            window.follow(selected);
+            self.draw_entries(output, window, selected)?;
+        }
+    }
+
+    /// Draws the entries in the window, highlighting the selected one.
+    fn draw_entries(
+        &self,
+        output: &TextOutput,
+        window: Window,
+        selected: usize,
+    ) -> Result<(), Error> {
+        // Padding all labels to the same width overwrites longer labels when the window scrolls.
+        let width = self
+            .entries
+            .iter()
+            .map(|entry| entry.label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (row, index) in (window.first..window.first + window.len).enumerate() {
+            let entry = &self.entries[index];
+
+            output.set_cursor_position(0, FIRST_ENTRY_ROW + row)?;
+
+            let color = if index == selected {
+                self.highlight_color
+            } else {
+                self.normal_color
+            };
+
+            output.with_color(color, |output| {
+                let mut writer = BufferedWriter::new(output);
+
+                let _ = write!(writer, "  {:width$}  ", entry.label, width = width);
+
+                writer.flush()
+            })??;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the remaining time below the entries or clears it, if there is none.
+    fn draw_timeout(
+        &self,
+        output: &TextOutput,
+        window: Window,
+        remaining: Option<usize>,
+    ) -> Result<(), Error> {
+        output.set_cursor_position(0, FIRST_ENTRY_ROW + window.len + 1)?;
+
+        let mut writer = BufferedWriter::new(output);
+
+        match remaining {
+            Some(ticks) => {
+                let seconds = (ticks + POLLS_PER_SECOND - 1) / POLLS_PER_SECOND;
+
+                let _ = write!(writer, "The default entry is selected in {:>3}s.", seconds);
+            }
+            None => {
+                let _ = write!(writer, "{:40}", "");
+            }
+        }
+
+        writer.flush()
+    }
+}
+
+/// The range of entries that is shown on the screen.
+#[derive(Clone, Copy, Debug)]
+struct Window {
+    /// The index of the first entry that is shown.
+    first: usize,
+    /// The number of entries that are shown.
+    len: usize,
+}
+
+impl Window {
+    /// Scrolls the window as little as possible, so that the selected entry is shown.
+    fn follow(&mut self, selected: usize) {
+        if selected < self.first {
+            self.first = selected;
+        } else if selected >= self.first + self.len {
+            self.first = selected + 1 - self.len;
+        }
+    }
+}
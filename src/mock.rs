@@ -0,0 +1,1996 @@
+//! An in-memory mock of the firmware tables, to unit test code built on this crate on the host.
+//!
+//! `MockSystem::new` returns a `SystemTable` whose boot services, runtime services and consoles
+//! are implemented by host code. The mock keeps its state in statics, because the firmware
+//! functions receive no context, so only one `MockSystem` can exist at a time. Creating a second
+//! one blocks until the first one is dropped, which serializes tests that run in parallel.
+//!
+//! The mock provides:
+//! - Page and pool allocations from a fixed arena, which are never reused.
+//! - A synthetic memory map, which can be replaced with `set_memory_map`.
+//! - Events, where timers expire immediately and `WaitForKey` is signaled while keys are queued.
+//! - `Stall` and `SetWatchdogTimer`, which only record their arguments.
+//! - `ExitBootServices`, which checks the map key like the firmware does.
+//! - Consoles that capture their output and an input that returns queued keys.
+//! - `GetTime` and `SetTime`, which operate on a stored time.
+//! - A read-only volume that the image was loaded from, which holds the files added with
+//!   `add_file`, reachable through the loaded image protocol of the image handle.
+//! - `LoadImage` and `StartImage` for a single image, which record the device path, the image
+//!   and the load options, and return the exit set with `set_image_exit`.
+//!
+//! Other protocol lookups fail with `Unsupported` or `NotFound` and variables are never found. `Exit`
+//! records the exit status and fails with `Unsupported`. `ResetSystem` records the reset and
+//! returns, which makes `RuntimeServices::reset_system` panic outside of the firmware function,
+//! so the panic can be caught by the test. The functions that have no bindings in this crate
+//! only count how often they were called.
+//!
+//! The mock functions never panic, because a panic cannot unwind out of a function with the
+//! calling convention of the firmware.
+
+use core::{
+    cell::UnsafeCell,
+    char,
+    mem::size_of,
+    ptr::{self, NonNull},
+    slice, str,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    boot::{
+        self, BootServices, EventNotify, EventType, InterfaceType, LocateSearchType, TimerDelay,
+    },
+    capsule::CapsuleHeader,
+    device::{DevicePath, DevicePathEndType, DevicePathMediaType, DevicePathType},
+    fs::{File, FileInfo, SimpleFileSystem, FILE_DIRECTORY, FILE_MODE_WRITE},
+    guid::{Guid, DEVICE_PATH_GUID, FILE_INFO_ID, FILE_SYSTEM_GUID, LOADED_IMAGE_PROTOCOL_GUID},
+    loaded_image::LoadedImage,
+    memory::{
+        MemoryAttributes, MemoryDescriptor, MemoryType, PhysicalAddress, VirtualAddress,
+        MEMORY_DESCRIPTOR_VERSION, PAGE_SIZE,
+    },
+    reset::ResetType,
+    runtime::RuntimeServices,
+    status::{Error, Status, Warning, SUCCESS},
+    system::{SystemTable, SYSTEM_TABLE_REVISION_2_70},
+    text::{TextInput, TextInputKey, TextOutput, TextOutputMode},
+    time::{Time, TimeCapabilities},
+    ucs2, Event, Handle, OptionalHandle, TableHeader,
+};
+
+/// The number of pages in the arena that allocations are made from.
+pub const MOCK_ARENA_PAGES: usize = 256;
+
+/// The maximum number of descriptors in the mock memory map.
+pub const MOCK_MAX_DESCRIPTORS: usize = 32;
+
+/// The maximum number of events that can be open at the same time.
+pub const MOCK_MAX_EVENTS: usize = 64;
+
+/// The maximum number of keys that can be queued.
+pub const MOCK_MAX_KEYS: usize = 32;
+
+/// The number of bytes of console output that are captured per console.
+///
+/// Output beyond this is discarded.
+pub const MOCK_CONSOLE_CAPACITY: usize = 16 * 1024;
+
+/// The maximum number of files on the mock volume.
+pub const MOCK_MAX_FILES: usize = 8;
+
+/// The maximum number of files that can be open at the same time.
+pub const MOCK_MAX_OPEN_FILES: usize = 16;
+
+/// The maximum length in bytes of a path on the mock volume.
+pub const MOCK_MAX_PATH_LEN: usize = 128;
+
+/// The maximum size of an image passed to `LoadImage`.
+pub const MOCK_MAX_IMAGE_SIZE: usize = 16 * 1024;
+
+/// The signature of the EFI System Table.
+const SYSTEM_TABLE_SIGNATURE: u64 = 0x5453_5953_2049_4249;
+/// The signature of the EFI Boot Services Table.
+const BOOT_SERVICES_SIGNATURE: u64 = 0x5652_4553_544f_4f42;
+/// The signature of the EFI Runtime Services Table.
+const RUNTIME_SERVICES_SIGNATURE: u64 = 0x5652_4553_544e_5552;
+
+/// The event returned as `WaitForKey` of the mock console input.
+const KEY_EVENT: Event = Event(usize::max_value());
+
+/// Allows the mock state to be placed in a `static`.
+struct MockCell<T>(UnsafeCell<T>);
+
+// This is safe, because the cells are only accessed while the mock lock is held.
+unsafe impl<T> Sync for MockCell<T> {}
+
+impl<T> MockCell<T> {
+    /// Returns a mutable reference to the contents.
+    ///
+    /// # Safety
+    /// The caller must make sure that no other reference to the contents is used at the same time.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get(&self) -> &'static mut T {
+        &mut *(self.0.get() as *mut T)
+    }
+}
+
+/// The memory that allocations are made from.
+#[repr(C, align(4096))]
+struct Arena([u8; MOCK_ARENA_PAGES * PAGE_SIZE]);
+
+/// Text captured from the calls to the mock, such as the output of a console.
+struct ConsoleBuffer {
+    /// The captured output as UTF-8.
+    buffer: [u8; MOCK_CONSOLE_CAPACITY],
+    /// The number of bytes in `buffer` that are in use.
+    len: usize,
+}
+
+impl ConsoleBuffer {
+    /// Appends a character to the captured output.
+    fn push(&mut self, character: char) {
+        let mut encoded = [0; 4];
+        let encoded = character.encode_utf8(&mut encoded).as_bytes();
+
+        if self.len + encoded.len() <= MOCK_CONSOLE_CAPACITY {
+            self.buffer[self.len..self.len + encoded.len()].copy_from_slice(encoded);
+            self.len += encoded.len();
+        }
+    }
+
+    /// Returns the captured output.
+    fn as_str(&self) -> &str {
+        // This is safe, because only complete UTF-8 sequences are pushed.
+        unsafe { str::from_utf8_unchecked(&self.buffer[..self.len]) }
+    }
+}
+
+/// An event created through the mock boot services.
+#[derive(Clone, Copy)]
+struct EventSlot {
+    /// Whether the slot belongs to an open event.
+    open: bool,
+    /// The type the event was created with.
+    event_type: EventType,
+    /// Whether the event is signaled.
+    signaled: bool,
+    /// Whether the event is a periodic timer, which stays signaled.
+    periodic: bool,
+}
+
+/// An unused event slot.
+const EMPTY_EVENT: EventSlot = EventSlot {
+    open: false,
+    event_type: EventType::empty(),
+    signaled: false,
+    periodic: false,
+};
+
+/// An unused memory descriptor.
+const EMPTY_DESCRIPTOR: MemoryDescriptor = MemoryDescriptor {
+    Type: MemoryType::ReservedMemoryType,
+    PhysicalStart: PhysicalAddress(0),
+    VirtualStart: VirtualAddress(0),
+    NumberOfPages: 0,
+    Attribute: MemoryAttributes::empty(),
+};
+
+/// An empty key.
+const EMPTY_KEY: TextInputKey = TextInputKey {
+    ScanCode: 0,
+    UnicodeChar: 0,
+};
+
+/// A file on the mock volume.
+#[derive(Clone, Copy)]
+struct MockFile {
+    /// The path of the file relative to the root directory, without a leading backslash.
+    path: &'static str,
+    /// The contents of the file.
+    data: &'static [u8],
+}
+
+/// An unused file entry.
+const EMPTY_FILE: MockFile = MockFile {
+    path: "",
+    data: &[],
+};
+
+/// A file or directory opened on the mock volume.
+#[derive(Clone, Copy)]
+struct OpenFile {
+    /// Whether the slot belongs to an open file.
+    open: bool,
+    /// The address of the protocol interface of the file, which is allocated from the arena.
+    file: usize,
+    /// The index of a file whose path starts with the path of the open file.
+    entry: usize,
+    /// The length of the path of the open file, which is a prefix of the path of `entry`.
+    len: usize,
+    /// The current position in the file.
+    position: usize,
+}
+
+impl OpenFile {
+    /// Returns whether the open file is a directory, which is the case if it is only a prefix of
+    /// the path of its entry.
+    fn is_directory(&self, files: &[MockFile]) -> bool {
+        self.len == 0 || self.len < files[self.entry].path.len()
+    }
+}
+
+/// An unused open file slot.
+const CLOSED_FILE: OpenFile = OpenFile {
+    open: false,
+    file: 0,
+    entry: 0,
+    len: 0,
+    position: 0,
+};
+
+/// The state that the mock firmware functions operate on.
+struct MockState {
+    /// The memory that allocations are made from.
+    arena: Arena,
+    /// The offset of the first free byte in the arena.
+    arena_used: usize,
+    /// The descriptors of the memory map.
+    descriptors: [MemoryDescriptor; MOCK_MAX_DESCRIPTORS],
+    /// The number of valid entries in `descriptors`, or `None` for the default map.
+    descriptor_count: Option<usize>,
+    /// The current map key, which changes with every allocation.
+    map_key: usize,
+    /// Whether `ExitBootServices` succeeded.
+    exited: bool,
+    /// The current task priority level.
+    tpl: usize,
+    /// The events that can be created.
+    events: [EventSlot; MOCK_MAX_EVENTS],
+    /// The keys returned by the console input.
+    keys: [TextInputKey; MOCK_MAX_KEYS],
+    /// The number of queued keys.
+    key_count: usize,
+    /// The output of the console.
+    console_out: ConsoleBuffer,
+    /// The output of the error console.
+    console_error: ConsoleBuffer,
+    /// The total number of microseconds passed to `Stall`.
+    stalled: u64,
+    /// The last timeout passed to `SetWatchdogTimer`.
+    watchdog_timeout: Option<usize>,
+    /// The time returned by `GetTime`, `None` for the default time.
+    time: Option<Time>,
+    /// The value returned by `GetNextHighMonotonicCount`.
+    high_monotonic_count: u32,
+    /// The status passed to `Exit`.
+    exit_status: Option<isize>,
+    /// The type and status passed to `ResetSystem`.
+    reset: Option<(ResetType, Status)>,
+    /// The number of calls to functions that have no bindings in this crate.
+    unbound_calls: usize,
+    /// The files on the mock volume.
+    files: [MockFile; MOCK_MAX_FILES],
+    /// The number of valid entries in `files`.
+    file_count: usize,
+    /// The files opened on the mock volume.
+    open_files: [OpenFile; MOCK_MAX_OPEN_FILES],
+    /// Whether an image was loaded with `LoadImage` and neither started nor unloaded since.
+    image_loaded: bool,
+    /// The file path of the device path passed to `LoadImage`, if one was passed.
+    image_path: Option<ConsoleBuffer>,
+    /// The image passed to `LoadImage` in memory.
+    image_source: [u8; MOCK_MAX_IMAGE_SIZE],
+    /// The size of the image passed to `LoadImage` in memory, if one was passed.
+    image_source_len: Option<usize>,
+    /// The load options the loaded image was started with, if it was started.
+    image_load_options: Option<ConsoleBuffer>,
+    /// The exit status and exit data that `StartImage` returns.
+    image_exit: (Status, Option<&'static str>),
+}
+
+impl MockState {
+    /// Resets the state to the one of a freshly started system.
+    fn reset(&mut self) {
+        self.arena_used = 0;
+        self.descriptor_count = None;
+        self.map_key = 1;
+        self.exited = false;
+        self.tpl = boot::TPL_APPLICATION;
+        self.events = [EMPTY_EVENT; MOCK_MAX_EVENTS];
+        self.key_count = 0;
+        self.console_out.len = 0;
+        self.console_error.len = 0;
+        self.stalled = 0;
+        self.watchdog_timeout = None;
+        self.time = None;
+        self.high_monotonic_count = 0;
+        self.exit_status = None;
+        self.reset = None;
+        self.unbound_calls = 0;
+        self.file_count = 0;
+        self.open_files = [CLOSED_FILE; MOCK_MAX_OPEN_FILES];
+        self.image_loaded = false;
+        self.image_path = None;
+        self.image_source_len = None;
+        self.image_load_options = None;
+        self.image_exit = (SUCCESS, None);
+    }
+
+    /// Returns the descriptors of the current memory map.
+    fn memory_map(&mut self) -> &[MemoryDescriptor] {
+        let count = match self.descriptor_count {
+            Some(count) => count,
+            None => {
+                self.descriptors[0] = MemoryDescriptor {
+                    Type: MemoryType::ConventionalMemory,
+                    PhysicalStart: PhysicalAddress(self.arena.0.as_ptr() as u64),
+                    VirtualStart: VirtualAddress(0),
+                    NumberOfPages: MOCK_ARENA_PAGES as u64,
+                    Attribute: MemoryAttributes::WB,
+                };
+
+                1
+            }
+        };
+
+        &self.descriptors[..count]
+    }
+
+    /// Allocates `size` bytes aligned to `align` from the arena.
+    fn allocate(&mut self, size: usize, align: usize) -> Result<usize, Error> {
+        let base = self.arena.0.as_ptr() as usize;
+        let start = (base + self.arena_used + align - 1) & !(align - 1);
+        let end = start.checked_add(size).ok_or(Error::OutOfResources)?;
+
+        if end > base + self.arena.0.len() {
+            return Err(Error::OutOfResources);
+        }
+
+        self.arena_used = end - base;
+        self.map_key += 1;
+
+        Ok(start)
+    }
+
+    /// Returns the slot of an open event.
+    fn event(&mut self, event: Event) -> Option<&mut EventSlot> {
+        event
+            .0
+            .checked_sub(1)
+            .and_then(move |index| self.events.get_mut(index))
+            .filter(|slot| slot.open)
+    }
+
+    /// Opens the file or directory whose path consists of the first `len` bytes of the path of
+    /// `entry`.
+    fn open_file(&mut self, entry: usize, len: usize) -> Result<*mut File, Error> {
+        let index = self
+            .open_files
+            .iter()
+            .position(|slot| !slot.open)
+            .ok_or(Error::OutOfResources)?;
+        let file = self.allocate(size_of::<File>(), 8)?;
+
+        // This is safe, because the memory was just allocated from the arena for the file.
+        unsafe { (file as *mut File).write(mock_file()) };
+
+        self.open_files[index] = OpenFile {
+            open: true,
+            file,
+            entry,
+            len,
+            position: 0,
+        };
+
+        Ok(file as *mut File)
+    }
+
+    /// Returns the index of the slot of an open file.
+    fn find_open_file(&self, file: &File) -> Result<usize, Error> {
+        let address = file as *const File as usize;
+
+        self.open_files
+            .iter()
+            .position(|slot| slot.open && slot.file == address)
+            .ok_or(Error::InvalidParameter)
+    }
+
+    /// Returns the index of the file whose path starts with `path`, followed by the end of the
+    /// path or a backslash.
+    fn find_file(&self, path: &[u8]) -> Option<usize> {
+        self.files[..self.file_count].iter().position(|file| {
+            let file_path = file.path.as_bytes();
+
+            file_path.len() >= path.len()
+                && file_path[..path.len()].eq_ignore_ascii_case(path)
+                && (file_path.len() == path.len() || file_path[path.len()] == b'\\')
+        })
+    }
+
+    /// Checks whether an event is signaled, clearing the signal unless it is periodic.
+    fn check_event(&mut self, event: Event) -> Result<bool, Error> {
+        if event == KEY_EVENT {
+            return Ok(self.key_count > 0);
+        }
+
+        let slot = self.event(event).ok_or(Error::InvalidParameter)?;
+
+        if slot.event_type.contains(EventType::NOTIFY_SIGNAL) {
+            return Err(Error::InvalidParameter);
+        }
+
+        let signaled = slot.signaled;
+        if !slot.periodic {
+            slot.signaled = false;
+        }
+
+        Ok(signaled)
+    }
+}
+
+/// Whether a `MockSystem` currently exists.
+static LOCK: AtomicBool = AtomicBool::new(false);
+
+/// The handles of the image, the consoles, the mock volume and the image loaded with `LoadImage`.
+static HANDLES: [u8; 5] = [0; 5];
+
+/// The mode of the mock consoles.
+static CONSOLE_MODE: TextOutputMode = TextOutputMode {
+    MaxMode: 1,
+    Mode: 0,
+    Attribute: 0x07,
+    CursorColumn: 0,
+    CursorRow: 0,
+    CursorVisible: true,
+};
+
+static STATE: MockCell<MockState> = MockCell(UnsafeCell::new(MockState {
+    arena: Arena([0; MOCK_ARENA_PAGES * PAGE_SIZE]),
+    arena_used: 0,
+    descriptors: [EMPTY_DESCRIPTOR; MOCK_MAX_DESCRIPTORS],
+    descriptor_count: None,
+    map_key: 1,
+    exited: false,
+    tpl: boot::TPL_APPLICATION,
+    events: [EMPTY_EVENT; MOCK_MAX_EVENTS],
+    keys: [EMPTY_KEY; MOCK_MAX_KEYS],
+    key_count: 0,
+    console_out: ConsoleBuffer {
+        buffer: [0; MOCK_CONSOLE_CAPACITY],
+        len: 0,
+    },
+    console_error: ConsoleBuffer {
+        buffer: [0; MOCK_CONSOLE_CAPACITY],
+        len: 0,
+    },
+    stalled: 0,
+    watchdog_timeout: None,
+    time: None,
+    high_monotonic_count: 0,
+    exit_status: None,
+    reset: None,
+    unbound_calls: 0,
+    files: [EMPTY_FILE; MOCK_MAX_FILES],
+    file_count: 0,
+    open_files: [CLOSED_FILE; MOCK_MAX_OPEN_FILES],
+    image_loaded: false,
+    image_path: None,
+    image_source: [0; MOCK_MAX_IMAGE_SIZE],
+    image_source_len: None,
+    image_load_options: None,
+    image_exit: (SUCCESS, None),
+}));
+
+static BOOT_SERVICES: MockCell<BootServices> = MockCell(UnsafeCell::new(BootServices {
+    Hdr: TableHeader {
+        Signature: BOOT_SERVICES_SIGNATURE,
+        Revision: SYSTEM_TABLE_REVISION_2_70,
+        HeaderSize: size_of::<BootServices>() as u32,
+        CRC32: 0,
+        Reserved: 0,
+    },
+    RaiseTpl: raise_tpl,
+    RestoreTpl: restore_tpl,
+    AllocatePages: allocate_pages,
+    FreePages: free_pages,
+    GetMemoryMap: get_memory_map,
+    AllocatePool: allocate_pool,
+    FreePool: free_pool,
+    CreateEvent: create_event,
+    SetTimer: set_timer,
+    WaitForEvent: wait_for_event,
+    SignalEvent: signal_event,
+    CloseEvent: close_event,
+    CheckEvent: check_event,
+    InstallProtocolInterface: install_protocol_interface,
+    ReinstallProtocolInterface: unbound,
+    UninstallProtocolInterface: uninstall_protocol_interface,
+    HandleProtocol: handle_protocol,
+    _rsvd: 0,
+    RegisterProtocolNotify: unbound,
+    LocateHandle: locate_handle,
+    LocateDevicePath: unbound,
+    InstallConfigurationTable: unbound,
+    LoadImage: load_image,
+    StartImage: start_image,
+    Exit: exit,
+    UnloadImage: unload_image,
+    ExitBootServices: exit_boot_services,
+    GetNextMonotonicCount: unbound,
+    Stall: stall,
+    SetWatchdogTimer: set_watchdog_timer,
+    ConnectController: unbound,
+    DisconnectController: unbound,
+    OpenProtocol: open_protocol,
+    CloseProtocol: unbound,
+    OpenProtocolInformation: unbound,
+    ProtocolsPerHandle: protocols_per_handle,
+    LocateHandleBuffer: locate_handle_buffer,
+    LocateProtocol: locate_protocol,
+    InstallMultipleProtocolInterfaces: unbound,
+    UninstallMultipleProtocolInterfaces: unbound,
+    CalculateCrc32: unbound,
+    CopyMem: unbound,
+    SetMem: unbound,
+    CreateEventEx: create_event_ex,
+}));
+
+static RUNTIME_SERVICES: MockCell<RuntimeServices> = MockCell(UnsafeCell::new(RuntimeServices {
+    Hdr: TableHeader {
+        Signature: RUNTIME_SERVICES_SIGNATURE,
+        Revision: SYSTEM_TABLE_REVISION_2_70,
+        HeaderSize: size_of::<RuntimeServices>() as u32,
+        CRC32: 0,
+        Reserved: 0,
+    },
+    GetTime: get_time,
+    SetTime: set_time,
+    GetWakeupTime: get_wakeup_time,
+    SetWakeupTime: set_wakeup_time,
+    SetVirtualAddressMap: set_virtual_address_map,
+    ConvertPointer: convert_pointer,
+    GetVariable: get_variable,
+    GetNextVariableName: get_next_variable_name,
+    SetVariable: set_variable,
+    GetNextHighMonotonicCount: get_next_high_monotonic_count,
+    ResetSystem: reset_system,
+    UpdateCapsule: update_capsule,
+    QueryCapsuleCapabilities: query_capsule_capabilities,
+    QueryVariableInfo: query_variable_info,
+}));
+
+static CONSOLE_IN: MockCell<TextInput> = MockCell(UnsafeCell::new(TextInput {
+    Reset: input_reset,
+    ReadKeyStroke: read_key_stroke,
+    WaitForKey: KEY_EVENT,
+}));
+
+static CONSOLE_OUT: MockCell<TextOutput> = MockCell(UnsafeCell::new(TextOutput {
+    Reset: output_reset,
+    OutputString: output_string,
+    TestString: test_string,
+    QueryMode: query_mode,
+    SetMode: set_mode,
+    SetAttribute: set_attribute,
+    ClearScreen: clear_screen,
+    SetCursorPosition: set_cursor_position,
+    EnableCursor: enable_cursor,
+    Mode: &CONSOLE_MODE,
+}));
+
+static CONSOLE_ERROR: MockCell<TextOutput> = MockCell(UnsafeCell::new(TextOutput {
+    Reset: output_reset,
+    OutputString: output_string,
+    TestString: test_string,
+    QueryMode: query_mode,
+    SetMode: set_mode,
+    SetAttribute: set_attribute,
+    ClearScreen: clear_screen,
+    SetCursorPosition: set_cursor_position,
+    EnableCursor: enable_cursor,
+    Mode: &CONSOLE_MODE,
+}));
+
+/// The system table referring to the other tables, created by the first `MockSystem`.
+static SYSTEM_TABLE: MockCell<Option<SystemTable>> = MockCell(UnsafeCell::new(None));
+
+/// The loaded image protocols of the image and of the image loaded with `LoadImage`, which are
+/// created again for every `MockSystem`.
+static LOADED_IMAGES: MockCell<Option<[LoadedImage; 2]>> = MockCell(UnsafeCell::new(None));
+
+/// The simple file system protocol of the mock volume.
+static FILE_SYSTEM: SimpleFileSystem = SimpleFileSystem {
+    Revision: 0x0001_0000,
+    OpenVolume: open_volume,
+};
+
+/// The device path of the mock volume, which only consists of the end node.
+static VOLUME_DEVICE_PATH: DevicePath = DevicePath {
+    Type: DevicePathType::End as u8,
+    SubType: DevicePathEndType::Entire as u8,
+    Length: size_of::<DevicePath>() as u16,
+};
+
+/// Returns the mock state.
+fn state() -> &'static mut MockState {
+    // This is safe, because the mock functions are only reachable through a `MockSystem`, which
+    // holds the lock, and none of them keeps the reference past its return.
+    unsafe { STATE.get() }
+}
+
+/// Returns the mock handle with the given index.
+fn handle(index: usize) -> Handle {
+    Handle(NonNull::from(&HANDLES[index]).cast())
+}
+
+/// Returns the mock system table, creating it on the first call.
+fn system_table() -> &'static mut SystemTable {
+    // This is safe, because the lock is held and the tables are only borrowed mutably once,
+    // when the system table is created.
+    let system_table = unsafe { SYSTEM_TABLE.get() };
+
+    if system_table.is_none() {
+        *system_table = Some(SystemTable {
+            Hdr: TableHeader {
+                Signature: SYSTEM_TABLE_SIGNATURE,
+                Revision: SYSTEM_TABLE_REVISION_2_70,
+                HeaderSize: size_of::<SystemTable>() as u32,
+                CRC32: 0,
+                Reserved: 0,
+            },
+            FirmwareVendor: ucs2!("nuefil mock").as_ptr(),
+            FirmwareRevision: 0,
+            ConsoleInHandle: handle(1),
+            ConsoleIn: unsafe { CONSOLE_IN.get() },
+            ConsoleOutHandle: handle(2),
+            ConsoleOut: unsafe { CONSOLE_OUT.get() },
+            ConsoleErrorHandle: handle(2),
+            ConsoleError: unsafe { CONSOLE_ERROR.get() },
+            RuntimeServices: unsafe { RUNTIME_SERVICES.get() },
+            BootServices: unsafe { BOOT_SERVICES.get() },
+            Entries: 0,
+            // A dangling pointer allows `config_tables` to create an empty slice.
+            ConfigurationTables: NonNull::dangling().as_ptr(),
+        });
+    }
+
+    match system_table {
+        Some(ref mut system_table) => system_table,
+        None => unreachable!(),
+    }
+}
+
+/// Returns the loaded image protocol of the image (0) or of the image loaded with `LoadImage` (1).
+fn loaded_image(index: usize) -> &'static mut LoadedImage {
+    // This is safe, because the lock is held and the mock functions do not keep the reference
+    // past their return.
+    let loaded_images = unsafe { LOADED_IMAGES.get() };
+
+    if loaded_images.is_none() {
+        let new = |parent_handle| LoadedImage {
+            Revision: 0x1000,
+            ParentHandle: parent_handle,
+            SystemTable: system_table(),
+            DeviceHandle: Some(handle(3)),
+            FilePath: 0,
+            Reserved: 0,
+            LoadOptionsSize: 0,
+            LoadOptions: ptr::null(),
+            ImageBase: 0,
+            ImageSize: 0,
+            ImageCodeType: MemoryType::LoaderCode,
+            ImageDataType: MemoryType::LoaderData,
+            Unload: unload_image,
+        };
+
+        *loaded_images = Some([new(None), new(Some(handle(0)))]);
+    }
+
+    match loaded_images {
+        Some(ref mut loaded_images) => &mut loaded_images[index],
+        None => unreachable!(),
+    }
+}
+
+/// Returns the protocol interface of a mock file.
+fn mock_file() -> File {
+    File {
+        Revision: 0x0001_0000,
+        Open: file_open,
+        Close: file_close,
+        Delete: file_delete,
+        Read: file_read,
+        Write: file_write,
+        SetPosition: file_set_position,
+        GetPosition: file_get_position,
+        GetInfo: file_get_info,
+        SetInfo: file_set_info,
+        Flush: file_flush,
+    }
+}
+
+/// A host implemented firmware.
+///
+/// The system table and the tables it refers to stay valid after the `MockSystem` is dropped,
+/// but they must not be used until the next `MockSystem` is created.
+pub struct MockSystem {
+    /// Prevents construction outside of this module.
+    _private: (),
+}
+
+impl MockSystem {
+    /// Creates a mock firmware in its initial state.
+    ///
+    /// This blocks until any other `MockSystem` is dropped. The mock boot services are registered
+    /// with `boot::register_global`.
+    pub fn new() -> MockSystem {
+        while LOCK
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {}
+
+        state().reset();
+        // This is safe, because the lock is held, so no loaded image protocol is in use.
+        unsafe { *LOADED_IMAGES.get() = None };
+
+        let system = MockSystem { _private: () };
+        boot::register_global(&system.system_table().BootServices);
+
+        system
+    }
+
+    /// Returns the mock system table.
+    pub fn system_table(&self) -> &'static SystemTable {
+        system_table()
+    }
+
+    /// Returns the handle to pass as the image handle.
+    pub fn image_handle(&self) -> Handle {
+        handle(0)
+    }
+
+    /// Returns everything written to `ConsoleOut`.
+    pub fn console_output(&self) -> &str {
+        state().console_out.as_str()
+    }
+
+    /// Returns everything written to `ConsoleError`.
+    pub fn console_error(&self) -> &str {
+        state().console_error.as_str()
+    }
+
+    /// Discards the captured console output.
+    pub fn clear_console(&self) {
+        state().console_out.len = 0;
+        state().console_error.len = 0;
+    }
+
+    /// Queues a key to be returned by `ConsoleIn`.
+    ///
+    /// Returns `Err(Error::BufferTooSmall)` if `MOCK_MAX_KEYS` keys are already queued.
+    pub fn push_key(&self, key: TextInputKey) -> Result<(), Error> {
+        let state = state();
+
+        if state.key_count == MOCK_MAX_KEYS {
+            return Err(Error::BufferTooSmall);
+        }
+
+        state.keys[state.key_count] = key;
+        state.key_count += 1;
+
+        Ok(())
+    }
+
+    /// Queues the characters of a string to be returned by `ConsoleIn`.
+    pub fn push_str(&self, string: &str) -> Result<(), Error> {
+        for character in string.chars() {
+            let mut encoded = [0; 2];
+            if character.encode_utf16(&mut encoded).len() != 1 {
+                return Err(Error::InvalidParameter);
+            }
+
+            self.push_key(TextInputKey {
+                ScanCode: 0,
+                UnicodeChar: encoded[0],
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the memory map returned by `GetMemoryMap`.
+    ///
+    /// The default map consists of a single conventional memory descriptor, which covers the
+    /// arena that allocations are made from.
+    pub fn set_memory_map(&self, descriptors: &[MemoryDescriptor]) -> Result<(), Error> {
+        let state = state();
+
+        if descriptors.len() > MOCK_MAX_DESCRIPTORS {
+            return Err(Error::BufferTooSmall);
+        }
+
+        state.descriptors[..descriptors.len()].copy_from_slice(descriptors);
+        state.descriptor_count = Some(descriptors.len());
+        state.map_key += 1;
+
+        Ok(())
+    }
+
+    /// Changes the memory map key, as if the firmware allocated memory in the background.
+    pub fn invalidate_map_key(&self) {
+        state().map_key += 1;
+    }
+
+    /// Sets the time returned by `GetTime`.
+    pub fn set_time(&self, time: Time) {
+        state().time = Some(time);
+    }
+
+    /// Returns the total number of microseconds passed to `Stall`.
+    pub fn stalled(&self) -> u64 {
+        state().stalled
+    }
+
+    /// Returns the last timeout passed to `SetWatchdogTimer`, if it was called.
+    pub fn watchdog_timeout(&self) -> Option<usize> {
+        state().watchdog_timeout
+    }
+
+    /// Returns the number of events that were created but not closed.
+    pub fn open_events(&self) -> usize {
+        state().events.iter().filter(|slot| slot.open).count()
+    }
+
+    /// Returns the number of bytes allocated from the arena.
+    ///
+    /// Freeing memory does not reduce this number.
+    pub fn allocated(&self) -> usize {
+        state().arena_used
+    }
+
+    /// Returns whether `ExitBootServices` succeeded.
+    pub fn boot_services_exited(&self) -> bool {
+        state().exited
+    }
+
+    /// Returns the status passed to the last call of `Exit`, if it was called.
+    pub fn exit_status(&self) -> Option<isize> {
+        state().exit_status
+    }
+
+    /// Returns the type and status passed to the last call of `ResetSystem`, if it was called.
+    pub fn reset(&self) -> Option<(ResetType, Status)> {
+        state().reset
+    }
+
+    /// Returns the number of calls to firmware functions that have no bindings in this crate.
+    pub fn unbound_calls(&self) -> usize {
+        state().unbound_calls
+    }
+
+    /// Adds a file to the mock volume, which is the volume the image was loaded from.
+    ///
+    /// The components of `path` are separated by backslashes and the directories on the way
+    /// exist implicitly. Returns `Err(Error::BufferTooSmall)` if `MOCK_MAX_FILES` files were
+    /// already added and `Err(Error::InvalidParameter)` if the path is empty or longer than
+    /// `MOCK_MAX_PATH_LEN`.
+    pub fn add_file(&self, path: &'static str, data: &'static [u8]) -> Result<(), Error> {
+        let state = state();
+        let path = path.trim_start_matches('\\');
+
+        if path.is_empty() || path.len() > MOCK_MAX_PATH_LEN {
+            return Err(Error::InvalidParameter);
+        }
+
+        if state.file_count == MOCK_MAX_FILES {
+            return Err(Error::BufferTooSmall);
+        }
+
+        state.files[state.file_count] = MockFile { path, data };
+        state.file_count += 1;
+
+        Ok(())
+    }
+
+    /// Returns the number of files on the mock volume that are open.
+    pub fn open_files(&self) -> usize {
+        state().open_files.iter().filter(|slot| slot.open).count()
+    }
+
+    /// Sets the exit status and exit data that `StartImage` returns.
+    ///
+    /// By default, the image exits with `SUCCESS` and without exit data.
+    pub fn set_image_exit(&self, status: Status, data: Option<&'static str>) {
+        state().image_exit = (status, data);
+    }
+
+    /// Returns the file path in the device path passed to the last call of `LoadImage`, if it
+    /// was called with a device path.
+    pub fn loaded_image_path(&self) -> Option<&str> {
+        state().image_path.as_ref().map(ConsoleBuffer::as_str)
+    }
+
+    /// Returns the image passed in memory to the last call of `LoadImage`, if any.
+    pub fn loaded_image_source(&self) -> Option<&[u8]> {
+        let state = state();
+
+        state
+            .image_source_len
+            .map(move |len| &state.image_source[..len])
+    }
+
+    /// Returns the load options of the image loaded with `LoadImage` when it was started, if it
+    /// was started.
+    pub fn started_load_options(&self) -> Option<&str> {
+        state()
+            .image_load_options
+            .as_ref()
+            .map(ConsoleBuffer::as_str)
+    }
+}
+
+impl Default for MockSystem {
+    fn default() -> MockSystem {
+        MockSystem::new()
+    }
+}
+
+impl Drop for MockSystem {
+    fn drop(&mut self) {
+        boot::unregister_global();
+        LOCK.store(false, Ordering::Release);
+    }
+}
+
+eficall! {
+    fn unbound() {
+        state().unbound_calls += 1;
+    }
+}
+
+eficall! {
+    fn raise_tpl(new_tpl: usize) -> usize {
+        let state = state();
+        let old_tpl = state.tpl;
+        state.tpl = new_tpl;
+
+        old_tpl
+    }
+}
+
+eficall! {
+    fn restore_tpl(old_tpl: usize) {
+        state().tpl = old_tpl;
+    }
+}
+
+eficall! {
+    fn allocate_pages(
+        alloc_type: usize,
+        _memory_type: MemoryType,
+        pages: usize,
+        memory: &mut PhysicalAddress,
+    ) -> Status {
+        let state = state();
+
+        let size = match pages.checked_mul(PAGE_SIZE) {
+            Some(size) => size,
+            None => return Error::OutOfResources.into(),
+        };
+
+        // Only `AllocateAnyPages` (0) and `AllocateMaxAddress` (1) are supported.
+        if alloc_type > 1 {
+            return Error::NotFound.into();
+        }
+
+        let old_used = state.arena_used;
+        let address = match state.allocate(size, PAGE_SIZE) {
+            Ok(address) => address,
+            Err(error) => return error.into(),
+        };
+
+        if alloc_type == 1 && (address + size - 1) as u64 > memory.0 {
+            state.arena_used = old_used;
+            return Error::NotFound.into();
+        }
+
+        *memory = PhysicalAddress(address as u64);
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn free_pages(_memory: PhysicalAddress, _pages: usize) -> Status {
+        state().map_key += 1;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn get_memory_map(
+        memory_map_size: &mut usize,
+        memory_map: *mut MemoryDescriptor,
+        map_key: &mut usize,
+        descriptor_size: &mut usize,
+        descriptor_version: &mut u32,
+    ) -> Status {
+        let state = state();
+        let key = state.map_key;
+        let descriptors = state.memory_map();
+        let required_size = descriptors.len() * size_of::<MemoryDescriptor>();
+
+        *descriptor_size = size_of::<MemoryDescriptor>();
+        *descriptor_version = MEMORY_DESCRIPTOR_VERSION;
+
+        if *memory_map_size < required_size {
+            *memory_map_size = required_size;
+            return Error::BufferTooSmall.into();
+        }
+
+        if memory_map.is_null() {
+            return Error::InvalidParameter.into();
+        }
+
+        // This is safe, because the caller promised that the buffer is large enough.
+        unsafe { slice::from_raw_parts_mut(memory_map, descriptors.len()) }
+            .copy_from_slice(descriptors);
+
+        *memory_map_size = required_size;
+        *map_key = key;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn allocate_pool(
+        _pool_type: MemoryType,
+        size: usize,
+        buffer: &mut usize,
+    ) -> Status {
+        match state().allocate(size, 8) {
+            Ok(address) => {
+                *buffer = address;
+                SUCCESS
+            }
+            Err(error) => error.into(),
+        }
+    }
+}
+
+eficall! {
+    fn free_pool(_buffer: usize) -> Status {
+        state().map_key += 1;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn create_event(
+        event_type: EventType,
+        _notify_tpl: usize,
+        _notify_function: Option<EventNotify>,
+        _notify_context: usize,
+        event: &mut Event,
+    ) -> Status {
+        let state = state();
+
+        match state.events.iter().position(|slot| !slot.open) {
+            Some(index) => {
+                state.events[index] = EventSlot {
+                    open: true,
+                    event_type,
+                    signaled: false,
+                    periodic: false,
+                };
+                *event = Event(index + 1);
+
+                SUCCESS
+            }
+            None => Error::OutOfResources.into(),
+        }
+    }
+}
+
+eficall! {
+    fn set_timer(event: Event, delay: TimerDelay, _trigger_time: u64) -> Status {
+        let slot = match state().event(event) {
+            Some(slot) => slot,
+            None => return Error::InvalidParameter.into(),
+        };
+
+        if !slot.event_type.contains(EventType::TIMER) {
+            return Error::InvalidParameter.into();
+        }
+
+        // Mock time passes instantly, so timers expire as soon as they are set.
+        slot.signaled = delay != TimerDelay::Cancel;
+        slot.periodic = delay == TimerDelay::Periodic;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn wait_for_event(
+        number_of_events: usize,
+        events: *const Event,
+        index: &mut usize,
+    ) -> Status {
+        if number_of_events == 0 || events.is_null() {
+            return Error::InvalidParameter.into();
+        }
+
+        // This is safe, because the caller passes an array of the given length.
+        let events = unsafe { slice::from_raw_parts(events, number_of_events) };
+
+        for (i, event) in events.iter().enumerate() {
+            match state().check_event(*event) {
+                Ok(true) => {
+                    *index = i;
+                    return SUCCESS;
+                }
+                Ok(false) => (),
+                Err(error) => {
+                    *index = i;
+                    return error.into();
+                }
+            }
+        }
+
+        // Nothing could ever signal one of the events, so waiting would block forever.
+        Error::NotReady.into()
+    }
+}
+
+eficall! {
+    fn signal_event(event: Event) -> Status {
+        match state().event(event) {
+            Some(slot) => {
+                slot.signaled = true;
+                SUCCESS
+            }
+            None => Error::InvalidParameter.into(),
+        }
+    }
+}
+
+eficall! {
+    fn close_event(event: Event) -> Status {
+        match state().event(event) {
+            Some(slot) => {
+                *slot = EMPTY_EVENT;
+                SUCCESS
+            }
+            None => Error::InvalidParameter.into(),
+        }
+    }
+}
+
+eficall! {
+    fn check_event(event: Event) -> Status {
+        match state().check_event(event) {
+            Ok(true) => SUCCESS,
+            Ok(false) => Error::NotReady.into(),
+            Err(error) => error.into(),
+        }
+    }
+}
+
+eficall! {
+    fn install_protocol_interface(
+        _handle: &mut OptionalHandle,
+        _protocol: &Guid,
+        _interface_type: InterfaceType,
+        _interface: usize,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn uninstall_protocol_interface(
+        _handle: Handle,
+        _protocol: &Guid,
+        _interface: usize,
+    ) -> Status {
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn handle_protocol(
+        target: Handle,
+        protocol: &Guid,
+        interface: &mut usize,
+    ) -> Status {
+        let found = if target == handle(0) && *protocol == LOADED_IMAGE_PROTOCOL_GUID {
+            loaded_image(0) as *mut LoadedImage as usize
+        } else if target == handle(4) && *protocol == LOADED_IMAGE_PROTOCOL_GUID {
+            if !state().image_loaded {
+                return Error::InvalidParameter.into();
+            }
+
+            loaded_image(1) as *mut LoadedImage as usize
+        } else if target == handle(3) && *protocol == FILE_SYSTEM_GUID {
+            &FILE_SYSTEM as *const SimpleFileSystem as usize
+        } else if target == handle(3) && *protocol == DEVICE_PATH_GUID {
+            &VOLUME_DEVICE_PATH as *const DevicePath as usize
+        } else {
+            return Error::Unsupported.into();
+        };
+
+        *interface = found;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn locate_handle(
+        _search_type: LocateSearchType,
+        _protocol: &Guid,
+        _search_key: usize,
+        _buffer_size: &mut usize,
+        _buffer: *mut Handle,
+    ) -> Status {
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn load_image(
+        _boot_policy: bool,
+        _parent_image_handle: Handle,
+        device_path: *const DevicePath,
+        source_buffer: *const u8,
+        source_size: usize,
+        image_handle: &mut OptionalHandle,
+    ) -> Status {
+        let state = state();
+
+        state.image_path = None;
+        state.image_source_len = None;
+
+        // This is safe, because the caller passes a terminated device path or null.
+        if let Some(device_path) = unsafe { device_path.as_ref() } {
+            let mut path = ConsoleBuffer {
+                buffer: [0; MOCK_CONSOLE_CAPACITY],
+                len: 0,
+            };
+
+            for node in device_path
+                .nodes()
+                .filter(|node| node.is(DevicePathType::Media, DevicePathMediaType::Filepath as u8))
+            {
+                // This is safe, because the path of a file path node follows its header.
+                let units = unsafe {
+                    slice::from_raw_parts(
+                        (node as *const DevicePath).add(1) as *const u16,
+                        (node.length() - size_of::<DevicePath>()) / size_of::<u16>(),
+                    )
+                };
+
+                for character in ucs2::decode_until_nul_lossy(units) {
+                    path.push(character);
+                }
+            }
+
+            state.image_path = Some(path);
+        }
+
+        if source_buffer.is_null() {
+            // Without an image in memory, the image is loaded from the file in the device path.
+            let path = match state.image_path {
+                Some(ref path) => path.as_str().trim_start_matches('\\'),
+                None => return Error::InvalidParameter.into(),
+            };
+
+            match state.find_file(path.as_bytes()) {
+                Some(index) if state.files[index].path.len() == path.len() => (),
+                _ => return Error::NotFound.into(),
+            }
+        } else {
+            if source_size > MOCK_MAX_IMAGE_SIZE {
+                return Error::OutOfResources.into();
+            }
+
+            // This is safe, because the caller passes an image of the given size.
+            state.image_source[..source_size]
+                .copy_from_slice(unsafe { slice::from_raw_parts(source_buffer, source_size) });
+            state.image_source_len = Some(source_size);
+        }
+
+        let loaded_image = loaded_image(1);
+        loaded_image.LoadOptions = ptr::null();
+        loaded_image.LoadOptionsSize = 0;
+
+        state.image_loaded = true;
+        state.image_load_options = None;
+        *image_handle = Some(handle(4));
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn start_image(
+        image_handle: Handle,
+        exit_data_size: &mut usize,
+        exit_data: &mut *mut u16,
+    ) -> Status {
+        let state = state();
+
+        if image_handle != handle(4) || !state.image_loaded {
+            return Error::InvalidParameter.into();
+        }
+
+        let loaded_image = loaded_image(1);
+        let mut load_options = ConsoleBuffer {
+            buffer: [0; MOCK_CONSOLE_CAPACITY],
+            len: 0,
+        };
+
+        if !loaded_image.LoadOptions.is_null() {
+            // This is safe, because the loader sets load options of the given size.
+            let units = unsafe {
+                slice::from_raw_parts(
+                    loaded_image.LoadOptions,
+                    loaded_image.LoadOptionsSize as usize / size_of::<u16>(),
+                )
+            };
+
+            for character in ucs2::decode_until_nul_lossy(units) {
+                load_options.push(character);
+            }
+        }
+
+        // The image is unloaded when it exits.
+        state.image_loaded = false;
+        state.image_load_options = Some(load_options);
+
+        let (status, data) = state.image_exit;
+
+        *exit_data_size = 0;
+        *exit_data = ptr::null_mut();
+
+        if let Some(data) = data {
+            let len = ucs2::encoded_len(data) + 1;
+            let address = match state.allocate(len * size_of::<u16>(), 8) {
+                Ok(address) => address,
+                Err(error) => return error.into(),
+            };
+
+            // This is safe, because the memory was just allocated from the arena for the data.
+            ucs2::encode_into_lossy(data, unsafe {
+                slice::from_raw_parts_mut(address as *mut u16, len)
+            });
+
+            *exit_data_size = len * size_of::<u16>();
+            *exit_data = address as *mut u16;
+        }
+
+        status
+    }
+}
+
+eficall! {
+    fn unload_image(image_handle: Handle) -> Status {
+        let state = state();
+
+        if image_handle != handle(4) || !state.image_loaded {
+            return Error::InvalidParameter.into();
+        }
+
+        state.image_loaded = false;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn exit(
+        _image_handle: Handle,
+        exit_status: isize,
+        _exit_data_size: usize,
+        _exit_data: *const u16,
+    ) -> Status {
+        state().exit_status = Some(exit_status);
+
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn exit_boot_services(image_handle: Handle, map_key: usize) -> Status {
+        let state = state();
+
+        if image_handle != handle(0) || map_key != state.map_key {
+            return Error::InvalidParameter.into();
+        }
+
+        state.exited = true;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn stall(microseconds: usize) -> Status {
+        state().stalled += microseconds as u64;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn set_watchdog_timer(
+        timeout: usize,
+        _watchdog_code: u64,
+        _data_size: usize,
+        _watchdog_data: *const u16,
+    ) -> Status {
+        state().watchdog_timeout = Some(timeout);
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn open_protocol(
+        _handle: Handle,
+        _protocol: &Guid,
+        _interface: &mut usize,
+        _agent_handle: Handle,
+        _controller_handle: OptionalHandle,
+        _attributes: u32,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn protocols_per_handle(
+        _handle: Handle,
+        _protocol_buffer: &mut *mut *const Guid,
+        _protocol_buffer_count: &mut usize,
+    ) -> Status {
+        Error::InvalidParameter.into()
+    }
+}
+
+eficall! {
+    fn locate_handle_buffer(
+        _search_type: LocateSearchType,
+        _protocol: *const Guid,
+        _search_key: usize,
+        no_handles: &mut usize,
+        buffer: &mut *mut Handle,
+    ) -> Status {
+        *no_handles = 0;
+        *buffer = ptr::null_mut();
+
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn locate_protocol(
+        _protocol: &Guid,
+        _registration: usize,
+        interface: &mut usize,
+    ) -> Status {
+        *interface = 0;
+
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn create_event_ex(
+        event_type: EventType,
+        notify_tpl: usize,
+        notify_function: Option<EventNotify>,
+        notify_context: usize,
+        _event_group: *const Guid,
+        event: &mut Event,
+    ) -> Status {
+        create_event(
+            event_type,
+            notify_tpl,
+            notify_function,
+            notify_context,
+            event,
+        )
+    }
+}
+
+eficall! {
+    fn get_time(time: &mut Time, capabilities: *mut TimeCapabilities) -> Status {
+        *time = state().time.unwrap_or_default();
+
+        // This is safe, because the caller passes either null or a valid pointer.
+        if let Some(capabilities) = unsafe { capabilities.as_mut() } {
+            *capabilities = TimeCapabilities::default();
+        }
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn set_time(time: &Time) -> Status {
+        state().time = Some(*time);
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn get_wakeup_time(
+        _enabled: &mut bool,
+        _pending: &mut bool,
+        _time: &mut Time,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn set_wakeup_time(_enable: bool, _time: *const Time) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn set_virtual_address_map(
+        _memory_map_size: usize,
+        _descriptor_size: usize,
+        _descriptor_version: u32,
+        _virtual_map: *const MemoryDescriptor,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn convert_pointer(_debug_disposition: usize, _address: &mut usize) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn get_variable(
+        _variable_name: *const u16,
+        _vendor_guid: &Guid,
+        _attributes: *mut u32,
+        _data_size: &mut usize,
+        _data: *mut u8,
+    ) -> Status {
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn get_next_variable_name(
+        _variable_name_size: &mut usize,
+        _variable_name: *mut u16,
+        _vendor_guid: &mut Guid,
+    ) -> Status {
+        Error::NotFound.into()
+    }
+}
+
+eficall! {
+    fn set_variable(
+        _variable_name: *const u16,
+        _vendor_guid: &Guid,
+        _attributes: u32,
+        _data_size: usize,
+        _data: *const u8,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn get_next_high_monotonic_count(high_count: &mut u32) -> Status {
+        let state = state();
+        state.high_monotonic_count += 1;
+        *high_count = state.high_monotonic_count;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn reset_system(
+        reset_type: ResetType,
+        reset_status: Status,
+        _data_size: usize,
+        _reset_data: *const u8,
+    ) {
+        state().reset = Some((reset_type, reset_status));
+    }
+}
+
+eficall! {
+    fn update_capsule(
+        _capsule_header_array: *const *const CapsuleHeader,
+        _capsule_count: usize,
+        _scatter_gather_list: PhysicalAddress,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn query_capsule_capabilities(
+        _capsule_header_array: *const *const CapsuleHeader,
+        _capsule_count: usize,
+        _maximum_capsule_size: &mut u64,
+        _reset_type: &mut ResetType,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn query_variable_info(
+        _attributes: u32,
+        _maximum_variable_storage_size: &mut u64,
+        _remaining_variable_storage_size: &mut u64,
+        _maximum_variable_size: &mut u64,
+    ) -> Status {
+        Error::Unsupported.into()
+    }
+}
+
+eficall! {
+    fn open_volume(_file_system: &SimpleFileSystem, root: &mut *mut File) -> Status {
+        match state().open_file(0, 0) {
+            Ok(file) => {
+                *root = file;
+                SUCCESS
+            }
+            Err(error) => error.into(),
+        }
+    }
+}
+
+eficall! {
+    fn file_open(
+        file: &mut File,
+        new_handle: &mut *mut File,
+        file_name: *const u16,
+        open_mode: u64,
+        _attributes: u64,
+    ) -> Status {
+        let state = state();
+
+        let parent = match state.find_open_file(file) {
+            Ok(index) => state.open_files[index],
+            Err(error) => return error.into(),
+        };
+
+        // The volume is read-only.
+        if open_mode & FILE_MODE_WRITE != 0 {
+            return Error::WriteProtected.into();
+        }
+
+        let mut path = [0; MOCK_MAX_PATH_LEN];
+        let mut len = parent.len;
+        path[..len].copy_from_slice(&state.files[parent.entry].path.as_bytes()[..len]);
+
+        // This is safe, because the caller passes a null terminated name.
+        let mut name =
+            ucs2::decode_until_nul_lossy(unsafe { ucs2::from_ptr(file_name) }).peekable();
+
+        // A name starting with a backslash is relative to the root directory.
+        if name.peek() == Some(&'\\') {
+            len = 0;
+            name.next();
+        } else if len != 0 {
+            path[len] = b'\\';
+            len += 1;
+        }
+
+        for character in name {
+            if len + character.len_utf8() > MOCK_MAX_PATH_LEN {
+                return Error::NotFound.into();
+            }
+
+            len += character.encode_utf8(&mut path[len..]).len();
+        }
+
+        let entry = match state.find_file(&path[..len]) {
+            Some(entry) => entry,
+            None => return Error::NotFound.into(),
+        };
+
+        match state.open_file(entry, len) {
+            Ok(file) => {
+                *new_handle = file;
+                SUCCESS
+            }
+            Err(error) => error.into(),
+        }
+    }
+}
+
+eficall! {
+    fn file_close(file: &mut File) -> Status {
+        let state = state();
+
+        match state.find_open_file(file) {
+            Ok(index) => {
+                state.open_files[index].open = false;
+                SUCCESS
+            }
+            Err(error) => error.into(),
+        }
+    }
+}
+
+eficall! {
+    fn file_delete(file: &mut File) -> Status {
+        // The volume is read-only, so the file is only closed.
+        match file_close(file) {
+            SUCCESS => Status(Warning::DeleteFailure.code()),
+            status => status,
+        }
+    }
+}
+
+eficall! {
+    fn file_read(file: &mut File, buffer_size: &mut usize, buffer: *mut u8) -> Status {
+        let state = state();
+
+        let open_file = match state.find_open_file(file) {
+            Ok(index) => &mut state.open_files[index],
+            Err(error) => return error.into(),
+        };
+
+        // Reading directory entries is not supported.
+        if open_file.is_directory(&state.files) {
+            return Error::Unsupported.into();
+        }
+
+        let data = state.files[open_file.entry]
+            .data
+            .get(open_file.position..)
+            .unwrap_or(&[]);
+        let size = data.len().min(*buffer_size);
+
+        // This is safe, because the caller passes a buffer of the given size.
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), buffer, size) };
+
+        open_file.position += size;
+        *buffer_size = size;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn file_write(
+        _file: &mut File,
+        _buffer_size: &mut usize,
+        _buffer: *const u8,
+    ) -> Status {
+        Error::WriteProtected.into()
+    }
+}
+
+eficall! {
+    fn file_set_position(file: &mut File, position: u64) -> Status {
+        let state = state();
+
+        let open_file = match state.find_open_file(file) {
+            Ok(index) => &mut state.open_files[index],
+            Err(error) => return error.into(),
+        };
+
+        open_file.position = if position == u64::max_value() {
+            state.files[open_file.entry].data.len()
+        } else {
+            position as usize
+        };
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn file_get_position(file: &mut File, position: &mut u64) -> Status {
+        let state = state();
+
+        match state.find_open_file(file) {
+            Ok(index) => {
+                *position = state.open_files[index].position as u64;
+                SUCCESS
+            }
+            Err(error) => error.into(),
+        }
+    }
+}
+
+eficall! {
+    fn file_get_info(
+        file: &mut File,
+        information_type: &Guid,
+        buffer_size: &mut usize,
+        buffer: *mut u8,
+    ) -> Status {
+        let state = state();
+
+        let open_file = match state.find_open_file(file) {
+            Ok(index) => state.open_files[index],
+            Err(error) => return error.into(),
+        };
+
+        if *information_type != FILE_INFO_ID {
+            return Error::Unsupported.into();
+        }
+
+        if *buffer_size < size_of::<FileInfo>() {
+            *buffer_size = size_of::<FileInfo>();
+            return Error::BufferTooSmall.into();
+        }
+
+        let entry = state.files[open_file.entry];
+        let path = entry.path.get(..open_file.len).unwrap_or("");
+        let mut info = FileInfo {
+            Size: size_of::<FileInfo>() as u64,
+            ..FileInfo::default()
+        };
+
+        if open_file.is_directory(&state.files) {
+            info.Attribute = FILE_DIRECTORY;
+        } else {
+            info.FileSize = entry.data.len() as u64;
+            info.PhysicalSize = entry.data.len() as u64;
+        }
+
+        let name = path.rsplit('\\').next().unwrap_or("");
+        ucs2::encode_into_lossy(name, &mut info.FileName);
+
+        // This is safe, because the caller passes a buffer of the given size.
+        unsafe { (buffer as *mut FileInfo).write_unaligned(info) };
+        *buffer_size = size_of::<FileInfo>();
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn file_set_info(
+        _file: &mut File,
+        _information_type: &Guid,
+        _buffer_size: &mut usize,
+        _buffer: *const u8,
+    ) -> Status {
+        Error::WriteProtected.into()
+    }
+}
+
+eficall! {
+    fn file_flush(_file: &mut File) -> Status {
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn input_reset(_input: &TextInput, _extended_verification: bool) -> Status {
+        state().key_count = 0;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn read_key_stroke(_input: &TextInput, key: &mut TextInputKey) -> Status {
+        let state = state();
+
+        if state.key_count == 0 {
+            return Error::NotReady.into();
+        }
+
+        *key = state.keys[0];
+        state.keys.copy_within(1..state.key_count, 0);
+        state.key_count -= 1;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn output_reset(_output: &TextOutput, _extended_verification: bool) -> Status {
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn output_string(output: &TextOutput, string: *const u16) -> Status {
+        // This is safe, because the caller passes a null terminated string.
+        let string = unsafe { ucs2::from_ptr(string) };
+
+        let state = state();
+        let buffer = if ptr::eq(output, CONSOLE_ERROR.0.get()) {
+            &mut state.console_error
+        } else {
+            &mut state.console_out
+        };
+
+        for character in ucs2::decode_until_nul_lossy(string) {
+            buffer.push(character);
+        }
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn test_string(_output: &TextOutput, _string: *const u16) -> Status {
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn query_mode(
+        _output: &TextOutput,
+        mode: usize,
+        columns: &mut usize,
+        rows: &mut usize,
+    ) -> Status {
+        if mode != 0 {
+            return Error::Unsupported.into();
+        }
+
+        *columns = 80;
+        *rows = 25;
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn set_mode(_output: &TextOutput, mode: usize) -> Status {
+        if mode != 0 {
+            return Error::Unsupported.into();
+        }
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn set_attribute(_output: &TextOutput, _attribute: usize) -> Status {
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn clear_screen(_output: &TextOutput) -> Status {
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn set_cursor_position(_output: &TextOutput, column: usize, row: usize) -> Status {
+        if column >= 80 || row >= 25 {
+            return Error::Unsupported.into();
+        }
+
+        SUCCESS
+    }
+}
+
+eficall! {
+    fn enable_cursor(_output: &TextOutput, _visible: bool) -> Status {
+        SUCCESS
+    }
+}
@@ -0,0 +1,266 @@
+//! Provides convenience functions to download files over the network.
+//!
+//! The functions use the PXE base code protocol, so an image that was booted through PXE can
+//! load its kernel and configuration from the same boot server:
+//!
+//! ```ignore
+//! let server = net::boot_server()?;
+//! let size = net::tftp_get(&server, "vmlinuz", &mut kernel_buffer[..], &mut |_, _| true)?;
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+
+use crate::{
+    boot::BootServices,
+    loaded_image::LoadedImage,
+    protocol::Protocol,
+    pxe::{
+        IpAddress, PxeBaseCode, PxeBaseCodeCallback, PxeBaseCodeCallbackStatus,
+        PxeBaseCodeFunction, PxeBaseCodePacket, PXE_BASE_CODE_CALLBACK_REVISION,
+    },
+    status::Error,
+    system, Handle,
+};
+
+/// The TFTP opcode of a data packet.
+const TFTP_DATA: u16 = 3;
+/// The size of the header of a TFTP data packet.
+const TFTP_DATA_HEADER_SIZE: u32 = 4;
+
+/// Receives a file that is downloaded with `tftp_get`.
+pub trait TftpSink {
+    /// Returns the buffer that the file is read into.
+    ///
+    /// `size` is the size of the file, or `None` if the server does not report sizes. In that
+    /// case the file must fit into the returned buffer.
+    fn buffer(&mut self, size: Option<u64>) -> Result<&mut [u8], Error>;
+
+    /// Called with the size of the file after it was read into the buffer.
+    fn complete(&mut self, _size: usize) {}
+}
+
+impl TftpSink for [u8] {
+    fn buffer(&mut self, size: Option<u64>) -> Result<&mut [u8], Error> {
+        match size {
+            Some(size) if size > self.len() as u64 => Err(Error::BufferTooSmall),
+            Some(size) => Ok(&mut self[..size as usize]),
+            None => Ok(self),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TftpSink for Vec<u8> {
+    /// Resizes the vector to the size of the file.
+    ///
+    /// If the server does not report the size, the capacity of the vector is used.
+    fn buffer(&mut self, size: Option<u64>) -> Result<&mut [u8], Error> {
+        let size = match size {
+            Some(size) if size > usize::max_value() as u64 => return Err(Error::OutOfResources),
+            Some(size) => size as usize,
+            None => self.capacity(),
+        };
+
+        self.resize(size, 0);
+
+        Ok(self)
+    }
+
+    fn complete(&mut self, size: usize) {
+        self.truncate(size);
+    }
+}
+
+/// The callback protocol that reports the progress of a download.
+///
+/// The progress is reported to a separate function rather than the sink, because the firmware
+/// writes to the buffer of the sink while the callback runs.
+#[repr(C)]
+struct ProgressCallback<'a> {
+    /// The protocol interface, which must be the first field.
+    protocol: PxeBaseCodeCallback,
+    /// The function the progress is reported to.
+    progress: RefCell<&'a mut dyn FnMut(u64, Option<u64>) -> bool>,
+    /// The number of bytes received so far.
+    received: Cell<u64>,
+    /// The size of the file, if it is known.
+    total: Option<u64>,
+}
+
+eficall! {
+    fn report_progress(
+        this: &PxeBaseCodeCallback,
+        function: PxeBaseCodeFunction,
+        received: bool,
+        packet_len: u32,
+        packet: *const PxeBaseCodePacket,
+    ) -> PxeBaseCodeCallbackStatus {
+        // This is safe, because the callback is only installed as part of a `ProgressCallback`.
+        let instance = unsafe { &*(this as *const PxeBaseCodeCallback as *const ProgressCallback) };
+
+        if function != PxeBaseCodeFunction::Mtftp
+            || !received
+            || packet.is_null()
+            || packet_len < TFTP_DATA_HEADER_SIZE
+        {
+            return PxeBaseCodeCallbackStatus::Continue;
+        }
+
+        // This is safe, because the firmware passes a valid packet.
+        let packet = unsafe { &*packet };
+        if u16::from_be_bytes([packet.0[0], packet.0[1]]) != TFTP_DATA {
+            return PxeBaseCodeCallbackStatus::Continue;
+        }
+
+        let received = instance.received.get() + u64::from(packet_len - TFTP_DATA_HEADER_SIZE);
+        instance.received.set(received);
+
+        // The borrow cannot fail, because the firmware does not call the callback reentrantly.
+        let proceed = match instance.progress.try_borrow_mut() {
+            Ok(mut progress) => (*progress)(received, instance.total),
+            Err(_) => true,
+        };
+
+        if proceed {
+            PxeBaseCodeCallbackStatus::Continue
+        } else {
+            PxeBaseCodeCallbackStatus::Abort
+        }
+    }
+}
+
+/// Downloads `filename` from the TFTP `server` into `sink` with the PXE base code protocol on
+/// `handle`.
+///
+/// The protocol is started and configured with DHCP if that did not happen yet. The size of the
+/// file is queried first and passed to the sink, then the file is read into the buffer of the
+/// sink.
+///
+/// Whenever a part of the file was received, `progress` is called with the number of bytes
+/// received so far and the size of the file, if it is known. The download is aborted with
+/// `Error::Aborted` if it returns `false`. The progress is reported through a PXE base code
+/// callback protocol on `handle`, so it is not reported if another callback protocol is
+/// installed already.
+///
+/// Returns the size of the file.
+pub fn tftp_get_with<S: TftpSink + ?Sized>(
+    boot_services: &BootServices,
+    handle: Handle,
+    server: &IpAddress,
+    filename: &str,
+    sink: &mut S,
+    progress: &mut dyn FnMut(u64, Option<u64>) -> bool,
+) -> Result<usize, Error> {
+    let pxe = boot_services.handle_protocol::<PxeBaseCode>(handle)?;
+
+    pxe.start()?;
+    pxe.dhcp()?;
+
+    let total = match pxe.tftp_file_size(server, filename) {
+        Ok(size) => Some(size),
+        Err(Error::TftpError) | Err(Error::Unsupported) => None,
+        Err(error) => return Err(error),
+    };
+
+    let callback = ProgressCallback {
+        protocol: PxeBaseCodeCallback {
+            Revision: PXE_BASE_CODE_CALLBACK_REVISION,
+            Callback: report_progress,
+        },
+        progress: RefCell::new(progress),
+        received: Cell::new(0),
+        total,
+    };
+    let interface = &callback.protocol as *const PxeBaseCodeCallback as *const u8;
+
+    let buffer = sink.buffer(total)?;
+
+    // This is safe, because the callback is uninstalled before it goes out of scope.
+    let installed = unsafe {
+        boot_services
+            .install_protocol_interface(Some(handle), &PxeBaseCodeCallback::GUID, interface)
+            .is_ok()
+    };
+    let made_callbacks = pxe.Mode.MakeCallbacks;
+    let enable_callbacks = installed && !made_callbacks;
+
+    let result = if enable_callbacks {
+        pxe.set_make_callbacks(true)
+    } else {
+        Ok(())
+    }
+    .and_then(|()| pxe.tftp_read_file(server, filename, buffer));
+
+    // The callback is uninstalled on every path, because it refers to this stack frame.
+    let restored = if enable_callbacks {
+        pxe.set_make_callbacks(false)
+    } else {
+        Ok(())
+    };
+    let uninstalled = if installed {
+        boot_services.uninstall_protocol_interface(handle, &PxeBaseCodeCallback::GUID, interface)
+    } else {
+        Ok(())
+    };
+
+    let size = result?;
+    restored?;
+    uninstalled?;
+    sink.complete(size);
+
+    Ok(size)
+}
+
+/// Returns the handle of the PXE base code protocol that is used by `tftp_get`.
+///
+/// This is the device that the image was loaded from if it supports the protocol, and the first
+/// device that supports the protocol otherwise.
+pub fn pxe_handle(boot_services: &BootServices, image_handle: Handle) -> Result<Handle, Error> {
+    let device = boot_services
+        .handle_protocol::<LoadedImage>(image_handle)?
+        .DeviceHandle;
+
+    match device {
+        Some(device) if boot_services.supports_protocol(device, &PxeBaseCode::GUID) => Ok(device),
+        _ => boot_services
+            .first_handle_supporting(&PxeBaseCode::GUID)
+            .ok_or(Error::NotFound),
+    }
+}
+
+/// Returns the server that the image was booted from.
+///
+/// The boot services and image handle registered with `system::init` are used. Returns
+/// `Error::NotStarted` if `system::init` was not called.
+pub fn boot_server() -> Result<IpAddress, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+    let image_handle = system::image_handle().ok_or(Error::NotStarted)?;
+
+    let pxe =
+        boot_services.handle_protocol::<PxeBaseCode>(pxe_handle(boot_services, image_handle)?)?;
+    pxe.start()?;
+    pxe.dhcp()?;
+
+    pxe.boot_server()
+}
+
+/// Downloads `filename` from the TFTP `server` into `sink`.
+///
+/// The PXE base code protocol returned by `pxe_handle` is used, see `tftp_get_with` for details.
+/// The boot services and image handle registered with `system::init` are used. Returns
+/// `Error::NotStarted` if `system::init` was not called.
+pub fn tftp_get<S: TftpSink + ?Sized>(
+    server: &IpAddress,
+    filename: &str,
+    sink: &mut S,
+    progress: &mut dyn FnMut(u64, Option<u64>) -> bool,
+) -> Result<usize, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+    let image_handle = system::image_handle().ok_or(Error::NotStarted)?;
+
+    let handle = pxe_handle(boot_services, image_handle)?;
+
+    tftp_get_with(boot_services, handle, server, filename, sink, progress)
+}
@@ -0,0 +1,154 @@
+//! Provides services that allow management of labels contained in a Label Storage Area that are
+//! associated with a specific NVDIMM Device Path.
+//!
+//! The labels describe the namespaces that are located on the persistent memory of the NVDIMM.
+
+use core::convert::TryFrom;
+
+use crate::{
+    guid::{Guid, NVDIMM_LABEL_PROTOCOL_GUID},
+    protocol::Protocol,
+    status::{Error, Status},
+};
+
+/// The signature of a label index block.
+pub const NVDIMM_LABEL_INDEX_SIG: [u8; 16] = *b"NAMESPACE_INDEX\0";
+
+/// The label index block is used to manage the labels in the Label Storage Area.
+///
+/// Two label index blocks are located at the start of the Label Storage Area.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct NvdimmLabelIndexBlock {
+    /// Signature of the index block. Must be `NVDIMM_LABEL_INDEX_SIG`.
+    pub Sig: [u8; 16],
+    /// Boolean attributes of this Label Storage Area.
+    pub Flags: [u8; 3],
+    /// Size of each label in bytes, 128 bytes << LabelSize.
+    pub LabelSize: u8,
+    /// Sequence number used to identify which of the two index blocks is current.
+    pub Seq: u32,
+    /// The offset of this index block in the Label Storage Area.
+    pub MyOff: u64,
+    /// The size of this index block in bytes.
+    pub MySize: u64,
+    /// The offset of the other index block in the Label Storage Area.
+    pub OtherOff: u64,
+    /// The offset of the first slot where labels are stored in this Label Storage Area.
+    pub LabelOff: u64,
+    /// The total number of slots for storing labels in this Label Storage Area.
+    pub NSlot: u32,
+    /// Major version number.
+    pub Major: u16,
+    /// Minor version number.
+    pub Minor: u16,
+    /// 64-bit Fletcher64 checksum of all fields in this index block.
+    pub Checksum: u64,
+    // The free slot bitmap follows the index block.
+}
+
+/// Describes a single namespace label in the Label Storage Area.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct NvdimmLabel {
+    /// Unique identifier for this namespace.
+    pub Uuid: Guid,
+    /// NULL-terminated string using UTF-8 character formatting.
+    pub Name: [u8; 64],
+    /// Boolean attributes of this namespace.
+    pub Flags: u32,
+    /// Number of labels to describe this namespace.
+    pub NLabel: u16,
+    /// Position of this label in the list of labels for this namespace.
+    pub Position: u16,
+    /// Identifies the set of NVDIMMs the interleave set for this namespace is made of.
+    pub SetCookie: u64,
+    /// The logical block size of the namespace, or 0 for byte addressable namespaces.
+    pub LbaSize: u64,
+    /// The DIMM physical address where the NVDIMM contribution to this namespace begins.
+    pub Dpa: u64,
+    /// The size of the contribution to this namespace by this NVDIMM.
+    pub RawSize: u64,
+    /// Slot number of this label in the Label Storage Area.
+    pub Slot: u32,
+    /// Alignment hint used to advertise the preferred alignment of the data.
+    pub Alignment: u8,
+    /// Shall be 0.
+    pub Reserved: [u8; 3],
+    /// Range Type GUID that describes the access mechanism for the specified DPA range.
+    pub TypeGuid: Guid,
+    /// Identifies the address abstraction mechanism for this namespace.
+    pub AddressAbstractionGuid: Guid,
+    /// Shall be 0.
+    pub Reserved1: [u8; 88],
+    /// 64-bit Fletcher64 checksum of all fields in this label.
+    pub Checksum: u64,
+}
+
+/// Provides services that allow management of labels contained in a Label Storage Area.
+#[repr(C)]
+pub struct NvdimmLabelProtocol {
+    /// Retrieves the size of the Label Storage Area and the maximum amount of label data that
+    /// can be transferred in a single call.
+    pub LabelStorageInformation: eficall! { fn(
+        &NvdimmLabelProtocol,
+        SizeOfLabelStorageArea: &mut u32,
+        MaxTransferLength: &mut u32,
+    ) -> Status },
+    /// Retrieves the label data for the requested offset and length from within the Label
+    /// Storage Area.
+    pub LabelStorageRead: eficall! { fn(
+        &NvdimmLabelProtocol,
+        Offset: u32,
+        TransferLength: u32,
+        LabelData: *mut u8,
+    ) -> Status },
+    /// Writes the label data for the requested offset and length in to the Label Storage Area.
+    pub LabelStorageWrite: eficall! { fn(
+        &NvdimmLabelProtocol,
+        Offset: u32,
+        TransferLength: u32,
+        LabelData: *const u8,
+    ) -> Status },
+}
+
+unsafe impl Protocol for NvdimmLabelProtocol {
+    const GUID: Guid = NVDIMM_LABEL_PROTOCOL_GUID;
+}
+
+impl NvdimmLabelProtocol {
+    /// Retrieves the size of the Label Storage Area and the maximum amount of label data that
+    /// can be transferred in a single call.
+    ///
+    /// Returns the size of the Label Storage Area and the maximum transfer length in bytes.
+    pub fn label_storage_information(&self) -> Result<(u32, u32), Error> {
+        let mut size = 0;
+        let mut max_transfer_length = 0;
+
+        (self.LabelStorageInformation)(self, &mut size, &mut max_transfer_length)?;
+
+        Ok((size, max_transfer_length))
+    }
+
+    /// Reads label data at the given offset in the Label Storage Area into the buffer.
+    ///
+    /// The buffer must not be longer than the maximum transfer length.
+    pub fn label_storage_read(&self, offset: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        let length = u32::try_from(buffer.len()).map_err(|_| Error::BadBufferSize)?;
+
+        (self.LabelStorageRead)(self, offset, length, buffer.as_mut_ptr())?;
+
+        Ok(())
+    }
+
+    /// Writes the label data in the buffer at the given offset in the Label Storage Area.
+    ///
+    /// The buffer must not be longer than the maximum transfer length.
+    pub fn label_storage_write(&self, offset: u32, buffer: &[u8]) -> Result<(), Error> {
+        let length = u32::try_from(buffer.len()).map_err(|_| Error::BadBufferSize)?;
+
+        (self.LabelStorageWrite)(self, offset, length, buffer.as_ptr())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+//! Reporting of panics on the console.
+//!
+//! Without a report, a panic in a boot loader usually shows up as a silent hang. `print_panic`
+//! can be called from a custom panic handler. With the `panic-handler` feature enabled, this
+//! module provides a panic handler that prints the panic to the standard error console using the
+//! system table registered with `system::init`, waits for `PANIC_RESET_DELAY` seconds and then
+//! resets the system. With the `diagnostics` feature also enabled, it prints a register and stack
+//! dump after the panic message. After boot services were exited, the consoles are no longer
+//! usable, so the panic handler resets the system right away. A panic on an application processor
+//! started by `smp::run_on_all_aps` stops the processor instead.
+
+use core::{fmt::Write, panic::PanicInfo};
+
+use crate::{
+    status::Error,
+    system::SystemTable,
+    text::{BackgroundColor, BufferedWriter, Color, ForegroundColor},
+};
+
+/// The number of seconds the panic handler waits before it resets the system.
+pub const PANIC_RESET_DELAY: usize = 10;
+
+/// Prints the panic message and location in red to the standard error console.
+pub fn print_panic(system_table: &'static SystemTable, info: &PanicInfo) -> Result<(), Error> {
+    let output = &*system_table.ConsoleError;
+    let color = Color::new(ForegroundColor::LightRed, BackgroundColor::Black);
+
+    output.with_color(color, |output| {
+        let mut writer = BufferedWriter::new(output);
+
+        writer
+            .write_fmt(format_args!("\r\n{}\r\n", info))
+            .map_err(|_| Error::DeviceError)?;
+
+        writer.flush()
+    })?
+}
+
+#[cfg(feature = "panic-handler")]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    use crate::{reset::ResetType, smp, system};
+
+    // Boot services may only be used on the bootstrap processor, which reports the panic of an
+    // application processor instead.
+    if smp::on_application_processor() {
+        smp::halt_after_panic();
+    }
+
+    if let Some(system_table) = system::system_table() {
+        // The consoles belong to the boot services, so they must not be used after boot services
+        // were exited.
+        if let Some(boot_services) = system::boot_services() {
+            // There is nothing left to do if the panic cannot be printed.
+            let _ = print_panic(system_table, info);
+
+            #[cfg(all(
+                feature = "diagnostics",
+                any(target_arch = "x86_64", target_arch = "aarch64")
+            ))]
+            let _ = crate::diagnostics::print_dump(system_table);
+
+            for _ in 0..PANIC_RESET_DELAY {
+                let _ = boot_services.stall(1_000_000);
+            }
+        }
+
+        system_table
+            .RuntimeServices
+            .reset_system(ResetType::Cold, Error::Aborted.into())
+    }
+
+    // Without a system table, the system cannot be reset.
+    loop {}
+}
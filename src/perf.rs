@@ -0,0 +1,315 @@
+//! Measures where the time during boot goes.
+//!
+//! After `init` selected a clock, `span` starts a named span that ends when the returned guard is
+//! dropped. Spans can be nested and are recorded into a fixed size table, which `write_report`
+//! prints with the duration of every span.
+//!
+//! ```ignore
+//! perf::init(boot_services)?;
+//!
+//! {
+//!     let _span = perf::span("load kernel");
+//!     // ...
+//! }
+//!
+//! perf::write_report(&mut writer)?;
+//! ```
+//!
+//! The Timestamp protocol is used as the clock if the firmware provides it. Otherwise the time
+//! stamp counter of `x86_64` processors is used, with its frequency measured using `Stall`. The
+//! Timestamp protocol is only used while the boot services registered with `boot::register_global`
+//! are available, so no time is measured with it after boot services are exited.
+
+use core::{
+    cell::UnsafeCell,
+    fmt::{self, Write},
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    boot::{self, BootServices},
+    status::Error,
+    timestamp::{Timestamp, TimestampProperties},
+};
+
+/// The maximum number of spans that are recorded.
+pub const MAX_SPANS: usize = 64;
+
+/// The number of microseconds the time stamp counter is measured for to find its frequency.
+pub const CALIBRATION_MICROSECONDS: usize = 10_000;
+
+/// The clock that is used to measure spans.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClockSource {
+    /// The Timestamp protocol of the firmware.
+    Timestamp,
+    /// The time stamp counter of the processor, with a measured frequency.
+    Tsc,
+}
+
+/// A span that was recorded.
+#[derive(Clone, Copy, Debug)]
+pub struct SpanRecord {
+    /// The name of the span.
+    pub name: &'static str,
+    /// The number of spans that were running when this span started.
+    pub depth: usize,
+    /// The clock value when the span started.
+    pub start: u64,
+    /// The clock value when the span ended, if it already ended.
+    pub end: Option<u64>,
+}
+
+impl SpanRecord {
+    /// The value of unused entries of the table.
+    const EMPTY: SpanRecord = SpanRecord {
+        name: "",
+        depth: 0,
+        start: 0,
+        end: None,
+    };
+
+    /// Returns the duration of the span, if it already ended.
+    pub fn duration(&self) -> Option<Duration> {
+        self.end
+            .map(|end| ticks_to_duration(ticks_between(self.start, end)))
+    }
+}
+
+/// The table of recorded spans.
+struct Records(UnsafeCell<[SpanRecord; MAX_SPANS]>);
+
+// Every span writes only its own entry, which it reserved with `SPAN_COUNT`.
+unsafe impl Sync for Records {}
+
+/// The Timestamp protocol, if it is used as the clock.
+static TIMESTAMP: AtomicPtr<Timestamp> = AtomicPtr::new(ptr::null_mut());
+/// The frequency of the clock in Hz, or 0 if no clock was selected.
+static FREQUENCY: AtomicU64 = AtomicU64::new(0);
+/// The value of the clock before it wraps around to zero.
+static END_VALUE: AtomicU64 = AtomicU64::new(u64::max_value());
+/// The number of spans that were started, including those that did not fit into the table.
+static SPAN_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// The number of recorded spans that are running.
+static DEPTH: AtomicUsize = AtomicUsize::new(0);
+/// The recorded spans.
+static RECORDS: Records = Records(UnsafeCell::new([SpanRecord::EMPTY; MAX_SPANS]));
+
+/// Selects the clock used to measure spans.
+///
+/// The Timestamp protocol is only selected if the boot services are registered globally, since
+/// it cannot be used anymore once they are exited.
+///
+/// Returns `Error::Unsupported` if the firmware does not provide the Timestamp protocol and the
+/// processor has no usable time stamp counter.
+pub fn init(boot_services: &'static BootServices) -> Result<ClockSource, Error> {
+    let timestamp = match boot::global() {
+        Some(_) => boot_services.locate_protocol::<Timestamp>().ok(),
+        None => None,
+    };
+
+    if let Some(timestamp) = timestamp {
+        let properties = timestamp.properties()?;
+
+        if properties.Frequency != 0 {
+            END_VALUE.store(properties.EndValue, Ordering::SeqCst);
+            TIMESTAMP.store(timestamp as *const _ as *mut _, Ordering::SeqCst);
+            FREQUENCY.store(properties.Frequency, Ordering::SeqCst);
+
+            return Ok(ClockSource::Timestamp);
+        }
+    }
+
+    let frequency = calibrate_tsc(boot_services)?;
+
+    TIMESTAMP.store(ptr::null_mut(), Ordering::SeqCst);
+    END_VALUE.store(u64::max_value(), Ordering::SeqCst);
+    FREQUENCY.store(frequency, Ordering::SeqCst);
+
+    Ok(ClockSource::Tsc)
+}
+
+/// Measures the frequency of the time stamp counter.
+#[cfg(target_arch = "x86_64")]
+fn calibrate_tsc(boot_services: &BootServices) -> Result<u64, Error> {
+    let start = read_tsc();
+
+    boot_services.stall(CALIBRATION_MICROSECONDS)?;
+
+    let ticks = read_tsc().wrapping_sub(start);
+
+    Ok(ticks * (1_000_000 / CALIBRATION_MICROSECONDS as u64))
+}
+
+/// Measures the frequency of the time stamp counter.
+#[cfg(not(target_arch = "x86_64"))]
+fn calibrate_tsc(_boot_services: &BootServices) -> Result<u64, Error> {
+    Err(Error::Unsupported)
+}
+
+/// Reads the time stamp counter.
+#[cfg(target_arch = "x86_64")]
+fn read_tsc() -> u64 {
+    // This is safe, because every `x86_64` processor has a time stamp counter.
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
+
+/// Returns the current value of the clock.
+///
+/// Returns `None` if `init` was not called or if the clock is the Timestamp protocol and boot
+/// services were exited.
+pub fn now() -> Option<u64> {
+    if FREQUENCY.load(Ordering::SeqCst) == 0 {
+        return None;
+    }
+
+    // This is safe, because only protocol interfaces of the `'static` boot services are stored
+    // and they are only used while the boot services are registered.
+    match unsafe { TIMESTAMP.load(Ordering::SeqCst).as_ref() } {
+        Some(_) if boot::global().is_none() => None,
+        Some(timestamp) => Some(timestamp.timestamp()),
+        #[cfg(target_arch = "x86_64")]
+        None => Some(read_tsc()),
+        #[cfg(not(target_arch = "x86_64"))]
+        None => None,
+    }
+}
+
+/// Returns the number of clock ticks from `start` to `end`.
+fn ticks_between(start: u64, end: u64) -> u64 {
+    let properties = TimestampProperties {
+        Frequency: FREQUENCY.load(Ordering::SeqCst),
+        EndValue: END_VALUE.load(Ordering::SeqCst),
+    };
+
+    properties.ticks_between(start, end)
+}
+
+/// Converts a number of clock ticks to a duration.
+///
+/// Returns a zero duration if `init` was not called.
+pub fn ticks_to_duration(ticks: u64) -> Duration {
+    let frequency = u128::from(FREQUENCY.load(Ordering::SeqCst));
+
+    if frequency == 0 {
+        return Duration::from_secs(0);
+    }
+
+    let nanoseconds = u128::from(ticks) * 1_000_000_000 / frequency;
+
+    Duration::new(
+        (nanoseconds / 1_000_000_000) as u64,
+        (nanoseconds % 1_000_000_000) as u32,
+    )
+}
+
+/// Starts a span that ends when the returned guard is dropped.
+///
+/// Nothing is recorded if `init` was not called or the table is full.
+pub fn span(name: &'static str) -> Span {
+    let start = match now() {
+        Some(start) => start,
+        None => return Span { index: None },
+    };
+
+    let index = SPAN_COUNT.fetch_add(1, Ordering::SeqCst);
+
+    if index >= MAX_SPANS {
+        return Span { index: None };
+    }
+
+    let record = SpanRecord {
+        name,
+        depth: DEPTH.fetch_add(1, Ordering::SeqCst),
+        start,
+        end: None,
+    };
+
+    // This is safe, because the entry was reserved for this span.
+    unsafe { (*RECORDS.0.get())[index] = record };
+
+    Span { index: Some(index) }
+}
+
+/// A running span, which ends when it is dropped.
+#[must_use = "the span ends when it is dropped"]
+#[derive(Debug)]
+pub struct Span {
+    /// The entry of the span in the table, if it is recorded.
+    index: Option<usize>,
+}
+
+impl Span {
+    /// Returns the time since the span started, if it is recorded.
+    pub fn elapsed(&self) -> Option<Duration> {
+        let index = self.index?;
+        let now = now()?;
+
+        // This is safe, because only this span writes its entry.
+        let start = unsafe { (*RECORDS.0.get())[index].start };
+
+        Some(ticks_to_duration(ticks_between(start, now)))
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(index) = self.index {
+            let end = now();
+
+            // This is safe, because only this span writes its entry.
+            unsafe { (*RECORDS.0.get())[index].end = end };
+
+            // Spans that were running when `reset` was called must not make the depth wrap around.
+            let _ = DEPTH.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |depth| {
+                depth.checked_sub(1)
+            });
+        }
+    }
+}
+
+/// Returns the recorded spans in the order they were started.
+pub fn spans() -> impl Iterator<Item = SpanRecord> {
+    let count = SPAN_COUNT.load(Ordering::SeqCst).min(MAX_SPANS);
+
+    // This is safe, because the entries up to `count` were written when their spans started.
+    (0..count).map(|index| unsafe { (*RECORDS.0.get())[index] })
+}
+
+/// Returns the number of spans that did not fit into the table.
+pub fn dropped_spans() -> usize {
+    SPAN_COUNT.load(Ordering::SeqCst).saturating_sub(MAX_SPANS)
+}
+
+/// Forgets all recorded spans.
+///
+/// This should not be called while spans are running, since their entries are reused.
+pub fn reset() {
+    DEPTH.store(0, Ordering::SeqCst);
+    SPAN_COUNT.store(0, Ordering::SeqCst);
+}
+
+/// Writes the duration of every recorded span, indented by its nesting depth.
+pub fn write_report<W: Write>(writer: &mut W) -> fmt::Result {
+    for record in spans() {
+        write!(writer, "{:1$}", "", record.depth * 2)?;
+
+        match record.duration() {
+            Some(duration) => write!(
+                writer,
+                "{}: {}.{:03} ms\r\n",
+                record.name,
+                duration.as_millis(),
+                duration.subsec_micros() % 1000
+            )?,
+            None => write!(writer, "{}: running\r\n", record.name)?,
+        }
+    }
+
+    match dropped_spans() {
+        0 => Ok(()),
+        dropped => write!(writer, "{} spans were not recorded\r\n", dropped),
+    }
+}
@@ -7,7 +7,12 @@
 //! was accessed. This protocol is attached the device handle of a pointer device, and can be used for
 //! input from the user in the preboot environment.
 
-use crate::{status::Status, Event};
+use crate::{
+    guid::{Guid, SIMPLE_POINTER_GUID},
+    protocol::Protocol,
+    status::Status,
+    Event,
+};
 
 /// The following data values in the EFI_SIMPLE_POINTER_MODE interface are read-only and are
 /// changed by using the appropriate interface functions.
@@ -73,11 +78,15 @@ pub struct SimplePointerState {
 #[repr(C)]
 pub struct SimplePointer {
     /// Resets the pointer device.
-    pub Reset: extern "win64" fn(&mut SimplePointer, ExtendedVerification: bool) -> Status,
+    pub Reset: eficall! { fn(&mut SimplePointer, ExtendedVerification: bool) -> Status },
     /// Retrieves the current state of the pointer device.
-    pub GetState: extern "win64" fn(&mut SimplePointer, State: &mut SimplePointerState) -> Status,
+    pub GetState: eficall! { fn(&mut SimplePointer, State: &mut SimplePointerState) -> Status },
     /// Event to use with EFI_BOOT_SERVICES.WaitForEvent() to wait for input from the pointer device.
     pub WaitForInput: Event,
     /// Pointer to EFI_SIMPLE_POINTER_MODE data.
     pub Mode: &'static mut SimplePointerMode,
 }
+
+unsafe impl Protocol for SimplePointer {
+    const GUID: Guid = SIMPLE_POINTER_GUID;
+}
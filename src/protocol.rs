@@ -0,0 +1,33 @@
+//! Associates protocol interface types with the GUIDs that identify them.
+//!
+//! This allows protocols to be located by type, for example with
+//! `BootServices::handle_protocol::<BlockIo>(handle)`.
+
+use crate::guid::Guid;
+
+/// A protocol interface identified by a GUID.
+///
+/// # Safety
+/// `GUID` must identify a protocol whose interface has exactly the layout of the implementing type.
+/// Otherwise the typed protocol lookups hand out references to memory of the wrong type.
+pub unsafe trait Protocol {
+    /// The GUID identifying the protocol.
+    const GUID: Guid;
+}
+
+/// Implements `Protocol` for an interface type.
+///
+/// ```ignore
+/// unsafe_protocol!(MyProtocol, MY_PROTOCOL_GUID);
+/// ```
+///
+/// The macro expands to an `unsafe impl`, so the caller is responsible for upholding the
+/// requirements of the `Protocol` trait.
+#[macro_export]
+macro_rules! unsafe_protocol {
+    ($protocol:ty, $guid:expr) => {
+        unsafe impl $crate::protocol::Protocol for $protocol {
+            const GUID: $crate::guid::Guid = $guid;
+        }
+    };
+}
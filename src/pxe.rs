@@ -0,0 +1,448 @@
+//! Provides the PXE base code protocol, which is used to boot from the network.
+//!
+//! The protocol is installed on the network device that a PXE boot was started from. It already
+//! holds the DHCP configuration of the boot, so an image loaded through PXE can download further
+//! files from the same server with TFTP.
+
+use core::ptr;
+
+use crate::{
+    guid::Guid,
+    guids,
+    protocol::Protocol,
+    status::{Error, Status},
+};
+
+/// The maximum length of a file name passed to the TFTP functions, including the null
+/// terminator.
+pub const MAX_FILENAME_LEN: usize = 256;
+
+/// The maximum number of addresses in the IP receive filter.
+pub const MAX_IP_CNT: usize = 8;
+/// The number of entries in the ARP cache.
+pub const MAX_ARP_ENTRIES: usize = 8;
+/// The number of entries in the route table.
+pub const MAX_ROUTE_ENTRIES: usize = 8;
+
+/// An IPv4 or IPv6 address.
+///
+/// IPv4 addresses only use the first four bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C, align(4))]
+pub struct IpAddress(pub [u8; 16]);
+
+impl IpAddress {
+    /// Creates an IPv4 address.
+    pub fn v4(address: [u8; 4]) -> IpAddress {
+        let mut bytes = [0; 16];
+        bytes[..4].copy_from_slice(&address);
+
+        IpAddress(bytes)
+    }
+
+    /// Returns the address as an IPv4 address.
+    pub fn as_v4(&self) -> [u8; 4] {
+        [self.0[0], self.0[1], self.0[2], self.0[3]]
+    }
+}
+
+/// A hardware address, such as a MAC address, padded to 32 bytes.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct MacAddress(pub [u8; 32]);
+
+/// A DHCP or PXE packet that was received or sent by the protocol.
+#[derive(Clone, Copy)]
+#[repr(C, align(4))]
+pub struct PxeBaseCodePacket(pub [u8; 1472]);
+
+impl PxeBaseCodePacket {
+    /// Returns the address of the next server in the boot process (`siaddr`) of a DHCPv4 packet.
+    pub fn server_address(&self) -> IpAddress {
+        let mut address = [0; 4];
+        address.copy_from_slice(&self.0[20..24]);
+
+        IpAddress::v4(address)
+    }
+
+    /// Returns the null terminated boot file name of a DHCPv4 packet (`file`).
+    pub fn boot_file(&self) -> &[u8] {
+        let file = &self.0[108..236];
+        let len = file
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(file.len());
+
+        &file[..len]
+    }
+}
+
+/// The filter for received IP packets.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct PxeBaseCodeIpFilter {
+    /// The `IP_FILTER_*` bits of the filter.
+    pub Filters: u8,
+    /// The number of valid entries in `IpList`.
+    pub IpCnt: u8,
+    pub Reserved: u16,
+    /// The addresses that packets are received for.
+    pub IpList: [IpAddress; MAX_IP_CNT],
+}
+
+/// An entry of the ARP cache.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct PxeBaseCodeArpEntry {
+    pub IpAddr: IpAddress,
+    pub MacAddr: MacAddress,
+}
+
+/// An entry of the route table.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct PxeBaseCodeRouteEntry {
+    pub IpAddr: IpAddress,
+    pub SubnetMask: IpAddress,
+    pub GwAddr: IpAddress,
+}
+
+/// The last ICMP error packet that was received.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct PxeBaseCodeIcmpError {
+    pub Type: u8,
+    pub Code: u8,
+    pub Checksum: u16,
+    pub u: u32,
+    pub Data: [u8; 494],
+}
+
+/// The last TFTP error packet that was received.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct PxeBaseCodeTftpError {
+    /// The TFTP error code.
+    pub ErrorCode: u8,
+    /// The null terminated error message sent by the server.
+    pub ErrorString: [u8; 127],
+}
+
+impl PxeBaseCodeTftpError {
+    /// Returns the error message sent by the server.
+    pub fn message(&self) -> &[u8] {
+        let len = self
+            .ErrorString
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(self.ErrorString.len());
+
+        &self.ErrorString[..len]
+    }
+}
+
+/// The current state of the PXE base code protocol.
+#[repr(C)]
+pub struct PxeBaseCodeMode {
+    /// Whether `Start` was called.
+    pub Started: bool,
+    pub Ipv6Available: bool,
+    pub Ipv6Supported: bool,
+    /// Whether the protocol was started with IPv6.
+    pub UsingIpv6: bool,
+    pub BisSupported: bool,
+    pub BisDetected: bool,
+    pub AutoArp: bool,
+    pub SendGUID: bool,
+    pub DhcpDiscoverValid: bool,
+    /// Whether `DhcpAck` contains a valid packet.
+    pub DhcpAckReceived: bool,
+    /// Whether `ProxyOffer` contains a valid packet.
+    pub ProxyOfferReceived: bool,
+    pub PxeDiscoverValid: bool,
+    pub PxeReplyReceived: bool,
+    pub PxeBisReplyReceived: bool,
+    pub IcmpErrorReceived: bool,
+    /// Whether `TftpError` contains a valid packet.
+    pub TftpErrorReceived: bool,
+    /// Whether the callback protocol is called during network operations.
+    pub MakeCallbacks: bool,
+    pub TTL: u8,
+    pub ToS: u8,
+    /// The address of the device.
+    pub StationIp: IpAddress,
+    pub SubnetMask: IpAddress,
+    pub DhcpDiscover: PxeBaseCodePacket,
+    /// The DHCP acknowledgement that configured the device.
+    pub DhcpAck: PxeBaseCodePacket,
+    /// The offer of a proxy DHCP server that provided the boot server.
+    pub ProxyOffer: PxeBaseCodePacket,
+    pub PxeDiscover: PxeBaseCodePacket,
+    pub PxeReply: PxeBaseCodePacket,
+    pub PxeBisReply: PxeBaseCodePacket,
+    pub IpFilter: PxeBaseCodeIpFilter,
+    pub ArpCacheEntries: u32,
+    pub ArpCache: [PxeBaseCodeArpEntry; MAX_ARP_ENTRIES],
+    pub RouteTableEntries: u32,
+    pub RouteTable: [PxeBaseCodeRouteEntry; MAX_ROUTE_ENTRIES],
+    pub IcmpError: PxeBaseCodeIcmpError,
+    pub TftpError: PxeBaseCodeTftpError,
+}
+
+/// The operation performed by `Mtftp`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum PxeBaseCodeTftpOpcode {
+    TftpFirst,
+    /// Queries the size of a file with the TFTP `tsize` option.
+    TftpGetFileSize,
+    /// Reads a file with TFTP.
+    TftpReadFile,
+    /// Writes a file with TFTP.
+    TftpWriteFile,
+    /// Reads a directory listing with TFTP.
+    TftpReadDirectory,
+    /// Queries the size of a file with multicast TFTP.
+    MtftpGetFileSize,
+    /// Reads a file with multicast TFTP.
+    MtftpReadFile,
+    /// Reads a directory listing with multicast TFTP.
+    MtftpReadDirectory,
+    MtftpLast,
+}
+
+/// The PXE base code protocol.
+#[repr(C)]
+pub struct PxeBaseCode {
+    /// The revision of the protocol.
+    pub Revision: u64,
+    /// Enables the use of the protocol.
+    pub Start: eficall! { fn(&PxeBaseCode, UseIpv6: bool) -> Status },
+    /// Disables the use of the protocol.
+    pub Stop: eficall! { fn(&PxeBaseCode) -> Status },
+    /// Configures the device with DHCP.
+    pub Dhcp: eficall! { fn(&PxeBaseCode, SortOffers: bool) -> Status },
+    pub Discover: usize,
+    /// Performs a TFTP or multicast TFTP operation.
+    pub Mtftp: eficall! { fn(
+        &PxeBaseCode,
+        Operation: PxeBaseCodeTftpOpcode,
+        BufferPtr: *mut u8,
+        Overwrite: bool,
+        BufferSize: &mut u64,
+        BlockSize: *const usize,
+        ServerIp: &IpAddress,
+        Filename: *const u8,
+        Info: *const u8,
+        DontUseBuffer: bool,
+    ) -> Status },
+    pub UdpWrite: usize,
+    pub UdpRead: usize,
+    pub SetIpFilter: usize,
+    pub Arp: usize,
+    /// Changes the parameters of the protocol. Parameters passed as null are not changed.
+    pub SetParameters: eficall! { fn(
+        &PxeBaseCode,
+        NewAutoArp: *const bool,
+        NewSendGUID: *const bool,
+        NewTTL: *const u8,
+        NewToS: *const u8,
+        NewMakeCallback: *const bool,
+    ) -> Status },
+    pub SetStationIp: usize,
+    pub SetPackets: usize,
+    /// The current state of the protocol.
+    pub Mode: &'static PxeBaseCodeMode,
+}
+
+unsafe impl Protocol for PxeBaseCode {
+    const GUID: Guid = guids::protocol::PXE_BASE_CODE;
+}
+
+/// Writes `filename` with a null terminator into `buffer`.
+fn ascii_filename<'a>(
+    filename: &str,
+    buffer: &'a mut [u8; MAX_FILENAME_LEN],
+) -> Result<&'a [u8], Error> {
+    if !filename.is_ascii() || filename.contains('\0') || filename.len() >= MAX_FILENAME_LEN {
+        return Err(Error::InvalidParameter);
+    }
+
+    buffer[..filename.len()].copy_from_slice(filename.as_bytes());
+    buffer[filename.len()] = 0;
+
+    Ok(&buffer[..=filename.len()])
+}
+
+impl PxeBaseCode {
+    /// Starts the protocol with IPv4, unless it was already started.
+    pub fn start(&self) -> Result<(), Error> {
+        if self.Mode.Started {
+            return Ok(());
+        }
+
+        let status = (self.Start)(self, false);
+        if status == Error::AlreadyStarted.into() {
+            return Ok(());
+        }
+        status?;
+
+        Ok(())
+    }
+
+    /// Stops the protocol.
+    pub fn stop(&self) -> Result<(), Error> {
+        (self.Stop)(self)?;
+
+        Ok(())
+    }
+
+    /// Configures the device with DHCP, unless it was already configured.
+    pub fn dhcp(&self) -> Result<(), Error> {
+        if self.Mode.DhcpAckReceived {
+            return Ok(());
+        }
+
+        (self.Dhcp)(self, true)?;
+
+        Ok(())
+    }
+
+    /// Returns the server that provided the boot file.
+    ///
+    /// This is the `siaddr` of the proxy DHCP offer if one was received and of the DHCP
+    /// acknowledgement otherwise. Returns `Error::NotReady` if the device was not configured and
+    /// `Error::Unsupported` if IPv6 is used.
+    pub fn boot_server(&self) -> Result<IpAddress, Error> {
+        let mode = self.Mode;
+
+        if mode.UsingIpv6 {
+            Err(Error::Unsupported)
+        } else if mode.ProxyOfferReceived {
+            Ok(mode.ProxyOffer.server_address())
+        } else if mode.DhcpAckReceived {
+            Ok(mode.DhcpAck.server_address())
+        } else {
+            Err(Error::NotReady)
+        }
+    }
+
+    /// Enables or disables calls of the callback protocol during network operations.
+    pub fn set_make_callbacks(&self, make_callbacks: bool) -> Result<(), Error> {
+        (self.SetParameters)(
+            self,
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            &make_callbacks,
+        )?;
+
+        Ok(())
+    }
+
+    /// Queries the size of a file on a TFTP server.
+    ///
+    /// The server must support the `tsize` option. `filename` must be ASCII and shorter than
+    /// `MAX_FILENAME_LEN`.
+    pub fn tftp_file_size(&self, server: &IpAddress, filename: &str) -> Result<u64, Error> {
+        let mut filename_buffer = [0; MAX_FILENAME_LEN];
+        let filename = ascii_filename(filename, &mut filename_buffer)?;
+        let mut size = 0;
+
+        (self.Mtftp)(
+            self,
+            PxeBaseCodeTftpOpcode::TftpGetFileSize,
+            ptr::null_mut(),
+            false,
+            &mut size,
+            ptr::null(),
+            server,
+            filename.as_ptr(),
+            ptr::null(),
+            false,
+        )?;
+
+        Ok(size)
+    }
+
+    /// Reads a file from a TFTP server into `buffer`.
+    ///
+    /// Returns the size of the file. Returns `Error::BufferTooSmall` if the file does not fit
+    /// into `buffer`. `filename` must be ASCII and shorter than `MAX_FILENAME_LEN`.
+    pub fn tftp_read_file(
+        &self,
+        server: &IpAddress,
+        filename: &str,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        let mut filename_buffer = [0; MAX_FILENAME_LEN];
+        let filename = ascii_filename(filename, &mut filename_buffer)?;
+        let mut size = buffer.len() as u64;
+
+        (self.Mtftp)(
+            self,
+            PxeBaseCodeTftpOpcode::TftpReadFile,
+            buffer.as_mut_ptr(),
+            false,
+            &mut size,
+            ptr::null(),
+            server,
+            filename.as_ptr(),
+            ptr::null(),
+            false,
+        )?;
+
+        Ok(size as usize)
+    }
+}
+
+/// The operation during which the callback protocol is called.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum PxeBaseCodeFunction {
+    FunctionFirst,
+    Dhcp,
+    Discover,
+    Mtftp,
+    UdpWrite,
+    UdpRead,
+    Arp,
+    Igmp,
+    FunctionLast,
+}
+
+/// Whether the operation continues after a call of the callback protocol.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum PxeBaseCodeCallbackStatus {
+    /// The operation continues.
+    Continue,
+    /// The operation is aborted and returns `Error::Aborted`.
+    Abort,
+}
+
+/// The current revision of the PXE base code callback protocol.
+pub const PXE_BASE_CODE_CALLBACK_REVISION: u64 = 0x0001_0000;
+
+/// The callback protocol, which is installed on the handle of the PXE base code protocol by its
+/// caller to observe packets sent and received by the protocol.
+#[repr(C)]
+pub struct PxeBaseCodeCallback {
+    /// The revision of the protocol.
+    pub Revision: u64,
+    /// Called when a packet is sent or received and periodically while waiting for packets.
+    ///
+    /// `Packet` is null when no packet was sent or received.
+    pub Callback: eficall! { fn(
+        &PxeBaseCodeCallback,
+        Function: PxeBaseCodeFunction,
+        Received: bool,
+        PacketLen: u32,
+        Packet: *const PxeBaseCodePacket,
+    ) -> PxeBaseCodeCallbackStatus },
+}
+
+unsafe impl Protocol for PxeBaseCodeCallback {
+    const GUID: Guid = guids::protocol::PXE_BASE_CODE_CALLBACK;
+}
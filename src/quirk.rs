@@ -0,0 +1,185 @@
+//! Handles firmware that violates the specification.
+//!
+//! In `QuirkMode::Strict`, which is the default, a violation of the specification panics, so
+//! firmware bugs are noticed during development. In `QuirkMode::Permissive`, the violation is
+//! recorded in the quirk log instead and the affected function degrades gracefully, usually by
+//! returning an error or by skipping the malformed data. Production loaders that must boot on
+//! buggy firmware should select the permissive mode and report the quirk log later.
+//!
+//! In the permissive mode, the wrappers do not panic because of the behavior of the firmware.
+//! Every violation that affects the result of a wrapper is returned as the error given by
+//! `Quirk::error`.
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::status::Error;
+
+/// The number of quirks the quirk log can hold.
+///
+/// Further quirks are only counted.
+pub const MAX_QUIRKS: usize = 16;
+
+/// Selects how violations of the specification are handled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuirkMode {
+    /// Violations panic.
+    Strict,
+    /// Violations are recorded in the quirk log and handled gracefully.
+    Permissive,
+}
+
+/// A violation of the specification by the firmware.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Quirk {
+    /// `WaitForEvent` returned an index that does not refer to one of the events.
+    WrongEventIndex {
+        /// The returned index.
+        index: usize,
+        /// The number of events that were waited for.
+        count: usize,
+    },
+    /// A memory descriptor is smaller than `MemoryDescriptor`.
+    UndersizedDescriptor {
+        /// The size of the descriptor that the firmware reported.
+        size: usize,
+    },
+    /// The firmware reported that it transferred more bytes than the buffer holds.
+    OversizedTransfer {
+        /// The number of bytes the firmware reported.
+        size: usize,
+        /// The size of the buffer.
+        capacity: usize,
+    },
+}
+
+impl Quirk {
+    /// Returns the error that is returned in the permissive mode if the quirk affects a result.
+    pub fn error(self) -> Error {
+        match self {
+            Quirk::WrongEventIndex { .. } => Error::WrongEventIndex,
+            Quirk::UndersizedDescriptor { .. } => Error::UndersizedDescriptor,
+            Quirk::OversizedTransfer { .. } => Error::OversizedTransfer,
+        }
+    }
+}
+
+impl fmt::Display for Quirk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Quirk::WrongEventIndex { index, count } => write!(
+                f,
+                "WaitForEvent returned index {} for {} events",
+                index, count
+            ),
+            Quirk::UndersizedDescriptor { size } => write!(
+                f,
+                "the memory descriptor size {} is smaller than the standard says",
+                size
+            ),
+            Quirk::OversizedTransfer { size, capacity } => write!(
+                f,
+                "{} bytes were transferred into a buffer of {} bytes",
+                size, capacity
+            ),
+        }
+    }
+}
+
+/// The table of recorded quirks.
+struct QuirkLog(UnsafeCell<[Option<Quirk>; MAX_QUIRKS]>);
+
+// Every report writes only its own entry, which it reserved with `QUIRK_COUNT`, and entries are
+// only read after their bit in `QUIRK_READY` was set, which happens after the write completed.
+unsafe impl Sync for QuirkLog {}
+
+/// Whether the strict mode is selected.
+static STRICT: AtomicBool = AtomicBool::new(true);
+/// The number of quirks that were reported, including those that did not fit into the log.
+static QUIRK_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// One bit per entry of `QUIRKS`, which is set once the entry was written.
+///
+/// `MAX_QUIRKS` is small enough for the 16 bits that `usize` has on every target.
+static QUIRK_READY: AtomicUsize = AtomicUsize::new(0);
+/// The recorded quirks.
+static QUIRKS: QuirkLog = QuirkLog(UnsafeCell::new([None; MAX_QUIRKS]));
+
+/// Selects how violations of the specification are handled.
+pub fn set_mode(mode: QuirkMode) {
+    STRICT.store(mode == QuirkMode::Strict, Ordering::SeqCst);
+}
+
+/// Returns how violations of the specification are handled.
+pub fn mode() -> QuirkMode {
+    if STRICT.load(Ordering::SeqCst) {
+        QuirkMode::Strict
+    } else {
+        QuirkMode::Permissive
+    }
+}
+
+/// Reports a violation of the specification.
+///
+/// Returns the error for the quirk, so it can be returned directly by the caller.
+///
+/// # Panics
+/// Panics in `QuirkMode::Strict`.
+pub fn report(quirk: Quirk) -> Error {
+    if mode() == QuirkMode::Strict {
+        panic!("The firmware violated the specification: {}.", quirk);
+    }
+
+    let index = QUIRK_COUNT.fetch_add(1, Ordering::SeqCst);
+
+    if index < MAX_QUIRKS {
+        // This is safe, because the index was reserved above, so no one else writes this entry,
+        // and its bit in `QUIRK_READY` is not set yet, so no one reads it.
+        unsafe { (*QUIRKS.0.get())[index] = Some(quirk) };
+        QUIRK_READY.fetch_or(1 << index, Ordering::Release);
+    }
+
+    quirk.error()
+}
+
+/// Reports an `OversizedTransfer` quirk if `size` exceeds `capacity`.
+pub(crate) fn check_transfer(size: usize, capacity: usize) -> Result<usize, Error> {
+    if size > capacity {
+        return Err(report(Quirk::OversizedTransfer { size, capacity }));
+    }
+
+    Ok(size)
+}
+
+/// Returns the recorded quirks in the order they were reported.
+///
+/// Quirks that are still being recorded by another processor are skipped.
+pub fn quirks() -> impl Iterator<Item = Quirk> {
+    let count = QUIRK_COUNT.load(Ordering::SeqCst).min(MAX_QUIRKS);
+
+    (0..count).filter_map(|index| {
+        if QUIRK_READY.load(Ordering::Acquire) & (1 << index) == 0 {
+            return None;
+        }
+
+        // This is safe, because the entry was completely written and is never written again.
+        unsafe { (*QUIRKS.0.get())[index] }
+    })
+}
+
+/// Returns the number of reported quirks that did not fit into the quirk log.
+pub fn dropped_quirks() -> usize {
+    QUIRK_COUNT
+        .load(Ordering::SeqCst)
+        .saturating_sub(MAX_QUIRKS)
+}
+
+/// Clears the quirk log.
+///
+/// This must not be called while quirks are reported concurrently.
+pub fn clear() {
+    QUIRK_READY.store(0, Ordering::SeqCst);
+    QUIRK_COUNT.store(0, Ordering::SeqCst);
+}
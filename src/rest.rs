@@ -0,0 +1,165 @@
+//! The REST EX protocol is designed to send HTTP messages to a REST service, such as a Redfish
+//! service on a BMC, and to receive the corresponding responses.
+//!
+//! Instances of the protocol are created with the REST EX service binding protocol.
+
+use crate::{
+    guid::{Guid, REST_EX_PROTOCOL_GUID},
+    http::{HttpConfigData, HttpMessage},
+    protocol::Protocol,
+    status::{Error, Status},
+    Event,
+};
+
+// REST service types
+pub const REST_EX_SERVICE_UNSPECIFIC: u32 = 1;
+pub const REST_EX_SERVICE_REDFISH: u32 = 2;
+pub const REST_EX_SERVICE_ODATA: u32 = 3;
+pub const REST_EX_SERVICE_VENDOR_SPECIFIC: u32 = 0xff;
+
+// REST service access modes
+pub const REST_EX_SERVICE_IN_BAND_ACCESS: u8 = 0x01;
+pub const REST_EX_SERVICE_OUT_OF_BAND_ACCESS: u8 = 0x02;
+
+// REST EX configuration types
+pub const REST_EX_CONFIG_TYPE_HTTP: u8 = 0x00;
+pub const REST_EX_CONFIG_TYPE_UNSPECIFIC: u8 = 0x01;
+
+/// The header of the REST service information.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct RestExServiceInfoHeader {
+    /// The length of the REST service information, including this header.
+    pub Length: u32,
+    /// The major version of the REST service information structure.
+    pub MajorVersion: u8,
+    /// The minor version of the REST service information structure.
+    pub MinorVersion: u8,
+}
+
+/// Information about the REST service, version 1.0.
+#[derive(Debug)]
+#[repr(C)]
+pub struct RestExServiceInfo {
+    /// The header of the REST service information.
+    pub Header: RestExServiceInfoHeader,
+    /// The type of the REST service.
+    pub RestServiceType: u32,
+    /// Whether the REST service is accessed in band or out of band.
+    pub RestServiceAccessMode: u8,
+    /// The name of a vendor specific REST service.
+    pub VendorRestServiceName: Guid,
+    /// The length of `VendorSpecificData`.
+    pub VendorSpecificDataLength: u32,
+    /// Vendor specific data of the REST service.
+    pub VendorSpecificData: *const u8,
+    /// The type of configuration data that `Configure()` expects.
+    pub RestExConfigType: u8,
+    /// Reserved.
+    pub Reserved: [u8; 3],
+}
+
+/// The configuration data of a REST EX instance with the configuration type
+/// `REST_EX_CONFIG_TYPE_HTTP`.
+#[repr(C)]
+pub struct RestExHttpConfigData {
+    /// The configuration of the underlying HTTP instance.
+    pub HttpConfigData: HttpConfigData,
+    /// The timeout of `SendReceive()` in milliseconds.
+    pub SendReceiveTimeout: u32,
+}
+
+/// The token used for asynchronous REST requests.
+#[repr(C)]
+pub struct RestExHttpToken {
+    /// The event that is signaled when the request has completed.
+    pub Event: Event,
+    /// The status of the request once the event was signaled.
+    pub Status: Status,
+    /// The response of the REST service.
+    pub ResponseMessage: *mut HttpMessage,
+}
+
+/// Provides services to communicate with a REST service.
+#[repr(C)]
+pub struct RestEx {
+    /// Sends an HTTP request to the REST service and returns the response.
+    pub SendReceive: eficall! { fn(
+        &RestEx,
+        RequestMessage: &HttpMessage,
+        ResponseMessage: &mut HttpMessage,
+    ) -> Status },
+    /// Returns information about the REST service.
+    pub GetService: eficall! { fn(
+        &RestEx,
+        RestExServiceInfo: &mut *const RestExServiceInfo,
+    ) -> Status },
+    /// Returns the current configuration of the instance.
+    pub GetModeData: eficall! { fn(&RestEx, RestExConfigData: &mut *const u8) -> Status },
+    /// Configures or resets the instance.
+    pub Configure: eficall! { fn(&RestEx, RestExConfigData: *const u8) -> Status },
+    /// Sends an HTTP request to the REST service asynchronously.
+    pub AsyncSendReceive: eficall! { fn(
+        &RestEx,
+        RequestMessage: *const HttpMessage,
+        RestExToken: &mut RestExHttpToken,
+        TimeOutInMilliSeconds: *const usize,
+    ) -> Status },
+    /// Registers for event notifications of the REST service.
+    pub EventService: eficall! { fn(
+        &RestEx,
+        RequestMessage: *const HttpMessage,
+        RestExToken: &mut RestExHttpToken,
+    ) -> Status },
+}
+
+unsafe impl Protocol for RestEx {
+    const GUID: Guid = REST_EX_PROTOCOL_GUID;
+}
+
+impl RestEx {
+    /// Sends an HTTP request to the REST service and returns the response.
+    ///
+    /// The headers and the body of the response are allocated by the driver and must be freed
+    /// by the caller using `FreePool()`.
+    pub fn send_receive(
+        &self,
+        request: &HttpMessage,
+        response: &mut HttpMessage,
+    ) -> Result<(), Error> {
+        (self.SendReceive)(self, request, response)?;
+
+        Ok(())
+    }
+
+    /// Returns information about the REST service.
+    ///
+    /// The information is allocated by the driver and must be freed by the caller using
+    /// `FreePool()`.
+    pub fn get_service(&self) -> Result<&RestExServiceInfo, Error> {
+        let mut info = core::ptr::null();
+
+        (self.GetService)(self, &mut info)?;
+
+        if info.is_null() {
+            Err(Error::NotFound)
+        } else {
+            // This is safe under the assumption, that the firmware returned a valid pointer.
+            Ok(unsafe { &*info })
+        }
+    }
+
+    /// Configures the instance to use HTTP with the given configuration.
+    pub fn configure_http(&self, config: &RestExHttpConfigData) -> Result<(), Error> {
+        (self.Configure)(self, config as *const RestExHttpConfigData as *const u8)?;
+
+        Ok(())
+    }
+
+    /// Resets the instance to the unconfigured state.
+    pub fn reset(&self) -> Result<(), Error> {
+        (self.Configure)(self, core::ptr::null())?;
+
+        Ok(())
+    }
+}
@@ -7,93 +7,304 @@
 //! is made by the OS, then the function pointers in this table are fixed up
 //! to point to the new virtually mapped entry points.
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::ptr;
+
+use bitflags::bitflags;
+
 use crate::{
     capsule::CapsuleHeader,
     guid::Guid,
-    memory::{MemoryDescriptor, PhysicalAddress},
+    memory::{MemoryDescriptor, MemoryMapOwned, PhysicalAddress},
     reset::ResetType,
-    status::Status,
+    status::{Error, Status},
+    string::CStr16,
+    system::SYSTEM_TABLE_REVISION_2_00,
     time::{Time, TimeCapabilities},
     TableHeader,
 };
 
+bitflags! {
+    /// The attributes of an EFI variable.
+    pub struct VariableAttributes: u32 {
+        /// The variable is stored in non-volatile storage and persists across resets.
+        const NON_VOLATILE = 0x01;
+        /// The variable can be accessed before `ExitBootServices` is called.
+        const BOOTSERVICE_ACCESS = 0x02;
+        /// The variable can be accessed after `ExitBootServices` is called.
+        ///
+        /// This requires `BOOTSERVICE_ACCESS` to be set as well.
+        const RUNTIME_ACCESS = 0x04;
+        /// The variable is a hardware error record.
+        const HARDWARE_ERROR_RECORD = 0x08;
+        /// Writes to the variable must be authenticated with a count based signature.
+        const AUTHENTICATED_WRITE_ACCESS = 0x10;
+        /// Writes to the variable must be authenticated with a time based signature.
+        const TIME_BASED_AUTHENTICATED_WRITE_ACCESS = 0x20;
+        /// The data is appended to the existing value instead of replacing it.
+        const APPEND_WRITE = 0x40;
+    }
+}
+
+/// The number of times `variable_value` tries to read a variable that keeps growing.
+#[cfg(feature = "alloc")]
+const MAX_VARIABLE_READ_ATTEMPTS: usize = 4;
+
+/// Information about the storage of EFI variables, as returned by `QueryVariableInfo`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VariableStorageInfo {
+    /// The maximum size of the storage space available for variables with the given attributes.
+    pub maximum_storage_size: u64,
+    /// The remaining size of the storage space available for variables with the given attributes.
+    pub remaining_storage_size: u64,
+    /// The maximum size of an individual variable with the given attributes.
+    pub maximum_variable_size: u64,
+}
+
 /// Contains a table header and pointers to all of the runtime services.
 #[repr(C)]
 pub struct RuntimeServices {
     pub Hdr: TableHeader,
     /// Returns the current time and date information, and the time-keeping capabilities of the hardware
     /// platform.
-    pub GetTime: extern "win64" fn(Time: &mut Time, Capabilities: *mut TimeCapabilities) -> Status,
+    pub GetTime: eficall! { fn(Time: &mut Time, Capabilities: *mut TimeCapabilities) -> Status },
     /// Sets the current local time and date information.
-    pub SetTime: extern "win64" fn(Time: &Time) -> Status,
+    pub SetTime: eficall! { fn(Time: &Time) -> Status },
     /// Returns the current wakeup alarm clock setting.
-    pub GetWakeupTime:
-        extern "win64" fn(Enabled: &mut bool, Pending: &mut bool, Time: &mut Time) -> Status,
+    pub GetWakeupTime: eficall! { fn(
+        Enabled: &mut bool,
+        Pending: &mut bool,
+        Time: &mut Time,
+    ) -> Status },
     /// Sets the system wakeup alarm clock time.
-    pub SetWakeupTime: extern "win64" fn(Enable: bool, Time: *const Time) -> Status,
+    pub SetWakeupTime: eficall! { fn(Enable: bool, Time: *const Time) -> Status },
     /// Changes the runtime addressing mode of EFI firmware from physical to virtual.
-    SetVirtualAddressMap: extern "win64" fn(
+    pub(crate) SetVirtualAddressMap: eficall! { fn(
         MemoryMapSize: usize,
         DescriptorSize: usize,
         DescriptorVersion: u32,
         VirtualMap: *const MemoryDescriptor,
-    ) -> Status,
+    ) -> Status },
     /// Determines the new virtual address that is to be used on subsequent memory accesses.
-    pub ConvertPointer: extern "win64" fn(DebugDisposition: usize, Address: &mut usize) -> Status,
+    pub ConvertPointer: eficall! { fn(DebugDisposition: usize, Address: &mut usize) -> Status },
     /// Returns the value of a variable.
-    pub GetVariable: extern "win64" fn(
+    pub GetVariable: eficall! { fn(
         VariableName: *const u16,
         VendorGuid: &Guid,
         Attributes: *mut u32,
         DataSize: &mut usize,
         Data: *mut u8,
-    ) -> Status,
+    ) -> Status },
     /// Enumerates the current variable names.
-    pub GetNextVariableName: extern "win64" fn(
+    pub GetNextVariableName: eficall! { fn(
         VariableNameSize: &mut usize,
         VariableName: *mut u16,
         VendorGuid: &mut Guid,
-    ) -> Status,
+    ) -> Status },
     /// Sets the value of a variable.
-    pub SetVariable: extern "win64" fn(
+    pub SetVariable: eficall! { fn(
         VariableName: *const u16,
         VendorGuid: &Guid,
         Attributes: u32,
         DataSize: usize,
         Data: *const u8,
-    ) -> Status,
+    ) -> Status },
     /// Returns the next high 32 bits of the platform’s monotonic counter.
-    pub GetNextHighMonotonicCount: extern "win64" fn(HighCount: &mut u32) -> Status,
+    pub GetNextHighMonotonicCount: eficall! { fn(HighCount: &mut u32) -> Status },
     /// Resets the entire platform. If the platform supports EFI_RESET_NOTIFICATION_PROTOCOL,
     /// then prior to completing the reset of the platform, all of the pending notifications must be called.
-    pub ResetSystem: extern "win64" fn(
+    ///
+    /// The firmware does not return from this function, but the type allows the mock to return.
+    pub ResetSystem: eficall! { fn(
         ResetType: ResetType,
         ResetStatus: Status,
         DataSize: usize,
         ResetData: *const u8,
-    ) -> !,
+    ) },
     /// Passes capsules to the firmware with both virtual and physical mapping. Depending on the
     /// intended consumption, the firmware may process the capsule immediately. If the payload should
     /// persist across a system reset, the reset value returned from EFI_QueryCapsuleCapabilities
     /// must be passed into ResetSystem() and will cause the capsule to be processed by the firmware
     /// as part of the reset process.
-    pub UpdateCapsule: extern "win64" fn(
+    pub UpdateCapsule: eficall! { fn(
         CapsuleHeaderArray: *const *const CapsuleHeader,
         CapsuleCount: usize,
         ScatterGatherList: PhysicalAddress,
-    ) -> Status,
+    ) -> Status },
     /// Returns if the capsule can be supported via UpdateCapsule().
-    pub QueryCapsuleCapabilities: extern "win64" fn(
+    pub QueryCapsuleCapabilities: eficall! { fn(
         CapsuleHeaderArray: *const *const CapsuleHeader,
         CapsuleCount: usize,
         MaximumCapsuleSize: &mut u64,
         ResetType: &mut ResetType,
-    ) -> Status,
+    ) -> Status },
     /// Returns information about the EFI variables.
-    pub QueryVariableInfo: extern "win64" fn(
+    pub QueryVariableInfo: eficall! { fn(
         Attributes: u32,
         MaximumVariableStorageSize: &mut u64,
         RemainingVariableStorageSize: &mut u64,
         MaximumVariableSize: &mut u64,
-    ) -> Status,
+    ) -> Status },
+}
+
+impl RuntimeServices {
+    /// Changes the runtime addressing mode of EFI firmware from physical to virtual.
+    ///
+    /// `virtual_map` should contain the runtime regions with their new virtual addresses, as
+    /// returned by `MemoryMap::build_virtual_map`.
+    ///
+    /// # Safety
+    /// This may only be called once after `ExitBootServices` and the new mappings must be in
+    /// place before the runtime services are used again.
+    pub unsafe fn set_virtual_address_map(
+        &self,
+        virtual_map: &MemoryMapOwned,
+    ) -> Result<(), Error> {
+        let descriptors = virtual_map.as_bytes();
+
+        (self.SetVirtualAddressMap)(
+            descriptors.len(),
+            virtual_map.descriptor_size(),
+            virtual_map.version(),
+            descriptors.as_ptr() as *const MemoryDescriptor,
+        )?;
+
+        Ok(())
+    }
+
+    /// Reads the variable with the given name and vendor into `data`.
+    ///
+    /// Returns the size of the variable and its attributes. On failure, the error is returned
+    /// together with the size reported by the firmware, which is the required size of `data` if
+    /// the error is `Error::BufferTooSmall`.
+    pub fn get_variable(
+        &self,
+        name: &CStr16,
+        vendor: &Guid,
+        data: &mut [u8],
+    ) -> Result<(usize, u32), (Error, usize)> {
+        let mut attributes = 0;
+        let mut size = data.len();
+
+        (self.GetVariable)(
+            name.as_ptr(),
+            vendor,
+            &mut attributes,
+            &mut size,
+            data.as_mut_ptr(),
+        )
+        .with_value(())
+        .map_err(|error| (error, size))?;
+
+        Ok((size, attributes))
+    }
+
+    /// Returns the size of the variable with the given name and vendor.
+    pub fn variable_size(&self, name: &CStr16, vendor: &Guid) -> Result<usize, Error> {
+        let mut size = 0;
+
+        let status = (self.GetVariable)(
+            name.as_ptr(),
+            vendor,
+            ptr::null_mut(),
+            &mut size,
+            ptr::null_mut(),
+        );
+
+        // An empty variable fits into the empty buffer.
+        if status == Error::BufferTooSmall.into() {
+            return Ok(size);
+        }
+        status?;
+
+        Ok(size)
+    }
+
+    /// Returns the value of the variable with the given name and vendor.
+    ///
+    /// The variable may grow between querying its size and reading it, so reading it is retried
+    /// up to `MAX_VARIABLE_READ_ATTEMPTS` times before `Error::BufferTooSmall` is returned.
+    #[cfg(feature = "alloc")]
+    pub fn variable_value(&self, name: &CStr16, vendor: &Guid) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+
+        for _ in 0..MAX_VARIABLE_READ_ATTEMPTS {
+            match self.get_variable(name, vendor, &mut data) {
+                Ok((size, _)) => {
+                    data.truncate(size);
+
+                    return Ok(data);
+                }
+                Err((Error::BufferTooSmall, size)) => data.resize(size, 0),
+                Err((error, _)) => return Err(error),
+            }
+        }
+
+        Err(Error::BufferTooSmall)
+    }
+
+    /// Sets the value of the variable with the given name and vendor.
+    ///
+    /// An empty `data` deletes the variable, unless `attributes` contains `APPEND_WRITE`.
+    pub fn set_variable(
+        &self,
+        name: &CStr16,
+        vendor: &Guid,
+        attributes: VariableAttributes,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        (self.SetVariable)(
+            name.as_ptr(),
+            vendor,
+            attributes.bits(),
+            data.len(),
+            data.as_ptr(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns information about the storage of variables with the given attributes.
+    ///
+    /// Returns `Error::Unsupported` if the firmware predates UEFI 2.0, which introduced this
+    /// function.
+    pub fn query_variable_info(&self, attributes: u32) -> Result<VariableStorageInfo, Error> {
+        if !self
+            .Hdr
+            .provides(SYSTEM_TABLE_REVISION_2_00, self, &self.QueryVariableInfo)
+        {
+            return Err(Error::Unsupported);
+        }
+
+        let mut info = VariableStorageInfo {
+            maximum_storage_size: 0,
+            remaining_storage_size: 0,
+            maximum_variable_size: 0,
+        };
+
+        (self.QueryVariableInfo)(
+            attributes,
+            &mut info.maximum_storage_size,
+            &mut info.remaining_storage_size,
+            &mut info.maximum_variable_size,
+        )?;
+
+        Ok(info)
+    }
+
+    /// Resets the entire platform.
+    ///
+    /// `status` is the reason for the reset, which is `SUCCESS` for a normal reset.
+    pub fn reset_system(&self, reset_type: ResetType, status: Status) -> ! {
+        (self.ResetSystem)(reset_type, status, 0, ptr::null());
+
+        // Only the mock returns from `ResetSystem`. Panicking here instead of in the mock lets
+        // tests catch the reset.
+        #[cfg(feature = "mock")]
+        panic!("the system was reset ({:?}): {}", reset_type, status);
+
+        #[cfg(not(feature = "mock"))]
+        loop {}
+    }
 }
@@ -0,0 +1,391 @@
+//! Reads the secure boot signature databases and predicts whether an image is allowed to load.
+//!
+//! The databases `db` and `dbx` contain a sequence of `EFI_SIGNATURE_LIST`s, each holding
+//! signatures of one type, such as SHA-256 hashes of images or X.509 certificates. When secure
+//! boot is enabled, `LoadImage` rejects an image with `Error::SecurityViolation` if its hash or a
+//! certificate of its signer is in `dbx`, or if neither is in `db`. Checking the databases
+//! beforehand allows a loader to explain why an image is rejected.
+
+use core::mem::size_of;
+
+use crate::{
+    boot::BootServices,
+    boxed::PoolBuffer,
+    guid::Guid,
+    guids::{signature, vendor},
+    memory::MemoryType,
+    runtime::RuntimeServices,
+    status::Error,
+    system,
+    var_store::VarStore,
+};
+
+/// The size of the fixed part of an `EFI_SIGNATURE_LIST`.
+const SIGNATURE_LIST_SIZE: usize = size_of::<Guid>() + size_of::<u32>() * 3;
+
+/// A signature list, which contains signatures of the same type and size.
+#[derive(Clone, Copy, Debug)]
+pub struct SignatureList<'a> {
+    /// The type of the signatures, one of the GUIDs in `guids::signature`.
+    pub signature_type: Guid,
+    /// The header, whose format depends on the signature type.
+    pub header: &'a [u8],
+    /// The size of each signature including its owner.
+    signature_size: usize,
+    /// The signatures of the list.
+    signatures: &'a [u8],
+}
+
+impl<'a> SignatureList<'a> {
+    /// Returns the signatures of the list.
+    pub fn signatures(&self) -> Signatures<'a> {
+        Signatures {
+            signatures: self.signatures,
+            signature_size: self.signature_size,
+        }
+    }
+}
+
+/// A signature and the agent that added it.
+#[derive(Clone, Copy, Debug)]
+pub struct SignatureData<'a> {
+    /// The agent that added the signature.
+    pub owner: Guid,
+    /// The signature itself, such as a hash or certificate.
+    pub data: &'a [u8],
+}
+
+/// The iterator returned by `SignatureList::signatures`.
+pub struct Signatures<'a> {
+    signatures: &'a [u8],
+    signature_size: usize,
+}
+
+impl<'a> Iterator for Signatures<'a> {
+    type Item = SignatureData<'a>;
+
+    fn next(&mut self) -> Option<SignatureData<'a>> {
+        if self.signatures.len() < self.signature_size {
+            return None;
+        }
+
+        let (signature, rest) = self.signatures.split_at(self.signature_size);
+        self.signatures = rest;
+
+        let mut owner = [0; 16];
+        owner.copy_from_slice(&signature[..16]);
+
+        Some(SignatureData {
+            owner: Guid::from_bytes(owner),
+            data: &signature[16..],
+        })
+    }
+}
+
+/// Iterates over the signature lists in a signature database.
+///
+/// Returns `Error::BadBufferSize` and stops if a list is truncated or inconsistent.
+pub struct SignatureLists<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> SignatureLists<'a> {
+    /// Iterates over the signature lists in `data`, such as the value of `db`.
+    pub fn new(data: &'a [u8]) -> SignatureLists<'a> {
+        SignatureLists { data }
+    }
+
+    /// Parses the signature list at the start of the data.
+    fn parse(&self) -> Result<(SignatureList<'a>, usize), Error> {
+        let data = self.data;
+
+        if data.len() < SIGNATURE_LIST_SIZE {
+            return Err(Error::BadBufferSize);
+        }
+
+        let mut signature_type = [0; 16];
+        signature_type.copy_from_slice(&data[..16]);
+        let read_u32 = |offset: usize| {
+            let bytes = &data[offset..offset + 4];
+
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+        };
+        let list_size = read_u32(16);
+        let header_size = read_u32(20);
+        let signature_size = read_u32(24);
+
+        let signatures_start = SIGNATURE_LIST_SIZE
+            .checked_add(header_size)
+            .ok_or(Error::BadBufferSize)?;
+        if list_size > data.len()
+            || signatures_start > list_size
+            || signature_size < size_of::<Guid>()
+            || (list_size - signatures_start) % signature_size != 0
+        {
+            return Err(Error::BadBufferSize);
+        }
+
+        let list = SignatureList {
+            signature_type: Guid::from_bytes(signature_type),
+            header: &data[SIGNATURE_LIST_SIZE..signatures_start],
+            signature_size,
+            signatures: &data[signatures_start..list_size],
+        };
+
+        Ok((list, list_size))
+    }
+}
+
+impl<'a> Iterator for SignatureLists<'a> {
+    type Item = Result<SignatureList<'a>, Error>;
+
+    fn next(&mut self) -> Option<Result<SignatureList<'a>, Error>> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        match self.parse() {
+            Ok((list, size)) => {
+                self.data = &self.data[size..];
+
+                Some(Ok(list))
+            }
+            Err(error) => {
+                self.data = &[];
+
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Returns the signature type of hashes with the size of `hash`.
+fn hash_type(hash: &[u8]) -> Result<Guid, Error> {
+    match hash.len() {
+        20 => Ok(signature::CERT_SHA1),
+        28 => Ok(signature::CERT_SHA224),
+        32 => Ok(signature::CERT_SHA256),
+        48 => Ok(signature::CERT_SHA384),
+        64 => Ok(signature::CERT_SHA512),
+        _ => Err(Error::InvalidParameter),
+    }
+}
+
+/// Returns whether the signature lists in `data` contain a signature of `signature_type` that
+/// is `value`.
+fn contains(data: &[u8], signature_type: &Guid, value: &[u8]) -> Result<bool, Error> {
+    for list in SignatureLists::new(data) {
+        let list = list?;
+
+        if list.signature_type == *signature_type
+            && list.signatures().any(|signature| signature.data == value)
+        {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// A signature database read from a variable.
+pub struct SignatureDatabase {
+    /// The value of the variable, or `None` if the variable does not exist.
+    buffer: Option<PoolBuffer<u8>>,
+}
+
+impl SignatureDatabase {
+    /// Reads the signature database `name`, such as `db` or `dbx`.
+    ///
+    /// A missing variable is treated as an empty database.
+    pub fn read(
+        boot_services: &BootServices,
+        runtime_services: &RuntimeServices,
+        name: &str,
+    ) -> Result<SignatureDatabase, Error> {
+        let store = VarStore::new(runtime_services, vendor::IMAGE_SECURITY_DATABASE);
+
+        let size = match store.size(name) {
+            Ok(0) | Err(Error::NotFound) => return Ok(SignatureDatabase { buffer: None }),
+            Ok(size) => size,
+            Err(error) => return Err(error),
+        };
+
+        let mut buffer = PoolBuffer::new(boot_services, MemoryType::LoaderData, size)?;
+
+        match store.get_raw(name, &mut buffer) {
+            Ok((read, _)) if read == size => Ok(SignatureDatabase {
+                buffer: Some(buffer),
+            }),
+            result => {
+                buffer.free(boot_services)?;
+
+                // The variable changed between the calls.
+                Err(result.err().unwrap_or(Error::BadBufferSize))
+            }
+        }
+    }
+
+    /// Returns the raw value of the database.
+    pub fn data(&self) -> &[u8] {
+        match self.buffer {
+            Some(ref buffer) => buffer,
+            None => &[],
+        }
+    }
+
+    /// Returns the signature lists of the database.
+    pub fn lists(&self) -> SignatureLists {
+        SignatureLists::new(self.data())
+    }
+
+    /// Returns whether the database contains the hash of an image.
+    ///
+    /// The hash algorithm is selected by the size of `hash`, which is one of SHA-1, SHA-224,
+    /// SHA-256, SHA-384 and SHA-512. For PE images this is the Authenticode hash.
+    pub fn contains_hash(&self, hash: &[u8]) -> Result<bool, Error> {
+        contains(self.data(), &hash_type(hash)?, hash)
+    }
+
+    /// Returns whether the database contains the DER encoded X.509 `certificate`.
+    ///
+    /// Certificates revoked by the hash of their to be signed part are not detected.
+    pub fn contains_certificate(&self, certificate: &[u8]) -> Result<bool, Error> {
+        contains(self.data(), &signature::CERT_X509, certificate)
+    }
+
+    /// Frees the database.
+    pub fn free(self, boot_services: &BootServices) -> Result<(), Error> {
+        match self.buffer {
+            Some(buffer) => buffer.free(boot_services),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns whether secure boot is enabled, so `LoadImage` verifies images.
+pub fn secure_boot_enabled(runtime_services: &RuntimeServices) -> Result<bool, Error> {
+    match VarStore::global(runtime_services).get::<u8>("SecureBoot") {
+        Ok(value) => Ok(value == 1),
+        Err(Error::NotFound) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// The predicted result of loading an image.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    /// Secure boot is disabled, so the image is not verified.
+    SecureBootDisabled,
+    /// The hash of the image is in `db`.
+    HashAllowed,
+    /// A certificate of a signer of the image is in `db`.
+    CertificateAllowed,
+    /// The hash of the image is in `dbx`.
+    HashRevoked,
+    /// A certificate of a signer of the image is in `dbx`.
+    CertificateRevoked,
+    /// Neither the hash of the image nor a certificate of a signer is in `db`.
+    NotTrusted,
+}
+
+impl Verdict {
+    /// Returns whether the image is loaded.
+    pub fn is_allowed(self) -> bool {
+        match self {
+            Verdict::SecureBootDisabled | Verdict::HashAllowed | Verdict::CertificateAllowed => {
+                true
+            }
+            Verdict::HashRevoked | Verdict::CertificateRevoked | Verdict::NotTrusted => false,
+        }
+    }
+
+    /// Returns a message that describes the verdict.
+    pub fn description(self) -> &'static str {
+        match self {
+            Verdict::SecureBootDisabled => "secure boot is disabled",
+            Verdict::HashAllowed => "the image hash is allowed by db",
+            Verdict::CertificateAllowed => "a signing certificate is allowed by db",
+            Verdict::HashRevoked => "the image hash is revoked by dbx",
+            Verdict::CertificateRevoked => "a signing certificate is revoked by dbx",
+            Verdict::NotTrusted => "neither the image hash nor a signing certificate is in db",
+        }
+    }
+}
+
+/// Predicts whether `LoadImage` accepts an image.
+///
+/// `image_hash` is the Authenticode hash of the image and `signers` are the DER encoded
+/// certificates in the signature chains of the image. Signature verification itself is not
+/// performed, so an image with an invalid signature may still be rejected.
+pub fn predict_with(
+    boot_services: &BootServices,
+    runtime_services: &RuntimeServices,
+    image_hash: &[u8],
+    signers: &[&[u8]],
+) -> Result<Verdict, Error> {
+    if !secure_boot_enabled(runtime_services)? {
+        return Ok(Verdict::SecureBootDisabled);
+    }
+
+    let dbx = SignatureDatabase::read(boot_services, runtime_services, "dbx")?;
+    let revoked = check(
+        &dbx,
+        image_hash,
+        signers,
+        Verdict::HashRevoked,
+        Verdict::CertificateRevoked,
+    );
+    dbx.free(boot_services)?;
+    if let Some(verdict) = revoked? {
+        return Ok(verdict);
+    }
+
+    let db = SignatureDatabase::read(boot_services, runtime_services, "db")?;
+    let allowed = check(
+        &db,
+        image_hash,
+        signers,
+        Verdict::HashAllowed,
+        Verdict::CertificateAllowed,
+    );
+    db.free(boot_services)?;
+
+    Ok(allowed?.unwrap_or(Verdict::NotTrusted))
+}
+
+/// Returns `hash_verdict` or `certificate_verdict` if `database` contains the hash or a signer.
+fn check(
+    database: &SignatureDatabase,
+    image_hash: &[u8],
+    signers: &[&[u8]],
+    hash_verdict: Verdict,
+    certificate_verdict: Verdict,
+) -> Result<Option<Verdict>, Error> {
+    if database.contains_hash(image_hash)? {
+        return Ok(Some(hash_verdict));
+    }
+
+    for signer in signers {
+        if database.contains_certificate(signer)? {
+            return Ok(Some(certificate_verdict));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Predicts whether `LoadImage` accepts an image, see `predict_with`.
+///
+/// The boot and runtime services registered with `system::init` are used. Returns
+/// `Error::NotStarted` if `system::init` was not called.
+pub fn predict(image_hash: &[u8], signers: &[&[u8]]) -> Result<Verdict, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+    let system_table = system::system_table().ok_or(Error::NotStarted)?;
+
+    predict_with(
+        boot_services,
+        system_table.RuntimeServices,
+        image_hash,
+        signers,
+    )
+}
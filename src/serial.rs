@@ -0,0 +1,250 @@
+//! This protocol is used to communicate with any type of character-based I/O device.
+//!
+//! The `SerialLogger` writes text and log messages to such a device, which is useful on machines
+//! without a usable video console.
+
+use core::fmt;
+
+use crate::{
+    guid::Guid,
+    guids,
+    protocol::Protocol,
+    quirk,
+    status::{Error, Status},
+    text,
+};
+
+/// The revision of the first version of the Serial I/O protocol.
+pub const SERIAL_IO_PROTOCOL_REVISION: u32 = 0x0001_0000;
+
+/// The baud rate `SerialLogger::new` configures if none is given.
+pub const DEFAULT_BAUD_RATE: u64 = 115_200;
+
+/// The parity of the data on a serial device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Parity {
+    /// The default parity of the device.
+    Default,
+    /// No parity bit.
+    None,
+    /// Even parity.
+    Even,
+    /// Odd parity.
+    Odd,
+    /// The parity bit is always set.
+    Mark,
+    /// The parity bit is always cleared.
+    Space,
+}
+
+/// The number of stop bits of a serial device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum StopBits {
+    /// The default number of stop bits of the device.
+    Default,
+    /// One stop bit.
+    One,
+    /// One and a half stop bits.
+    OneFive,
+    /// Two stop bits.
+    Two,
+}
+
+/// The current attributes of a serial device.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct SerialIoMode {
+    /// A mask of the control bits that the device supports.
+    pub ControlMask: u32,
+    /// The number of microseconds to wait before timing out a read or write operation.
+    pub Timeout: u32,
+    /// The current baud rate, or 0 if it is unknown.
+    pub BaudRate: u64,
+    /// The number of characters the device buffers on input.
+    pub ReceiveFifoDepth: u32,
+    /// The number of data bits in each character.
+    pub DataBits: u32,
+    /// The parity, as a value of `Parity`.
+    pub Parity: u32,
+    /// The number of stop bits, as a value of `StopBits`.
+    pub StopBits: u32,
+}
+
+/// Used to communicate with a character-based I/O device.
+#[repr(C)]
+pub struct SerialIo {
+    /// The revision to which the protocol interface adheres.
+    pub Revision: u32,
+    /// Resets the hardware device.
+    pub Reset: eficall! { fn(&SerialIo) -> Status },
+    /// Sets the baud rate, receive FIFO depth, timeout, parity, data bits and stop bits.
+    pub SetAttributes: eficall! { fn(
+        &SerialIo,
+        BaudRate: u64,
+        ReceiveFifoDepth: u32,
+        Timeout: u32,
+        Parity: Parity,
+        DataBits: u8,
+        StopBits: StopBits,
+    ) -> Status },
+    /// Sets the control bits on a serial device.
+    pub SetControl: eficall! { fn(&SerialIo, Control: u32) -> Status },
+    /// Reads the status of the control bits on a serial device.
+    pub GetControl: eficall! { fn(&SerialIo, Control: &mut u32) -> Status },
+    /// Sends a buffer of characters to a serial device.
+    pub Write: eficall! { fn(&SerialIo, BufferSize: &mut usize, Buffer: *const u8) -> Status },
+    /// Receives a buffer of characters from a serial device.
+    pub Read: eficall! { fn(&SerialIo, BufferSize: &mut usize, Buffer: *mut u8) -> Status },
+    /// The current attributes of the device.
+    pub Mode: &'static SerialIoMode,
+}
+
+unsafe impl Protocol for SerialIo {
+    const GUID: Guid = guids::protocol::SERIAL_IO;
+}
+
+impl SerialIo {
+    /// Resets the hardware device.
+    pub fn reset(&self) -> Result<(), Error> {
+        (self.Reset)(self)?;
+
+        Ok(())
+    }
+
+    /// Sets the attributes of the device.
+    ///
+    /// A value of 0 for `baud_rate`, `receive_fifo_depth`, `timeout` or `data_bits` selects the
+    /// default of the device.
+    pub fn set_attributes(
+        &self,
+        baud_rate: u64,
+        receive_fifo_depth: u32,
+        timeout: u32,
+        parity: Parity,
+        data_bits: u8,
+        stop_bits: StopBits,
+    ) -> Result<(), Error> {
+        (self.SetAttributes)(
+            self,
+            baud_rate,
+            receive_fifo_depth,
+            timeout,
+            parity,
+            data_bits,
+            stop_bits,
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes data to the device.
+    ///
+    /// Returns the number of bytes written, which is less than the length of `data` if the write
+    /// timed out.
+    pub fn write(&self, data: &[u8]) -> Result<usize, Error> {
+        let mut size = data.len();
+
+        let status = (self.Write)(self, &mut size, data.as_ptr());
+
+        if status != Error::Timeout.into() {
+            status?;
+        }
+
+        quirk::check_transfer(size, data.len())
+    }
+
+    /// Writes all of `data` to the device.
+    pub fn write_all(&self, mut data: &[u8]) -> Result<(), Error> {
+        while !data.is_empty() {
+            match self.write(data)? {
+                0 => return Err(Error::Timeout),
+                size => data = &data[size..],
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads data from the device into `buffer`.
+    ///
+    /// Returns the number of bytes read, which is less than the length of `buffer` if the read
+    /// timed out.
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize, Error> {
+        let mut size = buffer.len();
+
+        let status = (self.Read)(self, &mut size, buffer.as_mut_ptr());
+
+        if status != Error::Timeout.into() {
+            status?;
+        }
+
+        quirk::check_transfer(size, buffer.len())
+    }
+}
+
+/// Writes text to a serial device, translating line feeds to carriage return line feed pairs.
+#[derive(Clone, Copy)]
+pub struct SerialLogger<'a> {
+    /// The device the text is written to.
+    serial: &'a SerialIo,
+}
+
+impl<'a> SerialLogger<'a> {
+    /// Creates a logger that configures the device for the given baud rate.
+    ///
+    /// The device is set to 8 data bits, no parity and one stop bit. If `baud_rate` is `None`,
+    /// `DEFAULT_BAUD_RATE` is used.
+    pub fn new(serial: &'a SerialIo, baud_rate: Option<u64>) -> Result<SerialLogger<'a>, Error> {
+        serial.set_attributes(
+            baud_rate.unwrap_or(DEFAULT_BAUD_RATE),
+            0,
+            0,
+            Parity::None,
+            8,
+            StopBits::One,
+        )?;
+
+        Ok(SerialLogger { serial })
+    }
+
+    /// Creates a logger that leaves the attributes of the device unchanged.
+    pub fn with_current_attributes(serial: &'a SerialIo) -> SerialLogger<'a> {
+        SerialLogger { serial }
+    }
+
+    /// Returns the device the text is written to.
+    pub fn serial(&self) -> &'a SerialIo {
+        self.serial
+    }
+
+    /// Writes `string`, translating line feeds.
+    fn write_text(&self, string: &str) -> Result<(), Error> {
+        text::write_crlf(string, |text| self.serial.write_all(text.as_bytes()))
+    }
+}
+
+impl<'a> fmt::Write for SerialLogger<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_text(s).map_err(|_| fmt::Error)
+    }
+}
+
+#[cfg(feature = "log")]
+impl log::Log for SerialLogger<'static> {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        use core::fmt::Write;
+
+        let mut logger = *self;
+
+        // There is no way to report the error here.
+        let _ = writeln!(logger, "[{}] {}", record.level(), record.args());
+    }
+
+    fn flush(&self) {}
+}
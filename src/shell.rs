@@ -1,16 +1,25 @@
-use crate::{status::Status, Handle};
+use crate::{
+    guid::{Guid, SHELL_GUID, SHELL_PARAMETERS_GUID},
+    protocol::Protocol,
+    status::Status,
+    Handle,
+};
 
 #[repr(C)]
 pub struct Shell {
-    pub Execute: extern "win64" fn(
+    pub Execute: eficall! { fn(
         ImageHandle: &Handle,
         CommandLine: *const u16,
         Environment: *const *const u16,
         Status: *mut Status,
-    ) -> Status,
+    ) -> Status },
     //TODO
 }
 
+unsafe impl Protocol for Shell {
+    const GUID: Guid = SHELL_GUID;
+}
+
 #[repr(C)]
 pub struct ShellParameters {
     pub Argv: *const *const u16,
@@ -19,3 +28,7 @@ pub struct ShellParameters {
     pub StdOut: Handle,
     pub StdErr: Handle,
 }
+
+unsafe impl Protocol for ShellParameters {
+    const GUID: Guid = SHELL_PARAMETERS_GUID;
+}
@@ -0,0 +1,204 @@
+//! Allows consumers to log SMBIOS data records, and enables the producer to create the SMBIOS tables
+//! for a platform.
+//!
+//! This protocol is defined in the UEFI Platform Initialization Specification. The records can be
+//! decoded with the typed structures in `structures`.
+
+pub mod structures;
+
+use crate::{
+    guid::{Guid, SMBIOS_PROTOCOL_GUID},
+    protocol::Protocol,
+    status::{Error, Status},
+    Handle, OptionalHandle,
+};
+
+/// The handle value to pass to `Add()` to let the producer assign a unique handle and to
+/// `GetNext()` to start the search with the first record.
+pub const SMBIOS_HANDLE_PI_RESERVED: u16 = 0xfffe;
+
+/// The maximum length of a string in an SMBIOS record, excluding the null terminator.
+pub const SMBIOS_STRING_MAX_LENGTH: usize = 64;
+
+/// The header that precedes every SMBIOS record.
+///
+/// The formatted area of the record directly follows the header, the unformed area (the string
+/// set) follows the formatted area and is terminated by two null bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct SmbiosTableHeader {
+    /// The SMBIOS structure type.
+    pub Type: u8,
+    /// The length of the formatted area of the record, including the header.
+    pub Length: u8,
+    /// The handle of the record, which can be used to refer to this record.
+    pub Handle: u16,
+}
+
+/// Allows consumers to log SMBIOS data records, and enables the producer to create the SMBIOS tables
+/// for a platform.
+#[repr(C)]
+pub struct Smbios {
+    /// Add an SMBIOS record.
+    pub Add: eficall! { fn(
+        &Smbios,
+        ProducerHandle: OptionalHandle,
+        SmbiosHandle: &mut u16,
+        Record: *const SmbiosTableHeader,
+    ) -> Status },
+    /// Update the string associated with an existing SMBIOS record.
+    pub UpdateString: eficall! { fn(
+        &Smbios,
+        SmbiosHandle: &mut u16,
+        StringNumber: &mut usize,
+        String: *const u8,
+    ) -> Status },
+    /// Remove an SMBIOS record.
+    pub Remove: eficall! { fn(&Smbios, SmbiosHandle: u16) -> Status },
+    /// Discover SMBIOS records.
+    pub GetNext: eficall! { fn(
+        &Smbios,
+        SmbiosHandle: &mut u16,
+        Type: *const u8,
+        Record: &mut *const SmbiosTableHeader,
+        ProducerHandle: *mut OptionalHandle,
+    ) -> Status },
+    /// The major revision of the SMBIOS specification supported.
+    pub MajorVersion: u8,
+    /// The minor revision of the SMBIOS specification supported.
+    pub MinorVersion: u8,
+}
+
+unsafe impl Protocol for Smbios {
+    const GUID: Guid = SMBIOS_PROTOCOL_GUID;
+}
+
+impl Smbios {
+    /// Add an SMBIOS record.
+    ///
+    /// `record` must point to a complete SMBIOS record, including its string set.
+    /// `producer_handle` is the handle of the producer of the record, if any. If `handle` is
+    /// `None`, the producer assigns a unique handle.
+    ///
+    /// Returns the handle of the added record.
+    ///
+    /// # Safety
+    /// The firmware reads past the header, so `record` must be followed by the rest of the
+    /// formatted area and a double null terminated string set.
+    pub unsafe fn add(
+        &self,
+        producer_handle: Option<Handle>,
+        handle: Option<u16>,
+        record: &SmbiosTableHeader,
+    ) -> Result<u16, Error> {
+        let mut smbios_handle = handle.unwrap_or(SMBIOS_HANDLE_PI_RESERVED);
+
+        (self.Add)(self, producer_handle, &mut smbios_handle, record)?;
+
+        Ok(smbios_handle)
+    }
+
+    /// Update the string associated with an existing SMBIOS record.
+    ///
+    /// `string_number` is the one based index of the string within the string set of the record.
+    /// The string must be ASCII, must not contain null characters and must not be longer than
+    /// `SMBIOS_STRING_MAX_LENGTH`.
+    pub fn update_string(
+        &self,
+        handle: u16,
+        string_number: usize,
+        string: &str,
+    ) -> Result<(), Error> {
+        if string.len() > SMBIOS_STRING_MAX_LENGTH
+            || string.bytes().any(|byte| byte == 0 || !byte.is_ascii())
+        {
+            return Err(Error::InvalidParameter);
+        }
+
+        let mut buffer = [0u8; SMBIOS_STRING_MAX_LENGTH + 1];
+        buffer[..string.len()].copy_from_slice(string.as_bytes());
+
+        let mut handle = handle;
+        let mut string_number = string_number;
+
+        (self.UpdateString)(self, &mut handle, &mut string_number, buffer.as_ptr())?;
+
+        Ok(())
+    }
+
+    /// Remove an SMBIOS record.
+    pub fn remove(&self, handle: u16) -> Result<(), Error> {
+        (self.Remove)(self, handle)?;
+
+        Ok(())
+    }
+
+    /// Discover the SMBIOS record following the record with the given handle.
+    ///
+    /// `handle` is updated to the handle of the returned record. To start the search, pass
+    /// `SMBIOS_HANDLE_PI_RESERVED`. If `record_type` is given, only records of that type are
+    /// returned.
+    ///
+    /// Returns the record and the handle of its producer, if the record has one.
+    pub fn get_next(
+        &self,
+        handle: &mut u16,
+        record_type: Option<u8>,
+    ) -> Result<(&SmbiosTableHeader, OptionalHandle), Error> {
+        let mut record = core::ptr::null();
+        let mut producer_handle = None;
+
+        let type_ptr = match record_type {
+            Some(ref record_type) => record_type as *const u8,
+            None => core::ptr::null(),
+        };
+
+        (self.GetNext)(self, handle, type_ptr, &mut record, &mut producer_handle)?;
+
+        if record.is_null() {
+            return Err(Error::NotFound);
+        }
+
+        // This is safe under the assumption, that the firmware returned a valid pointer.
+        Ok((unsafe { &*record }, producer_handle))
+    }
+
+    /// Returns an iterator over all SMBIOS records, optionally restricted to a single type.
+    pub fn records(&self, record_type: Option<u8>) -> SmbiosRecords {
+        SmbiosRecords {
+            smbios: self,
+            handle: SMBIOS_HANDLE_PI_RESERVED,
+            record_type,
+            finished: false,
+        }
+    }
+}
+
+/// An iterator over the SMBIOS records known to the SMBIOS protocol.
+pub struct SmbiosRecords<'a> {
+    /// The protocol used to discover the records.
+    smbios: &'a Smbios,
+    /// The handle of the last returned record.
+    handle: u16,
+    /// The type of the records to return, if restricted.
+    record_type: Option<u8>,
+    /// Whether the last record was already returned.
+    finished: bool,
+}
+
+impl<'a> Iterator for SmbiosRecords<'a> {
+    type Item = (&'a SmbiosTableHeader, OptionalHandle);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        // The firmware starts over with the first record after the last one was returned,
+        // so the iteration has to be stopped explicitly.
+        let record = self.smbios.get_next(&mut self.handle, self.record_type).ok();
+        self.finished = record.is_none();
+
+        record
+    }
+}
@@ -0,0 +1,493 @@
+//! Decodes the common SMBIOS structures into typed records.
+//!
+//! Fields that were added in later versions of the SMBIOS specification are `None` if the
+//! formatted area of a record is too short to contain them. String fields are `None` if the
+//! string number is zero, refers to a missing string or the string is not valid UTF-8.
+
+use core::{mem::size_of, slice, str};
+
+use super::{Smbios, SmbiosTableHeader};
+use crate::{guid::Guid, status::Error};
+
+/// An SMBIOS record with its formatted area and string set.
+#[derive(Clone, Copy, Debug)]
+pub struct SmbiosRecord<'a> {
+    /// The formatted area, including the header.
+    formatted: &'a [u8],
+    /// The string set without the final null terminator.
+    strings: &'a [u8],
+}
+
+impl<'a> SmbiosRecord<'a> {
+    /// Creates a record from the header returned by the SMBIOS protocol.
+    ///
+    /// # Safety
+    /// `header` must be followed by the rest of the formatted area and a double null terminated
+    /// string set, like the records returned by `Smbios::get_next`.
+    pub unsafe fn from_header(header: &'a SmbiosTableHeader) -> SmbiosRecord<'a> {
+        let start = header as *const SmbiosTableHeader as *const u8;
+        let length = usize::from(header.Length).max(size_of::<SmbiosTableHeader>());
+        let strings_start = start.add(length);
+
+        // The string set ends with two null bytes, an empty one consists only of them.
+        let mut strings_len = 0;
+        while *strings_start.add(strings_len) != 0 || *strings_start.add(strings_len + 1) != 0 {
+            strings_len += 1;
+        }
+
+        SmbiosRecord {
+            formatted: slice::from_raw_parts(start, length),
+            strings: slice::from_raw_parts(strings_start, strings_len),
+        }
+    }
+
+    /// Parses the record at the start of `data`, such as the structure table of an SMBIOS entry
+    /// point.
+    ///
+    /// Returns the record and its total size. Returns `Error::BadBufferSize` if the record is
+    /// truncated.
+    pub fn parse(data: &'a [u8]) -> Result<(SmbiosRecord<'a>, usize), Error> {
+        if data.len() < size_of::<SmbiosTableHeader>() {
+            return Err(Error::BadBufferSize);
+        }
+
+        let length = usize::from(data[1]);
+        if length < size_of::<SmbiosTableHeader>() || length > data.len() {
+            return Err(Error::BadBufferSize);
+        }
+
+        let strings_len = data[length..]
+            .windows(2)
+            .position(|bytes| bytes == [0, 0])
+            .ok_or(Error::BadBufferSize)?;
+
+        let record = SmbiosRecord {
+            formatted: &data[..length],
+            strings: &data[length..length + strings_len],
+        };
+
+        Ok((record, length + strings_len + 2))
+    }
+
+    /// Returns the structure type of the record.
+    pub fn record_type(&self) -> u8 {
+        self.formatted[0]
+    }
+
+    /// Returns the handle of the record.
+    pub fn handle(&self) -> u16 {
+        u16::from_le_bytes([self.formatted[2], self.formatted[3]])
+    }
+
+    /// Returns the formatted area of the record, including the header.
+    pub fn formatted(&self) -> &'a [u8] {
+        self.formatted
+    }
+
+    /// Returns the string with the given one based number.
+    pub fn string(&self, number: u8) -> Option<&'a str> {
+        if number == 0 {
+            return None;
+        }
+
+        let string = self
+            .strings
+            .split(|&byte| byte == 0)
+            .nth(usize::from(number) - 1)?;
+
+        str::from_utf8(string).ok()
+    }
+
+    /// Returns the byte at `offset` in the formatted area.
+    pub fn byte(&self, offset: usize) -> Option<u8> {
+        self.formatted.get(offset).cloned()
+    }
+
+    /// Returns the little endian word at `offset` in the formatted area.
+    pub fn word(&self, offset: usize) -> Option<u16> {
+        let bytes = self.formatted.get(offset..offset + 2)?;
+
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Returns the little endian double word at `offset` in the formatted area.
+    pub fn dword(&self, offset: usize) -> Option<u32> {
+        let bytes = self.formatted.get(offset..offset + 4)?;
+
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Returns the little endian quad word at `offset` in the formatted area.
+    pub fn qword(&self, offset: usize) -> Option<u64> {
+        let low = self.dword(offset)?;
+        let high = self.dword(offset + 4)?;
+
+        Some(u64::from(low) | u64::from(high) << 32)
+    }
+
+    /// Returns the string whose number is at `offset` in the formatted area.
+    pub fn string_at(&self, offset: usize) -> Option<&'a str> {
+        self.string(self.byte(offset)?)
+    }
+}
+
+/// An SMBIOS structure that can be decoded from a record.
+pub trait SmbiosStructure<'a>: Sized {
+    /// The structure type of the records.
+    const TYPE: u8;
+    /// The length of the formatted area in the first version of the structure.
+    const MIN_LENGTH: usize;
+
+    /// Decodes the fields of a record whose type and length were checked.
+    fn decode_fields(record: &SmbiosRecord<'a>) -> Self;
+
+    /// Decodes a record.
+    ///
+    /// Returns `Error::Unsupported` if the record has a different type and
+    /// `Error::BadBufferSize` if it is too short.
+    fn decode(record: &SmbiosRecord<'a>) -> Result<Self, Error> {
+        if record.record_type() != Self::TYPE {
+            return Err(Error::Unsupported);
+        }
+        if record.formatted().len() < Self::MIN_LENGTH {
+            return Err(Error::BadBufferSize);
+        }
+
+        Ok(Self::decode_fields(record))
+    }
+}
+
+/// The BIOS information (type 0).
+#[derive(Clone, Copy, Debug)]
+pub struct BiosInfo<'a> {
+    pub vendor: Option<&'a str>,
+    pub version: Option<&'a str>,
+    /// The release date in the form `mm/dd/yyyy`.
+    pub release_date: Option<&'a str>,
+    /// The segment where the BIOS starts, which is zero for UEFI.
+    pub starting_segment: u16,
+    /// The size of the BIOS ROM in bytes, if it is below 16 MiB.
+    pub rom_size: Option<u64>,
+    /// The BIOS characteristics bits.
+    pub characteristics: u64,
+    pub major_release: Option<u8>,
+    pub minor_release: Option<u8>,
+    pub embedded_controller_major_release: Option<u8>,
+    pub embedded_controller_minor_release: Option<u8>,
+}
+
+impl<'a> SmbiosStructure<'a> for BiosInfo<'a> {
+    const TYPE: u8 = 0;
+    const MIN_LENGTH: usize = 0x12;
+
+    fn decode_fields(record: &SmbiosRecord<'a>) -> BiosInfo<'a> {
+        let rom_size = match record.byte(0x09) {
+            Some(0xff) | None => None,
+            Some(size) => Some((u64::from(size) + 1) * 64 * 1024),
+        };
+
+        BiosInfo {
+            vendor: record.string_at(0x04),
+            version: record.string_at(0x05),
+            release_date: record.string_at(0x08),
+            starting_segment: record.word(0x06).unwrap_or(0),
+            rom_size,
+            characteristics: record.qword(0x0a).unwrap_or(0),
+            major_release: record.byte(0x14),
+            minor_release: record.byte(0x15),
+            embedded_controller_major_release: record.byte(0x16),
+            embedded_controller_minor_release: record.byte(0x17),
+        }
+    }
+}
+
+/// The system information (type 1).
+#[derive(Clone, Copy, Debug)]
+pub struct SystemInfo<'a> {
+    pub manufacturer: Option<&'a str>,
+    pub product: Option<&'a str>,
+    pub version: Option<&'a str>,
+    pub serial: Option<&'a str>,
+    /// The UUID of the system, in the byte order of SMBIOS 2.6 and later.
+    ///
+    /// This is `None` if the UUID is not set or not present.
+    pub uuid: Option<Guid>,
+    /// The event that woke up the system.
+    pub wake_up_type: Option<u8>,
+    pub sku: Option<&'a str>,
+    pub family: Option<&'a str>,
+}
+
+impl<'a> SmbiosStructure<'a> for SystemInfo<'a> {
+    const TYPE: u8 = 1;
+    const MIN_LENGTH: usize = 0x08;
+
+    fn decode_fields(record: &SmbiosRecord<'a>) -> SystemInfo<'a> {
+        let uuid = record.formatted().get(0x08..0x18).and_then(|bytes| {
+            // All zero bytes mean that the UUID is not present, all one bytes that it is not set.
+            if bytes.iter().all(|&byte| byte == 0) || bytes.iter().all(|&byte| byte == 0xff) {
+                return None;
+            }
+
+            let mut uuid = [0; 16];
+            uuid.copy_from_slice(bytes);
+
+            Some(Guid::from_bytes(uuid))
+        });
+
+        SystemInfo {
+            manufacturer: record.string_at(0x04),
+            product: record.string_at(0x05),
+            version: record.string_at(0x06),
+            serial: record.string_at(0x07),
+            uuid,
+            wake_up_type: record.byte(0x18),
+            sku: record.string_at(0x19),
+            family: record.string_at(0x1a),
+        }
+    }
+}
+
+/// The baseboard information (type 2).
+#[derive(Clone, Copy, Debug)]
+pub struct BaseboardInfo<'a> {
+    pub manufacturer: Option<&'a str>,
+    pub product: Option<&'a str>,
+    pub version: Option<&'a str>,
+    pub serial: Option<&'a str>,
+    pub asset_tag: Option<&'a str>,
+    /// The baseboard feature flags.
+    pub feature_flags: Option<u8>,
+    pub location_in_chassis: Option<&'a str>,
+    /// The handle of the chassis record that contains the board.
+    pub chassis_handle: Option<u16>,
+    pub board_type: Option<u8>,
+}
+
+impl<'a> SmbiosStructure<'a> for BaseboardInfo<'a> {
+    const TYPE: u8 = 2;
+    const MIN_LENGTH: usize = 0x08;
+
+    fn decode_fields(record: &SmbiosRecord<'a>) -> BaseboardInfo<'a> {
+        BaseboardInfo {
+            manufacturer: record.string_at(0x04),
+            product: record.string_at(0x05),
+            version: record.string_at(0x06),
+            serial: record.string_at(0x07),
+            asset_tag: record.string_at(0x08),
+            feature_flags: record.byte(0x09),
+            location_in_chassis: record.string_at(0x0a),
+            chassis_handle: record.word(0x0b),
+            board_type: record.byte(0x0d),
+        }
+    }
+}
+
+/// The processor information (type 4).
+#[derive(Clone, Copy, Debug)]
+pub struct ProcessorInfo<'a> {
+    pub socket: Option<&'a str>,
+    pub processor_type: u8,
+    /// The processor family, resolved from the extended family field if necessary.
+    pub family: u16,
+    pub manufacturer: Option<&'a str>,
+    /// The raw processor identification, which is the CPUID signature and feature flags on x86.
+    pub id: u64,
+    pub version: Option<&'a str>,
+    /// The external clock in MHz, if it is known.
+    pub external_clock: Option<u16>,
+    /// The maximum speed in MHz, if it is known.
+    pub max_speed: Option<u16>,
+    /// The current speed in MHz, if it is known.
+    pub current_speed: Option<u16>,
+    /// The socket and CPU status bits.
+    pub status: u8,
+    pub serial: Option<&'a str>,
+    pub asset_tag: Option<&'a str>,
+    pub part_number: Option<&'a str>,
+    pub core_count: Option<u16>,
+    pub cores_enabled: Option<u16>,
+    pub thread_count: Option<u16>,
+}
+
+impl<'a> SmbiosStructure<'a> for ProcessorInfo<'a> {
+    const TYPE: u8 = 4;
+    const MIN_LENGTH: usize = 0x1a;
+
+    fn decode_fields(record: &SmbiosRecord<'a>) -> ProcessorInfo<'a> {
+        // Counts above 254 are stored in the extended fields added in SMBIOS 3.0.
+        let count = |offset: usize, extended_offset: usize| match record.byte(offset)? {
+            0 => None,
+            0xff => record.word(extended_offset),
+            count => Some(u16::from(count)),
+        };
+        let speed = |offset: usize| match record.word(offset)? {
+            0 => None,
+            speed => Some(speed),
+        };
+        let family = match record.byte(0x06).unwrap_or(0) {
+            0xfe => record.word(0x28).unwrap_or(0xfe),
+            family => u16::from(family),
+        };
+
+        ProcessorInfo {
+            socket: record.string_at(0x04),
+            processor_type: record.byte(0x05).unwrap_or(0),
+            family,
+            manufacturer: record.string_at(0x07),
+            id: record.qword(0x08).unwrap_or(0),
+            version: record.string_at(0x10),
+            external_clock: speed(0x12),
+            max_speed: speed(0x14),
+            current_speed: speed(0x16),
+            status: record.byte(0x18).unwrap_or(0),
+            serial: record.string_at(0x20),
+            asset_tag: record.string_at(0x21),
+            part_number: record.string_at(0x22),
+            core_count: count(0x23, 0x2a),
+            cores_enabled: count(0x24, 0x2c),
+            thread_count: count(0x25, 0x2e),
+        }
+    }
+}
+
+/// The physical memory array (type 16), which describes a set of memory devices.
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicalMemoryArray {
+    pub location: u8,
+    /// The function of the memory, such as system memory or video memory.
+    pub array_use: u8,
+    pub error_correction: u8,
+    /// The maximum capacity in bytes, if it is known.
+    pub maximum_capacity: Option<u64>,
+    /// The handle of the record describing the last error, if there is one.
+    pub error_information_handle: Option<u16>,
+    /// The number of slots or sockets for memory devices.
+    pub number_of_devices: u16,
+}
+
+impl<'a> SmbiosStructure<'a> for PhysicalMemoryArray {
+    const TYPE: u8 = 16;
+    const MIN_LENGTH: usize = 0x0f;
+
+    fn decode_fields(record: &SmbiosRecord<'a>) -> PhysicalMemoryArray {
+        let maximum_capacity = match record.dword(0x07).unwrap_or(0x8000_0000) {
+            0x8000_0000 => record.qword(0x0f).filter(|&capacity| capacity != 0),
+            capacity => Some(u64::from(capacity) * 1024),
+        };
+        let error_information_handle = match record.word(0x0b) {
+            Some(0xfffe) | Some(0xffff) | None => None,
+            handle => handle,
+        };
+
+        PhysicalMemoryArray {
+            location: record.byte(0x04).unwrap_or(0),
+            array_use: record.byte(0x05).unwrap_or(0),
+            error_correction: record.byte(0x06).unwrap_or(0),
+            maximum_capacity,
+            error_information_handle,
+            number_of_devices: record.word(0x0d).unwrap_or(0),
+        }
+    }
+}
+
+/// A memory device (type 17), such as a DIMM.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryDevice<'a> {
+    /// The handle of the physical memory array that the device belongs to.
+    pub array_handle: u16,
+    /// The total width in bits including error correction, if it is known.
+    pub total_width: Option<u16>,
+    /// The data width in bits, if it is known.
+    pub data_width: Option<u16>,
+    /// The size in bytes, which is zero if no device is installed in the socket, or `None` if
+    /// the size is not known.
+    pub size: Option<u64>,
+    pub form_factor: u8,
+    pub device_locator: Option<&'a str>,
+    pub bank_locator: Option<&'a str>,
+    pub memory_type: u8,
+    /// The maximum speed in MT/s, if it is known.
+    pub speed: Option<u32>,
+    pub manufacturer: Option<&'a str>,
+    pub serial: Option<&'a str>,
+    pub asset_tag: Option<&'a str>,
+    pub part_number: Option<&'a str>,
+    /// The configured speed in MT/s, if it is known.
+    pub configured_speed: Option<u32>,
+}
+
+impl<'a> MemoryDevice<'a> {
+    /// Returns whether a device is installed in the socket.
+    pub fn is_installed(&self) -> bool {
+        self.size != Some(0)
+    }
+}
+
+impl<'a> SmbiosStructure<'a> for MemoryDevice<'a> {
+    const TYPE: u8 = 17;
+    const MIN_LENGTH: usize = 0x15;
+
+    fn decode_fields(record: &SmbiosRecord<'a>) -> MemoryDevice<'a> {
+        let width = |offset: usize| match record.word(offset)? {
+            0xffff => None,
+            width => Some(width),
+        };
+        let size = match record.word(0x0c) {
+            Some(0xffff) | None => None,
+            // The size is stored in the extended size field in MiB.
+            Some(0x7fff) => record
+                .dword(0x1c)
+                .map(|size| u64::from(size & 0x7fff_ffff) * 1024 * 1024),
+            Some(size) if size & 0x8000 != 0 => Some(u64::from(size & 0x7fff) * 1024),
+            Some(size) => Some(u64::from(size) * 1024 * 1024),
+        };
+        // Speeds above 65534 MT/s are stored in the extended speed fields added in SMBIOS 3.3.
+        let speed = |offset: usize, extended_offset: usize| match record.word(offset)? {
+            0 => None,
+            0xffff => record.dword(extended_offset),
+            speed => Some(u32::from(speed)),
+        };
+
+        MemoryDevice {
+            array_handle: record.word(0x04).unwrap_or(0),
+            total_width: width(0x08),
+            data_width: width(0x0a),
+            size,
+            form_factor: record.byte(0x0e).unwrap_or(0),
+            device_locator: record.string_at(0x10),
+            bank_locator: record.string_at(0x11),
+            memory_type: record.byte(0x12).unwrap_or(0),
+            speed: speed(0x15, 0x54),
+            manufacturer: record.string_at(0x17),
+            serial: record.string_at(0x18),
+            asset_tag: record.string_at(0x19),
+            part_number: record.string_at(0x1a),
+            configured_speed: speed(0x20, 0x58),
+        }
+    }
+}
+
+impl Smbios {
+    /// Returns an iterator over the records of structure `T`, decoded.
+    ///
+    /// Records that are too short to be decoded are skipped.
+    pub fn structures<'a, T: SmbiosStructure<'a>>(&'a self) -> impl Iterator<Item = T> + 'a {
+        self.records(Some(T::TYPE)).filter_map(|(header, _)| {
+            // This is safe, because the protocol returns complete records.
+            T::decode(&unsafe { SmbiosRecord::from_header(header) }).ok()
+        })
+    }
+
+    /// Returns the first record of structure `T`, decoded.
+    ///
+    /// Returns `Error::NotFound` if there is no such record.
+    pub fn structure<'a, T: SmbiosStructure<'a>>(&'a self) -> Result<T, Error> {
+        self.structures().next().ok_or(Error::NotFound)
+    }
+
+    /// Returns the system information, which identifies the machine.
+    pub fn system_info(&self) -> Result<SystemInfo, Error> {
+        self.structure()
+    }
+}
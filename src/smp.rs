@@ -0,0 +1,714 @@
+//! Runs code on the application processors through the MP Services protocol.
+//!
+//! `run_on_all_aps` calls a closure on every enabled application processor and waits for all of
+//! them to return. `park_aps_at` starts the application processors at an entry point of the
+//! kernel on stacks allocated as `LoaderData`, so they are waiting where the kernel expects them
+//! until boot services are exited.
+
+use core::{
+    hint::spin_loop,
+    mem::size_of,
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering},
+};
+
+use crate::{
+    boot::{BootServices, EventType, OwnedEvent, TPL_APPLICATION},
+    guid::Guid,
+    guids,
+    memory::{MemoryType, PhysicalAddress, PAGE_SIZE},
+    protocol::Protocol,
+    status::{Error, Status},
+    system, Event,
+};
+
+/// The processor is the bootstrap processor.
+pub const PROCESSOR_AS_BSP_BIT: u32 = 0x0000_0001;
+/// The processor is enabled.
+pub const PROCESSOR_ENABLED_BIT: u32 = 0x0000_0002;
+/// The processor passed its built-in self test.
+pub const PROCESSOR_HEALTH_STATUS_BIT: u32 = 0x0000_0004;
+
+/// The number of pages of the stack of each parked application processor.
+pub const DEFAULT_AP_STACK_PAGES: usize = 16;
+
+/// How long `park_aps_at` waits for each application processor to start.
+const AP_START_TIMEOUT_MICROSECONDS: usize = 1_000_000;
+/// How long the bootstrap processor stalls between checks of the application processors.
+const AP_POLL_MICROSECONDS: usize = 100;
+
+/// A function that is run on an application processor.
+pub type ApProcedure = eficall! { fn(ProcedureArgument: usize) };
+
+/// The physical location of a processor.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct CpuPhysicalLocation {
+    /// The zero-based physical package number.
+    pub Package: u32,
+    /// The zero-based physical core number within the package.
+    pub Core: u32,
+    /// The zero-based logical thread number within the core.
+    pub Thread: u32,
+}
+
+/// Information about a processor.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct ProcessorInformation {
+    /// The unique identifier of the processor, such as its local APIC ID.
+    pub ProcessorId: u64,
+    /// A combination of `PROCESSOR_AS_BSP_BIT`, `PROCESSOR_ENABLED_BIT` and
+    /// `PROCESSOR_HEALTH_STATUS_BIT`.
+    pub StatusFlag: u32,
+    /// The physical location of the processor.
+    pub Location: CpuPhysicalLocation,
+}
+
+impl ProcessorInformation {
+    /// Returns whether the processor is the bootstrap processor.
+    pub fn is_bsp(&self) -> bool {
+        self.StatusFlag & PROCESSOR_AS_BSP_BIT != 0
+    }
+
+    /// Returns whether the processor is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.StatusFlag & PROCESSOR_ENABLED_BIT != 0
+    }
+}
+
+/// Manages the processors of the system during boot services.
+#[repr(C)]
+pub struct MpServices {
+    /// Returns the number of processors and the number of enabled processors.
+    pub GetNumberOfProcessors: eficall! { fn(
+        This: &MpServices,
+        NumberOfProcessors: &mut usize,
+        NumberOfEnabledProcessors: &mut usize,
+    ) -> Status },
+    /// Returns information about a processor.
+    pub GetProcessorInfo: eficall! { fn(
+        This: &MpServices,
+        ProcessorNumber: usize,
+        ProcessorInfoBuffer: &mut ProcessorInformation,
+    ) -> Status },
+    /// Runs a function on all enabled application processors.
+    pub StartupAllAPs: eficall! { fn(
+        This: &MpServices,
+        Procedure: ApProcedure,
+        SingleThread: bool,
+        WaitEvent: Event,
+        TimeoutInMicroseconds: usize,
+        ProcedureArgument: usize,
+        FailedCpuList: *mut *mut usize,
+    ) -> Status },
+    /// Runs a function on a single application processor.
+    pub StartupThisAP: eficall! { fn(
+        This: &MpServices,
+        Procedure: ApProcedure,
+        ProcessorNumber: usize,
+        WaitEvent: Event,
+        TimeoutInMicroseconds: usize,
+        ProcedureArgument: usize,
+        Finished: *mut bool,
+    ) -> Status },
+    SwitchBSP: usize,
+    EnableDisableAP: usize,
+    /// Returns the number of the processor that calls this function.
+    pub WhoAmI: eficall! { fn(This: &MpServices, ProcessorNumber: &mut usize) -> Status },
+}
+
+unsafe impl Protocol for MpServices {
+    const GUID: Guid = guids::protocol::MP_SERVICES;
+}
+
+impl MpServices {
+    /// Returns the number of processors and the number of enabled processors.
+    pub fn number_of_processors(&self) -> Result<(usize, usize), Error> {
+        let mut processors = 0;
+        let mut enabled = 0;
+
+        (self.GetNumberOfProcessors)(self, &mut processors, &mut enabled)?;
+
+        Ok((processors, enabled))
+    }
+
+    /// Returns information about the processor with the given number.
+    pub fn processor_info(&self, processor: usize) -> Result<ProcessorInformation, Error> {
+        let mut info = ProcessorInformation::default();
+
+        (self.GetProcessorInfo)(self, processor, &mut info)?;
+
+        Ok(info)
+    }
+
+    /// Returns the number of the calling processor.
+    ///
+    /// Unlike the other functions, this can be called on application processors.
+    pub fn who_am_i(&self) -> Result<usize, Error> {
+        let mut processor = 0;
+
+        (self.WhoAmI)(self, &mut processor)?;
+
+        Ok(processor)
+    }
+
+    /// Runs `procedure` on all enabled application processors at the same time.
+    ///
+    /// If `wait_event` is `None`, this returns when all processors are done. Otherwise it returns
+    /// immediately and the event is signaled when all processors are done. A `timeout` of zero
+    /// waits forever. Returns `Error::NotStarted` if there are no enabled application processors.
+    ///
+    /// # Safety
+    /// `procedure` must be safe to run on an application processor with `argument`, which has
+    /// to stay valid until the procedure returns.
+    pub unsafe fn startup_all_aps(
+        &self,
+        procedure: ApProcedure,
+        wait_event: Option<&Event>,
+        timeout: usize,
+        argument: usize,
+    ) -> Result<(), Error> {
+        let wait_event = wait_event.cloned().unwrap_or(Event(0));
+
+        (self.StartupAllAPs)(
+            self,
+            procedure,
+            false,
+            wait_event,
+            timeout,
+            argument,
+            0 as *mut *mut usize,
+        )?;
+
+        Ok(())
+    }
+
+    /// Runs `procedure` on the application processor with the given number.
+    ///
+    /// If `wait_event` is `None`, this returns when the processor is done. Otherwise it returns
+    /// immediately and the event is signaled when the processor is done. A `timeout` of zero
+    /// waits forever.
+    ///
+    /// # Safety
+    /// See `startup_all_aps`.
+    pub unsafe fn startup_this_ap(
+        &self,
+        procedure: ApProcedure,
+        processor: usize,
+        wait_event: Option<&Event>,
+        timeout: usize,
+        argument: usize,
+    ) -> Result<(), Error> {
+        let wait_event = wait_event.cloned().unwrap_or(Event(0));
+
+        (self.StartupThisAP)(
+            self,
+            procedure,
+            processor,
+            wait_event,
+            timeout,
+            argument,
+            0 as *mut bool,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The argument of `run_trampoline`.
+struct RunContext<'a, F> {
+    /// The protocol that started the procedure.
+    mp_services: &'a MpServices,
+    /// The closure to run.
+    closure: &'a F,
+}
+
+/// The protocol that runs the closure of `run_on_all_aps_with`, while the closure runs.
+static RUNNING_MP_SERVICES: AtomicPtr<MpServices> = AtomicPtr::new(ptr::null_mut());
+/// The number of the bootstrap processor, while `RUNNING_MP_SERVICES` is set.
+static BSP_NUMBER: AtomicUsize = AtomicUsize::new(0);
+/// The number of application processors on which the closure of `run_on_all_aps_with` panicked.
+static AP_PANICKED: AtomicUsize = AtomicUsize::new(0);
+/// The number of application processors on which the closure of `run_on_all_aps_with` returned.
+static AP_FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+eficall! {
+    /// Calls the closure of a `RunContext` with the number of the processor.
+    fn run_trampoline<F: Fn(usize) + Sync>(argument: usize) {
+        // This is safe, because `run_on_all_aps_with` passes a `RunContext<F>` that outlives the
+        // call.
+        let context = unsafe { &*(argument as *const RunContext<F>) };
+
+        if let Ok(processor) = context.mp_services.who_am_i() {
+            (context.closure)(processor);
+        }
+
+        // The context must not be used afterwards, because it may be gone once every processor
+        // is counted.
+        AP_FINISHED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns whether the caller runs on an application processor that was started by
+/// `run_on_all_aps`.
+///
+/// Boot services may only be called on the bootstrap processor, so a custom panic handler can use
+/// this to avoid them when a closure passed to `run_on_all_aps` panics.
+pub fn on_application_processor() -> bool {
+    let mp_services = RUNNING_MP_SERVICES.load(Ordering::SeqCst);
+    if mp_services.is_null() {
+        return false;
+    }
+
+    // This is safe, because the protocol is only registered while `run_on_all_aps_with` uses it.
+    match unsafe { &*mp_services }.who_am_i() {
+        Ok(processor) => processor != BSP_NUMBER.load(Ordering::SeqCst),
+        Err(_) => false,
+    }
+}
+
+/// Stops the calling application processor after the closure of `run_on_all_aps` panicked.
+///
+/// `run_on_all_aps_with` returns `Error::Aborted` on the bootstrap processor once the closure
+/// returned or panicked on all other processors. The processor stays busy, so the firmware cannot
+/// run anything else on it. The closure must not be used after this was called.
+pub fn halt_after_panic() -> ! {
+    AP_PANICKED.fetch_add(1, Ordering::SeqCst);
+
+    loop {
+        spin_loop();
+    }
+}
+
+/// Calls `closure` on every enabled application processor with the number of the processor.
+///
+/// The processors run concurrently and this returns once all of them are done. The closure must
+/// not use boot services, which may only be called on the bootstrap processor. If there are no
+/// enabled application processors, this returns immediately.
+///
+/// A panic in the closure cannot unwind through the firmware. Instead, the panic handler of the
+/// `panic-handler` feature stops the processor with `halt_after_panic` and this returns
+/// `Error::Aborted` after waiting for the closure to finish on all other processors, because they
+/// still borrow it. Custom panic handlers should do the same if `on_application_processor`
+/// returns `true`.
+pub fn run_on_all_aps_with<F: Fn(usize) + Sync>(
+    boot_services: &BootServices,
+    closure: &F,
+) -> Result<(), Error> {
+    let mp_services = boot_services.locate_protocol::<MpServices>()?;
+    let context = RunContext {
+        mp_services,
+        closure,
+    };
+
+    // This is safe, because no notification function is used.
+    let completion =
+        unsafe { OwnedEvent::new(boot_services, EventType::empty(), TPL_APPLICATION, None, 0)? };
+
+    // The number of enabled processors includes the bootstrap processor.
+    let (_, enabled) = mp_services.number_of_processors()?;
+    let application_processors = enabled.saturating_sub(1);
+
+    BSP_NUMBER.store(mp_services.who_am_i()?, Ordering::SeqCst);
+    AP_PANICKED.store(0, Ordering::SeqCst);
+    AP_FINISHED.store(0, Ordering::SeqCst);
+    RUNNING_MP_SERVICES.store(
+        mp_services as *const MpServices as *mut MpServices,
+        Ordering::SeqCst,
+    );
+
+    // This is safe, because the context outlives the procedure, which is awaited below.
+    let started = unsafe {
+        mp_services.startup_all_aps(
+            run_trampoline::<F>,
+            Some(&completion),
+            0,
+            &context as *const RunContext<F> as usize,
+        )
+    };
+
+    let result = match started {
+        Ok(()) => loop {
+            if AP_PANICKED.load(Ordering::SeqCst) != 0 {
+                break Err(Error::Aborted);
+            }
+
+            match boot_services.check_event(&completion) {
+                Ok(false) => (),
+                Ok(true) => break Ok(()),
+                Err(error) => break Err(error),
+            }
+
+            if let Err(error) = boot_services.stall(AP_POLL_MICROSECONDS) {
+                break Err(error);
+            }
+        },
+        Err(Error::NotStarted) => Ok(()),
+        Err(error) => Err(error),
+    };
+
+    if started.is_ok() && result.is_err() {
+        // The processors may still run the closure, which borrows the context and the closure of
+        // the caller, so they have to be awaited before returning.
+        while AP_FINISHED.load(Ordering::SeqCst) + AP_PANICKED.load(Ordering::SeqCst)
+            < application_processors
+        {
+            if boot_services.stall(AP_POLL_MICROSECONDS).is_err() {
+                spin_loop();
+            }
+        }
+
+        // The firmware may still signal the event after the processors returned, or never if one
+        // of them panicked, so it has to stay open.
+        completion.into_raw();
+        RUNNING_MP_SERVICES.store(ptr::null_mut(), Ordering::SeqCst);
+
+        return result;
+    }
+
+    RUNNING_MP_SERVICES.store(ptr::null_mut(), Ordering::SeqCst);
+    completion.close(boot_services)?;
+
+    result
+}
+
+/// Calls `closure` on every enabled application processor with the number of the processor.
+///
+/// The boot services registered with `system::init` are used. Returns `Error::NotStarted` if
+/// `system::init` was not called.
+pub fn run_on_all_aps<F: Fn(usize) + Sync>(closure: &F) -> Result<(), Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+
+    run_on_all_aps_with(boot_services, closure)
+}
+
+/// The processor waits in the trampoline until the other processors started.
+const PARK_WAITING: u8 = 0;
+/// The processor jumps to its entry point.
+const PARK_RELEASED: u8 = 1;
+/// The processor returns from the trampoline to the firmware.
+const PARK_ABORTED: u8 = 2;
+
+/// The address of the `ParkContext` of the processor that `park_aps_at_with` is starting, until
+/// the processor claims it.
+static PENDING_PARK_CONTEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// The argument of `park_trampoline`, which is stored at the top of the stack of the processor.
+#[repr(C, align(16))]
+struct ParkContext {
+    /// The address that the processor jumps to.
+    entry: u64,
+    /// The second argument of the entry point.
+    argument: u64,
+    /// The initial stack pointer.
+    stack_top: u64,
+    /// The number of the processor.
+    processor: u64,
+    /// One of `PARK_WAITING`, `PARK_RELEASED` and `PARK_ABORTED`.
+    state: AtomicU8,
+    /// The event that is signaled when the processor returns from the trampoline.
+    completion: Event,
+    /// The address of the stack.
+    stack: u64,
+    /// The address of the context of the processor that was started before, or zero.
+    previous: u64,
+}
+
+/// Claims the `ParkContext` at `argument` and waits until the processor is released.
+///
+/// Returns the entry point, its argument, the initial stack pointer and the number of the
+/// processor, or `None` if the processor must return to the firmware.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn wait_for_release(argument: usize) -> Option<(u64, u64, u64, u64)> {
+    // If `park_aps_at_with` gave up on the processor, the context may be freed already.
+    PENDING_PARK_CONTEXT
+        .compare_exchange(argument, 0, Ordering::SeqCst, Ordering::SeqCst)
+        .ok()?;
+
+    // This is safe, because `park_aps_at_with` passes a `ParkContext` in the allocated stack,
+    // which stays allocated until the processor returns or jumps to its entry point.
+    let context = unsafe { &*(argument as *const ParkContext) };
+
+    loop {
+        match context.state.load(Ordering::SeqCst) {
+            PARK_RELEASED => {
+                return Some((
+                    context.entry,
+                    context.argument,
+                    context.stack_top,
+                    context.processor,
+                ))
+            }
+            PARK_ABORTED => return None,
+            _ => spin_loop(),
+        }
+    }
+}
+
+eficall! {
+    /// Switches to the stack of a `ParkContext` and jumps to its entry point.
+    #[cfg(target_arch = "x86_64")]
+    fn park_trampoline(argument: usize) {
+        let (entry, argument, stack_top, processor) = match wait_for_release(argument) {
+            Some(release) => release,
+            None => return,
+        };
+
+        // This is safe, because the stack was allocated for this processor and the entry point was
+        // supplied by the caller of `park_aps_at`.
+        unsafe {
+            asm!(
+                "mov rsp, {stack_top}",
+                "push 0",
+                "jmp {entry}",
+                stack_top = in(reg) stack_top,
+                entry = in(reg) entry,
+                in("rdi") processor,
+                in("rsi") argument,
+                options(noreturn),
+            );
+        }
+    }
+}
+
+eficall! {
+    /// Switches to the stack of a `ParkContext` and jumps to its entry point.
+    #[cfg(target_arch = "aarch64")]
+    fn park_trampoline(argument: usize) {
+        let (entry, argument, stack_top, processor) = match wait_for_release(argument) {
+            Some(release) => release,
+            None => return,
+        };
+
+        // This is safe, because the stack was allocated for this processor and the entry point was
+        // supplied by the caller of `park_aps_at`.
+        unsafe {
+            asm!(
+                "mov sp, {stack_top}",
+                "mov x30, xzr",
+                "br {entry}",
+                stack_top = in(reg) stack_top,
+                entry = in(reg) entry,
+                in("x0") processor,
+                in("x1") argument,
+                options(noreturn),
+            );
+        }
+    }
+}
+
+/// Starts the application processor with the given number in `park_trampoline`, where it waits
+/// until it is released.
+///
+/// Returns the address of its `ParkContext`, or `None` if the processor is the bootstrap
+/// processor or disabled.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+unsafe fn start_parked_ap(
+    boot_services: &BootServices,
+    mp_services: &MpServices,
+    processor: usize,
+    entry: PhysicalAddress,
+    argument: u64,
+    stack_pages: usize,
+    previous: u64,
+) -> Result<Option<u64>, Error> {
+    let info = mp_services.processor_info(processor)?;
+    if info.is_bsp() || !info.is_enabled() {
+        return Ok(None);
+    }
+
+    let stack = boot_services.allocate_pages(MemoryType::LoaderData, stack_pages)?;
+    let context_address =
+        stack as u64 + (stack_pages * PAGE_SIZE - size_of::<ParkContext>()) as u64;
+
+    let completion =
+        match OwnedEvent::new(boot_services, EventType::empty(), TPL_APPLICATION, None, 0) {
+            Ok(completion) => completion,
+            Err(error) => {
+                let _ = boot_services.free_pages(stack, stack_pages);
+
+                return Err(error);
+            }
+        };
+
+    (context_address as usize as *mut ParkContext).write(ParkContext {
+        entry: entry.0,
+        argument,
+        stack_top: context_address,
+        processor: processor as u64,
+        state: AtomicU8::new(PARK_WAITING),
+        completion: completion.event(),
+        stack: stack as u64,
+        previous,
+    });
+    PENDING_PARK_CONTEXT.store(context_address as usize, Ordering::SeqCst);
+
+    if let Err(error) = mp_services.startup_this_ap(
+        park_trampoline,
+        processor,
+        Some(&completion),
+        0,
+        context_address as usize,
+    ) {
+        PENDING_PARK_CONTEXT.store(0, Ordering::SeqCst);
+        let _ = completion.close(boot_services);
+        let _ = boot_services.free_pages(stack, stack_pages);
+
+        return Err(error);
+    }
+
+    let mut waited = 0;
+    let started = loop {
+        if PENDING_PARK_CONTEXT.load(Ordering::SeqCst) != context_address as usize {
+            break true;
+        }
+
+        if waited >= AP_START_TIMEOUT_MICROSECONDS
+            || boot_services.stall(AP_POLL_MICROSECONDS).is_err()
+        {
+            // Withdrawing the context fails if the processor claimed it in the meantime.
+            break PENDING_PARK_CONTEXT
+                .compare_exchange(
+                    context_address as usize,
+                    0,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_err();
+        }
+
+        waited += AP_POLL_MICROSECONDS;
+    };
+
+    // The firmware refers to the event until the processor returns, which a parked processor
+    // never does. The procedure of a processor that did not start stays pending as well.
+    completion.into_raw();
+
+    if !started {
+        // The processor no longer touches the context if it starts later, so its stack can be
+        // freed.
+        let _ = boot_services.free_pages(stack, stack_pages);
+
+        return Err(Error::Timeout);
+    }
+
+    Ok(Some(context_address))
+}
+
+/// Starts every enabled application processor at `entry` on its own stack.
+///
+/// The entry point is called like a function with the System V calling convention on `x86_64` or
+/// the AAPCS64 on `aarch64`, with the processor number and `argument` as arguments and a zero
+/// return address. It must never return and usually waits until the kernel releases the
+/// processor. Each stack has `stack_pages` pages and is allocated as `LoaderData`, so it stays
+/// reserved in the memory map. The processors only jump to the entry point once all of them
+/// started, and the completion events of the processors are never closed, because the firmware
+/// keeps referring to them.
+///
+/// The processors stay at the entry point only until boot services are exited. Firmware based on
+/// EDK2 sends an INIT to all application processors in `ExitBootServices`, which puts them back
+/// into the wait-for-SIPI state, so the kernel has to start them again with INIT-SIPI-SIPI. The
+/// parked processors are useful for work that is done before boot services are exited.
+///
+/// Returns the number of parked processors. Returns `Error::Timeout` if a processor did not start
+/// and `Error::Unsupported` on architectures other than `x86_64` and `aarch64`. On errors, the
+/// processors that started already return to the firmware and their stacks are freed.
+///
+/// # Safety
+/// `entry` must be the address of code that is safe to run on the application processors in the
+/// way described above.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub unsafe fn park_aps_at_with(
+    boot_services: &BootServices,
+    entry: PhysicalAddress,
+    argument: u64,
+    stack_pages: usize,
+) -> Result<usize, Error> {
+    let stack_size = stack_pages * PAGE_SIZE;
+    if stack_size < 2 * size_of::<ParkContext>() {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mp_services = boot_services.locate_protocol::<MpServices>()?;
+    let (processors, _) = mp_services.number_of_processors()?;
+
+    let mut last = 0;
+    let mut parked = 0;
+    let mut result = Ok(());
+    for processor in 0..processors {
+        match start_parked_ap(
+            boot_services,
+            mp_services,
+            processor,
+            entry,
+            argument,
+            stack_pages,
+            last,
+        ) {
+            Ok(Some(context)) => {
+                last = context;
+                parked += 1;
+            }
+            Ok(None) => (),
+            Err(error) => {
+                result = Err(error);
+                break;
+            }
+        }
+    }
+
+    let state = if result.is_ok() {
+        PARK_RELEASED
+    } else {
+        PARK_ABORTED
+    };
+
+    let mut context_address = last;
+    while context_address != 0 {
+        let context = &*(context_address as usize as *const ParkContext);
+        // The context becomes part of the stack once the processor is released.
+        let (previous, completion, stack) = (context.previous, context.completion, context.stack);
+
+        context.state.store(state, Ordering::SeqCst);
+
+        if state == PARK_ABORTED {
+            // The stack can only be freed after the processor returned from the trampoline.
+            if boot_services.wait_for_event(&completion).is_ok() {
+                let _ = OwnedEvent::from_raw(completion).close(boot_services);
+                let _ = boot_services.free_pages(stack as usize as *const u8, stack_pages);
+            }
+        }
+
+        context_address = previous;
+    }
+
+    result.map(|()| parked)
+}
+
+/// Starts every enabled application processor at `entry` on its own stack.
+///
+/// Parking application processors is only supported on `x86_64` and `aarch64`.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub unsafe fn park_aps_at_with(
+    _boot_services: &BootServices,
+    _entry: PhysicalAddress,
+    _argument: u64,
+    _stack_pages: usize,
+) -> Result<usize, Error> {
+    Err(Error::Unsupported)
+}
+
+/// Starts every enabled application processor at `entry` on a stack of
+/// `DEFAULT_AP_STACK_PAGES` pages.
+///
+/// The boot services registered with `system::init` are used. Returns `Error::NotStarted` if
+/// `system::init` was not called.
+///
+/// # Safety
+/// See `park_aps_at_with`.
+pub unsafe fn park_aps_at(entry: PhysicalAddress, argument: u64) -> Result<usize, Error> {
+    let boot_services = system::boot_services().ok_or(Error::NotStarted)?;
+
+    park_aps_at_with(boot_services, entry, argument, DEFAULT_AP_STACK_PAGES)
+}
@@ -1,6 +1,6 @@
 //! Handles UEFI status codes.
 
-use core::ops::Try;
+use core::{fmt, ops::Try};
 
 /// Represents the successful status.
 pub const SUCCESS: Status = Status(0);
@@ -25,9 +25,26 @@ pub const ERROR_BIT: usize = 1 << 15;
 #[cfg(target_pointer_width = "8")]
 pub const ERROR_BIT: usize = 1 << 7;
 
+/// The bit below the error bit, which marks a status code as reserved for use by OEMs.
+pub const OEM_BIT: usize = ERROR_BIT >> 1;
+
+/// The lowest of the OEM codes that are reserved for the errors defined by this crate.
+///
+/// The errors defined by this crate use the highest OEM codes, because the specification reserves
+/// all other codes. `Status::oem_error` and `Status::oem_warning` reject these codes, so that the
+/// statuses they create never decode as one of these errors.
+pub const RESERVED_OEM_CODES_START: usize = OVERSIZED_TRANSFER_CODE;
+
+/// The OEM code of `Error::WrongEventIndex`.
+const WRONG_EVENT_INDEX_CODE: usize = OEM_BIT - 1;
+/// The OEM code of `Error::UndersizedDescriptor`.
+const UNDERSIZED_DESCRIPTOR_CODE: usize = OEM_BIT - 2;
+/// The OEM code of `Error::OversizedTransfer`.
+const OVERSIZED_TRANSFER_CODE: usize = OEM_BIT - 3;
+
 /// Represents an error in a UEFI status code.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-#[repr(usize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// The operation completed successfully.
     Success,
@@ -95,7 +112,7 @@ pub enum Error {
     EndOfMedia,
     /// Error code 29 is not defined as of UEFI version 2.7A.
     Error29,
-    /// Error code 29 is not defined as of UEFI version 2.7A.
+    /// Error code 30 is not defined as of UEFI version 2.7A.
     Error30,
     /// The end of the file was reached.
     EndOfFile,
@@ -106,17 +123,52 @@ pub enum Error {
     /// status.
     CompromisedData,
     /// There is an address conflict address allocation.
-    Error34,
+    IpAddressConflict,
     /// A HTTP error occurred during the network operation.
     HttpError,
-    /// There is an unknown error.
-    Unknown,
+    /// The network is unreachable.
+    NetworkUnreachable,
+    /// The host is unreachable.
+    HostUnreachable,
+    /// The protocol is unreachable on the remote host.
+    ProtocolUnreachable,
+    /// The port is unreachable on the remote host.
+    PortUnreachable,
+    /// The connection was closed by the remote host.
+    ConnectionFin,
+    /// The connection was reset by the remote host.
+    ConnectionReset,
+    /// The connection was refused by the remote host.
+    ConnectionRefused,
+    /// An error code that is reserved by the specification, with the code without the error bit.
+    Unknown(usize),
+    /// An error defined by an OEM, with the code passed to `Status::oem_error`.
+    ///
+    /// The codes starting at `RESERVED_OEM_CODES_START` are used by the errors below instead.
+    Oem(usize),
+    /// The firmware returned an event index that does not refer to one of the events.
+    ///
+    /// This and the following errors are never returned by the firmware. They are returned
+    /// instead of panicking when the firmware violates the specification in the permissive
+    /// quirk mode and are converted to OEM error codes.
+    WrongEventIndex,
+    /// The firmware reported memory descriptors smaller than the standard says.
+    UndersizedDescriptor,
+    /// The firmware reported that it transferred more data than the buffer holds.
+    OversizedTransfer,
 }
 
 impl From<usize> for Error {
+    /// Converts a status code without the error bit into an error.
     fn from(value: usize) -> Self {
         use self::Error::*;
         match value {
+            value if value & OEM_BIT != 0 => match value & !OEM_BIT {
+                WRONG_EVENT_INDEX_CODE => WrongEventIndex,
+                UNDERSIZED_DESCRIPTOR_CODE => UndersizedDescriptor,
+                OVERSIZED_TRANSFER_CODE => OversizedTransfer,
+                code => Oem(code),
+            },
             0 => Success,
             1 => LoadError,
             2 => InvalidParameter,
@@ -151,16 +203,205 @@ impl From<usize> for Error {
             31 => EndOfFile,
             32 => InvalidLanguage,
             33 => CompromisedData,
-            34 => Error34,
+            34 => IpAddressConflict,
             35 => HttpError,
-            _ => Unknown,
+            100 => NetworkUnreachable,
+            101 => HostUnreachable,
+            102 => ProtocolUnreachable,
+            103 => PortUnreachable,
+            104 => ConnectionFin,
+            105 => ConnectionReset,
+            106 => ConnectionRefused,
+            code => Unknown(code),
+        }
+    }
+}
+
+impl Error {
+    /// The former name of `IpAddressConflict`.
+    #[deprecated(note = "renamed to `IpAddressConflict`")]
+    #[allow(non_upper_case_globals)]
+    pub const Error34: Error = Error::IpAddressConflict;
+
+    /// Returns the status code of the error without the error bit.
+    pub fn code(self) -> usize {
+        use self::Error::*;
+        match self {
+            Success => 0,
+            LoadError => 1,
+            InvalidParameter => 2,
+            Unsupported => 3,
+            BadBufferSize => 4,
+            BufferTooSmall => 5,
+            NotReady => 6,
+            DeviceError => 7,
+            WriteProtected => 8,
+            OutOfResources => 9,
+            VolumeCorrupted => 10,
+            VolumeFull => 11,
+            NoMedia => 12,
+            MediaChanged => 13,
+            NotFound => 14,
+            AccessDenied => 15,
+            NoResponse => 16,
+            NoMapping => 17,
+            Timeout => 18,
+            NotStarted => 19,
+            AlreadyStarted => 20,
+            Aborted => 21,
+            IcmpError => 22,
+            TftpError => 23,
+            ProtocolError => 24,
+            IncompatibleVersion => 25,
+            SecurityViolation => 26,
+            CrcError => 27,
+            EndOfMedia => 28,
+            Error29 => 29,
+            Error30 => 30,
+            EndOfFile => 31,
+            InvalidLanguage => 32,
+            CompromisedData => 33,
+            IpAddressConflict => 34,
+            HttpError => 35,
+            Unknown(code) => code,
+            WrongEventIndex => WRONG_EVENT_INDEX_CODE | OEM_BIT,
+            UndersizedDescriptor => UNDERSIZED_DESCRIPTOR_CODE | OEM_BIT,
+            OversizedTransfer => OVERSIZED_TRANSFER_CODE | OEM_BIT,
+            NetworkUnreachable => 100,
+            HostUnreachable => 101,
+            ProtocolUnreachable => 102,
+            PortUnreachable => 103,
+            ConnectionFin => 104,
+            ConnectionReset => 105,
+            ConnectionRefused => 106,
+            Oem(code) => code | OEM_BIT,
+        }
+    }
+
+    /// Returns the name of the status code as used in the specification.
+    pub fn name(self) -> &'static str {
+        use self::Error::*;
+        match self {
+            Success => "EFI_SUCCESS",
+            LoadError => "EFI_LOAD_ERROR",
+            InvalidParameter => "EFI_INVALID_PARAMETER",
+            Unsupported => "EFI_UNSUPPORTED",
+            BadBufferSize => "EFI_BAD_BUFFER_SIZE",
+            BufferTooSmall => "EFI_BUFFER_TOO_SMALL",
+            NotReady => "EFI_NOT_READY",
+            DeviceError => "EFI_DEVICE_ERROR",
+            WriteProtected => "EFI_WRITE_PROTECTED",
+            OutOfResources => "EFI_OUT_OF_RESOURCES",
+            VolumeCorrupted => "EFI_VOLUME_CORRUPTED",
+            VolumeFull => "EFI_VOLUME_FULL",
+            NoMedia => "EFI_NO_MEDIA",
+            MediaChanged => "EFI_MEDIA_CHANGED",
+            NotFound => "EFI_NOT_FOUND",
+            AccessDenied => "EFI_ACCESS_DENIED",
+            NoResponse => "EFI_NO_RESPONSE",
+            NoMapping => "EFI_NO_MAPPING",
+            Timeout => "EFI_TIMEOUT",
+            NotStarted => "EFI_NOT_STARTED",
+            AlreadyStarted => "EFI_ALREADY_STARTED",
+            Aborted => "EFI_ABORTED",
+            IcmpError => "EFI_ICMP_ERROR",
+            TftpError => "EFI_TFTP_ERROR",
+            ProtocolError => "EFI_PROTOCOL_ERROR",
+            IncompatibleVersion => "EFI_INCOMPATIBLE_VERSION",
+            SecurityViolation => "EFI_SECURITY_VIOLATION",
+            CrcError => "EFI_CRC_ERROR",
+            EndOfMedia => "EFI_END_OF_MEDIA",
+            Error29 => "EFI_ERROR_29",
+            Error30 => "EFI_ERROR_30",
+            EndOfFile => "EFI_END_OF_FILE",
+            InvalidLanguage => "EFI_INVALID_LANGUAGE",
+            CompromisedData => "EFI_COMPROMISED_DATA",
+            IpAddressConflict => "EFI_IP_ADDRESS_CONFLICT",
+            HttpError => "EFI_HTTP_ERROR",
+            NetworkUnreachable => "EFI_NETWORK_UNREACHABLE",
+            HostUnreachable => "EFI_HOST_UNREACHABLE",
+            ProtocolUnreachable => "EFI_PROTOCOL_UNREACHABLE",
+            PortUnreachable => "EFI_PORT_UNREACHABLE",
+            ConnectionFin => "EFI_CONNECTION_FIN",
+            ConnectionReset => "EFI_CONNECTION_RESET",
+            ConnectionRefused => "EFI_CONNECTION_REFUSED",
+            Unknown(_) => "EFI_UNKNOWN_ERROR",
+            Oem(_) => "EFI_OEM_ERROR",
+            WrongEventIndex => "NUEFIL_WRONG_EVENT_INDEX",
+            UndersizedDescriptor => "NUEFIL_UNDERSIZED_DESCRIPTOR",
+            OversizedTransfer => "NUEFIL_OVERSIZED_TRANSFER",
+        }
+    }
+
+    /// Returns a brief description of the status code.
+    pub fn description(self) -> &'static str {
+        use self::Error::*;
+        match self {
+            Success => "the operation completed successfully",
+            LoadError => "the image failed to load",
+            InvalidParameter => "a parameter was incorrect",
+            Unsupported => "the operation is not supported",
+            BadBufferSize => "the buffer was not the proper size for the request",
+            BufferTooSmall => "the buffer is not large enough to hold the requested data",
+            NotReady => "there is no data pending upon return",
+            DeviceError => "the physical device reported an error while attempting the operation",
+            WriteProtected => "the device cannot be written to",
+            OutOfResources => "a resource has run out",
+            VolumeCorrupted => "an inconsistency was detected on the file system",
+            VolumeFull => "there is no more space on the file system",
+            NoMedia => "the device does not contain any medium to perform the operation",
+            MediaChanged => "the medium in the device has changed since the last access",
+            NotFound => "the item was not found",
+            AccessDenied => "access was denied",
+            NoResponse => "the server was not found or did not respond to the request",
+            NoMapping => "a mapping to a device does not exist",
+            Timeout => "the timeout time expired",
+            NotStarted => "the protocol has not been started",
+            AlreadyStarted => "the protocol has already been started",
+            Aborted => "the operation was aborted",
+            IcmpError => "an ICMP error occurred during the network operation",
+            TftpError => "a TFTP error occurred during the network operation",
+            ProtocolError => "a protocol error occurred during the network operation",
+            IncompatibleVersion => "the function encountered an incompatible internal version",
+            SecurityViolation => "the function was not performed due to a security violation",
+            CrcError => "a CRC error was detected",
+            EndOfMedia => "beginning or end of media was reached",
+            Error29 => "undefined error code",
+            Error30 => "undefined error code",
+            EndOfFile => "the end of the file was reached",
+            InvalidLanguage => "the language specified was invalid",
+            CompromisedData => "the security status of the data is unknown or compromised",
+            IpAddressConflict => "there is an address conflict in the address allocation",
+            HttpError => "an HTTP error occurred during the network operation",
+            NetworkUnreachable => "the network is unreachable",
+            HostUnreachable => "the host is unreachable",
+            ProtocolUnreachable => "the protocol is unreachable on the remote host",
+            PortUnreachable => "the port is unreachable on the remote host",
+            ConnectionFin => "the connection was closed by the remote host",
+            ConnectionReset => "the connection was reset by the remote host",
+            ConnectionRefused => "the connection was refused by the remote host",
+            Unknown(_) => "unknown error code",
+            Oem(_) => "error code defined by an OEM",
+            WrongEventIndex => "the firmware returned an event index that is out of range",
+            UndersizedDescriptor => "the firmware reported an undersized memory descriptor",
+            OversizedTransfer => "the firmware transferred more data than the buffer holds",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unknown(code) => write!(f, "unknown error {}", code),
+            Error::Oem(code) => write!(f, "OEM error {:#x}", code),
+            _ => write!(f, "{}: {}", self.name(), self.description()),
         }
     }
 }
 
 /// Represents a warning in a UEFI status code.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-#[repr(usize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Warning {
     /// The operation completed successfully.
     Success,
@@ -178,14 +419,17 @@ pub enum Warning {
     FileSystem,
     /// The operation will be processed across a system reset.
     ResetRequired,
-    /// There is an unknown warning.
-    Unknown,
+    /// A warning code that is reserved by the specification, with the code.
+    Unknown(usize),
+    /// A warning defined by an OEM, with the code passed to `Status::oem_warning`.
+    Oem(usize),
 }
 
 impl From<usize> for Warning {
     fn from(value: usize) -> Self {
         use self::Warning::*;
         match value {
+            value if value & OEM_BIT != 0 => Oem(value & !OEM_BIT),
             0 => Success,
             1 => UnknownGlyph,
             2 => DeleteFailure,
@@ -194,7 +438,72 @@ impl From<usize> for Warning {
             5 => StaleData,
             6 => FileSystem,
             7 => ResetRequired,
-            _ => Unknown,
+            code => Unknown(code),
+        }
+    }
+}
+
+impl Warning {
+    /// Returns the status code of the warning.
+    pub fn code(self) -> usize {
+        use self::Warning::*;
+        match self {
+            Success => 0,
+            UnknownGlyph => 1,
+            DeleteFailure => 2,
+            WriteFailure => 3,
+            BufferTooSmall => 4,
+            StaleData => 5,
+            FileSystem => 6,
+            ResetRequired => 7,
+            Unknown(code) => code,
+            Oem(code) => code | OEM_BIT,
+        }
+    }
+
+    /// Returns the name of the status code as used in the specification.
+    pub fn name(self) -> &'static str {
+        use self::Warning::*;
+        match self {
+            Success => "EFI_SUCCESS",
+            UnknownGlyph => "EFI_WARN_UNKNOWN_GLYPH",
+            DeleteFailure => "EFI_WARN_DELETE_FAILURE",
+            WriteFailure => "EFI_WARN_WRITE_FAILURE",
+            BufferTooSmall => "EFI_WARN_BUFFER_TOO_SMALL",
+            StaleData => "EFI_WARN_STALE_DATA",
+            FileSystem => "EFI_WARN_FILE_SYSTEM",
+            ResetRequired => "EFI_WARN_RESET_REQUIRED",
+            Unknown(_) => "EFI_WARN_UNKNOWN",
+            Oem(_) => "EFI_WARN_OEM",
+        }
+    }
+
+    /// Returns a brief description of the status code.
+    pub fn description(self) -> &'static str {
+        use self::Warning::*;
+        match self {
+            Success => "the operation completed successfully",
+            UnknownGlyph => {
+                "the string contained characters that could not be rendered and were skipped"
+            }
+            DeleteFailure => "the handle was closed, but the file was not deleted",
+            WriteFailure => "the handle was closed, but the data was not flushed properly",
+            BufferTooSmall => "the data was truncated to the buffer size",
+            StaleData => "the data has not been updated within the timeframe set by local policy",
+            FileSystem => "the resulting buffer contains a UEFI-compliant file system",
+            ResetRequired => "the operation will be processed across a system reset",
+            Unknown(_) => "unknown warning code",
+            Oem(_) => "warning code defined by an OEM",
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Unknown(code) => write!(f, "unknown warning {}", code),
+            Warning::Oem(code) => write!(f, "OEM warning {:#x}", code),
+            _ => write!(f, "{}: {}", self.name(), self.description()),
         }
     }
 }
@@ -202,6 +511,201 @@ impl From<usize> for Warning {
 /// Represents a result with an UEFI status code as error.
 pub type Result<T> = ::core::result::Result<T, Error>;
 
+/// The successful outcome of a firmware call together with the warning it returned.
+///
+/// Most firmware calls return `Warning::Success`, but some can complete with a warning that the
+/// caller may want to inspect, such as `Warning::UnknownGlyph` when outputting text.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Completion<T> {
+    /// The value produced by the call.
+    value: T,
+    /// The warning returned by the call.
+    warning: Warning,
+}
+
+impl<T> Completion<T> {
+    /// Creates a new completion from a value and the warning that came with it.
+    pub fn new(value: T, warning: Warning) -> Self {
+        Completion { value, warning }
+    }
+
+    /// Returns the warning returned by the call.
+    pub fn warning(&self) -> Warning {
+        self.warning
+    }
+
+    /// Returns `true` if the call returned a warning other than `Warning::Success`.
+    pub fn is_warning(&self) -> bool {
+        self.warning != Warning::Success
+    }
+
+    /// Returns the value, discarding the warning.
+    pub fn value(self) -> T {
+        self.value
+    }
+
+    /// Splits the completion into the value and the warning.
+    pub fn split(self) -> (T, Warning) {
+        (self.value, self.warning)
+    }
+
+    /// Maps the value, keeping the warning.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Completion<U> {
+        Completion {
+            value: f(self.value),
+            warning: self.warning,
+        }
+    }
+}
+
+impl From<Warning> for Completion<()> {
+    fn from(warning: Warning) -> Self {
+        Completion::new((), warning)
+    }
+}
+
+/// A result that preserves the warning of a successful firmware call.
+pub type StatusResult<T> = ::core::result::Result<Completion<T>, Error>;
+
+/// Combinators for handling the warning stored in a `StatusResult`.
+pub trait StatusResultExt<T> {
+    /// Returns the value and discards any warning.
+    fn ignore_warning(self) -> ::core::result::Result<T, Error>;
+
+    /// Treats a warning like an error.
+    ///
+    /// The returned status is the original status code of the call, so it can be either an
+    /// error or a warning.
+    fn warn_as_err(self) -> ::core::result::Result<T, Status>;
+
+    /// Calls `f` with the warning if the call returned one and returns the value.
+    fn handle_warning<F: FnOnce(Warning)>(self, f: F) -> ::core::result::Result<T, Error>;
+}
+
+impl<T> StatusResultExt<T> for StatusResult<T> {
+    fn ignore_warning(self) -> ::core::result::Result<T, Error> {
+        self.map(Completion::value)
+    }
+
+    fn warn_as_err(self) -> ::core::result::Result<T, Status> {
+        match self {
+            Ok(completion) if completion.is_warning() => Err(Status(completion.warning.code())),
+            Ok(completion) => Ok(completion.value),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn handle_warning<F: FnOnce(Warning)>(self, f: F) -> ::core::result::Result<T, Error> {
+        self.map(|completion| {
+            if completion.is_warning() {
+                f(completion.warning);
+            }
+
+            completion.value
+        })
+    }
+}
+
+/// An error together with the firmware call that returned it.
+///
+/// This is useful when a wrapper chains multiple firmware calls, as the error code alone does not
+/// tell which of the calls failed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NuefilError {
+    /// The name of the firmware call that failed, as used in the specification.
+    operation: &'static str,
+    /// The error returned by the call.
+    error: Error,
+    /// A relevant parameter of the call, given by its name and value.
+    parameter: Option<(&'static str, usize)>,
+}
+
+impl NuefilError {
+    /// Creates a new error for the given operation.
+    pub fn new(operation: &'static str, error: Error) -> Self {
+        NuefilError {
+            operation,
+            error,
+            parameter: None,
+        }
+    }
+
+    /// Records a relevant parameter of the failed call.
+    pub fn with_parameter(mut self, name: &'static str, value: usize) -> Self {
+        self.parameter = Some((name, value));
+        self
+    }
+
+    /// Returns the name of the firmware call that failed.
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// Returns the error returned by the call.
+    pub fn error(&self) -> Error {
+        self.error
+    }
+
+    /// Returns the recorded parameter of the call, if any.
+    pub fn parameter(&self) -> Option<(&'static str, usize)> {
+        self.parameter
+    }
+}
+
+impl fmt::Display for NuefilError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} failed", self.operation)?;
+
+        if let Some((name, value)) = self.parameter {
+            write!(f, " ({} = {:#x})", name, value)?;
+        }
+
+        write!(f, ": {}", self.error)
+    }
+}
+
+impl From<NuefilError> for Error {
+    fn from(error: NuefilError) -> Self {
+        error.error
+    }
+}
+
+impl From<NuefilError> for Status {
+    fn from(error: NuefilError) -> Self {
+        error.error.into()
+    }
+}
+
+/// Adds the failing operation to the error of a result.
+pub trait ErrorExt<T> {
+    /// Records the name of the firmware call that returned the error.
+    fn context(self, operation: &'static str) -> ::core::result::Result<T, NuefilError>;
+
+    /// Records the name of the firmware call that returned the error and a relevant parameter.
+    fn context_with(
+        self,
+        operation: &'static str,
+        name: &'static str,
+        value: usize,
+    ) -> ::core::result::Result<T, NuefilError>;
+}
+
+impl<T> ErrorExt<T> for ::core::result::Result<T, Error> {
+    fn context(self, operation: &'static str) -> ::core::result::Result<T, NuefilError> {
+        self.map_err(|error| NuefilError::new(operation, error))
+    }
+
+    fn context_with(
+        self,
+        operation: &'static str,
+        name: &'static str,
+        value: usize,
+    ) -> ::core::result::Result<T, NuefilError> {
+        self.map_err(|error| NuefilError::new(operation, error).with_parameter(name, value))
+    }
+}
+
 /// Success, error, and warning codes returned by boot services and runtime services
 /// functions.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -214,6 +718,136 @@ impl Status {
     pub fn new(value: usize) -> Self {
         Status(value)
     }
+
+    /// Creates an OEM defined error status code.
+    ///
+    /// Returns `None` if `code` does not fit into the OEM range or is one of the codes starting at
+    /// `RESERVED_OEM_CODES_START`, which are reserved for the errors defined by this crate.
+    pub fn oem_error(code: usize) -> Option<Self> {
+        if code < RESERVED_OEM_CODES_START {
+            Some(Status(code | ERROR_BIT | OEM_BIT))
+        } else {
+            None
+        }
+    }
+
+    /// Creates an OEM defined warning status code.
+    ///
+    /// Returns `None` if `code` does not fit into the OEM range or is one of the codes starting at
+    /// `RESERVED_OEM_CODES_START`, which are reserved for the errors defined by this crate.
+    pub fn oem_warning(code: usize) -> Option<Self> {
+        if code < RESERVED_OEM_CODES_START {
+            Some(Status(code | OEM_BIT))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the raw value of the status code.
+    pub fn raw(self) -> usize {
+        self.0
+    }
+
+    /// Returns `true` if the status code is `SUCCESS`.
+    pub fn is_success(self) -> bool {
+        self == SUCCESS
+    }
+
+    /// Returns `true` if the status code is an error.
+    pub fn is_error(self) -> bool {
+        self.0 & ERROR_BIT != 0
+    }
+
+    /// Returns `true` if the status code is a warning.
+    pub fn is_warning(self) -> bool {
+        !self.is_error() && !self.is_success()
+    }
+
+    /// Returns `true` if the status code lies in the range reserved for OEMs.
+    pub fn is_oem(self) -> bool {
+        self.0 & OEM_BIT != 0
+    }
+
+    /// Returns the OEM defined code, if the status code lies in the range reserved for OEMs.
+    ///
+    /// The code is the value passed to `oem_error` or `oem_warning`.
+    pub fn oem_code(self) -> Option<usize> {
+        if self.is_oem() {
+            Some(self.0 & !(ERROR_BIT | OEM_BIT))
+        } else {
+            None
+        }
+    }
+
+    /// Converts the status into a `StatusResult` carrying the given value on success.
+    pub fn with_value<T>(self, value: T) -> StatusResult<T> {
+        let warning = self?;
+
+        Ok(Completion::new(value, warning))
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(code) = self.oem_code() {
+            let kind = if self.is_error() { "error" } else { "warning" };
+            write!(f, "OEM {} {:#x}", kind, code)?;
+        } else if self.is_error() {
+            write!(f, "{}", Error::from(self.0 & !ERROR_BIT))?;
+        } else {
+            write!(f, "{}", Warning::from(self.0))?;
+        }
+
+        write!(f, " ({:#x})", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Status {
+    fn format(&self, f: defmt::Formatter) {
+        if let Some(code) = self.oem_code() {
+            let kind = if self.is_error() { "error" } else { "warning" };
+            defmt::write!(f, "OEM {=str} {=usize:#x}", kind, code);
+        } else if self.is_error() {
+            defmt::write!(f, "{}", Error::from(self.0 & !ERROR_BIT));
+        } else {
+            defmt::write!(f, "{}", Warning::from(self.0));
+        }
+
+        defmt::write!(f, " ({=usize:#x})", self.0);
+    }
+}
+
+impl From<usize> for Status {
+    fn from(value: usize) -> Self {
+        Status(value)
+    }
+}
+
+impl From<Status> for usize {
+    fn from(status: Status) -> Self {
+        status.0
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<r_efi::efi::Status> for Status {
+    fn from(status: r_efi::efi::Status) -> Self {
+        Status(status.as_usize())
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<Status> for r_efi::efi::Status {
+    fn from(status: Status) -> Self {
+        r_efi::efi::Status::from_usize(status.0)
+    }
+}
+
+impl From<Error> for Status {
+    fn from(error: Error) -> Self {
+        Status(error.code() | ERROR_BIT)
+    }
 }
 
 impl Try for Status {
@@ -229,10 +863,10 @@ impl Try for Status {
     }
 
     fn from_error(v: Self::Error) -> Self {
-        Status(v as usize | ERROR_BIT)
+        Status(v.code() | ERROR_BIT)
     }
 
     fn from_ok(v: Self::Ok) -> Self {
-        Status(v as usize & !(ERROR_BIT))
+        Status(v.code() & !(ERROR_BIT))
     }
 }
@@ -0,0 +1,250 @@
+//! Null-terminated UCS-2 strings as used by the UEFI firmware.
+//!
+//! UEFI strings are made up of 16 bit characters that can only represent the basic multilingual
+//! plane of Unicode. The `ucs2!` macro converts string literals at compile time, so they can be
+//! passed to the firmware without any conversion at runtime.
+
+use core::{char, fmt};
+
+use crate::{status::Error, ucs2};
+
+/// A single UCS-2 character.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(transparent)]
+pub struct Char16(u16);
+
+impl Char16 {
+    /// The null character that terminates UCS-2 strings.
+    pub const NULL: Char16 = Char16(0);
+
+    /// Creates a character from a UCS-2 code unit.
+    ///
+    /// Returns `None` for surrogate code units, which are not valid UCS-2 characters.
+    pub fn from_u16(code_unit: u16) -> Option<Char16> {
+        match code_unit {
+            0xd800...0xdfff => None,
+            code_unit => Some(Char16(code_unit)),
+        }
+    }
+}
+
+impl From<Char16> for u16 {
+    fn from(character: Char16) -> u16 {
+        character.0
+    }
+}
+
+impl From<Char16> for char {
+    fn from(character: Char16) -> char {
+        // Surrogates are never stored in a `Char16`.
+        char::from_u32(u32::from(character.0)).unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+impl core::convert::TryFrom<char> for Char16 {
+    type Error = Error;
+
+    fn try_from(character: char) -> Result<Char16, Error> {
+        let code_point = character as u32;
+
+        if code_point <= 0xffff {
+            Ok(Char16(code_point as u16))
+        } else {
+            Err(Error::InvalidParameter)
+        }
+    }
+}
+
+/// A null-terminated UCS-2 string.
+#[derive(Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct CStr16([u16]);
+
+impl CStr16 {
+    /// Creates a string from UCS-2 code units that end with a null character.
+    ///
+    /// Returns `Error::InvalidParameter` if the code units are not terminated by the only null
+    /// character or contain surrogates.
+    pub fn from_ucs2_with_nul(code_units: &[u16]) -> Result<&CStr16, Error> {
+        match code_units.iter().position(|&code_unit| code_unit == 0) {
+            Some(nul) if nul + 1 == code_units.len() => (),
+            _ => return Err(Error::InvalidParameter),
+        }
+
+        if code_units
+            .iter()
+            .any(|&code_unit| Char16::from_u16(code_unit).is_none())
+        {
+            return Err(Error::InvalidParameter);
+        }
+
+        // This is safe, because the code units were validated above.
+        Ok(unsafe { CStr16::from_ucs2_unchecked(code_units) })
+    }
+
+    /// Creates a string from UCS-2 code units without validating them.
+    ///
+    /// # Safety
+    /// `code_units` must end with the only null character and must not contain surrogates.
+    pub unsafe fn from_ucs2_unchecked(code_units: &[u16]) -> &CStr16 {
+        &*(code_units as *const [u16] as *const CStr16)
+    }
+
+    /// Creates a string from a pointer to null-terminated UCS-2 code units.
+    ///
+    /// # Safety
+    /// `pointer` must point to valid code units that end with a null character and stay valid
+    /// for the lifetime `'a`.
+    pub unsafe fn from_ptr<'a>(pointer: *const u16) -> &'a CStr16 {
+        let len = ucs2::len_until_nul(pointer);
+
+        CStr16::from_ucs2_unchecked(core::slice::from_raw_parts(pointer, len + 1))
+    }
+
+    /// Returns a pointer to the code units that can be passed to the firmware.
+    pub fn as_ptr(&self) -> *const u16 {
+        self.0.as_ptr()
+    }
+
+    /// Returns the number of characters, excluding the null terminator.
+    pub fn len(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    /// Returns true if the string only consists of the null terminator.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the code units, excluding the null terminator.
+    pub fn to_ucs2(&self) -> &[u16] {
+        &self.0[..self.len()]
+    }
+
+    /// Returns the code units, including the null terminator.
+    pub fn to_ucs2_with_nul(&self) -> &[u16] {
+        &self.0
+    }
+
+    /// Returns the bytes of the code units in native byte order, including the null terminator.
+    pub fn as_bytes_with_nul(&self) -> &[u8] {
+        // This is safe, because every `u16` consists of two initialized bytes.
+        unsafe { core::slice::from_raw_parts(self.0.as_ptr() as *const u8, self.0.len() * 2) }
+    }
+
+    /// Returns an iterator over the characters of the string.
+    pub fn chars(&self) -> impl Iterator<Item = Char16> + '_ {
+        self.to_ucs2().iter().map(|&code_unit| Char16(code_unit))
+    }
+}
+
+impl fmt::Display for CStr16 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use core::fmt::Write;
+
+        for character in self.chars() {
+            f.write_char(char::from(character))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the number of UCS-2 characters needed to encode the string.
+///
+/// This is used by the `ucs2!` macro.
+#[doc(hidden)]
+pub const fn ucs2_len(string: &str) -> usize {
+    let bytes = string.as_bytes();
+    let mut len = 0;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        // Every byte that is not a continuation byte starts a new character.
+        if bytes[index] & 0xc0 != 0x80 {
+            len += 1;
+        }
+
+        index += 1;
+    }
+
+    len
+}
+
+/// Returns true if the string can be encoded as a null-terminated UCS-2 string.
+///
+/// This is used by the `ucs2!` macro.
+#[doc(hidden)]
+pub const fn is_ucs2(string: &str) -> bool {
+    let bytes = string.as_bytes();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        // Null characters would terminate the string and four byte sequences encode
+        // characters outside of the basic multilingual plane.
+        if bytes[index] == 0 || bytes[index] >= 0xf0 {
+            return false;
+        }
+
+        index += 1;
+    }
+
+    true
+}
+
+/// Decodes the UTF-8 character starting at `index` into a UCS-2 code unit.
+///
+/// Returns the code unit and the index of the next character.
+/// This is used by the `ucs2!` macro.
+#[doc(hidden)]
+pub const fn decode_ucs2_unit(bytes: &[u8], index: usize) -> (u16, usize) {
+    let first = bytes[index] as u16;
+
+    if first < 0x80 {
+        (first, index + 1)
+    } else if first < 0xe0 {
+        (
+            ((first & 0x1f) << 6) | (bytes[index + 1] as u16 & 0x3f),
+            index + 2,
+        )
+    } else {
+        (
+            ((first & 0x0f) << 12)
+                | ((bytes[index + 1] as u16 & 0x3f) << 6)
+                | (bytes[index + 2] as u16 & 0x3f),
+            index + 3,
+        )
+    }
+}
+
+/// Converts a string literal to a `&'static CStr16` at compile time.
+///
+/// Compilation fails if the string contains null characters or characters outside of the basic
+/// multilingual plane.
+#[macro_export]
+macro_rules! ucs2 {
+    ($string:expr) => {{
+        const UTF8: &str = $string;
+        const LEN: usize = $crate::string::ucs2_len(UTF8) + 1;
+        const _: () = [()][!$crate::string::is_ucs2(UTF8) as usize];
+        const UCS2: [u16; LEN] = {
+            let bytes = UTF8.as_bytes();
+            let mut ucs2 = [0u16; LEN];
+            let mut byte_index = 0;
+            let mut char_index = 0;
+
+            while byte_index < bytes.len() {
+                let (code_unit, next_index) = $crate::string::decode_ucs2_unit(bytes, byte_index);
+
+                ucs2[char_index] = code_unit;
+                byte_index = next_index;
+                char_index += 1;
+            }
+
+            ucs2
+        };
+
+        // This is safe, because the string was validated and converted above.
+        unsafe { $crate::string::CStr16::from_ucs2_unchecked(&UCS2) }
+    }};
+}
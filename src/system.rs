@@ -6,16 +6,97 @@
 //! ExitBootServices(), only the Hdr, FirmwareVendor, FirmwareRevision,
 //! RuntimeServices, NumberOfTableEntries, and ConfigurationTable fields are valid.
 
-use core::slice;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+use core::{
+    ffi::c_void,
+    ptr, slice,
+    sync::atomic::{AtomicPtr, Ordering},
+};
 
 use crate::{
-    boot::BootServices,
+    boot::{self, BootServices},
     config::ConfigurationTable,
+    guid::{Guid, MEMORY_ATTRIBUTES_TABLE_GUID},
+    memory::MemoryAttributesTable,
     runtime::RuntimeServices,
+    status::Error,
+    string::CStr16,
     text::{TextInput, TextOutput},
     Handle, TableHeader,
 };
 
+/// The system table registered with `init`.
+static GLOBAL_SYSTEM_TABLE: AtomicPtr<SystemTable> = AtomicPtr::new(ptr::null_mut());
+
+/// The image handle registered with `init`.
+static GLOBAL_IMAGE_HANDLE: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+
+/// Registers the image handle and system table passed to the entry point of the image.
+///
+/// This also registers the boot services with `boot::register_global`, so they are available
+/// through `boot_services` until boot services are exited.
+///
+/// Returns `Error::AlreadyStarted` if `init` was already called.
+pub fn init(image_handle: Handle, system_table: &'static SystemTable) -> Result<(), Error> {
+    GLOBAL_SYSTEM_TABLE
+        .compare_exchange(
+            ptr::null_mut(),
+            system_table as *const SystemTable as *mut SystemTable,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        )
+        .map_err(|_| Error::AlreadyStarted)?;
+
+    GLOBAL_IMAGE_HANDLE.store(image_handle.as_ptr(), Ordering::SeqCst);
+    boot::register_global(&*system_table.BootServices);
+
+    Ok(())
+}
+
+/// Returns the system table registered with `init`, if any.
+///
+/// The system table stays registered after boot services are exited, but only the fields that
+/// remain valid at that point may be used.
+pub fn system_table() -> Option<&'static SystemTable> {
+    // This is safe, because only `'static` references can be registered.
+    unsafe { GLOBAL_SYSTEM_TABLE.load(Ordering::SeqCst).as_ref() }
+}
+
+/// Returns the boot services, if they were registered and were not exited yet.
+pub fn boot_services() -> Option<&'static BootServices> {
+    boot::global()
+}
+
+/// Returns the image handle registered with `init`, if any.
+pub fn image_handle() -> Option<Handle> {
+    // This is safe, because only handles passed to the entry point can be registered.
+    unsafe { Handle::from_ptr(GLOBAL_IMAGE_HANDLE.load(Ordering::SeqCst)) }
+}
+
+/// The revision of the UEFI specification version 2.7.
+pub const SYSTEM_TABLE_REVISION_2_70: u32 = (2 << 16) | 70;
+/// The revision of the UEFI specification version 2.6.
+pub const SYSTEM_TABLE_REVISION_2_60: u32 = (2 << 16) | 60;
+/// The revision of the UEFI specification version 2.5.
+pub const SYSTEM_TABLE_REVISION_2_50: u32 = (2 << 16) | 50;
+/// The revision of the UEFI specification version 2.4.
+pub const SYSTEM_TABLE_REVISION_2_40: u32 = (2 << 16) | 40;
+/// The revision of the UEFI specification version 2.3.1.
+pub const SYSTEM_TABLE_REVISION_2_31: u32 = (2 << 16) | 31;
+/// The revision of the UEFI specification version 2.3.
+pub const SYSTEM_TABLE_REVISION_2_30: u32 = (2 << 16) | 30;
+/// The revision of the UEFI specification version 2.2.
+pub const SYSTEM_TABLE_REVISION_2_20: u32 = (2 << 16) | 20;
+/// The revision of the UEFI specification version 2.1.
+pub const SYSTEM_TABLE_REVISION_2_10: u32 = (2 << 16) | 10;
+/// The revision of the UEFI specification version 2.0.
+pub const SYSTEM_TABLE_REVISION_2_00: u32 = 2 << 16;
+/// The revision of the EFI specification version 1.10.
+pub const SYSTEM_TABLE_REVISION_1_10: u32 = (1 << 16) | 10;
+/// The revision of the EFI specification version 1.02.
+pub const SYSTEM_TABLE_REVISION_1_02: u32 = (1 << 16) | 2;
+
 /// Contains pointers to the runtime and boot services tables.
 #[repr(C)]
 pub struct SystemTable {
@@ -57,18 +138,44 @@ pub struct SystemTable {
     pub BootServices: &'static mut BootServices,
     /// The number of system configuration tables in the buffer
     /// `ConfigurationTables`.
-    Entries: usize,
+    pub(crate) Entries: usize,
     /// A pointer to the system configuration tables. The number of
     /// entries in the table is `Entries`.
-    ConfigurationTables: *const ConfigurationTable,
+    pub(crate) ConfigurationTables: *const ConfigurationTable,
 }
 
 impl SystemTable {
+    /// Returns the name of the vendor of the system firmware.
+    pub fn firmware_vendor(&self) -> &'static CStr16 {
+        // This is safe under the assumption that the firmware supplied a null terminated string.
+        unsafe { CStr16::from_ptr(self.FirmwareVendor) }
+    }
+
+    /// Returns the name of the vendor of the system firmware as a `String`.
+    #[cfg(feature = "alloc")]
+    pub fn firmware_vendor_string(&self) -> String {
+        self.firmware_vendor().to_string()
+    }
+
     /// Returns a slice to all the configuration tables available.
     pub fn config_tables(&self) -> &'static [ConfigurationTable] {
         // This is safe under the assumption that the firmware supplied valid values.
         unsafe { slice::from_raw_parts(self.ConfigurationTables, self.Entries) }
     }
+
+    /// Returns the configuration table with the given GUID, if it is available.
+    pub fn find_config_table(&self, guid: &Guid) -> Option<&'static ConfigurationTable> {
+        self.config_tables()
+            .iter()
+            .find(|table| table.VendorGuid == *guid)
+    }
+
+    /// Returns the memory attributes table, if the firmware provides one.
+    pub fn memory_attributes_table(&self) -> Option<&'static MemoryAttributesTable> {
+        self.find_config_table(&MEMORY_ATTRIBUTES_TABLE_GUID)
+            // This is safe under the assumption that the firmware supplied a valid table.
+            .map(|table| unsafe { &*(table.VendorTable as *const MemoryAttributesTable) })
+    }
 }
 
 // This is safe, since all the data in the system table is owned by the firmware.
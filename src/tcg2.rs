@@ -0,0 +1,161 @@
+//! Provides the TCG2 protocol, which is used to measure data into the PCRs of a TPM 2.0.
+//!
+//! Every measurement extends a PCR with the hash of the data and appends an event to the event
+//! log, which allows a verifier to replay the PCR values.
+
+use core::mem::size_of;
+
+use crate::{
+    boot::BootServices,
+    boxed::PoolBuffer,
+    guid::Guid,
+    guids,
+    memory::MemoryType,
+    protocol::Protocol,
+    status::{Error, Status},
+};
+
+/// The data is only extended into the PCR and no event is logged.
+pub const EFI_TCG2_EXTEND_ONLY: u64 = 0x0000_0000_0000_0001;
+/// The data is a PE/COFF image, which is hashed like an Authenticode signature.
+pub const PE_COFF_IMAGE: u64 = 0x0000_0000_0000_0010;
+
+/// The version of `EFI_TCG2_EVENT_HEADER`.
+pub const EFI_TCG2_EVENT_HEADER_VERSION: u16 = 1;
+
+/// An event that measures an image or data used by the boot loader.
+pub const EV_IPL: u32 = 0x0000_000d;
+/// An event that measures data that is identified by a tag.
+pub const EV_EVENT_TAG: u32 = 0x0000_0006;
+
+/// The size of `EFI_TCG2_EVENT` without the event data.
+const EVENT_SIZE: usize = size_of::<u32>() + EVENT_HEADER_SIZE;
+/// The size of `EFI_TCG2_EVENT_HEADER`.
+const EVENT_HEADER_SIZE: usize = size_of::<u32>() * 3 + size_of::<u16>();
+
+/// The version of a structure or protocol.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Tcg2Version {
+    pub Major: u8,
+    pub Minor: u8,
+}
+
+/// The capabilities of the TPM and the protocol.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Tcg2BootServiceCapability {
+    /// The size of the structure, which must be set by the caller.
+    pub Size: u8,
+    pub StructureVersion: Tcg2Version,
+    pub ProtocolVersion: Tcg2Version,
+    /// The hash algorithms supported by the TPM.
+    pub HashAlgorithmBitmap: u32,
+    /// The event log formats supported by the protocol.
+    pub SupportedEventLogs: u32,
+    /// Whether a TPM is present.
+    pub TPMPresentFlag: bool,
+    pub MaxCommandSize: u16,
+    pub MaxResponseSize: u16,
+    pub ManufacturerID: u32,
+    pub NumberOfPCRBanks: u32,
+    /// The hash algorithms of the PCR banks that are extended.
+    pub ActivePcrBanks: u32,
+}
+
+impl Default for Tcg2BootServiceCapability {
+    fn default() -> Tcg2BootServiceCapability {
+        Tcg2BootServiceCapability {
+            Size: size_of::<Tcg2BootServiceCapability>() as u8,
+            StructureVersion: Tcg2Version { Major: 0, Minor: 0 },
+            ProtocolVersion: Tcg2Version { Major: 0, Minor: 0 },
+            HashAlgorithmBitmap: 0,
+            SupportedEventLogs: 0,
+            TPMPresentFlag: false,
+            MaxCommandSize: 0,
+            MaxResponseSize: 0,
+            ManufacturerID: 0,
+            NumberOfPCRBanks: 0,
+            ActivePcrBanks: 0,
+        }
+    }
+}
+
+/// The TCG2 protocol.
+#[repr(C)]
+pub struct Tcg2 {
+    /// Returns the capabilities of the TPM and the protocol.
+    pub GetCapability: eficall! { fn(
+        &Tcg2,
+        ProtocolCapability: &mut Tcg2BootServiceCapability,
+    ) -> Status },
+    pub GetEventLog: usize,
+    /// Extends a PCR with the hash of the data and logs the event.
+    pub HashLogExtendEvent: eficall! { fn(
+        &Tcg2,
+        Flags: u64,
+        DataToHash: u64,
+        DataToHashLen: u64,
+        EfiTcgEvent: *const u8,
+    ) -> Status },
+    pub SubmitCommand: usize,
+    pub GetActivePcrBanks: usize,
+    pub SetActivePcrBanks: usize,
+    pub GetResultOfSetActivePcrBanks: usize,
+}
+
+unsafe impl Protocol for Tcg2 {
+    const GUID: Guid = guids::protocol::TCG2;
+}
+
+impl Tcg2 {
+    /// Returns the capabilities of the TPM and the protocol.
+    pub fn capability(&self) -> Result<Tcg2BootServiceCapability, Error> {
+        let mut capability = Tcg2BootServiceCapability::default();
+
+        (self.GetCapability)(self, &mut capability)?;
+
+        Ok(capability)
+    }
+
+    /// Extends `pcr` with the hash of `data` and logs an event of `event_type` with `event_data`.
+    ///
+    /// `flags` is a combination of `EFI_TCG2_EXTEND_ONLY` and `PE_COFF_IMAGE`.
+    pub fn hash_log_extend_event(
+        &self,
+        boot_services: &BootServices,
+        flags: u64,
+        data: &[u8],
+        pcr: u32,
+        event_type: u32,
+        event_data: &[u8],
+    ) -> Result<(), Error> {
+        let size = EVENT_SIZE + event_data.len();
+
+        if size > u32::max_value() as usize {
+            return Err(Error::InvalidParameter);
+        }
+
+        let mut event = PoolBuffer::new(boot_services, MemoryType::LoaderData, size)?;
+
+        event[0..4].copy_from_slice(&(size as u32).to_le_bytes());
+        event[4..8].copy_from_slice(&(EVENT_HEADER_SIZE as u32).to_le_bytes());
+        event[8..10].copy_from_slice(&EFI_TCG2_EVENT_HEADER_VERSION.to_le_bytes());
+        event[10..14].copy_from_slice(&pcr.to_le_bytes());
+        event[14..18].copy_from_slice(&event_type.to_le_bytes());
+        event[EVENT_SIZE..].copy_from_slice(event_data);
+
+        let status = (self.HashLogExtendEvent)(
+            self,
+            flags,
+            data.as_ptr() as u64,
+            data.len() as u64,
+            event.as_ptr(),
+        );
+
+        event.free(boot_services)?;
+        status?;
+
+        Ok(())
+    }
+}
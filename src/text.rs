@@ -3,16 +3,30 @@
 //! services environment. Also included here are the definitions of three console devices: one for input
 //! and one each for normal output and errors.
 
-use core::fmt;
+pub mod draw;
+pub mod log;
+pub mod mux;
+pub mod pager;
+pub mod snapshot;
+pub mod widgets;
+pub mod wrap;
+
+use bitflags::bitflags;
+use core::{fmt, time::Duration};
 
 use crate::{
-    status::{Error, Status, Warning},
+    boot::{OwnedEvent, TimerDelay},
+    guid::{Guid, SIMPLE_TEXT_INPUT_EX_GUID, SIMPLE_TEXT_INPUT_GUID, SIMPLE_TEXT_OUTPUT_GUID},
+    protocol::Protocol,
+    status::{Completion, Error, Status, StatusResult, Warning},
+    string::CStr16,
     system::SystemTable,
-    Event,
+    ucs2, Event,
 };
 
 /// Keystroke information for the key that was pressed.
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(C)]
 pub struct TextInputKey {
     /// If there is a pending keystroke, then ScanCode is the EFI scan code defined in
@@ -24,19 +38,271 @@ pub struct TextInputKey {
     pub UnicodeChar: u16,
 }
 
+/// The EFI scan codes of keys that do not represent a printable character.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ScanCode {
+    /// No scan code, the key is a printable character.
+    Null,
+    /// Move cursor up 1 row.
+    Up,
+    /// Move cursor down 1 row.
+    Down,
+    /// Move cursor right 1 column.
+    Right,
+    /// Move cursor left 1 column.
+    Left,
+    /// Home.
+    Home,
+    /// End.
+    End,
+    /// Insert.
+    Insert,
+    /// Delete.
+    Delete,
+    /// Page Up.
+    PageUp,
+    /// Page Down.
+    PageDown,
+    /// Function 1.
+    F1,
+    /// Function 2.
+    F2,
+    /// Function 3.
+    F3,
+    /// Function 4.
+    F4,
+    /// Function 5.
+    F5,
+    /// Function 6.
+    F6,
+    /// Function 7.
+    F7,
+    /// Function 8.
+    F8,
+    /// Function 9.
+    F9,
+    /// Function 10.
+    F10,
+    /// Function 11.
+    F11,
+    /// Function 12.
+    F12,
+    /// Escape.
+    Escape,
+    /// Function 13.
+    F13,
+    /// Function 14.
+    F14,
+    /// Function 15.
+    F15,
+    /// Function 16.
+    F16,
+    /// Function 17.
+    F17,
+    /// Function 18.
+    F18,
+    /// Function 19.
+    F19,
+    /// Function 20.
+    F20,
+    /// Function 21.
+    F21,
+    /// Function 22.
+    F22,
+    /// Function 23.
+    F23,
+    /// Function 24.
+    F24,
+    /// Mute.
+    Mute,
+    /// Volume Up.
+    VolumeUp,
+    /// Volume Down.
+    VolumeDown,
+    /// Brightness Up.
+    BrightnessUp,
+    /// Brightness Down.
+    BrightnessDown,
+    /// Suspend.
+    Suspend,
+    /// Hibernate.
+    Hibernate,
+    /// Toggle Display.
+    ToggleDisplay,
+    /// Recovery.
+    Recovery,
+    /// Eject.
+    Eject,
+    /// A scan code reserved for OEM use.
+    Oem(u16),
+    /// The scan code is not known.
+    Unknown(u16),
+}
+
+impl From<u16> for ScanCode {
+    fn from(scan_code: u16) -> ScanCode {
+        match scan_code {
+            0x00 => ScanCode::Null,
+            0x01 => ScanCode::Up,
+            0x02 => ScanCode::Down,
+            0x03 => ScanCode::Right,
+            0x04 => ScanCode::Left,
+            0x05 => ScanCode::Home,
+            0x06 => ScanCode::End,
+            0x07 => ScanCode::Insert,
+            0x08 => ScanCode::Delete,
+            0x09 => ScanCode::PageUp,
+            0x0a => ScanCode::PageDown,
+            0x0b => ScanCode::F1,
+            0x0c => ScanCode::F2,
+            0x0d => ScanCode::F3,
+            0x0e => ScanCode::F4,
+            0x0f => ScanCode::F5,
+            0x10 => ScanCode::F6,
+            0x11 => ScanCode::F7,
+            0x12 => ScanCode::F8,
+            0x13 => ScanCode::F9,
+            0x14 => ScanCode::F10,
+            0x15 => ScanCode::F11,
+            0x16 => ScanCode::F12,
+            0x17 => ScanCode::Escape,
+            0x68 => ScanCode::F13,
+            0x69 => ScanCode::F14,
+            0x6a => ScanCode::F15,
+            0x6b => ScanCode::F16,
+            0x6c => ScanCode::F17,
+            0x6d => ScanCode::F18,
+            0x6e => ScanCode::F19,
+            0x6f => ScanCode::F20,
+            0x70 => ScanCode::F21,
+            0x71 => ScanCode::F22,
+            0x72 => ScanCode::F23,
+            0x73 => ScanCode::F24,
+            0x7f => ScanCode::Mute,
+            0x80 => ScanCode::VolumeUp,
+            0x81 => ScanCode::VolumeDown,
+            0x100 => ScanCode::BrightnessUp,
+            0x101 => ScanCode::BrightnessDown,
+            0x102 => ScanCode::Suspend,
+            0x103 => ScanCode::Hibernate,
+            0x104 => ScanCode::ToggleDisplay,
+            0x105 => ScanCode::Recovery,
+            0x106 => ScanCode::Eject,
+            num @ 0x8000...0xffff => ScanCode::Oem(num),
+            num => ScanCode::Unknown(num),
+        }
+    }
+}
+
+impl From<ScanCode> for u16 {
+    fn from(scan_code: ScanCode) -> u16 {
+        match scan_code {
+            ScanCode::Null => 0x00,
+            ScanCode::Up => 0x01,
+            ScanCode::Down => 0x02,
+            ScanCode::Right => 0x03,
+            ScanCode::Left => 0x04,
+            ScanCode::Home => 0x05,
+            ScanCode::End => 0x06,
+            ScanCode::Insert => 0x07,
+            ScanCode::Delete => 0x08,
+            ScanCode::PageUp => 0x09,
+            ScanCode::PageDown => 0x0a,
+            ScanCode::F1 => 0x0b,
+            ScanCode::F2 => 0x0c,
+            ScanCode::F3 => 0x0d,
+            ScanCode::F4 => 0x0e,
+            ScanCode::F5 => 0x0f,
+            ScanCode::F6 => 0x10,
+            ScanCode::F7 => 0x11,
+            ScanCode::F8 => 0x12,
+            ScanCode::F9 => 0x13,
+            ScanCode::F10 => 0x14,
+            ScanCode::F11 => 0x15,
+            ScanCode::F12 => 0x16,
+            ScanCode::Escape => 0x17,
+            ScanCode::F13 => 0x68,
+            ScanCode::F14 => 0x69,
+            ScanCode::F15 => 0x6a,
+            ScanCode::F16 => 0x6b,
+            ScanCode::F17 => 0x6c,
+            ScanCode::F18 => 0x6d,
+            ScanCode::F19 => 0x6e,
+            ScanCode::F20 => 0x6f,
+            ScanCode::F21 => 0x70,
+            ScanCode::F22 => 0x71,
+            ScanCode::F23 => 0x72,
+            ScanCode::F24 => 0x73,
+            ScanCode::Mute => 0x7f,
+            ScanCode::VolumeUp => 0x80,
+            ScanCode::VolumeDown => 0x81,
+            ScanCode::BrightnessUp => 0x100,
+            ScanCode::BrightnessDown => 0x101,
+            ScanCode::Suspend => 0x102,
+            ScanCode::Hibernate => 0x103,
+            ScanCode::ToggleDisplay => 0x104,
+            ScanCode::Recovery => 0x105,
+            ScanCode::Eject => 0x106,
+            ScanCode::Oem(num) => num,
+            ScanCode::Unknown(num) => num,
+        }
+    }
+}
+
+/// A key that was pressed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Key {
+    /// A key that represents a character.
+    ///
+    /// Control characters like backspace (`'\u{8}'`) and carriage return (`'\r'`) are
+    /// reported as characters as well.
+    Char(char),
+    /// A key that does not represent a character.
+    Special(ScanCode),
+}
+
+impl From<TextInputKey> for Key {
+    fn from(key: TextInputKey) -> Key {
+        match ScanCode::from(key.ScanCode) {
+            ScanCode::Null => Key::Char(
+                core::char::from_u32(u32::from(key.UnicodeChar))
+                    .unwrap_or(core::char::REPLACEMENT_CHARACTER),
+            ),
+            scan_code => Key::Special(scan_code),
+        }
+    }
+}
+
+impl TextInputKey {
+    /// Returns the scan code of the key.
+    pub fn scan_code(&self) -> ScanCode {
+        ScanCode::from(self.ScanCode)
+    }
+
+    /// Returns the key that was pressed.
+    pub fn key(&self) -> Key {
+        Key::from(*self)
+    }
+}
+
 /// This protocol is used to obtain input from the ConsoleIn device. The EFI specification requires that
 /// the EFI_SIMPLE_TEXT_INPUT_PROTOCOL supports the same languages as the corresponding
 /// EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL.
 #[repr(C)]
 pub struct TextInput {
     /// Reset the ConsoleIn device.
-    pub Reset: extern "win64" fn(&TextInput, bool) -> Status,
+    pub Reset: eficall! { fn(&TextInput, bool) -> Status },
     /// Returns the next input character.
-    pub ReadKeyStroke: extern "win64" fn(&TextInput, &mut TextInputKey) -> Status,
+    pub ReadKeyStroke: eficall! { fn(&TextInput, &mut TextInputKey) -> Status },
     /// Event to use with EFI_BOOT_SERVICES.WaitForEvent() to wait for a key to be available.
     pub WaitForKey: Event,
 }
 
+unsafe impl Protocol for TextInput {
+    const GUID: Guid = SIMPLE_TEXT_INPUT_GUID;
+}
+
 impl TextInput {
     /// Reset the ConsoleOut device.
     pub fn reset(&self, extended_verification: bool) -> Result<(), Error> {
@@ -63,6 +329,331 @@ impl TextInput {
 
         self.try_read_key_stroke()
     }
+
+    /// Returns the next input character after waiting for it at most `timeout`.
+    ///
+    /// Returns `None` if no key was pressed in time.
+    pub fn read_key_timeout(
+        &self,
+        system_table: &'static SystemTable,
+        timeout: Duration,
+    ) -> Result<Option<TextInputKey>, Error> {
+        let boot_services = &system_table.BootServices;
+        let timer = OwnedEvent::timer(boot_services)?;
+
+        let result = (|| {
+            // The timer is set in units of 100 nanoseconds.
+            let trigger_time = (timeout.as_nanos() / 100).min(u128::from(u64::MAX)) as u64;
+            boot_services.set_timer(&timer, TimerDelay::Relative, trigger_time)?;
+
+            let events = [self.WaitForKey, timer.event()];
+
+            if *boot_services.wait_for_events(&events)? == timer.event() {
+                Ok(None)
+            } else {
+                self.try_read_key_stroke().map(Some)
+            }
+        })();
+
+        timer.close(boot_services)?;
+
+        result
+    }
+
+    /// Reads a line of input into `buffer` and returns it without the line terminator.
+    ///
+    /// The line is terminated by Enter. Backspace removes the last character. Characters that
+    /// do not fit into the buffer and keys that do not represent a character are ignored.
+    /// The input is echoed to `output` as specified by `echo`.
+    pub fn read_line<'a>(
+        &self,
+        system_table: &'static SystemTable,
+        output: &TextOutput,
+        buffer: &'a mut [u8],
+        echo: Echo,
+    ) -> Result<&'a str, Error> {
+        let mut len = 0;
+
+        loop {
+            let character = match self.read_key_stroke(system_table)?.key() {
+                Key::Char(character) => character,
+                Key::Special(_) => continue,
+            };
+
+            match character {
+                '\r' | '\n' => break,
+                '\u{8}' => {
+                    // This is safe, because only whole characters are written to the buffer.
+                    let line = unsafe { core::str::from_utf8_unchecked(&buffer[..len]) };
+
+                    if let Some(last) = line.chars().last() {
+                        len -= last.len_utf8();
+
+                        if echo != Echo::Hidden {
+                            output.output_string("\u{8} \u{8}")?;
+                        }
+                    }
+                }
+                character if character.is_control() => (),
+                character => {
+                    if len + character.len_utf8() > buffer.len() {
+                        continue;
+                    }
+
+                    character.encode_utf8(&mut buffer[len..]);
+                    len += character.len_utf8();
+
+                    let mut echo_buffer = [0; 4];
+
+                    match echo {
+                        Echo::Visible => {
+                            output.output_string(character.encode_utf8(&mut echo_buffer))?;
+                        }
+                        Echo::Masked(mask) => {
+                            output.output_string(mask.encode_utf8(&mut echo_buffer))?;
+                        }
+                        Echo::Hidden => (),
+                    }
+                }
+            }
+        }
+
+        if echo != Echo::Hidden {
+            output.output_string("\r\n")?;
+        }
+
+        // This is safe, because only whole characters are written to the buffer.
+        Ok(unsafe { core::str::from_utf8_unchecked(&buffer[..len]) })
+    }
+}
+
+/// Specifies how the input of `TextInput::read_line` is echoed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Echo {
+    /// The characters are echoed as typed.
+    Visible,
+    /// The given character is echoed instead of the typed characters, for example for passwords.
+    Masked(char),
+    /// Nothing is echoed.
+    Hidden,
+}
+
+bitflags! {
+    /// The state of the shift, control, alt and logo keys.
+    #[derive(Default)]
+    pub struct KeyShiftState: u32 {
+        /// The right shift key is pressed.
+        const RIGHT_SHIFT_PRESSED = 0x0000_0001;
+        /// The left shift key is pressed.
+        const LEFT_SHIFT_PRESSED = 0x0000_0002;
+        /// The right control key is pressed.
+        const RIGHT_CONTROL_PRESSED = 0x0000_0004;
+        /// The left control key is pressed.
+        const LEFT_CONTROL_PRESSED = 0x0000_0008;
+        /// The right alt key is pressed.
+        const RIGHT_ALT_PRESSED = 0x0000_0010;
+        /// The left alt key is pressed.
+        const LEFT_ALT_PRESSED = 0x0000_0020;
+        /// The right logo key is pressed.
+        const RIGHT_LOGO_PRESSED = 0x0000_0040;
+        /// The left logo key is pressed.
+        const LEFT_LOGO_PRESSED = 0x0000_0080;
+        /// The menu key is pressed.
+        const MENU_KEY_PRESSED = 0x0000_0100;
+        /// The system request key is pressed.
+        const SYS_REQ_PRESSED = 0x0000_0200;
+        /// The other flags are valid.
+        const SHIFT_STATE_VALID = 0x8000_0000;
+    }
+}
+
+bitflags! {
+    /// The state of the toggle keys.
+    #[derive(Default)]
+    pub struct KeyToggleState: u8 {
+        /// Scroll lock is active.
+        const SCROLL_LOCK_ACTIVE = 0x01;
+        /// Num lock is active.
+        const NUM_LOCK_ACTIVE = 0x02;
+        /// Caps lock is active.
+        const CAPS_LOCK_ACTIVE = 0x04;
+        /// Partial keystrokes, like a pressed shift key alone, are reported.
+        const KEY_STATE_EXPOSED = 0x40;
+        /// The other flags are valid.
+        const TOGGLE_STATE_VALID = 0x80;
+    }
+}
+
+/// The state of the modifier and toggle keys when a key was pressed.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct KeyState {
+    /// The state of the shift, control, alt and logo keys.
+    pub KeyShiftState: KeyShiftState,
+    /// The state of the toggle keys.
+    pub KeyToggleState: KeyToggleState,
+}
+
+/// Keystroke information for the key that was pressed, including the key state.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct KeyData {
+    /// The key that was pressed.
+    pub Key: TextInputKey,
+    /// The state of the modifier and toggle keys.
+    pub KeyState: KeyState,
+}
+
+/// The extended text input protocol, which also reports the state of the modifier keys.
+#[repr(C)]
+pub struct TextInputEx {
+    /// Reset the input device.
+    pub Reset: eficall! { fn(&TextInputEx, bool) -> Status },
+    /// Returns the next input character and the key state.
+    pub ReadKeyStrokeEx: eficall! { fn(&TextInputEx, &mut KeyData) -> Status },
+    /// Event to use with EFI_BOOT_SERVICES.WaitForEvent() to wait for a key to be available.
+    pub WaitForKeyEx: Event,
+    /// Sets the state of the toggle keys.
+    pub SetState: eficall! { fn(&TextInputEx, &KeyToggleState) -> Status },
+    /// Registers a notification function for a particular keystroke.
+    RegisterKeyNotify: eficall! { fn() },
+    /// Removes a registered notification function.
+    UnregisterKeyNotify: eficall! { fn() },
+}
+
+unsafe impl Protocol for TextInputEx {
+    const GUID: Guid = SIMPLE_TEXT_INPUT_EX_GUID;
+}
+
+impl TextInputEx {
+    /// Reset the input device.
+    pub fn reset(&self, extended_verification: bool) -> Result<(), Error> {
+        (self.Reset)(self, extended_verification)?;
+
+        Ok(())
+    }
+
+    /// Returns the next input character and the key state, if it exists.
+    pub fn try_read_key_stroke(&self) -> Result<KeyData, Error> {
+        let mut key_data = KeyData::default();
+
+        (self.ReadKeyStrokeEx)(self, &mut key_data)?;
+
+        Ok(key_data)
+    }
+
+    /// Sets the state of the toggle keys.
+    pub fn set_state(&self, state: KeyToggleState) -> Result<(), Error> {
+        (self.SetState)(self, &state)?;
+
+        Ok(())
+    }
+}
+
+/// A key that was pressed together with the state of the modifier keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KeyEvent {
+    /// The key that was pressed.
+    pub key: Key,
+    /// Whether a shift key was pressed.
+    pub shift: bool,
+    /// Whether a control key was pressed.
+    pub ctrl: bool,
+    /// Whether an alt key was pressed.
+    pub alt: bool,
+    /// Whether a logo key was pressed.
+    pub logo: bool,
+}
+
+impl From<TextInputKey> for KeyEvent {
+    fn from(key: TextInputKey) -> KeyEvent {
+        KeyEvent {
+            key: key.key(),
+            shift: false,
+            ctrl: false,
+            alt: false,
+            logo: false,
+        }
+    }
+}
+
+impl From<KeyData> for KeyEvent {
+    fn from(key_data: KeyData) -> KeyEvent {
+        let state = key_data.KeyState.KeyShiftState;
+
+        if !state.contains(KeyShiftState::SHIFT_STATE_VALID) {
+            return KeyEvent::from(key_data.Key);
+        }
+
+        KeyEvent {
+            key: key_data.Key.key(),
+            shift: state
+                .intersects(KeyShiftState::LEFT_SHIFT_PRESSED | KeyShiftState::RIGHT_SHIFT_PRESSED),
+            ctrl: state.intersects(
+                KeyShiftState::LEFT_CONTROL_PRESSED | KeyShiftState::RIGHT_CONTROL_PRESSED,
+            ),
+            alt: state
+                .intersects(KeyShiftState::LEFT_ALT_PRESSED | KeyShiftState::RIGHT_ALT_PRESSED),
+            logo: state
+                .intersects(KeyShiftState::LEFT_LOGO_PRESSED | KeyShiftState::RIGHT_LOGO_PRESSED),
+        }
+    }
+}
+
+/// Reads `KeyEvent`s from the console input device.
+///
+/// The extended text input protocol is used if the console supports it, otherwise the modifier
+/// keys are always reported as released.
+pub struct Keyboard {
+    /// The simple text input protocol of the console.
+    input: &'static TextInput,
+    /// The extended text input protocol of the console, if it is supported.
+    input_ex: Option<&'static TextInputEx>,
+}
+
+impl Keyboard {
+    /// Creates a keyboard for the console input device.
+    pub fn new(system_table: &'static SystemTable) -> Keyboard {
+        let input_ex = system_table
+            .BootServices
+            .handle_protocol::<TextInputEx>(system_table.ConsoleInHandle)
+            .ok();
+
+        Keyboard {
+            input: &*system_table.ConsoleIn,
+            input_ex,
+        }
+    }
+
+    /// Returns true if the state of the modifier keys is available.
+    pub fn has_modifiers(&self) -> bool {
+        self.input_ex.is_some()
+    }
+
+    /// Returns the event that is signaled when a key is available.
+    pub fn wait_event(&self) -> &Event {
+        match self.input_ex {
+            Some(input_ex) => &input_ex.WaitForKeyEx,
+            None => &self.input.WaitForKey,
+        }
+    }
+
+    /// Returns the next key event, if it exists.
+    pub fn try_read_key(&self) -> Result<KeyEvent, Error> {
+        match self.input_ex {
+            Some(input_ex) => input_ex.try_read_key_stroke().map(KeyEvent::from),
+            None => self.input.try_read_key_stroke().map(KeyEvent::from),
+        }
+    }
+
+    /// Returns the next key event after waiting for it.
+    pub fn read_key(&self, system_table: &'static SystemTable) -> Result<KeyEvent, Error> {
+        system_table
+            .BootServices
+            .wait_for_event(self.wait_event())?;
+
+        self.try_read_key()
+    }
 }
 
 /// The following data values in the SIMPLE_TEXT_OUTPUT_MODE interface are read-only and are
@@ -84,31 +675,55 @@ pub struct TextOutputMode {
     pub CursorVisible: bool,
 }
 
+/// Options that control how `TextOutput::output_string_with_options` outputs text.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OutputOptions {
+    /// The character that replaces characters the device cannot render.
+    ///
+    /// If this is `None`, the string is passed to the device unchanged.
+    pub fallback: Option<char>,
+}
+
+/// A saved cursor position and visibility.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CursorState {
+    /// The column of the cursor.
+    pub column: usize,
+    /// The row of the cursor.
+    pub row: usize,
+    /// Whether the cursor was visible.
+    pub visible: bool,
+}
+
 /// This protocol is used to control text-based output devices.
 #[repr(C)]
 pub struct TextOutput {
     /// Reset the ConsoleOut device.
-    pub Reset: extern "win64" fn(&TextOutput, bool) -> Status,
+    pub Reset: eficall! { fn(&TextOutput, bool) -> Status },
     /// Displays the string on the device at the current cursor location.
-    pub OutputString: extern "win64" fn(&TextOutput, *const u16) -> Status,
+    pub OutputString: eficall! { fn(&TextOutput, *const u16) -> Status },
     /// Tests to see if the ConsoleOut device supports this string.
-    pub TestString: extern "win64" fn(&TextOutput, *const u16) -> Status,
+    pub TestString: eficall! { fn(&TextOutput, *const u16) -> Status },
     /// Queries information concerning the output device’s supported text mode.
-    pub QueryMode: extern "win64" fn(&TextOutput, usize, &mut usize, &mut usize) -> Status,
+    pub QueryMode: eficall! { fn(&TextOutput, usize, &mut usize, &mut usize) -> Status },
     /// Sets the current mode of the output device.
-    pub SetMode: extern "win64" fn(&TextOutput, usize) -> Status,
+    pub SetMode: eficall! { fn(&TextOutput, usize) -> Status },
     /// Sets the foreground and background color of the text that is output.
-    pub SetAttribute: extern "win64" fn(&TextOutput, usize) -> Status,
+    pub SetAttribute: eficall! { fn(&TextOutput, usize) -> Status },
     /// Clears the screen with the currently set background color.
-    pub ClearScreen: extern "win64" fn(&TextOutput) -> Status,
+    pub ClearScreen: eficall! { fn(&TextOutput) -> Status },
     /// Sets the current cursor position.
-    pub SetCursorPosition: extern "win64" fn(&TextOutput, usize, usize) -> Status,
+    pub SetCursorPosition: eficall! { fn(&TextOutput, usize, usize) -> Status },
     /// Turns the visibility of the cursor on/off.
-    pub EnableCursor: extern "win64" fn(&TextOutput, bool) -> Status,
+    pub EnableCursor: eficall! { fn(&TextOutput, bool) -> Status },
     /// Reference to SIMPLE_TEXT_OUTPUT_MODE data.
     pub Mode: &'static TextOutputMode,
 }
 
+unsafe impl Protocol for TextOutput {
+    const GUID: Guid = SIMPLE_TEXT_OUTPUT_GUID;
+}
+
 impl TextOutput {
     /// Reset the ConsoleOut device.
     pub fn reset(&self, extended_verification: bool) -> Result<(), Error> {
@@ -118,8 +733,78 @@ impl TextOutput {
     }
 
     /// Displays the string on the device at the current cursor location.
-    pub fn output_string(&self, string: &str) -> Result<Warning, Error> {
-        with_utf16_str(string, |utf16| (self.OutputString)(self, utf16))
+    ///
+    /// The returned completion carries `Warning::UnknownGlyph` if the device skipped characters
+    /// it could not render.
+    pub fn output_string(&self, string: &str) -> StatusResult<()> {
+        Ok(with_utf16_str(string, |utf16| (self.OutputString)(self, utf16))?.into())
+    }
+
+    /// Displays the string on the device at the current cursor location using the given options.
+    ///
+    /// If a fallback character is given, the characters the device does not support according to
+    /// `TestString` are replaced by it.
+    pub fn output_string_with_options(
+        &self,
+        string: &str,
+        options: OutputOptions,
+    ) -> StatusResult<()> {
+        let fallback = match options.fallback {
+            Some(fallback) => fallback,
+            None => return self.output_string(string),
+        };
+
+        if self.test_string(string).is_ok() {
+            return self.output_string(string);
+        }
+
+        let mut buffer = [0u8; 256];
+        let mut len = 0;
+        let mut warning = Warning::Success;
+
+        let mut flush = |buffer: &[u8]| -> Result<(), Error> {
+            // This is safe, because only whole characters are written to the buffer.
+            let result = self.output_string(unsafe { core::str::from_utf8_unchecked(buffer) })?;
+
+            if warning == Warning::Success {
+                warning = result.warning();
+            }
+
+            Ok(())
+        };
+
+        for character in string.chars() {
+            let mut encoded = [0u8; 4];
+
+            // ASCII characters are assumed to be supported by every device.
+            let character = if character.is_ascii()
+                || self
+                    .test_string(character.encode_utf8(&mut encoded))
+                    .is_ok()
+            {
+                character
+            } else {
+                fallback
+            };
+
+            if len + character.len_utf8() > buffer.len() {
+                flush(&buffer[..len])?;
+                len = 0;
+            }
+
+            len += character.encode_utf8(&mut buffer[len..]).len();
+        }
+
+        flush(&buffer[..len])?;
+
+        Ok(Completion::new((), warning))
+    }
+
+    /// Displays the UCS-2 string on the device at the current cursor location.
+    ///
+    /// Unlike `output_string`, the string is passed to the firmware without any conversion.
+    pub fn output_ucs2(&self, string: &CStr16) -> StatusResult<()> {
+        (self.OutputString)(self, string.as_ptr()).with_value(())
     }
 
     /// Tests to see if the ConsoleOut device supports this string.
@@ -155,6 +840,34 @@ impl TextOutput {
         Ok(())
     }
 
+    /// Returns the foreground and background color of the text that is output.
+    pub fn attribute(&self) -> Color {
+        Color(self.Mode.Attribute as usize)
+    }
+
+    /// Runs `function` with the given color set and restores the previous color afterwards.
+    ///
+    /// The previous color is restored even if `function` fails.
+    pub fn with_color<F, R>(&self, color: Color, function: F) -> Result<R, Error>
+    where
+        F: FnOnce(&TextOutput) -> R,
+    {
+        let previous = self.attribute();
+
+        self.set_attribute(color)?;
+
+        let result = function(self);
+
+        self.set_attribute(previous)?;
+
+        Ok(result)
+    }
+
+    /// Displays the string in the given color and restores the previous color afterwards.
+    pub fn write_colored(&self, color: Color, string: &str) -> StatusResult<()> {
+        self.with_color(color, |output| output.output_string(string))?
+    }
+
     /// Clears the screen with the currently set background color.
     pub fn clear_screen(&self) -> Result<(), Error> {
         (self.ClearScreen)(self)?;
@@ -169,6 +882,64 @@ impl TextOutput {
         Ok(())
     }
 
+    /// Returns the current cursor position as column and row.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        (
+            self.Mode.CursorColumn as usize,
+            self.Mode.CursorRow as usize,
+        )
+    }
+
+    /// Returns the number of columns and rows of the current mode.
+    pub fn current_mode_size(&self) -> Result<(usize, usize), Error> {
+        self.query_mode(self.Mode.Mode as usize)
+    }
+
+    /// Returns the current cursor position and visibility, so it can be restored later.
+    pub fn save_cursor(&self) -> CursorState {
+        let (column, row) = self.cursor_position();
+
+        CursorState {
+            column,
+            row,
+            visible: self.Mode.CursorVisible,
+        }
+    }
+
+    /// Restores a cursor position and visibility returned by `save_cursor`.
+    pub fn restore_cursor(&self, state: CursorState) -> Result<(), Error> {
+        self.set_cursor_position(state.column, state.row)?;
+
+        if state.visible != self.Mode.CursorVisible {
+            self.enable_cursor(state.visible)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves the cursor relative to its current position.
+    ///
+    /// The new position is clamped to the screen of the current mode.
+    pub fn move_cursor(&self, columns: isize, rows: isize) -> Result<(), Error> {
+        let (column, row) = self.cursor_position();
+        let (max_columns, max_rows) = self.current_mode_size()?;
+
+        let clamp = |position: usize, delta: isize, max: usize| {
+            let position = if delta < 0 {
+                position.saturating_sub(delta.wrapping_neg() as usize)
+            } else {
+                position.saturating_add(delta as usize)
+            };
+
+            position.min(max.saturating_sub(1))
+        };
+
+        self.set_cursor_position(
+            clamp(column, columns, max_columns),
+            clamp(row, rows, max_rows),
+        )
+    }
+
     /// Turns the visibility of the cursor on/off.
     pub fn enable_cursor(&self, enable: bool) -> Result<(), Error> {
         (self.EnableCursor)(self, enable)?;
@@ -183,6 +954,73 @@ impl<'a> fmt::Write for &'a TextOutput {
     }
 }
 
+/// The number of UTF-16 code units a `BufferedWriter` can hold, including the null terminator.
+const BUFFERED_WRITER_CAPACITY: usize = 512;
+
+/// Collects formatted output and passes it to the firmware in few large calls.
+///
+/// The buffer is flushed at every newline, when it is full, when `flush` is called and when the
+/// writer is dropped.
+pub struct BufferedWriter<'a> {
+    /// The output device the text is written to.
+    output: &'a TextOutput,
+    /// The UTF-16 encoded text that was not written yet.
+    buffer: [u16; BUFFERED_WRITER_CAPACITY],
+    /// The number of code units in the buffer.
+    len: usize,
+}
+
+impl<'a> BufferedWriter<'a> {
+    /// Creates a buffered writer for the given output device.
+    pub fn new(output: &'a TextOutput) -> BufferedWriter<'a> {
+        BufferedWriter {
+            output,
+            buffer: [0; BUFFERED_WRITER_CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Writes the buffered text to the output device.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.len == 0 {
+            return Ok(());
+        }
+
+        self.buffer[self.len] = 0;
+        self.len = 0;
+
+        (self.output.OutputString)(self.output, self.buffer.as_ptr())?;
+
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Write for BufferedWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for character in s.chars() {
+            // Keep room for a surrogate pair and the null terminator.
+            if self.len + 3 > BUFFERED_WRITER_CAPACITY {
+                self.flush().map_err(|_| fmt::Error)?;
+            }
+
+            self.len += character.encode_utf16(&mut self.buffer[self.len..]).len();
+
+            if character == '\n' {
+                self.flush().map_err(|_| fmt::Error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Drop for BufferedWriter<'a> {
+    fn drop(&mut self) {
+        // There is no way to report the error here.
+        let _ = self.flush();
+    }
+}
+
 /// Executes the given function with the UTF16-encoded string.
 ///
 /// `function` will get a UTF16-encoded null-terminated string as its argument when its called.
@@ -194,48 +1032,46 @@ fn with_utf16_str<FunctionType>(string: &str, function: FunctionType) -> Result<
 where
     FunctionType: Fn(*const u16) -> Status,
 {
-    const BUFFER_SIZE: usize = 256;
-
-    let mut buffer = [0u16; BUFFER_SIZE];
+    let mut buffer = [0u16; 256];
+    let mut warning = Warning::Success;
 
-    let mut current_index = 0;
+    ucs2::encode_chunked(string, &mut buffer, |chunk| -> Result<(), Error> {
+        let chunk_warning = function(chunk.as_ptr())?;
 
-    let warning = Warning::Success;
-
-    // Flushes the buffer
-    let flush_buffer = |ref mut buffer: &mut [u16; BUFFER_SIZE],
-                        ref mut current_index,
-                        ref mut warning|
-     -> Result<(), Error> {
-        buffer[*current_index] = 0;
-        *current_index = 0;
-
-        if *warning == Warning::Success {
-            *warning = function(buffer.as_ptr())?;
-        } else {
-            function(buffer.as_ptr())?;
+        // Only the first warning is reported.
+        if warning == Warning::Success {
+            warning = chunk_warning;
         }
 
         Ok(())
-    };
+    })?;
 
-    for character in string.chars() {
-        // If there is not enough space in the buffer, flush it
-        if current_index + character.len_utf16() + 1 >= BUFFER_SIZE {
-            flush_buffer(&mut buffer, current_index, warning)?;
+    Ok(warning)
+}
+
+/// Passes `string` to `write` with every line feed translated to a carriage return line feed pair.
+///
+/// Consoles and serial terminals only start a new line at the beginning of the screen if the line
+/// feed is preceded by a carriage return.
+pub(crate) fn write_crlf<E, F>(string: &str, mut write: F) -> Result<(), E>
+where
+    F: FnMut(&str) -> Result<(), E>,
+{
+    for (index, line) in string.split('\n').enumerate() {
+        if index != 0 {
+            write("\r\n")?;
         }
 
-        character.encode_utf16(&mut buffer[current_index..]);
-        current_index += character.len_utf16();
+        if !line.is_empty() {
+            write(line)?;
+        }
     }
 
-    flush_buffer(&mut buffer, current_index, warning)?;
-
-    Ok(warning)
+    Ok(())
 }
 
 /// Represents color information for text.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(C)]
 pub struct Color(usize);
 
@@ -244,6 +1080,17 @@ impl Color {
     pub const fn new(foreground: ForegroundColor, background: BackgroundColor) -> Color {
         Color(foreground as usize | background as usize)
     }
+
+    /// Returns the index of the foreground color, which is the value of its `ForegroundColor`.
+    pub fn foreground_index(self) -> usize {
+        self.0 & 0x0f
+    }
+
+    /// Returns the index of the background color, which is the value of its `BackgroundColor`
+    /// shifted right by four bits.
+    pub fn background_index(self) -> usize {
+        (self.0 >> 4) & 0x07
+    }
 }
 
 /// Represents a foreground color for text.
@@ -0,0 +1,308 @@
+//! Helpers to draw frames, rules and tables on a text console.
+//!
+//! The UEFI specification requires consoles to support the Unicode box-drawing characters,
+//! but some firmware does not render them. If `TestString` rejects them, ASCII characters are
+//! used instead.
+//!
+//! All helpers draw line by line, starting at the current cursor position.
+
+use core::fmt::{self, Write};
+
+use super::{BufferedWriter, TextOutput};
+use crate::status::Error;
+
+/// The characters used to draw frames and tables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BoxChars {
+    /// A horizontal line.
+    pub horizontal: char,
+    /// A vertical line.
+    pub vertical: char,
+    /// The top left corner.
+    pub top_left: char,
+    /// The top right corner.
+    pub top_right: char,
+    /// The bottom left corner.
+    pub bottom_left: char,
+    /// The bottom right corner.
+    pub bottom_right: char,
+    /// A vertical line with a branch to the right.
+    pub left_tee: char,
+    /// A vertical line with a branch to the left.
+    pub right_tee: char,
+    /// A horizontal line with a branch downwards.
+    pub top_tee: char,
+    /// A horizontal line with a branch upwards.
+    pub bottom_tee: char,
+    /// Crossing horizontal and vertical lines.
+    pub cross: char,
+}
+
+/// The Unicode box-drawing characters.
+pub const UNICODE_BOX_CHARS: BoxChars = BoxChars {
+    horizontal: '\u{2500}',
+    vertical: '\u{2502}',
+    top_left: '\u{250c}',
+    top_right: '\u{2510}',
+    bottom_left: '\u{2514}',
+    bottom_right: '\u{2518}',
+    left_tee: '\u{251c}',
+    right_tee: '\u{2524}',
+    top_tee: '\u{252c}',
+    bottom_tee: '\u{2534}',
+    cross: '\u{253c}',
+};
+
+/// ASCII replacements for the box-drawing characters.
+pub const ASCII_BOX_CHARS: BoxChars = BoxChars {
+    horizontal: '-',
+    vertical: '|',
+    top_left: '+',
+    top_right: '+',
+    bottom_left: '+',
+    bottom_right: '+',
+    left_tee: '+',
+    right_tee: '+',
+    top_tee: '+',
+    bottom_tee: '+',
+    cross: '+',
+};
+
+/// The alignment of text within a fixed width.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Alignment {
+    /// The text starts at the left edge.
+    Left,
+    /// The text is centered.
+    Center,
+    /// The text ends at the right edge.
+    Right,
+}
+
+/// A column of a table.
+#[derive(Clone, Copy, Debug)]
+pub struct TableColumn<'a> {
+    /// The text in the header row.
+    pub header: &'a str,
+    /// The number of characters in the column, excluding the padding.
+    pub width: usize,
+    /// The alignment of the cells in the column.
+    pub alignment: Alignment,
+}
+
+/// Draws frames, rules and tables on an output device.
+pub struct BoxDrawer<'a> {
+    /// The output device to draw on.
+    output: &'a TextOutput,
+    /// The characters used for drawing.
+    chars: BoxChars,
+}
+
+impl<'a> BoxDrawer<'a> {
+    /// Creates a drawer for the given output device.
+    ///
+    /// The Unicode box-drawing characters are used if the device supports them.
+    pub fn new(output: &'a TextOutput) -> BoxDrawer<'a> {
+        let unicode = UNICODE_BOX_CHARS;
+        let mut test = [0u8; 11 * 3];
+        let mut len = 0;
+
+        for &character in &[
+            unicode.horizontal,
+            unicode.vertical,
+            unicode.top_left,
+            unicode.top_right,
+            unicode.bottom_left,
+            unicode.bottom_right,
+            unicode.left_tee,
+            unicode.right_tee,
+            unicode.top_tee,
+            unicode.bottom_tee,
+            unicode.cross,
+        ] {
+            len += character.encode_utf8(&mut test[len..]).len();
+        }
+
+        // This is safe, because only whole characters were written.
+        let test = unsafe { core::str::from_utf8_unchecked(&test[..len]) };
+
+        let chars = if output.test_string(test).is_ok() {
+            UNICODE_BOX_CHARS
+        } else {
+            ASCII_BOX_CHARS
+        };
+
+        BoxDrawer::with_chars(output, chars)
+    }
+
+    /// Creates a drawer that uses the given characters.
+    pub fn with_chars(output: &'a TextOutput, chars: BoxChars) -> BoxDrawer<'a> {
+        BoxDrawer { output, chars }
+    }
+
+    /// Returns the characters used for drawing.
+    pub fn chars(&self) -> BoxChars {
+        self.chars
+    }
+
+    /// Draws a horizontal rule of the given width.
+    pub fn rule(&self, width: usize) -> Result<(), Error> {
+        let mut writer = BufferedWriter::new(self.output);
+
+        write_line(&mut writer, self.chars.horizontal, width)
+            .and_then(|_| writer.write_str("\r\n"))
+            .map_err(|_| Error::DeviceError)?;
+
+        writer.flush()
+    }
+
+    /// Draws a frame around the given lines.
+    ///
+    /// `width` is the number of characters inside the frame. Longer lines are truncated.
+    pub fn frame(&self, width: usize, lines: &[&str]) -> Result<(), Error> {
+        let mut writer = BufferedWriter::new(self.output);
+        let chars = self.chars;
+
+        let result: fmt::Result = (|| {
+            writer.write_char(chars.top_left)?;
+            write_line(&mut writer, chars.horizontal, width)?;
+            writer.write_char(chars.top_right)?;
+            writer.write_str("\r\n")?;
+
+            for line in lines {
+                writer.write_char(chars.vertical)?;
+                write_aligned(&mut writer, line, width, Alignment::Left)?;
+                writer.write_char(chars.vertical)?;
+                writer.write_str("\r\n")?;
+            }
+
+            writer.write_char(chars.bottom_left)?;
+            write_line(&mut writer, chars.horizontal, width)?;
+            writer.write_char(chars.bottom_right)?;
+            writer.write_str("\r\n")
+        })();
+
+        result.map_err(|_| Error::DeviceError)?;
+
+        writer.flush()
+    }
+
+    /// Draws a table with the given columns and rows.
+    ///
+    /// Every row should contain one cell per column. Missing cells are left empty, additional
+    /// cells are ignored and cells that are too long are truncated.
+    pub fn table(&self, columns: &[TableColumn], rows: &[&[&str]]) -> Result<(), Error> {
+        let mut writer = BufferedWriter::new(self.output);
+        let chars = self.chars;
+
+        let result: fmt::Result = (|| {
+            write_separator(
+                &mut writer,
+                columns,
+                chars.horizontal,
+                chars.top_left,
+                chars.top_tee,
+                chars.top_right,
+            )?;
+
+            writer.write_char(chars.vertical)?;
+            for column in columns {
+                writer.write_char(' ')?;
+                write_aligned(&mut writer, column.header, column.width, Alignment::Center)?;
+                writer.write_char(' ')?;
+                writer.write_char(chars.vertical)?;
+            }
+            writer.write_str("\r\n")?;
+
+            write_separator(
+                &mut writer,
+                columns,
+                chars.horizontal,
+                chars.left_tee,
+                chars.cross,
+                chars.right_tee,
+            )?;
+
+            for row in rows {
+                writer.write_char(chars.vertical)?;
+                for (index, column) in columns.iter().enumerate() {
+                    let cell = row.get(index).cloned().unwrap_or("");
+
+                    writer.write_char(' ')?;
+                    write_aligned(&mut writer, cell, column.width, column.alignment)?;
+                    writer.write_char(' ')?;
+                    writer.write_char(chars.vertical)?;
+                }
+                writer.write_str("\r\n")?;
+            }
+
+            write_separator(
+                &mut writer,
+                columns,
+                chars.horizontal,
+                chars.bottom_left,
+                chars.bottom_tee,
+                chars.bottom_right,
+            )
+        })();
+
+        result.map_err(|_| Error::DeviceError)?;
+
+        writer.flush()
+    }
+}
+
+/// Writes `character` `count` times.
+fn write_line(writer: &mut dyn Write, character: char, count: usize) -> fmt::Result {
+    for _ in 0..count {
+        writer.write_char(character)?;
+    }
+
+    Ok(())
+}
+
+/// Writes `text` aligned within `width` characters, truncating it if necessary.
+fn write_aligned(
+    writer: &mut dyn Write,
+    text: &str,
+    width: usize,
+    alignment: Alignment,
+) -> fmt::Result {
+    let len = text.chars().count().min(width);
+    let padding = width - len;
+
+    let (before, after) = match alignment {
+        Alignment::Left => (0, padding),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::Right => (padding, 0),
+    };
+
+    write_line(writer, ' ', before)?;
+    for character in text.chars().take(len) {
+        writer.write_char(character)?;
+    }
+    write_line(writer, ' ', after)
+}
+
+/// Writes a horizontal separator line of a table.
+fn write_separator(
+    writer: &mut dyn Write,
+    columns: &[TableColumn],
+    horizontal: char,
+    left: char,
+    middle: char,
+    right: char,
+) -> fmt::Result {
+    writer.write_char(left)?;
+
+    for (index, column) in columns.iter().enumerate() {
+        if index > 0 {
+            writer.write_char(middle)?;
+        }
+
+        write_line(writer, horizontal, column.width + 2)?;
+    }
+
+    writer.write_char(right)?;
+    writer.write_str("\r\n")
+}
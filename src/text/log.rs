@@ -0,0 +1,106 @@
+//! Leveled log messages on the console with colored prefixes.
+//!
+//! Errors are written to the standard error console, everything else to the console output.
+//! The `info!`, `warn!` and `error!` macros take the system table as their first argument,
+//! followed by the format string and its arguments.
+
+use core::fmt::{self, Write};
+
+use super::{BackgroundColor, BufferedWriter, Color, ForegroundColor};
+use crate::{status::Error, system::SystemTable};
+
+/// The severity of a log message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Level {
+    /// Information about the normal operation.
+    Info,
+    /// Something unexpected that does not prevent the operation.
+    Warn,
+    /// Something that made the operation fail.
+    Error,
+}
+
+impl Level {
+    /// Returns the prefix of messages with this level.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Level::Info => "[INFO]",
+            Level::Warn => "[WARN]",
+            Level::Error => "[ERROR]",
+        }
+    }
+
+    /// Returns the color of the prefix of messages with this level.
+    pub fn color(self) -> Color {
+        let foreground = match self {
+            Level::Info => ForegroundColor::LightGreen,
+            Level::Warn => ForegroundColor::Yellow,
+            Level::Error => ForegroundColor::LightRed,
+        };
+
+        Color::new(foreground, BackgroundColor::Black)
+    }
+}
+
+/// Writes a log message with the given level, followed by a line break.
+pub fn log(
+    system_table: &'static SystemTable,
+    level: Level,
+    args: fmt::Arguments,
+) -> Result<(), Error> {
+    let output = match level {
+        Level::Error => &*system_table.ConsoleError,
+        Level::Info | Level::Warn => &*system_table.ConsoleOut,
+    };
+
+    output.write_colored(level.color(), level.tag())?;
+
+    let mut writer = BufferedWriter::new(output);
+
+    writer
+        .write_char(' ')
+        .and_then(|_| writer.write_fmt(args))
+        .and_then(|_| writer.write_str("\r\n"))
+        .map_err(|_| Error::DeviceError)?;
+
+    writer.flush()
+}
+
+/// Writes an informational log message.
+pub fn info(system_table: &'static SystemTable, args: fmt::Arguments) -> Result<(), Error> {
+    log(system_table, Level::Info, args)
+}
+
+/// Writes a warning log message.
+pub fn warn(system_table: &'static SystemTable, args: fmt::Arguments) -> Result<(), Error> {
+    log(system_table, Level::Warn, args)
+}
+
+/// Writes an error log message to the standard error console.
+pub fn error(system_table: &'static SystemTable, args: fmt::Arguments) -> Result<(), Error> {
+    log(system_table, Level::Error, args)
+}
+
+/// Writes an informational log message to the console.
+#[macro_export]
+macro_rules! info {
+    ($system_table:expr, $($arg:tt)*) => {
+        $crate::text::log::info($system_table, format_args!($($arg)*))
+    };
+}
+
+/// Writes a warning log message to the console.
+#[macro_export]
+macro_rules! warn {
+    ($system_table:expr, $($arg:tt)*) => {
+        $crate::text::log::warn($system_table, format_args!($($arg)*))
+    };
+}
+
+/// Writes an error log message to the standard error console.
+#[macro_export]
+macro_rules! error {
+    ($system_table:expr, $($arg:tt)*) => {
+        $crate::text::log::error($system_table, format_args!($($arg)*))
+    };
+}
@@ -0,0 +1,274 @@
+//! Writes the same output to multiple sinks at once.
+//!
+//! A `MuxWriter` passes everything written to it on to a console, a serial device and an
+//! in-memory `RingBuffer`, so a single log message reaches the screen, the serial console and a
+//! buffer that can be retrieved later, for example to include it in a crash dump. Every sink has
+//! a minimum level and only receives messages of at least that level.
+
+use core::fmt::{self, Write};
+
+use super::{log::Level, TextOutput};
+use crate::{
+    serial::SerialLogger,
+    status::{Error, StatusResultExt},
+};
+
+/// The number of sinks a `MuxWriter` can hold.
+pub const MAX_SINKS: usize = 4;
+
+/// Keeps the most recent output in a fixed buffer, overwriting the oldest output when full.
+pub struct RingBuffer<'a> {
+    /// The memory the output is stored in.
+    buffer: &'a mut [u8],
+    /// The index of the oldest byte in the buffer.
+    start: usize,
+    /// The number of bytes stored in the buffer.
+    len: usize,
+    /// The number of bytes that were overwritten.
+    overwritten: u64,
+}
+
+impl<'a> RingBuffer<'a> {
+    /// Creates an empty ring buffer that stores its contents in `buffer`.
+    pub fn new(buffer: &'a mut [u8]) -> RingBuffer<'a> {
+        RingBuffer {
+            buffer,
+            start: 0,
+            len: 0,
+            overwritten: 0,
+        }
+    }
+
+    /// Returns the number of bytes stored in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if nothing is stored in the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bytes the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of bytes that were overwritten by newer output.
+    pub fn overwritten(&self) -> u64 {
+        self.overwritten
+    }
+
+    /// Appends `data`, overwriting the oldest bytes if the buffer is full.
+    pub fn write_bytes(&mut self, data: &[u8]) {
+        let capacity = self.buffer.len();
+
+        for &byte in data {
+            if capacity == 0 {
+                self.overwritten += 1;
+                continue;
+            }
+
+            if self.len == capacity {
+                self.buffer[self.start] = byte;
+                self.start = (self.start + 1) % capacity;
+                self.overwritten += 1;
+            } else {
+                self.buffer[(self.start + self.len) % capacity] = byte;
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Returns the stored bytes from oldest to newest, split in two where the buffer wraps.
+    ///
+    /// The first byte may be in the middle of a UTF-8 encoded character if older output was
+    /// overwritten.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let end = self.start + self.len;
+
+        if end <= self.buffer.len() {
+            (&self.buffer[self.start..end], &[])
+        } else {
+            (
+                &self.buffer[self.start..],
+                &self.buffer[..end - self.buffer.len()],
+            )
+        }
+    }
+
+    /// Returns an iterator over the stored bytes from oldest to newest.
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        let (first, second) = self.as_slices();
+
+        first.iter().chain(second.iter()).cloned()
+    }
+
+    /// Removes all stored bytes.
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+}
+
+impl<'a> fmt::Write for RingBuffer<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+
+        Ok(())
+    }
+}
+
+/// A destination of the output of a `MuxWriter`.
+pub enum Sink<'a> {
+    /// A text console, on which line feeds are translated to carriage return line feed pairs.
+    Console(&'a TextOutput),
+    /// A serial device.
+    Serial(SerialLogger<'a>),
+    /// An in-memory buffer, which can be read with `MuxWriter::memory`.
+    Memory(RingBuffer<'a>),
+}
+
+impl<'a> Sink<'a> {
+    /// Writes `string` to the sink.
+    fn write_text(&mut self, string: &str) -> Result<(), Error> {
+        match self {
+            Sink::Console(output) => {
+                super::write_crlf(string, |text| output.output_string(text).ignore_warning())
+            }
+            Sink::Serial(serial) => serial.write_str(string).map_err(|_| Error::DeviceError),
+            Sink::Memory(buffer) => {
+                buffer.write_bytes(string.as_bytes());
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes the tag of `level` to the sink, colored on consoles.
+    fn write_tag(&mut self, level: Level) -> Result<(), Error> {
+        match self {
+            Sink::Console(output) => output
+                .write_colored(level.color(), level.tag())
+                .ignore_warning(),
+            _ => self.write_text(level.tag()),
+        }
+    }
+}
+
+/// Writes the same output to multiple sinks, filtered by level.
+///
+/// Text written through `fmt::Write` has the level set with `set_level`, which is
+/// `Level::Info` initially. If writing to one sink fails, the other sinks are still written to.
+pub struct MuxWriter<'a> {
+    /// The sinks together with the minimum level of the output they receive.
+    sinks: [Option<(Sink<'a>, Level)>; MAX_SINKS],
+    /// The level of the output written through `fmt::Write`.
+    level: Level,
+}
+
+impl<'a> MuxWriter<'a> {
+    /// Creates a writer without any sinks.
+    pub fn new() -> MuxWriter<'a> {
+        MuxWriter {
+            sinks: [None, None, None, None],
+            level: Level::Info,
+        }
+    }
+
+    /// Adds a sink that receives output of at least `min_level`.
+    ///
+    /// Returns `Error::OutOfResources` if the writer already holds `MAX_SINKS` sinks.
+    pub fn add_sink(&mut self, sink: Sink<'a>, min_level: Level) -> Result<(), Error> {
+        let slot = self
+            .sinks
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(Error::OutOfResources)?;
+
+        *slot = Some((sink, min_level));
+
+        Ok(())
+    }
+
+    /// Returns the first in-memory buffer sink, if there is one.
+    pub fn memory(&self) -> Option<&RingBuffer<'a>> {
+        self.sinks.iter().find_map(|slot| match slot {
+            Some((Sink::Memory(buffer), _)) => Some(buffer),
+            _ => None,
+        })
+    }
+
+    /// Returns the first in-memory buffer sink mutably, if there is one.
+    pub fn memory_mut(&mut self) -> Option<&mut RingBuffer<'a>> {
+        self.sinks.iter_mut().find_map(|slot| match slot {
+            Some((Sink::Memory(buffer), _)) => Some(buffer),
+            _ => None,
+        })
+    }
+
+    /// Returns the level of the output written through `fmt::Write`.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Sets the level of the output written through `fmt::Write`.
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+    }
+
+    /// Writes a log message with the given level, followed by a line break.
+    ///
+    /// Returns the first error that occurred, after all sinks were written to.
+    pub fn log(&mut self, level: Level, args: fmt::Arguments) -> Result<(), Error> {
+        let mut result = Ok(());
+
+        for (sink, _) in self.sinks_for(level) {
+            let written = sink.write_tag(level).and_then(|_| {
+                MuxSinkWriter(sink)
+                    .write_fmt(format_args!(" {}\n", args))
+                    .map_err(|_| Error::DeviceError)
+            });
+
+            result = result.and(written);
+        }
+
+        result
+    }
+
+    /// Returns the sinks that receive output of the given level.
+    fn sinks_for(&mut self, level: Level) -> impl Iterator<Item = &mut (Sink<'a>, Level)> {
+        self.sinks
+            .iter_mut()
+            .filter_map(|slot| slot.as_mut())
+            .filter(move |(_, min_level)| level >= *min_level)
+    }
+}
+
+impl<'a> Default for MuxWriter<'a> {
+    fn default() -> MuxWriter<'a> {
+        MuxWriter::new()
+    }
+}
+
+impl<'a> fmt::Write for MuxWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let level = self.level;
+        let mut result = Ok(());
+
+        for (sink, _) in self.sinks_for(level) {
+            result = result.and(sink.write_text(s).map_err(|_| fmt::Error));
+        }
+
+        result
+    }
+}
+
+/// Formats directly into a single sink.
+struct MuxSinkWriter<'a, 'b>(&'b mut Sink<'a>);
+
+impl<'a, 'b> fmt::Write for MuxSinkWriter<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_text(s).map_err(|_| fmt::Error)
+    }
+}
@@ -0,0 +1,125 @@
+//! A writer that pauses output whenever the screen is full.
+
+use core::fmt;
+
+use super::{Key, TextOutput};
+use crate::{status::Error, system::SystemTable};
+
+/// The prompt shown when the screen is full.
+const PROMPT: &str = "-- more --";
+
+/// Writes to the console output and waits for a key press whenever the screen is full.
+///
+/// Any key continues the output, `q` aborts it. After the output was aborted, all further
+/// writes fail, so formatting loops stop early.
+pub struct Pager {
+    /// The system table used to wait for key presses.
+    system_table: &'static SystemTable,
+    /// The console output device.
+    output: &'static TextOutput,
+    /// The number of columns of the screen.
+    columns: usize,
+    /// The number of rows of the screen.
+    rows: usize,
+    /// The column the next character is written to.
+    column: usize,
+    /// The number of rows written since the last pause.
+    written_rows: usize,
+    /// Whether the user aborted the output.
+    aborted: bool,
+}
+
+impl Pager {
+    /// Creates a pager for the console output device of the given system table.
+    pub fn new(system_table: &'static SystemTable) -> Result<Pager, Error> {
+        let output = &*system_table.ConsoleOut;
+        let (columns, rows) = output.current_mode_size()?;
+        let (column, _) = output.cursor_position();
+
+        Ok(Pager {
+            system_table,
+            output,
+            columns: columns.max(1),
+            rows: rows.max(2),
+            column,
+            written_rows: 0,
+            aborted: false,
+        })
+    }
+
+    /// Returns true if the user aborted the output.
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    /// Shows the prompt and waits for a key press.
+    fn pause(&mut self) -> Result<(), Error> {
+        self.output.output_string(PROMPT)?;
+
+        let key = self
+            .system_table
+            .ConsoleIn
+            .read_key_stroke(self.system_table)?
+            .key();
+
+        // Remove the prompt again.
+        self.output.output_string("\r")?;
+        for _ in 0..PROMPT.len() {
+            self.output.output_string(" ")?;
+        }
+        self.output.output_string("\r")?;
+
+        self.written_rows = 0;
+        self.aborted = key == Key::Char('q') || key == Key::Char('Q');
+
+        Ok(())
+    }
+}
+
+impl fmt::Write for Pager {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.aborted {
+            return Err(fmt::Error);
+        }
+
+        let mut start = 0;
+
+        for (index, character) in s.char_indices() {
+            // The last row is kept free for the prompt.
+            if self.written_rows + 1 >= self.rows {
+                self.output
+                    .output_string(&s[start..index])
+                    .map_err(|_| fmt::Error)?;
+                start = index;
+
+                self.pause().map_err(|_| fmt::Error)?;
+
+                if self.aborted {
+                    return Err(fmt::Error);
+                }
+            }
+
+            match character {
+                '\n' => {
+                    self.written_rows += 1;
+                    self.column = 0;
+                }
+                '\r' => self.column = 0,
+                _ => {
+                    self.column += 1;
+
+                    if self.column >= self.columns {
+                        self.written_rows += 1;
+                        self.column = 0;
+                    }
+                }
+            }
+        }
+
+        self.output
+            .output_string(&s[start..])
+            .map_err(|_| fmt::Error)?;
+
+        Ok(())
+    }
+}
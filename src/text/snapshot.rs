@@ -0,0 +1,248 @@
+//! An off-screen copy of the text on the console.
+//!
+//! A `ScreenBuffer` records text and colors like a console would display them and can redraw
+//! them later, for example to switch between a log view and a menu.
+
+use core::fmt;
+
+use super::{Color, TextOutput};
+use crate::status::Error;
+
+/// A single character on the screen.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScreenCell {
+    /// The character in the cell.
+    pub character: char,
+    /// The color of the character.
+    pub attribute: Color,
+}
+
+/// Records text written to a screen of a fixed size.
+///
+/// The cells are stored in a buffer provided by the caller, which needs room for
+/// `columns * rows` cells. When the last row is full, the contents scroll up like on a console.
+pub struct ScreenBuffer<'a> {
+    /// The cells of the screen, row by row.
+    cells: &'a mut [ScreenCell],
+    /// The number of columns of the screen.
+    columns: usize,
+    /// The number of rows of the screen.
+    rows: usize,
+    /// The column the next character is written to.
+    column: usize,
+    /// The row the next character is written to.
+    row: usize,
+    /// The color of the characters that are written.
+    attribute: Color,
+}
+
+impl<'a> ScreenBuffer<'a> {
+    /// Creates an empty screen buffer with the given size and color.
+    ///
+    /// Returns `Error::BufferTooSmall` if `cells` cannot hold `columns * rows` cells.
+    pub fn new(
+        cells: &'a mut [ScreenCell],
+        columns: usize,
+        rows: usize,
+        attribute: Color,
+    ) -> Result<ScreenBuffer<'a>, Error> {
+        let len = columns.checked_mul(rows).ok_or(Error::BufferTooSmall)?;
+
+        if columns == 0 || rows == 0 || cells.len() < len {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let mut buffer = ScreenBuffer {
+            cells: &mut cells[..len],
+            columns,
+            rows,
+            column: 0,
+            row: 0,
+            attribute,
+        };
+
+        buffer.clear();
+
+        Ok(buffer)
+    }
+
+    /// Creates an empty screen buffer with the size and color of the current mode of the output
+    /// device.
+    pub fn for_output(
+        cells: &'a mut [ScreenCell],
+        output: &TextOutput,
+    ) -> Result<ScreenBuffer<'a>, Error> {
+        let (columns, rows) = output.current_mode_size()?;
+
+        ScreenBuffer::new(cells, columns, rows, output.attribute())
+    }
+
+    /// Clears the screen with the current color and moves the cursor to the top left.
+    pub fn clear(&mut self) {
+        let blank = self.blank();
+
+        for cell in self.cells.iter_mut() {
+            *cell = blank;
+        }
+
+        self.column = 0;
+        self.row = 0;
+    }
+
+    /// Sets the color of the characters that are written.
+    pub fn set_attribute(&mut self, attribute: Color) {
+        self.attribute = attribute;
+    }
+
+    /// Returns the cell at the given position.
+    pub fn cell(&self, column: usize, row: usize) -> Option<&ScreenCell> {
+        if column < self.columns && row < self.rows {
+            self.cells.get(row * self.columns + column)
+        } else {
+            None
+        }
+    }
+
+    /// Records a single character.
+    pub fn put_char(&mut self, character: char) {
+        match character {
+            '\r' => self.column = 0,
+            '\n' => self.next_row(),
+            '\u{8}' => self.column = self.column.saturating_sub(1),
+            character => {
+                if self.column >= self.columns {
+                    self.column = 0;
+                    self.next_row();
+                }
+
+                self.cells[self.row * self.columns + self.column] = ScreenCell {
+                    character,
+                    attribute: self.attribute,
+                };
+                self.column += 1;
+            }
+        }
+    }
+
+    /// Clears the output device and draws the recorded text on it.
+    ///
+    /// The cursor is placed where the next recorded character would be written.
+    pub fn redraw(&self, output: &TextOutput) -> Result<(), Error> {
+        let previous_attribute = output.attribute();
+
+        output.clear_screen()?;
+
+        for row in 0..self.rows {
+            let cells = &self.cells[row * self.columns..(row + 1) * self.columns];
+            // Writing to the last cell of the screen would scroll it.
+            let cells = if row + 1 == self.rows {
+                &cells[..self.columns - 1]
+            } else {
+                cells
+            };
+
+            output.set_cursor_position(0, row)?;
+            self.draw_cells(output, cells)?;
+        }
+
+        output.set_attribute(previous_attribute)?;
+        output.set_cursor_position(self.column.min(self.columns - 1), self.row)?;
+
+        Ok(())
+    }
+
+    /// Returns a writer that writes to both the output device and this buffer.
+    pub fn recorder<'b>(&'b mut self, output: &'b TextOutput) -> Recorder<'a, 'b> {
+        Recorder {
+            output,
+            buffer: self,
+        }
+    }
+
+    /// Draws the given cells, changing the color only where necessary.
+    fn draw_cells(&self, output: &TextOutput, cells: &[ScreenCell]) -> Result<(), Error> {
+        let mut text = [0u8; 128];
+        let mut len = 0;
+        let mut attribute = None;
+
+        for cell in cells {
+            if attribute != Some(cell.attribute) || len + 4 > text.len() {
+                // This is safe, because only whole characters are written to the text.
+                output.output_string(unsafe { core::str::from_utf8_unchecked(&text[..len]) })?;
+                len = 0;
+
+                if attribute != Some(cell.attribute) {
+                    output.set_attribute(cell.attribute)?;
+                    attribute = Some(cell.attribute);
+                }
+            }
+
+            len += cell.character.encode_utf8(&mut text[len..]).len();
+        }
+
+        // This is safe, because only whole characters are written to the text.
+        output.output_string(unsafe { core::str::from_utf8_unchecked(&text[..len]) })?;
+
+        Ok(())
+    }
+
+    /// Moves the cursor to the start of the next row, scrolling if necessary.
+    fn next_row(&mut self) {
+        self.column = 0;
+
+        if self.row + 1 < self.rows {
+            self.row += 1;
+            return;
+        }
+
+        let blank = self.blank();
+
+        self.cells.copy_within(self.columns.., 0);
+        for cell in &mut self.cells[(self.rows - 1) * self.columns..] {
+            *cell = blank;
+        }
+    }
+
+    /// Returns an empty cell with the current color.
+    fn blank(&self) -> ScreenCell {
+        ScreenCell {
+            character: ' ',
+            attribute: self.attribute,
+        }
+    }
+}
+
+impl<'a> fmt::Write for ScreenBuffer<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for character in s.chars() {
+            self.put_char(character);
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes to an output device and records the text in a `ScreenBuffer`.
+pub struct Recorder<'a, 'b> {
+    /// The output device the text is written to.
+    output: &'b TextOutput,
+    /// The buffer the text is recorded in.
+    buffer: &'b mut ScreenBuffer<'a>,
+}
+
+impl<'a, 'b> Recorder<'a, 'b> {
+    /// Sets the color of the text on the output device and in the buffer.
+    pub fn set_attribute(&mut self, attribute: Color) -> Result<(), Error> {
+        self.output.set_attribute(attribute)?;
+        self.buffer.set_attribute(attribute);
+
+        Ok(())
+    }
+}
+
+impl<'a, 'b> fmt::Write for Recorder<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.output.output_string(s).map_err(|_| fmt::Error)?;
+        self.buffer.write_str(s)
+    }
+}
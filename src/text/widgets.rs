@@ -0,0 +1,157 @@
+//! Simple widgets to show the progress of long operations on a text console.
+//!
+//! The widgets remember the cursor position they were created at and redraw themselves in
+//! place.
+
+use core::fmt::Write;
+
+use super::{BufferedWriter, TextOutput};
+use crate::status::Error;
+
+/// Shows the progress of an operation as a bar, a percentage and the absolute counts.
+pub struct ProgressBar<'a> {
+    /// The output device the bar is drawn on.
+    output: &'a TextOutput,
+    /// The column the bar starts at.
+    column: usize,
+    /// The row the bar is drawn in.
+    row: usize,
+    /// The number of characters inside the bar.
+    width: usize,
+    /// The count that is reached when the operation is complete.
+    total: u64,
+    /// The current count.
+    current: u64,
+}
+
+impl<'a> ProgressBar<'a> {
+    /// Creates a progress bar at the current cursor position and draws it.
+    ///
+    /// `width` is the number of characters inside the bar and `total` is the count that is
+    /// reached when the operation is complete, for example the number of bytes to read.
+    pub fn new(output: &'a TextOutput, width: usize, total: u64) -> Result<ProgressBar<'a>, Error> {
+        let (column, row) = output.cursor_position();
+
+        let progress_bar = ProgressBar {
+            output,
+            column,
+            row,
+            width,
+            total,
+            current: 0,
+        };
+
+        progress_bar.draw()?;
+
+        Ok(progress_bar)
+    }
+
+    /// Sets the current count and redraws the bar.
+    pub fn set(&mut self, current: u64) -> Result<(), Error> {
+        self.current = current.min(self.total);
+
+        self.draw()
+    }
+
+    /// Adds `amount` to the current count and redraws the bar.
+    pub fn advance(&mut self, amount: u64) -> Result<(), Error> {
+        self.set(self.current.saturating_add(amount))
+    }
+
+    /// Fills the bar and moves the cursor to the next line.
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.set(self.total)?;
+
+        self.output.output_string("\r\n")?;
+
+        Ok(())
+    }
+
+    /// Draws the bar at its position.
+    fn draw(&self) -> Result<(), Error> {
+        let permille = if self.total == 0 {
+            1000
+        } else {
+            (u128::from(self.current) * 1000 / u128::from(self.total)) as usize
+        };
+        let filled = self.width * permille / 1000;
+
+        self.output.set_cursor_position(self.column, self.row)?;
+
+        let mut writer = BufferedWriter::new(self.output);
+
+        let _ = writer.write_char('[');
+        for i in 0..self.width {
+            let _ = writer.write_char(if i < filled { '#' } else { ' ' });
+        }
+        let _ = write!(
+            writer,
+            "] {:>3}% {}/{}",
+            permille / 10,
+            self.current,
+            self.total
+        );
+
+        writer.flush()
+    }
+}
+
+/// The characters a `Spinner` cycles through.
+const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Shows that an operation of unknown length is still running.
+pub struct Spinner<'a> {
+    /// The output device the spinner is drawn on.
+    output: &'a TextOutput,
+    /// The column the spinner is drawn in.
+    column: usize,
+    /// The row the spinner is drawn in.
+    row: usize,
+    /// The index of the current frame.
+    frame: usize,
+}
+
+impl<'a> Spinner<'a> {
+    /// Creates a spinner at the current cursor position and draws it.
+    pub fn new(output: &'a TextOutput) -> Result<Spinner<'a>, Error> {
+        let (column, row) = output.cursor_position();
+
+        let spinner = Spinner {
+            output,
+            column,
+            row,
+            frame: 0,
+        };
+
+        spinner.draw()?;
+
+        Ok(spinner)
+    }
+
+    /// Advances the spinner to its next frame.
+    pub fn tick(&mut self) -> Result<(), Error> {
+        self.frame = (self.frame + 1) % SPINNER_FRAMES.len();
+
+        self.draw()
+    }
+
+    /// Replaces the spinner with the given message and moves the cursor to the next line.
+    pub fn finish(self, message: &str) -> Result<(), Error> {
+        self.output.set_cursor_position(self.column, self.row)?;
+        self.output.output_string(message)?;
+        self.output.output_string("\r\n")?;
+
+        Ok(())
+    }
+
+    /// Draws the current frame at the position of the spinner.
+    fn draw(&self) -> Result<(), Error> {
+        let mut frame = [0; 4];
+
+        self.output.set_cursor_position(self.column, self.row)?;
+        self.output
+            .output_string(SPINNER_FRAMES[self.frame].encode_utf8(&mut frame))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+//! A writer that wraps text at word boundaries.
+
+use core::fmt;
+
+use super::TextOutput;
+use crate::status::Error;
+
+/// The maximum number of bytes of a word that is kept before it is written.
+const MAX_WORD_LEN: usize = 256;
+
+/// Writes to an output device and wraps lines at word boundaries.
+///
+/// The line width is taken from the current mode of the device and queried again whenever the
+/// mode changes. Continuation lines are indented by a fixed number of columns. Words that are
+/// longer than a line are broken at the end of the line.
+pub struct WrapWriter<'a> {
+    /// The output device the text is written to.
+    output: &'a TextOutput,
+    /// The number of columns continuation lines are indented by.
+    indent: usize,
+    /// The mode the line width was queried for.
+    mode: i32,
+    /// The number of columns a line can hold.
+    width: usize,
+    /// The column the next character is written to.
+    column: usize,
+    /// The word that was not written yet.
+    word: [u8; MAX_WORD_LEN],
+    /// The number of bytes in `word`.
+    word_len: usize,
+    /// The number of characters in `word`.
+    word_chars: usize,
+}
+
+impl<'a> WrapWriter<'a> {
+    /// Creates a wrapping writer that indents continuation lines by `indent` columns.
+    pub fn new(output: &'a TextOutput, indent: usize) -> Result<WrapWriter<'a>, Error> {
+        let (column, _) = output.cursor_position();
+
+        let mut writer = WrapWriter {
+            output,
+            indent,
+            mode: -1,
+            width: 0,
+            column,
+            word: [0; MAX_WORD_LEN],
+            word_len: 0,
+            word_chars: 0,
+        };
+
+        writer.update_width()?;
+
+        Ok(writer)
+    }
+
+    /// Writes the pending word to the output device.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.word_len == 0 {
+            return Ok(());
+        }
+
+        if self.column + self.word_chars > self.width && self.column > self.indent {
+            self.new_line()?;
+        }
+
+        // This is safe, because only whole characters are written to the word.
+        let word = unsafe { core::str::from_utf8_unchecked(&self.word[..self.word_len]) };
+        self.output.output_string(word)?;
+
+        self.column += self.word_chars;
+        self.word_len = 0;
+        self.word_chars = 0;
+
+        Ok(())
+    }
+
+    /// Queries the line width again, if the mode of the output device changed.
+    fn update_width(&mut self) -> Result<(), Error> {
+        if self.output.Mode.Mode != self.mode {
+            let (columns, _) = self.output.current_mode_size()?;
+
+            self.mode = self.output.Mode.Mode;
+            // The last column is left empty, because writing to it moves the cursor to the next
+            // line on some devices.
+            self.width = columns.saturating_sub(1).max(self.indent + 1);
+        }
+
+        Ok(())
+    }
+
+    /// Starts an indented continuation line.
+    fn new_line(&mut self) -> Result<(), Error> {
+        self.output.output_string("\r\n")?;
+
+        for _ in 0..self.indent {
+            self.output.output_string(" ")?;
+        }
+
+        self.column = self.indent;
+
+        Ok(())
+    }
+
+    /// Writes a single character.
+    fn put_char(&mut self, character: char) -> Result<(), Error> {
+        self.update_width()?;
+
+        match character {
+            '\r' => Ok(()),
+            '\n' => {
+                self.flush()?;
+                self.output.output_string("\r\n")?;
+                self.column = 0;
+
+                Ok(())
+            }
+            ' ' => {
+                self.flush()?;
+
+                if self.column + 1 >= self.width {
+                    self.new_line()
+                } else if self.column > self.indent || self.column == 0 {
+                    self.output.output_string(" ")?;
+                    self.column += 1;
+
+                    Ok(())
+                } else {
+                    // Spaces at the start of continuation lines are dropped.
+                    Ok(())
+                }
+            }
+            character => {
+                if self.word_len + character.len_utf8() > MAX_WORD_LEN
+                    || self.word_chars + 1 > self.width - self.indent
+                {
+                    self.flush()?;
+                    self.new_line()?;
+                }
+
+                self.word_len += character.encode_utf8(&mut self.word[self.word_len..]).len();
+                self.word_chars += 1;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Write for WrapWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for character in s.chars() {
+            self.put_char(character).map_err(|_| fmt::Error)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Drop for WrapWriter<'a> {
+    fn drop(&mut self) {
+        // There is no way to report the error here.
+        let _ = self.flush();
+    }
+}
@@ -1,4 +1,104 @@
 //! Defines the time types used by UEFI.
+//!
+//! `Time` can be converted to and from Unix timestamps, moved between time zones and shifted by a
+//! `Duration`. Comparisons between times compare the instants they represent, so times in
+//! different time zones compare equal if they describe the same moment.
+
+use core::{
+    cmp::Ordering,
+    convert::TryFrom,
+    ops::{Add, Sub},
+    time::Duration,
+};
+
+use crate::status::Error;
+
+/// The value of `Time::TimeZone` for a local time without a known offset to UTC.
+pub const UNSPECIFIED_TIMEZONE: u16 = 0x07ff;
+
+/// The bit of `Time::Daylight` that indicates that the time is affected by daylight saving time.
+pub const TIME_ADJUST_DAYLIGHT: u8 = 0x01;
+
+/// The bit of `Time::Daylight` that indicates that the time was adjusted for daylight saving time.
+pub const TIME_IN_DAYLIGHT: u8 = 0x02;
+
+/// The earliest year a `Time` can represent.
+pub const MIN_YEAR: u16 = 1900;
+
+/// The latest year a `Time` can represent.
+pub const MAX_YEAR: u16 = 9999;
+
+/// The largest offset to UTC in minutes.
+const MAX_TIME_ZONE: i16 = 1440;
+
+/// The number of seconds in a day.
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The number of nanoseconds in a second.
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+/// Returns whether `year` is a leap year in the Gregorian calendar.
+pub fn is_leap_year(year: u16) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the number of days in the given month of the given year.
+///
+/// Returns 0 if `month` is not in the range 1 to 12.
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Returns the number of days between 1970-01-01 and the given date.
+fn days_from_civil(year: u16, month: u8, day: u8) -> i64 {
+    // The year is shifted to start in March, so the leap day is the last day of the year.
+    let year = i64::from(year) - if month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the year, month and day of the given number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month as u8, day as u8)
+}
+
+/// A day of the week.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Weekday {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
 
 /// This represents the current time information.
 #[derive(Copy, Clone, Debug, Default)]
@@ -81,6 +181,352 @@ pub struct Time {
     _Pad2: u8,
 }
 
+impl Time {
+    /// Creates a time in UTC.
+    ///
+    /// Returns `Error::InvalidParameter` if any of the values is out of range.
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        nanosecond: u32,
+    ) -> Result<Time, Error> {
+        let time = Time {
+            Year: year,
+            Month: month,
+            Day: day,
+            Hour: hour,
+            Minute: minute,
+            Second: second,
+            _Pad1: 0,
+            Nanosecond: nanosecond,
+            TimeZone: 0,
+            Daylight: 0,
+            _Pad2: 0,
+        };
+
+        if time.is_valid() {
+            Ok(time)
+        } else {
+            Err(Error::InvalidParameter)
+        }
+    }
+
+    /// Returns whether all fields are within the ranges the specification allows.
+    pub fn is_valid(&self) -> bool {
+        let time_zone_valid = match self.time_zone() {
+            Some(time_zone) => -MAX_TIME_ZONE <= time_zone && time_zone <= MAX_TIME_ZONE,
+            None => true,
+        };
+
+        MIN_YEAR <= self.Year
+            && self.Year <= MAX_YEAR
+            && 1 <= self.Day
+            && self.Day <= days_in_month(self.Year, self.Month)
+            && self.Hour < 24
+            && self.Minute < 60
+            && self.Second < 60
+            && self.Nanosecond < NANOS_PER_SECOND
+            && time_zone_valid
+            && self.Daylight & !(TIME_ADJUST_DAYLIGHT | TIME_IN_DAYLIGHT) == 0
+    }
+
+    /// Returns the offset to UTC in minutes, where local time is UTC minus the offset.
+    ///
+    /// Returns `None` for a local time without a known offset.
+    pub fn time_zone(&self) -> Option<i16> {
+        if self.TimeZone == UNSPECIFIED_TIMEZONE {
+            None
+        } else {
+            Some(self.TimeZone as i16)
+        }
+    }
+
+    /// Returns whether the time was adjusted for daylight saving time.
+    pub fn in_daylight(&self) -> bool {
+        self.Daylight & TIME_IN_DAYLIGHT != 0
+    }
+
+    /// Returns the day of the week.
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a Thursday.
+        match (days_from_civil(self.Year, self.Month, self.Day) + 4).rem_euclid(7) {
+            0 => Weekday::Sunday,
+            1 => Weekday::Monday,
+            2 => Weekday::Tuesday,
+            3 => Weekday::Wednesday,
+            4 => Weekday::Thursday,
+            5 => Weekday::Friday,
+            _ => Weekday::Saturday,
+        }
+    }
+
+    /// Returns the day of the year, starting with 1 for January 1st.
+    pub fn day_of_year(&self) -> u16 {
+        (days_from_civil(self.Year, self.Month, self.Day) - days_from_civil(self.Year, 1, 1) + 1)
+            as u16
+    }
+
+    /// Returns the number of seconds of the local date and time since 1970-01-01 00:00:00.
+    fn local_seconds(&self) -> i64 {
+        days_from_civil(self.Year, self.Month, self.Day) * SECONDS_PER_DAY
+            + i64::from(self.Hour) * 3600
+            + i64::from(self.Minute) * 60
+            + i64::from(self.Second)
+    }
+
+    /// Returns the number of seconds since the Unix epoch.
+    ///
+    /// A time without a known offset to UTC is treated as UTC.
+    pub fn to_unix_timestamp(&self) -> i64 {
+        self.local_seconds() + i64::from(self.time_zone().unwrap_or(0)) * 60
+    }
+
+    /// Creates a time in UTC from the number of seconds since the Unix epoch.
+    ///
+    /// Returns `Error::InvalidParameter` if the time is outside the range of years `Time` can
+    /// represent or `nanosecond` is not less than one second.
+    pub fn from_unix_timestamp(seconds: i64, nanosecond: u32) -> Result<Time, Error> {
+        Time::from_local_seconds(seconds, nanosecond, 0, 0).ok_or(Error::InvalidParameter)
+    }
+
+    /// Creates a time from the number of seconds of the local time since 1970-01-01 00:00:00.
+    fn from_local_seconds(
+        seconds: i64,
+        nanosecond: u32,
+        time_zone: u16,
+        daylight: u8,
+    ) -> Option<Time> {
+        let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+        let second_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+
+        if year < i64::from(MIN_YEAR) || year > i64::from(MAX_YEAR) {
+            return None;
+        }
+
+        let time = Time {
+            Year: year as u16,
+            Month: month,
+            Day: day,
+            Hour: (second_of_day / 3600) as u8,
+            Minute: (second_of_day / 60 % 60) as u8,
+            Second: (second_of_day % 60) as u8,
+            _Pad1: 0,
+            Nanosecond: nanosecond,
+            TimeZone: time_zone,
+            Daylight: daylight,
+            _Pad2: 0,
+        };
+
+        if time.is_valid() {
+            Some(time)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the same instant in the given time zone, which is the offset to UTC in minutes.
+    ///
+    /// The daylight saving time information is cleared, because it belongs to the old time zone.
+    /// A time without a known offset to UTC is treated as UTC.
+    pub fn to_time_zone(&self, time_zone: i16) -> Result<Time, Error> {
+        if time_zone < -MAX_TIME_ZONE || time_zone > MAX_TIME_ZONE {
+            return Err(Error::InvalidParameter);
+        }
+
+        Time::from_local_seconds(
+            self.to_unix_timestamp() - i64::from(time_zone) * 60,
+            self.Nanosecond,
+            time_zone as u16,
+            0,
+        )
+        .ok_or(Error::InvalidParameter)
+    }
+
+    /// Returns the same instant in UTC.
+    pub fn to_utc(&self) -> Result<Time, Error> {
+        self.to_time_zone(0)
+    }
+
+    /// Adjusts a time that is affected by daylight saving time for entering it.
+    ///
+    /// The time is moved forward by an hour and the time zone is decreased accordingly, as the
+    /// specification describes. Times that are not affected or already adjusted are returned
+    /// unchanged.
+    pub fn enter_daylight_saving(&self) -> Result<Time, Error> {
+        if self.Daylight != TIME_ADJUST_DAYLIGHT {
+            return Ok(*self);
+        }
+
+        self.shift_daylight(60, TIME_ADJUST_DAYLIGHT | TIME_IN_DAYLIGHT)
+    }
+
+    /// Reverts the adjustment of `enter_daylight_saving`.
+    pub fn leave_daylight_saving(&self) -> Result<Time, Error> {
+        if self.Daylight != TIME_ADJUST_DAYLIGHT | TIME_IN_DAYLIGHT {
+            return Ok(*self);
+        }
+
+        self.shift_daylight(-60, TIME_ADJUST_DAYLIGHT)
+    }
+
+    /// Moves the local time by `minutes` without changing the instant.
+    fn shift_daylight(&self, minutes: i16, daylight: u8) -> Result<Time, Error> {
+        let time_zone = match self.time_zone() {
+            Some(time_zone) => (time_zone - minutes) as u16,
+            None => UNSPECIFIED_TIMEZONE,
+        };
+
+        Time::from_local_seconds(
+            self.local_seconds() + i64::from(minutes) * 60,
+            self.Nanosecond,
+            time_zone,
+            daylight,
+        )
+        .ok_or(Error::InvalidParameter)
+    }
+
+    /// Returns the time `duration` later, keeping the time zone, or `None` if it cannot be
+    /// represented.
+    pub fn checked_add(&self, duration: Duration) -> Option<Time> {
+        let mut seconds = self
+            .local_seconds()
+            .checked_add(i64::try_from(duration.as_secs()).ok()?)?;
+        let mut nanosecond = self.Nanosecond + duration.subsec_nanos();
+
+        if nanosecond >= NANOS_PER_SECOND {
+            nanosecond -= NANOS_PER_SECOND;
+            seconds = seconds.checked_add(1)?;
+        }
+
+        Time::from_local_seconds(seconds, nanosecond, self.TimeZone, self.Daylight)
+    }
+
+    /// Returns the time `duration` earlier, keeping the time zone, or `None` if it cannot be
+    /// represented.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Time> {
+        let mut seconds = self
+            .local_seconds()
+            .checked_sub(i64::try_from(duration.as_secs()).ok()?)?;
+        let mut nanosecond = self.Nanosecond;
+
+        if nanosecond < duration.subsec_nanos() {
+            nanosecond += NANOS_PER_SECOND;
+            seconds = seconds.checked_sub(1)?;
+        }
+
+        Time::from_local_seconds(
+            seconds,
+            nanosecond - duration.subsec_nanos(),
+            self.TimeZone,
+            self.Daylight,
+        )
+    }
+
+    /// Returns the time elapsed from `earlier` to this time.
+    ///
+    /// Returns `None` if `earlier` is later than this time.
+    pub fn duration_since(&self, earlier: &Time) -> Option<Duration> {
+        let mut seconds = self.to_unix_timestamp() - earlier.to_unix_timestamp();
+        let mut nanosecond = i64::from(self.Nanosecond) - i64::from(earlier.Nanosecond);
+
+        if nanosecond < 0 {
+            nanosecond += i64::from(NANOS_PER_SECOND);
+            seconds -= 1;
+        }
+
+        if seconds < 0 {
+            None
+        } else {
+            Some(Duration::new(seconds as u64, nanosecond as u32))
+        }
+    }
+}
+
+impl Add<Duration> for Time {
+    type Output = Time;
+
+    /// # Panics
+    /// Panics if the result cannot be represented.
+    fn add(self, duration: Duration) -> Time {
+        self.checked_add(duration)
+            .expect("overflow when adding a duration to a time")
+    }
+}
+
+impl Sub<Duration> for Time {
+    type Output = Time;
+
+    /// # Panics
+    /// Panics if the result cannot be represented.
+    fn sub(self, duration: Duration) -> Time {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting a duration from a time")
+    }
+}
+
+impl PartialEq for Time {
+    fn eq(&self, other: &Time) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Time {}
+
+impl PartialOrd for Time {
+    fn partial_cmp(&self, other: &Time) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Time {
+    fn cmp(&self, other: &Time) -> Ordering {
+        (self.to_unix_timestamp(), self.Nanosecond)
+            .cmp(&(other.to_unix_timestamp(), other.Nanosecond))
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<r_efi::efi::Time> for Time {
+    fn from(time: r_efi::efi::Time) -> Time {
+        Time {
+            Year: time.year,
+            Month: time.month,
+            Day: time.day,
+            Hour: time.hour,
+            Minute: time.minute,
+            Second: time.second,
+            _Pad1: 0,
+            Nanosecond: time.nanosecond,
+            TimeZone: time.timezone as u16,
+            Daylight: time.daylight,
+            _Pad2: 0,
+        }
+    }
+}
+
+#[cfg(feature = "r-efi")]
+impl From<Time> for r_efi::efi::Time {
+    fn from(time: Time) -> r_efi::efi::Time {
+        r_efi::efi::Time {
+            year: time.Year,
+            month: time.Month,
+            day: time.Day,
+            hour: time.Hour,
+            minute: time.Minute,
+            second: time.Second,
+            pad1: 0,
+            nanosecond: time.Nanosecond,
+            timezone: time.TimeZone as i16,
+            daylight: time.Daylight,
+            pad2: 0,
+        }
+    }
+}
+
 /// This provides the capabilities of the
 /// real time clock device as exposed through the EFI interfaces.
 #[derive(Copy, Clone, Debug, Default)]
@@ -0,0 +1,134 @@
+//! Periodic timers that provide a clock for animations and polling loops.
+//!
+//! A `Ticker` waits for a periodic timer event instead of busy-waiting with `Stall`, so the
+//! processor can idle and other events keep being processed between ticks.
+
+use core::{mem::ManuallyDrop, time::Duration};
+
+use crate::{
+    boot::{BootServices, TimerDelay},
+    status::Error,
+    Event,
+};
+
+/// Signals a tick every period.
+///
+/// If more than a period passes between two waits, the missed ticks are merged into one, so the
+/// ticker never has to catch up.
+pub struct Ticker<'a> {
+    /// The boot services used to wait for the timer.
+    boot_services: &'a BootServices,
+    /// The periodic timer event.
+    event: Event,
+    /// The period of the timer in units of 100 nanoseconds.
+    period: u64,
+    /// The number of ticks that were waited for.
+    ticks: u64,
+}
+
+impl<'a> Ticker<'a> {
+    /// Creates a ticker that signals a tick every `period`.
+    ///
+    /// Returns `Error::InvalidParameter` if the period is shorter than 100 nanoseconds, which is
+    /// the resolution of the firmware timers.
+    pub fn new(boot_services: &'a BootServices, period: Duration) -> Result<Ticker<'a>, Error> {
+        let period = period.as_nanos() / 100;
+
+        if period == 0 {
+            return Err(Error::InvalidParameter);
+        }
+
+        let period = period.min(u128::from(u64::max_value())) as u64;
+
+        let ticker = Ticker {
+            boot_services,
+            event: boot_services.create_timer_event()?,
+            period,
+            ticks: 0,
+        };
+
+        boot_services.set_timer(&ticker.event, TimerDelay::Periodic, period)?;
+
+        Ok(ticker)
+    }
+
+    /// Returns the period of the ticker.
+    pub fn period(&self) -> Duration {
+        Duration::from_nanos(self.period.saturating_mul(100))
+    }
+
+    /// Returns the timer event, for example to wait for it together with other events.
+    ///
+    /// Ticks that are consumed through the event are not counted.
+    pub fn event(&self) -> Event {
+        self.event
+    }
+
+    /// Returns the number of ticks that were waited for so far.
+    pub fn tick_count(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Waits for the next tick and returns the number of ticks so far.
+    pub fn wait_tick(&mut self) -> Result<u64, Error> {
+        self.boot_services.wait_for_event(&self.event)?;
+
+        self.ticks += 1;
+
+        Ok(self.ticks)
+    }
+
+    /// Returns the number of ticks so far if a tick happened since the last one, without waiting.
+    pub fn try_tick(&mut self) -> Result<Option<u64>, Error> {
+        if !self.boot_services.check_event(&self.event)? {
+            return Ok(None);
+        }
+
+        self.ticks += 1;
+
+        Ok(Some(self.ticks))
+    }
+
+    /// Returns an iterator that waits for a tick before every item.
+    ///
+    /// The items are the number of ticks so far. The iterator never ends.
+    pub fn ticks(&mut self) -> Ticks<'_, 'a> {
+        Ticks { ticker: self }
+    }
+
+    /// Restarts the period from now.
+    pub fn reset(&mut self) -> Result<(), Error> {
+        self.boot_services
+            .set_timer(&self.event, TimerDelay::Periodic, self.period)
+    }
+
+    /// Stops the timer and closes its event.
+    pub fn stop(self) -> Result<(), Error> {
+        let this = ManuallyDrop::new(self);
+
+        this.boot_services
+            .set_timer(&this.event, TimerDelay::Cancel, 0)?;
+        this.boot_services.close_event(this.event)
+    }
+}
+
+impl<'a> Drop for Ticker<'a> {
+    fn drop(&mut self) {
+        // There is no way to report the error here.
+        let _ = self.boot_services.close_event(self.event);
+    }
+}
+
+/// An iterator that waits for a tick of a `Ticker` before every item.
+pub struct Ticks<'t, 'a> {
+    /// The ticker that is waited for.
+    ticker: &'t mut Ticker<'a>,
+}
+
+impl<'t, 'a> Iterator for Ticks<'t, 'a> {
+    type Item = Result<u64, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.ticker.wait_tick())
+    }
+}
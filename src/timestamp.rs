@@ -0,0 +1,58 @@
+//! The Timestamp protocol provides a platform independent counter with a known frequency.
+
+use crate::{
+    guid::Guid,
+    guids,
+    protocol::Protocol,
+    status::{Error, Status},
+};
+
+/// The properties of the timestamp counter.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct TimestampProperties {
+    /// The frequency of the counter in Hz.
+    pub Frequency: u64,
+    /// The value of the counter before it wraps around to zero.
+    pub EndValue: u64,
+}
+
+impl TimestampProperties {
+    /// Returns the number of ticks from `start` to `end`, accounting for one wrap around.
+    pub fn ticks_between(&self, start: u64, end: u64) -> u64 {
+        if end >= start {
+            end - start
+        } else {
+            (self.EndValue - start).wrapping_add(end).wrapping_add(1)
+        }
+    }
+}
+
+/// Provides a platform independent counter with a known frequency.
+#[repr(C)]
+pub struct Timestamp {
+    /// Returns the current value of the counter.
+    pub GetTimestamp: eficall! { fn() -> u64 },
+    /// Returns the properties of the counter.
+    pub GetProperties: eficall! { fn(Properties: &mut TimestampProperties) -> Status },
+}
+
+unsafe impl Protocol for Timestamp {
+    const GUID: Guid = guids::protocol::TIMESTAMP;
+}
+
+impl Timestamp {
+    /// Returns the current value of the counter.
+    pub fn timestamp(&self) -> u64 {
+        (self.GetTimestamp)()
+    }
+
+    /// Returns the properties of the counter.
+    pub fn properties(&self) -> Result<TimestampProperties, Error> {
+        let mut properties = TimestampProperties::default();
+
+        (self.GetProperties)(&mut properties)?;
+
+        Ok(properties)
+    }
+}
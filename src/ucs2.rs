@@ -0,0 +1,187 @@
+//! Conversions between Rust strings and the null terminated strings used by the firmware.
+//!
+//! The UEFI specification describes these strings as UCS-2, but firmware generally handles UTF-16,
+//! so characters outside the basic multilingual plane are encoded as surrogate pairs.
+
+use core::{
+    char::{self, DecodeUtf16},
+    fmt::{self, Write},
+    iter::Cloned,
+    slice,
+};
+
+use crate::status::Error;
+
+/// Returns the number of code units needed to encode `string`, excluding the null terminator.
+pub fn encoded_len(string: &str) -> usize {
+    string.chars().map(char::len_utf16).sum()
+}
+
+/// Encodes `string` into `buffer`, followed by a null terminator.
+///
+/// Returns the number of code units written, excluding the null terminator.
+///
+/// Returns `Error::InvalidParameter` if `string` contains a null character, which would end the
+/// string early, and `Error::BufferTooSmall` if `buffer` cannot hold the string and terminator.
+pub fn encode_into(string: &str, buffer: &mut [u16]) -> Result<usize, Error> {
+    if string.contains('\0') {
+        return Err(Error::InvalidParameter);
+    }
+
+    if encoded_len(string) >= buffer.len() {
+        return Err(Error::BufferTooSmall);
+    }
+
+    Ok(encode_into_lossy(string, buffer))
+}
+
+/// Encodes as much of `string` into `buffer` as fits, followed by a null terminator.
+///
+/// Null characters are replaced with U+FFFD and characters are never split. Nothing is written
+/// to an empty buffer.
+///
+/// Returns the number of code units written, excluding the null terminator.
+pub fn encode_into_lossy(string: &str, buffer: &mut [u16]) -> usize {
+    if buffer.is_empty() {
+        return 0;
+    }
+
+    let mut len = 0;
+
+    for character in string.chars() {
+        let character = if character == '\0' {
+            char::REPLACEMENT_CHARACTER
+        } else {
+            character
+        };
+
+        if len + character.len_utf16() >= buffer.len() {
+            break;
+        }
+
+        len += character.encode_utf16(&mut buffer[len..]).len();
+    }
+
+    buffer[len] = 0;
+
+    len
+}
+
+/// Encodes `string` in chunks that fit into `buffer`.
+///
+/// `function` is called with every chunk, including its null terminator. It is called at least
+/// once, even for an empty string, and characters are never split between chunks.
+///
+/// # Panics
+/// Panics if `buffer` cannot hold a surrogate pair and a null terminator.
+pub fn encode_chunked<F, E>(string: &str, buffer: &mut [u16], mut function: F) -> Result<(), E>
+where
+    F: FnMut(&[u16]) -> Result<(), E>,
+{
+    assert!(
+        buffer.len() >= 3,
+        "The buffer is too small to encode a character."
+    );
+
+    let mut len = 0;
+
+    for character in string.chars() {
+        if len + character.len_utf16() >= buffer.len() {
+            buffer[len] = 0;
+            function(&buffer[..=len])?;
+            len = 0;
+        }
+
+        len += character.encode_utf16(&mut buffer[len..]).len();
+    }
+
+    buffer[len] = 0;
+    function(&buffer[..=len])
+}
+
+/// Returns the code units before the first null terminator, or all of them if there is none.
+pub fn until_nul(units: &[u16]) -> &[u16] {
+    match units.iter().position(|&unit| unit == 0) {
+        Some(len) => &units[..len],
+        None => units,
+    }
+}
+
+/// Returns the number of code units before the null terminator of `string`.
+///
+/// # Safety
+/// `string` must point to a null terminated string.
+pub unsafe fn len_until_nul(string: *const u16) -> usize {
+    let mut len = 0;
+
+    while *string.add(len) != 0 {
+        len += 1;
+    }
+
+    len
+}
+
+/// Returns the code units of the null terminated `string`, excluding the null terminator.
+///
+/// # Safety
+/// `string` must point to a null terminated string that lives for `'a`.
+pub unsafe fn from_ptr<'a>(string: *const u16) -> &'a [u16] {
+    slice::from_raw_parts(string, len_until_nul(string))
+}
+
+/// Decodes `units` up to the first null terminator.
+///
+/// Unpaired surrogates are returned as errors.
+pub fn decode_until_nul(units: &[u16]) -> DecodeUtf16<Cloned<slice::Iter<u16>>> {
+    char::decode_utf16(until_nul(units).iter().cloned())
+}
+
+/// Decodes `units` up to the first null terminator, replacing unpaired surrogates with U+FFFD.
+pub fn decode_until_nul_lossy(units: &[u16]) -> impl Iterator<Item = char> + '_ {
+    decode_until_nul(units).map(|character| character.unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+/// Formats code units up to the first null terminator, replacing unpaired surrogates with U+FFFD.
+#[derive(Clone, Copy)]
+pub struct Lossy<'a>(&'a [u16]);
+
+impl<'a> Lossy<'a> {
+    /// Wraps the code units, stopping at the first null terminator.
+    pub fn new(units: &'a [u16]) -> Lossy<'a> {
+        Lossy(until_nul(units))
+    }
+
+    /// Returns the code units up to the null terminator.
+    pub fn units(&self) -> &'a [u16] {
+        self.0
+    }
+
+    /// Returns the decoded characters.
+    pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+        decode_until_nul_lossy(self.0)
+    }
+}
+
+impl<'a> fmt::Display for Lossy<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for character in self.chars() {
+            f.write_char(character)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for Lossy<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char('"')?;
+
+        for character in self.chars() {
+            for escaped in character.escape_debug() {
+                f.write_char(escaped)?;
+            }
+        }
+
+        f.write_char('"')
+    }
+}
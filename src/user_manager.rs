@@ -0,0 +1,334 @@
+//! This protocol manages user profiles.
+//!
+//! A user profile is a collection of information about a user, such as the name of the user,
+//! the credentials that identify the user and the access policies that apply to the user.
+//! Each piece of information is stored in a user information record.
+
+use core::{mem::size_of, ptr};
+
+use crate::{
+    guid::{Guid, USER_MANAGER_PROTOCOL_GUID},
+    protocol::Protocol,
+    status::{Error, Status},
+    Handle,
+};
+
+/// Handle to a user profile.
+///
+/// Type VOID *.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct UserProfileHandle(pub usize);
+
+/// Handle to a user information record.
+///
+/// Type VOID *.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct UserInfoHandle(pub usize);
+
+// User information record types
+pub const USER_INFO_EMPTY_RECORD: u8 = 0x00;
+pub const USER_INFO_NAME_RECORD: u8 = 0x01;
+pub const USER_INFO_CREATE_DATE_RECORD: u8 = 0x02;
+pub const USER_INFO_USAGE_DATE_RECORD: u8 = 0x03;
+pub const USER_INFO_USAGE_COUNT_RECORD: u8 = 0x04;
+pub const USER_INFO_IDENTIFIER_RECORD: u8 = 0x05;
+pub const USER_INFO_CREDENTIAL_TYPE_RECORD: u8 = 0x06;
+pub const USER_INFO_CREDENTIAL_TYPE_NAME_RECORD: u8 = 0x07;
+pub const USER_INFO_CREDENTIAL_PROVIDER_RECORD: u8 = 0x08;
+pub const USER_INFO_CREDENTIAL_PROVIDER_NAME_RECORD: u8 = 0x09;
+pub const USER_INFO_PKCS11_RECORD: u8 = 0x0a;
+pub const USER_INFO_CBEFF_RECORD: u8 = 0x0b;
+pub const USER_INFO_FAR_RECORD: u8 = 0x0c;
+pub const USER_INFO_RETRY_RECORD: u8 = 0x0d;
+pub const USER_INFO_ACCESS_POLICY_RECORD: u8 = 0x0e;
+pub const USER_INFO_IDENTITY_POLICY_RECORD: u8 = 0x0f;
+pub const USER_INFO_GUID_RECORD: u8 = 0xff;
+
+// User information record attributes
+pub const USER_INFO_STORAGE: u16 = 0x000f;
+pub const USER_INFO_STORAGE_VOLATILE: u16 = 0x0000;
+pub const USER_INFO_STORAGE_CREDENTIAL_NV: u16 = 0x0001;
+pub const USER_INFO_STORAGE_PLATFORM_NV: u16 = 0x0002;
+pub const USER_INFO_PRIVATE: u16 = 0x0010;
+pub const USER_INFO_EXCLUSIVE: u16 = 0x0020;
+
+// Access policy types
+pub const USER_INFO_ACCESS_FORBID_LOAD: u32 = 0x0000_0001;
+pub const USER_INFO_ACCESS_PERMIT_LOAD: u32 = 0x0000_0002;
+pub const USER_INFO_ACCESS_ENROLL_SELF: u32 = 0x0000_0003;
+pub const USER_INFO_ACCESS_ENROLL_OTHERS: u32 = 0x0000_0004;
+pub const USER_INFO_ACCESS_MANAGE: u32 = 0x0000_0005;
+pub const USER_INFO_ACCESS_SETUP: u32 = 0x0000_0006;
+pub const USER_INFO_ACCESS_FORBID_CONNECT: u32 = 0x0000_0007;
+pub const USER_INFO_ACCESS_PERMIT_CONNECT: u32 = 0x0000_0008;
+pub const USER_INFO_ACCESS_BOOT_ORDER: u32 = 0x0000_0009;
+
+/// The header of a user information record.
+///
+/// The record data directly follows the header, see `UserInfoRecord`.
+#[derive(Debug)]
+#[repr(C)]
+pub struct UserInfo {
+    /// The user credential identifier associated with this user information or else the
+    /// null GUID if the user information is not associated with any credential provider.
+    pub Credential: Guid,
+    /// The type of user information.
+    pub InfoType: u8,
+    /// Must be set to 0.
+    pub Reserved1: u8,
+    /// The attributes of the user profile information.
+    pub InfoAttribs: u16,
+    /// The size of the user information, in bytes, including this header.
+    pub InfoSize: u32,
+}
+
+/// A user information record read by `UserManager::get_info`.
+#[derive(Clone, Copy, Debug)]
+pub struct UserInfoRecord<'a> {
+    /// The header of the record.
+    header: &'a UserInfo,
+    /// The data of the record that follows the header.
+    data: &'a [u8],
+}
+
+impl<'a> UserInfoRecord<'a> {
+    /// Returns the header of the record.
+    pub fn header(&self) -> &'a UserInfo {
+        self.header
+    }
+
+    /// Returns the data of the record that follows the header.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Describes a single access policy in an access policy record.
+///
+/// The policy data directly follows the header.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct UserInfoAccessControl {
+    /// The type of access control.
+    pub Type: u32,
+    /// The size of the access control, in bytes, including this header.
+    pub Size: u32,
+}
+
+/// This protocol manages user profiles.
+#[repr(C)]
+pub struct UserManager {
+    /// Create a new user profile.
+    pub Create: eficall! { fn(&UserManager, User: &mut UserProfileHandle) -> Status },
+    /// Delete an existing user profile.
+    pub Delete: eficall! { fn(&UserManager, User: UserProfileHandle) -> Status },
+    /// Enumerate all of the enrolled users on the platform.
+    pub GetNext: eficall! { fn(&UserManager, User: &mut UserProfileHandle) -> Status },
+    /// Return the current user profile handle.
+    pub Current: eficall! { fn(&UserManager, CurrentUser: &mut UserProfileHandle) -> Status },
+    /// Identify a user.
+    pub Identify: eficall! { fn(&UserManager, User: &mut UserProfileHandle) -> Status },
+    /// Find a user using a user information record.
+    pub Find: eficall! { fn(
+        &UserManager,
+        User: &mut UserProfileHandle,
+        UserInfo: *mut UserInfoHandle,
+        Info: *const UserInfo,
+        InfoSize: usize,
+    ) -> Status },
+    /// Called by credential provider to notify of information change.
+    pub Notify: eficall! { fn(&UserManager, Changed: Handle) -> Status },
+    /// Return information attached to the user.
+    pub GetInfo: eficall! { fn(
+        &UserManager,
+        User: UserProfileHandle,
+        UserInfo: UserInfoHandle,
+        Info: *mut UserInfo,
+        InfoSize: &mut usize,
+    ) -> Status },
+    /// Add or update user information.
+    pub SetInfo: eficall! { fn(
+        &UserManager,
+        User: UserProfileHandle,
+        UserInfo: &mut UserInfoHandle,
+        Info: *const UserInfo,
+        InfoSize: usize,
+    ) -> Status },
+    /// Delete user information.
+    pub DeleteInfo: eficall! { fn(
+        &UserManager,
+        User: UserProfileHandle,
+        UserInfo: UserInfoHandle,
+    ) -> Status },
+    /// Enumerate user information of the specified user profile.
+    pub GetNextInfo: eficall! { fn(
+        &UserManager,
+        User: UserProfileHandle,
+        UserInfo: &mut UserInfoHandle,
+    ) -> Status },
+}
+
+unsafe impl Protocol for UserManager {
+    const GUID: Guid = USER_MANAGER_PROTOCOL_GUID;
+}
+
+impl UserManager {
+    /// Return the current user profile handle.
+    pub fn current(&self) -> Result<UserProfileHandle, Error> {
+        let mut user = UserProfileHandle(0);
+
+        (self.Current)(self, &mut user)?;
+
+        Ok(user)
+    }
+
+    /// Identify a user.
+    ///
+    /// Returns the handle of the identified user, which becomes the current user.
+    pub fn identify(&self) -> Result<UserProfileHandle, Error> {
+        let mut user = UserProfileHandle(0);
+
+        (self.Identify)(self, &mut user)?;
+
+        Ok(user)
+    }
+
+    /// Enumerate all of the enrolled users on the platform.
+    ///
+    /// `user` is updated to the next user. To start the enumeration, pass a null handle.
+    pub fn get_next(&self, user: &mut UserProfileHandle) -> Result<(), Error> {
+        (self.GetNext)(self, user)?;
+
+        Ok(())
+    }
+
+    /// Enumerate user information of the specified user profile.
+    ///
+    /// `info` is updated to the next record. To start the enumeration, pass a null handle.
+    pub fn get_next_info(
+        &self,
+        user: UserProfileHandle,
+        info: &mut UserInfoHandle,
+    ) -> Result<(), Error> {
+        (self.GetNextInfo)(self, user, info)?;
+
+        Ok(())
+    }
+
+    /// Returns an iterator over all the enrolled users on the platform.
+    pub fn users(&self) -> Users {
+        Users {
+            manager: self,
+            user: UserProfileHandle(0),
+        }
+    }
+
+    /// Returns an iterator over the user information records of the user profile.
+    pub fn infos(&self, user: UserProfileHandle) -> UserInfos {
+        UserInfos {
+            manager: self,
+            user,
+            info: UserInfoHandle(0),
+        }
+    }
+
+    /// Returns the size in bytes of the user information record.
+    pub fn get_info_size(
+        &self,
+        user: UserProfileHandle,
+        info: UserInfoHandle,
+    ) -> Result<usize, Error> {
+        let mut size = 0;
+
+        let status = (self.GetInfo)(self, user, info, ptr::null_mut(), &mut size);
+
+        // The firmware returns `BufferTooSmall` and the required size, so the status only
+        // matters if no size was returned.
+        if size == 0 {
+            status?;
+        }
+
+        Ok(size)
+    }
+
+    /// Reads the user information record into the buffer.
+    ///
+    /// The buffer must be at least `get_info_size` bytes large and must be suitably aligned
+    /// for a `UserInfo`. Returns `Error::BadBufferSize` if the size of the record in its header
+    /// does not fit into the buffer.
+    pub fn get_info<'a>(
+        &self,
+        user: UserProfileHandle,
+        info: UserInfoHandle,
+        buffer: &'a mut [u8],
+    ) -> Result<UserInfoRecord<'a>, Error> {
+        if buffer.len() < size_of::<UserInfo>()
+            || buffer.as_ptr() as usize % core::mem::align_of::<UserInfo>() != 0
+        {
+            return Err(Error::InvalidParameter);
+        }
+
+        let mut size = buffer.len();
+
+        (self.GetInfo)(
+            self,
+            user,
+            info,
+            buffer.as_mut_ptr() as *mut UserInfo,
+            &mut size,
+        )?;
+
+        let (header, data) = buffer.split_at(size_of::<UserInfo>());
+        // This is safe, because the buffer was checked to be large enough and aligned.
+        let header = unsafe { &*(header.as_ptr() as *const UserInfo) };
+
+        let data_size = (header.InfoSize as usize)
+            .checked_sub(size_of::<UserInfo>())
+            .ok_or(Error::BadBufferSize)?;
+
+        Ok(UserInfoRecord {
+            header,
+            data: data.get(..data_size).ok_or(Error::BadBufferSize)?,
+        })
+    }
+}
+
+/// An iterator over the enrolled users.
+pub struct Users<'a> {
+    /// The protocol used to enumerate the users.
+    manager: &'a UserManager,
+    /// The last returned user.
+    user: UserProfileHandle,
+}
+
+impl<'a> Iterator for Users<'a> {
+    type Item = UserProfileHandle;
+
+    fn next(&mut self) -> Option<UserProfileHandle> {
+        self.manager.get_next(&mut self.user).ok()?;
+
+        Some(self.user)
+    }
+}
+
+/// An iterator over the user information records of a user profile.
+pub struct UserInfos<'a> {
+    /// The protocol used to enumerate the records.
+    manager: &'a UserManager,
+    /// The user whose records are enumerated.
+    user: UserProfileHandle,
+    /// The last returned record.
+    info: UserInfoHandle,
+}
+
+impl<'a> Iterator for UserInfos<'a> {
+    type Item = UserInfoHandle;
+
+    fn next(&mut self) -> Option<UserInfoHandle> {
+        self.manager.get_next_info(self.user, &mut self.info).ok()?;
+
+        Some(self.info)
+    }
+}
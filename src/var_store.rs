@@ -0,0 +1,462 @@
+//! Typed access to the EFI variables of a vendor.
+//!
+//! A `VarStore` reads and writes the variables in the namespace of one vendor GUID, encoding the
+//! names and converting the values, so loader configuration stored in NVRAM does not need to be
+//! sliced by hand. Writes use the attributes of the store, which are selected with its builder
+//! methods. A `Transaction` writes several related variables, restoring the previous values if
+//! one of the writes fails.
+
+use core::{mem::size_of, slice};
+
+use crate::{
+    guid::{Guid, GLOBAL_VARIABLE_GUID},
+    runtime::{RuntimeServices, VariableAttributes},
+    status::Error,
+    string::CStr16,
+    ucs2::{self, Lossy},
+};
+
+/// The maximum length of a variable name in code units, excluding the null terminator.
+pub const MAX_NAME_LEN: usize = 127;
+
+/// The maximum size of a value that implements `VarValue`.
+pub const MAX_VALUE_SIZE: usize = 16;
+
+/// The number of writes a `Transaction` can hold.
+pub const MAX_TRANSACTION_WRITES: usize = 8;
+
+/// A value that is stored in a variable with a fixed size.
+///
+/// Integers are stored in little endian byte order, as the firmware does for `Timeout` and
+/// `BootCurrent`.
+pub trait VarValue: Sized {
+    /// The size of the stored value in bytes, which is at most `MAX_VALUE_SIZE`.
+    const SIZE: usize;
+
+    /// Decodes the value from exactly `SIZE` bytes.
+    fn decode(bytes: &[u8]) -> Self;
+
+    /// Encodes the value into exactly `SIZE` bytes.
+    fn encode(&self, bytes: &mut [u8]);
+}
+
+macro_rules! impl_var_value {
+    ($($ty:ty),*) => {
+        $(
+            impl VarValue for $ty {
+                const SIZE: usize = size_of::<$ty>();
+
+                fn decode(bytes: &[u8]) -> $ty {
+                    let mut array = [0; size_of::<$ty>()];
+                    array.copy_from_slice(bytes);
+
+                    <$ty>::from_le_bytes(array)
+                }
+
+                fn encode(&self, bytes: &mut [u8]) {
+                    bytes.copy_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_var_value!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl VarValue for bool {
+    const SIZE: usize = 1;
+
+    fn decode(bytes: &[u8]) -> bool {
+        bytes[0] != 0
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = *self as u8;
+    }
+}
+
+impl VarValue for Guid {
+    const SIZE: usize = 16;
+
+    fn decode(bytes: &[u8]) -> Guid {
+        let mut array = [0; 16];
+        array.copy_from_slice(bytes);
+
+        Guid::from_bytes(array)
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_bytes());
+    }
+}
+
+/// Encodes `name` and passes it to `function`.
+///
+/// Returns `Error::InvalidParameter` if the name is empty, longer than `MAX_NAME_LEN` or contains
+/// a null character.
+fn with_name<T, F>(name: &str, function: F) -> Result<T, Error>
+where
+    F: FnOnce(&CStr16) -> Result<T, Error>,
+{
+    let mut buffer = [0; MAX_NAME_LEN + 1];
+
+    if name.is_empty() {
+        return Err(Error::InvalidParameter);
+    }
+
+    let len = ucs2::encode_into(name, &mut buffer).map_err(|_| Error::InvalidParameter)?;
+
+    function(CStr16::from_ucs2_with_nul(&buffer[..=len])?)
+}
+
+/// Encodes `value` and returns the buffer holding it together with its size.
+fn encode_value<T: VarValue>(value: &T) -> Result<([u8; MAX_VALUE_SIZE], usize), Error> {
+    let mut buffer = [0; MAX_VALUE_SIZE];
+
+    if T::SIZE > MAX_VALUE_SIZE {
+        return Err(Error::InvalidParameter);
+    }
+
+    value.encode(&mut buffer[..T::SIZE]);
+
+    Ok((buffer, T::SIZE))
+}
+
+/// The variables of one vendor, written with a fixed set of attributes.
+#[derive(Clone, Copy)]
+pub struct VarStore<'a> {
+    /// The runtime services used to access the variables.
+    runtime_services: &'a RuntimeServices,
+    /// The vendor the variables belong to.
+    vendor: Guid,
+    /// The attributes of written variables.
+    attributes: VariableAttributes,
+}
+
+impl<'a> VarStore<'a> {
+    /// Creates a store for the variables of `vendor`.
+    ///
+    /// Written variables are non-volatile and accessible at boot time and at runtime.
+    pub fn new(runtime_services: &'a RuntimeServices, vendor: Guid) -> VarStore<'a> {
+        VarStore {
+            runtime_services,
+            vendor,
+            attributes: VariableAttributes::NON_VOLATILE
+                | VariableAttributes::BOOTSERVICE_ACCESS
+                | VariableAttributes::RUNTIME_ACCESS,
+        }
+    }
+
+    /// Creates a store for the globally defined variables, such as `Timeout` and `BootOrder`.
+    pub fn global(runtime_services: &'a RuntimeServices) -> VarStore<'a> {
+        VarStore::new(runtime_services, GLOBAL_VARIABLE_GUID)
+    }
+
+    /// Returns a store with the same attributes for the variables of `vendor`.
+    pub fn scoped(&self, vendor: Guid) -> VarStore<'a> {
+        VarStore { vendor, ..*self }
+    }
+
+    /// Returns the vendor the variables belong to.
+    pub fn vendor(&self) -> Guid {
+        self.vendor
+    }
+
+    /// Returns the attributes of written variables.
+    pub fn attributes(&self) -> VariableAttributes {
+        self.attributes
+    }
+
+    /// Sets the attributes of written variables.
+    pub fn with_attributes(mut self, attributes: VariableAttributes) -> VarStore<'a> {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Selects whether written variables persist across resets.
+    pub fn non_volatile(mut self, non_volatile: bool) -> VarStore<'a> {
+        self.attributes
+            .set(VariableAttributes::NON_VOLATILE, non_volatile);
+        self
+    }
+
+    /// Selects whether written variables are accessible after `ExitBootServices`.
+    ///
+    /// They are always accessible at boot time, which the specification requires for variables
+    /// accessible at runtime.
+    pub fn runtime_access(mut self, runtime_access: bool) -> VarStore<'a> {
+        self.attributes
+            .insert(VariableAttributes::BOOTSERVICE_ACCESS);
+        self.attributes
+            .set(VariableAttributes::RUNTIME_ACCESS, runtime_access);
+        self
+    }
+
+    /// Reads the variable `name` into `data`.
+    ///
+    /// Returns the size of the variable and its attributes. If `data` is too small,
+    /// `Error::BufferTooSmall` is returned.
+    pub fn get_raw(
+        &self,
+        name: &str,
+        data: &mut [u8],
+    ) -> Result<(usize, VariableAttributes), Error> {
+        let (size, attributes) = with_name(name, |name| {
+            self.runtime_services
+                .get_variable(name, &self.vendor, data)
+                .map_err(|(error, _)| error)
+        })?;
+
+        Ok((size, VariableAttributes::from_bits_truncate(attributes)))
+    }
+
+    /// Returns the size of the variable `name`.
+    pub fn size(&self, name: &str) -> Result<usize, Error> {
+        with_name(name, |name| {
+            self.runtime_services.variable_size(name, &self.vendor)
+        })
+    }
+
+    /// Reads the variable `name` as a `T`.
+    ///
+    /// Returns `Error::BadBufferSize` if the size of the variable is not the size of `T`.
+    pub fn get<T: VarValue>(&self, name: &str) -> Result<T, Error> {
+        let mut buffer = [0; MAX_VALUE_SIZE];
+
+        if T::SIZE > MAX_VALUE_SIZE {
+            return Err(Error::InvalidParameter);
+        }
+
+        let size = match self.get_raw(name, &mut buffer[..T::SIZE]) {
+            Ok((size, _)) => size,
+            Err(Error::BufferTooSmall) => return Err(Error::BadBufferSize),
+            Err(error) => return Err(error),
+        };
+
+        if size != T::SIZE {
+            return Err(Error::BadBufferSize);
+        }
+
+        Ok(T::decode(&buffer[..size]))
+    }
+
+    /// Reads the variable `name` as a UCS-2 string into `buffer`.
+    ///
+    /// The string ends at the first null terminator or at the end of the variable. Returns
+    /// `Error::BadBufferSize` if the size of the variable is odd.
+    pub fn get_string<'b>(&self, name: &str, buffer: &'b mut [u16]) -> Result<Lossy<'b>, Error> {
+        let size = {
+            // This is safe, because every byte pattern is a valid `u16`.
+            let bytes = unsafe {
+                slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 2)
+            };
+
+            self.get_raw(name, bytes)?.0
+        };
+
+        if size % 2 != 0 {
+            return Err(Error::BadBufferSize);
+        }
+
+        Ok(Lossy::new(&buffer[..size / 2]))
+    }
+
+    /// Writes `data` to the variable `name`.
+    pub fn set_raw(&self, name: &str, data: &[u8]) -> Result<(), Error> {
+        self.write(name, self.attributes, data)
+    }
+
+    /// Writes `value` to the variable `name`.
+    pub fn set<T: VarValue>(&self, name: &str, value: &T) -> Result<(), Error> {
+        let (buffer, size) = encode_value(value)?;
+
+        self.set_raw(name, &buffer[..size])
+    }
+
+    /// Writes `value` to the variable `name`, including its null terminator.
+    pub fn set_string(&self, name: &str, value: &CStr16) -> Result<(), Error> {
+        self.set_raw(name, value.as_bytes_with_nul())
+    }
+
+    /// Deletes the variable `name`.
+    pub fn delete(&self, name: &str) -> Result<(), Error> {
+        self.write(name, self.attributes, &[])
+    }
+
+    /// Starts a transaction that writes several variables of this store at once.
+    pub fn transaction<'b>(&self) -> Transaction<'a, 'b> {
+        Transaction {
+            store: *self,
+            writes: [None; MAX_TRANSACTION_WRITES],
+        }
+    }
+
+    /// Writes `data` to the variable `name` with the given attributes.
+    fn write(&self, name: &str, attributes: VariableAttributes, data: &[u8]) -> Result<(), Error> {
+        // Appending nothing would not delete the variable.
+        let attributes = if data.is_empty() {
+            attributes - VariableAttributes::APPEND_WRITE
+        } else {
+            attributes
+        };
+
+        with_name(name, |name| {
+            self.runtime_services
+                .set_variable(name, &self.vendor, attributes, data)
+        })
+    }
+}
+
+/// The new value of a variable in a transaction.
+#[derive(Clone, Copy)]
+enum StagedData<'b> {
+    /// An encoded `VarValue`.
+    Value([u8; MAX_VALUE_SIZE], usize),
+    /// Borrowed data, which deletes the variable if it is empty.
+    Borrowed(&'b [u8]),
+}
+
+impl<'b> StagedData<'b> {
+    /// Returns the data that is written.
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            StagedData::Value(buffer, size) => &buffer[..*size],
+            StagedData::Borrowed(data) => data,
+        }
+    }
+}
+
+/// The value of a variable before the transaction was committed.
+#[derive(Clone, Copy)]
+enum Backup {
+    /// The variable did not exist.
+    Absent,
+    /// The variable existed with the given attributes and its data was saved in the scratch
+    /// buffer at `offset`.
+    Present {
+        /// The start of the saved data in the scratch buffer.
+        offset: usize,
+        /// The size of the saved data.
+        size: usize,
+        /// The attributes of the variable.
+        attributes: VariableAttributes,
+    },
+}
+
+/// Writes several related variables of a store, so that either all or none of them are changed.
+///
+/// The firmware has no transactions, so the previous values are saved before anything is written
+/// and restored if one of the writes fails. Authenticated variables cannot be restored this way.
+pub struct Transaction<'a, 'b> {
+    /// The store the variables are written to.
+    store: VarStore<'a>,
+    /// The staged writes in the order they are committed.
+    writes: [Option<(&'b str, StagedData<'b>)>; MAX_TRANSACTION_WRITES],
+}
+
+impl<'a, 'b> Transaction<'a, 'b> {
+    /// Stages writing `data` to the variable `name`.
+    ///
+    /// Returns `Error::OutOfResources` if `MAX_TRANSACTION_WRITES` writes are already staged.
+    pub fn set_raw(&mut self, name: &'b str, data: &'b [u8]) -> Result<(), Error> {
+        self.stage(name, StagedData::Borrowed(data))
+    }
+
+    /// Stages writing `value` to the variable `name`.
+    pub fn set<T: VarValue>(&mut self, name: &'b str, value: &T) -> Result<(), Error> {
+        let (buffer, size) = encode_value(value)?;
+
+        self.stage(name, StagedData::Value(buffer, size))
+    }
+
+    /// Stages writing `value` to the variable `name`, including its null terminator.
+    pub fn set_string(&mut self, name: &'b str, value: &'b CStr16) -> Result<(), Error> {
+        self.set_raw(name, value.as_bytes_with_nul())
+    }
+
+    /// Stages deleting the variable `name`.
+    pub fn delete(&mut self, name: &'b str) -> Result<(), Error> {
+        self.stage(name, StagedData::Borrowed(&[]))
+    }
+
+    /// Writes all staged variables.
+    ///
+    /// The previous values are saved in `scratch` first, so nothing is written if
+    /// `Error::BufferTooSmall` is returned because it cannot hold them. If a write fails, the
+    /// variables that were already written are restored and the error of the write is returned.
+    /// Restoring is best effort, since the firmware may fail to write them as well.
+    pub fn commit(self, scratch: &mut [u8]) -> Result<(), Error> {
+        let mut backups = [None; MAX_TRANSACTION_WRITES];
+        let mut offset = 0;
+
+        for (backup, &(name, _)) in backups.iter_mut().zip(self.staged()) {
+            *backup = Some(match self.store.get_raw(name, &mut scratch[offset..]) {
+                Ok((size, attributes)) => {
+                    let saved = Backup::Present {
+                        offset,
+                        size,
+                        attributes,
+                    };
+                    offset += size;
+
+                    saved
+                }
+                Err(Error::NotFound) => Backup::Absent,
+                Err(error) => return Err(error),
+            });
+        }
+
+        for (index, (name, data)) in self.staged().enumerate() {
+            if let Err(error) = self.store.set_raw(name, data.as_slice()) {
+                self.restore(&backups[..index], scratch);
+
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restores the variables of the first writes, in reverse order.
+    fn restore(&self, backups: &[Option<Backup>], scratch: &[u8]) {
+        for (backup, write) in backups.iter().zip(self.writes.iter()).rev() {
+            let name = match write {
+                Some((name, _)) => name,
+                None => continue,
+            };
+
+            // There is no way to report a failed restore besides the original error.
+            let _ = match backup {
+                Some(Backup::Present {
+                    offset,
+                    size,
+                    attributes,
+                }) => self
+                    .store
+                    .write(name, *attributes, &scratch[*offset..*offset + *size]),
+                Some(Backup::Absent) => self.store.delete(name),
+                None => Ok(()),
+            };
+        }
+    }
+
+    /// Returns the staged writes in order.
+    fn staged(&self) -> impl Iterator<Item = &(&'b str, StagedData<'b>)> {
+        self.writes.iter().filter_map(|write| write.as_ref())
+    }
+
+    /// Adds a write to the transaction.
+    fn stage(&mut self, name: &'b str, data: StagedData<'b>) -> Result<(), Error> {
+        with_name(name, |_| Ok(()))?;
+
+        let slot = self
+            .writes
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(Error::OutOfResources)?;
+
+        *slot = Some((name, data));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+//! Keeps the platform watchdog from firing during long operations.
+//!
+//! The firmware arms a five minute watchdog before starting a boot option. Operations such as
+//! downloads over slow links can take longer than that, so `Watchdog` periodically resets the
+//! watchdog from a timer event until it is stopped.
+
+use core::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    boot::{BootServices, EventType, TimerDelay, TPL_CALLBACK},
+    status::Error,
+    Event,
+};
+
+/// The watchdog code used when resetting the watchdog.
+///
+/// The codes up to 0xffff are reserved for the firmware.
+pub const WATCHDOG_CODE: u64 = 0x1_0000;
+
+/// The timeout in seconds that the keep-alive timer sets, or 0 if no `Watchdog` is running.
+///
+/// There is only one watchdog per platform, so there is at most one `Watchdog` at a time.
+static KEEP_ALIVE_TIMEOUT: AtomicUsize = AtomicUsize::new(0);
+
+eficall! {
+    /// Resets the watchdog timer, with the boot services as the context.
+    fn keep_alive(_event: Event, context: usize) {
+        // This is safe, because the context is the `'static` boot services passed to
+        // `Watchdog::start`.
+        let boot_services = unsafe { &*(context as *const BootServices) };
+        let timeout = KEEP_ALIVE_TIMEOUT.load(Ordering::SeqCst);
+
+        if timeout != 0 {
+            // There is no way to report the error here.
+            let _ = boot_services.set_watchdog_timer(timeout, WATCHDOG_CODE, None);
+        }
+    }
+}
+
+/// Periodically resets the watchdog timer until it is stopped or dropped.
+///
+/// When the keep-alive stops, the watchdog is disabled.
+pub struct Watchdog {
+    /// The boot services used to reset the watchdog.
+    boot_services: &'static BootServices,
+    /// The periodic timer event that resets the watchdog.
+    event: Event,
+    /// The timeout in seconds that is set on every reset.
+    timeout: usize,
+    /// The interval of the timer in units of 100 nanoseconds.
+    interval: u64,
+}
+
+impl Watchdog {
+    /// Sets the watchdog to `timeout` seconds and resets it every `interval`.
+    ///
+    /// The interval should be well below the timeout, since the timer only fires while the task
+    /// priority level is below `TPL_CALLBACK`.
+    ///
+    /// Returns `Error::AlreadyStarted` if another `Watchdog` is running and
+    /// `Error::InvalidParameter` if the timeout or the interval is zero.
+    pub fn start(
+        boot_services: &'static BootServices,
+        timeout: usize,
+        interval: Duration,
+    ) -> Result<Watchdog, Error> {
+        let interval = interval.as_nanos() / 100;
+
+        if timeout == 0 || interval == 0 {
+            return Err(Error::InvalidParameter);
+        }
+
+        let interval = interval.min(u128::from(u64::max_value())) as u64;
+
+        if KEEP_ALIVE_TIMEOUT
+            .compare_exchange(0, timeout, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(Error::AlreadyStarted);
+        }
+
+        // This is safe, because the context is valid for the lifetime of the program.
+        let event = unsafe {
+            boot_services.create_event(
+                EventType::TIMER | EventType::NOTIFY_SIGNAL,
+                TPL_CALLBACK,
+                Some(keep_alive),
+                boot_services as *const BootServices as usize,
+            )
+        };
+
+        let event = event.map_err(|error| {
+            KEEP_ALIVE_TIMEOUT.store(0, Ordering::SeqCst);
+            error
+        })?;
+
+        let watchdog = Watchdog {
+            boot_services,
+            event,
+            timeout,
+            interval,
+        };
+
+        watchdog.arm()?;
+
+        Ok(watchdog)
+    }
+
+    /// Returns the timeout in seconds that is set on every reset.
+    pub fn timeout(&self) -> usize {
+        self.timeout
+    }
+
+    /// Disables the watchdog until the returned guard is dropped.
+    ///
+    /// This is useful for operations that wait for the user without a time limit.
+    pub fn pause(&mut self) -> Result<WatchdogPause, Error> {
+        self.disarm()?;
+
+        Ok(WatchdogPause { watchdog: self })
+    }
+
+    /// Stops resetting the watchdog and disables it.
+    pub fn stop(mut self) -> Result<(), Error> {
+        self.disarm()
+    }
+
+    /// Resets the watchdog and starts the periodic timer.
+    fn arm(&self) -> Result<(), Error> {
+        KEEP_ALIVE_TIMEOUT.store(self.timeout, Ordering::SeqCst);
+
+        self.boot_services
+            .set_watchdog_timer(self.timeout, WATCHDOG_CODE, None)?;
+        self.boot_services
+            .set_timer(&self.event, TimerDelay::Periodic, self.interval)
+    }
+
+    /// Cancels the periodic timer and disables the watchdog.
+    fn disarm(&mut self) -> Result<(), Error> {
+        self.boot_services
+            .set_timer(&self.event, TimerDelay::Cancel, 0)?;
+        self.boot_services
+            .set_watchdog_timer(0, WATCHDOG_CODE, None)
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        // There is no way to report the error here.
+        let _ = self.disarm();
+        let _ = self.boot_services.close_event(self.event);
+
+        KEEP_ALIVE_TIMEOUT.store(0, Ordering::SeqCst);
+    }
+}
+
+/// Keeps the watchdog disabled while it exists.
+///
+/// Dropping the guard resets the watchdog and restarts the periodic timer.
+pub struct WatchdogPause<'a> {
+    /// The paused watchdog.
+    watchdog: &'a mut Watchdog,
+}
+
+impl<'a> Drop for WatchdogPause<'a> {
+    fn drop(&mut self) {
+        // There is no way to report the error here.
+        let _ = self.watchdog.arm();
+    }
+}
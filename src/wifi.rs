@@ -0,0 +1,502 @@
+//! Provides the protocols needed to connect to 802.11 wireless networks.
+//!
+//! The wireless MAC connection protocol is used to scan for and connect to networks, while the
+//! supplicant protocol handles the authentication and the key management of the connection.
+
+use crate::{
+    guid::{Guid, SUPPLICANT_PROTOCOL_GUID, WIRELESS_MAC_CONNECTION_II_PROTOCOL_GUID},
+    protocol::Protocol,
+    status::{Error, Status},
+    Event,
+};
+
+/// The maximum length of an SSID in bytes.
+pub const SSID_MAX_LENGTH: usize = 32;
+
+/// The minimum length of a WPA2 pre-shared key passphrase.
+pub const PSK_PASSWORD_MIN_LENGTH: usize = 8;
+
+/// The maximum length of a WPA2 pre-shared key passphrase.
+pub const PSK_PASSWORD_MAX_LENGTH: usize = 63;
+
+/// The OUI of the suite selectors defined by IEEE 802.11.
+pub const IEEE_80211_OUI: [u8; 3] = [0x00, 0x0f, 0xac];
+
+/// The AKM suite for WPA2 with a pre-shared key.
+pub const AKM_SUITE_PSK: SuiteSelector = SuiteSelector {
+    Oui: IEEE_80211_OUI,
+    SuiteType: 2,
+};
+
+/// The cipher suite CCMP-128 used by WPA2.
+pub const CIPHER_SUITE_CCMP: SuiteSelector = SuiteSelector {
+    Oui: IEEE_80211_OUI,
+    SuiteType: 4,
+};
+
+/// The identifier of a wireless network.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub struct Ssid {
+    /// The length of the SSID in bytes.
+    pub SSIdLen: u8,
+    /// The SSID.
+    pub SSId: [u8; SSID_MAX_LENGTH],
+}
+
+impl Ssid {
+    /// Creates a new SSID from the given bytes.
+    ///
+    /// Returns `Error::InvalidParameter` if the SSID is longer than `SSID_MAX_LENGTH` bytes.
+    pub fn new(ssid: &[u8]) -> Result<Ssid, Error> {
+        if ssid.len() > SSID_MAX_LENGTH {
+            return Err(Error::InvalidParameter);
+        }
+
+        let mut result = Ssid {
+            SSIdLen: ssid.len() as u8,
+            SSId: [0; SSID_MAX_LENGTH],
+        };
+        result.SSId[..ssid.len()].copy_from_slice(ssid);
+
+        Ok(result)
+    }
+
+    /// Returns the bytes of the SSID.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.SSId[..usize::min(self.SSIdLen as usize, SSID_MAX_LENGTH)]
+    }
+}
+
+/// Identifies an AKM or cipher suite.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub struct SuiteSelector {
+    /// The organization that defined the suite.
+    pub Oui: [u8; 3],
+    /// The type of the suite.
+    pub SuiteType: u8,
+}
+
+/// A list of AKM suites.
+///
+/// `AKMSuiteCount` selectors follow the count.
+#[repr(C)]
+pub struct AkmSuiteSelector {
+    /// The number of AKM suites in the list.
+    pub AKMSuiteCount: u16,
+    /// The first AKM suite of the list.
+    pub AKMSuiteList: [SuiteSelector; 1],
+}
+
+/// A list of cipher suites.
+///
+/// `CipherSuiteCount` selectors follow the count.
+#[repr(C)]
+pub struct CipherSuiteSelector {
+    /// The number of cipher suites in the list.
+    pub CipherSuiteCount: u16,
+    /// The first cipher suite of the list.
+    pub CipherSuiteList: [SuiteSelector; 1],
+}
+
+/// The type of a basic service set.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum BssType {
+    /// A network with an access point.
+    IeeeInfrastructureBSS,
+    /// An ad-hoc network.
+    IeeeIndependentBSS,
+    /// A mesh network.
+    IeeeMeshBSS,
+    /// Any of the above.
+    IeeeAnyBss,
+}
+
+/// Describes a wireless network.
+#[repr(C)]
+pub struct Network {
+    /// The type of the network.
+    pub BSSType: BssType,
+    /// The identifier of the network.
+    pub SSId: Ssid,
+    /// The AKM suites supported by the network.
+    pub AKMSuite: *const AkmSuiteSelector,
+    /// The cipher suites supported by the network.
+    pub CipherSuite: *const CipherSuiteSelector,
+}
+
+/// Describes a network found during a scan.
+#[repr(C)]
+pub struct NetworkDescription {
+    /// The network that was found.
+    pub Network: Network,
+    /// The signal quality of the network, from 0 to 100.
+    pub NetworkQuality: u8,
+}
+
+/// The SSIDs to scan for.
+///
+/// `NumOfSSID` SSIDs follow the count.
+#[repr(C)]
+pub struct GetNetworksData {
+    /// The number of SSIDs in the list.
+    pub NumOfSSID: u32,
+    /// The first SSID of the list.
+    pub SSIDList: [Ssid; 1],
+}
+
+/// The networks found during a scan.
+///
+/// `NumOfNetworkDesc` descriptions follow the count.
+#[repr(C)]
+pub struct GetNetworksResult {
+    /// The number of networks found.
+    pub NumOfNetworkDesc: u8,
+    /// The first network of the list.
+    pub NetworkDesc: [NetworkDescription; 1],
+}
+
+impl GetNetworksResult {
+    /// Returns the networks found during the scan.
+    pub fn networks(&self) -> &[NetworkDescription] {
+        // This is safe under the assumption, that the firmware returned a valid result.
+        unsafe {
+            core::slice::from_raw_parts(
+                self.NetworkDesc.as_ptr(),
+                self.NumOfNetworkDesc as usize,
+            )
+        }
+    }
+}
+
+/// The token used to scan for networks.
+#[repr(C)]
+pub struct GetNetworksToken {
+    /// The event that is signaled when the scan has completed.
+    pub Event: Event,
+    /// The status of the scan once the event was signaled.
+    pub Status: Status,
+    /// The SSIDs to scan for.
+    pub Data: *const GetNetworksData,
+    /// The result of the scan, allocated by the driver. It must be freed with `FreePool()`.
+    pub Result: *mut GetNetworksResult,
+}
+
+/// The data needed to connect to a network.
+#[repr(C)]
+pub struct ConnectNetworkData {
+    /// The network to connect to.
+    pub Network: *const Network,
+    /// The timeout of the connection attempt in units of time units (TU, 1024 microseconds).
+    pub FailureTimeout: u32,
+}
+
+/// The result of a connection attempt.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum ConnectNetworkResultCode {
+    /// The connection was established.
+    ConnectSuccess,
+    /// The connection was refused by the network.
+    ConnectRefused,
+    /// The connection could not be established.
+    ConnectFailed,
+    /// The connection attempt timed out.
+    ConnectFailureTimeout,
+    /// The connection could not be established for an unspecified reason.
+    ConnectFailedReasonUnspecified,
+}
+
+/// The token used to connect to a network.
+#[repr(C)]
+pub struct ConnectNetworkToken {
+    /// The event that is signaled when the connection attempt has completed.
+    pub Event: Event,
+    /// The status of the connection attempt once the event was signaled.
+    pub Status: Status,
+    /// The network to connect to.
+    pub Data: *const ConnectNetworkData,
+    /// The result of the connection attempt.
+    pub ResultCode: ConnectNetworkResultCode,
+}
+
+/// The token used to disconnect from a network.
+#[repr(C)]
+pub struct DisconnectNetworkToken {
+    /// The event that is signaled when the disconnection has completed.
+    pub Event: Event,
+    /// The status of the disconnection once the event was signaled.
+    pub Status: Status,
+}
+
+/// Provides management service interfaces for 802.11 connections.
+#[repr(C)]
+pub struct WirelessMacConnectionII {
+    /// Request a survey of potential wireless networks that administrator can later elect to try
+    /// to join.
+    pub GetNetworks: eficall! { fn(
+        &WirelessMacConnectionII,
+        Token: &mut GetNetworksToken,
+    ) -> Status },
+    /// Connect a wireless network specified by a particular SSID, BSS type and security type.
+    pub ConnectNetwork: eficall! { fn(
+        &WirelessMacConnectionII,
+        Token: &mut ConnectNetworkToken,
+    ) -> Status },
+    /// Request a disconnection with current connected wireless network.
+    pub DisconnectNetwork: eficall! { fn(
+        &WirelessMacConnectionII,
+        Token: &mut DisconnectNetworkToken,
+    ) -> Status },
+}
+
+unsafe impl Protocol for WirelessMacConnectionII {
+    const GUID: Guid = WIRELESS_MAC_CONNECTION_II_PROTOCOL_GUID;
+}
+
+impl WirelessMacConnectionII {
+    /// Request a survey of potential wireless networks.
+    ///
+    /// The result is available in the token once its event was signaled.
+    pub fn get_networks(&self, token: &mut GetNetworksToken) -> Result<(), Error> {
+        (self.GetNetworks)(self, token)?;
+
+        Ok(())
+    }
+
+    /// Connect to a wireless network.
+    ///
+    /// The result is available in the token once its event was signaled.
+    pub fn connect_network(&self, token: &mut ConnectNetworkToken) -> Result<(), Error> {
+        (self.ConnectNetwork)(self, token)?;
+
+        Ok(())
+    }
+
+    /// Disconnect from the currently connected wireless network.
+    pub fn disconnect_network(&self, token: &mut DisconnectNetworkToken) -> Result<(), Error> {
+        (self.DisconnectNetwork)(self, token)?;
+
+        Ok(())
+    }
+}
+
+/// The type of data that is set or retrieved with the supplicant protocol.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum SupplicantDataType {
+    /// The AKM suite, a `SuiteSelector`.
+    AkmSuite,
+    /// The group data cipher suite, a `SuiteSelector`.
+    GroupDataCipherSuite,
+    /// The pairwise cipher suite, a `SuiteSelector`.
+    PairwiseCipherSuite,
+    /// The null terminated ASCII passphrase of the pre-shared key.
+    PskPassword,
+    /// The SSID of the target network, an `Ssid`.
+    TargetSsidName,
+    /// The MAC address of the station.
+    StationMac,
+    /// The MAC address of the target network.
+    TargetSsidMac,
+    /// The pairwise transient key.
+    Ptk,
+    /// The group temporal key.
+    Gtk,
+    /// The state of the supplicant.
+    State,
+    /// The state of the link.
+    LinkState,
+    /// Whether the keys need to be refreshed.
+    KeyRefresh,
+    /// The supported AKM suites.
+    SupportedAkmSuites,
+    /// The cipher suites supported in software.
+    SupportedSoftwareCipherSuites,
+    /// The cipher suites supported in hardware.
+    SupportedHardwareCipherSuites,
+    /// The integrity group temporal key.
+    Igtk,
+    /// The pairwise master key.
+    Pmk,
+}
+
+/// Whether a packet is encrypted or decrypted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum SupplicantCryptMode {
+    /// Encrypt the packet.
+    Encrypt,
+    /// Decrypt the packet.
+    Decrypt,
+}
+
+/// A fragment of a packet.
+#[repr(C)]
+pub struct SupplicantFragmentData {
+    /// The length of the fragment in bytes.
+    pub FragmentLength: u32,
+    /// The data of the fragment.
+    pub FragmentBuffer: *mut u8,
+}
+
+/// Provides services to process authentication and data encryption/decryption for security
+/// management.
+#[repr(C)]
+pub struct Supplicant {
+    /// Builds the response packet for a received EAPOL packet.
+    pub BuildResponsePacket: eficall! { fn(
+        &Supplicant,
+        RequestBuffer: *const u8,
+        RequestBufferSize: usize,
+        Buffer: *mut u8,
+        BufferSize: &mut usize,
+    ) -> Status },
+    /// Encrypts or decrypts a packet.
+    pub ProcessPacket: eficall! { fn(
+        &Supplicant,
+        FragmentTable: &mut *mut SupplicantFragmentData,
+        FragmentCount: &mut u32,
+        CryptMode: SupplicantCryptMode,
+    ) -> Status },
+    /// Sets supplicant configuration data.
+    pub SetData: eficall! { fn(
+        &Supplicant,
+        DataType: SupplicantDataType,
+        Data: *const u8,
+        DataSize: usize,
+    ) -> Status },
+    /// Gets supplicant configuration data.
+    pub GetData: eficall! { fn(
+        &Supplicant,
+        DataType: SupplicantDataType,
+        Data: *mut u8,
+        DataSize: &mut usize,
+    ) -> Status },
+}
+
+unsafe impl Protocol for Supplicant {
+    const GUID: Guid = SUPPLICANT_PROTOCOL_GUID;
+}
+
+impl Supplicant {
+    /// Sets supplicant configuration data.
+    pub fn set_data(&self, data_type: SupplicantDataType, data: &[u8]) -> Result<(), Error> {
+        (self.SetData)(self, data_type, data.as_ptr(), data.len())?;
+
+        Ok(())
+    }
+
+    /// Gets supplicant configuration data.
+    ///
+    /// Returns the number of bytes written into the buffer.
+    pub fn get_data(
+        &self,
+        data_type: SupplicantDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        let mut size = buffer.len();
+
+        (self.GetData)(self, data_type, buffer.as_mut_ptr(), &mut size)?;
+
+        Ok(size)
+    }
+
+    /// Configures the supplicant for a WPA2 personal network with the given credentials.
+    pub fn configure(&self, credentials: &WifiCredentials) -> Result<(), Error> {
+        let ssid = &credentials.ssid;
+        // This is safe, because `Ssid` is a plain C structure without padding.
+        let ssid_bytes = unsafe {
+            core::slice::from_raw_parts(
+                ssid as *const Ssid as *const u8,
+                core::mem::size_of::<Ssid>(),
+            )
+        };
+
+        self.set_data(SupplicantDataType::TargetSsidName, ssid_bytes)?;
+        self.set_data(
+            SupplicantDataType::PskPassword,
+            &credentials.password[..=credentials.password_length],
+        )?;
+        self.set_data(
+            SupplicantDataType::AkmSuite,
+            &suite_selector_bytes(&AKM_SUITE_PSK),
+        )?;
+        self.set_data(
+            SupplicantDataType::PairwiseCipherSuite,
+            &suite_selector_bytes(&CIPHER_SUITE_CCMP),
+        )?;
+        self.set_data(
+            SupplicantDataType::GroupDataCipherSuite,
+            &suite_selector_bytes(&CIPHER_SUITE_CCMP),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Returns the raw bytes of a suite selector.
+fn suite_selector_bytes(selector: &SuiteSelector) -> [u8; 4] {
+    [
+        selector.Oui[0],
+        selector.Oui[1],
+        selector.Oui[2],
+        selector.SuiteType,
+    ]
+}
+
+/// The credentials needed to join a WPA2 personal network.
+#[derive(Clone)]
+pub struct WifiCredentials {
+    /// The SSID of the network.
+    ssid: Ssid,
+    /// The null terminated passphrase of the network.
+    password: [u8; PSK_PASSWORD_MAX_LENGTH + 1],
+    /// The length of the passphrase, excluding the null terminator.
+    password_length: usize,
+}
+
+impl WifiCredentials {
+    /// Creates new credentials for a WPA2 personal network.
+    ///
+    /// The passphrase must consist of 8 to 63 printable ASCII characters.
+    pub fn new(ssid: &str, password: &str) -> Result<WifiCredentials, Error> {
+        let ssid = Ssid::new(ssid.as_bytes())?;
+
+        if password.len() < PSK_PASSWORD_MIN_LENGTH
+            || password.len() > PSK_PASSWORD_MAX_LENGTH
+            || password.bytes().any(|byte| byte < 0x20 || byte > 0x7e)
+        {
+            return Err(Error::InvalidParameter);
+        }
+
+        let mut buffer = [0; PSK_PASSWORD_MAX_LENGTH + 1];
+        buffer[..password.len()].copy_from_slice(password.as_bytes());
+
+        Ok(WifiCredentials {
+            ssid,
+            password: buffer,
+            password_length: password.len(),
+        })
+    }
+
+    /// Returns the SSID of the network.
+    pub fn ssid(&self) -> &Ssid {
+        &self.ssid
+    }
+
+    /// Returns a network description suitable to connect to the network with
+    /// `WirelessMacConnectionII::connect_network`.
+    ///
+    /// The AKM and cipher suites are left unspecified, so the driver negotiates them.
+    pub fn network(&self) -> Network {
+        Network {
+            BSSType: BssType::IeeeInfrastructureBSS,
+            SSId: self.ssid,
+            AKMSuite: core::ptr::null(),
+            CipherSuite: core::ptr::null(),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "mock")]
+
+use nuefil::{
+    chainload::Chainload,
+    mock::MockSystem,
+    status::{Error, Status, SUCCESS},
+};
+
+const IMAGE_PATH: &str = "\\EFI\\BOOT\\app.efi";
+const IMAGE: &[u8] = b"MZ not really an image";
+
+#[test]
+fn the_image_is_loaded_with_its_device_path() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+    system.add_file(IMAGE_PATH, IMAGE).unwrap();
+
+    let exit = Chainload::new(boot_services, system.image_handle())
+        .start(IMAGE_PATH)
+        .unwrap();
+
+    assert_eq!(exit.status(), SUCCESS);
+    assert_eq!(system.loaded_image_path(), Some(IMAGE_PATH));
+    assert_eq!(system.loaded_image_source(), Some(IMAGE));
+    assert_eq!(system.open_files(), 0);
+}
+
+#[test]
+fn load_options_are_passed_and_the_exit_is_returned() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+    system.add_file(IMAGE_PATH, IMAGE).unwrap();
+    system.set_image_exit(Error::Aborted.into(), Some("gave up"));
+
+    let exit = Chainload::new(boot_services, system.image_handle())
+        .load_options("quiet splash")
+        .start("EFI/BOOT/app.efi")
+        .unwrap();
+
+    assert_eq!(exit.status(), Status::from(Error::Aborted));
+    assert_eq!(
+        exit.description()
+            .map(|description| description.to_string()),
+        Some("gave up".to_string())
+    );
+    assert_eq!(system.started_load_options(), Some("quiet splash"));
+}
+
+#[test]
+fn the_firmware_loads_the_image_with_the_boot_policy() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+    system.add_file(IMAGE_PATH, IMAGE).unwrap();
+
+    let exit = Chainload::new(boot_services, system.image_handle())
+        .boot_policy(true)
+        .start(IMAGE_PATH)
+        .unwrap();
+
+    assert_eq!(exit.status(), SUCCESS);
+    assert_eq!(system.loaded_image_path(), Some(IMAGE_PATH));
+    assert_eq!(system.loaded_image_source(), None);
+    assert_eq!(system.started_load_options(), Some(""));
+}
+
+#[test]
+fn a_missing_image_is_not_found() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+    system.add_file(IMAGE_PATH, IMAGE).unwrap();
+
+    let result = Chainload::new(boot_services, system.image_handle()).start("\\EFI\\missing.efi");
+
+    assert_eq!(result.err(), Some(Error::NotFound));
+    assert_eq!(system.loaded_image_path(), None);
+    assert_eq!(system.open_files(), 0);
+}
@@ -0,0 +1,465 @@
+#![cfg(feature = "fat")]
+
+use std::collections::BTreeMap;
+
+use nuefil::{
+    fat::{FatDevice, FatType, FatVolume, ATTR_DIRECTORY},
+    status::Error,
+};
+
+const SECTOR_SIZE: usize = 512;
+
+const HELLO: &[u8; 11] = b"HELLO   TXT";
+
+/// A device that keeps the written sectors in memory and reads zeros everywhere else.
+#[derive(Default)]
+struct Image {
+    sectors: BTreeMap<u64, [u8; SECTOR_SIZE]>,
+}
+
+impl Image {
+    fn write(&mut self, offset: u64, bytes: &[u8]) {
+        for (index, &byte) in bytes.iter().enumerate() {
+            let position = offset + index as u64;
+            let sector = self
+                .sectors
+                .entry(position / SECTOR_SIZE as u64)
+                .or_insert([0; SECTOR_SIZE]);
+
+            sector[(position % SECTOR_SIZE as u64) as usize] = byte;
+        }
+    }
+}
+
+impl FatDevice for Image {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        for (index, byte) in buffer.iter_mut().enumerate() {
+            let position = offset + index as u64;
+
+            *byte = self
+                .sectors
+                .get(&(position / SECTOR_SIZE as u64))
+                .map_or(0, |sector| sector[(position % SECTOR_SIZE as u64) as usize]);
+        }
+
+        Ok(())
+    }
+}
+
+/// A volume with one sector per cluster that is being built.
+struct Builder {
+    image: Image,
+    fat_type: FatType,
+    fat_offset: u64,
+    root_dir_offset: u64,
+    data_offset: u64,
+}
+
+impl Builder {
+    fn new(fat_type: FatType) -> Builder {
+        Builder::with_root_cluster(fat_type, 2)
+    }
+
+    fn with_root_cluster(fat_type: FatType, root_cluster: u32) -> Builder {
+        let (clusters, root_dir_entries, fat_entry_size) = match fat_type {
+            FatType::Fat12 => (100u64, 16u64, 2u64),
+            FatType::Fat16 => (5000, 16, 2),
+            FatType::Fat32 => (70000, 0, 4),
+        };
+        let reserved_sectors = 1;
+        let fat_sectors = ((clusters + 2) * fat_entry_size + 511) / 512;
+        let root_dir_sectors = (root_dir_entries * 32 + 511) / 512;
+        let first_data_sector = reserved_sectors + fat_sectors + root_dir_sectors;
+        let total_sectors = first_data_sector + clusters;
+
+        let mut boot_sector = [0; SECTOR_SIZE];
+        boot_sector[11..13].copy_from_slice(&(SECTOR_SIZE as u16).to_le_bytes());
+        boot_sector[13] = 1;
+        boot_sector[14..16].copy_from_slice(&(reserved_sectors as u16).to_le_bytes());
+        boot_sector[16] = 1;
+        boot_sector[17..19].copy_from_slice(&(root_dir_entries as u16).to_le_bytes());
+        if fat_type == FatType::Fat32 {
+            boot_sector[32..36].copy_from_slice(&(total_sectors as u32).to_le_bytes());
+            boot_sector[36..40].copy_from_slice(&(fat_sectors as u32).to_le_bytes());
+            boot_sector[44..48].copy_from_slice(&root_cluster.to_le_bytes());
+        } else {
+            boot_sector[19..21].copy_from_slice(&(total_sectors as u16).to_le_bytes());
+            boot_sector[22..24].copy_from_slice(&(fat_sectors as u16).to_le_bytes());
+        }
+        boot_sector[510] = 0x55;
+        boot_sector[511] = 0xaa;
+
+        let mut image = Image::default();
+        image.write(0, &boot_sector);
+
+        let mut builder = Builder {
+            image,
+            fat_type,
+            fat_offset: reserved_sectors * SECTOR_SIZE as u64,
+            root_dir_offset: (reserved_sectors + fat_sectors) * SECTOR_SIZE as u64,
+            data_offset: first_data_sector * SECTOR_SIZE as u64,
+        };
+        if fat_type == FatType::Fat32 && root_cluster >= 2 {
+            builder.chain(&[root_cluster]);
+        }
+
+        builder
+    }
+
+    /// Sets the FAT entry of `cluster` to `value`.
+    fn set_fat(&mut self, cluster: u32, value: u32) {
+        match self.fat_type {
+            FatType::Fat12 => {
+                let offset = self.fat_offset + u64::from(cluster + cluster / 2);
+                let mut bytes = [0; 2];
+                self.image.read_at(offset, &mut bytes).unwrap();
+                let old = u16::from_le_bytes(bytes);
+                let value = value as u16 & 0x0fff;
+
+                let new = if cluster % 2 == 1 {
+                    (old & 0x000f) | (value << 4)
+                } else {
+                    (old & 0xf000) | value
+                };
+                self.image.write(offset, &new.to_le_bytes());
+            }
+            FatType::Fat16 => {
+                let offset = self.fat_offset + u64::from(cluster) * 2;
+                self.image.write(offset, &(value as u16).to_le_bytes());
+            }
+            FatType::Fat32 => {
+                let offset = self.fat_offset + u64::from(cluster) * 4;
+                self.image.write(offset, &value.to_le_bytes());
+            }
+        }
+    }
+
+    /// Links `clusters` into a chain that ends after the last one.
+    fn chain(&mut self, clusters: &[u32]) {
+        let end = match self.fat_type {
+            FatType::Fat12 => 0x0fff,
+            FatType::Fat16 => 0xffff,
+            FatType::Fat32 => 0x0fff_ffff,
+        };
+
+        for pair in clusters.windows(2) {
+            self.set_fat(pair[0], pair[1]);
+        }
+        if let Some(&last) = clusters.last() {
+            self.set_fat(last, end);
+        }
+    }
+
+    fn cluster_offset(&self, cluster: u32) -> u64 {
+        self.data_offset + u64::from(cluster - 2) * SECTOR_SIZE as u64
+    }
+
+    /// Writes `entries` to the root directory.
+    fn root(&mut self, entries: &[[u8; 32]]) {
+        let offset = match self.fat_type {
+            FatType::Fat32 => self.cluster_offset(2),
+            _ => self.root_dir_offset,
+        };
+
+        self.entries(offset, entries);
+    }
+
+    /// Writes `entries` to the directory starting at `cluster`.
+    fn dir(&mut self, cluster: u32, entries: &[[u8; 32]]) {
+        let offset = self.cluster_offset(cluster);
+
+        self.entries(offset, entries);
+    }
+
+    fn entries(&mut self, offset: u64, entries: &[[u8; 32]]) {
+        for (index, entry) in entries.iter().enumerate() {
+            self.image.write(offset + index as u64 * 32, entry);
+        }
+    }
+
+    /// Writes `data` to the clusters of `chain` and links them.
+    fn file(&mut self, chain: &[u32], data: &[u8]) {
+        for (&cluster, part) in chain.iter().zip(data.chunks(SECTOR_SIZE)) {
+            let offset = self.cluster_offset(cluster);
+            self.image.write(offset, part);
+        }
+
+        self.chain(chain);
+    }
+
+    fn mount(self) -> FatVolume<Image> {
+        let volume = match FatVolume::mount(self.image) {
+            Ok(volume) => volume,
+            Err(error) => panic!("mounting failed: {:?}", error),
+        };
+        assert_eq!(volume.fat_type(), self.fat_type);
+
+        volume
+    }
+}
+
+fn short_entry(name: &[u8; 11], attributes: u8, cluster: u32, size: u32) -> [u8; 32] {
+    let mut entry = [0; 32];
+    entry[..11].copy_from_slice(name);
+    entry[11] = attributes;
+    entry[20..22].copy_from_slice(&((cluster >> 16) as u16).to_le_bytes());
+    entry[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+    entry[28..32].copy_from_slice(&size.to_le_bytes());
+
+    entry
+}
+
+/// Returns the long file name entries of `name` in the order they are stored on disk.
+fn long_entries(name: &str, short_name: &[u8; 11]) -> Vec<[u8; 32]> {
+    let checksum = short_name
+        .iter()
+        .fold(0u8, |sum, &byte| sum.rotate_right(1).wrapping_add(byte));
+
+    let mut units: Vec<u16> = name.encode_utf16().collect();
+    if units.len() % 13 != 0 {
+        units.push(0);
+    }
+    while units.len() % 13 != 0 {
+        units.push(0xffff);
+    }
+
+    let count = units.len() / 13;
+    let offsets = (1..11)
+        .step_by(2)
+        .chain((14..26).step_by(2))
+        .chain((28..32).step_by(2));
+
+    (1..=count)
+        .rev()
+        .map(|sequence| {
+            let mut entry = [0; 32];
+            entry[0] = sequence as u8 | if sequence == count { 0x40 } else { 0 };
+            entry[11] = 0x0f;
+            entry[13] = checksum;
+
+            for (offset, &unit) in offsets.clone().zip(&units[(sequence - 1) * 13..]) {
+                entry[offset..offset + 2].copy_from_slice(&unit.to_le_bytes());
+            }
+
+            entry
+        })
+        .collect()
+}
+
+fn deleted() -> [u8; 32] {
+    let mut entry = short_entry(HELLO, 0, 0, 0);
+    entry[0] = 0xe5;
+
+    entry
+}
+
+fn contents(len: usize) -> Vec<u8> {
+    (0..len).map(|index| (index % 251) as u8).collect()
+}
+
+fn read_file(volume: &FatVolume<Image>, path: &str) -> Result<Vec<u8>, Error> {
+    let mut file = volume.open(path)?;
+    let mut data = vec![0; file.size() as usize];
+    file.read_exact(&mut data)?;
+
+    Ok(data)
+}
+
+/// Reads a file spanning three clusters that are not contiguous.
+fn reads_a_file_spanning_clusters(fat_type: FatType) {
+    let data = contents(1300);
+    let mut builder = Builder::new(fat_type);
+    builder.file(&[7, 4, 9], &data);
+    builder.root(&[short_entry(HELLO, 0, 7, data.len() as u32)]);
+
+    let volume = builder.mount();
+
+    assert_eq!(read_file(&volume, "hello.txt"), Ok(data));
+}
+
+#[test]
+fn reads_a_file_on_fat12() {
+    reads_a_file_spanning_clusters(FatType::Fat12);
+}
+
+#[test]
+fn reads_a_file_on_fat16() {
+    reads_a_file_spanning_clusters(FatType::Fat16);
+}
+
+#[test]
+fn reads_a_file_on_fat32() {
+    reads_a_file_spanning_clusters(FatType::Fat32);
+}
+
+#[test]
+fn seeking_back_walks_the_chain_again() {
+    let data = contents(1300);
+    let mut builder = Builder::new(FatType::Fat12);
+    builder.file(&[3, 5, 4], &data);
+    builder.root(&[short_entry(HELLO, 0, 3, data.len() as u32)]);
+
+    let volume = builder.mount();
+    let mut file = volume.open("HELLO.TXT").unwrap();
+    let mut buffer = [0; 100];
+
+    file.seek(1100);
+    file.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer[..], &data[1100..1200]);
+
+    file.seek(10);
+    file.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer[..], &data[10..110]);
+}
+
+#[test]
+fn assembles_long_file_names() {
+    let short_name = b"ALONGF~1TXT";
+    let mut entries = long_entries("A long file name.txt", short_name);
+    entries.push(short_entry(short_name, 0, 3, 5));
+
+    let mut builder = Builder::new(FatType::Fat16);
+    builder.file(&[3], b"hello");
+    builder.root(&entries);
+
+    let volume = builder.mount();
+    let entry = volume.find("a LONG file name.TXT").unwrap();
+
+    assert!(entry.has_long_name());
+    assert_eq!(entry.name().to_string(), "A long file name.txt");
+    assert!(entry.matches("alongf~1.txt"));
+    assert_eq!(read_file(&volume, "ALONGF~1.TXT"), Ok(b"hello".to_vec()));
+}
+
+#[test]
+fn ignores_long_file_names_with_a_wrong_checksum() {
+    let mut entries = long_entries("A long file name.txt", b"OTHER   TXT");
+    entries.push(short_entry(HELLO, 0, 3, 0));
+
+    let mut builder = Builder::new(FatType::Fat12);
+    builder.root(&entries);
+
+    let volume = builder.mount();
+    let entry = volume.find("hello.txt").unwrap();
+
+    assert!(!entry.has_long_name());
+    assert_eq!(entry.name().to_string(), "HELLO.TXT");
+}
+
+#[test]
+fn follows_paths_through_directories() {
+    let mut builder = Builder::new(FatType::Fat32);
+    builder.chain(&[3, 6]);
+    let mut first = vec![deleted(); 16];
+    first[0] = short_entry(b".          ", ATTR_DIRECTORY, 3, 0);
+    first[1] = short_entry(b"..         ", ATTR_DIRECTORY, 0, 0);
+    builder.dir(3, &first);
+    // The entry of the file is in the second cluster of the directory.
+    builder.dir(6, &[short_entry(HELLO, 0, 8, 5)]);
+    builder.file(&[8], b"inner");
+    builder.root(&[
+        short_entry(b"EFI        ", ATTR_DIRECTORY, 3, 0),
+        short_entry(b"OUTER   TXT", 0, 9, 5),
+    ]);
+    builder.file(&[9], b"outer");
+
+    let volume = builder.mount();
+
+    assert_eq!(
+        read_file(&volume, "\\EFI\\hello.txt"),
+        Ok(b"inner".to_vec())
+    );
+    assert_eq!(
+        read_file(&volume, "efi/../outer.txt"),
+        Ok(b"outer".to_vec())
+    );
+    assert_eq!(volume.open_dir("").unwrap().count(), 2);
+}
+
+#[test]
+fn a_cyclic_directory_chain_is_corrupted() {
+    let mut builder = Builder::new(FatType::Fat12);
+    builder.set_fat(3, 4);
+    builder.set_fat(4, 3);
+    builder.dir(3, &[deleted(); 16]);
+    builder.dir(4, &[deleted(); 16]);
+    builder.root(&[short_entry(b"LOOP       ", ATTR_DIRECTORY, 3, 0)]);
+
+    let volume = builder.mount();
+
+    assert_eq!(
+        volume.find("loop/missing").err(),
+        Some(Error::VolumeCorrupted)
+    );
+}
+
+#[test]
+fn a_chain_shorter_than_the_file_is_corrupted() {
+    let mut builder = Builder::new(FatType::Fat16);
+    builder.file(&[3], &contents(512));
+    builder.root(&[short_entry(HELLO, 0, 3, 1000)]);
+
+    let volume = builder.mount();
+
+    assert_eq!(read_file(&volume, "hello.txt"), Err(Error::VolumeCorrupted));
+}
+
+#[test]
+fn a_chain_leading_to_a_reserved_cluster_is_corrupted() {
+    let mut builder = Builder::new(FatType::Fat32);
+    builder.file(&[3], &contents(512));
+    builder.set_fat(3, 1);
+    builder.root(&[short_entry(HELLO, 0, 3, 1000)]);
+
+    let volume = builder.mount();
+
+    assert_eq!(read_file(&volume, "hello.txt"), Err(Error::VolumeCorrupted));
+}
+
+#[test]
+fn a_directory_at_a_reserved_cluster_is_corrupted() {
+    for &fat_type in &[FatType::Fat12, FatType::Fat16, FatType::Fat32] {
+        let mut builder = Builder::new(fat_type);
+        builder.root(&[short_entry(b"EFI        ", ATTR_DIRECTORY, 1, 0)]);
+
+        let volume = builder.mount();
+        let entry = volume.find("efi").unwrap();
+
+        assert_eq!(
+            volume.read_dir(&entry).err(),
+            Some(Error::VolumeCorrupted),
+            "{:?}",
+            fat_type
+        );
+        assert_eq!(
+            volume.find("efi/hello.txt").err(),
+            Some(Error::VolumeCorrupted),
+            "{:?}",
+            fat_type
+        );
+    }
+}
+
+#[test]
+fn a_file_at_a_reserved_cluster_is_corrupted() {
+    let mut builder = Builder::new(FatType::Fat12);
+    builder.root(&[short_entry(HELLO, 0, 1, 10)]);
+
+    let volume = builder.mount();
+
+    assert_eq!(read_file(&volume, "hello.txt"), Err(Error::VolumeCorrupted));
+}
+
+#[test]
+fn a_reserved_fat32_root_cluster_is_corrupted() {
+    for &root_cluster in &[0, 1, 70002] {
+        let builder = Builder::with_root_cluster(FatType::Fat32, root_cluster);
+
+        assert_eq!(
+            FatVolume::mount(builder.image).err(),
+            Some(Error::VolumeCorrupted),
+            "{}",
+            root_cluster
+        );
+    }
+}
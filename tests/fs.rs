@@ -0,0 +1,48 @@
+#![cfg(feature = "mock")]
+
+use nuefil::{
+    fs::{self, FILE_MODE_READ},
+    mock::MockSystem,
+    status::{Status, StatusResultExt, Warning},
+};
+
+const FILE_PATH: &str = "\\EFI\\nuefil\\config.txt";
+
+#[test]
+fn a_failed_delete_reports_the_warning() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+    system.add_file(FILE_PATH, b"timeout=5").unwrap();
+
+    let file = fs::open_with(
+        boot_services,
+        system.image_handle(),
+        FILE_PATH,
+        FILE_MODE_READ,
+    )
+    .unwrap();
+    let completion = file.delete().unwrap();
+
+    assert_eq!(completion.warning(), Warning::DeleteFailure);
+    assert_eq!(system.open_files(), 0);
+}
+
+#[test]
+fn the_warning_of_a_failed_delete_can_be_treated_as_an_error() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+    system.add_file(FILE_PATH, b"timeout=5").unwrap();
+
+    let file = fs::open_with(
+        boot_services,
+        system.image_handle(),
+        FILE_PATH,
+        FILE_MODE_READ,
+    )
+    .unwrap();
+
+    assert_eq!(
+        file.delete().warn_as_err(),
+        Err(Status(Warning::DeleteFailure.code()))
+    );
+}
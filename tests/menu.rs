@@ -0,0 +1,114 @@
+#![cfg(feature = "mock")]
+
+use nuefil::{
+    menu::{Menu, MenuEntry},
+    mock::MockSystem,
+    text::{ScanCode, TextInputKey},
+};
+
+fn scan(code: ScanCode) -> TextInputKey {
+    TextInputKey {
+        ScanCode: code.into(),
+        UnicodeChar: 0,
+    }
+}
+
+fn enter() -> TextInputKey {
+    TextInputKey {
+        ScanCode: 0,
+        UnicodeChar: u16::from(b'\r'),
+    }
+}
+
+fn entries(labels: &[&'static str]) -> Vec<MenuEntry<'static, usize>> {
+    labels
+        .iter()
+        .enumerate()
+        .map(|(value, &label)| MenuEntry { label, value })
+        .collect()
+}
+
+#[test]
+fn enter_selects_highlighted_entry() {
+    let system = MockSystem::new();
+    let entries = entries(&["first", "second", "third"]);
+
+    system.push_key(scan(ScanCode::Down)).unwrap();
+    system.push_key(scan(ScanCode::Down)).unwrap();
+    system.push_key(scan(ScanCode::Up)).unwrap();
+    system.push_key(enter()).unwrap();
+
+    let chosen = Menu::new(&entries).title("Boot").run(system.system_table());
+
+    assert_eq!(chosen, Ok(Some(&1)));
+    assert!(system.console_output().contains("Boot"));
+    assert!(system.console_output().contains("second"));
+}
+
+#[test]
+fn escape_cancels() {
+    let system = MockSystem::new();
+    let entries = entries(&["first", "second"]);
+
+    system.push_key(scan(ScanCode::Escape)).unwrap();
+
+    assert_eq!(Menu::new(&entries).run(system.system_table()), Ok(None));
+}
+
+#[test]
+fn timeout_selects_default() {
+    let system = MockSystem::new();
+    let entries = entries(&["first", "second", "third"]);
+
+    let chosen = Menu::new(&entries)
+        .default(2)
+        .timeout(1)
+        .run(system.system_table());
+
+    assert_eq!(chosen, Ok(Some(&2)));
+    assert_eq!(system.stalled(), 1_000_000);
+    assert!(system.console_output().contains("selected in   1s"));
+}
+
+#[test]
+fn empty_menu_is_rejected() {
+    let system = MockSystem::new();
+    let entries = entries(&[]);
+
+    assert!(Menu::new(&entries).run(system.system_table()).is_err());
+}
+
+#[test]
+fn more_entries_than_rows_scroll() {
+    let system = MockSystem::new();
+    let labels: Vec<&'static str> = (0..60)
+        .map(|index| &*Box::leak(format!("entry {}", index).into_boxed_str()))
+        .collect();
+    let entries = entries(&labels);
+
+    system.push_key(scan(ScanCode::End)).unwrap();
+    system.push_key(scan(ScanCode::Up)).unwrap();
+    system.push_key(enter()).unwrap();
+
+    let chosen = Menu::new(&entries).timeout(5).run(system.system_table());
+
+    assert_eq!(chosen, Ok(Some(&58)));
+    assert!(system.console_output().contains("entry 59"));
+}
+
+#[test]
+fn default_entry_below_the_screen_is_shown() {
+    let system = MockSystem::new();
+    let labels: Vec<&'static str> = (0..60)
+        .map(|index| &*Box::leak(format!("entry {}", index).into_boxed_str()))
+        .collect();
+    let entries = entries(&labels);
+
+    system.push_key(enter()).unwrap();
+
+    let chosen = Menu::new(&entries).default(45).run(system.system_table());
+
+    assert_eq!(chosen, Ok(Some(&45)));
+    assert!(system.console_output().contains("entry 45"));
+    assert!(!system.console_output().contains("entry 0 "));
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "mock")]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use nuefil::{
+    boot::ExitBootServices,
+    memory::{MemoryType, PAGE_SIZE},
+    mock::{MockSystem, MOCK_ARENA_PAGES},
+    reset::ResetType,
+    status::{Error, SUCCESS},
+};
+
+#[test]
+fn console_output_is_captured() {
+    let system = MockSystem::new();
+    let system_table = system.system_table();
+
+    system_table.ConsoleOut.output_string("hello\r\n").unwrap();
+    system_table.ConsoleError.output_string("oops").unwrap();
+
+    assert_eq!(system.console_output(), "hello\r\n");
+    assert_eq!(system.console_error(), "oops");
+
+    system.clear_console();
+
+    assert_eq!(system.console_output(), "");
+}
+
+#[test]
+fn queued_keys_are_read_in_order() {
+    let system = MockSystem::new();
+    let input = &*system.system_table().ConsoleIn;
+
+    system.push_str("ab").unwrap();
+
+    assert_eq!(
+        input.try_read_key_stroke().unwrap().UnicodeChar,
+        u16::from(b'a')
+    );
+    assert_eq!(
+        input.try_read_key_stroke().unwrap().UnicodeChar,
+        u16::from(b'b')
+    );
+    assert_eq!(input.try_read_key_stroke().err(), Some(Error::NotReady));
+}
+
+#[test]
+fn stall_and_watchdog_are_recorded() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+
+    boot_services.stall(250).unwrap();
+    boot_services.stall(750).unwrap();
+    boot_services.set_watchdog_timer(0, 0x10000, None).unwrap();
+
+    assert_eq!(system.stalled(), 1000);
+    assert_eq!(system.watchdog_timeout(), Some(0));
+}
+
+#[test]
+fn allocations_come_from_the_arena() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+
+    let pages = boot_services
+        .allocate_pages(MemoryType::LoaderData, 2)
+        .unwrap();
+
+    assert_eq!(pages as usize % PAGE_SIZE, 0);
+    assert_eq!(system.allocated() % PAGE_SIZE, 0);
+    assert!(system.allocated() >= 2 * PAGE_SIZE);
+    assert_eq!(
+        boot_services
+            .allocate_pages(MemoryType::LoaderData, MOCK_ARENA_PAGES)
+            .err(),
+        Some(Error::OutOfResources)
+    );
+}
+
+#[test]
+fn memory_map_covers_the_arena() {
+    let system = MockSystem::new();
+    let boot_services = &*system.system_table().BootServices;
+
+    let memory_map = boot_services
+        .get_memory_map(MemoryType::LoaderData)
+        .unwrap();
+    let descriptors: Vec<_> = memory_map.iter().collect();
+
+    assert_eq!(descriptors.len(), 1);
+    assert_eq!(descriptors[0].Type, MemoryType::ConventionalMemory);
+    assert_eq!(descriptors[0].NumberOfPages, MOCK_ARENA_PAGES as u64);
+}
+
+#[test]
+fn exit_boot_services_retries_on_a_stale_map_key() {
+    let system = MockSystem::new();
+
+    let mut calls = 0;
+    let mut invalidate_map_key = |_: &_, _: &_| {
+        // The callback runs once, so the memory map is only stale on the first attempt.
+        system.invalidate_map_key();
+        calls += 1;
+    };
+
+    let runtime_view = ExitBootServices::new(system.system_table(), system.image_handle())
+        .before_exit(&mut invalidate_map_key)
+        .exit();
+
+    assert!(runtime_view.is_ok());
+    assert!(system.boot_services_exited());
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn reset_is_recorded_and_panics_outside_the_firmware() {
+    let system = MockSystem::new();
+    let runtime_services = &*system.system_table().RuntimeServices;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        runtime_services.reset_system(ResetType::Warm, SUCCESS)
+    }));
+
+    assert!(result.is_err());
+    match system.reset() {
+        Some((ResetType::Warm, status)) => assert_eq!(status, SUCCESS),
+        other => panic!("unexpected reset {:?}", other),
+    }
+}
+
+#[test]
+fn a_new_mock_starts_in_the_initial_state() {
+    {
+        let system = MockSystem::new();
+        system.push_str("x").unwrap();
+        system.system_table().ConsoleOut.output_string("x").unwrap();
+    }
+
+    let system = MockSystem::new();
+
+    assert_eq!(system.console_output(), "");
+    assert!(system.reset().is_none());
+    assert_eq!(system.exit_status(), None);
+    assert_eq!(system.unbound_calls(), 0);
+    assert_eq!(
+        system.system_table().ConsoleIn.try_read_key_stroke().err(),
+        Some(Error::NotReady)
+    );
+}
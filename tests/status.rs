@@ -0,0 +1,150 @@
+use nuefil::status::{Error, Status, Warning, ERROR_BIT, OEM_BIT, RESERVED_OEM_CODES_START};
+
+const ERRORS: &[Error] = &[
+    Error::Success,
+    Error::LoadError,
+    Error::InvalidParameter,
+    Error::Unsupported,
+    Error::BadBufferSize,
+    Error::BufferTooSmall,
+    Error::NotReady,
+    Error::DeviceError,
+    Error::WriteProtected,
+    Error::OutOfResources,
+    Error::VolumeCorrupted,
+    Error::VolumeFull,
+    Error::NoMedia,
+    Error::MediaChanged,
+    Error::NotFound,
+    Error::AccessDenied,
+    Error::NoResponse,
+    Error::NoMapping,
+    Error::Timeout,
+    Error::NotStarted,
+    Error::AlreadyStarted,
+    Error::Aborted,
+    Error::IcmpError,
+    Error::TftpError,
+    Error::ProtocolError,
+    Error::IncompatibleVersion,
+    Error::SecurityViolation,
+    Error::CrcError,
+    Error::EndOfMedia,
+    Error::Error29,
+    Error::Error30,
+    Error::EndOfFile,
+    Error::InvalidLanguage,
+    Error::CompromisedData,
+    Error::IpAddressConflict,
+    Error::HttpError,
+    Error::NetworkUnreachable,
+    Error::HostUnreachable,
+    Error::ProtocolUnreachable,
+    Error::PortUnreachable,
+    Error::ConnectionFin,
+    Error::ConnectionReset,
+    Error::ConnectionRefused,
+    Error::Unknown(36),
+    Error::Unknown(50),
+    Error::Unknown(99),
+    Error::Unknown(107),
+    Error::Oem(0x1234),
+    Error::WrongEventIndex,
+    Error::UndersizedDescriptor,
+    Error::OversizedTransfer,
+];
+
+#[test]
+fn every_error_round_trips_through_its_code() {
+    for &error in ERRORS {
+        assert_eq!(Error::from(error.code()), error, "{:?}", error);
+    }
+}
+
+#[test]
+fn every_error_round_trips_through_a_status() {
+    for &error in ERRORS {
+        let status = Status::from(error);
+
+        assert!(status.is_error(), "{:?}", error);
+        assert_eq!(status.with_value(()).err(), Some(error), "{:?}", error);
+    }
+}
+
+const WARNINGS: &[Warning] = &[
+    Warning::Success,
+    Warning::UnknownGlyph,
+    Warning::DeleteFailure,
+    Warning::WriteFailure,
+    Warning::BufferTooSmall,
+    Warning::StaleData,
+    Warning::FileSystem,
+    Warning::ResetRequired,
+    Warning::Unknown(8),
+    Warning::Unknown(50),
+    Warning::Oem(0x1234),
+];
+
+#[test]
+fn every_warning_round_trips_through_a_status() {
+    for &warning in WARNINGS {
+        let status = Status(warning.code());
+
+        assert!(!status.is_error(), "{:?}", warning);
+        assert_eq!(Warning::from(status.0), warning, "{:?}", warning);
+        assert_eq!(
+            status.with_value(()).map(|completion| completion.warning()),
+            Ok(warning),
+            "{:?}",
+            warning
+        );
+    }
+}
+
+#[test]
+fn reserved_error_codes_keep_their_code() {
+    for &code in &[36, 50, 99, 107] {
+        let status = Status(ERROR_BIT | code);
+
+        assert_eq!(status.with_value(()).err(), Some(Error::Unknown(code)));
+        assert_eq!(Status::from(Error::Unknown(code)), status);
+    }
+}
+
+#[test]
+fn crate_errors_use_reserved_oem_codes() {
+    for &error in &[
+        Error::WrongEventIndex,
+        Error::UndersizedDescriptor,
+        Error::OversizedTransfer,
+    ] {
+        let code = Status::from(error).oem_code();
+
+        assert!(code.is_some(), "{:?}", error);
+        assert!(code >= Some(RESERVED_OEM_CODES_START), "{:?}", error);
+    }
+}
+
+#[test]
+fn oem_statuses_cannot_alias_crate_errors() {
+    for code in RESERVED_OEM_CODES_START..OEM_BIT {
+        assert_eq!(Status::oem_error(code), None, "{:#x}", code);
+        assert_eq!(Status::oem_warning(code), None, "{:#x}", code);
+    }
+
+    let code = RESERVED_OEM_CODES_START - 1;
+    let status = Status::oem_error(code).unwrap();
+
+    assert_eq!(status.with_value(()).err(), Some(Error::Oem(code)));
+    assert_eq!(
+        Status::oem_warning(code).map(Status::raw),
+        Some(code | OEM_BIT)
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_error_names_still_resolve() {
+    assert_eq!(Error::Error34, Error::IpAddressConflict);
+    assert_eq!(Error::Error34.code(), 34);
+}